@@ -0,0 +1,5 @@
+// smoelius: `dylint` behaves identically to `cargo-dylint`, except that it never treats a first
+// argument of "dylint" as having come from Cargo. See `Invocation` in `src/lib.rs`.
+fn main() {
+    cargo_dylint::main();
+}
@@ -0,0 +1,1009 @@
+use clap::{crate_version, ArgAction, CommandFactory, FromArgMatches, Parser};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Debug,
+    path::Path,
+};
+
+// smoelius: The binary can be invoked in three ways:
+// - by Cargo, as `cargo-dylint dylint <args>` (i.e., the user ran `cargo dylint <args>`)
+// - directly, as `cargo-dylint <args>`
+// - as the standalone `dylint` binary, i.e., `dylint <args>`
+// When the binary is named `cargo-dylint`, a first argument of "dylint" is assumed to have come
+// from Cargo. So to pass a positional argument "dylint" to `cargo-dylint` directly, the user must
+// write `cargo-dylint dylint dylint`. The standalone binary never strips a first argument of
+// "dylint".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Invocation {
+    CargoSubcommand,
+    CargoDylint,
+    Standalone,
+}
+
+impl Invocation {
+    fn detect<T: AsRef<OsStr>>(args: &[T]) -> Self {
+        let is_standalone = args
+            .first()
+            .and_then(|arg| Path::new(arg.as_ref()).file_stem())
+            .map_or(false, |file_stem| file_stem == "dylint");
+        if is_standalone {
+            Self::Standalone
+        } else if args.get(1).map_or(false, |arg| arg.as_ref() == "dylint") {
+            Self::CargoSubcommand
+        } else {
+            Self::CargoDylint
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::CargoSubcommand | Self::CargoDylint => "cargo-dylint",
+            Self::Standalone => "dylint",
+        }
+    }
+
+    fn bin_name(self) -> &'static str {
+        match self {
+            Self::CargoSubcommand => "cargo dylint",
+            Self::CargoDylint => "cargo-dylint",
+            Self::Standalone => "dylint",
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Parser)]
+#[clap(
+    version = crate_version!(),
+    args_conflicts_with_subcommands = true,
+    after_help = r#"ENVIRONMENT VARIABLES:
+
+DYLINT_CACHE_DIR (default: `dylint` in the user's cache directory) is the directory where Dylint
+caches libraries built from git repositories.
+
+DYLINT_DRIVER_PATH (default: $HOME/.dylint_drivers) is the directory where Dylint stores rustc
+drivers. `--driver-path` takes precedence over it.
+
+DYLINT_LIBRARY_PATH (default: none) is a colon-separated list of directories where Dylint searches
+for libraries.
+
+DYLINT_RUSTFLAGS (default: none) is a space-separated list of flags that Dylint passes to `rustc`
+when checking the packages in the workspace. Flags from the `rustflags` metadata key and from
+`--rustflags` are appended to these.
+
+METADATA EXAMPLE:
+
+    [workspace.metadata.dylint]
+    libraries = [
+        { git = "https://github.com/trailofbits/dylint", pattern = "examples/*/*" },
+        { path = "libs/*" },
+    ]
+    exclude_paths = ["src/generated/**"]
+    rustflags = ["--cfg", "special"]
+
+EXIT STATUS:
+
+    0   Success
+    1   Any other failure, e.g., `cargo check` failed
+    2   A library failed to build
+    3   `--deny-warnings` was passed and Dylint lints produced warnings
+    4   A driver failed to build
+    5   Checking timed out (`--timeout` or `--per-crate-timeout`)
+    64  Invalid arguments or conflicting options
+"#,
+)]
+struct Dylint {
+    #[clap(flatten)]
+    name_opts: NameOpts,
+
+    #[clap(skip)]
+    allow_dirty: bool,
+
+    #[clap(long, hide = true)]
+    allow_downgrade: bool,
+
+    #[clap(long, hide = true)]
+    bisect: bool,
+
+    #[clap(skip)]
+    bisect_end: Option<String>,
+
+    #[clap(skip)]
+    bisect_max_attempts: Option<usize>,
+
+    #[clap(skip)]
+    bisect_start: Option<String>,
+
+    #[clap(
+        long,
+        help = "Abort compilation if a Dylint lint pass panics. By default, the panic is reported \
+        as an error and compilation continues with the pass disabled."
+    )]
+    broken_lint_is_fatal: bool,
+
+    #[clap(skip)]
+    cache_clean: bool,
+
+    #[clap(skip)]
+    cache_stats: bool,
+
+    #[clap(
+        long,
+        help = "Show and count only the first of several Dylint lint warnings with the same \
+        lint, primary spans, and message, e.g., a warning produced for both a library and its \
+        tests"
+    )]
+    dedup: bool,
+
+    #[clap(
+        long,
+        help = "Exit with a non-zero status if Dylint lints produce warnings. The exit status \
+        (3) differs from the one used when compilation fails (1)."
+    )]
+    deny_warnings: bool,
+
+    #[clap(skip)]
+    dist_out_dir: Option<String>,
+
+    #[clap(skip)]
+    dist_path: Option<String>,
+
+    #[clap(skip)]
+    dist_tag: bool,
+
+    #[clap(
+        global = true,
+        long,
+        value_name = "dir",
+        help = "Directory where Dylint stores rustc drivers (overrides DYLINT_DRIVER_PATH). The \
+        directory is created if it does not exist. A relative path is resolved relative to the \
+        current directory."
+    )]
+    driver_path: Option<String>,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        long = "exclude-path",
+        value_name = "glob",
+        help = "Do not show Dylint lint warnings for files matching <glob>. Relative globs are \
+        resolved relative to the workspace root."
+    )]
+    exclude_paths: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Print the workspace's `dylint.toml` configuration, merged with the files it \
+        extends, with the file and line each value comes from"
+    )]
+    explain_config: bool,
+
+    #[clap(
+        action = ArgAction::Append,
+        long,
+        value_delimiter = ',',
+        value_name = "paths",
+        help = "Check only the packages containing the comma-separated files <paths>, and show \
+        only the Dylint lint warnings in those files. Diagnostics are printed to stdout as JSON \
+        messages, one per line, like those of `cargo check --message-format=json`. Files that do \
+        not belong to any workspace target are skipped with a warning."
+    )]
+    files: Vec<String>,
+
+    #[clap(long, help = "Automatically apply lint suggestions")]
+    fix: bool,
+
+    #[clap(long, hide = true)]
+    force: bool,
+
+    #[clap(
+        global = true,
+        long,
+        help = "Pass `--frozen` to Cargo, i.e., require that Cargo.lock is up to date and run \
+        without accessing the network"
+    )]
+    frozen: bool,
+
+    #[clap(
+        long,
+        value_name = "secs",
+        help = "While a crate is being checked, print a line every <secs> seconds saying which \
+        crate and libraries are still being checked"
+    )]
+    heartbeat: Option<u64>,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        long = "include-path",
+        value_name = "glob",
+        help = "Show Dylint lint warnings only for files matching <glob>. Relative globs are \
+        resolved relative to the workspace root."
+    )]
+    include_paths: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Choose the libraries to load from those discovered, using a checklist. The last \
+        choice is remembered in the workspace's target directory. Requires a terminal."
+    )]
+    interactive: bool,
+
+    #[clap(long, hide = true)]
+    isolate: bool,
+
+    #[clap(long, help = "Continue if `cargo check` fails")]
+    keep_going: bool,
+
+    #[clap(
+        long,
+        help = "Also check dependencies, and show Dylint lint warnings in those that Cargo \
+        compiles with `--cap-lints`. Lints built into `rustc` remain capped."
+    )]
+    lint_deps: bool,
+
+    #[clap(long, hide = true)]
+    list: bool,
+
+    #[clap(skip)]
+    list_json: bool,
+
+    #[clap(
+        global = true,
+        long,
+        help = "Pass `--locked` to Cargo, i.e., require that Cargo.lock is up to date"
+    )]
+    locked: bool,
+
+    #[clap(
+        long,
+        value_name = "path",
+        help = "Path to Cargo.toml. Note: if the manifest uses metadata, then \
+        `--manifest-path <path>` must appear before `--`, not after."
+    )]
+    manifest_path: Option<String>,
+
+    #[clap(long = "new", hide = true)]
+    new_path: Option<String>,
+
+    #[clap(skip)]
+    new_workspace: bool,
+
+    #[clap(
+        long,
+        requires = "watch",
+        help = "Do not clear the screen before each check in `--watch` mode"
+    )]
+    no_clear: bool,
+
+    #[clap(
+        long,
+        requires = "files",
+        help = "With `--files`, show diagnostics on stderr as `rustc` renders them, rather than \
+        as JSON"
+    )]
+    no_json: bool,
+
+    #[clap(
+        global = true,
+        long,
+        help = "Pass `--offline` to Cargo, i.e., run without accessing the network"
+    )]
+    offline: bool,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        short,
+        long = "package",
+        value_name = "spec",
+        help = "Package to check"
+    )]
+    packages: Vec<String>,
+
+    #[clap(
+        long,
+        value_name = "secs",
+        help = "Stop checking any one crate after <secs> seconds, and report the crate and the \
+        libraries it was being checked with"
+    )]
+    per_crate_timeout: Option<u64>,
+
+    #[clap(
+        action = ArgAction::Count,
+        global = true,
+        short,
+        long,
+        help = "Do not show progress running commands besides `cargo check` and `cargo fix`. \
+        Pass twice (`-qq`) to also hide Dylint's own warnings."
+    )]
+    quiet: u8,
+
+    #[clap(
+        long,
+        help = "Skip the phases (driver builds, library builds, and checks) that completed in an \
+        interrupted run, provided that their inputs are unchanged"
+    )]
+    resume: bool,
+
+    #[clap(long, hide = true)]
+    rust_version: Option<String>,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        long,
+        value_name = "flags",
+        allow_hyphen_values = true,
+        help = "Space-separated flags to append to those Dylint passes to `rustc` when checking \
+        the workspace"
+    )]
+    rustflags: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Do not show Dylint lint warnings. Instead, count them and print the counts for \
+        each package and lint. Errors produced by `rustc` are still shown."
+    )]
+    summary: bool,
+
+    #[clap(
+        long,
+        value_name = "secs",
+        help = "Stop checking after <secs> seconds in total, killing `cargo check` and its \
+        children, and report the libraries that were being checked"
+    )]
+    timeout: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Print how long each phase took (driver builds, library builds, and checks) and \
+        how long each library's late lint passes took, slowest first. The timings are also \
+        written as JSON to `dylint/timings.json` in the workspace's target directory."
+    )]
+    timings: bool,
+
+    #[clap(
+        long,
+        help = "Show diagnostics with the real paths of files whose paths were remapped with \
+        `--remap-path-prefix`, rather than the remapped paths. Path filters and fix reports use \
+        the real paths regardless."
+    )]
+    unremap_paths: bool,
+
+    #[clap(long = "upgrade", hide = true)]
+    upgrade_path: Option<String>,
+
+    #[clap(
+        long,
+        help = "Check the workspace, then check it again whenever its sources change. If a \
+        library built from a path in the workspace metadata changes, rebuild it first."
+    )]
+    watch: bool,
+
+    #[clap(long, help = "Check all packages in the workspace")]
+    workspace: bool,
+
+    #[clap(subcommand)]
+    subcmd: Option<DylintSubCommand>,
+
+    #[clap(hide = true)]
+    names: Vec<String>,
+
+    #[clap(last = true, help = "Arguments for `cargo check`")]
+    args: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+enum DylintSubCommand {
+    #[clap(
+        about = "Manage the global cache of libraries built from git repositories",
+        long_about = "Manage the global cache of libraries built from git repositories.
+
+A library named by a git entry in a workspace's metadata is stored in the cache after it is built, \
+keyed by the commit, toolchain, profile, and features it was built with. Other workspaces that name \
+the same library link or copy it from the cache rather than build it again. The cache is in \
+`dylint/libraries` in the user's cache directory, or in `libraries` in DYLINT_CACHE_DIR if it is \
+set."
+    )]
+    Cache {
+        #[clap(subcommand)]
+        subcmd: CacheSubCommand,
+    },
+
+    #[clap(
+        about = "Build library package for distribution",
+        long_about = "Build the library package at <PATH> for each (toolchain, target) pair listed \
+in its `[package.metadata.dylint.dist]` table.
+
+Each artifact is written to <out-dir>/<target>/ with a name of the form \"DLL_PREFIX <name> '@' \
+TOOLCHAIN DLL_SUFFIX\". The SHA-256 sum of each artifact is written to <out-dir>/SHA256SUMS, and \
+a JSON description of all of them to <out-dir>/manifest.json.
+
+The package's directory must not contain uncommitted changes unless `--allow-dirty` is passed."
+    )]
+    Dist {
+        #[clap(long, help = "Build even if the package has uncommitted changes")]
+        allow_dirty: bool,
+
+        #[clap(
+            long,
+            help = "Continue if building for a pair fails, and report the failures at the end"
+        )]
+        keep_going: bool,
+
+        #[clap(
+            long,
+            value_name = "path",
+            help = "Directory to write the artifacts to (default: `dylint/dist` in the package's \
+            target directory)"
+        )]
+        out_dir: Option<String>,
+
+        #[clap(
+            long,
+            help = "Create an annotated git tag `<name>-v<version>` whose message is the manifest"
+        )]
+        tag: bool,
+
+        #[clap(help = "Path to library package")]
+        path: String,
+    },
+
+    #[clap(
+        about = "List libraries or lints",
+        long_about = "If no libraries are named, list the name, toolchain, and location of all \
+discovered libraries.
+
+If at least one library is named, list the name, level, and description of all lints in all named \
+libraries.
+
+Combine with `--all` to list all lints in all discovered libraries."
+    )]
+    List {
+        #[clap(
+            long,
+            help = "Output JSON. When listing lints, include the metadata (e.g., longer \
+            descriptions and examples) that each library exports, if any."
+        )]
+        json: bool,
+
+        #[clap(flatten)]
+        name_opts: NameOpts,
+    },
+
+    #[clap(
+        about = "Create a new library package",
+        long_about = "Create a new library package at <PATH>"
+    )]
+    New {
+        #[clap(long, help = "Put the package in its own workspace")]
+        isolate: bool,
+
+        #[clap(
+            long,
+            help = "Create a workspace containing a combined library and one example lint"
+        )]
+        workspace: bool,
+
+        #[clap(help = "Path to library package")]
+        path: String,
+    },
+
+    #[clap(
+        about = "Upgrade library package",
+        long_about = "Upgrade the library package at <PATH> to the latest version of \
+`clippy_utils`.
+
+Known `rustc` API changes between the old and new toolchains are fixed with simple rewrites to the \
+package's `src` directory. Compile errors that remain afterwards are reported.
+
+With `--start`, `--end`, or `--max-attempts`, `--bisect` binary searches the nightly toolchains \
+between <start> and <end> for the most recent one with which the package and a matching driver \
+build. The package's toolchain is set to that nightly, and the errors produced by the earliest \
+failing nightly are reported. Drivers already in the driver cache are reused."
+    )]
+    Upgrade {
+        #[clap(long, hide = true)]
+        allow_downgrade: bool,
+
+        #[clap(
+            long,
+            help = "Experimental: Update dependencies and search for the most recent applicable \
+            toolchain. Without `--start`, `--end`, or `--max-attempts`, this uses \
+            `cargo-bisect-rustc` and is Unix only."
+        )]
+        bisect: bool,
+
+        #[clap(
+            long,
+            value_name = "date",
+            help = "With `--bisect`: the most recent nightly toolchain to try (default: the \
+            toolchain of the latest `clippy_utils`)"
+        )]
+        end: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "n",
+            help = "With `--bisect`: stop after building with <n> toolchains"
+        )]
+        max_attempts: Option<usize>,
+
+        #[clap(
+            long,
+            value_name = "version",
+            help = "Upgrade to the version of `clippy_utils` with tag `rust-<version>`"
+        )]
+        rust_version: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "date",
+            help = "With `--bisect`: the oldest nightly toolchain to try (default: the package's \
+            current toolchain)"
+        )]
+        start: Option<String>,
+
+        #[clap(help = "Path to library package")]
+        path: String,
+    },
+}
+
+#[derive(Debug, Parser)]
+enum CacheSubCommand {
+    #[clap(about = "Remove all libraries from the cache")]
+    Clean,
+
+    #[clap(about = "Show the libraries in the cache and how much space they take")]
+    Stats,
+}
+
+#[derive(Debug, Parser)]
+struct NameOpts {
+    #[clap(long, help = "Load all discovered libraries")]
+    all: bool,
+
+    #[clap(
+        long,
+        help = "Use a library in DYLINT_LIBRARY_PATH even if its toolchain is not installed (by \
+        default, such libraries are skipped)"
+    )]
+    allow_uninstalled_toolchain: bool,
+
+    #[clap(
+        long,
+        help = "When a library name is not found, do not suggest the names of similar libraries"
+    )]
+    exact: bool,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        long = "lib",
+        value_name = "name",
+        help = "Library name to load lints from. A file with a name of the form \"DLL_PREFIX \
+        <name> '@' TOOLCHAIN DLL_SUFFIX\" is searched for in the directories listed in \
+        DYLINT_LIBRARY_PATH, and in the `target/release` directories produced by building the \
+        current workspace's metadata entries (see example below)."
+    )]
+    libs: Vec<String>,
+
+    #[clap(
+        long,
+        value_name = "path",
+        help = "Load libraries from a TOML file with the same schema as \
+        `[workspace.metadata.dylint]`, but containing only `libraries`. Use `-` to read from \
+        standard input. The libraries are added to those in the workspace metadata."
+    )]
+    libs_from: Option<String>,
+
+    #[clap(
+        long,
+        requires = "libs_from",
+        help = "Load libraries only from `--libs-from`, not from the workspace metadata"
+    )]
+    libs_from_only: bool,
+
+    #[clap(long, help = "Do not build metadata entries")]
+    no_build: bool,
+
+    #[clap(
+        long,
+        help = "Resolve library names from scratch, rather than reusing the resolution cached in \
+        the workspace's target directory"
+    )]
+    no_cache: bool,
+
+    #[clap(long, help = "Ignore metadata entirely")]
+    no_metadata: bool,
+
+    #[clap(
+        action = ArgAction::Append,
+        number_of_values = 1,
+        long = "path",
+        value_name = "path",
+        help = "Library path to load lints from"
+    )]
+    paths: Vec<String>,
+}
+
+#[allow(deprecated)]
+impl From<Dylint> for dylint::Dylint {
+    fn from(opts: Dylint) -> Self {
+        let opts = process_deprecated_options(opts);
+        let Dylint {
+            name_opts:
+                NameOpts {
+                    all,
+                    allow_uninstalled_toolchain,
+                    exact,
+                    libs,
+                    libs_from,
+                    libs_from_only,
+                    no_build,
+                    no_cache,
+                    no_metadata,
+                    paths,
+                },
+            allow_dirty,
+            allow_downgrade,
+            bisect,
+            bisect_end,
+            bisect_max_attempts,
+            bisect_start,
+            broken_lint_is_fatal,
+            cache_clean,
+            cache_stats,
+            dedup,
+            deny_warnings,
+            dist_out_dir,
+            dist_path,
+            dist_tag,
+            driver_path,
+            exclude_paths,
+            explain_config,
+            files,
+            fix,
+            force,
+            frozen,
+            heartbeat,
+            include_paths,
+            interactive,
+            isolate,
+            keep_going,
+            lint_deps,
+            list,
+            list_json,
+            locked,
+            manifest_path,
+            new_path,
+            new_workspace,
+            no_clear,
+            no_json,
+            offline,
+            packages,
+            per_crate_timeout,
+            quiet,
+            resume,
+            rust_version,
+            rustflags,
+            summary,
+            timeout,
+            timings,
+            unremap_paths,
+            upgrade_path,
+            watch,
+            workspace,
+            subcmd: _,
+            names,
+            args,
+        } = opts;
+        Self {
+            all,
+            allow_dirty,
+            allow_downgrade,
+            allow_uninstalled_toolchain,
+            bisect,
+            bisect_end,
+            bisect_max_attempts,
+            bisect_start,
+            broken_lint_is_fatal,
+            cache_clean,
+            cache_stats,
+            dedup,
+            deny_warnings,
+            dist_out_dir,
+            dist_path,
+            dist_tag,
+            driver_path,
+            exact,
+            exclude_paths,
+            explain_config,
+            files,
+            fix,
+            force,
+            frozen,
+            heartbeat,
+            include_paths,
+            interactive,
+            isolate,
+            keep_going,
+            libs,
+            libs_from,
+            libs_from_only,
+            lint_deps,
+            list,
+            list_json,
+            locked,
+            manifest_path,
+            new_path,
+            new_workspace,
+            no_build,
+            no_cache,
+            no_clear,
+            no_json,
+            no_metadata,
+            offline,
+            packages,
+            paths,
+            per_crate_timeout,
+            quiet: quiet >= 1,
+            quiet_warnings: quiet >= 2,
+            resume,
+            rust_version,
+            rustflags,
+            summary,
+            timeout,
+            timings,
+            unremap_paths,
+            upgrade_path,
+            watch,
+            workspace,
+            names,
+            args,
+        }
+    }
+}
+
+fn process_deprecated_options(mut opts: Dylint) -> Dylint {
+    if opts.list {
+        dylint::__warn(
+            &dylint::Dylint::default(),
+            "`--list` is deprecated. Use subcommand `list`.",
+        );
+    }
+    if opts.new_path.is_some() {
+        dylint::__warn(
+            &dylint::Dylint::default(),
+            "`--new` is deprecated. Use subcommand `new`.",
+        );
+    }
+    if opts.upgrade_path.is_some() {
+        dylint::__warn(
+            &dylint::Dylint::default(),
+            "`--upgrade` is deprecated. Use subcommand `upgrade`.",
+        );
+    }
+    if !opts.names.is_empty() {
+        dylint::__warn(
+            &dylint::Dylint::default(),
+            "Referring to libraries by bare name is deprecated. Use `--lib` or `--path`.",
+        );
+    }
+    if let Some(subcmd) = opts.subcmd.take() {
+        match subcmd {
+            DylintSubCommand::Cache { subcmd } => match subcmd {
+                CacheSubCommand::Clean => opts.cache_clean = true,
+                CacheSubCommand::Stats => opts.cache_stats = true,
+            },
+            DylintSubCommand::Dist {
+                allow_dirty,
+                keep_going,
+                out_dir,
+                tag,
+                path,
+            } => {
+                opts.allow_dirty |= allow_dirty;
+                opts.keep_going |= keep_going;
+                opts.dist_out_dir = out_dir;
+                opts.dist_tag |= tag;
+                opts.dist_path = Some(path);
+            }
+            DylintSubCommand::List { json, name_opts } => {
+                opts.name_opts.absorb(name_opts);
+                opts.list = true;
+                opts.list_json |= json;
+            }
+            DylintSubCommand::New {
+                isolate,
+                workspace,
+                path,
+            } => {
+                opts.isolate |= isolate;
+                opts.new_workspace |= workspace;
+                opts.new_path = Some(path);
+            }
+            DylintSubCommand::Upgrade {
+                allow_downgrade,
+                bisect,
+                end,
+                max_attempts,
+                rust_version,
+                start,
+                path,
+            } => {
+                opts.allow_downgrade |= allow_downgrade;
+                opts.bisect |= bisect;
+                opts.bisect_end = end;
+                opts.bisect_max_attempts = max_attempts;
+                opts.bisect_start = start;
+                opts.rust_version = rust_version;
+                opts.upgrade_path = Some(path);
+            }
+        }
+    }
+    opts
+}
+
+impl NameOpts {
+    pub fn absorb(&mut self, other: Self) {
+        self.all |= other.all;
+        self.allow_uninstalled_toolchain |= other.allow_uninstalled_toolchain;
+        self.exact |= other.exact;
+        self.libs.extend(other.libs);
+        if other.libs_from.is_some() {
+            self.libs_from = other.libs_from;
+        }
+        self.libs_from_only |= other.libs_from_only;
+        self.no_build |= other.no_build;
+        self.no_cache |= other.no_cache;
+        self.no_metadata |= other.no_metadata;
+        self.paths.extend(other.paths);
+    }
+}
+
+/// Entry point shared by the `cargo-dylint` and `dylint` binaries
+pub fn main() {
+    env_logger::try_init().unwrap_or_else(|error| {
+        dylint::__warn(
+            &dylint::Dylint::default(),
+            &format!("`env_logger` already initialized: {error}"),
+        );
+    });
+
+    let args: Vec<_> = std::env::args().map(OsString::from).collect();
+
+    // smoelius: Mimic what the standard library does when `main` returns an error, but allow the
+    // exit code to depend upon the error.
+    if let Err(error) = cargo_dylint(&args) {
+        eprintln!("Error: {error:?}");
+        std::process::exit(error.exit_code());
+    }
+}
+
+fn cargo_dylint<T: AsRef<OsStr>>(args: &[T]) -> dylint::ColorizedResult<()> {
+    let opts = parse_args(args).unwrap_or_else(|error| {
+        // smoelius: `--help` and `--version` are reported as errors, but they should still exit
+        // successfully. Only genuine parse errors get the usage error exit code.
+        if !error.use_stderr() {
+            error.exit();
+        }
+        error.print().unwrap_or_default();
+        std::process::exit(dylint::USAGE_ERROR_EXIT_CODE);
+    });
+    dylint::run(&dylint::Dylint::from(opts)).map_err(dylint::ColorizedError::new)
+}
+
+fn parse_args<T: AsRef<OsStr>>(args: &[T]) -> Result<Dylint, clap::Error> {
+    let invocation = Invocation::detect(args);
+    let n_skipped = usize::from(invocation == Invocation::CargoSubcommand);
+    let args = args
+        .iter()
+        .take(1)
+        .chain(args.iter().skip(1 + n_skipped))
+        .map(AsRef::as_ref);
+    let matches = Dylint::command()
+        .name(invocation.name())
+        .bin_name(invocation.bin_name())
+        .try_get_matches_from(args)?;
+    Dylint::from_arg_matches(&matches)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use clap::error::ErrorKind;
+
+    #[test]
+    fn verify_cli() {
+        Dylint::command().debug_assert();
+    }
+
+    #[test]
+    fn invocation_forms() {
+        for args in [
+            &["cargo-dylint", "dylint", "--lib", "foo"][..],
+            &["/path/to/cargo-dylint", "dylint", "--lib", "foo"],
+            &["cargo-dylint", "--lib", "foo"],
+            &["dylint", "--lib", "foo"],
+            &["/path/to/dylint", "--lib", "foo"],
+            &["dylint.exe", "--lib", "foo"],
+        ] {
+            let opts = parse_args(args).unwrap();
+            assert_eq!(vec!["foo".to_owned()], opts.name_opts.libs, "{args:?}");
+            assert!(opts.names.is_empty(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn positional_dylint() {
+        for args in [
+            &["cargo-dylint", "dylint", "dylint"][..],
+            &["cargo-dylint", "--all", "dylint"],
+            &["dylint", "dylint"],
+        ] {
+            let opts = parse_args(args).unwrap();
+            assert_eq!(vec!["dylint".to_owned()], opts.names, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn version() {
+        for (args, expected) in [
+            (
+                &["cargo-dylint", "dylint", "--version"][..],
+                "cargo-dylint ",
+            ),
+            (&["cargo-dylint", "--version"], "cargo-dylint "),
+            (&["dylint", "--version"], "dylint "),
+        ] {
+            let error = parse_args(args).unwrap_err();
+            assert_eq!(ErrorKind::DisplayVersion, error.kind());
+            assert_eq!(
+                format!("{expected}{}\n", crate_version!()),
+                error.to_string(),
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn help() {
+        for (args, expected) in [
+            (
+                &["cargo-dylint", "dylint", "--help"][..],
+                "cargo dylint [OPTIONS]",
+            ),
+            (&["cargo-dylint", "--help"], "cargo-dylint [OPTIONS]"),
+            (&["dylint", "--help"], "dylint [OPTIONS]"),
+        ] {
+            let error = parse_args(args).unwrap_err();
+            assert_eq!(ErrorKind::DisplayHelp, error.kind());
+            assert!(
+                error.to_string().contains(&format!("Usage: {expected}")),
+                "{args:?}"
+            );
+        }
+    }
+}
+
+/// `no_env_logger_warning` fails if [`std::process::Command::new`] is replaced with
+/// [`assert_cmd::cargo::CommandCargoExt::cargo_bin`]. I don't understand why.
+///
+/// [`assert_cmd::cargo::CommandCargoExt::cargo_bin`]: https://docs.rs/assert_cmd/latest/assert_cmd/cargo/trait.CommandCargoExt.html#tymethod.cargo_bin
+/// [`std::process::Command::new`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.new
+#[test]
+fn no_env_logger_warning() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    std::process::Command::new("cargo")
+        .args(["run", "--bin", "cargo-dylint"])
+        // std::process::Command::cargo_bin("cargo-dylint").unwrap()
+        .assert()
+        // smoelius: Without arguments, `cargo-dylint` is a direct invocation with nothing to do.
+        .success()
+        .stderr(predicates::str::contains("`env_logger` already initialized").not());
+}
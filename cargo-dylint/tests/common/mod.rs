@@ -0,0 +1,107 @@
+// smoelius: Each file in `tests` is compiled as its own crate, and no crate uses all of the
+// functions in this module.
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Context, Result};
+use assert_cmd::prelude::*;
+use std::{
+    fs::{write, OpenOptions},
+    io::Write,
+    path::Path,
+};
+use tempfile::{tempdir, TempDir};
+
+/// Creates a package with `cargo init` in a new temporary directory. The package is named after
+/// the directory.
+pub fn package() -> Result<TempDir> {
+    let tempdir = tempdir()?;
+
+    std::process::Command::new("cargo")
+        .current_dir(&tempdir)
+        .args([
+            "init",
+            "--name",
+            tempdir
+                .path()
+                .file_name()
+                .ok_or_else(|| anyhow!("Could not get file name"))?
+                .to_string_lossy()
+                .trim_start_matches('.'),
+        ])
+        .assert()
+        .success();
+
+    Ok(tempdir)
+}
+
+/// Like [`package`], but the package's workspace metadata lists the example libraries `examples`
+/// (e.g., `"restriction/const_path_join"`).
+pub fn package_with_examples(examples: &[&str]) -> Result<TempDir> {
+    let tempdir = package()?;
+
+    append_workspace_metadata(tempdir.path(), examples, "")?;
+
+    Ok(tempdir)
+}
+
+/// Like [`package_with_examples`], but the package's `src/main.rs` file contains `main_rs`.
+pub fn package_with_main_rs(examples: &[&str], main_rs: &str) -> Result<TempDir> {
+    let tempdir = package_with_examples(examples)?;
+
+    write(tempdir.path().join("src/main.rs"), main_rs)?;
+
+    Ok(tempdir)
+}
+
+/// Appends a `[workspace.metadata.dylint]` table to the manifest in `path`. The table lists the
+/// example libraries `examples`, and is followed by `extra`, which can contain additional keys.
+pub fn append_workspace_metadata(path: &Path, examples: &[&str], extra: &str) -> Result<()> {
+    append_to_manifest(
+        path,
+        &format!(
+            "\n[workspace.metadata.dylint]\n{}{extra}",
+            libraries(examples)
+        ),
+    )
+}
+
+/// Returns a `libraries` key listing the example libraries `examples`.
+pub fn libraries(examples: &[&str]) -> String {
+    #[allow(unknown_lints, env_cargo_path)]
+    let examples_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("examples");
+
+    path_libraries(
+        &examples
+            .iter()
+            .map(|example| examples_dir.join(example))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Returns a `libraries` key listing the libraries at `paths`.
+pub fn path_libraries(paths: &[impl AsRef<Path>]) -> String {
+    let entries = paths
+        .iter()
+        .map(|path| {
+            let path_spec = path.as_ref().to_string_lossy().replace('\\', "\\\\");
+            format!("    {{ path = \"{path_spec}\" }},\n")
+        })
+        .collect::<String>();
+
+    format!("libraries = [\n{entries}]\n")
+}
+
+pub fn append_to_manifest(path: &Path, contents: &str) -> Result<()> {
+    let manifest = path.join("Cargo.toml");
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(true)
+        .open(&manifest)
+        .with_context(|| format!("Could not open `{}`", manifest.to_string_lossy()))?;
+
+    writeln!(file, "{contents}")
+        .with_context(|| format!("Could not write to `{}`", manifest.to_string_lossy()))
+}
@@ -0,0 +1,165 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::package_with_main_rs;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS_WITH_DYLINT_WARNING: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+const MAIN_RS_WITH_RUSTC_WARNING: &str = r#"
+fn main() {
+    let x = 0;
+}
+"#;
+
+#[test]
+fn dylint_warning() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings"])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE)
+        .stderr(
+            predicate::str::contains("warning: path could be constructed from a string literal")
+                .and(predicate::str::contains(
+                    "`--deny-warnings` was passed and Dylint lints produced 1 warning",
+                )),
+        );
+}
+
+#[test]
+fn rustc_warning() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_RUSTC_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning: unused variable: `x`"));
+}
+
+#[test]
+fn path_filter_without_deny_warnings() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--include-path", "src/**"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "path could be constructed from a string literal",
+        ));
+}
+
+#[test]
+fn exclude_path() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--deny-warnings",
+            "--exclude-path",
+            "src/main.rs",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("path could be constructed from a string literal").not());
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--deny-warnings",
+            "--include-path",
+            "src/**",
+        ])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE);
+}
+
+#[test]
+fn summary() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--quiet", "--summary"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(format!(r"(?m)^1  \S+  {LIB_NAME}$")).unwrap())
+        .stderr(predicate::str::contains("path could be constructed from a string literal").not());
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--summary", "--deny-warnings"])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE);
+}
+
+#[test]
+fn timings() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--quiet", "--timings"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"(?m)^cargo check with toolchain `[^`]*` +\d+\.\d{3}s$")
+                .unwrap()
+                .and(
+                    predicate::str::is_match(format!(r"(?m)^{LIB_NAME}::\w+ +\d+\.\d{{3}}s$"))
+                        .unwrap(),
+                ),
+        );
+
+    assert!(tempdir.path().join("target/dylint/timings.json").exists());
+}
+
+#[test]
+fn fix_conflict() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings", "--fix"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--deny-warnings` cannot be used with `--fix`",
+        ));
+}
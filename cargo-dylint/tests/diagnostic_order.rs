@@ -0,0 +1,61 @@
+use assert_cmd::prelude::*;
+use std::fs::write;
+use tempfile::TempDir;
+
+mod common;
+use common::package_with_main_rs;
+
+const EXAMPLES: &[&str] = &["restriction/env_literal", "restriction/const_path_join"];
+
+const MAIN_RS: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+    let _ = std::env::var("RUST_LOG");
+    let _ = std::path::Path::new("..").join("src");
+    let _ = std::env::var("RUST_BACKTRACE");
+}
+"#;
+
+// smoelius: Cargo's status lines include timings, e.g., `Finished dev [unoptimized + debuginfo]
+// target(s) in 0.45s`, so they are excluded from the comparison.
+const STATUSES: &[&str] = &["Blocking", "Checking", "Compiling", "Finished"];
+
+#[test]
+fn diagnostic_order_is_deterministic() {
+    let tempdir = package_with_main_rs(EXAMPLES, MAIN_RS).unwrap();
+
+    // smoelius: The first run builds the libraries, and its output says so.
+    let _ = stderr(&tempdir);
+
+    let stderrs = (0..5).map(|_| stderr(&tempdir)).collect::<Vec<_>>();
+
+    assert!(
+        stderrs[0].contains("warning: path could be constructed from a string literal")
+            && stderrs[0].contains("warning: referring to an environment variable"),
+        "{:?}",
+        stderrs[0]
+    );
+
+    for stderr in &stderrs[1..] {
+        assert_eq!(&stderrs[0], stderr);
+    }
+}
+
+fn stderr(tempdir: &TempDir) -> String {
+    // smoelius: Rewriting `main.rs` ensures that Cargo rechecks the package rather than replaying
+    // cached diagnostics.
+    write(tempdir.path().join("src/main.rs"), MAIN_RS).unwrap();
+
+    let assert = std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(tempdir)
+        .args(["dylint", "--all"])
+        .assert()
+        .success();
+
+    String::from_utf8_lossy(&assert.get_output().stderr)
+        .lines()
+        .filter(|line| !STATUSES.contains(&line.trim_start().split(' ').next().unwrap_or_default()))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
@@ -0,0 +1,167 @@
+use assert_cmd::prelude::*;
+use dylint_internal::{find_and_replace, rustup::SanitizeEnvironment, testing::new_template};
+use predicates::prelude::*;
+use std::{
+    env::consts,
+    fs::{read, read_to_string, OpenOptions},
+    io::Write,
+    path::Path,
+};
+use tempfile::tempdir;
+
+// smoelius: Both toolchains are ones `boundary_toolchains` shows the template builds with.
+const TOOLCHAINS: &[&str] = &["nightly-2023-05-30", "nightly-2023-06-29"];
+
+#[test]
+fn dist() {
+    let tempdir = tempdir().unwrap();
+
+    new_dist_package(tempdir.path(), TOOLCHAINS);
+
+    let out_dir = tempdir.path().join("dist");
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "dist", "--allow-dirty", "--out-dir"])
+        .arg(&out_dir)
+        .arg(tempdir.path())
+        .assert()
+        .success();
+
+    let manifest = serde_json::from_str::<serde_json::Value>(
+        &read_to_string(out_dir.join("manifest.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!("fill_me_in", manifest["name"]);
+
+    let artifacts = manifest["artifacts"].as_array().unwrap();
+    assert_eq!(TOOLCHAINS.len(), artifacts.len());
+
+    let sums = read_to_string(out_dir.join("SHA256SUMS")).unwrap();
+
+    for (&toolchain, artifact) in TOOLCHAINS.iter().zip(artifacts) {
+        assert_eq!(toolchain, artifact["toolchain"]);
+
+        let target = artifact["target"].as_str().unwrap();
+        let path = artifact["path"].as_str().unwrap();
+        assert_eq!(
+            format!(
+                "{target}/{}fill_me_in@{toolchain}{}",
+                consts::DLL_PREFIX,
+                consts::DLL_SUFFIX
+            ),
+            path
+        );
+        assert!(!read(out_dir.join(path)).unwrap().is_empty());
+
+        let sha256 = artifact["sha256"].as_str().unwrap();
+        assert_eq!(64, sha256.len());
+        assert!(sums.contains(&format!("{sha256}  {path}\n")));
+    }
+}
+
+#[test]
+fn dist_keep_going() {
+    let tempdir = tempdir().unwrap();
+
+    new_dist_package(tempdir.path(), &[TOOLCHAINS[1], "nightly-1970-01-01"]);
+
+    let out_dir = tempdir.path().join("dist");
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args([
+            "dylint",
+            "dist",
+            "--allow-dirty",
+            "--keep-going",
+            "--out-dir",
+        ])
+        .arg(&out_dir)
+        .arg(tempdir.path())
+        .assert()
+        .code(dylint::LIBRARY_BUILD_FAILED_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "Could not build for 1 of 2 platforms",
+        ))
+        .stderr(predicate::str::contains(
+            "Could not build for toolchain `nightly-1970-01-01`",
+        ));
+
+    let manifest = serde_json::from_str::<serde_json::Value>(
+        &read_to_string(out_dir.join("manifest.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(1, manifest["artifacts"].as_array().unwrap().len());
+}
+
+#[test]
+fn dist_dirty() {
+    let tempdir = tempdir().unwrap();
+
+    new_dist_package(tempdir.path(), &TOOLCHAINS[..1]);
+
+    std::process::Command::new("git")
+        .current_dir(&tempdir)
+        .args(["init", "--quiet"])
+        .assert()
+        .success();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "dist"])
+        .arg(tempdir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has uncommitted changes"));
+}
+
+#[test]
+fn dist_tag_conflicts_with_allow_dirty() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "dist", "--tag", "--allow-dirty", "."])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--tag` cannot be used with `--allow-dirty`",
+        ));
+}
+
+fn new_dist_package(path: &Path, toolchains: &[&str]) {
+    new_template(path).unwrap();
+
+    find_and_replace(
+        &path.join("Cargo.toml"),
+        &[r"s/\r?\nclippy_utils = [^\r\n]*//"],
+    )
+    .unwrap();
+
+    for toolchain in toolchains {
+        // smoelius: A toolchain that does not exist is expected to fail to install.
+        let _ = dylint_internal::Command::new("rustup")
+            .sanitize_environment()
+            .args([
+                "toolchain",
+                "install",
+                toolchain,
+                "--profile",
+                "minimal",
+                "--component",
+                "llvm-tools-preview",
+                "--component",
+                "rustc-dev",
+            ])
+            .success();
+    }
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(path.join("Cargo.toml"))
+        .unwrap();
+    writeln!(file, "\n[package.metadata.dylint.dist]\nplatforms = [").unwrap();
+    for toolchain in toolchains {
+        writeln!(file, "    {{ toolchain = \"{toolchain}\" }},").unwrap();
+    }
+    writeln!(file, "]").unwrap();
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use assert_cmd::prelude::*;
+use cargo_metadata::MetadataCommand;
+use dylint_internal::{env, find_and_replace, rustup::SanitizeEnvironment, testing::new_template};
+use predicates::prelude::*;
+use std::{
+    env::join_paths,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+// smoelius: The second library is a "fork" of the first: it has a different name but declares a
+// lint with the same name.
+#[test]
+fn duplicate_lints() {
+    let tempdirs = (tempdir().unwrap(), tempdir().unwrap());
+
+    new_template(tempdirs.0.path()).unwrap();
+    new_template(tempdirs.1.path()).unwrap();
+
+    find_and_replace(
+        &tempdirs.1.path().join("Cargo.toml"),
+        [r#"s/^name = "fill_me_in"$/name = "fill_me_in_fork"/"#],
+    )
+    .unwrap();
+
+    for tempdir in [&tempdirs.0, &tempdirs.1] {
+        dylint_internal::cargo::build(&format!("dylint-template in {:?}", tempdir.path()), false)
+            .sanitize_environment()
+            .current_dir(tempdir)
+            .success()
+            .unwrap();
+    }
+
+    let target_debugs = (
+        target_debug(tempdirs.0.path()).unwrap(),
+        target_debug(tempdirs.1.path()).unwrap(),
+    );
+
+    let paths = join_paths([&target_debugs.0, &target_debugs.1]).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdirs.0)
+        .envs([(env::DYLINT_LIBRARY_PATH, paths)])
+        .args(["dylint", "--all", "--no-metadata"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("lint `fill_me_in` is declared by both `")
+                .and(predicate::str::contains(
+                    target_debugs.0.to_string_lossy().as_ref(),
+                ))
+                .and(predicate::str::contains(
+                    target_debugs.1.to_string_lossy().as_ref(),
+                ))
+                .and(predicate::str::contains("duplicate specification").not()),
+        );
+}
+
+// smoelius: For the test to pass on OSX, the paths have to be canonicalized, because `/var` is
+// symlinked to `/private/var`.
+fn target_debug(path: &Path) -> Result<PathBuf> {
+    let metadata = MetadataCommand::new().current_dir(path).no_deps().exec()?;
+    let debug_dir = metadata.target_directory.join("debug");
+    debug_dir
+        .canonicalize()
+        .with_context(|| format!("Could not canonicalize {debug_dir:?}"))
+        .map_err(Into::into)
+}
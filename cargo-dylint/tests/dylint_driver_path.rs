@@ -1,9 +1,10 @@
+use assert_cmd::prelude::*;
 use dylint_internal::{
     driver as dylint_driver, env,
     rustup::{toolchain_path, SanitizeEnvironment},
     testing::new_template,
 };
-use std::fs::create_dir_all;
+use std::{fs::create_dir_all, path::Path};
 use tempfile::tempdir_in;
 
 #[test]
@@ -25,12 +26,77 @@ fn dylint_driver_path() {
 
     // smoelius: Verify that the driver can be run directly.
     // https://github.com/trailofbits/dylint/issues/54
-    let toolchain_path = toolchain_path(tempdir.path()).unwrap();
-    let toolchain = toolchain_path.iter().last().unwrap();
+    let toolchain = toolchain(tempdir.path());
     let mut command = dylint_driver(
-        &toolchain.to_string_lossy(),
-        &dylint_driver_path.join(toolchain).join("dylint-driver"),
+        &toolchain,
+        &dylint_driver_path.join(&toolchain).join("dylint-driver"),
     )
     .unwrap();
     command.success().unwrap();
 }
+
+#[test]
+fn relative_driver_path_is_created() {
+    let tempdir = tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+    new_template(tempdir.path()).unwrap();
+
+    let dylint_driver_path = tempdir.path().join("target/relative_drivers");
+
+    assert!(!dylint_driver_path.exists());
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .env_remove(env::DYLINT_DRIVER_PATH)
+        .args([
+            "dylint",
+            "--path",
+            ".",
+            "--driver-path",
+            "target/relative_drivers",
+        ])
+        .assert()
+        .success();
+
+    assert!(dylint_driver_path.join("README.txt").exists());
+    assert!(dylint_driver_path
+        .join(toolchain(tempdir.path()))
+        .join("dylint-driver")
+        .exists());
+}
+
+#[test]
+fn driver_path_flag_overrides_env() {
+    let tempdir = tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+    new_template(tempdir.path()).unwrap();
+
+    let env_driver_path = tempdir.path().join("target/env_drivers");
+    let flag_driver_path = tempdir.path().join("target/flag_drivers");
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .envs([(env::DYLINT_DRIVER_PATH, &*env_driver_path)])
+        .args(["dylint", "--path", ".", "--driver-path"])
+        .arg(&flag_driver_path)
+        .assert()
+        .success();
+
+    assert!(!env_driver_path.exists());
+    assert!(flag_driver_path
+        .join(toolchain(tempdir.path()))
+        .join("dylint-driver")
+        .exists());
+}
+
+fn toolchain(path: &Path) -> String {
+    let toolchain_path = toolchain_path(path).unwrap();
+    toolchain_path
+        .iter()
+        .last()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned()
+}
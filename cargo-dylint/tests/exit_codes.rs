@@ -0,0 +1,102 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs::{create_dir, read_to_string, write};
+
+mod common;
+use common::package;
+
+#[test]
+fn success() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("EXIT STATUS"));
+}
+
+#[test]
+fn usage_error() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--lib", "foo"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--lib` cannot be used with `--all`",
+        ));
+}
+
+#[test]
+fn unknown_argument() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--no-such-argument"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE);
+}
+
+#[test]
+fn library_build_failed() {
+    let tempdir = package().unwrap();
+
+    let lib_dir = tempdir.path().join("broken_lib");
+    create_dir(&lib_dir).unwrap();
+    create_dir(lib_dir.join("src")).unwrap();
+    write(
+        lib_dir.join("Cargo.toml"),
+        r#"[package]
+name = "broken_lib"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[lib]
+crate-type = ["cdylib"]
+
+[workspace]
+"#,
+    )
+    .unwrap();
+    write(lib_dir.join("src/lib.rs"), r#"compile_error!("broken");"#).unwrap();
+
+    let mut manifest = read_to_string(tempdir.path().join("Cargo.toml")).unwrap();
+    manifest.push_str(
+        r#"
+[workspace]
+exclude = ["broken_lib"]
+
+[workspace.metadata.dylint]
+libraries = [
+    { path = "broken_lib" },
+]
+"#,
+    );
+    write(tempdir.path().join("Cargo.toml"), manifest).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--all"])
+        .assert()
+        .code(dylint::LIBRARY_BUILD_FAILED_EXIT_CODE);
+}
+
+#[test]
+fn quiet_levels() {
+    const WARNING: &str = "Nothing to do. Did you forget `--all`?";
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "-q"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(WARNING));
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "-qq"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(WARNING).not());
+}
@@ -0,0 +1,65 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs::{create_dir, write};
+use tempfile::tempdir;
+
+#[test]
+fn explain_config() {
+    let tempdir = tempdir().unwrap();
+
+    write(
+        tempdir.path().join("dylint.toml"),
+        r#"[non_local_effect_before_error_return]
+work_limit = 1_000_000
+"#,
+    )
+    .unwrap();
+
+    let package = tempdir.path().join("package");
+    create_dir(&package).unwrap();
+
+    std::process::Command::new("cargo")
+        .current_dir(&package)
+        .args(["init", "--name", "package"])
+        .assert()
+        .success();
+
+    write(
+        package.join("dylint.toml"),
+        r#"extends = "../dylint.toml"
+
+[global]
+exclude-paths = ["src/generated/**"]
+"#,
+    )
+    .unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&package)
+        .args(["dylint", "--explain-config"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r#"(?m)^exclude-paths = \["src/generated/\*\*"\]  # .*package.dylint\.toml:4$"#,
+            )
+            .unwrap()
+            .and(
+                predicate::str::is_match(r#"(?m)^work_limit = 1000000  # .*dylint\.toml:2$"#)
+                    .unwrap(),
+            ),
+        );
+}
+
+#[test]
+fn fix_conflict() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--explain-config", "--fix"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--explain-config` cannot be used with `--fix`, `list`, or `--watch`",
+        ));
+}
@@ -0,0 +1,175 @@
+// smoelius: The file names in the JSON messages are compared with `/`-separated paths.
+#![cfg(unix)]
+
+use anyhow::{anyhow, Context, Result};
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{
+    fs::{create_dir_all, write},
+    path::Path,
+};
+use tempfile::{tempdir, TempDir};
+
+const CATEGORY: &str = "restriction";
+const LIB_NAME: &str = "const_path_join";
+
+fn workspace_manifest(path_spec: &str) -> String {
+    format!(
+        r#"
+[workspace]
+members = ["a", "b"]
+
+[workspace.metadata.dylint]
+libraries = [
+    {{ path = "{path_spec}" }},
+]
+"#,
+    )
+}
+
+const LINTED_FN: &str = r#"
+pub fn f() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+#[test]
+fn nested_module() {
+    let tempdir = workspace().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--files", "b/src/foo/bar.rs"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(r#""reason":"compiler-message""#)
+                .and(predicate::str::contains(
+                    r#""file_name":"b/src/foo/bar.rs""#,
+                ))
+                .and(predicate::str::contains(r#""file_name":"b/src/lib.rs""#).not())
+                .and(predicate::str::contains(r#""file_name":"a/src/lib.rs""#).not()),
+        )
+        .stderr(predicate::str::contains("Checking a v0.1.0").not());
+}
+
+#[test]
+fn multiple_packages() {
+    let tempdir = workspace().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(tempdir.path().join("b"))
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--files",
+            "../a/src/lib.rs,src/foo/mod.rs",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(r#""file_name":"a/src/lib.rs""#)
+                .and(predicate::str::contains(
+                    r#""file_name":"b/src/foo/mod.rs""#,
+                ))
+                .and(predicate::str::contains(r#""file_name":"b/src/foo/bar.rs""#).not()),
+        );
+}
+
+#[test]
+fn no_json() {
+    let tempdir = workspace().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--files",
+            "b/src/foo/bar.rs",
+            "--no-json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("compiler-message").not())
+        .stderr(
+            predicate::str::contains("warning: path could be constructed from a string literal")
+                .and(predicate::str::contains("b/src/foo/bar.rs")),
+        );
+}
+
+#[test]
+fn file_outside_targets() {
+    let tempdir = workspace().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--files", "notes.rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(
+            predicate::str::contains("`notes.rs` does not belong to any workspace target")
+                .and(predicate::str::contains("Checking a v0.1.0").not())
+                .and(predicate::str::contains("Checking b v0.1.0").not()),
+        );
+}
+
+// smoelius: The workspace has two members, `a` and `b`. `b`'s library has a nested module tree:
+// `src/lib.rs` declares `foo`, and `src/foo/mod.rs` declares `bar`. Every file in either member
+// contains a warning. The modules are public, so that `rustc` does not warn about dead code in
+// files other than those asked about. `notes.rs`, at the workspace root, belongs to no target.
+fn workspace() -> Result<TempDir> {
+    let tempdir = tempdir()?;
+
+    #[allow(unknown_lints, env_cargo_path)]
+    let parent = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .ok_or_else(|| anyhow!("Could not get parent directory"))?;
+
+    let path_spec = parent
+        .join("examples")
+        .join(CATEGORY)
+        .join(LIB_NAME)
+        .to_string_lossy()
+        .replace('\\', "\\\\");
+
+    write_file(
+        &tempdir.path().join("Cargo.toml"),
+        &workspace_manifest(&path_spec),
+    )?;
+    write_file(&tempdir.path().join("notes.rs"), LINTED_FN)?;
+
+    for (name, lib_rs) in [("a", String::new()), ("b", "pub mod foo;\n".to_owned())] {
+        let package = tempdir.path().join(name);
+        write_file(
+            &package.join("Cargo.toml"),
+            &format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"),
+        )?;
+        write_file(&package.join("src/lib.rs"), &(lib_rs + LINTED_FN))?;
+    }
+
+    write_file(
+        &tempdir.path().join("b/src/foo/mod.rs"),
+        &format!("pub mod bar;\n{LINTED_FN}"),
+    )?;
+    write_file(&tempdir.path().join("b/src/foo/bar.rs"), LINTED_FN)?;
+
+    Ok(tempdir)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).with_context(|| {
+            format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+        })?;
+    }
+    write(path, contents)
+        .with_context(|| format!("`write` failed for `{}`", path.to_string_lossy()))
+}
@@ -1,11 +1,12 @@
 use anyhow::{anyhow, Context, Result};
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use std::{
     fs::{read_to_string, write, OpenOptions},
     io::Write,
     path::Path,
 };
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
 
 const CATEGORY: &str = "restriction";
 const LIB_NAME: &str = "const_path_join";
@@ -35,9 +36,89 @@ fn main() {
 }
 "#;
 
+// smoelius: The generated file is included from `OUT_DIR`, which is in the target directory, i.e.,
+// outside of the workspace. So the suggestion for the generated file is not applied.
+const BUILD_RS: &str = r#"
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(
+        std::path::Path::new(&out_dir).join("generated.rs"),
+        "pub fn generated() -> std::path::PathBuf {\n    std::path::Path::new(\"..\").join(\"target\")\n}\n",
+    )
+    .unwrap();
+}
+"#;
+
+const MAIN_RS_WITH_GENERATED: &str = r#"
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+fn main() {
+    let _ = generated();
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+const MAIN_WITH_GENERATED_FIXED: &str = r#"
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+fn main() {
+    let _ = generated();
+    let _ = std::path::PathBuf::from("../target");
+}
+"#;
+
 #[test]
 fn fix() {
-    let tempdir = tempdir().unwrap();
+    let tempdir = package().unwrap();
+
+    write(tempdir.path().join("src/main.rs"), MAIN_RS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--fix", LIB_NAME, "--", "--allow-dirty"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Fixes: applied 2, skipped 0 outside workspace",
+        ));
+
+    let main_actual = read_to_string(tempdir.path().join("src/main.rs")).unwrap();
+
+    assert_eq!(main_actual, MAIN_FIXED);
+}
+
+#[test]
+fn fix_out_dir() {
+    let tempdir = package().unwrap();
+
+    write(tempdir.path().join("build.rs"), BUILD_RS).unwrap();
+
+    write(tempdir.path().join("src/main.rs"), MAIN_RS_WITH_GENERATED).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--fix", LIB_NAME, "--", "--allow-dirty"])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::is_match(
+                r"note: the suggested fix for `const_path_join` was not applied because `[^`]*[/\\]out[/\\]generated\.rs` is outside the workspace",
+            )
+            .unwrap()
+            .and(predicate::str::contains(
+                "Fixes: applied 1, skipped 1 outside workspace",
+            )),
+        );
+
+    let main_actual = read_to_string(tempdir.path().join("src/main.rs")).unwrap();
+
+    assert_eq!(main_actual, MAIN_WITH_GENERATED_FIXED);
+}
+
+fn package() -> Result<TempDir> {
+    let tempdir = tempdir().with_context(|| "`tempdir` failed")?;
 
     std::process::Command::new("cargo")
         .current_dir(&tempdir)
@@ -55,20 +136,9 @@ fn fix() {
         .assert()
         .success();
 
-    append_workspace_metadata(tempdir.path()).unwrap();
-
-    write(tempdir.path().join("src/main.rs"), MAIN_RS).unwrap();
-
-    std::process::Command::cargo_bin("cargo-dylint")
-        .unwrap()
-        .current_dir(&tempdir)
-        .args(["dylint", "--fix", LIB_NAME, "--", "--allow-dirty"])
-        .assert()
-        .success();
-
-    let main_actual = read_to_string(tempdir.path().join("src/main.rs")).unwrap();
+    append_workspace_metadata(tempdir.path())?;
 
-    assert_eq!(main_actual, MAIN_FIXED);
+    Ok(tempdir)
 }
 
 fn append_workspace_metadata(path: &Path) -> Result<()> {
@@ -0,0 +1,77 @@
+use dylint_internal::{env, rustup::SanitizeEnvironment, testing::new_template};
+use std::{
+    fs::{create_dir_all, read_dir},
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir_in;
+
+#[test]
+fn hermetic() {
+    let tempdir = tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+    new_template(tempdir.path()).unwrap();
+
+    let dylint_driver_path = tempdir.path().join("target/dylint_drivers");
+    let home = tempdir.path().join("target/home");
+
+    create_dir_all(&dylint_driver_path).unwrap();
+    create_dir_all(&home).unwrap();
+
+    // smoelius: The outer `cargo` and `rustup` need the real homes, since `HOME` will be fake.
+    let cargo_home = real_home(env::CARGO_HOME, ".cargo");
+    let rustup_home = real_home(env::RUSTUP_HOME, ".rustup");
+
+    let hermetic_test = |dylint_driver_path: Option<&Path>| {
+        let mut command = dylint_internal::cargo::test("hermetic dylint-template", false);
+        command.sanitize_environment().current_dir(&tempdir).envs([
+            (env::DYLINT_TESTING_HERMETIC, Path::new("1")),
+            (env::HOME, &*home),
+            (env::CARGO_HOME, &*cargo_home),
+            (env::RUSTUP_HOME, &*rustup_home),
+        ]);
+        if let Some(dylint_driver_path) = dylint_driver_path {
+            command.envs([(env::DYLINT_DRIVER_PATH, dylint_driver_path)]);
+        } else {
+            command.env_remove(env::DYLINT_DRIVER_PATH);
+        }
+        command
+    };
+
+    let error = hermetic_test(None).output().unwrap_err();
+    assert!(
+        error.to_string().contains(
+            "Hermetic mode requires `DYLINT_DRIVER_PATH` to be set to a directory containing a \
+             pre-built driver"
+        ),
+        "{error}"
+    );
+
+    let error = hermetic_test(Some(&dylint_driver_path))
+        .output()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Hermetic mode requires a pre-built driver"),
+        "{error}"
+    );
+
+    // smoelius: Provision the driver by running the tests once in non-hermetic mode.
+    dylint_internal::cargo::test("dylint-template", false)
+        .sanitize_environment()
+        .current_dir(&tempdir)
+        .envs([(env::DYLINT_DRIVER_PATH, &*dylint_driver_path)])
+        .success()
+        .unwrap();
+
+    hermetic_test(Some(&dylint_driver_path)).success().unwrap();
+
+    assert!(read_dir(&home).unwrap().next().is_none());
+}
+
+fn real_home(key: &str, dir: &str) -> PathBuf {
+    std::env::var_os(key).map_or_else(
+        || PathBuf::from(std::env::var_os(env::HOME).unwrap()).join(dir),
+        PathBuf::from,
+    )
+}
@@ -0,0 +1,36 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+#[test]
+fn requires_terminal() {
+    let tempdir = tempdir().unwrap();
+
+    std::process::Command::new("cargo")
+        .current_dir(&tempdir)
+        .args(["init", "--name", "interactive_test"])
+        .assert()
+        .success();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--interactive"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`--interactive` requires a terminal. Use `--lib <name>` to select libraries instead.",
+        ));
+}
+
+#[test]
+fn all_conflict() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--interactive", "--all"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`--interactive` cannot be used with `--all`, `--lib`, or `--path`",
+        ));
+}
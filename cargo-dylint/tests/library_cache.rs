@@ -0,0 +1,89 @@
+use assert_cmd::prelude::*;
+use dylint_internal::env;
+use predicates::prelude::*;
+use std::{fs::OpenOptions, io::Write, path::Path};
+use tempfile::{tempdir, TempDir};
+
+// smoelius: "Separate lints into categories" commit
+const REV: &str = "402fc24351c60a3c474e786fd76aa66aa8638d55";
+
+#[test]
+fn shared_between_workspaces() {
+    let cache_dir = tempdir().unwrap();
+
+    let workspaces = [
+        workspace("library_cache_test_a"),
+        workspace("library_cache_test_b"),
+    ];
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&workspaces[0])
+        .env(env::DYLINT_CACHE_DIR, cache_dir.path())
+        .args(["dylint", "--all"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Building workspace metadata entry `crate_wide_allow`",
+        ));
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&workspaces[1])
+        .env(env::DYLINT_CACHE_DIR, cache_dir.path())
+        .args(["dylint", "--all"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Building workspace metadata entry").not());
+
+    cache(cache_dir.path(), "stats").success().stdout(
+        predicate::str::contains("crate_wide_allow")
+            .and(predicate::str::contains(REV))
+            .and(predicate::str::contains("Total: 1 library")),
+    );
+
+    cache(cache_dir.path(), "clean")
+        .success()
+        .stdout(predicate::str::contains("Removed 1 cached library"));
+
+    cache(cache_dir.path(), "stats")
+        .success()
+        .stdout(predicate::str::contains("Total: 0 libraries"));
+}
+
+fn cache(cache_dir: &Path, subcommand: &str) -> assert_cmd::assert::Assert {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .env(env::DYLINT_CACHE_DIR, cache_dir)
+        .args(["dylint", "cache", subcommand])
+        .assert()
+}
+
+fn workspace(name: &str) -> TempDir {
+    let tempdir = tempdir().unwrap();
+
+    dylint_internal::cargo::init(&format!("package `{name}`"), false)
+        .current_dir(&tempdir)
+        .args(["--name", name])
+        .success()
+        .unwrap();
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .append(true)
+        .open(tempdir.path().join("Cargo.toml"))
+        .unwrap();
+
+    write!(
+        file,
+        r#"
+[workspace.metadata.dylint]
+libraries = [
+    {{ git = "https://github.com/trailofbits/dylint", pattern = "examples/general/crate_wide_allow", rev = "{REV}" }},
+]
+"#,
+    )
+    .unwrap();
+
+    tempdir
+}
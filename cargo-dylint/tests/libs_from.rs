@@ -0,0 +1,106 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs::write;
+
+mod common;
+use common::{append_workspace_metadata, libraries, package};
+
+#[test]
+fn file() {
+    let tempdir = package().unwrap();
+
+    write(
+        tempdir.path().join("libs.toml"),
+        libraries(&["restriction/const_path_join"]),
+    )
+    .unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "list", "--libs-from", "libs.toml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("const_path_join"));
+}
+
+#[test]
+fn stdin() {
+    let tempdir = package().unwrap();
+
+    assert_cmd::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "list", "--libs-from", "-"])
+        .write_stdin(libraries(&["restriction/const_path_join"]))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("const_path_join"));
+}
+
+#[test]
+fn merge_and_replace() {
+    let tempdir = package().unwrap();
+
+    append_workspace_metadata(tempdir.path(), &["restriction/try_io_result"], "").unwrap();
+
+    write(
+        tempdir.path().join("libs.toml"),
+        libraries(&["restriction/const_path_join"]),
+    )
+    .unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "list", "--libs-from", "libs.toml"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("const_path_join")
+                .and(predicate::str::contains("try_io_result")),
+        );
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "list",
+            "--libs-from",
+            "libs.toml",
+            "--libs-from-only",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("const_path_join")
+                .and(predicate::str::contains("try_io_result").not()),
+        );
+}
+
+#[test]
+fn unknown_key() {
+    let tempdir = package().unwrap();
+
+    assert_cmd::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "list", "--libs-from", "-"])
+        .write_stdin(r#"rustflags = ["--cfg", "special"]"#)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not parse library specification from standard input",
+        ));
+}
+
+#[test]
+fn libs_from_only_requires_libs_from() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "list", "--libs-from-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--libs-from"));
+}
@@ -0,0 +1,14 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn fix_conflict() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--lint-deps", "--fix"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--lint-deps` cannot be used with `--fix`",
+        ));
+}
@@ -7,7 +7,7 @@ use assert_cmd::prelude::*;
 use cargo_metadata::MetadataCommand;
 use dylint_internal::{
     clippy_utils::{set_clippy_utils_dependency_revision, set_toolchain_channel},
-    env, library_filename,
+    env, lib_paths, library_filename,
     rustup::SanitizeEnvironment,
     testing::new_template,
 };
@@ -15,6 +15,7 @@ use glob::glob;
 use predicates::prelude::*;
 use std::{
     env::join_paths,
+    fs::{read_to_string, write},
     path::{Path, PathBuf},
 };
 use tempfile::tempdir;
@@ -174,7 +175,7 @@ fn list_by_path() {
         &tempdir
             .path()
             .join("target/debug")
-            .join(library_filename("fill_me_in", "*"))
+            .join(library_filename::compose("fill_me_in", "*", None))
             .to_string_lossy(),
     )
     .ok()
@@ -193,6 +194,118 @@ fn list_by_path() {
         );
 }
 
+#[test]
+fn list_json_with_metadata() {
+    let tempdir = tempdir().unwrap();
+
+    new_template(tempdir.path()).unwrap();
+
+    let lib_rs = tempdir.path().join("src/lib.rs");
+    let contents = read_to_string(&lib_rs).unwrap();
+    let contents = contents.replace(
+        "    \"description goes here\"\n}",
+        "    \"description goes here\",\n    \
+             description = \"A longer description\",\n    \
+             bad_example = \"let x = 1;\",\n    \
+             good_example = \"let y = 1;\"\n}",
+    );
+    write(lib_rs, contents).unwrap();
+
+    dylint_internal::cargo::build(&format!("dylint-template in {:?}", tempdir.path()), false)
+        .sanitize_environment()
+        .current_dir(&tempdir)
+        .success()
+        .unwrap();
+
+    let assert = std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .envs([(
+            env::DYLINT_LIBRARY_PATH,
+            target_debug(tempdir.path()).unwrap(),
+        )])
+        .args(["dylint", "list", "--json", "--all", "--no-metadata"])
+        .assert()
+        .success();
+
+    let libraries =
+        serde_json::from_slice::<serde_json::Value>(&assert.get_output().stdout).unwrap();
+    assert_eq!(
+        serde_json::json!([{
+            "name": "fill_me_in",
+            "level": "warn",
+            "desc": "description goes here",
+            "description": "A longer description",
+            "bad_example": "let x = 1;",
+            "good_example": "let y = 1;",
+        }]),
+        libraries[0]["lints"]
+    );
+}
+
+// smoelius: The CLI lists lints by running the driver in `--dylint-list` mode. Check that what the
+// CLI prints agrees with what the driver reports.
+#[test]
+fn driver_list() {
+    let tempdir = tempdir().unwrap();
+
+    new_template(tempdir.path()).unwrap();
+
+    dylint_internal::cargo::build(&format!("dylint-template in {:?}", tempdir.path()), false)
+        .sanitize_environment()
+        .current_dir(&tempdir)
+        .success()
+        .unwrap();
+
+    let path = glob(
+        &tempdir
+            .path()
+            .join("target/debug")
+            .join(library_filename::compose("fill_me_in", "*", None))
+            .to_string_lossy(),
+    )
+    .ok()
+    .as_mut()
+    .and_then(Iterator::next)
+    .unwrap()
+    .unwrap();
+
+    let assert = std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args([
+            "dylint",
+            "list",
+            "--json",
+            "--path",
+            &path.to_string_lossy(),
+        ])
+        .assert()
+        .success();
+
+    let libraries =
+        serde_json::from_slice::<serde_json::Value>(&assert.get_output().stdout).unwrap();
+    let toolchain = libraries[0]["toolchain"].as_str().unwrap();
+
+    let driver = dylint::driver_builder::get(&dylint::Dylint::default(), toolchain).unwrap();
+
+    let output = dylint_internal::driver(toolchain, &driver)
+        .unwrap()
+        .envs([(env::DYLINT_LIB_PATHS, lib_paths::encode([&path]))])
+        .args(["--dylint-list", "--json"])
+        .output()
+        .unwrap();
+
+    let listings = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap();
+    assert_eq!(
+        serde_json::json!([{
+            "path": path.to_string_lossy(),
+            "dylint_version": "0.1.0",
+            "toolchain": toolchain,
+            "lints": libraries[0]["lints"],
+        }]),
+        listings
+    );
+}
+
 // smoelius: For the tests to pass on OSX, the paths have to be canonicalized, because `/var` is
 // symlinked to `/private/var`.
 fn target_debug(path: &Path) -> Result<PathBuf> {
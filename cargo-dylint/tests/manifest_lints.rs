@@ -0,0 +1,99 @@
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs::write;
+use tempfile::TempDir;
+
+mod common;
+use common::{append_workspace_metadata, package};
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+const DENY: &str = r#"
+[lints.dylint]
+const_path_join = "deny"
+"#;
+
+const UNKNOWN: &str = r#"
+[lints.dylint]
+const_path_join = "warn"
+no_such_lint = "deny"
+"#;
+
+#[test]
+fn deny() {
+    let tempdir = package_with_lints(DENY).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: path could be constructed from a string literal",
+        ));
+}
+
+#[test]
+fn rustflags_override() {
+    let tempdir = package_with_lints(DENY).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--rustflags",
+            "-A const_path_join",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("path could be constructed from a string literal").not());
+}
+
+#[test]
+fn unknown_lint() {
+    let tempdir = package_with_lints(UNKNOWN).unwrap();
+
+    let assert = std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: path could be constructed from a string literal",
+        ));
+
+    let stderr = std::str::from_utf8(&assert.get_output().stderr).unwrap();
+    assert_eq!(
+        1,
+        stderr
+            .matches(
+                "`no_such_lint` in a `[lints.dylint]` table is not a lint in any of the libraries \
+                 being checked"
+            )
+            .count(),
+        "{stderr:?}"
+    );
+}
+
+fn package_with_lints(lints: &str) -> Result<TempDir> {
+    let tempdir = package()?;
+
+    append_workspace_metadata(tempdir.path(), &[EXAMPLE], lints)?;
+
+    write(tempdir.path().join("src/main.rs"), MAIN_RS)?;
+
+    Ok(tempdir)
+}
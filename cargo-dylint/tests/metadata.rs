@@ -1,7 +1,10 @@
 use assert_cmd::prelude::*;
 use dylint_internal::{env, packaging::isolate};
 use predicates::prelude::*;
-use std::{fs::OpenOptions, io::Write};
+use std::{
+    fs::{read_to_string, write, OpenOptions},
+    io::Write,
+};
 use tempfile::{tempdir, tempdir_in};
 
 // smoelius: "Separate lints into categories" commit
@@ -283,3 +286,51 @@ pattern = "examples/general/crate_wide_allow"
         .failure()
         .stderr(predicate::str::is_match(r"Unknown library keys:\r?\n\s*revision\r?\n").unwrap());
 }
+
+#[test]
+fn registry_source() {
+    let tempdir = tempdir().unwrap();
+
+    dylint_internal::cargo::init("package `registry_source_test`", false)
+        .current_dir(&tempdir)
+        .args(["--name", "registry_source_test"])
+        .success()
+        .unwrap();
+
+    let manifest = tempdir.path().join("Cargo.toml");
+    let contents = read_to_string(&manifest).unwrap();
+
+    // smoelius: `if_chain` is published without a `rust-toolchain` file.
+    for (entry, message) in [
+        (
+            r#"package = "if_chain"
+version = "=1.0.2""#,
+            "An entry without a `git` or `path` field must have `registry-source = true`",
+        ),
+        (
+            r#"version = "=1.0.2"
+registry-source = true"#,
+            "A `registry-source` entry must name its package",
+        ),
+        (
+            r#"package = "if_chain"
+version = "=1.0.2"
+registry-source = true"#,
+            "Version 1.0.2 of `if_chain` was published without a `rust-toolchain` file",
+        ),
+    ] {
+        write(
+            &manifest,
+            format!("{contents}\n[[workspace.metadata.dylint.libraries]]\n{entry}\n"),
+        )
+        .unwrap();
+
+        std::process::Command::cargo_bin("cargo-dylint")
+            .unwrap()
+            .current_dir(&tempdir)
+            .args(["dylint", "--all", "--no-cache"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+}
@@ -0,0 +1,43 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::package_with_examples;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+
+#[test]
+fn near_miss() {
+    let tempdir = package_with_examples(&[EXAMPLE]).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", "const_path_jion"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Could not find `--lib const_path_jion`")
+                .and(predicate::str::contains(
+                    "the library names closest to `const_path_jion` are: `const_path_join`",
+                ))
+                .and(predicate::str::contains("libraries were looked for in:")),
+        );
+}
+
+#[test]
+fn exact() {
+    let tempdir = package_with_examples(&[EXAMPLE]).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", "const_path_jion", "--exact"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Could not find `--lib const_path_jion`")
+                .and(predicate::str::contains("closest").not())
+                .and(predicate::str::contains("libraries were looked for in:")),
+        );
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use dylint_internal::env;
+use std::fs::{create_dir_all, write};
+use tempfile::TempDir;
+
+mod common;
+use common::{append_to_manifest, libraries, package};
+
+const EXAMPLE: &str = "restriction/const_path_join";
+
+// smoelius: Nothing listens on port 9 (discard) on a typical machine, so any attempt to reach the
+// network through this proxy fails.
+const UNREACHABLE_PROXY: &str = "http://127.0.0.1:9";
+
+const MAIN_RS: &str = r#"
+fn main() {
+    helper::helper();
+}
+"#;
+
+// smoelius: The first run populates Cargo's caches, and builds the library and the driver. The
+// later runs must then succeed with the network unreachable.
+#[test]
+fn offline() {
+    let tempdir = vendored_package().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--all"])
+        .assert()
+        .success();
+
+    for flag in ["--offline", "--frozen"] {
+        std::process::Command::cargo_bin("cargo-dylint")
+            .unwrap()
+            .current_dir(&tempdir)
+            .env(env::CARGO_HTTP_PROXY, UNREACHABLE_PROXY)
+            .args(["dylint", "--all", flag])
+            .assert()
+            .success();
+    }
+}
+
+// smoelius: The package's only dependency is vendored as a path dependency.
+fn vendored_package() -> Result<TempDir> {
+    let tempdir = package()?;
+
+    let helper = tempdir.path().join("vendor/helper");
+    create_dir_all(helper.join("src"))?;
+    write(
+        helper.join("Cargo.toml"),
+        r#"[package]
+name = "helper"
+version = "0.1.0"
+edition = "2021"
+publish = false
+"#,
+    )?;
+    write(helper.join("src/lib.rs"), "pub fn helper() {}\n")?;
+
+    append_to_manifest(
+        tempdir.path(),
+        &format!(
+            "helper = {{ path = \"vendor/helper\" }}\n\n[workspace.metadata.dylint]\n{}",
+            libraries(&[EXAMPLE])
+        ),
+    )?;
+
+    write(tempdir.path().join("src/main.rs"), MAIN_RS)?;
+
+    Ok(tempdir)
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use assert_cmd::prelude::*;
+use dylint_internal::testing::new_template;
+use predicates::prelude::*;
+use std::fs::{read_to_string, write};
+use tempfile::{tempdir, TempDir};
+
+mod common;
+use common::{append_to_manifest, package, path_libraries};
+
+// smoelius: The library's package is named `acme-lints`, but its `Cargo.toml` file sets
+// `lib.name = "lints"`.
+#[test]
+fn list_shows_package_name() {
+    let (_lints, workspace) = lints_and_workspace().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&workspace)
+        .args(["dylint", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lints (package `acme-lints`)"));
+}
+
+#[test]
+fn lib_by_package_name() {
+    let (_lints, workspace) = lints_and_workspace().unwrap();
+
+    for name in ["acme-lints", "acme_lints", "lints"] {
+        std::process::Command::cargo_bin("cargo-dylint")
+            .unwrap()
+            .current_dir(&workspace)
+            .args(["dylint", "list", "--lib", name])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("fill_me_in"));
+    }
+}
+
+fn lints_and_workspace() -> Result<(TempDir, TempDir)> {
+    let lints = tempdir()?;
+
+    new_template(lints.path())?;
+
+    let manifest = lints.path().join("Cargo.toml");
+    let contents = read_to_string(&manifest)
+        .with_context(|| format!("Could not read `{}`", manifest.to_string_lossy()))?;
+    let contents = contents
+        .replace(r#"name = "fill_me_in""#, r#"name = "acme-lints""#)
+        .replace("[lib]\n", "[lib]\nname = \"lints\"\n");
+    write(&manifest, contents)
+        .with_context(|| format!("Could not write `{}`", manifest.to_string_lossy()))?;
+
+    let workspace = package()?;
+
+    append_to_manifest(
+        workspace.path(),
+        &format!(
+            "\n[workspace.metadata.dylint]\n{}",
+            path_libraries(&[lints.path()])
+        ),
+    )?;
+
+    Ok((lints, workspace))
+}
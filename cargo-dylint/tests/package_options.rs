@@ -41,6 +41,52 @@ fn new_package() {
         .unwrap();
 }
 
+#[test]
+fn new_workspace_package() {
+    let tempdir = tempdir().unwrap();
+
+    let path_buf = tempdir.path().join("filled_in");
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "new", &path_buf.to_string_lossy(), "--workspace"])
+        .assert()
+        .success();
+
+    check_dylint_dependencies(&path_buf).unwrap();
+
+    dylint_internal::packaging::use_local_packages(&path_buf).unwrap();
+
+    // smoelius: Test the packages one at a time, as `scripts/test.sh` does.
+    for package_path in [path_buf.clone(), path_buf.join("example_lint")] {
+        dylint_internal::cargo::test("filled-in dylint-template workspace package", false)
+            .sanitize_environment()
+            .current_dir(&package_path)
+            .success()
+            .unwrap();
+    }
+}
+
+#[test]
+fn new_workspace_package_isolate() {
+    let tempdir = tempdir().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args([
+            "dylint",
+            "new",
+            &tempdir.path().join("filled_in").to_string_lossy(),
+            "--workspace",
+            "--isolate",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`--isolate` cannot be used with `--workspace`",
+        ));
+}
+
 fn check_dylint_dependencies(path: &Path) -> Result<()> {
     let metadata = MetadataCommand::new().current_dir(path).no_deps().exec()?;
     for package in metadata.packages {
@@ -63,7 +109,7 @@ fn downgrade_upgrade_package() {
     // smoelius: I broke this downgrading code when I switched dylint-template from using a git tag
     // to a git revision to refer to `clippy_utils`. For now, just hardcode the downgrade version.
     /* let mut rust_version = rust_version(tempdir.path()).unwrap();
-    assert!(rust_version.minor != 0);
+    assert_ne!(rust_version.minor, 0);
     rust_version.minor -= 1; */
     let rust_version = Version::parse(RUST_VERSION).unwrap();
 
@@ -130,6 +176,42 @@ fn downgrade_upgrade_package() {
     }
 }
 
+#[test]
+fn upgrade_bisect_range_options() {
+    let tempdir = tempdir().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args([
+            "dylint",
+            "upgrade",
+            &tempdir.path().to_string_lossy(),
+            "--start",
+            "2023-01-01",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`--start` can be used only with `--bisect`",
+        ));
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args([
+            "dylint",
+            "upgrade",
+            &tempdir.path().to_string_lossy(),
+            "--bisect",
+            "--max-attempts",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`--max-attempts` must be at least 1",
+        ));
+}
+
 #[allow(dead_code)]
 fn rust_version(path: &Path) -> Result<Version> {
     let re = Regex::new(r#"^clippy_utils = .*\btag = "rust-([^"]*)""#).unwrap();
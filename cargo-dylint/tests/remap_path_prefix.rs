@@ -0,0 +1,109 @@
+#![cfg(unix)]
+
+use assert_cmd::prelude::*;
+use dylint_internal::env;
+use predicates::prelude::*;
+use std::fs::{create_dir_all, write};
+
+mod common;
+use common::package_with_main_rs;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+// smoelius: Cargo passes the paths of a workspace member's files to `rustc` relative to the
+// workspace root. So remapping `src` changes the paths in the member's diagnostics.
+const REMAP: &str = "--remap-path-prefix=src=/remapped/src";
+
+#[test]
+fn remapped_path_is_shown() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--rustflags", REMAP])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("/remapped/src/main.rs"));
+}
+
+#[test]
+fn exclude_path_matches_real_path() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--rustflags",
+            REMAP,
+            "--exclude-path",
+            "src/main.rs",
+            "--deny-warnings",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("path could be constructed from a string literal").not());
+}
+
+#[test]
+fn unremap_paths() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--rustflags",
+            REMAP,
+            "--unremap-paths",
+        ])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("warning: path could be constructed from a string literal")
+                .and(predicate::str::contains("src/main.rs"))
+                .and(predicate::str::contains("/remapped").not()),
+        );
+}
+
+// smoelius: Cargo uses `build.rustflags` only if neither `CARGO_ENCODED_RUSTFLAGS` nor `RUSTFLAGS`
+// is set.
+#[test]
+fn unremap_paths_from_cargo_config() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+
+    create_dir_all(tempdir.path().join(".cargo")).unwrap();
+    write(
+        tempdir.path().join(".cargo/config.toml"),
+        format!("[build]\nrustflags = [\"{REMAP}\"]\n"),
+    )
+    .unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .env_remove(env::CARGO_ENCODED_RUSTFLAGS)
+        .env_remove(env::RUSTFLAGS)
+        .args(["dylint", "--lib", LIB_NAME, "--unremap-paths"])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("warning: path could be constructed from a string literal")
+                .and(predicate::str::contains("src/main.rs"))
+                .and(predicate::str::contains("/remapped").not()),
+        );
+}
@@ -0,0 +1,98 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs::write;
+
+mod common;
+use common::package_with_main_rs;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS_WITH_DYLINT_WARNING: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+const MAIN_RS_WITHOUT_WARNINGS: &str = r"
+fn main() {}
+";
+
+const SKIPPING: &str = r"Skipping `cargo check [^`]*`, which completed in the interrupted run";
+
+// smoelius: With `--deny-warnings`, a run fails after its check completes, much as though it had
+// been interrupted. The resumed run skips the check but still counts its warning.
+#[test]
+fn resume() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITH_DYLINT_WARNING).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings"])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE);
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings", "--resume"])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE)
+        .stderr(
+            predicate::str::is_match(SKIPPING)
+                .unwrap()
+                .and(predicate::str::contains(
+                    "`--deny-warnings` was passed and Dylint lints produced 1 warning",
+                ))
+                .and(
+                    predicate::str::contains("path could be constructed from a string literal")
+                        .not(),
+                ),
+        );
+
+    write(
+        tempdir.path().join("dylint.toml"),
+        "[another_lint]\nsetting = true\n",
+    )
+    .unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--deny-warnings", "--resume"])
+        .assert()
+        .code(dylint::WARNINGS_DENIED_EXIT_CODE)
+        .stderr(
+            predicate::str::contains("changed since the interrupted run; starting over")
+                .and(predicate::str::is_match(SKIPPING).unwrap().not())
+                .and(predicate::str::contains(
+                    "path could be constructed from a string literal",
+                )),
+        );
+}
+
+#[test]
+fn successful_run_is_not_resumed() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS_WITHOUT_WARNINGS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success();
+
+    assert!(!tempdir.path().join("target/dylint/run-state.json").exists());
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--resume"])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("Found no interrupted run to resume; starting over")
+                .and(predicate::str::is_match(SKIPPING).unwrap().not()),
+        );
+}
@@ -0,0 +1,88 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{
+    fs::{read_to_string, set_permissions, write, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+mod common;
+use common::package_with_main_rs;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS: &str = r#"
+fn main() {
+    let _ = std::path::Path::new("..").join("target");
+}
+"#;
+
+// smoelius: The wrapper records its arguments, one invocation per line, and then runs them, as a
+// caching wrapper would on a cache miss.
+fn wrapper_sh(record: &Path) -> String {
+    format!(
+        r#"#! /bin/sh
+echo "$@" >> '{}'
+exec "$@"
+"#,
+        record.to_string_lossy()
+    )
+}
+
+#[test]
+fn workspace_wrapper() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+    let (wrapper, record) = wrapper(&tempdir).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .env("RUSTC_WORKSPACE_WRAPPER", &wrapper)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: path could be constructed from a string literal",
+        ));
+
+    let contents = read_to_string(&record).unwrap();
+    assert!(
+        contents
+            .lines()
+            .any(|line| line.split(' ').next().map_or(false, |program| {
+                Path::new(program).file_name() == Some("dylint-driver".as_ref())
+            }) && line.contains("--crate-name")),
+        "{contents:?}"
+    );
+}
+
+#[test]
+fn empty_workspace_wrapper() {
+    let tempdir = package_with_main_rs(&[EXAMPLE], MAIN_RS).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .env("RUSTC_WORKSPACE_WRAPPER", "")
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: path could be constructed from a string literal",
+        ));
+}
+
+fn wrapper(tempdir: &TempDir) -> Result<(PathBuf, PathBuf)> {
+    let wrapper = tempdir.path().join("wrapper.sh");
+    let record = tempdir.path().join("record.txt");
+
+    write(&wrapper, wrapper_sh(&record))?;
+    set_permissions(&wrapper, Permissions::from_mode(0o755))?;
+
+    Ok((wrapper, record))
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use dylint_internal::env;
+use predicates::prelude::*;
+use std::fs::write;
+use tempfile::TempDir;
+
+mod common;
+use common::{append_workspace_metadata, package};
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+const MAIN_RS: &str = r#"
+#[cfg(special)]
+compile_error!("`special` is visible");
+
+fn main() {}
+"#;
+
+// smoelius: Cargo does not track `DYLINT_RUSTFLAGS`. So `command_line` checks with the flags before
+// checking without them. A failed check leaves nothing behind that a later check could reuse.
+
+#[test]
+fn command_line() {
+    let tempdir = package_with_main_rs("").unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--rustflags", "--cfg special"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`special` is visible"));
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .success();
+}
+
+#[test]
+fn metadata() {
+    let tempdir = package_with_main_rs(r#"rustflags = ["--cfg", "special"]"#).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`special` is visible"));
+}
+
+#[test]
+fn metadata_whitespace() {
+    let tempdir = package_with_main_rs(r#"rustflags = ["--cfg special"]"#).unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`rustflags` entries cannot contain whitespace",
+        ));
+}
+
+// smoelius: `RUSTFLAGS` are removed when the library is built, but must reach the check.
+#[test]
+fn rustflags_reach_check() {
+    let tempdir = package_with_main_rs("").unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME])
+        .env(env::RUSTFLAGS, "--cfg special")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`special` is visible"));
+}
+
+fn package_with_main_rs(extra_metadata: &str) -> Result<TempDir> {
+    let tempdir = package()?;
+
+    append_workspace_metadata(tempdir.path(), &[EXAMPLE], extra_metadata)?;
+
+    write(tempdir.path().join("src/main.rs"), MAIN_RS)?;
+
+    Ok(tempdir)
+}
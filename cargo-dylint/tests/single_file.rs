@@ -0,0 +1,72 @@
+use dylint_internal::{library_filename, rustup::SanitizeEnvironment};
+use glob::glob;
+use std::{fs::write, path::Path};
+use tempfile::tempdir;
+
+const LIB_NAME: &str = "early_lint";
+
+// smoelius: `missing` cannot be resolved, because the file is compiled on its own. The
+// parenthesized expression is flagged by `early_lint`'s early pass, which runs despite the error.
+const LIB_RS: &str = r"
+use missing::Thing;
+
+pub fn one() -> u32 {
+    (1)
+}
+";
+
+#[test]
+fn single_file() {
+    let tempdir = tempdir().unwrap();
+
+    dylint_internal::cargo::build(&format!("`{LIB_NAME}`"), false)
+        .sanitize_environment()
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/testing/early_lint"))
+        .args([
+            "--target-dir",
+            &tempdir.path().join("target").to_string_lossy(),
+        ])
+        .success()
+        .unwrap();
+
+    let path = glob(
+        &tempdir
+            .path()
+            .join("target/debug")
+            .join(library_filename::compose(LIB_NAME, "*", None))
+            .to_string_lossy(),
+    )
+    .ok()
+    .as_mut()
+    .and_then(Iterator::next)
+    .unwrap()
+    .unwrap();
+
+    let (_, toolchain, _) = library_filename::parse_path(&path).unwrap();
+
+    let driver = dylint::driver_builder::get(&dylint::Dylint::default(), &toolchain).unwrap();
+
+    let lib_rs = tempdir.path().join("lib.rs");
+    write(&lib_rs, LIB_RS).unwrap();
+
+    let output = dylint_internal::driver(&toolchain, &driver)
+        .unwrap()
+        .args([
+            "--single-file",
+            &lib_rs.to_string_lossy(),
+            "--lib-path",
+            &path.to_string_lossy(),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{output:#?}");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("E0432"), "{stdout}");
+    assert!(stdout.contains("parenthesized expression"), "{stdout}");
+    assert!(
+        stdout.contains("1 error(s) caused by unresolved names were suppressed"),
+        "{stdout}"
+    );
+}
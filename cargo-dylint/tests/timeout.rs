@@ -0,0 +1,191 @@
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+mod common;
+use common::package_with_examples;
+
+const EXAMPLE: &str = "restriction/const_path_join";
+const LIB_NAME: &str = "const_path_join";
+
+// smoelius: The build script sleeps far longer than any test should take. If the timeout fails to
+// kill it, the test hangs rather than passing.
+const BUILD_RS: &str = r#"
+fn main() {
+    std::thread::sleep(std::time::Duration::from_secs(600));
+}
+"#;
+
+// smoelius: The proc macro sleeps while the driver expands it, i.e., after the per-crate timeout's
+// clock has started.
+const SLEEP_LIB_RS: &str = r#"
+#[proc_macro]
+pub fn sleep(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    std::thread::sleep(std::time::Duration::from_secs(600));
+    proc_macro::TokenStream::new()
+}
+"#;
+
+const SLEEP_CARGO_TOML: &str = r#"
+[package]
+name = "sleep"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+proc-macro = true
+"#;
+
+const MAIN_RS_WITH_SLEEP: &str = r"
+sleep::sleep!();
+
+fn main() {}
+";
+
+#[test]
+fn timeout() {
+    let tempdir = package_with_build_rs().unwrap();
+
+    let start = Instant::now();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--timeout", "5"])
+        .assert()
+        .code(dylint::TIMEOUT_EXIT_CODE)
+        .stderr(
+            predicate::str::is_match(format!(
+                r"Timed out after 5s checking with toolchain `[^`]*` and library `{LIB_NAME}`"
+            ))
+            .unwrap(),
+        );
+
+    assert!(start.elapsed() < Duration::from_secs(300));
+}
+
+#[test]
+fn timeout_keep_going() {
+    let tempdir = package_with_build_rs().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--timeout",
+            "5",
+            "--keep-going",
+        ])
+        .assert()
+        .code(dylint::TIMEOUT_EXIT_CODE)
+        .stderr(predicate::str::contains("Timed out after 5s"));
+}
+
+#[test]
+fn per_crate_timeout() {
+    let tempdir = package_with_proc_macro().unwrap();
+
+    let start = Instant::now();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args(["dylint", "--lib", LIB_NAME, "--per-crate-timeout", "5"])
+        .assert()
+        .code(dylint::TIMEOUT_EXIT_CODE)
+        .stderr(
+            predicate::str::is_match(format!(
+                r"Timed out after 5s checking package `[^`]*` with library `{LIB_NAME}`"
+            ))
+            .unwrap(),
+        );
+
+    assert!(start.elapsed() < Duration::from_secs(300));
+}
+
+#[test]
+fn heartbeat() {
+    let tempdir = package_with_proc_macro().unwrap();
+
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .current_dir(&tempdir)
+        .args([
+            "dylint",
+            "--lib",
+            LIB_NAME,
+            "--per-crate-timeout",
+            "5",
+            "--heartbeat",
+            "1",
+        ])
+        .assert()
+        .code(dylint::TIMEOUT_EXIT_CODE)
+        .stderr(
+            predicate::str::is_match(format!(
+                r"Note: still checking package `[^`]*` with library `{LIB_NAME}`, [1-4]s elapsed"
+            ))
+            .unwrap(),
+        );
+}
+
+#[test]
+fn zero_timeout() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--per-crate-timeout", "0"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--per-crate-timeout` must be at least 1",
+        ));
+}
+
+#[test]
+fn timeout_conflicts_with_watch() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--timeout", "60", "--watch"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--timeout` cannot be used with `--watch`",
+        ));
+}
+
+fn package_with_build_rs() -> Result<TempDir> {
+    let tempdir = package_with_examples(&[EXAMPLE])?;
+
+    write(tempdir.path().join("build.rs"), BUILD_RS)?;
+
+    Ok(tempdir)
+}
+
+fn package_with_proc_macro() -> Result<TempDir> {
+    let tempdir = package_with_examples(&[EXAMPLE])?;
+
+    let manifest = tempdir.path().join("Cargo.toml");
+    let contents = read_to_string(&manifest)?;
+    write(
+        &manifest,
+        contents.replace(
+            "[dependencies]\n",
+            "[dependencies]\nsleep = { path = \"sleep\" }\n",
+        ),
+    )?;
+
+    create_dir_all(tempdir.path().join("sleep/src"))?;
+    write(tempdir.path().join("sleep/Cargo.toml"), SLEEP_CARGO_TOML)?;
+    write(tempdir.path().join("sleep/src/lib.rs"), SLEEP_LIB_RS)?;
+    write(tempdir.path().join("src/main.rs"), MAIN_RS_WITH_SLEEP)?;
+
+    Ok(tempdir)
+}
@@ -0,0 +1,92 @@
+use assert_cmd::prelude::*;
+use dylint_internal::{env, library_filename, rustup::SanitizeEnvironment, testing::new_template};
+use glob::glob;
+use predicates::prelude::*;
+use std::{
+    fs::{copy, write},
+    path::Path,
+};
+use tempfile::tempdir;
+
+// smoelius: No one has this toolchain installed.
+const UNINSTALLED: &str = "nightly-2000-01-01";
+
+#[test]
+fn only_uninstalled() {
+    let tempdir = tempdir().unwrap();
+
+    write(
+        tempdir
+            .path()
+            .join(library_filename::compose("fill_me_in", UNINSTALLED, None)),
+        "",
+    )
+    .unwrap();
+
+    list(tempdir.path(), &[]).failure().stderr(
+        predicate::str::contains(
+            "Found only libraries matching `fill_me_in` whose toolchains are not installed",
+        )
+        .and(predicate::str::contains(format!(
+            "(toolchain `{UNINSTALLED}`)"
+        )))
+        .and(predicate::str::contains("--allow-uninstalled-toolchain")),
+    );
+}
+
+#[test]
+fn installed_is_preferred() {
+    let template = tempdir().unwrap();
+
+    new_template(template.path()).unwrap();
+
+    dylint_internal::cargo::build(&format!("dylint-template in {:?}", template.path()), false)
+        .sanitize_environment()
+        .current_dir(&template)
+        .success()
+        .unwrap();
+
+    let path = glob(
+        &template
+            .path()
+            .join("target/debug")
+            .join(library_filename::compose("fill_me_in", "*", None))
+            .to_string_lossy(),
+    )
+    .ok()
+    .as_mut()
+    .and_then(Iterator::next)
+    .unwrap()
+    .unwrap();
+
+    let tempdir = tempdir().unwrap();
+
+    copy(&path, tempdir.path().join(path.file_name().unwrap())).unwrap();
+
+    write(
+        tempdir
+            .path()
+            .join(library_filename::compose("fill_me_in", UNINSTALLED, None)),
+        "",
+    )
+    .unwrap();
+
+    list(tempdir.path(), &[])
+        .success()
+        .stdout(predicate::str::contains("fill_me_in"));
+
+    list(tempdir.path(), &["--allow-uninstalled-toolchain"])
+        .failure()
+        .stderr(predicate::str::contains(
+            "Found multiple libraries matching `fill_me_in`",
+        ));
+}
+
+fn list(dylint_library_path: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .env(env::DYLINT_LIBRARY_PATH, dylint_library_path)
+        .args(["dylint", "list", "--lib", "fill_me_in", "--no-metadata"])
+        .args(args)
+        .assert()
+}
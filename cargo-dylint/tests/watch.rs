@@ -0,0 +1,24 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn fix_conflict() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--watch", "--fix"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains(
+            "`--watch` cannot be used with `--fix`",
+        ));
+}
+
+#[test]
+fn no_clear_requires_watch() {
+    std::process::Command::cargo_bin("cargo-dylint")
+        .unwrap()
+        .args(["dylint", "--all", "--no-clear"])
+        .assert()
+        .code(dylint::USAGE_ERROR_EXIT_CODE)
+        .stderr(predicate::str::contains("--watch"));
+}
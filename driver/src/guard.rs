@@ -0,0 +1,192 @@
+//! Panic isolation for late lint passes, disabled by setting `DYLINT_BROKEN_LINT_IS_FATAL`
+//!
+//! Each late lint pass a library registers is wrapped in a pass that catches panics in the wrapped
+//! pass's methods. A panic is reported as an error naming the library, the pass, and the panic
+//! message, and the pass is not run again. Compilation otherwise continues, so the other passes'
+//! warnings are still shown. Early lint passes are not wrapped.
+
+use dylint_internal::env;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, Once},
+};
+
+// smoelius: Module passes are created once per module. So a pass that panicked is recorded here,
+// to keep later instances from running and from reporting the same pass again.
+static DISABLED: Mutex<BTreeSet<(String, String)>> = Mutex::new(BTreeSet::new());
+
+thread_local! {
+    static CATCHING: Cell<bool> = Cell::new(false);
+    static MESSAGE: RefCell<Option<String>> = RefCell::new(None);
+}
+
+#[must_use]
+pub fn enabled() -> bool {
+    !env::enabled(env::DYLINT_BROKEN_LINT_IS_FATAL)
+}
+
+// smoelius: See the comment preceding `timing::wrap_late_passes`.
+#[rustversion::before(2023-06-28)]
+pub fn wrap_late_passes(
+    _lint_store: &mut rustc_lint::LintStore,
+    _late_start: usize,
+    _module_start: usize,
+    _library: &str,
+) {
+}
+
+/// Wraps the late lint passes registered since `late_start` and the late module passes registered
+/// since `module_start`
+#[rustversion::since(2023-06-28)]
+pub fn wrap_late_passes(
+    lint_store: &mut rustc_lint::LintStore,
+    late_start: usize,
+    module_start: usize,
+    library: &str,
+) {
+    install_panic_hook();
+    for factory in lint_store.late_passes.split_off(late_start) {
+        let library = library.to_owned();
+        lint_store
+            .register_late_pass(move |tcx| Box::new(guarded::Guarded::new(&library, factory(tcx))));
+    }
+    for factory in lint_store.late_module_passes.split_off(module_start) {
+        let library = library.to_owned();
+        lint_store.register_late_mod_pass(move |tcx| {
+            Box::new(guarded::Guarded::new(&library, factory(tcx)))
+        });
+    }
+}
+
+#[rustversion::since(2023-06-28)]
+mod guarded {
+    use super::{catch, DISABLED};
+    use rustc_ast as ast;
+    use rustc_hir as hir;
+    use rustc_lint::{LateContext, LateLintPass, LintContext, LintPass};
+    use rustc_span::{def_id::LocalDefId, Span};
+
+    pub struct Guarded<'tcx> {
+        library: String,
+        pass: Box<dyn LateLintPass<'tcx> + 'tcx>,
+        disabled: bool,
+    }
+
+    impl<'tcx> Guarded<'tcx> {
+        pub fn new(library: &str, pass: Box<dyn LateLintPass<'tcx> + 'tcx>) -> Self {
+            let disabled = DISABLED.lock().map_or(false, |disabled| {
+                disabled.contains(&(library.to_owned(), pass.name().to_owned()))
+            });
+            Self {
+                library: library.to_owned(),
+                pass,
+                disabled,
+            }
+        }
+
+        fn disable(&mut self, cx: &LateContext<'tcx>, message: &str) {
+            self.disabled = true;
+            let newly_disabled = DISABLED.lock().map_or(true, |mut disabled| {
+                disabled.insert((self.library.clone(), self.pass.name().to_owned()))
+            });
+            if newly_disabled {
+                cx.sess().err(format!(
+                    "lint pass `{}` of library `{}` panicked and was disabled: {message}",
+                    self.pass.name(),
+                    self.library,
+                ));
+            }
+        }
+    }
+
+    impl LintPass for Guarded<'_> {
+        fn name(&self) -> &'static str {
+            self.pass.name()
+        }
+    }
+
+    macro_rules! guarded_methods {
+        ($_args:tt, [$($(#[$attr:meta])* fn $name:ident($($param:ident: $arg:ty),*);)*]) => {
+            $(
+                fn $name(&mut self, cx: &LateContext<'tcx>, $($param: $arg),*) {
+                    if self.disabled {
+                        return;
+                    }
+                    let pass = &mut self.pass;
+                    if let Err(message) = catch(|| pass.$name(cx, $($param),*)) {
+                        self.disable(cx, &message);
+                    }
+                }
+            )*
+        };
+    }
+
+    impl<'tcx> LateLintPass<'tcx> for Guarded<'tcx> {
+        rustc_lint::late_lint_methods!(guarded_methods, []);
+    }
+}
+
+/// Calls `f`, and returns the panic message if `f` panics
+#[rustversion::attr(before(2023-06-28), allow(dead_code))]
+fn catch(f: impl FnOnce()) -> Result<(), String> {
+    CATCHING.with(|catching| catching.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(false));
+    result.map_err(|payload| {
+        MESSAGE
+            .with(|message| message.borrow_mut().take())
+            .unwrap_or_else(|| payload_message(&*payload))
+    })
+}
+
+// smoelius: The panic hook runs before the panic is caught. So, for a caught panic, the hook
+// records the message and location instead of printing them. Other panics are passed to the
+// previous hook.
+#[rustversion::attr(before(2023-06-28), allow(dead_code))]
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) {
+                let payload = payload_message(info.payload());
+                let message = match info.location() {
+                    Some(location) => format!("{payload} (at {location})"),
+                    None => payload,
+                };
+                MESSAGE.with(|cell| *cell.borrow_mut() = Some(message));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::panic, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn catch_returns_panic_message() {
+        install_panic_hook();
+        assert_eq!(Ok(()), catch(|| {}));
+        let message = catch(|| panic!("oops: {}", 42)).unwrap_err();
+        assert!(message.starts_with("oops: 42 (at "), "{message}");
+        assert!(message.contains("guard.rs"), "{message}");
+    }
+}
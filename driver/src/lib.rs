@@ -10,18 +10,35 @@ extern crate rustc_lint;
 extern crate rustc_session;
 extern crate rustc_span;
 
-use anyhow::{bail, ensure, Result};
-use dylint_internal::{env, parse_path_filename, rustup::is_rustc};
+use anyhow::{bail, ensure, Context, Result};
+use dylint_internal::{env, lib_paths, library_filename, rustup::is_rustc};
+use serde_json::{Map, Value};
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     ffi::{CString, OsStr},
     path::{Path, PathBuf},
 };
 
+mod guard;
+
+mod list;
+
+mod load_error;
+
+mod single_file;
+
+mod timing;
+
+mod watchdog;
+
+mod wrapper;
+
 pub const DYLINT_VERSION: &str = "0.1.0";
 
 type DylintVersionFunc = unsafe fn() -> *mut std::os::raw::c_char;
 
+type DylintMetadataFunc = unsafe fn() -> *mut std::os::raw::c_char;
+
 type RegisterLintsFunc =
     unsafe fn(sess: &rustc_session::Session, store: &mut rustc_lint::LintStore);
 
@@ -49,43 +66,84 @@ impl From<&rustc_lint::Lint> for Lint {
 }
 
 impl LoadedLibrary {
-    fn register_lints(
-        &self,
-        sess: &rustc_session::Session,
-        lint_store: &mut rustc_lint::LintStore,
-    ) {
-        (|| unsafe {
+    /// Returns the version returned by the library's `dylint_version` function
+    fn dylint_version(&self) -> Result<String> {
+        unsafe {
             if let Ok(func) = self.lib.get::<DylintVersionFunc>(b"dylint_version") {
-                let dylint_version = CString::from_raw(func()).into_string()?;
-                ensure!(
-                    dylint_version == DYLINT_VERSION,
-                    "`{}` has dylint version `{}`, but `{}` was expected",
-                    self.path.to_string_lossy(),
-                    dylint_version,
-                    DYLINT_VERSION
-                );
+                CString::from_raw(func()).into_string().map_err(Into::into)
             } else {
                 bail!(
                     "could not find `dylint_version` in `{}`",
                     self.path.to_string_lossy()
                 );
             }
+        }
+    }
+
+    /// Checks the library's `dylint_version` and returns its `register_lints` function
+    fn register_lints_func(&self) -> Result<RegisterLintsFunc> {
+        let dylint_version = self.dylint_version()?;
+        ensure!(
+            dylint_version == DYLINT_VERSION,
+            "`{}` has dylint version `{}`, but `{}` was expected",
+            self.path.to_string_lossy(),
+            dylint_version,
+            DYLINT_VERSION
+        );
+        unsafe {
             if let Ok(func) = self.lib.get::<RegisterLintsFunc>(b"register_lints") {
-                func(sess, lint_store);
+                Ok(*func)
             } else {
                 bail!(
                     "could not find `register_lints` in `{}`",
                     self.path.to_string_lossy()
                 );
             }
-            Ok(())
-        })()
-        .unwrap_or_else(|err| {
-            sess.err(err.to_string());
-        });
+        }
+    }
+
+    // smoelius: Libraries built with older versions of `dylint_linting` do not export
+    // `dylint_metadata`. Their lints are listed without metadata.
+    fn metadata(&self) -> Vec<(String, Map<String, Value>)> {
+        let json = unsafe {
+            let Ok(func) = self.lib.get::<DylintMetadataFunc>(b"dylint_metadata") else {
+                return Vec::new();
+            };
+            CString::from_raw(func()).into_string().unwrap_or_default()
+        };
+        serde_json::from_str::<Vec<Map<String, Value>>>(&json)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|mut metadata| {
+                let name = metadata.remove("name")?.as_str()?.to_lowercase();
+                Some((name, metadata))
+            })
+            .collect()
     }
 }
 
+/// Returns the lints that `register_lints` declares. The lints are registered in a scratch
+/// `LintStore`, not the one the compiler uses.
+fn lints(sess: &rustc_session::Session, register_lints: RegisterLintsFunc) -> Vec<Lint> {
+    let mut lint_store = rustc_lint::LintStore::new();
+    unsafe {
+        register_lints(sess, &mut lint_store);
+    }
+    lint_store
+        .get_lints()
+        .iter()
+        .map(|&lint| lint.into())
+        .collect()
+}
+
+/// Returns the lowercase names of the lints that `register_lints` declares
+fn lint_names(sess: &rustc_session::Session, register_lints: RegisterLintsFunc) -> Vec<String> {
+    lints(sess, register_lints)
+        .into_iter()
+        .map(|lint| lint.name.to_lowercase())
+        .collect()
+}
+
 struct Callbacks {
     loaded_libs: Vec<LoadedLibrary>,
 }
@@ -99,14 +157,29 @@ struct Callbacks {
 impl Callbacks {
     // smoelius: Load the libraries when `Callbacks` is created and not later (e.g., in `config`)
     // to ensure that the libraries live long enough.
-    fn new(paths: Vec<PathBuf>) -> Self {
+    // smoelius: A library is loaded from its run-private copy, if there is one, so that a rebuild
+    // of the library during the run cannot be observed half-written. But the library is otherwise
+    // referred to by its original path, e.g., in dep-info and in error messages.
+    // smoelius: The libraries are loaded, and their lints registered, in the order of their paths.
+    // So the order in which lint passes run, and thus in which their diagnostics are emitted, does
+    // not depend on the order in which the paths were given.
+    fn new(paths: Vec<PathBuf>, copies: &[PathBuf]) -> Self {
+        let mut paths_and_load_paths = paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let load_path = copies.get(i).cloned().unwrap_or_else(|| path.clone());
+                (path, load_path)
+            })
+            .collect::<Vec<_>>();
+        paths_and_load_paths.sort();
         let mut loaded_libs = Vec::new();
-        for path in paths {
+        for (path, load_path) in paths_and_load_paths {
             unsafe {
                 // smoelius: `libloading` does not define `RTLD_NODELETE`.
                 #[cfg(unix)]
                 let result = libloading::os::unix::Library::open(
-                    Some(&path),
+                    Some(&load_path),
                     libloading::os::unix::RTLD_LAZY
                         | libloading::os::unix::RTLD_LOCAL
                         | libc::RTLD_NODELETE,
@@ -114,7 +187,7 @@ impl Callbacks {
                 .map(Into::into);
 
                 #[cfg(not(unix))]
-                let result = libloading::Library::new(&path);
+                let result = libloading::Library::new(&load_path);
 
                 let lib = result.unwrap_or_else(|err| {
                     // smoelius: rust-lang/rust#111633 changed the type of `early_error`'s `msg`
@@ -122,11 +195,7 @@ impl Callbacks {
                     // smoelius: And rust-lang/rust#111748 made it that `msg` is borrowed for
                     // `'static`. Since the program is about to exit, it's probably fine to leak the
                     // string.
-                    let msg = format!(
-                        "could not load library `{}`: {}",
-                        path.to_string_lossy(),
-                        err
-                    );
+                    let msg = load_error::explain(&path, &load_path, &err.to_string());
                     early_error(msg);
                 });
 
@@ -148,6 +217,14 @@ fn early_error(msg: String) -> ! {
 #[rustversion::since(2023-06-28)]
 extern crate rustc_errors;
 
+// smoelius: `rustc_ast` and `rustc_hir` are used by the expansions of `late_lint_methods` in
+// `guard.rs` and `timing.rs`.
+#[rustversion::since(2023-06-28)]
+extern crate rustc_ast;
+
+#[rustversion::since(2023-06-28)]
+extern crate rustc_hir;
+
 #[rustversion::since(2023-06-28)]
 fn early_error(msg: impl Into<rustc_errors::DiagnosticMessage>) -> ! {
     let handler =
@@ -200,6 +277,23 @@ impl rustc_driver::Callbacks for Callbacks {
                     before.insert(lint.into());
                 });
             }
+            // smoelius: Cargo does not know that the driver reads `DYLINT_MANIFEST_LINTS`.
+            // Recording the variable in the dep-info file causes Cargo to recheck a
+            // crate when the levels in a `[lints.dylint]` table change.
+            if let Ok(value) = env::var(env::DYLINT_MANIFEST_LINTS) {
+                sess.parse_sess.env_depinfo.lock().insert((
+                    rustc_span::Symbol::intern(env::DYLINT_MANIFEST_LINTS),
+                    Some(rustc_span::Symbol::intern(&value)),
+                ));
+            }
+            let timings = timing::enabled();
+            let guard_enabled = guard::enabled();
+            // smoelius: `LintStore::register_lints` panics if a lint with the same name was already
+            // registered, without saying which libraries declare it. So when more than one library
+            // is loaded, each library's lints are first registered in a scratch store. A library
+            // that declares an already-declared lint is reported and not registered. Note that this
+            // means each library's `register_lints` function is called twice.
+            let mut declarers = BTreeMap::<String, &Path>::new();
             for loaded_lib in &loaded_libs {
                 if let Some(path) = loaded_lib.path.to_str() {
                     sess.parse_sess
@@ -207,14 +301,60 @@ impl rustc_driver::Callbacks for Callbacks {
                         .lock()
                         .insert(rustc_span::Symbol::intern(path));
                 }
-                loaded_lib.register_lints(sess, lint_store);
+                let register_lints = match loaded_lib.register_lints_func() {
+                    Ok(register_lints) => register_lints,
+                    Err(err) => {
+                        sess.err(err.to_string());
+                        continue;
+                    }
+                };
+                if loaded_libs.len() > 1 {
+                    let lint_names = lint_names(sess, register_lints);
+                    if let Some((name, declarer)) = lint_names.iter().find_map(|name| {
+                        declarers
+                            .get(name)
+                            .map(|declarer| (name, declarer.to_string_lossy()))
+                    }) {
+                        sess.err(format!(
+                            "lint `{name}` is declared by both `{declarer}` and `{}`",
+                            loaded_lib.path.to_string_lossy()
+                        ));
+                        continue;
+                    }
+                    declarers.extend(
+                        lint_names
+                            .into_iter()
+                            .map(|name| (name, loaded_lib.path.as_path())),
+                    );
+                }
+                let late_start = lint_store.late_passes.len();
+                let module_start = lint_store.late_module_passes.len();
+                unsafe {
+                    register_lints(sess, lint_store);
+                }
+                let library = library_filename::parse_path(&loaded_lib.path)
+                    .map_or_else(|| "<unknown>".to_owned(), |(name, _, _)| name);
+                if timings {
+                    timing::wrap_late_passes(lint_store, late_start, module_start, &library);
+                }
+                if guard_enabled {
+                    guard::wrap_late_passes(lint_store, late_start, module_start, &library);
+                }
             }
             if list_enabled() {
                 let mut after = BTreeSet::<Lint>::new();
                 lint_store.get_lints().iter().for_each(|&lint| {
                     after.insert(lint.into());
                 });
-                list_lints(&before, &after);
+                if list_json_enabled() {
+                    let metadata = loaded_libs
+                        .iter()
+                        .flat_map(LoadedLibrary::metadata)
+                        .collect();
+                    list_lints_json(&before, &after, &metadata);
+                } else {
+                    list_lints(&before, &after);
+                }
                 std::process::exit(0);
             }
         }));
@@ -230,24 +370,35 @@ fn list_enabled() -> bool {
     env::var(env::DYLINT_LIST).map_or(false, |value| value != "0")
 }
 
+// smoelius: `DYLINT_LIST=json` asks for the lints as a JSON array, including any metadata the
+// libraries export.
+#[must_use]
+fn list_json_enabled() -> bool {
+    env::var(env::DYLINT_LIST).map_or(false, |value| value == "json")
+}
+
 fn list_lints(before: &BTreeSet<Lint>, after: &BTreeSet<Lint>) {
     let difference: Vec<Lint> = after.difference(before).cloned().collect();
 
-    let name_width = difference
+    print_lints(&difference, "    ");
+}
+
+fn print_lints(lints: &[Lint], indent: &str) {
+    let name_width = lints
         .iter()
         .map(|lint| lint.name.len())
         .max()
         .unwrap_or_default();
 
-    let level_width = difference
+    let level_width = lints
         .iter()
         .map(|lint| lint.level.as_str().len())
         .max()
         .unwrap_or_default();
 
-    for Lint { name, level, desc } in difference {
+    for Lint { name, level, desc } in lints {
         println!(
-            "    {:<name_width$}    {:<level_width$}    {}",
+            "{indent}{:<name_width$}    {:<level_width$}    {}",
             name.to_lowercase(),
             level.as_str(),
             desc,
@@ -257,31 +408,78 @@ fn list_lints(before: &BTreeSet<Lint>, after: &BTreeSet<Lint>) {
     }
 }
 
+fn list_lints_json(
+    before: &BTreeSet<Lint>,
+    after: &BTreeSet<Lint>,
+    metadata: &BTreeMap<String, Map<String, Value>>,
+) {
+    let lints = after
+        .difference(before)
+        .map(|lint| lint_json(lint, metadata))
+        .collect();
+
+    println!("{}", Value::Array(lints));
+}
+
+fn lint_json(
+    Lint { name, level, desc }: &Lint,
+    metadata: &BTreeMap<String, Map<String, Value>>,
+) -> Value {
+    let name = name.to_lowercase();
+    let mut lint = Map::new();
+    lint.insert("name".to_owned(), Value::from(name.clone()));
+    lint.insert("level".to_owned(), Value::from(level.as_str()));
+    lint.insert("desc".to_owned(), Value::from(*desc));
+    if let Some(metadata) = metadata.get(&name) {
+        lint.extend(metadata.clone());
+    }
+    Value::Object(lint)
+}
+
 pub fn dylint_driver<T: AsRef<OsStr>>(args: &[T]) -> Result<()> {
     if args.len() <= 1 || args.iter().any(|arg| arg.as_ref() == "-V") {
         println!("{} {}", env!("RUSTUP_TOOLCHAIN"), env!("CARGO_PKG_VERSION"));
         return Ok(());
     }
 
+    if args[1].as_ref() == "--single-file" {
+        return single_file::run(&args[1..]);
+    }
+
+    if args[1].as_ref() == "--dylint-list" {
+        return list::run(&args[2..]);
+    }
+
+    if let Some(wrapper) = wrapper::inner() {
+        return wrapper::run(&wrapper, &args[1..]);
+    }
+
     run(&args[1..])
 }
 
 pub fn run<T: AsRef<OsStr>>(args: &[T]) -> Result<()> {
     let sysroot = sysroot().ok();
     let rustflags = rustflags();
-    let paths = paths();
+    let paths = paths()?;
 
-    let rustc_args = rustc_args(args, &sysroot, &rustflags, &paths)?;
+    let mut rustc_args = rustc_args(args, &sysroot, &manifest_lints(), &rustflags, &paths)?;
+    rustc_args.extend(force_warn_args(&rustc_args, &lint_deps()));
 
-    let mut callbacks = Callbacks::new(paths);
+    if !list_enabled() {
+        watchdog::start(&paths, &rustc_args)?;
+    }
+
+    let mut callbacks = Callbacks::new(paths, &copies()?);
 
     // smoelius: I am not sure that this should be here. `RUST_LOG=debug cargo test` fails because
     // of the log messages.
     log::debug!("{:?}", rustc_args);
 
-    rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks)
-        .run()
-        .map_err(|_| std::process::exit(1))
+    let result = rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run();
+
+    timing::write_timings()?;
+
+    result.map_err(|_| std::process::exit(1))
 }
 
 fn sysroot() -> Result<PathBuf> {
@@ -299,17 +497,74 @@ fn rustflags() -> Vec<String> {
     )
 }
 
-fn paths() -> Vec<PathBuf> {
+// smoelius: `DYLINT_LIB_PATHS` is encoded so that any path can be passed, including one that is
+// not valid UTF-8. `DYLINT_LIBS`, a JSON array of paths, is the format used before
+// `DYLINT_LIB_PATHS` was introduced, and is still accepted if `DYLINT_LIB_PATHS` is not set.
+fn paths() -> Result<Vec<PathBuf>> {
+    if let Ok(lib_paths) = env::var(env::DYLINT_LIB_PATHS) {
+        return lib_paths::decode(&lib_paths)
+            .with_context(|| format!("could not decode `{}`", env::DYLINT_LIB_PATHS));
+    }
+    let Ok(dylint_libs) = env::var(env::DYLINT_LIBS) else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_str(&dylint_libs)
+        .with_context(|| format!("could not parse `{}`", env::DYLINT_LIBS))
+}
+
+fn copies() -> Result<Vec<PathBuf>> {
+    let Ok(dylint_lib_copies) = env::var(env::DYLINT_LIB_COPIES) else {
+        return Ok(Vec::new());
+    };
+    lib_paths::decode(&dylint_lib_copies)
+        .with_context(|| format!("could not decode `{}`", env::DYLINT_LIB_COPIES))
+}
+
+fn lint_deps() -> Vec<String> {
     (|| -> Result<_> {
-        let dylint_libs = env::var(env::DYLINT_LIBS)?;
-        serde_json::from_str(&dylint_libs).map_err(Into::into)
+        let lint_deps = env::var(env::DYLINT_LINT_DEPS)?;
+        serde_json::from_str(&lint_deps).map_err(Into::into)
     })()
     .unwrap_or_default()
 }
 
+// smoelius: `DYLINT_MANIFEST_LINTS` maps each workspace member's manifest directory to the flags
+// that implement its `[lints.dylint]` table. The crate being compiled is identified by
+// `CARGO_MANIFEST_DIR`.
+fn manifest_lints() -> Vec<String> {
+    (|| -> Result<_> {
+        let manifest_lints = env::var(env::DYLINT_MANIFEST_LINTS)?;
+        let mut manifest_lints =
+            serde_json::from_str::<BTreeMap<String, Vec<String>>>(&manifest_lints)?;
+        let manifest_dir = env::var(env::CARGO_MANIFEST_DIR)?;
+        Ok(manifest_lints.remove(&manifest_dir).unwrap_or_default())
+    })()
+    .unwrap_or_default()
+}
+
+// smoelius: Cargo passes `--cap-lints` when compiling a dependency, and the cap applies to the
+// lints Dylint loads as much as to `rustc`'s own. But the cap does not apply to force-warned lints.
+// So force-warning the lints named in `DYLINT_LINT_DEPS` makes them fire in the dependency, while
+// `rustc`'s own lints remain capped.
+fn force_warn_args<T: AsRef<str>>(rustc_args: &[String], lint_names: &[T]) -> Vec<String> {
+    if !rustc_args
+        .iter()
+        .any(|arg| arg == "--cap-lints" || arg.starts_with("--cap-lints="))
+    {
+        return Vec::new();
+    }
+    lint_names
+        .iter()
+        .map(|name| format!("--force-warn={}", name.as_ref()))
+        .collect()
+}
+
+// smoelius: The flags from `[lints.dylint]` tables come before the arguments, so that flags in
+// `RUSTFLAGS` (which Cargo includes in the arguments) and in `DYLINT_RUSTFLAGS` override them.
 fn rustc_args<T: AsRef<OsStr>, U: AsRef<str>, V: AsRef<Path>>(
     args: &[T],
     sysroot: &Option<PathBuf>,
+    manifest_lints: &[U],
     rustflags: &[U],
     paths: &[V],
 ) -> Result<Vec<String>> {
@@ -333,12 +588,17 @@ fn rustc_args<T: AsRef<OsStr>, U: AsRef<str>, V: AsRef<Path>>(
         ]);
     }
     for path in paths {
-        if let Some((name, _)) = parse_path_filename(path.as_ref()) {
+        if let Some((name, _, _)) = library_filename::parse_path(path.as_ref()) {
             rustc_args.push(format!(r#"--cfg=dylint_lib="{name}""#));
         } else {
             bail!("could not parse `{}`", path.as_ref().to_string_lossy());
         }
     }
+    rustc_args.extend(
+        manifest_lints
+            .iter()
+            .map(|manifest_lint| manifest_lint.as_ref().to_owned()),
+    );
     rustc_args.extend(args.map(|s| s.as_ref().to_string_lossy().to_string()));
     rustc_args.extend(
         rustflags
@@ -362,6 +622,7 @@ mod test {
                 &["--crate-name", "name"],
                 &None,
                 &[] as &[&str],
+                &[] as &[&str],
                 &[] as &[&Path]
             )
             .unwrap(),
@@ -376,6 +637,7 @@ mod test {
                 &["rustc", "--crate-name", "name"],
                 &None,
                 &[] as &[&str],
+                &[] as &[&str],
                 &[] as &[&Path]
             )
             .unwrap(),
@@ -383,6 +645,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn force_warn_only_when_capped() {
+        let to_strings = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let lint_names = ["fill_me_in"];
+        assert!(force_warn_args(&to_strings(&["rustc"]), &lint_names).is_empty());
+        for capped in [
+            to_strings(&["rustc", "--cap-lints", "allow"]),
+            to_strings(&["rustc", "--cap-lints=allow"]),
+        ] {
+            assert_eq!(
+                force_warn_args(&capped, &lint_names),
+                vec!["--force-warn=fill_me_in"]
+            );
+        }
+    }
+
+    #[test]
+    fn manifest_lints_precede_args() {
+        assert_eq!(
+            rustc_args(
+                &["rustc", "--crate-name", "name", "-A", "lint"],
+                &None,
+                &["--deny=lint"],
+                &["-W", "lint"],
+                &[] as &[&Path]
+            )
+            .unwrap(),
+            vec![
+                "rustc",
+                "--deny=lint",
+                "--crate-name",
+                "name",
+                "-A",
+                "lint",
+                "-W",
+                "lint"
+            ]
+        );
+    }
+
     #[test]
     fn qualified_rustc() {
         assert_eq!(
@@ -390,6 +692,7 @@ mod test {
                 &["/bin/rustc", "--crate-name", "name"],
                 &None,
                 &[] as &[&str],
+                &[] as &[&str],
                 &[] as &[&Path]
             )
             .unwrap(),
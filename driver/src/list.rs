@@ -0,0 +1,163 @@
+use crate::{
+    copies, lint_json, lints, paths, print_lints, rustc_args, sysroot, Callbacks, Lint,
+    LoadedLibrary,
+};
+use anyhow::{bail, Result};
+use dylint_internal::library_filename;
+use serde_json::{Map, Value};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+// smoelius: `--dylint-list` mode is meant for answering "why didn't my lint fire?" The driver loads
+// the libraries named by `DYLINT_LIB_PATHS` (or `DYLINT_LIBS`) and prints what it found in each:
+// the library's path, the version returned by its `dylint_version` function, its toolchain, and
+// its lints. Each library's `register_lints` function is called with a scratch `LintStore`. No
+// crate is compiled; `rustc` is run only to obtain a `Session`.
+//
+// The CLI's `list` command uses this mode's JSON output.
+
+struct Listing {
+    path: PathBuf,
+    dylint_version: Option<String>,
+    toolchain: Option<String>,
+    lints: Vec<Lint>,
+    metadata: Vec<(String, Map<String, Value>)>,
+    error: Option<String>,
+}
+
+struct ListCallbacks {
+    loaded_libs: Vec<LoadedLibrary>,
+    json: bool,
+}
+
+/// Runs the driver in `--dylint-list` mode. `args` are the arguments following `--dylint-list`,
+/// i.e., nothing or `--json`.
+pub fn run<T: AsRef<OsStr>>(args: &[T]) -> Result<()> {
+    let json = match args {
+        [] => false,
+        [arg] if arg.as_ref() == "--json" => true,
+        _ => bail!("`--dylint-list` accepts only `--json`"),
+    };
+
+    let sysroot = sysroot().ok();
+    let paths = paths()?;
+
+    // smoelius: The paths are not passed to `rustc_args`, which would fail on a path that does not
+    // parse as a library's. A library with such a path is listed with an unknown toolchain.
+    let rustc_args = rustc_args(
+        &["rustc", "-W", "help"],
+        &sysroot,
+        &[] as &[&str],
+        &[] as &[&str],
+        &[] as &[&Path],
+    )?;
+
+    let Callbacks { loaded_libs } = Callbacks::new(paths, &copies()?);
+
+    let mut callbacks = ListCallbacks { loaded_libs, json };
+
+    rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks)
+        .run()
+        .map_err(|_| std::process::exit(1))
+}
+
+impl rustc_driver::Callbacks for ListCallbacks {
+    fn config(&mut self, config: &mut rustc_interface::Config) {
+        let loaded_libs = self.loaded_libs.split_off(0);
+        let json = self.json;
+        // smoelius: The listings are printed as soon as the libraries' lints have been registered,
+        // and the driver then exits, as in `DYLINT_LIST` mode.
+        config.register_lints = Some(Box::new(move |sess, _| {
+            let listings = loaded_libs
+                .iter()
+                .map(|loaded_lib| listing(sess, loaded_lib))
+                .collect::<Vec<_>>();
+            if json {
+                println!(
+                    "{}",
+                    Value::Array(listings.iter().map(Listing::to_json).collect())
+                );
+            } else {
+                for listing in &listings {
+                    listing.print();
+                }
+            }
+            std::process::exit(0);
+        }));
+    }
+}
+
+fn listing(sess: &rustc_session::Session, loaded_lib: &LoadedLibrary) -> Listing {
+    let dylint_version = loaded_lib.dylint_version().ok();
+    let toolchain =
+        library_filename::parse_path(&loaded_lib.path).map(|(_, toolchain, _)| toolchain);
+    let (lints, metadata, error) = match loaded_lib.register_lints_func() {
+        Ok(register_lints) => {
+            let mut lints = lints(sess, register_lints);
+            lints.sort();
+            (lints, loaded_lib.metadata(), None)
+        }
+        Err(err) => (Vec::new(), Vec::new(), Some(err.to_string())),
+    };
+    Listing {
+        path: loaded_lib.path.clone(),
+        dylint_version,
+        toolchain,
+        lints,
+        metadata,
+        error,
+    }
+}
+
+impl Listing {
+    fn to_json(&self) -> Value {
+        let metadata = self.metadata.iter().cloned().collect();
+        let mut listing = Map::new();
+        listing.insert(
+            "path".to_owned(),
+            Value::from(self.path.to_string_lossy().to_string()),
+        );
+        listing.insert(
+            "dylint_version".to_owned(),
+            self.dylint_version.clone().map_or(Value::Null, Value::from),
+        );
+        listing.insert(
+            "toolchain".to_owned(),
+            self.toolchain.clone().map_or(Value::Null, Value::from),
+        );
+        listing.insert(
+            "lints".to_owned(),
+            Value::Array(
+                self.lints
+                    .iter()
+                    .map(|lint| lint_json(lint, &metadata))
+                    .collect(),
+            ),
+        );
+        if let Some(error) = &self.error {
+            listing.insert("error".to_owned(), Value::from(error.clone()));
+        }
+        Value::Object(listing)
+    }
+
+    fn print(&self) {
+        println!("{}", self.path.to_string_lossy());
+        println!(
+            "    dylint version: {}",
+            self.dylint_version.as_deref().unwrap_or("<unknown>")
+        );
+        println!(
+            "    toolchain: {}",
+            self.toolchain.as_deref().unwrap_or("<unknown>")
+        );
+        if let Some(error) = &self.error {
+            println!("    error: {error}");
+        } else {
+            println!("    lints:");
+            print_lints(&self.lints, "        ");
+        }
+        println!();
+    }
+}
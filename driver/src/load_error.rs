@@ -0,0 +1,266 @@
+//! Explanations of why a library could not be loaded
+//!
+//! The error that `libloading` returns is just what the dynamic loader reported, e.g., "cannot open
+//! shared object file" or "undefined symbol: _ZN...". The explanation adds the path that was tried,
+//! whether the file exists, the library's and the driver's toolchains, and for an undefined symbol,
+//! the demangled symbol name.
+
+use dylint_internal::library_filename;
+use std::{
+    env::current_dir,
+    fs::{metadata, Metadata},
+    path::{Path, PathBuf},
+};
+
+// smoelius: glibc reports "undefined symbol: SYMBOL", and macOS's dyld reports "Symbol not found:
+// SYMBOL".
+const UNDEFINED_SYMBOL_PREFIXES: &[&str] = &["undefined symbol: ", "Symbol not found: "];
+
+/// Returns the message to report when the library at `path` could not be loaded from `load_path`
+/// (e.g., a run-private copy of `path`). `error` is the error `libloading` returned.
+pub fn explain(path: &Path, load_path: &Path, error: &str) -> String {
+    let mut msg = format!(
+        "could not load library `{}`: {}",
+        path.to_string_lossy(),
+        error
+    );
+
+    let load_path = absolute(load_path);
+    match metadata(&load_path) {
+        Ok(metadata) => note(
+            &mut msg,
+            &format!(
+                "tried to load `{}`, which exists and {}",
+                load_path.to_string_lossy(),
+                permissions(&metadata)
+            ),
+        ),
+        Err(error) => note(
+            &mut msg,
+            &format!(
+                "tried to load `{}`, which could not be accessed: {}",
+                load_path.to_string_lossy(),
+                error
+            ),
+        ),
+    }
+
+    let driver_toolchain = env!("RUSTUP_TOOLCHAIN");
+    let filename = library_filename::parse_path(path);
+    let toolchain_matches = if let Some((_, toolchain, target)) = filename {
+        note(
+            &mut msg,
+            &format!(
+                "the library's filename says it was built with `{toolchain}`; the driver was built \
+                 with `{driver_toolchain}`"
+            ),
+        );
+        if let Some(target) = target {
+            note(
+                &mut msg,
+                &format!("the library's filename says it was built for `{target}`"),
+            );
+        }
+        Some(toolchain.starts_with(driver_toolchain))
+    } else {
+        note(
+            &mut msg,
+            &format!(
+                "the library's filename does not name a toolchain; the driver was built with \
+                 `{driver_toolchain}`"
+            ),
+        );
+        None
+    };
+
+    if let Some(symbol) = undefined_symbol(error) {
+        note(
+            &mut msg,
+            &format!(
+                "the undefined symbol is `{:#}`",
+                rustc_demangle::demangle(symbol)
+            ),
+        );
+        if toolchain_matches != Some(true) {
+            help(
+                &mut msg,
+                "the library and the driver were probably built with different toolchains; \
+                 rebuild the library with the driver's toolchain",
+            );
+        }
+    }
+
+    msg
+}
+
+fn note(msg: &mut String, note: &str) {
+    msg.push_str("\n  = note: ");
+    msg.push_str(note);
+}
+
+fn help(msg: &mut String, help: &str) {
+    msg.push_str("\n  = help: ");
+    msg.push_str(help);
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    format!(
+        "has permissions {:o}",
+        metadata.permissions().mode() & 0o7777
+    )
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "is read-only".to_owned()
+    } else {
+        "is writable".to_owned()
+    }
+}
+
+fn undefined_symbol(error: &str) -> Option<&str> {
+    UNDEFINED_SYMBOL_PREFIXES.iter().find_map(|prefix| {
+        let (_, rest) = error.split_once(prefix)?;
+        rest.split(|c: char| c.is_whitespace() || c == ',' || c == ')')
+            .next()
+            .filter(|symbol| !symbol.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    // smoelius: `libloading` must fail to load these fixtures for real. The undefined symbol case
+    // requires a library built against a different toolchain, so only the loader's message is
+    // simulated.
+    fn load(path: &Path) -> String {
+        unsafe { libloading::Library::new(path) }
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn missing_file() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(library_filename::compose(
+            "missing",
+            env!("RUSTUP_TOOLCHAIN"),
+            None,
+        ));
+
+        let msg = explain(&path, &path, &load(&path));
+
+        assert!(
+            msg.contains(&format!(
+                "tried to load `{}`, which could not be accessed",
+                path.to_string_lossy()
+            )),
+            "{msg}"
+        );
+        assert!(
+            msg.contains(&format!(
+                "the library's filename says it was built with `{}`",
+                env!("RUSTUP_TOOLCHAIN")
+            )),
+            "{msg}"
+        );
+        assert!(!msg.contains("help:"), "{msg}");
+    }
+
+    #[test]
+    fn invalid_file() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(library_filename::compose(
+            "invalid",
+            "nightly-2021-01-01-x86_64-unknown-linux-gnu",
+            None,
+        ));
+        write(&path, "not a library").unwrap();
+
+        let msg = explain(&path, &path, &load(&path));
+
+        assert!(msg.contains("which exists and "), "{msg}");
+        assert!(
+            msg.contains(
+                "the library's filename says it was built with \
+                 `nightly-2021-01-01-x86_64-unknown-linux-gnu`"
+            ),
+            "{msg}"
+        );
+    }
+
+    #[test]
+    fn target() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(library_filename::compose(
+            "cross",
+            env!("RUSTUP_TOOLCHAIN"),
+            Some("aarch64-unknown-linux-gnu"),
+        ));
+        write(&path, "not a library").unwrap();
+
+        let msg = explain(&path, &path, &load(&path));
+
+        assert!(
+            msg.contains(
+                "the library's filename says it was built for `aarch64-unknown-linux-gnu`"
+            ),
+            "{msg}"
+        );
+    }
+
+    #[test]
+    fn undefined_symbol_is_demangled() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(library_filename::compose(
+            "stale",
+            "nightly-2021-01-01-x86_64-unknown-linux-gnu",
+            None,
+        ));
+        write(&path, "").unwrap();
+
+        let msg = explain(
+            &path,
+            &path,
+            &format!(
+                "{}: undefined symbol: _ZN12rustc_middle2ty7context6TyCtxt4span17h0123456789abcdefE",
+                path.to_string_lossy()
+            ),
+        );
+
+        assert!(
+            msg.contains("the undefined symbol is `rustc_middle::ty::context::TyCtxt::span`"),
+            "{msg}"
+        );
+        assert!(
+            msg.contains("probably built with different toolchains"),
+            "{msg}"
+        );
+    }
+
+    #[test]
+    fn macos_undefined_symbol() {
+        assert_eq!(
+            Some("__ZN4core3fmt5write17h0123456789abcdefE"),
+            undefined_symbol(
+                "dlopen(libfoo.dylib, 0x0005): Symbol not found: \
+                 __ZN4core3fmt5write17h0123456789abcdefE\n  Referenced from: libfoo.dylib"
+            )
+        );
+    }
+}
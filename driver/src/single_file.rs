@@ -0,0 +1,335 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use dylint_internal::{env, lib_paths};
+use serde_json::{json, Value};
+use std::{
+    ffi::OsStr,
+    fs::{create_dir_all, remove_dir_all},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+// smoelius: Single-file mode is meant for editor integration. The file is compiled on its own, as
+// the root of a library crate, without involving Cargo. So any names the file imports from other
+// modules or from external crates cannot be resolved. The errors that result are filtered out, and
+// a warning explaining why is emitted in their place.
+//
+// Note that `rustc` does not run late lint passes on a crate with errors. So if any errors are
+// filtered, only early (pre-expansion and early) lints will have run.
+
+// smoelius: Error codes associated with unresolved names.
+const RESOLUTION_ERROR_CODES: &[&str] = &[
+    "E0405", "E0412", "E0422", "E0425", "E0432", "E0433", "E0463", "E0531",
+];
+
+// smoelius: Messages of errors associated with unresolved names that have no error code.
+const RESOLUTION_ERROR_PREFIXES: &[&str] = &[
+    "cannot find attribute `",
+    "cannot find derive macro `",
+    "cannot find macro `",
+];
+
+const DEFAULT_EDITION: &str = "2021";
+
+#[derive(Debug, Eq, PartialEq)]
+struct Opts {
+    path: PathBuf,
+    lib_paths: Vec<PathBuf>,
+    edition: String,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Summary {
+    errors: usize,
+    filtered: usize,
+}
+
+/// Runs the driver in single-file mode. `args` are the arguments following the driver's name,
+/// i.e., `--single-file <path> [--lib-path <path>]... [--edition <edition>]`.
+pub fn run<T: AsRef<OsStr>>(args: &[T]) -> Result<()> {
+    let opts = parse_args(args)?;
+
+    let out_dir = std::env::temp_dir().join(format!("dylint-single-file-{}", std::process::id()));
+    create_dir_all(&out_dir)
+        .with_context(|| format!("could not create `{}`", out_dir.to_string_lossy()))?;
+
+    let result = compile(&opts, &out_dir);
+
+    remove_dir_all(&out_dir)
+        .with_context(|| format!("could not remove `{}`", out_dir.to_string_lossy()))?;
+
+    let (summary, success) = result?;
+
+    if summary.errors > 0 || (!success && summary.filtered == 0) {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn parse_args<T: AsRef<OsStr>>(args: &[T]) -> Result<Opts> {
+    let mut path = None;
+    let mut lib_paths = Vec::new();
+    let mut edition = None;
+
+    let mut args = args.iter().map(AsRef::as_ref);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("`{}` requires a value", arg.to_string_lossy()))
+        };
+        match arg.to_str() {
+            Some("--single-file") => {
+                ensure!(path.is_none(), "`--single-file` was given more than once");
+                path = Some(PathBuf::from(value()?));
+            }
+            Some("--lib-path") => {
+                lib_paths.push(PathBuf::from(value()?));
+            }
+            Some("--edition") => {
+                edition = Some(value()?.to_string_lossy().to_string());
+            }
+            _ => bail!("unexpected argument `{}`", arg.to_string_lossy()),
+        }
+    }
+
+    Ok(Opts {
+        path: path.ok_or_else(|| anyhow!("`--single-file` is required"))?,
+        lib_paths,
+        edition: edition.unwrap_or_else(|| DEFAULT_EDITION.to_owned()),
+    })
+}
+
+// smoelius: The driver invokes itself as a `rustc` wrapper so that it can intercept `rustc`'s
+// diagnostics.
+fn compile(opts: &Opts, out_dir: &Path) -> Result<(Summary, bool)> {
+    let current_exe =
+        std::env::current_exe().with_context(|| "could not get current executable")?;
+    let lib_paths = lib_paths::encode(&opts.lib_paths);
+
+    let mut child = Command::new(current_exe)
+        .args([
+            "rustc",
+            "--crate-name=dylint_single_file",
+            "--crate-type=lib",
+            "--emit=metadata",
+            "--error-format=json",
+            &format!("--edition={}", opts.edition),
+            "--out-dir",
+        ])
+        .arg(out_dir)
+        .arg(&opts.path)
+        .env(env::DYLINT_LIB_PATHS, lib_paths)
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| "could not spawn `rustc`")?;
+
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow!("could not get `rustc`'s stderr"))?;
+
+    let summary = filter_diagnostics(BufReader::new(stderr), std::io::stdout().lock())?;
+
+    let status = child
+        .wait()
+        .with_context(|| "could not get `rustc`'s exit status")?;
+
+    Ok((summary, status.success()))
+}
+
+fn filter_diagnostics(reader: impl BufRead, mut writer: impl Write) -> Result<Summary> {
+    let mut summary = Summary::default();
+
+    for line in reader.lines() {
+        let line = line.with_context(|| "could not read from `rustc`")?;
+
+        let Ok(diagnostic) = serde_json::from_str::<Value>(&line) else {
+            // smoelius: Not a diagnostic, e.g., an ICE message. Pass it along.
+            eprintln!("{line}");
+            continue;
+        };
+
+        let level = diagnostic.get("level").and_then(Value::as_str);
+        let code = diagnostic.pointer("/code/code").and_then(Value::as_str);
+        let message = diagnostic
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        if level == Some("error") && is_resolution_error(code, message) {
+            summary.filtered += 1;
+            continue;
+        }
+
+        // smoelius: The summary messages that `rustc` emits at the end (e.g., "aborting due to 2
+        // previous errors") would be wrong if any errors were filtered.
+        if (level == Some("error") && code.is_none() && message.starts_with("aborting due to"))
+            || level == Some("failure-note")
+        {
+            continue;
+        }
+
+        if level == Some("error") {
+            summary.errors += 1;
+        }
+
+        writeln!(writer, "{line}").with_context(|| "could not write diagnostic")?;
+    }
+
+    if summary.filtered > 0 {
+        let message = format!(
+            "{} error(s) caused by unresolved names were suppressed; lints requiring type \
+             information did not run",
+            summary.filtered
+        );
+        let warning = json!({
+            "$message_type": "diagnostic",
+            "message": message,
+            "code": null,
+            "level": "warning",
+            "spans": [],
+            "children": [],
+            "rendered": format!("warning: {message}\n\n"),
+        });
+        writeln!(writer, "{warning}").with_context(|| "could not write diagnostic")?;
+    }
+
+    Ok(summary)
+}
+
+fn is_resolution_error(code: Option<&str>, message: &str) -> bool {
+    code.map_or(false, |code| RESOLUTION_ERROR_CODES.contains(&code))
+        || RESOLUTION_ERROR_PREFIXES
+            .iter()
+            .any(|prefix| message.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn diagnostic(level: &str, code: Option<&str>, message: &str) -> String {
+        json!({
+            "$message_type": "diagnostic",
+            "message": message,
+            "code": code.map(|code| json!({ "code": code, "explanation": null })),
+            "level": level,
+            "spans": [],
+            "children": [],
+            "rendered": format!("{level}: {message}\n"),
+        })
+        .to_string()
+    }
+
+    fn filter(lines: &[String]) -> (Summary, Vec<Value>) {
+        let mut output = Vec::new();
+        let summary = filter_diagnostics(lines.join("\n").as_bytes(), &mut output).unwrap();
+        let diagnostics = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (summary, diagnostics)
+    }
+
+    #[test]
+    fn parse_args_defaults() {
+        assert_eq!(
+            Opts {
+                path: PathBuf::from("src/lib.rs"),
+                lib_paths: vec![],
+                edition: DEFAULT_EDITION.to_owned(),
+            },
+            parse_args(&["--single-file", "src/lib.rs"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_args_all() {
+        assert_eq!(
+            Opts {
+                path: PathBuf::from("src/lib.rs"),
+                lib_paths: vec![PathBuf::from("a"), PathBuf::from("b")],
+                edition: "2018".to_owned(),
+            },
+            parse_args(&[
+                "--lib-path",
+                "a",
+                "--single-file",
+                "src/lib.rs",
+                "--edition",
+                "2018",
+                "--lib-path",
+                "b"
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_args_errors() {
+        assert!(parse_args(&["--lib-path", "a"]).is_err());
+        assert!(parse_args(&["--single-file"]).is_err());
+        assert!(parse_args(&["--single-file", "a", "--single-file", "b"]).is_err());
+        assert!(parse_args(&["--single-file", "a", "--crate-type=bin"]).is_err());
+    }
+
+    #[test]
+    fn lint_warnings_pass_through() {
+        let (summary, diagnostics) = filter(&[diagnostic(
+            "warning",
+            Some("const_path_join"),
+            "path could be constructed from a string literal",
+        )]);
+        assert_eq!(Summary::default(), summary);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("const_path_join", diagnostics[0]["code"]["code"]);
+    }
+
+    #[test]
+    fn resolution_errors_are_filtered() {
+        let (summary, diagnostics) = filter(&[
+            diagnostic("error", Some("E0432"), "unresolved import `foo`"),
+            diagnostic("error", None, "cannot find macro `bar` in this scope"),
+            diagnostic("error", None, "aborting due to 2 previous errors"),
+            diagnostic(
+                "failure-note",
+                None,
+                "For more information about this error, try `rustc --explain E0432`.",
+            ),
+        ]);
+        assert_eq!(
+            Summary {
+                errors: 0,
+                filtered: 2
+            },
+            summary
+        );
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("warning", diagnostics[0]["level"]);
+        assert!(diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("2 error(s) caused by unresolved names were suppressed"));
+    }
+
+    #[test]
+    fn other_errors_are_kept() {
+        let (summary, diagnostics) = filter(&[
+            diagnostic("error", Some("E0432"), "unresolved import `foo`"),
+            diagnostic("error", Some("E0308"), "mismatched types"),
+        ]);
+        assert_eq!(
+            Summary {
+                errors: 1,
+                filtered: 1
+            },
+            summary
+        );
+        assert_eq!(2, diagnostics.len());
+        assert_eq!("E0308", diagnostics[0]["code"]["code"]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Per-lint-pass timing, enabled by setting `DYLINT_TIMINGS` to a directory
+//!
+//! Each late lint pass a library registers is wrapped in a pass that measures the time spent in
+//! the wrapped pass's methods. When the driver finishes, it writes one file to the
+//! directory, mapping `library::Pass` to seconds.
+
+use anyhow::{Context, Result};
+use dylint_internal::env;
+use std::{
+    collections::BTreeMap,
+    fs::write,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+static ELAPSED: Mutex<BTreeMap<String, Duration>> = Mutex::new(BTreeMap::new());
+
+#[must_use]
+pub fn enabled() -> bool {
+    env::var(env::DYLINT_TIMINGS).is_ok()
+}
+
+// smoelius: `late_lint_methods` took a lifetime argument in older toolchains. Rather than support
+// both forms, timing is supported only by toolchains for which the driver already requires other
+// recent APIs. With older toolchains, passes are not wrapped and no timings are written.
+#[rustversion::before(2023-06-28)]
+pub fn wrap_late_passes(
+    _lint_store: &mut rustc_lint::LintStore,
+    _late_start: usize,
+    _module_start: usize,
+    _library: &str,
+) {
+}
+
+/// Wraps the late lint passes registered since `late_start` and the late module passes registered
+/// since `module_start`
+#[rustversion::since(2023-06-28)]
+pub fn wrap_late_passes(
+    lint_store: &mut rustc_lint::LintStore,
+    late_start: usize,
+    module_start: usize,
+    library: &str,
+) {
+    for factory in lint_store.late_passes.split_off(late_start) {
+        let library = library.to_owned();
+        lint_store
+            .register_late_pass(move |tcx| Box::new(timed::Timed::new(&library, factory(tcx))));
+    }
+    for factory in lint_store.late_module_passes.split_off(module_start) {
+        let library = library.to_owned();
+        lint_store
+            .register_late_mod_pass(move |tcx| Box::new(timed::Timed::new(&library, factory(tcx))));
+    }
+}
+
+#[rustversion::since(2023-06-28)]
+mod timed {
+    use super::ELAPSED;
+    use rustc_ast as ast;
+    use rustc_hir as hir;
+    use rustc_lint::{LateContext, LateLintPass, LintPass};
+    use rustc_span::{def_id::LocalDefId, Span};
+    use std::time::{Duration, Instant};
+
+    pub struct Timed<'tcx> {
+        name: String,
+        pass: Box<dyn LateLintPass<'tcx> + 'tcx>,
+        elapsed: Duration,
+    }
+
+    impl<'tcx> Timed<'tcx> {
+        pub fn new(library: &str, pass: Box<dyn LateLintPass<'tcx> + 'tcx>) -> Self {
+            Self {
+                name: format!("{library}::{}", pass.name()),
+                pass,
+                elapsed: Duration::ZERO,
+            }
+        }
+    }
+
+    impl LintPass for Timed<'_> {
+        fn name(&self) -> &'static str {
+            self.pass.name()
+        }
+    }
+
+    macro_rules! timed_methods {
+        ($_args:tt, [$($(#[$attr:meta])* fn $name:ident($($param:ident: $arg:ty),*);)*]) => {
+            $(
+                fn $name(&mut self, cx: &LateContext<'tcx>, $($param: $arg),*) {
+                    let start = Instant::now();
+                    self.pass.$name(cx, $($param),*);
+                    self.elapsed += start.elapsed();
+                }
+            )*
+        };
+    }
+
+    impl<'tcx> LateLintPass<'tcx> for Timed<'tcx> {
+        rustc_lint::late_lint_methods!(timed_methods, []);
+    }
+
+    // smoelius: Passes are created and dropped more than once per crate (e.g., module passes), so
+    // the times are accumulated.
+    impl Drop for Timed<'_> {
+        fn drop(&mut self) {
+            if let Ok(mut elapsed) = ELAPSED.lock() {
+                *elapsed.entry(std::mem::take(&mut self.name)).or_default() += self.elapsed;
+            }
+        }
+    }
+}
+
+/// Writes the accumulated timings to the directory named by `DYLINT_TIMINGS`, if set
+pub fn write_timings() -> Result<()> {
+    let dir = match env::var(env::DYLINT_TIMINGS) {
+        Ok(dir) => dir,
+        Err(_) => return Ok(()),
+    };
+
+    let seconds = ELAPSED
+        .lock()
+        .map(|elapsed| {
+            elapsed
+                .iter()
+                .map(|(name, elapsed)| (name.clone(), elapsed.as_secs_f64()))
+                .collect::<BTreeMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    if seconds.is_empty() {
+        return Ok(());
+    }
+
+    // smoelius: Several driver processes can write to the directory in one run, and a process id
+    // can be reused within a run. So the filename includes a timestamp too.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let path = Path::new(&dir).join(format!("{}-{nanos}.json", std::process::id()));
+
+    write(&path, serde_json::to_string(&seconds)?)
+        .with_context(|| format!("Could not write to `{}`", path.to_string_lossy()))
+}
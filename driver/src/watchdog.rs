@@ -0,0 +1,166 @@
+//! Per-crate timeout and heartbeat, enabled by setting `DYLINT_PER_CRATE_TIMEOUT` or
+//! `DYLINT_HEARTBEAT` to a number of seconds
+//!
+//! A thread prints a line to standard error every `DYLINT_HEARTBEAT` seconds while the crate is
+//! being checked. If the crate is still being checked after `DYLINT_PER_CRATE_TIMEOUT` seconds, the
+//! thread writes a file describing the crate to the directory in `DYLINT_TIMEOUTS` (if set) and
+//! exits the process.
+
+use anyhow::{Context, Result};
+use dylint_internal::{env, library_filename};
+use std::{
+    fs::write,
+    path::{Path, PathBuf},
+    thread::{sleep, spawn},
+    time::{Duration, Instant},
+};
+
+pub fn start(paths: &[PathBuf], rustc_args: &[String]) -> Result<()> {
+    let timeout = seconds(env::DYLINT_PER_CRATE_TIMEOUT)?;
+    let heartbeat = seconds(env::DYLINT_HEARTBEAT)?;
+    if timeout.is_none() && heartbeat.is_none() {
+        return Ok(());
+    }
+
+    let crate_name = match crate_name(rustc_args) {
+        Some(crate_name) => crate_name,
+        None => return Ok(()),
+    };
+    let package = env::var(env::CARGO_PKG_NAME).ok();
+    let libraries = paths
+        .iter()
+        .filter_map(|path| library_filename::parse_path(path).map(|(name, _, _)| name))
+        .collect::<Vec<_>>();
+    let description = describe(package.as_deref(), crate_name, &libraries);
+
+    let start = Instant::now();
+    spawn(move || watch(&description, start, timeout, heartbeat));
+
+    Ok(())
+}
+
+fn seconds(key: &str) -> Result<Option<Duration>> {
+    env::var(key)
+        .ok()
+        .map(|value| {
+            value
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .with_context(|| format!("`{key}` is not a number of seconds: {value}"))
+        })
+        .transpose()
+}
+
+fn crate_name(rustc_args: &[String]) -> Option<&str> {
+    rustc_args
+        .windows(2)
+        .find(|window| window[0] == "--crate-name")
+        .map(|window| window[1].as_str())
+}
+
+// smoelius: Cargo names a package's library crate after the package, with hyphens replaced by
+// underscores. Mentioning the crate separately is necessary only when the two names differ in
+// some other way, e.g., for a binary target.
+fn describe(package: Option<&str>, crate_name: &str, libraries: &[String]) -> String {
+    let subject = match package {
+        Some(package) if package.replace('-', "_") == crate_name => format!("package `{package}`"),
+        Some(package) => format!("crate `{crate_name}` of package `{package}`"),
+        None => format!("crate `{crate_name}`"),
+    };
+    let libraries = libraries
+        .iter()
+        .map(|library| format!("`{library}`"))
+        .collect::<Vec<_>>();
+    format!(
+        "{subject} with {} {}",
+        if libraries.len() == 1 {
+            "library"
+        } else {
+            "libraries"
+        },
+        libraries.join(", ")
+    )
+}
+
+fn watch(
+    description: &str,
+    start: Instant,
+    timeout: Option<Duration>,
+    heartbeat: Option<Duration>,
+) {
+    let mut next_heartbeat = heartbeat;
+    loop {
+        let next = match (timeout, next_heartbeat) {
+            (Some(timeout), Some(next_heartbeat)) => timeout.min(next_heartbeat),
+            (Some(next), None) | (None, Some(next)) => next,
+            (None, None) => return,
+        };
+        sleep(next.saturating_sub(start.elapsed()));
+
+        let elapsed = start.elapsed();
+        if let Some(timeout) = timeout {
+            if elapsed >= timeout {
+                expire(description, timeout);
+            }
+        }
+        if let (Some(heartbeat), Some(next)) = (heartbeat, next_heartbeat) {
+            if elapsed >= next {
+                eprintln!(
+                    "Note: still checking {description}, {}s elapsed",
+                    elapsed.as_secs()
+                );
+                next_heartbeat = Some(next + heartbeat);
+            }
+        }
+    }
+}
+
+// smoelius: The process exits without unwinding. Whatever the compiler was doing (e.g., waiting on
+// a lock that will never be released) is abandoned.
+fn expire(description: &str, timeout: Duration) -> ! {
+    eprintln!(
+        "Error: timed out after {}s checking {description}",
+        timeout.as_secs()
+    );
+    if let Ok(dir) = env::var(env::DYLINT_TIMEOUTS) {
+        let path = Path::new(&dir).join(format!("{}.json", std::process::id()));
+        let json = serde_json::json!({
+            "description": description,
+            "seconds": timeout.as_secs(),
+        });
+        let _ = write(path, json.to_string());
+    }
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(
+            "package `my-package` with library `question_mark_in_expression`",
+            describe(
+                Some("my-package"),
+                "my_package",
+                &["question_mark_in_expression".to_owned()]
+            )
+        );
+        assert_eq!(
+            "crate `main` of package `my-package` with libraries `a`, `b`",
+            describe(
+                Some("my-package"),
+                "main",
+                &["a".to_owned(), "b".to_owned()]
+            )
+        );
+    }
+
+    #[test]
+    fn crate_names() {
+        let rustc_args = ["rustc", "--crate-name", "name", "--edition=2021"].map(ToOwned::to_owned);
+        assert_eq!(Some("name"), crate_name(&rustc_args));
+        assert_eq!(None, crate_name(&rustc_args[..2]));
+    }
+}
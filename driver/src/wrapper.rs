@@ -0,0 +1,73 @@
+//! Composition with a wrapper that was set before Dylint set the driver as the wrapper
+//!
+//! Cargo runs a `RUSTC_WORKSPACE_WRAPPER` (or `RUSTC_WRAPPER`) as `WRAPPER RUSTC ARGS...`. When
+//! Dylint makes the driver the wrapper, it passes the wrapper the user had set (e.g., a caching
+//! wrapper) in `DYLINT_RUSTC_WRAPPER`. The driver then runs that wrapper as `WRAPPER DRIVER
+//! ARGS...`, i.e., with the driver in place of `rustc`, since it is the driver that compiles the
+//! crate with the Dylint libraries loaded. `DYLINT_RUSTC_WRAPPER` is removed from the wrapper's
+//! environment. So when the wrapper runs the driver, the driver compiles the crate rather than
+//! running the wrapper again.
+
+use anyhow::{Context, Result};
+use dylint_internal::{env, rustup::is_rustc};
+use std::{
+    env::{current_exe, var_os},
+    ffi::{OsStr, OsString},
+    process::Command,
+};
+
+/// Returns the wrapper the driver should run the compilation through, if any
+pub fn inner() -> Option<OsString> {
+    var_os(env::DYLINT_RUSTC_WRAPPER).filter(|wrapper| !wrapper.is_empty())
+}
+
+/// Runs `wrapper` with the driver in place of `rustc`. `args` are the driver's arguments, excluding
+/// the driver's own path. If the wrapper fails, the driver exits with the wrapper's exit code.
+pub fn run<T: AsRef<OsStr>>(wrapper: &OsStr, args: &[T]) -> Result<()> {
+    let driver = current_exe().with_context(|| "could not get the driver's path")?;
+
+    let status = Command::new(wrapper)
+        .arg(driver)
+        .args(wrapper_args(args))
+        .env_remove(env::DYLINT_RUSTC_WRAPPER)
+        .status()
+        .with_context(|| format!("could not run wrapper `{}`", wrapper.to_string_lossy()))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+// smoelius: Cargo passes `rustc`'s path as the wrapper's first argument. The driver takes its
+// place, so it is dropped. If the first argument is not `rustc`'s path (e.g., the driver was run
+// directly), the arguments are passed as is.
+fn wrapper_args<T: AsRef<OsStr>>(args: &[T]) -> &[T] {
+    match args.split_first() {
+        Some((first, rest)) if is_rustc(first.as_ref()) => rest,
+        _ => args,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rustc_is_dropped() {
+        assert_eq!(
+            wrapper_args(&[
+                "/home/user/.rustup/toolchains/nightly/bin/rustc",
+                "--crate-name",
+                "name"
+            ]),
+            ["--crate-name", "name"]
+        );
+        assert_eq!(
+            wrapper_args(&["--crate-name", "name"]),
+            ["--crate-name", "name"]
+        );
+        assert_eq!(wrapper_args(&["-vV"]), ["-vV"]);
+    }
+}
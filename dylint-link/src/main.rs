@@ -2,39 +2,44 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::panic)]
 
-#[cfg(target_os = "windows")]
-use anyhow::ensure;
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use dylint_internal::{cargo::cargo_home, env, library_filename, Command};
 use if_chain::if_chain;
 use std::{
-    env::{args, consts},
+    env::args,
     ffi::OsStr,
-    fs::{copy, read_to_string},
+    fs::{copy, create_dir_all, read, read_to_string, rename},
     path::{Path, PathBuf},
 };
-#[cfg(target_os = "windows")]
-use std::{fs::File, io::Read};
 use toml_edit::{Document, Item};
 
 fn main() -> Result<()> {
     env_logger::init();
 
-    let linker = linker()?;
+    let strip = strip()?;
+
     let args: Vec<String> = args().collect();
+    let output_path = output_path(&args[1..]);
+    let target = output_path.as_deref().and_then(output_path_target);
+
+    let linker = linker(target.as_deref())?;
     Command::new(linker).args(&args[1..]).success()?;
 
-    if let Some(path) = output_path(args.iter())? {
-        copy_library(&path)?;
+    if let Some(path) = output_path {
+        copy_library(&path, target.as_deref(), strip)?;
     }
 
     Ok(())
 }
 
-fn linker() -> Result<PathBuf> {
-    let rustup_toolchain = env::var(env::RUSTUP_TOOLCHAIN)?;
-    let target = parse_toolchain(&rustup_toolchain)
-        .map_or_else(|| env!("TARGET").to_owned(), |(_, target)| target);
+fn linker(target: Option<&str>) -> Result<PathBuf> {
+    let target = if let Some(target) = target {
+        target.to_owned()
+    } else {
+        let rustup_toolchain = env::var(env::RUSTUP_TOOLCHAIN)?;
+        parse_toolchain(&rustup_toolchain)
+            .map_or_else(|| env!("TARGET").to_owned(), |(_, target)| target)
+    };
     let cargo_home = cargo_home().with_context(|| "Could not determine `CARGO_HOME`")?;
     let config_toml = cargo_home.join("config.toml");
     if config_toml.is_file() {
@@ -84,96 +89,268 @@ fn default_linker() -> Result<PathBuf> {
     Ok(PathBuf::from("cc"))
 }
 
-#[cfg(target_os = "windows")]
-fn output_path<'a, I>(iter: I) -> Result<Option<PathBuf>>
-where
-    I: Iterator<Item = &'a String>,
-{
-    for arg in iter {
-        if let Some(path) = arg.strip_prefix("/OUT:") {
-            return Ok(Some(path.into()));
-        }
-        if let Some(path) = arg.strip_prefix('@') {
-            return extract_out_path_from_linker_response_file(path);
+// smoelius: The output path is looked for in the same way on all platforms, so that it is found
+// whichever flavor of linker is used. MSVC-style linkers (e.g., `link.exe`) take `/OUT:<path>`, and
+// GNU-style linkers (e.g., `cc`) take `-o <path>`. Either may be passed some of its arguments in a
+// response file (`@<path>`), e.g., when the command line would otherwise be too long. If several
+// output paths are given, the last one is used, as a linker would. An `@` argument that cannot be
+// read as a response file is not one (e.g., it could be a path containing `@`), and is ignored.
+fn output_path(args: &[String]) -> Option<PathBuf> {
+    let mut path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(response_file) = arg.strip_prefix('@') {
+            if let Some(response_file_path) = response_file_args(Path::new(response_file))
+                .ok()
+                .and_then(|args| output_path(&args))
+            {
+                path = Some(response_file_path);
+            }
+        } else if let Some(out) = strip_msvc_out(arg) {
+            path = Some(out.into());
+        } else if arg == "-o" {
+            if let Some(out) = iter.next() {
+                path = Some(out.into());
+            }
         }
     }
 
-    Ok(None)
+    path
 }
 
-#[cfg(not(target_os = "windows"))]
-#[allow(clippy::unnecessary_wraps)]
-fn output_path<'a, I>(mut iter: I) -> Result<Option<PathBuf>>
-where
-    I: Iterator<Item = &'a String>,
-{
-    while let Some(arg) = iter.next() {
-        if arg == "-o" {
-            if let Some(path) = iter.next() {
-                return Ok(Some(path.into()));
-            }
-        }
+// smoelius: MSVC-style options are case insensitive and can start with `/` or `-`.
+fn strip_msvc_out(arg: &str) -> Option<&str> {
+    let prefix = arg.get(..5)?;
+    if prefix.eq_ignore_ascii_case("/OUT:") || prefix.eq_ignore_ascii_case("-OUT:") {
+        arg.get(5..)
+    } else {
+        None
     }
+}
 
-    Ok(None)
+// MinerSebas: On Windows the cmd line has a Limit of 8191 Characters.
+// If your command would exceed this you can instead use a Linker Response File to set
+// arguments. (https://docs.microsoft.com/en-us/cpp/build/reference/at-specify-a-linker-response-file?view=msvc-160)
+// smoelius: `rustc` writes one argument per line. A response file for an MSVC-style linker is
+// UTF-16LE with a byte order mark, and each argument is quoted, with `"` escaped as `\"`. A
+// response file for a GNU-style linker is UTF-8, and each argument has `\` and ` ` escaped with a
+// backslash.
+fn response_file_args(path: &Path) -> Result<Vec<String>> {
+    let buf =
+        read(path).with_context(|| format!("`read` failed for `{}`", path.to_string_lossy()))?;
+
+    if let Some(buf) = buf.strip_prefix(&[0xff, 0xfe]) {
+        let file: Vec<u16> = buf
+            .chunks_exact(2)
+            .map(|a| u16::from_le_bytes([a[0], a[1]]))
+            .collect();
+        Ok(String::from_utf16_lossy(&file)
+            .lines()
+            .map(unquote_msvc)
+            .collect())
+    } else {
+        Ok(String::from_utf8_lossy(&buf)
+            .lines()
+            .map(unescape_gnu)
+            .collect())
+    }
 }
 
-#[cfg(target_os = "windows")]
-fn extract_out_path_from_linker_response_file(path: impl AsRef<Path>) -> Result<Option<PathBuf>> {
-    // MinerSebas: On Windows the cmd line has a Limit of 8191 Characters.
-    // If your command would exceed this you can instead use a Linker Response File to set
-    // arguments. (https://docs.microsoft.com/en-us/cpp/build/reference/at-specify-a-linker-response-file?view=msvc-160)
-
-    // MinerSebas: Read the Linker Response File
-    let mut buf: Vec<u8> = Vec::new();
-    File::open(path)?.read_to_end(&mut buf)?;
-
-    // MinerSebas: Convert the File from UTF-16 to a Rust UTF-8 String
-    // (Only necessary for MSVC, the GNU Linker uses UTF-8 isntead.)
-    // Based on: https://stackoverflow.com/a/57172592
-    let file: Vec<u16> = buf
-        .chunks_exact(2)
-        .into_iter()
-        .map(|a| u16::from_ne_bytes([a[0], a[1]]))
-        .collect();
-    let file = String::from_utf16_lossy(file.as_slice());
-
-    let paths: Vec<_> = file
-        .lines()
-        .flat_map(|line| line.trim().trim_matches('"').strip_prefix("/OUT:"))
-        .collect();
-
-    ensure!(paths.len() <= 1, "Found multiple output paths");
-
-    // smoelius: Do not raise an error if no output path is found.
-    Ok(paths.last().map(Into::into))
+fn unquote_msvc(line: &str) -> String {
+    let line = line.trim();
+    line.strip_prefix('"')
+        .and_then(|line| line.strip_suffix('"'))
+        .unwrap_or(line)
+        .replace("\\\"", "\"")
+}
+
+fn unescape_gnu(line: &str) -> String {
+    let mut arg = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            arg.extend(chars.next());
+        } else {
+            arg.push(c);
+        }
+    }
+    arg
 }
 
-fn copy_library(path: &Path) -> Result<()> {
+fn copy_library(path: &Path, target: Option<&str>, strip: Option<Strip>) -> Result<()> {
     if_chain! {
-        if let Some(lib_name) = parse_path_plain_filename(path);
+        if let Some(lib_name) = library_filename::parse_plain_path(path);
         let cargo_pkg_name = env::var(env::CARGO_PKG_NAME)?;
         if lib_name == cargo_pkg_name.replace('-', "_");
         then {
             let rustup_toolchain = env::var(env::RUSTUP_TOOLCHAIN)?;
-            let filename_with_toolchain = library_filename(&lib_name, &rustup_toolchain);
-            let parent = path
-                .parent()
-                .ok_or_else(|| anyhow!("Could not get parent directory"))?;
-            let path_with_toolchain = strip_deps(parent).join(filename_with_toolchain);
-            copy(path, &path_with_toolchain).with_context(|| {
-                format!(
-                    "Could not copy `{}` to `{}`",
-                    path.to_string_lossy(),
-                    path_with_toolchain.to_string_lossy()
-                )
-            })?;
+            let path_with_toolchain =
+                copy_library_with_toolchain(path, &lib_name, &rustup_toolchain, target)?;
+            if let Some(strip) = strip {
+                strip_library(&path_with_toolchain, strip)?;
+            }
+            install_library(&path_with_toolchain);
         }
     }
 
     Ok(())
 }
 
+fn copy_library_with_toolchain(
+    path: &Path,
+    lib_name: &str,
+    toolchain: &str,
+    target: Option<&str>,
+) -> Result<PathBuf> {
+    let filename_with_toolchain = library_filename::compose(lib_name, toolchain, target);
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Could not get parent directory"))?;
+    let path_with_toolchain = strip_deps(parent).join(filename_with_toolchain);
+    replace(path, &path_with_toolchain)?;
+    Ok(path_with_toolchain)
+}
+
+// smoelius: Debug info is stripped from the copy of the library with the toolchain in its name, not
+// from the file the linker wrote. Only debug info is stripped, so the symbols Dylint looks up
+// (e.g., `dylint_version` and `register_lints`) remain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Strip {
+    /// Remove the debug info
+    Debuginfo,
+    /// Write the debug info to a file next to the library before removing it, so that backtraces
+    /// can still be symbolized
+    Split,
+}
+
+fn strip() -> Result<Option<Strip>> {
+    let value = std::env::var(env::DYLINT_LINK_STRIP).unwrap_or_default();
+    match value.as_str() {
+        "" | "0" => Ok(None),
+        "debuginfo" => Ok(Some(Strip::Debuginfo)),
+        "split" => Ok(Some(Strip::Split)),
+        _ => bail!(
+            "`{}` must be `debuginfo` or `split`, but is `{value}`",
+            env::DYLINT_LINK_STRIP
+        ),
+    }
+}
+
+// smoelius: The debug info is written to `<library>.debug`, and a `.gnu_debuglink` section naming
+// that file is added to the library, so that debuggers and `backtrace` can find it.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn strip_library(path: &Path, strip: Strip) -> Result<()> {
+    let objcopy = std::env::var(env::DYLINT_LINK_OBJCOPY).unwrap_or_else(|_| "objcopy".to_owned());
+    let mut args = vec![std::ffi::OsString::from("--strip-debug")];
+    if strip == Strip::Split {
+        let debug_path = with_added_extension(path, "debug");
+        Command::new(&objcopy)
+            .args([
+                OsStr::new("--only-keep-debug"),
+                path.as_os_str(),
+                debug_path.as_os_str(),
+            ])
+            .success()?;
+        let mut debuglink = std::ffi::OsString::from("--add-gnu-debuglink=");
+        debuglink.push(&debug_path);
+        args.push(debuglink);
+    }
+    args.push(path.into());
+    Command::new(&objcopy).args(args).success()
+}
+
+// smoelius: On macOS, the debug info is written to a `<library>.dSYM` bundle, which is where
+// debuggers and `backtrace` look for it.
+#[cfg(target_os = "macos")]
+fn strip_library(path: &Path, strip: Strip) -> Result<()> {
+    if strip == Strip::Split {
+        let dsym_path = with_added_extension(path, "dSYM");
+        Command::new("dsymutil")
+            .args([path.as_os_str(), OsStr::new("-o"), dsym_path.as_os_str()])
+            .success()?;
+    }
+    Command::new("strip")
+        .args([OsStr::new("-S"), path.as_os_str()])
+        .success()
+}
+
+// smoelius: The MSVC linker writes debug info to a separate `.pdb` file, so there is nothing to
+// strip.
+#[cfg(target_os = "windows")]
+#[allow(clippy::unnecessary_wraps)]
+fn strip_library(_path: &Path, _strip: Strip) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+// smoelius: Installing the library is a convenience, so a failure to install it produces a
+// warning rather than failing the build.
+fn install_library(path_with_toolchain: &Path) {
+    let install_dir = match std::env::var_os(env::DYLINT_LINK_INSTALL_DIR) {
+        Some(install_dir) if !install_dir.is_empty() => PathBuf::from(install_dir),
+        _ => return,
+    };
+    match install_library_in(path_with_toolchain, &install_dir) {
+        Ok(installed) => {
+            if !env::enabled(env::DYLINT_LINK_QUIET) {
+                eprintln!("note: installed `{}`", installed.to_string_lossy());
+            }
+        }
+        Err(error) => {
+            eprintln!(
+                "warning: could not install `{}` in `{}`: {error:?}",
+                path_with_toolchain.to_string_lossy(),
+                install_dir.to_string_lossy()
+            );
+        }
+    }
+}
+
+fn install_library_in(path_with_toolchain: &Path, install_dir: &Path) -> Result<PathBuf> {
+    create_dir_all(install_dir).with_context(|| {
+        format!(
+            "`create_dir_all` failed for `{}`",
+            install_dir.to_string_lossy()
+        )
+    })?;
+    let filename = path_with_toolchain
+        .file_name()
+        .ok_or_else(|| anyhow!("Could not get file name"))?;
+    let installed = install_dir.join(filename);
+    replace(path_with_toolchain, &installed)?;
+    Ok(installed)
+}
+
+// smoelius: Copy to a temporary file and then rename it, so that `to` is replaced rather than
+// overwritten in place. A process that has the old library loaded, or hard linked, continues to see
+// the old library in its entirety.
+fn replace(from: &Path, to: &Path) -> Result<()> {
+    let tempfile = to.with_extension("tmp");
+    copy(from, &tempfile).with_context(|| {
+        format!(
+            "Could not copy `{}` to `{}`",
+            from.to_string_lossy(),
+            tempfile.to_string_lossy()
+        )
+    })?;
+    rename(&tempfile, to).with_context(|| {
+        format!(
+            "Could not rename `{}` to `{}`",
+            tempfile.to_string_lossy(),
+            to.to_string_lossy()
+        )
+    })?;
+    Ok(())
+}
+
 // smoelius: I do not know what the right/best way to parse a toolchain is. `parse_toolchain` does
 // so by looking for the architecture.
 fn parse_toolchain(toolchain: &str) -> Option<(String, String)> {
@@ -189,12 +366,19 @@ fn parse_toolchain(toolchain: &str) -> Option<(String, String)> {
         })
 }
 
-fn parse_path_plain_filename(path: &Path) -> Option<String> {
-    let filename = path.file_name()?;
-    let s = filename.to_string_lossy();
-    let file_stem = s.strip_suffix(consts::DLL_SUFFIX)?;
-    let lib_name = file_stem.strip_prefix(consts::DLL_PREFIX)?;
-    Some(lib_name.to_owned())
+// smoelius: With `--target`, Cargo writes a package's artifacts to
+// `<target-dir>/<target>/<profile>` rather than to `<target-dir>/<profile>`. So the library was
+// built with `--target` if the directory above the profile directory looks like a target triple.
+// The target's linker is then used, and the target is added to the copy's filename.
+fn output_path_target(path: &Path) -> Option<String> {
+    let profile_dir = strip_deps(path.parent()?);
+    let target = profile_dir.parent()?.file_name()?.to_str()?;
+    let (arch, _) = target.split_once('-')?;
+    if ARCHITECTURES.binary_search(&arch).is_ok() {
+        Some(target.to_owned())
+    } else {
+        None
+    }
 }
 
 fn strip_deps(path: &Path) -> PathBuf {
@@ -276,12 +460,23 @@ const ARCHITECTURES: &[&str] = &[
 mod test {
     #![allow(clippy::unwrap_used)]
 
-    use super::{env, ARCHITECTURES};
+    use super::{
+        copy_library_with_toolchain, env, install_library_in, output_path, output_path_target,
+        ARCHITECTURES,
+    };
     use assert_cmd::prelude::*;
-    use dylint_internal::packaging::isolate;
+    use dylint_internal::{library_filename, packaging::isolate};
     use predicates::prelude::*;
-    use std::fs::{create_dir, write};
-    use tempfile::{tempdir, tempdir_in};
+    use std::{
+        env::consts,
+        ffi::{c_char, CStr, OsStr},
+        fs::{create_dir, create_dir_all, metadata, read_dir, read_to_string, write, OpenOptions},
+        io::Write,
+        path::{Path, PathBuf},
+    };
+    use tempfile::{tempdir, tempdir_in, TempDir};
+
+    const TOOLCHAIN: &str = "nightly-2023-06-29-x86_64-pc-windows-msvc";
 
     #[test]
     fn architectures_are_current() {
@@ -306,6 +501,176 @@ mod test {
         assert_eq!(ARCHITECTURES, architectures);
     }
 
+    // smoelius: The following tests fake the arguments `rustc` passes to each flavor of linker, so
+    // that they can run on any platform.
+
+    #[test]
+    fn msvc_out() {
+        let (tempdir, library) = library("target");
+        let args = [
+            "/NOLOGO".to_owned(),
+            "/DLL".to_owned(),
+            format!("/OUT:{}", library.to_string_lossy()),
+            "/OPT:REF,NOICF".to_owned(),
+        ];
+        assert_copied(&tempdir, &library, &args);
+    }
+
+    #[test]
+    fn gnu_output() {
+        let (tempdir, library) = library("target");
+        let args = [
+            "-m64".to_owned(),
+            "-shared".to_owned(),
+            "-o".to_owned(),
+            library.to_string_lossy().to_string(),
+            "-Wl,--as-needed".to_owned(),
+        ];
+        assert_copied(&tempdir, &library, &args);
+    }
+
+    #[test]
+    fn msvc_response_file() {
+        let (tempdir, library) = library("target dir");
+        let contents = format!(
+            "\"/NOLOGO\"\n\"/DLL\"\n\"/OUT:{}\"\n",
+            library.to_string_lossy()
+        );
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(contents.encode_utf16().flat_map(u16::to_le_bytes));
+        let response_file = tempdir.path().join("linker-arguments");
+        write(&response_file, bytes).unwrap();
+        let args = [format!("@{}", response_file.to_string_lossy())];
+        assert_copied(&tempdir, &library, &args);
+    }
+
+    #[test]
+    fn gnu_response_file() {
+        let (tempdir, library) = library("target dir");
+        let escaped = library
+            .to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace(' ', "\\ ");
+        let response_file = tempdir.path().join("linker-arguments");
+        write(&response_file, format!("-m64\n-shared\n-o\n{escaped}\n")).unwrap();
+        let args = [
+            "-fuse-ld=lld".to_owned(),
+            format!("@{}", response_file.to_string_lossy()),
+        ];
+        assert_copied(&tempdir, &library, &args);
+    }
+
+    #[test]
+    fn target_output() {
+        let (tempdir, library) = library("target/aarch64-unknown-linux-gnu/debug");
+        let args = ["-o".to_owned(), library.to_string_lossy().to_string()];
+        assert_copied(&tempdir, &library, &args);
+        assert!(tempdir
+            .path()
+            .join("target/aarch64-unknown-linux-gnu/debug")
+            .join(library_filename::compose(
+                "foo",
+                TOOLCHAIN,
+                Some("aarch64-unknown-linux-gnu")
+            ))
+            .exists());
+    }
+
+    #[test]
+    fn output_path_targets() {
+        assert_eq!(
+            Some("aarch64-unknown-linux-gnu".to_owned()),
+            output_path_target(Path::new(
+                "target/aarch64-unknown-linux-gnu/release/deps/libfoo.so"
+            ))
+        );
+        assert_eq!(
+            None,
+            output_path_target(Path::new("target/release/deps/libfoo.so"))
+        );
+        assert_eq!(
+            None,
+            output_path_target(Path::new("my-target/debug/deps/libfoo.so"))
+        );
+    }
+
+    #[test]
+    fn last_output_path() {
+        let args = [
+            "-o".to_owned(),
+            "first".to_owned(),
+            "-out:second".to_owned(),
+        ];
+        assert_eq!(Some(PathBuf::from("second")), output_path(&args));
+    }
+
+    #[test]
+    fn unreadable_response_file() {
+        let tempdir = tempdir().unwrap();
+        let args = [
+            "-o".to_owned(),
+            "first".to_owned(),
+            format!("@{}", tempdir.path().join("nonexistent").to_string_lossy()),
+        ];
+        assert_eq!(Some(PathBuf::from("first")), output_path(&args));
+    }
+
+    #[test]
+    fn install() {
+        let (tempdir, library) = library("target");
+        let path_with_toolchain =
+            copy_library_with_toolchain(&library, "foo", TOOLCHAIN, None).unwrap();
+        let install_dir = tempdir.path().join("install/dir");
+        let installed = install_dir.join(library_filename::compose("foo", TOOLCHAIN, None));
+
+        // smoelius: The directory is created if it does not exist, and an older copy is
+        // overwritten.
+        write(&path_with_toolchain, "old").unwrap();
+        assert_eq!(
+            installed,
+            install_library_in(&path_with_toolchain, &install_dir).unwrap()
+        );
+        write(&path_with_toolchain, "new").unwrap();
+        install_library_in(&path_with_toolchain, &install_dir).unwrap();
+        assert_eq!("new", read_to_string(&installed).unwrap());
+
+        write(tempdir.path().join("file"), "").unwrap();
+        assert!(install_library_in(&path_with_toolchain, &tempdir.path().join("file")).is_err());
+    }
+
+    // smoelius: Creates a library in a `deps` directory, like Cargo's, under `dir` in a temporary
+    // directory.
+    fn library(dir: &str) -> (TempDir, PathBuf) {
+        let tempdir = tempdir().unwrap();
+        let deps = tempdir.path().join(dir).join("deps");
+        create_dir_all(&deps).unwrap();
+        let library = deps.join(format!("{}foo{}", consts::DLL_PREFIX, consts::DLL_SUFFIX));
+        write(&library, "").unwrap();
+        (tempdir, library)
+    }
+
+    fn assert_copied(tempdir: &TempDir, library: &Path, args: &[String]) {
+        let path = output_path(args).unwrap();
+        assert_eq!(library, path);
+        let target = output_path_target(&path);
+        let path_with_toolchain =
+            copy_library_with_toolchain(&path, "foo", TOOLCHAIN, target.as_deref()).unwrap();
+        assert_eq!(
+            library
+                .parent()
+                .and_then(Path::parent)
+                .unwrap()
+                .join(library_filename::compose(
+                    "foo",
+                    TOOLCHAIN,
+                    target.as_deref()
+                )),
+            path_with_toolchain
+        );
+        assert!(path_with_toolchain.starts_with(tempdir.path()));
+        assert!(path_with_toolchain.exists());
+    }
+
     #[cfg(all(target_arch = "x86_64", target_os = "linux"))]
     #[cfg_attr(
         dylint_lib = "non_thread_safe_call_in_test",
@@ -375,4 +740,90 @@ linker = "false"
                 .unwrap(),
             );
     }
+    const LIB_RS: &str = r#"
+#[no_mangle]
+pub extern "C" fn dylint_version() -> *mut std::os::raw::c_char {
+    std::ffi::CString::new("0.1.0").unwrap().into_raw()
+}
+
+#[no_mangle]
+pub fn register_lints() {}
+"#;
+
+    #[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+    #[cfg_attr(
+        dylint_lib = "non_thread_safe_call_in_test",
+        allow(non_thread_safe_call_in_test)
+    )]
+    #[test]
+    fn strip_split() {
+        let package = tempdir_in(".").unwrap();
+
+        dylint_internal::cargo::build("dylint-link", false)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .success()
+            .unwrap();
+
+        dylint_internal::cargo::init("package `strip_test`", false)
+            .current_dir(&package)
+            .args(["--lib", "--name", "strip_test"])
+            .success()
+            .unwrap();
+
+        isolate(package.path()).unwrap();
+
+        let mut manifest = OpenOptions::new()
+            .append(true)
+            .open(package.path().join("Cargo.toml"))
+            .unwrap();
+        writeln!(manifest, "\n[lib]\ncrate-type = [\"cdylib\"]").unwrap();
+
+        write(package.path().join("src/lib.rs"), LIB_RS).unwrap();
+
+        let package_cargo = package.path().join(".cargo");
+        create_dir(&package_cargo).unwrap();
+        write(
+            package_cargo.join("config.toml"),
+            r#"
+[target.x86_64-unknown-linux-gnu]
+linker = "../../target/debug/dylint-link"
+"#,
+        )
+        .unwrap();
+
+        std::process::Command::new("cargo")
+            .env(env::DYLINT_LINK_STRIP, "split")
+            .current_dir(&package)
+            .arg("build")
+            .assert()
+            .success();
+
+        let target_debug = package.path().join("target/debug");
+        let unstripped = target_debug.join("libstrip_test.so");
+        let stripped = read_dir(&target_debug)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("libstrip_test@")
+                    && path.extension() == Some(OsStr::new("so"))
+            })
+            .unwrap();
+
+        assert!(metadata(&stripped).unwrap().len() < metadata(&unstripped).unwrap().len());
+        assert!(PathBuf::from(format!("{}.debug", stripped.to_string_lossy())).exists());
+
+        // smoelius: Load the stripped library the way the driver does, and check that the symbols
+        // the driver looks up are still exported.
+        unsafe {
+            let lib = libloading::Library::new(&stripped).unwrap();
+            let dylint_version = lib
+                .get::<unsafe extern "C" fn() -> *mut c_char>(b"dylint_version")
+                .unwrap();
+            assert_eq!("0.1.0", CStr::from_ptr(dylint_version()).to_str().unwrap());
+            lib.get::<fn()>(b"register_lints").unwrap();
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use dylint_internal::{cargo::cargo_home, env};
+use dylint_internal::{cargo::cargo_home, env, verbatim};
 use std::{fs::OpenOptions, io::Write, path::Path};
 
 fn main() {
@@ -21,6 +21,10 @@ fn main() {
         // smoelius: Ensure the path exists at build time.
         assert!(path_buf.is_dir(), "{path_buf:?} is not a directory");
 
+        // smoelius: The path is written to a generated `Cargo.toml`. So resolve the `..`, but do
+        // not introduce a verbatim prefix.
+        let path_buf = verbatim::canonicalize(&path_buf).unwrap();
+
         format!(
             r#"Some("{}")"#,
             path_buf.to_string_lossy().replace('\\', "\\\\")
@@ -39,5 +43,7 @@ fn main() {
     )
     .unwrap();
 
+    println!("cargo:rustc-env=TARGET={}", env::var(env::TARGET).unwrap());
+
     println!("cargo:rerun-if-changed=build.rs");
 }
@@ -0,0 +1,1224 @@
+use anyhow::{Context, Result};
+use cargo_metadata::{
+    diagnostic::{Applicability, Diagnostic, DiagnosticLevel, DiagnosticSpan},
+    Message,
+};
+use glob::Pattern;
+use is_terminal::IsTerminal;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashSet},
+    fs::metadata,
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+// smoelius: When Dylint needs to inspect the diagnostics produced by `cargo check`, it asks Cargo
+// for JSON messages and renders them itself. The point is to be able to tell diagnostics produced
+// by Dylint lints apart from those produced by `rustc` (e.g., `unused_variables`).
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Counts {
+    /// Number of diagnostics at level warning or above that were produced by Dylint lints
+    pub warnings: usize,
+    /// The same diagnostics, counted by package name and lint name
+    #[serde(with = "by_package_and_lint")]
+    pub by_package_and_lint: BTreeMap<(String, String), usize>,
+}
+
+impl Counts {
+    pub fn add(&mut self, other: &Self) {
+        self.warnings += other.warnings;
+        for (key, count) in &other.by_package_and_lint {
+            *self.by_package_and_lint.entry(key.clone()).or_default() += count;
+        }
+    }
+
+    /// Writes one line per package and lint with the number of warnings the lint produced for
+    /// the package
+    pub fn write_summary(&self, mut writer: impl Write) -> Result<()> {
+        let count_width = self
+            .by_package_and_lint
+            .values()
+            .map(|count| count.to_string().len())
+            .max()
+            .unwrap_or_default();
+
+        let package_width = self
+            .by_package_and_lint
+            .keys()
+            .map(|(package, _)| package.len())
+            .max()
+            .unwrap_or_default();
+
+        for ((package, lint), count) in &self.by_package_and_lint {
+            writeln!(
+                writer,
+                "{count:>count_width$}  {package:<package_width$}  {lint}"
+            )
+            .with_context(|| "Could not write summary")?;
+        }
+
+        Ok(())
+    }
+}
+
+// smoelius: JSON object keys must be strings, so the map is serialized as a list of
+// `(package, lint, count)` triples.
+mod by_package_and_lint {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(
+        map: &BTreeMap<(String, String), usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            map.iter()
+                .map(|((package, lint), count)| (package, lint, count)),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<(String, String), usize>, D::Error> {
+        let triples = Vec::<(String, String, usize)>::deserialize(deserializer)?;
+        Ok(triples
+            .into_iter()
+            .map(|(package, lint, count)| ((package, lint), count))
+            .collect())
+    }
+}
+
+/// Where a file that a diagnostic refers to lies, as far as fixing the file is concerned
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileScope {
+    /// In the workspace and writable
+    Workspace,
+    /// Outside of the workspace, e.g., in a path dependency or in the target directory (which is
+    /// where `OUT_DIR` is)
+    OutsideWorkspace,
+    /// In the workspace, but not writable
+    ReadOnly,
+}
+
+/// Resolves the file names in diagnostics against the workspace being checked
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    root: PathBuf,
+    target_directory: PathBuf,
+    remapping: PathRemapping,
+}
+
+impl Workspace {
+    pub fn new(root: &Path, target_directory: &Path) -> Self {
+        Self {
+            root: normalize(root),
+            target_directory: normalize(target_directory),
+            remapping: PathRemapping::default(),
+        }
+    }
+
+    /// Sets the remapping used to recover the real paths of files whose paths were remapped with
+    /// `--remap-path-prefix`
+    #[must_use]
+    pub fn with_remapping(mut self, remapping: PathRemapping) -> Self {
+        self.remapping = remapping;
+        self
+    }
+
+    /// Returns `file_name` as an absolute path. `file_name` can be relative to the workspace root
+    /// (as it normally is for workspace members) or absolute. If `file_name` was remapped, the
+    /// returned path is the file's real path.
+    pub fn resolve(&self, file_name: &Path) -> PathBuf {
+        let unremapped = file_name
+            .to_str()
+            .and_then(|file_name| self.remapping.unremap(file_name));
+        let file_name = unremapped.as_deref().map_or(file_name, Path::new);
+        normalize(&self.root.join(file_name))
+    }
+
+    /// Returns the path of `file_name` relative to the workspace root, if `file_name` is under the
+    /// workspace root
+    pub fn relative_path(&self, file_name: &Path) -> Option<PathBuf> {
+        self.resolve(file_name)
+            .strip_prefix(&self.root)
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    pub fn classify(&self, file_name: &Path) -> FileScope {
+        let path = self.resolve(file_name);
+        if !path.starts_with(&self.root) || path.starts_with(&self.target_directory) {
+            return FileScope::OutsideWorkspace;
+        }
+        match metadata(&path) {
+            Ok(metadata) if !metadata.permissions().readonly() => FileScope::Workspace,
+            _ => FileScope::ReadOnly,
+        }
+    }
+
+    /// Returns `rendered` with the remapped file names of `diagnostic`'s spans replaced by the
+    /// files' real paths. A real path under the workspace root is shown relative to the root.
+    pub fn unremap_rendered(&self, diagnostic: &Diagnostic, rendered: &str) -> String {
+        let mut replacements = BTreeMap::new();
+        for span in all_spans(diagnostic) {
+            if let Some(unremapped) = self.remapping.unremap(&span.file_name) {
+                let path = normalize(&self.root.join(unremapped));
+                let real = path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+                replacements.insert(span.file_name.as_str(), real);
+            }
+        }
+        // smoelius: Longer file names are replaced first, so that a file name that is a prefix of
+        // another does not clobber it.
+        let mut replacements = replacements.into_iter().collect::<Vec<_>>();
+        replacements.sort_by_key(|(file_name, _)| std::cmp::Reverse(file_name.len()));
+        replacements
+            .into_iter()
+            .fold(rendered.to_owned(), |rendered, (file_name, real)| {
+                rendered.replace(file_name, &real)
+            })
+    }
+}
+
+fn all_spans(diagnostic: &Diagnostic) -> Vec<&DiagnosticSpan> {
+    let mut spans = Vec::new();
+    let mut stack = diagnostic
+        .spans
+        .iter()
+        .chain(
+            diagnostic
+                .children
+                .iter()
+                .flat_map(|child| child.spans.iter()),
+        )
+        .collect::<Vec<_>>();
+    while let Some(span) = stack.pop() {
+        if let Some(expansion) = &span.expansion {
+            stack.push(&expansion.span);
+        }
+        spans.push(span);
+    }
+    spans
+}
+
+// smoelius: `rustc`'s `--remap-path-prefix FROM=TO` replaces the prefix `FROM` with `TO` in the
+// paths it emits, including those in diagnostics. A `PathRemapping` undoes that, so that path
+// filters and fix reports operate on the files' real paths. Only remappings in flags Dylint can
+// see are undone, i.e., those in `CARGO_ENCODED_RUSTFLAGS`, `RUSTFLAGS`, Cargo's `build.rustflags`
+// configuration, and the flags Dylint passes to the driver.
+//
+// Remapping is not invertible in general: two different real paths can be remapped to the same
+// path. Like `rustc`, which applies the last of several matching remappings, `unremap` undoes the
+// last remapping whose `TO` matches.
+
+/// The remappings given by `--remap-path-prefix` flags
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PathRemapping {
+    /// `(from, to)` pairs, in the order in which the flags were given
+    prefixes: Vec<(String, String)>,
+}
+
+impl PathRemapping {
+    /// Collects the remappings in `rustflags`. Both `--remap-path-prefix FROM=TO` and
+    /// `--remap-path-prefix=FROM=TO` are recognized.
+    pub fn from_rustflags<T: AsRef<str>>(rustflags: &[T]) -> Self {
+        let mut prefixes = Vec::new();
+        let mut rustflags = rustflags.iter().map(AsRef::as_ref);
+        while let Some(rustflag) = rustflags.next() {
+            let remap = if rustflag == "--remap-path-prefix" {
+                rustflags.next()
+            } else {
+                rustflag.strip_prefix("--remap-path-prefix=")
+            };
+            // smoelius: `rustc` splits at the last `=`, so `FROM` may contain `=` but `TO` may not.
+            if let Some((from, to)) = remap.and_then(|remap| remap.rsplit_once('=')) {
+                prefixes.push((from.to_owned(), to.to_owned()));
+            }
+        }
+        Self { prefixes }
+    }
+
+    /// Returns the real path of `file_name`, if `file_name` is the result of a remapping
+    pub fn unremap(&self, file_name: &str) -> Option<String> {
+        self.prefixes.iter().rev().find_map(|(from, to)| {
+            let rest = strip_path_prefix(file_name, to)?;
+            if rest.is_empty() {
+                Some(from.clone())
+            } else if from.is_empty() || from.ends_with(is_separator) {
+                Some(format!("{from}{rest}"))
+            } else {
+                // smoelius: Join with the separator `from` uses, so that a Windows path is joined
+                // with `\` even on other platforms.
+                let separator = from.chars().rev().find(|&c| is_separator(c)).unwrap_or('/');
+                Some(format!("{from}{separator}{rest}"))
+            }
+        })
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+// smoelius: `/` and `\` are treated as equivalent, so that Windows paths, which can contain either,
+// are handled on any platform. A prefix matches only whole components, e.g., `/build` matches
+// `/build/lib.rs` but not `/builds/lib.rs`. An empty prefix matches only relative paths. Returns
+// the rest of the path, without a leading separator.
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() {
+        return (!path.starts_with(is_separator) && !has_drive_letter(path)).then_some(path);
+    }
+    let mut path_chars = path.chars();
+    for c in prefix.chars() {
+        let d = path_chars.next()?;
+        if c != d && !(is_separator(c) && is_separator(d)) {
+            return None;
+        }
+    }
+    let rest = path_chars.as_str();
+    if prefix.ends_with(is_separator) || rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(is_separator)
+    }
+}
+
+fn has_drive_letter(path: &str) -> bool {
+    let mut chars = path.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic()
+    )
+}
+
+// smoelius: `.` and `..` components are removed lexically, so that, e.g., a path dependency at
+// `../dep` is not considered to be in the workspace.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Decides which Dylint diagnostics are shown based on the files their primary spans refer to
+#[derive(Debug)]
+pub struct PathFilter {
+    workspace: Workspace,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    files: BTreeSet<PathBuf>,
+}
+
+impl PathFilter {
+    /// Creates a `PathFilter` from `--include-path` and `--exclude-path` globs. Relative globs are
+    /// resolved relative to the workspace root.
+    pub fn new(workspace: &Workspace, include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| {
+                    Pattern::new(glob).with_context(|| format!("Could not parse glob `{glob}`"))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            workspace: workspace.clone(),
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            files: BTreeSet::new(),
+        })
+    }
+
+    /// Further restricts the diagnostics shown to those whose primary spans are in `files`, which
+    /// are relative to the workspace root (as with `--files`)
+    #[must_use]
+    pub fn with_files(mut self, files: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.files = files.into_iter().map(|file| normalize(&file)).collect();
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.files.is_empty()
+    }
+
+    /// Returns true if a diagnostic referring to `file_name` should be shown. `file_name` can be
+    /// relative to the workspace root (as it normally is for workspace members) or absolute.
+    pub fn allows(&self, file_name: &Path) -> bool {
+        let path = self.workspace.resolve(file_name);
+        let relative_path = self.workspace.relative_path(file_name);
+        let matches = |pattern: &Pattern| {
+            if Path::new(pattern.as_str()).is_absolute() {
+                pattern.matches_path(&path)
+            } else {
+                relative_path
+                    .as_ref()
+                    .map_or(false, |relative_path| pattern.matches_path(relative_path))
+            }
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+            && (self.files.is_empty()
+                || relative_path
+                    .as_ref()
+                    .map_or(false, |relative_path| self.files.contains(relative_path)))
+    }
+
+    fn allows_diagnostic(&self, diagnostic: &Diagnostic) -> bool {
+        diagnostic
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .map_or(true, |span| {
+                self.allows(Path::new(&expansion_site(span).file_name))
+            })
+    }
+}
+
+// smoelius: A span originating in a macro expansion is attributed to the file containing the
+// outermost expansion site, i.e., the file the user wrote the macro call in.
+fn expansion_site(mut span: &DiagnosticSpan) -> &DiagnosticSpan {
+    while let Some(expansion) = &span.expansion {
+        span = &expansion.span;
+    }
+    span
+}
+
+// smoelius: Diagnostics written as JSON are for tools, so their `rendered` fields should not
+// contain ANSI escapes, even when Dylint's stderr is a terminal.
+pub fn message_format(options: MessageOptions) -> &'static str {
+    if !options.json && std::io::stderr().is_terminal() {
+        "--message-format=json-diagnostic-rendered-ansi"
+    } else {
+        "--message-format=json"
+    }
+}
+
+// smoelius: Cargo colors its status messages only when its stderr is a terminal. When Dylint pipes
+// `cargo fix`'s stderr, it asks for color explicitly, unless the user already chose.
+pub fn color_flag(args: &[String]) -> Option<&'static str> {
+    (std::io::stderr().is_terminal() && !args.iter().any(|arg| arg.starts_with("--color")))
+        .then_some("--color=always")
+}
+
+/// The outcomes of the machine-applicable suggestions seen during `--fix`
+#[derive(Debug)]
+pub struct FixReport {
+    workspace: Workspace,
+    /// Number of fixes that `cargo fix` reported applying
+    pub applied: usize,
+    /// Number of Dylint diagnostics whose suggestions were not applied because they target files
+    /// outside the workspace
+    pub skipped_outside_workspace: usize,
+    /// Number of Dylint diagnostics whose suggestions were not applied because they target files
+    /// that are not writable
+    pub skipped_read_only: usize,
+}
+
+impl FixReport {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            workspace: workspace.clone(),
+            applied: 0,
+            skipped_outside_workspace: 0,
+            skipped_read_only: 0,
+        }
+    }
+
+    /// Returns a one-line summary, e.g., "applied 12, skipped 3 outside workspace"
+    pub fn summary(&self) -> String {
+        let summary = format!(
+            "applied {}, skipped {} outside workspace",
+            self.applied, self.skipped_outside_workspace
+        );
+        if self.skipped_read_only == 0 {
+            summary
+        } else {
+            format!("{summary}, skipped {} not writable", self.skipped_read_only)
+        }
+    }
+
+    // smoelius: `cargo fix` reports only the diagnostics that remain after it has applied what it
+    // could. So a remaining diagnostic with a machine-applicable suggestion targeting a file
+    // outside the workspace is one whose fix was skipped.
+    fn record_skipped(&mut self, diagnostic: &Diagnostic) -> Option<String> {
+        let lint = diagnostic
+            .code
+            .as_ref()
+            .map_or("", |code| code.code.as_str());
+        for file_name in machine_applicable_file_names(diagnostic) {
+            let reason = match self.workspace.classify(Path::new(file_name)) {
+                FileScope::Workspace => continue,
+                FileScope::OutsideWorkspace => {
+                    self.skipped_outside_workspace += 1;
+                    "is outside the workspace"
+                }
+                FileScope::ReadOnly => {
+                    self.skipped_read_only += 1;
+                    "is not writable"
+                }
+            };
+            return Some(format!(
+                "note: the suggested fix for `{lint}` was not applied because `{file_name}` \
+                 {reason}\n"
+            ));
+        }
+        None
+    }
+}
+
+fn machine_applicable_file_names(diagnostic: &Diagnostic) -> impl Iterator<Item = &str> {
+    diagnostic
+        .spans
+        .iter()
+        .chain(
+            diagnostic
+                .children
+                .iter()
+                .flat_map(|child| child.spans.iter()),
+        )
+        .filter(|span| {
+            span.suggested_replacement.is_some()
+                && span.suggestion_applicability == Some(Applicability::MachineApplicable)
+        })
+        .map(|span| span.file_name.as_str())
+}
+
+// smoelius: `cargo fix` reports what it fixed with status messages such as
+// `Fixed src/lib.rs (3 fixes)`. Each line is passed through to stderr.
+pub fn process_fix_status(reader: impl Read, applied: &mut usize) -> Result<()> {
+    let mut stderr = std::io::stderr();
+    for line in BufReader::new(reader).split(b'\n') {
+        let line = line.with_context(|| "Could not read status from `cargo`")?;
+        stderr
+            .write_all(&line)
+            .and_then(|()| stderr.write_all(b"\n"))
+            .with_context(|| "Could not write to stderr")?;
+        *applied += fixes_in_status(&String::from_utf8_lossy(&line)).unwrap_or_default();
+    }
+    Ok(())
+}
+
+fn fixes_in_status(line: &str) -> Option<usize> {
+    let line = strip_ansi_escapes(line);
+    let rest = line.trim().strip_prefix("Fixed ")?;
+    let (_, count) = rest.rsplit_once(" (")?;
+    count.split_whitespace().next()?.parse().ok()
+}
+
+fn strip_ansi_escapes(s: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // smoelius: Skip to the end of the escape sequence, e.g., `\x1b[1m`.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+// smoelius: Only diagnostics produced by Dylint lints are subject to `path_filter`. Errors and
+// warnings produced by `rustc` are always shown, except in summary mode, where only errors produced
+// by `rustc` are shown.
+
+/// How `process_messages` treats the diagnostics it reads
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessageOptions {
+    /// Count Dylint lint warnings rather than show them
+    pub summary: bool,
+    /// Show diagnostics with the real paths of files whose paths were remapped
+    pub unremap_paths: bool,
+    /// Show and count only the first of several Dylint diagnostics with the same lint, primary
+    /// spans, and message
+    pub dedup: bool,
+    /// Write the diagnostics shown to stdout as Cargo's JSON messages, rather than to stderr as
+    /// `rustc` renders them
+    pub json: bool,
+}
+
+// smoelius: Messages are processed one at a time and dropped before the next is read. Nothing is
+// accumulated besides `counts`, whose size depends only on the numbers of packages and lints, and,
+// when deduplicating, one hash per distinct Dylint diagnostic. This matters for runs that produce
+// many diagnostics, particularly in summary mode.
+pub fn process_messages(
+    reader: impl Read,
+    lint_names: &BTreeSet<String>,
+    path_filter: &PathFilter,
+    options: MessageOptions,
+    counts: &mut Counts,
+    mut fix_report: Option<&mut FixReport>,
+) -> Result<()> {
+    let mut seen = HashSet::new();
+    for message in Message::parse_stream(BufReader::new(reader)) {
+        let message = message.with_context(|| "Could not read message from `cargo`")?;
+        match &message {
+            Message::CompilerMessage(compiler_message) => {
+                let diagnostic = &compiler_message.message;
+                let is_dylint_diagnostic = is_dylint_diagnostic(diagnostic, lint_names);
+                if is_dylint_diagnostic && !path_filter.allows_diagnostic(diagnostic) {
+                    continue;
+                }
+                if is_dylint_diagnostic && options.dedup && !seen.insert(dedup_key(diagnostic)) {
+                    continue;
+                }
+                let show = !options.summary
+                    || (!is_dylint_diagnostic
+                        && matches!(
+                            diagnostic.level,
+                            DiagnosticLevel::Error | DiagnosticLevel::Ice
+                        ));
+                if show && options.json {
+                    let json = serde_json::to_string(&message)
+                        .with_context(|| "Could not serialize message")?;
+                    writeln!(std::io::stdout().lock(), "{json}")
+                        .with_context(|| "Could not write to stdout")?;
+                } else if let Some(rendered) = diagnostic.rendered.as_ref().filter(|_| show) {
+                    let rendered = if options.unremap_paths {
+                        Cow::Owned(path_filter.workspace.unremap_rendered(diagnostic, rendered))
+                    } else {
+                        Cow::Borrowed(rendered)
+                    };
+                    // smoelius: Writing directly to `stderr` avoids capture by `libtest`.
+                    std::io::stderr()
+                        .write_all(rendered.as_bytes())
+                        .with_context(|| "Could not write to stderr")?;
+                }
+                if let Some(note) = fix_report
+                    .as_deref_mut()
+                    .filter(|_| is_dylint_diagnostic)
+                    .and_then(|fix_report| fix_report.record_skipped(diagnostic))
+                {
+                    std::io::stderr()
+                        .write_all(note.as_bytes())
+                        .with_context(|| "Could not write to stderr")?;
+                }
+                if is_dylint_warning(diagnostic, lint_names) {
+                    counts.warnings += 1;
+                    if let Some(code) = &diagnostic.code {
+                        *counts
+                            .by_package_and_lint
+                            .entry((
+                                package_name(&compiler_message.package_id.repr).to_owned(),
+                                code.code.clone(),
+                            ))
+                            .or_default() += 1;
+                    }
+                }
+            }
+            Message::TextLine(line) => {
+                println!("{line}");
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// smoelius: Two diagnostics are considered the same if they are produced by the same lint, have the
+// same primary spans, and have the same message, regardless of their notes and help messages. A
+// diagnostic is reduced to a hash so that the memory used to deduplicate stays small. The package
+// is not part of the key, so a diagnostic produced once for a library and once for its tests (e.g.,
+// with `--all-targets`) is shown once.
+fn dedup_key(diagnostic: &Diagnostic) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    diagnostic
+        .code
+        .as_ref()
+        .map(|code| &code.code)
+        .hash(&mut hasher);
+    diagnostic.message.hash(&mut hasher);
+    for span in diagnostic.spans.iter().filter(|span| span.is_primary) {
+        (
+            &span.file_name,
+            span.line_start,
+            span.column_start,
+            span.line_end,
+            span.column_end,
+        )
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// smoelius: A package id's `repr` has the form `name version (source)`.
+fn package_name(repr: &str) -> &str {
+    repr.split(' ').next().unwrap_or(repr)
+}
+
+fn is_dylint_warning(diagnostic: &Diagnostic, lint_names: &BTreeSet<String>) -> bool {
+    matches!(
+        diagnostic.level,
+        DiagnosticLevel::Warning | DiagnosticLevel::Error
+    ) && is_dylint_diagnostic(diagnostic, lint_names)
+}
+
+fn is_dylint_diagnostic(diagnostic: &Diagnostic, lint_names: &BTreeSet<String>) -> bool {
+    diagnostic
+        .code
+        .as_ref()
+        .map_or(false, |code| lint_names.contains(&code.code))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn compiler_message(level: &str, code: Option<&str>) -> String {
+        compiler_message_with_spans(level, code, "")
+    }
+
+    fn compiler_message_with_spans(level: &str, code: Option<&str>, spans: &str) -> String {
+        let code = code.map_or_else(
+            || "null".to_owned(),
+            |code| format!(r#"{{"code":"{code}","explanation":null}}"#),
+        );
+        format!(
+            r#"{{"reason":"compiler-message","package_id":"foo 0.1.0 (path+file:///foo)","manifest_path":"/foo/Cargo.toml","target":{{"kind":["lib"],"crate_types":["lib"],"name":"foo","src_path":"/foo/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true}},"message":{{"message":"message","code":{code},"level":"{level}","spans":[{spans}],"children":[],"rendered":""}}}}"#
+        )
+    }
+
+    fn span(file_name: &str, expansion: Option<&str>) -> String {
+        let expansion = expansion.map_or_else(
+            || "null".to_owned(),
+            |span| format!(r#"{{"span":{span},"macro_decl_name":"m!","def_site_span":null}}"#),
+        );
+        format!(
+            r#"{{"file_name":"{file_name}","byte_start":0,"byte_end":1,"line_start":1,"line_end":1,"column_start":1,"column_end":2,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":{expansion}}}"#
+        )
+    }
+
+    fn path_filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let to_strings = |globs: &[&str]| globs.iter().map(ToString::to_string).collect::<Vec<_>>();
+        PathFilter::new(
+            &Workspace::new(Path::new("/workspace"), Path::new("/workspace/target")),
+            &to_strings(include),
+            &to_strings(exclude),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exclude_relative_and_absolute() {
+        let path_filter = path_filter(&[], &["src/generated/**"]);
+        assert!(!path_filter.allows(Path::new("src/generated/a.rs")));
+        assert!(!path_filter.allows(Path::new("/workspace/src/generated/a/b.rs")));
+        assert!(path_filter.allows(Path::new("src/lib.rs")));
+        assert!(path_filter.allows(Path::new("/elsewhere/src/generated/a.rs")));
+    }
+
+    #[test]
+    fn include_and_exclude() {
+        let path_filter = path_filter(&["src/**"], &["src/generated/**"]);
+        assert!(path_filter.allows(Path::new("src/lib.rs")));
+        assert!(!path_filter.allows(Path::new("src/generated/a.rs")));
+        assert!(!path_filter.allows(Path::new("tests/test.rs")));
+    }
+
+    #[test]
+    fn absolute_glob() {
+        let path_filter = path_filter(&[], &["/elsewhere/**"]);
+        assert!(!path_filter.allows(Path::new("/elsewhere/src/lib.rs")));
+        assert!(path_filter.allows(Path::new("src/lib.rs")));
+    }
+
+    #[test]
+    fn files() {
+        let path_filter = path_filter(&[], &["b/src/generated/**"]).with_files([
+            PathBuf::from("b/src/foo/bar.rs"),
+            PathBuf::from("b/src/generated/a.rs"),
+        ]);
+        assert!(path_filter.allows(Path::new("b/src/foo/bar.rs")));
+        assert!(path_filter.allows(Path::new("/workspace/b/src/foo/../foo/bar.rs")));
+        assert!(!path_filter.allows(Path::new("b/src/lib.rs")));
+        assert!(!path_filter.allows(Path::new("b/src/generated/a.rs")));
+        assert!(!path_filter.allows(Path::new("/elsewhere/b/src/foo/bar.rs")));
+    }
+
+    const OUT_DIR_FILE: &str = "target/debug/build/foo-0123456789abcdef/out/generated.rs";
+
+    fn suggestion_span(file_name: &str) -> String {
+        span(file_name, None).replace(
+            r#""suggested_replacement":null,"suggestion_applicability":null"#,
+            r#""suggested_replacement":"x","suggestion_applicability":"MachineApplicable""#,
+        )
+    }
+
+    fn workspace_with_lib_rs() -> (tempfile::TempDir, Workspace) {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tempdir.path().join("src")).unwrap();
+        std::fs::write(tempdir.path().join("src/lib.rs"), "").unwrap();
+        let workspace = Workspace::new(tempdir.path(), &tempdir.path().join("target"));
+        (tempdir, workspace)
+    }
+
+    #[test]
+    fn classify() {
+        let (tempdir, workspace) = workspace_with_lib_rs();
+        assert_eq!(
+            FileScope::Workspace,
+            workspace.classify(Path::new("src/lib.rs"))
+        );
+        assert_eq!(
+            FileScope::OutsideWorkspace,
+            workspace.classify(&tempdir.path().join(OUT_DIR_FILE))
+        );
+        assert_eq!(
+            FileScope::OutsideWorkspace,
+            workspace.classify(Path::new("../dep/src/lib.rs"))
+        );
+        assert_eq!(
+            FileScope::ReadOnly,
+            workspace.classify(Path::new("src/missing.rs"))
+        );
+
+        let path = tempdir.path().join("src/lib.rs");
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        assert_eq!(
+            FileScope::ReadOnly,
+            workspace.classify(Path::new("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn fix_report_counts_skipped_suggestions() {
+        let (tempdir, workspace) = workspace_with_lib_rs();
+        let lint_names = BTreeSet::from(["const_path_join".to_owned()]);
+        let out_dir_file = tempdir
+            .path()
+            .join(OUT_DIR_FILE)
+            .to_string_lossy()
+            .replace('\\', "\\\\");
+        let stream = [
+            compiler_message_with_spans(
+                "warning",
+                Some("const_path_join"),
+                &suggestion_span(&out_dir_file),
+            ),
+            compiler_message_with_spans(
+                "warning",
+                Some("const_path_join"),
+                &suggestion_span("src/lib.rs"),
+            ),
+            compiler_message_with_spans(
+                "warning",
+                Some("const_path_join"),
+                &span(&out_dir_file, None),
+            ),
+            compiler_message_with_spans(
+                "warning",
+                Some("unused_mut"),
+                &suggestion_span(&out_dir_file),
+            ),
+        ]
+        .join("\n");
+        let mut fix_report = FixReport::new(&workspace);
+        process_messages(
+            stream.as_bytes(),
+            &lint_names,
+            &PathFilter::new(&workspace, &[], &[]).unwrap(),
+            MessageOptions::default(),
+            &mut Counts::default(),
+            Some(&mut fix_report),
+        )
+        .unwrap();
+        assert_eq!(1, fix_report.skipped_outside_workspace);
+        assert_eq!(0, fix_report.skipped_read_only);
+        assert_eq!(
+            "applied 0, skipped 1 outside workspace",
+            fix_report.summary()
+        );
+    }
+
+    #[test]
+    fn fixes_in_status_lines() {
+        assert_eq!(
+            Some(3),
+            fixes_in_status("       Fixed src/lib.rs (3 fixes)")
+        );
+        assert_eq!(
+            Some(1),
+            fixes_in_status("\x1b[0m\x1b[1m\x1b[32m       Fixed\x1b[0m src/main.rs (1 fix)")
+        );
+        assert_eq!(None, fixes_in_status("    Checking foo v0.1.0 (/foo)"));
+    }
+
+    #[test]
+    fn filters_only_dylint_diagnostics_and_uses_expansion_site() {
+        let lint_names = BTreeSet::from(["try_io_result".to_owned()]);
+        let generated = span("src/generated/a.rs", None);
+        let expanded_into_generated = span("src/macros.rs", Some(&generated));
+        let expanded_into_lib = span("src/generated/macros.rs", Some(&span("src/lib.rs", None)));
+        let stream = [
+            compiler_message_with_spans("warning", Some("try_io_result"), &generated),
+            compiler_message_with_spans("warning", Some("try_io_result"), &expanded_into_generated),
+            compiler_message_with_spans("warning", Some("try_io_result"), &expanded_into_lib),
+            compiler_message_with_spans("warning", Some("unused_variables"), &generated),
+        ]
+        .join("\n");
+        let mut counts = Counts::default();
+        process_messages(
+            stream.as_bytes(),
+            &lint_names,
+            &path_filter(&[], &["src/generated/**"]),
+            MessageOptions::default(),
+            &mut counts,
+            None,
+        )
+        .unwrap();
+        assert_eq!(1, counts.warnings);
+    }
+
+    #[test]
+    fn counts_only_dylint_warnings() {
+        let lint_names = BTreeSet::from(["try_io_result".to_owned()]);
+        let stream = [
+            compiler_message("warning", Some("try_io_result")),
+            compiler_message("error", Some("try_io_result")),
+            compiler_message("note", Some("try_io_result")),
+            compiler_message("warning", Some("unused_variables")),
+            compiler_message("warning", None),
+        ]
+        .join("\n");
+        let mut counts = Counts::default();
+        process_messages(
+            stream.as_bytes(),
+            &lint_names,
+            &path_filter(&[], &[]),
+            MessageOptions::default(),
+            &mut counts,
+            None,
+        )
+        .unwrap();
+        assert_eq!(2, counts.warnings);
+    }
+
+    #[test]
+    fn summary_counts_by_package_and_lint() {
+        let lint_names = BTreeSet::from(["try_io_result".to_owned(), "env_literal".to_owned()]);
+        let stream = [
+            compiler_message("warning", Some("try_io_result")),
+            compiler_message("warning", Some("env_literal")),
+            compiler_message("warning", Some("try_io_result")),
+            compiler_message("warning", Some("unused_variables")),
+        ]
+        .join("\n");
+        let mut counts = Counts::default();
+        process_messages(
+            stream.as_bytes(),
+            &lint_names,
+            &path_filter(&[], &[]),
+            MessageOptions {
+                summary: true,
+                ..Default::default()
+            },
+            &mut counts,
+            None,
+        )
+        .unwrap();
+        assert_eq!(3, counts.warnings);
+        let mut summary = Vec::new();
+        counts.write_summary(&mut summary).unwrap();
+        assert_eq!(
+            "1  foo  env_literal\n2  foo  try_io_result\n",
+            String::from_utf8(summary).unwrap()
+        );
+    }
+
+    #[test]
+    fn dedup_identical_dylint_diagnostics() {
+        let lint_names = BTreeSet::from(["try_io_result".to_owned()]);
+        let lib_rs = span("src/lib.rs", None);
+        let main_rs = span("src/main.rs", None);
+        let stream = [
+            compiler_message_with_spans("warning", Some("try_io_result"), &lib_rs),
+            compiler_message_with_spans("warning", Some("try_io_result"), &lib_rs),
+            compiler_message_with_spans("warning", Some("try_io_result"), &main_rs),
+            compiler_message_with_spans("warning", Some("unused_variables"), &lib_rs),
+            compiler_message_with_spans("warning", Some("unused_variables"), &lib_rs),
+        ]
+        .join("\n");
+        for (dedup, warnings) in [(false, 3), (true, 2)] {
+            let mut counts = Counts::default();
+            process_messages(
+                stream.as_bytes(),
+                &lint_names,
+                &path_filter(&[], &[]),
+                MessageOptions {
+                    summary: true,
+                    dedup,
+                    ..Default::default()
+                },
+                &mut counts,
+                None,
+            )
+            .unwrap();
+            assert_eq!(warnings, counts.warnings);
+        }
+    }
+
+    fn remapping_from(flags: &[&str]) -> PathRemapping {
+        PathRemapping::from_rustflags(flags)
+    }
+
+    #[test]
+    fn remapping_flags() {
+        assert_eq!(
+            remapping_from(&[
+                "--remap-path-prefix=/a=b=/x",
+                "--remap-path-prefix",
+                "/c=/y"
+            ]),
+            PathRemapping {
+                prefixes: vec![
+                    ("/a=b".to_owned(), "/x".to_owned()),
+                    ("/c".to_owned(), "/y".to_owned()),
+                ],
+            }
+        );
+        assert!(remapping_from(&["--cfg", "x", "--remap-path-prefix"])
+            .prefixes
+            .is_empty());
+    }
+
+    #[test]
+    fn remapping_whole_components() {
+        let remapping = remapping_from(&["--remap-path-prefix=/home/me/proj=/build"]);
+        assert_eq!(
+            Some("/home/me/proj/src/lib.rs".to_owned()),
+            remapping.unremap("/build/src/lib.rs")
+        );
+        assert_eq!(
+            Some("/home/me/proj".to_owned()),
+            remapping.unremap("/build")
+        );
+        assert_eq!(None, remapping.unremap("/builds/src/lib.rs"));
+        assert_eq!(None, remapping.unremap("src/lib.rs"));
+    }
+
+    #[test]
+    fn remapping_overlapping_prefixes() {
+        let remapping = remapping_from(&[
+            "--remap-path-prefix=/home/me=/x",
+            "--remap-path-prefix=/home/me/proj=/x/proj",
+        ]);
+        assert_eq!(
+            Some("/home/me/proj/src/lib.rs".to_owned()),
+            remapping.unremap("/x/proj/src/lib.rs")
+        );
+        assert_eq!(
+            Some("/home/me/dep/src/lib.rs".to_owned()),
+            remapping.unremap("/x/dep/src/lib.rs")
+        );
+
+        // smoelius: The last matching remapping wins, as with `rustc`.
+        let remapping = remapping_from(&[
+            "--remap-path-prefix=/home/me/proj=/x/proj",
+            "--remap-path-prefix=/elsewhere=/x",
+        ]);
+        assert_eq!(
+            Some("/elsewhere/proj/src/lib.rs".to_owned()),
+            remapping.unremap("/x/proj/src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn remapping_windows_paths() {
+        let remapping = remapping_from(&[r"--remap-path-prefix=C:\Users\me\proj=C:\build\"]);
+        assert_eq!(
+            Some(r"C:\Users\me\proj\src\lib.rs".to_owned()),
+            remapping.unremap(r"C:\build\src\lib.rs")
+        );
+        assert_eq!(
+            Some(r"C:\Users\me\proj\src/lib.rs".to_owned()),
+            remapping.unremap("C:/build/src/lib.rs")
+        );
+        assert_eq!(None, remapping.unremap(r"D:\build\src\lib.rs"));
+
+        let remapping = remapping_from(&[r"--remap-path-prefix=C:\Users\me\proj="]);
+        assert_eq!(
+            Some(r"C:\Users\me\proj\src\lib.rs".to_owned()),
+            remapping.unremap(r"src\lib.rs")
+        );
+        assert_eq!(None, remapping.unremap(r"C:\src\lib.rs"));
+    }
+
+    #[test]
+    fn path_filter_unremaps() {
+        let workspace = Workspace::new(Path::new("/workspace"), Path::new("/workspace/target"))
+            .with_remapping(remapping_from(&[
+                "--remap-path-prefix=/workspace=/remapped",
+            ]));
+        let path_filter =
+            PathFilter::new(&workspace, &[], &["src/generated/**".to_owned()]).unwrap();
+        assert!(!path_filter.allows(Path::new("/remapped/src/generated/a.rs")));
+        assert!(path_filter.allows(Path::new("/remapped/src/lib.rs")));
+        assert!(!path_filter.allows(Path::new("src/generated/a.rs")));
+    }
+
+    #[test]
+    fn unremap_rendered() {
+        let workspace = Workspace::new(Path::new("/workspace"), Path::new("/workspace/target"))
+            .with_remapping(remapping_from(&[
+                "--remap-path-prefix=/workspace=/remapped",
+                "--remap-path-prefix=/registry=/deps",
+            ]));
+        let message = compiler_message_with_spans(
+            "warning",
+            Some("try_io_result"),
+            &[
+                span(
+                    "/remapped/src/lib.rs",
+                    Some(&span("/deps/m/src/lib.rs", None)),
+                ),
+                span("other.rs", None),
+            ]
+            .join(","),
+        );
+        let diagnostic = match Message::parse_stream(message.as_bytes()).next().unwrap() {
+            Ok(Message::CompilerMessage(compiler_message)) => compiler_message.message,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            "--> src/lib.rs:1:1\n--> /registry/m/src/lib.rs:1:1\n--> other.rs:1:1\n",
+            workspace.unremap_rendered(
+                &diagnostic,
+                "--> /remapped/src/lib.rs:1:1\n--> /deps/m/src/lib.rs:1:1\n--> other.rs:1:1\n"
+            )
+        );
+    }
+
+    // smoelius: `allocation::Counting` tracks the bytes allocated by each thread separately. So
+    // allocations by other tests running concurrently do not affect `summary_memory_is_bounded`.
+    mod allocation {
+        use std::{
+            alloc::{GlobalAlloc, Layout, System},
+            cell::Cell,
+        };
+
+        thread_local! {
+            static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+            static PEAK: Cell<usize> = const { Cell::new(0) };
+        }
+
+        pub struct Counting;
+
+        unsafe impl GlobalAlloc for Counting {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                let ptr = System.alloc(layout);
+                if !ptr.is_null() {
+                    let _ = ALLOCATED.try_with(|allocated| {
+                        allocated.set(allocated.get() + layout.size());
+                        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+                    });
+                }
+                ptr
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout);
+                let _ = ALLOCATED.try_with(|allocated| {
+                    allocated.set(allocated.get().saturating_sub(layout.size()))
+                });
+            }
+        }
+
+        /// Returns the peak number of bytes allocated by the current thread while `f` runs, beyond
+        /// those allocated when `f` was called
+        pub fn peak(f: impl FnOnce()) -> usize {
+            let base = ALLOCATED.with(Cell::get);
+            PEAK.with(|peak| peak.set(base));
+            f();
+            PEAK.with(Cell::get) - base
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: allocation::Counting = allocation::Counting;
+
+    // smoelius: `Repeat` produces `n` copies of `message` without holding more than one in memory.
+    struct Repeat {
+        message: Vec<u8>,
+        n: usize,
+        offset: usize,
+    }
+
+    impl Read for Repeat {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.n == 0 {
+                return Ok(0);
+            }
+            let len = buf.len().min(self.message.len() - self.offset);
+            buf[..len].copy_from_slice(&self.message[self.offset..self.offset + len]);
+            self.offset += len;
+            if self.offset == self.message.len() {
+                self.offset = 0;
+                self.n -= 1;
+            }
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn summary_memory_is_bounded() {
+        const N: usize = 50_000;
+        const LIMIT: usize = 16 * 1024 * 1024;
+
+        let lint_names = BTreeSet::from(["try_io_result".to_owned()]);
+        let path_filter = path_filter(&[], &[]);
+        let message = compiler_message("warning", Some("try_io_result")).replace(
+            r#""rendered":"""#,
+            &format!(r#""rendered":"{}""#, "x".repeat(1024)),
+        ) + "\n";
+        let stream_len = message.len() * N;
+        let mut counts = Counts::default();
+
+        let peak = allocation::peak(|| {
+            process_messages(
+                Repeat {
+                    message: message.into_bytes(),
+                    n: N,
+                    offset: 0,
+                },
+                &lint_names,
+                &path_filter,
+                MessageOptions {
+                    summary: true,
+                    ..Default::default()
+                },
+                &mut counts,
+                None,
+            )
+            .unwrap();
+        });
+
+        assert_eq!(N, counts.warnings);
+        assert!(stream_len > 2 * LIMIT);
+        assert!(peak < LIMIT, "{peak}");
+    }
+}
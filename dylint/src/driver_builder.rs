@@ -1,20 +1,29 @@
-use crate::error::warn;
-use anyhow::{anyhow, ensure, Context, Result};
+use crate::error::{failure, note, warn, FailureKind};
+use anyhow::{anyhow, Context, Result};
 use cargo_metadata::MetadataCommand;
 use dylint_internal::{
     driver as dylint_driver, env,
-    rustup::{toolchain_path, SanitizeEnvironment},
+    rustup::{commit_hash, toolchain_path, SanitizeEnvironment},
+    verbatim,
 };
+use log::debug;
 use semver::Version;
 use std::{
-    env::consts,
-    fs::{copy, create_dir_all, write},
+    env::{consts, current_dir},
+    fs::{copy, create_dir_all, read_to_string, remove_file, write},
     path::{Path, PathBuf},
 };
 use tempfile::tempdir;
 
 include!(concat!(env!("OUT_DIR"), "/dylint_driver_manifest_dir.rs"));
 
+// smoelius: A toolchain can be reinstalled under the same name (e.g., `nightly`) with a different
+// commit, and a driver built against one commit's compiler internals will fail to load libraries
+// built against another's. So the commit hash is recorded in a manifest next to the driver.
+const MANIFEST_JSON: &str = "manifest.json";
+
+const RUSTC_COMMIT_HASH: &str = "rustc_commit_hash";
+
 const README_TXT: &str = r#"
 This directory contains Rust compiler drivers used by Dylint
 (https://github.com/trailofbits/dylint).
@@ -68,7 +77,7 @@ pub fn main() -> Result<()> {
     allow(question_mark_in_expression)
 )]
 pub fn get(opts: &crate::Dylint, toolchain: &str) -> Result<PathBuf> {
-    let dylint_drivers = dylint_drivers()?;
+    let dylint_drivers = dylint_drivers(opts)?;
 
     let driver_dir = dylint_drivers.join(toolchain);
     if !driver_dir.is_dir() {
@@ -81,59 +90,181 @@ pub fn get(opts: &crate::Dylint, toolchain: &str) -> Result<PathBuf> {
     }
 
     let driver = driver_dir.join("dylint-driver");
-    if !driver.exists() || is_outdated(opts, toolchain, &driver)? {
-        build(opts, toolchain, &driver)?;
-    }
+    crate::run_state::run(
+        &format!("build driver for toolchain `{toolchain}`"),
+        || {
+            Ok(crate::run_state::fingerprint(&(
+                toolchain,
+                crate::run_state::file_fingerprint(&driver),
+            )))
+        },
+        || {
+            crate::timings::time(
+                opts,
+                || format!("check/build driver for toolchain `{toolchain}`"),
+                || -> Result<()> {
+                    if !driver.exists() || is_outdated(opts, toolchain, &driver)? {
+                        build(opts, toolchain, &driver)
+                            .map_err(|error| failure(FailureKind::DriverBuild, error))?;
+                    }
+                    Ok(())
+                },
+            )
+        },
+    )?;
 
     Ok(driver)
 }
 
-fn dylint_drivers() -> Result<PathBuf> {
-    if let Ok(dylint_driver_path) = env::var(env::DYLINT_DRIVER_PATH) {
-        let dylint_drivers = Path::new(&dylint_driver_path);
-        ensure!(dylint_drivers.is_dir());
-        Ok(dylint_drivers.to_path_buf())
+/// Returns the drivers directory given by `--driver-path` or `DYLINT_DRIVER_PATH`, made absolute
+/// relative to the current directory
+///
+/// `--driver-path` takes precedence over `DYLINT_DRIVER_PATH`. `None` means the default, i.e.,
+/// `$HOME/.dylint_drivers`.
+pub fn driver_path(opts: &crate::Dylint) -> Result<Option<PathBuf>> {
+    let driver_path = match opts
+        .driver_path
+        .clone()
+        .or_else(|| env::var(env::DYLINT_DRIVER_PATH).ok())
+    {
+        Some(driver_path) => PathBuf::from(driver_path),
+        None => return Ok(None),
+    };
+    if driver_path.is_absolute() {
+        return Ok(Some(driver_path));
+    }
+    let current_dir = current_dir().with_context(|| "Could not get current directory")?;
+    Ok(Some(current_dir.join(driver_path)))
+}
+
+fn dylint_drivers(opts: &crate::Dylint) -> Result<PathBuf> {
+    let dylint_drivers = if let Some(driver_path) = driver_path(opts)? {
+        driver_path
     } else {
         let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find HOME directory"))?;
-        let dylint_drivers = Path::new(&home).join(".dylint_drivers");
-        if !dylint_drivers.is_dir() {
-            create_dir_all(&dylint_drivers).with_context(|| {
-                format!(
-                    "`create_dir_all` failed for `{}`",
-                    dylint_drivers.to_string_lossy()
-                )
-            })?;
-            let readme_txt = dylint_drivers.join("README.txt");
-            write(&readme_txt, README_TXT).with_context(|| {
-                format!("`write` failed for `{}`", readme_txt.to_string_lossy())
-            })?;
-        }
-        Ok(dylint_drivers)
+        Path::new(&home).join(".dylint_drivers")
+    };
+    if !dylint_drivers.is_dir() {
+        create_dir_all(&dylint_drivers).with_context(|| {
+            format!(
+                "`create_dir_all` failed for `{}`",
+                dylint_drivers.to_string_lossy()
+            )
+        })?;
+        let readme_txt = dylint_drivers.join("README.txt");
+        write(&readme_txt, README_TXT)
+            .with_context(|| format!("`write` failed for `{}`", readme_txt.to_string_lossy()))?;
     }
+    debug!(
+        "Using drivers directory `{}`",
+        dylint_drivers.to_string_lossy()
+    );
+    Ok(dylint_drivers)
 }
 
 fn is_outdated(opts: &crate::Dylint, toolchain: &str, driver: &Path) -> Result<bool> {
-    (|| -> Result<bool> {
-        let mut command = dylint_driver(toolchain, driver)?;
-        let output = command.args(["-V"]).output()?;
-        let stdout = std::str::from_utf8(&output.stdout)?;
-        let theirs = stdout
-            .trim_end()
-            .rsplit_once(' ')
-            .map(|(_, s)| s)
-            .ok_or_else(|| anyhow!("Could not determine driver version"))?;
-
-        let their_version = Version::parse(theirs)
-            .with_context(|| format!("Could not parse driver version `{theirs}`"))?;
-
-        let our_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
-
-        Ok(their_version < our_version)
-    })()
-    .or_else(|error| {
-        warn(opts, &error.to_string());
-        Ok(true)
-    })
+    let reason = outdated_reason(
+        || driver_version(toolchain, driver),
+        recorded_commit_hash(driver).as_deref(),
+        || commit_hash(toolchain),
+    );
+    match reason {
+        Ok(None) => Ok(false),
+        Ok(Some(reason)) => {
+            note(
+                opts,
+                &format!("Rebuilding driver for toolchain `{toolchain}` because {reason}"),
+            );
+            Ok(true)
+        }
+        Err(error) => {
+            warn(opts, &error.to_string());
+            Ok(true)
+        }
+    }
+}
+
+// smoelius: The probes are passed in so that the tests can simulate a reinstalled toolchain.
+fn outdated_reason(
+    driver_version: impl FnOnce() -> Result<Version>,
+    recorded_commit_hash: Option<&str>,
+    current_commit_hash: impl FnOnce() -> Result<Option<String>>,
+) -> Result<Option<String>> {
+    let their_version = driver_version()?;
+
+    let our_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
+
+    if their_version < our_version {
+        return Ok(Some(format!(
+            "it has version {their_version}, which is older than {our_version}"
+        )));
+    }
+
+    // smoelius: A driver without a recorded commit hash, or a toolchain that does not report one,
+    // gives nothing to compare.
+    let recorded_commit_hash = match recorded_commit_hash {
+        Some(recorded_commit_hash) => recorded_commit_hash,
+        None => return Ok(None),
+    };
+
+    Ok(current_commit_hash()?
+        .filter(|current_commit_hash| current_commit_hash != recorded_commit_hash)
+        .map(|current_commit_hash| {
+            format!(
+                "it was built with rustc commit {recorded_commit_hash}, but the installed \
+                 toolchain has commit {current_commit_hash}"
+            )
+        }))
+}
+
+fn driver_version(toolchain: &str, driver: &Path) -> Result<Version> {
+    let mut command = dylint_driver(toolchain, driver)?;
+    let output = command.args(["-V"]).output()?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    let theirs = stdout
+        .trim_end()
+        .rsplit_once(' ')
+        .map(|(_, s)| s)
+        .ok_or_else(|| anyhow!("Could not determine driver version"))?;
+
+    Version::parse(theirs).with_context(|| format!("Could not parse driver version `{theirs}`"))
+}
+
+fn manifest_path(driver: &Path) -> PathBuf {
+    driver.with_file_name(MANIFEST_JSON)
+}
+
+fn recorded_commit_hash(driver: &Path) -> Option<String> {
+    let contents = read_to_string(manifest_path(driver)).ok()?;
+    let manifest = serde_json::from_str::<serde_json::Value>(&contents).ok()?;
+    manifest
+        .get(RUSTC_COMMIT_HASH)?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
+
+// smoelius: If the commit hash is unknown, any old manifest is removed so that it cannot be
+// compared against later.
+fn write_manifest(driver: &Path, commit_hash: Option<String>) -> Result<()> {
+    let manifest_path = manifest_path(driver);
+    if let Some(commit_hash) = commit_hash {
+        let mut manifest = serde_json::Map::new();
+        manifest.insert(RUSTC_COMMIT_HASH.to_owned(), commit_hash.into());
+        write(
+            &manifest_path,
+            serde_json::Value::Object(manifest).to_string(),
+        )
+        .with_context(|| format!("`write` failed for `{}`", manifest_path.to_string_lossy()))
+    } else if manifest_path.exists() {
+        remove_file(&manifest_path).with_context(|| {
+            format!(
+                "`remove_file` failed for `{}`",
+                manifest_path.to_string_lossy()
+            )
+        })
+    } else {
+        Ok(())
+    }
 }
 
 #[cfg_attr(dylint_lib = "commented_code", allow(commented_code))]
@@ -143,12 +274,27 @@ fn build(opts: &crate::Dylint, toolchain: &str, driver: &Path) -> Result<()> {
 
     initialize(toolchain, package)?;
 
+    let commit_hash = commit_hash(toolchain).unwrap_or_else(|error| {
+        warn(opts, &error.to_string());
+        None
+    });
+
+    // smoelius: The driver package is generated, and has no Cargo.lock. So `--frozen` and
+    // `--locked` cannot be passed when building it. Only their "offline" part applies.
+    let cargo_flags = if opts.frozen || opts.offline {
+        vec!["--offline".to_owned()]
+    } else {
+        vec![]
+    };
+
     let metadata = MetadataCommand::new()
         .current_dir(package)
         .no_deps()
+        .other_options(cargo_flags.clone())
         .exec()?;
 
-    let toolchain_path = toolchain_path(package)?;
+    // smoelius: The toolchain path is passed to the linker, which may not handle verbatim paths.
+    let toolchain_path = verbatim::simplified(&toolchain_path(package)?);
 
     // smoelius: The commented code was the old behavior. It would cause the driver to have rpaths
     // like `$ORIGIN/../../`... (see https://github.com/trailofbits/dylint/issues/54). The new
@@ -168,6 +314,7 @@ fn build(opts: &crate::Dylint, toolchain: &str, driver: &Path) -> Result<()> {
         .sanitize_environment()
         .envs([(env::RUSTFLAGS, rustflags)])
         .current_dir(package)
+        .args(&cargo_flags)
         .success()?;
 
     let binary = metadata
@@ -185,6 +332,8 @@ fn build(opts: &crate::Dylint, toolchain: &str, driver: &Path) -> Result<()> {
         )
     })?;
 
+    write_manifest(driver, commit_hash)?;
+
     Ok(())
 }
 
@@ -233,6 +382,57 @@ mod test {
         dylint_lib = "non_thread_safe_call_in_test",
         allow(non_thread_safe_call_in_test)
     )]
+    #[test]
+    fn reinstalled_toolchain() {
+        let current = || Ok(Version::parse(env!("CARGO_PKG_VERSION"))?);
+
+        assert_eq!(
+            None,
+            outdated_reason(current, Some("aaaa"), || Ok(Some("aaaa".to_owned()))).unwrap()
+        );
+
+        let reason = outdated_reason(current, Some("aaaa"), || Ok(Some("bbbb".to_owned())))
+            .unwrap()
+            .unwrap();
+        assert!(
+            reason.contains("aaaa") && reason.contains("bbbb"),
+            "{reason}"
+        );
+
+        assert_eq!(
+            None,
+            outdated_reason(current, None, || Ok(Some("bbbb".to_owned()))).unwrap()
+        );
+
+        assert_eq!(
+            None,
+            outdated_reason(current, Some("aaaa"), || Ok(None)).unwrap()
+        );
+    }
+
+    #[test]
+    fn older_driver() {
+        let reason = outdated_reason(
+            || Ok(Version::new(0, 1, 0)),
+            Some("aaaa"),
+            || Ok(Some("aaaa".to_owned())),
+        )
+        .unwrap();
+        assert!(reason.is_some());
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let tempdir = tempdir().unwrap();
+        let driver = tempdir.path().join("dylint-driver");
+
+        write_manifest(&driver, Some("aaaa".to_owned())).unwrap();
+        assert_eq!(Some("aaaa"), recorded_commit_hash(&driver).as_deref());
+
+        write_manifest(&driver, None).unwrap();
+        assert_eq!(None, recorded_commit_hash(&driver));
+    }
+
     #[test]
     fn nightly() {
         let tempdir = tempdir().unwrap();
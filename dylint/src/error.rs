@@ -45,11 +45,116 @@ where
     }
 }
 
+impl ColorizedError<anyhow::Error> {
+    /// Returns the code the process should exit with, e.g., [`WARNINGS_DENIED_EXIT_CODE`] if the
+    /// error is a [`WarningsDenied`] error.
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        if self.0.is::<WarningsDenied>() {
+            WARNINGS_DENIED_EXIT_CODE
+        } else if let Some(failure) = self.0.downcast_ref::<Failure>() {
+            failure.kind.exit_code()
+        } else {
+            FAILURE_EXIT_CODE
+        }
+    }
+}
+
 pub type ColorizedResult<T> = Result<T, ColorizedError<anyhow::Error>>;
 
+// smoelius: The exit codes are documented in `cargo-dylint`'s `--help` output and in the README.
+// `WARNINGS_DENIED_EXIT_CODE` predates the others, which is why the codes are not in order of
+// severity.
+
+/// The exit code used when checking the workspace fails, and for errors not covered by the other
+/// exit codes
+pub const FAILURE_EXIT_CODE: i32 = 1;
+
+/// The exit code used when a library cannot be built
+pub const LIBRARY_BUILD_FAILED_EXIT_CODE: i32 = 2;
+
+/// The exit code used when `--deny-warnings` is passed and Dylint lints produce warnings. The code
+/// differs from the one used when compilation fails so that scripts can tell the two cases apart.
+pub const WARNINGS_DENIED_EXIT_CODE: i32 = 3;
+
+/// The exit code used when a driver cannot be built
+pub const DRIVER_BUILD_FAILED_EXIT_CODE: i32 = 4;
+
+/// The exit code used when checking is stopped by `--timeout` or `--per-crate-timeout`
+pub const TIMEOUT_EXIT_CODE: i32 = 5;
+
+/// The exit code used when Dylint is invoked incorrectly, e.g., with conflicting options. The code
+/// is `EX_USAGE` from BSD's `sysexits.h`.
+pub const USAGE_ERROR_EXIT_CODE: i32 = 64;
+
+/// A kind of failure with its own exit code
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FailureKind {
+    LibraryBuild,
+    DriverBuild,
+    Timeout,
+    Usage,
+}
+
+impl FailureKind {
+    #[must_use]
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::LibraryBuild => LIBRARY_BUILD_FAILED_EXIT_CODE,
+            Self::DriverBuild => DRIVER_BUILD_FAILED_EXIT_CODE,
+            Self::Timeout => TIMEOUT_EXIT_CODE,
+            Self::Usage => USAGE_ERROR_EXIT_CODE,
+        }
+    }
+}
+
+/// An error tagged with a [`FailureKind`]. The error displays exactly as the error it wraps.
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: FailureKind,
+    error: anyhow::Error,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Failure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Tags `error` with `kind`
+pub fn failure(kind: FailureKind, error: anyhow::Error) -> anyhow::Error {
+    Failure { kind, error }.into()
+}
+
+/// The error [`crate::run`] returns when `--deny-warnings` is passed and Dylint lints produce
+/// warnings.
+#[derive(Debug)]
+pub struct WarningsDenied {
+    pub count: usize,
+}
+
+impl std::fmt::Display for WarningsDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`--deny-warnings` was passed and Dylint lints produced {} warning{}",
+            self.count,
+            if self.count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+impl std::error::Error for WarningsDenied {}
+
 #[allow(clippy::expect_used)]
 pub fn warn(opts: &crate::Dylint, message: &str) {
-    if !opts.quiet {
+    if !opts.quiet_warnings {
         // smoelius: Writing directly to `stderr` avoids capture by `libtest`.
         std::io::stderr()
             .write_fmt(format_args!(
@@ -64,3 +169,49 @@ pub fn warn(opts: &crate::Dylint, message: &str) {
             .expect("Could not write to stderr");
     }
 }
+
+#[allow(clippy::expect_used)]
+pub fn note(opts: &crate::Dylint, message: &str) {
+    if !opts.quiet {
+        // smoelius: Writing directly to `stderr` avoids capture by `libtest`.
+        std::io::stderr()
+            .write_fmt(format_args!(
+                "{}: {message}\n",
+                if std::io::stderr().is_terminal() {
+                    Style::new().bold()
+                } else {
+                    Style::new()
+                }
+                .paint("Note")
+            ))
+            .expect("Could not write to stderr");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn exit_code_survives_context() {
+        for kind in [
+            FailureKind::LibraryBuild,
+            FailureKind::DriverBuild,
+            FailureKind::Timeout,
+            FailureKind::Usage,
+        ] {
+            let error = Err::<(), _>(failure(kind, anyhow!("inner")))
+                .context("outer")
+                .unwrap_err();
+            assert_eq!(kind.exit_code(), ColorizedError::new(error).exit_code());
+        }
+
+        assert_eq!(
+            FAILURE_EXIT_CODE,
+            ColorizedError::new(anyhow!("other")).exit_code()
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{display_location, workspace_metadata, Dylint, NameToolchainMap, ToolchainMap};
+use anyhow::{ensure, Context, Result};
+use dialoguer::{console::Term, MultiSelect};
+use is_terminal::IsTerminal;
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+const PROMPT: &str = "Select libraries (arrow keys move, space toggles, enter confirms)";
+
+/// Lets the user choose among the discovered libraries, and returns the chosen ones, built
+pub fn select(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<ToolchainMap> {
+    ensure!(
+        std::io::stdin().is_terminal() && std::io::stderr().is_terminal(),
+        "`--interactive` requires a terminal. Use `--lib <name>` to select libraries instead."
+    );
+
+    let name_toolchain_map = name_toolchain_map.get_or_try_init()?;
+
+    // smoelius: Items are identified by name and toolchain, which is how the last selection is
+    // remembered. A library's location can change from one run to the next, e.g., if it is
+    // rebuilt.
+    let mut items = Vec::new();
+    for (name, toolchain_map) in name_toolchain_map {
+        for (toolchain, maybe_libraries) in toolchain_map {
+            for maybe_library in maybe_libraries {
+                items.push((format!("{name}@{toolchain}"), toolchain, maybe_library));
+            }
+        }
+    }
+
+    ensure!(!items.is_empty(), "No libraries were found.");
+
+    let labels = items
+        .iter()
+        .map(|(key, _, maybe_library)| {
+            let location = display_location(&maybe_library.path())?;
+            Ok(format!("{key}  {location}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let selection_path = selection_path(opts)?;
+    let last_selection = last_selection(&selection_path);
+    let defaults = items
+        .iter()
+        .map(|(key, _, _)| last_selection.contains(key))
+        .collect::<Vec<_>>();
+
+    let chosen = MultiSelect::new()
+        .with_prompt(PROMPT)
+        .items(&labels)
+        .defaults(&defaults)
+        .interact_on(&Term::stderr())
+        .with_context(|| "Could not read selection")?;
+
+    save_selection(
+        &selection_path,
+        chosen.iter().map(|&index| items[index].0.as_str()),
+    )?;
+
+    let mut toolchain_map = ToolchainMap::new();
+    for index in chosen {
+        let (_, toolchain, maybe_library) = &items[index];
+        let path = maybe_library.build(opts)?;
+        toolchain_map
+            .entry((*toolchain).clone())
+            .or_insert_with(Default::default)
+            .insert(path);
+    }
+
+    Ok(toolchain_map)
+}
+
+fn selection_path(opts: &Dylint) -> Result<PathBuf> {
+    let metadata = workspace_metadata(opts)?;
+    Ok(metadata
+        .target_directory
+        .join("dylint/.interactive_selection")
+        .into())
+}
+
+// smoelius: A missing or unreadable selection file is treated as an empty selection.
+fn last_selection(path: &Path) -> BTreeSet<String> {
+    read_to_string(path)
+        .map(|contents| contents.lines().map(ToOwned::to_owned).collect())
+        .unwrap_or_default()
+}
+
+fn save_selection<'a>(path: &Path, keys: impl Iterator<Item = &'a str>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).with_context(|| {
+            format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+        })?;
+    }
+    let contents = keys.map(|key| format!("{key}\n")).collect::<String>();
+    write(path, contents)
+        .with_context(|| format!("Could not write to `{}`", path.to_string_lossy()))
+}
@@ -5,33 +5,57 @@
 #![deny(clippy::panic)]
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use cargo_metadata::MetadataCommand;
+use cargo_metadata::{Metadata, MetadataCommand};
 use dylint_internal::{
-    driver as dylint_driver, env, parse_path_filename, rustup::SanitizeEnvironment,
+    config::Config, driver as dylint_driver, env, lib_paths, library_filename,
+    rustup::SanitizeEnvironment, verbatim,
 };
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     env::{consts, current_dir},
     ffi::OsStr,
     fmt::Debug,
     path::{Path, PathBuf, MAIN_SEPARATOR},
+    time::{Duration, Instant},
 };
 
 #[cfg(feature = "metadata")]
 pub(crate) use cargo::{core, sources, util};
 
+mod diagnostics;
+
 pub mod driver_builder;
 
 mod error;
-use error::warn;
+use error::{failure, note, warn};
+
 #[doc(hidden)]
 pub use error::warn as __warn;
-pub use error::{ColorizedError, ColorizedResult};
+pub use error::{
+    ColorizedError, ColorizedResult, Failure, FailureKind, WarningsDenied,
+    DRIVER_BUILD_FAILED_EXIT_CODE, FAILURE_EXIT_CODE, LIBRARY_BUILD_FAILED_EXIT_CODE,
+    TIMEOUT_EXIT_CODE, USAGE_ERROR_EXIT_CODE, WARNINGS_DENIED_EXIT_CODE,
+};
+
+#[cfg(feature = "interactive")]
+mod interactive;
+
+#[cfg(feature = "metadata")]
+mod library_cache;
+
+mod library_copies;
+use library_copies::LibraryCopies;
+
+#[cfg(feature = "metadata")]
+mod manifest_lints;
+#[cfg(feature = "metadata")]
+use manifest_lints::ManifestLints;
 
 mod name_toolchain_map;
+use name_toolchain_map::{is_host_target, MaybeLibrary};
 pub use name_toolchain_map::{Lazy as NameToolchainMap, ToolchainMap};
-use name_toolchain_map::{LazyToolchainMap, MaybeLibrary};
 
 #[cfg(feature = "metadata")]
 pub(crate) mod metadata;
@@ -42,9 +66,18 @@ mod toml;
 #[cfg(feature = "package_options")]
 mod package_options;
 
+mod run_state;
+
+mod timeout;
+
+mod timings;
+
+#[cfg(feature = "watch")]
+mod watch;
+
 static REQUIRED_FORM: Lazy<String> = Lazy::new(|| {
     format!(
-        r#""{}" LIBRARY_NAME "@" TOOLCHAIN "{}""#,
+        r#""{}" LIBRARY_NAME "@" TOOLCHAIN [ "@" TARGET ] "{}""#,
         consts::DLL_PREFIX,
         consts::DLL_SUFFIX
     )
@@ -55,17 +88,70 @@ static REQUIRED_FORM: Lazy<String> = Lazy::new(|| {
 pub struct Dylint {
     pub all: bool,
 
+    #[deprecated]
+    pub allow_dirty: bool,
+
     #[deprecated]
     pub allow_downgrade: bool,
 
+    pub allow_uninstalled_toolchain: bool,
+
     #[deprecated]
     pub bisect: bool,
 
+    #[deprecated]
+    pub bisect_end: Option<String>,
+
+    #[deprecated]
+    pub bisect_max_attempts: Option<usize>,
+
+    #[deprecated]
+    pub bisect_start: Option<String>,
+
+    pub broken_lint_is_fatal: bool,
+
+    #[deprecated]
+    pub cache_clean: bool,
+
+    #[deprecated]
+    pub cache_stats: bool,
+
+    pub dedup: bool,
+
+    pub deny_warnings: bool,
+
+    #[deprecated]
+    pub dist_out_dir: Option<String>,
+
+    #[deprecated]
+    pub dist_path: Option<String>,
+
+    #[deprecated]
+    pub dist_tag: bool,
+
+    pub driver_path: Option<String>,
+
+    pub exact: bool,
+
+    pub exclude_paths: Vec<String>,
+
+    pub explain_config: bool,
+
+    pub files: Vec<String>,
+
     pub fix: bool,
 
     #[deprecated]
     pub force: bool,
 
+    pub frozen: bool,
+
+    pub heartbeat: Option<u64>,
+
+    pub include_paths: Vec<String>,
+
+    pub interactive: bool,
+
     #[deprecated]
     pub isolate: bool,
 
@@ -73,30 +159,70 @@ pub struct Dylint {
 
     pub libs: Vec<String>,
 
+    pub libs_from: Option<String>,
+
+    pub libs_from_only: bool,
+
+    pub lint_deps: bool,
+
     #[deprecated]
     pub list: bool,
 
+    #[deprecated]
+    pub list_json: bool,
+
+    pub locked: bool,
+
     pub manifest_path: Option<String>,
 
     #[deprecated]
     pub new_path: Option<String>,
 
+    #[deprecated]
+    pub new_workspace: bool,
+
     pub no_build: bool,
 
+    pub no_cache: bool,
+
+    pub no_clear: bool,
+
+    pub no_json: bool,
+
     pub no_metadata: bool,
 
+    pub offline: bool,
+
     pub packages: Vec<String>,
 
     pub paths: Vec<String>,
 
+    pub per_crate_timeout: Option<u64>,
+
     pub quiet: bool,
 
+    pub quiet_warnings: bool,
+
+    pub resume: bool,
+
     #[deprecated]
     pub rust_version: Option<String>,
 
+    pub rustflags: Vec<String>,
+
+    pub summary: bool,
+
+    pub timeout: Option<u64>,
+
+    pub timings: bool,
+
+    pub unremap_paths: bool,
+
     #[deprecated]
     pub upgrade_path: Option<String>,
 
+    pub watch: bool,
+
     pub workspace: bool,
 
     #[deprecated]
@@ -114,62 +240,287 @@ pub fn run(opts: &Dylint) -> Result<()> {
                 `--allow-downgrade`.",
             );
         }
+        // smoelius: A relative drivers directory is resolved now, so that it is unaffected by any
+        // later change of directory.
+        let driver_path = driver_builder::driver_path(opts)?
+            .map(|driver_path| driver_path.to_string_lossy().into_owned());
         Dylint {
             allow_downgrade: opts.allow_downgrade || opts.force,
+            driver_path,
             ..opts.clone()
         }
     };
 
+    validate(&opts).map_err(|error| failure(FailureKind::Usage, error))?;
+
+    if opts.bisect {
+        warn(&opts, "`--bisect` is experimental");
+    }
+
+    #[cfg(feature = "package_options")]
+    if let Some(path) = &opts.new_path {
+        return package_options::new_package(&opts, Path::new(path));
+    }
+
+    #[cfg(feature = "package_options")]
+    if let Some(path) = &opts.upgrade_path {
+        return package_options::upgrade_package(&opts, Path::new(path));
+    }
+
+    #[cfg(feature = "package_options")]
+    if let Some(path) = &opts.dist_path {
+        return package_options::dist_package(&opts, Path::new(path));
+    }
+
+    #[cfg(feature = "metadata")]
+    if opts.cache_clean {
+        return library_cache::clean(&opts);
+    }
+
+    #[cfg(feature = "metadata")]
+    if opts.cache_stats {
+        return library_cache::stats();
+    }
+
+    if opts.explain_config {
+        return explain_config(&opts);
+    }
+
+    let name_toolchain_map = NameToolchainMap::new(&opts);
+
+    let result = run_with_name_toolchain_map(&opts, &name_toolchain_map);
+
+    // smoelius: A failed run can be resumed. A successful run leaves nothing to resume.
+    run_state::finish(result.is_ok())?;
+
+    result
+}
+
+// smoelius: Errors returned by `validate` are usage errors.
+fn validate(opts: &Dylint) -> Result<()> {
     if opts.allow_downgrade && opts.upgrade_path.is_none() {
         bail!("`--allow-downgrade` can be used only with `--upgrade`");
     }
 
-    if opts.bisect {
-        #[cfg(not(unix))]
-        bail!("`--bisect` is supported only on Unix platforms");
-
-        #[cfg(unix)]
-        warn(&opts, "`--bisect` is experimental");
+    #[cfg(not(unix))]
+    if opts.bisect
+        && opts.bisect_start.is_none()
+        && opts.bisect_end.is_none()
+        && opts.bisect_max_attempts.is_none()
+    {
+        bail!(
+            "`--bisect` without `--start`, `--end`, or `--max-attempts` is supported only on Unix \
+            platforms"
+        );
     }
 
     if opts.bisect && opts.upgrade_path.is_none() {
         bail!("`--bisect` can be used only with `--upgrade`");
     }
 
+    if opts.bisect_start.is_some() && !opts.bisect {
+        bail!("`--start` can be used only with `--bisect`");
+    }
+
+    if opts.bisect_end.is_some() && !opts.bisect {
+        bail!("`--end` can be used only with `--bisect`");
+    }
+
+    if let Some(max_attempts) = opts.bisect_max_attempts {
+        if !opts.bisect {
+            bail!("`--max-attempts` can be used only with `--bisect`");
+        }
+        if max_attempts == 0 {
+            bail!("`--max-attempts` must be at least 1");
+        }
+    }
+
+    #[cfg(not(feature = "metadata"))]
+    if opts.cache_clean || opts.cache_stats {
+        bail!("`cache` requires the `metadata` feature");
+    }
+
     if opts.isolate && opts.new_path.is_none() {
         bail!("`--isolate` can be used only with `--new`");
     }
 
+    if opts.new_workspace && opts.new_path.is_none() {
+        bail!("`--workspace` can be used only with `new`");
+    }
+
+    if opts.isolate && opts.new_workspace {
+        bail!("`--isolate` cannot be used with `--workspace`");
+    }
+
     if opts.rust_version.is_some() && opts.upgrade_path.is_none() {
         bail!("`--rust-version` can be used only with `--upgrade`");
     }
 
-    #[cfg(feature = "package_options")]
-    if let Some(path) = &opts.new_path {
-        return package_options::new_package(&opts, Path::new(path));
+    if opts.dist_path.is_none() {
+        if opts.allow_dirty {
+            bail!("`--allow-dirty` can be used only with `dist`");
+        }
+        if opts.dist_out_dir.is_some() {
+            bail!("`--out-dir` can be used only with `dist`");
+        }
+        if opts.dist_tag {
+            bail!("`--tag` can be used only with `dist`");
+        }
     }
 
-    #[cfg(feature = "package_options")]
-    if let Some(path) = &opts.upgrade_path {
-        return package_options::upgrade_package(&opts, Path::new(path));
+    // smoelius: The tag would point to a commit other than the one the artifacts were built from.
+    if opts.dist_tag && opts.allow_dirty {
+        bail!("`--tag` cannot be used with `--allow-dirty`");
     }
 
-    let name_toolchain_map = NameToolchainMap::new(&opts);
+    #[cfg(not(feature = "metadata"))]
+    if opts.libs_from.is_some() {
+        bail!("`--libs-from` requires the `metadata` feature");
+    }
 
-    run_with_name_toolchain_map(&opts, &name_toolchain_map)
+    if opts.libs_from_only && opts.libs_from.is_none() {
+        bail!("`--libs-from-only` can be used only with `--libs-from`");
+    }
+
+    if opts.all && !opts.libs.is_empty() {
+        bail!("`--lib` cannot be used with `--all`");
+    }
+
+    if opts.deny_warnings && opts.fix {
+        bail!("`--deny-warnings` cannot be used with `--fix`");
+    }
+
+    if opts.summary && opts.fix {
+        bail!("`--summary` cannot be used with `--fix`");
+    }
+
+    if opts.lint_deps && opts.fix {
+        bail!("`--lint-deps` cannot be used with `--fix`");
+    }
+
+    if opts.explain_config && (opts.fix || opts.list || opts.watch) {
+        bail!("`--explain-config` cannot be used with `--fix`, `list`, or `--watch`");
+    }
+
+    if opts.list_json && !opts.list {
+        bail!("`--json` can be used only with `list`");
+    }
+
+    if opts.timings && opts.list {
+        bail!("`--timings` cannot be used with `list`");
+    }
+
+    if opts.resume && opts.list {
+        bail!("`--resume` cannot be used with `list`");
+    }
+
+    if opts.interactive {
+        if opts.all || !opts.libs.is_empty() || !opts.paths.is_empty() || !opts.names.is_empty() {
+            bail!("`--interactive` cannot be used with `--all`, `--lib`, or `--path`");
+        }
+        if opts.list {
+            bail!("`--interactive` cannot be used with `list`");
+        }
+    }
+
+    if opts.watch {
+        if opts.fix {
+            bail!("`--watch` cannot be used with `--fix`");
+        }
+        if opts.interactive {
+            bail!("`--watch` cannot be used with `--interactive`");
+        }
+        if opts.list {
+            bail!("`--watch` cannot be used with `list`");
+        }
+        if opts.new_path.is_some() || opts.upgrade_path.is_some() {
+            bail!("`--watch` cannot be used with `new` or `upgrade`");
+        }
+        if opts.dist_path.is_some() {
+            bail!("`--watch` cannot be used with `dist`");
+        }
+        if opts.resume {
+            bail!("`--watch` cannot be used with `--resume`");
+        }
+    }
+
+    if opts.no_clear && !opts.watch {
+        bail!("`--no-clear` can be used only with `--watch`");
+    }
+
+    // smoelius: `--files` chooses the packages to check, so it cannot be combined with options
+    // that choose them otherwise.
+    if !opts.files.is_empty() {
+        if !opts.packages.is_empty() || opts.workspace {
+            bail!("`--files` cannot be used with `--package` or `--workspace`");
+        }
+        if opts.fix || opts.summary || opts.watch {
+            bail!("`--files` cannot be used with `--fix`, `--summary`, or `--watch`");
+        }
+    }
+
+    if opts.no_json && opts.files.is_empty() {
+        bail!("`--no-json` can be used only with `--files`");
+    }
+
+    for (seconds, option) in [
+        (opts.timeout, "--timeout"),
+        (opts.per_crate_timeout, "--per-crate-timeout"),
+        (opts.heartbeat, "--heartbeat"),
+    ] {
+        if seconds == Some(0) {
+            bail!("`{option}` must be at least 1");
+        }
+    }
+
+    // smoelius: While `cargo check` runs with a timeout, ctrl-C is forwarded to it by a signal
+    // handler, which would replace the one `--watch` installs.
+    if opts.timeout.is_some() && opts.watch {
+        bail!("`--timeout` cannot be used with `--watch`");
+    }
+
+    Ok(())
 }
 
 fn run_with_name_toolchain_map(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<()> {
+    if opts.interactive {
+        #[cfg(feature = "interactive")]
+        {
+            run_state::init(opts)?;
+            let resolved = interactive::select(opts, name_toolchain_map)?;
+            if resolved.is_empty() {
+                warn(opts, "No libraries were selected.");
+                return Ok(());
+            }
+            return check_or_fix(opts, &resolved);
+        }
+
+        #[cfg(not(feature = "interactive"))]
+        bail!("`--interactive` requires the `interactive` feature");
+    }
+
     if opts.libs.is_empty() && opts.paths.is_empty() && opts.names.is_empty() && !opts.all {
         if opts.list {
             warn_if_empty(opts, name_toolchain_map)?;
-            return list_libs(name_toolchain_map);
+            return list_libs(opts, name_toolchain_map);
         }
 
         warn(opts, "Nothing to do. Did you forget `--all`?");
         return Ok(());
     }
 
+    if opts.watch {
+        #[cfg(feature = "watch")]
+        return watch::watch(opts, name_toolchain_map);
+
+        #[cfg(not(feature = "watch"))]
+        bail!("`--watch` requires the `watch` feature");
+    }
+
+    if !opts.list {
+        run_state::init(opts)?;
+    }
+
     let resolved = resolve(opts, name_toolchain_map)?;
 
     if resolved.is_empty() {
@@ -202,34 +553,67 @@ fn warn_if_empty(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result
     })
 }
 
-fn list_libs(name_toolchain_map: &NameToolchainMap) -> Result<()> {
+fn list_libs(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<()> {
     let name_toolchain_map = name_toolchain_map.get_or_try_init()?;
 
-    let name_width = name_toolchain_map
-        .keys()
-        .map(String::len)
-        .max()
-        .unwrap_or_default();
-
-    let toolchain_width = name_toolchain_map
-        .values()
-        .flat_map(LazyToolchainMap::keys)
-        .map(String::len)
-        .max()
-        .unwrap_or_default();
+    if opts.list_json {
+        let mut libraries = Vec::new();
+        for (name, toolchain_map) in name_toolchain_map {
+            for (toolchain, maybe_libraries) in toolchain_map {
+                for maybe_library in maybe_libraries {
+                    libraries.push(serde_json::json!({
+                        "name": name,
+                        "package": maybe_library.package_name(),
+                        "toolchain": toolchain,
+                        "path": maybe_library.path(),
+                    }));
+                }
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&libraries)?);
+        return Ok(());
+    }
 
+    let mut rows = Vec::new();
     for (name, toolchain_map) in name_toolchain_map {
         for (toolchain, maybe_libraries) in toolchain_map {
             for maybe_library in maybe_libraries {
-                let location = display_location(&maybe_library.path())?;
-                println!("{name:<name_width$}  {toolchain:<toolchain_width$}  {location}",);
+                rows.push((display_name(name, maybe_library), toolchain, maybe_library));
             }
         }
     }
 
+    let name_width = rows
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or_default();
+
+    let toolchain_width = rows
+        .iter()
+        .map(|(_, toolchain, _)| toolchain.len())
+        .max()
+        .unwrap_or_default();
+
+    for (name, toolchain, maybe_library) in rows {
+        let location = display_location(&maybe_library.path())?;
+        println!("{name:<name_width$}  {toolchain:<toolchain_width$}  {location}",);
+    }
+
     Ok(())
 }
 
+// smoelius: A library can be named by its package name as well as by its library name. So when
+// the two differ (other than in `-`s vs. `_`s), both are shown.
+fn display_name(name: &str, maybe_library: &MaybeLibrary) -> String {
+    match maybe_library.package_name() {
+        Some(package_name) if package_name.replace('-', "_") != name => {
+            format!("{name} (package `{package_name}`)")
+        }
+        _ => name.to_owned(),
+    }
+}
+
 #[cfg_attr(
     dylint_lib = "question_mark_in_expression",
     allow(question_mark_in_expression)
@@ -255,7 +639,6 @@ fn resolve(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<Toolc
     }
 
     for name in &opts.libs {
-        ensure!(!opts.all, "`--lib` cannot be used with `--all`");
         let (toolchain, maybe_library) =
             name_as_lib(name_toolchain_map, name, true)?.unwrap_or_else(|| unreachable!());
         let path = maybe_library.build(opts)?;
@@ -294,18 +677,20 @@ fn resolve(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<Toolc
                 .or_insert_with(Default::default)
                 .insert(path);
         } else {
-            not_found.push(name);
+            not_found.push(name.as_str());
         }
     }
 
     if !not_found.is_empty() {
         not_found.sort_unstable();
+        let notes = name_toolchain_map.not_found_notes(&not_found)?;
         bail!(
-            "Could not find the following libraries:{}",
+            "Could not find the following libraries:{}{}",
             not_found
                 .iter()
                 .map(|name| format!("\n    {name}"))
-                .collect::<String>()
+                .collect::<String>(),
+            notes
         );
     }
 
@@ -322,10 +707,10 @@ pub fn name_as_lib(
         return Ok(None);
     }
 
-    let name_toolchain_map = name_toolchain_map.get_or_try_init()?;
+    if let Some(mut toolchain_map) = name_toolchain_map.toolchain_map_for(name)? {
+        name_toolchain_map.retain_installed(name, &mut toolchain_map)?;
 
-    if let Some(toolchain_map) = name_toolchain_map.get(name) {
-        let mut toolchain_maybe_libraries = flatten_toolchain_map(toolchain_map);
+        let mut toolchain_maybe_libraries = flatten_toolchain_map(&toolchain_map);
 
         return match toolchain_maybe_libraries.len() {
             0 => Ok(None),
@@ -341,7 +726,10 @@ pub fn name_as_lib(
         };
     }
 
-    ensure!(!as_lib_only, "Could not find `--lib {}`", name);
+    if as_lib_only {
+        let notes = name_toolchain_map.not_found_notes(&[name])?;
+        bail!("Could not find `--lib {}`{}", name, notes);
+    }
 
     Ok(None)
 }
@@ -367,8 +755,14 @@ where
 }
 
 fn name_as_path(name: &str, as_path_only: bool) -> Result<Option<(String, PathBuf)>> {
-    if let Ok(path) = PathBuf::from(name).canonicalize() {
-        if let Some((_, toolchain)) = parse_path_filename(&path) {
+    if let Ok(path) = verbatim::canonicalize(name) {
+        if let Some((_, toolchain, target)) = library_filename::parse_path(&path) {
+            ensure!(
+                is_host_target(target.as_deref()),
+                "`{}` was built for `{}`, which is not the host",
+                name,
+                target.unwrap_or_default()
+            );
             return Ok(Some((toolchain, path)));
         }
 
@@ -401,12 +795,16 @@ fn name_as_path(name: &str, as_path_only: bool) -> Result<Option<(String, PathBu
 }
 
 fn list_lints(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
+    if opts.list_json {
+        return list_lints_json(opts, resolved);
+    }
+
     for (toolchain, paths) in resolved {
         for path in paths {
             let driver = driver_builder::get(opts, toolchain)?;
-            let dylint_libs = serde_json::to_string(&[path])?;
-            let (name, _) =
-                parse_path_filename(path).ok_or_else(|| anyhow!("Could not parse path"))?;
+            let lib_paths = lib_paths::encode(&[path]);
+            let (name, _, _) = library_filename::parse_path(path)
+                .ok_or_else(|| anyhow!("Could not parse path"))?;
 
             print!("{name}");
             if resolved.keys().len() >= 2 {
@@ -418,16 +816,9 @@ fn list_lints(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
             }
             println!();
 
-            // smoelius: `-W help` is the normal way to list lints, so we can be sure it
-            // gets the lints loaded. However, we don't actually use it to list the lints.
-            let mut command = dylint_driver(toolchain, &driver)?;
-            command
-                .envs([
-                    (env::DYLINT_LIBS, dylint_libs.as_str()),
-                    (env::DYLINT_LIST, "1"),
-                ])
-                .args(["rustc", "-W", "help"])
-                .success()?;
+            for listing in driver_listings(toolchain, &driver, &lib_paths, None)? {
+                print_lints(&listing.lints);
+            }
 
             println!();
         }
@@ -436,9 +827,123 @@ fn list_lints(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
     Ok(())
 }
 
+fn print_lints(lints: &[ListedLint]) {
+    let name_width = lints
+        .iter()
+        .map(|lint| lint.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let level_width = lints
+        .iter()
+        .map(|lint| lint.level.len())
+        .max()
+        .unwrap_or_default();
+
+    for ListedLint {
+        name, level, desc, ..
+    } in lints
+    {
+        println!("    {name:<name_width$}    {level:<level_width$}    {desc}");
+    }
+}
+
+// smoelius: The driver lists each library's lints, including any metadata the library exports.
+// The libraries are collected into one JSON document so that the output can be parsed as a whole.
+fn list_lints_json(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
+    let mut libraries = Vec::new();
+
+    for (toolchain, paths) in resolved {
+        for path in paths {
+            let driver = driver_builder::get(opts, toolchain)?;
+            let lib_paths = lib_paths::encode(&[path]);
+            let (name, _, _) = library_filename::parse_path(path)
+                .ok_or_else(|| anyhow!("Could not parse path"))?;
+
+            let lints = driver_listings(toolchain, &driver, &lib_paths, None)?
+                .into_iter()
+                .flat_map(|listing| listing.lints)
+                .collect::<Vec<_>>();
+
+            libraries.push(serde_json::json!({
+                "name": name,
+                "toolchain": toolchain,
+                "path": path,
+                "lints": lints,
+            }));
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&libraries)?);
+
+    Ok(())
+}
+
+/// A library as listed by the driver's `--dylint-list` mode
+#[derive(Deserialize)]
+struct Listing {
+    path: PathBuf,
+    lints: Vec<ListedLint>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ListedLint {
+    name: String,
+    level: String,
+    desc: String,
+    #[serde(flatten)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+// smoelius: The driver loads the libraries and calls their `register_lints` functions, so the
+// lints listed are exactly the ones the driver would register when checking.
+fn driver_listings(
+    toolchain: &str,
+    driver: &Path,
+    lib_paths: &str,
+    dylint_lib_copies: Option<&str>,
+) -> Result<Vec<Listing>> {
+    let mut command = dylint_driver(toolchain, driver)?;
+    command
+        .envs([(env::DYLINT_LIB_PATHS, lib_paths)])
+        .args(["--dylint-list", "--json"]);
+    if let Some(dylint_lib_copies) = dylint_lib_copies {
+        command.envs([(env::DYLINT_LIB_COPIES, dylint_lib_copies)]);
+    }
+    let output = command.output()?;
+    let listings = serde_json::from_slice::<Vec<Listing>>(&output.stdout)
+        .with_context(|| "Could not parse driver's list of libraries")?;
+    for listing in &listings {
+        if let Some(error) = &listing.error {
+            bail!(
+                "Could not list lints of `{}`: {error}",
+                listing.path.to_string_lossy()
+            );
+        }
+    }
+    Ok(listings)
+}
+
+fn lint_names(
+    toolchain: &str,
+    driver: &Path,
+    lib_paths: &str,
+    dylint_lib_copies: &str,
+) -> Result<BTreeSet<String>> {
+    Ok(
+        driver_listings(toolchain, driver, lib_paths, Some(dylint_lib_copies))?
+            .into_iter()
+            .flat_map(|listing| listing.lints)
+            .map(|lint| lint.name)
+            .collect(),
+    )
+}
+
 fn display_location(path: &Path) -> Result<String> {
     let current_dir = current_dir().with_context(|| "Could not get current directory")?;
-    let path_buf = match path.canonicalize() {
+    let current_dir = verbatim::simplified(&current_dir);
+    let path_buf = match verbatim::canonicalize(path) {
         Ok(path_buf) => path_buf,
         Err(_) => {
             return Ok("<unbuilt>".to_owned());
@@ -455,16 +960,104 @@ fn display_location(path: &Path) -> Result<String> {
 }
 
 fn check_or_fix(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
+    // smoelius: `cargo metadata` is run once, and its output is passed to the functions that need
+    // it. Running it can take a noticeable fraction of a second, e.g., on a large workspace.
+    let metadata = workspace_metadata(opts)?;
+
+    let dylint_rustflags = dylint_rustflags(opts, &metadata)?;
+
+    let workspace = workspace(&metadata).with_remapping(path_remapping(&dylint_rustflags)?);
+
+    let (packages, files) = if opts.files.is_empty() {
+        (opts.packages.clone(), Vec::new())
+    } else {
+        let file_packages = file_packages(opts, &metadata)?;
+        if file_packages.is_empty() {
+            warn(
+                opts,
+                "None of the files passed to `--files` belongs to a workspace target.",
+            );
+            return Ok(());
+        }
+        let packages = file_packages.values().cloned().collect::<BTreeSet<_>>();
+        (
+            packages.into_iter().collect(),
+            file_packages.into_keys().collect(),
+        )
+    };
+
+    let path_filter = path_filter(opts, &metadata, &workspace)?.with_files(files);
+
+    // smoelius: Diagnostics need to be processed only if they could affect the exit code, could be
+    // filtered out, or must be counted rather than shown. When fixing, they are processed to find
+    // the suggestions that were skipped because they target files outside the workspace. With
+    // `--unremap-paths`, they are processed to rewrite the file names in them, and with `--dedup`,
+    // to drop duplicates.
+    let process_diagnostics = opts.deny_warnings
+        || !path_filter.is_empty()
+        || opts.summary
+        || opts.fix
+        || opts.unremap_paths
+        || opts.dedup;
+
+    let message_options = diagnostics::MessageOptions {
+        summary: opts.summary,
+        unremap_paths: opts.unremap_paths,
+        dedup: opts.dedup,
+        json: !opts.files.is_empty() && !opts.no_json,
+    };
+
+    validate_check_or_fix(opts, &path_filter, process_diagnostics)
+        .map_err(|error| failure(FailureKind::Usage, error))?;
+
     let clippy_disable_docs_links = clippy_disable_docs_links()?;
 
+    let dylint_toml = dylint_toml(&metadata)?;
+
+    let cargo_flags = cargo_flags(opts);
+
     let mut failures = Vec::new();
 
+    let mut counts = diagnostics::Counts::default();
+
+    let mut fix_report = opts.fix.then(|| diagnostics::FixReport::new(&workspace));
+
+    let pass_dir = if opts.timings {
+        Some(tempfile::tempdir().with_context(|| "Could not create temporary directory")?)
+    } else {
+        None
+    };
+
+    let expired_dir = if opts.per_crate_timeout.is_some() {
+        Some(tempfile::tempdir().with_context(|| "Could not create temporary directory")?)
+    } else {
+        None
+    };
+
+    let deadline = opts
+        .timeout
+        .map(|timeout| Instant::now() + Duration::from_secs(timeout));
+
+    let mut timeouts = Vec::new();
+
+    let library_copies = LibraryCopies::new(opts, &metadata)?;
+
+    #[cfg(feature = "metadata")]
+    let manifest_lints = ManifestLints::new(&metadata)?;
+
+    #[cfg(feature = "metadata")]
+    let manifest_lint_names = manifest_lint_names(opts, resolved, &manifest_lints)?;
+
     for (toolchain, paths) in resolved {
-        let target_dir = target_dir(opts, toolchain)?;
+        let target_dir = target_dir(&metadata, toolchain);
         let target_dir_str = target_dir.to_string_lossy();
         let driver = driver_builder::get(opts, toolchain)?;
-        let dylint_libs = serde_json::to_string(&paths)?;
+        let lib_paths = lib_paths::encode(paths);
         let description = format!("with toolchain `{toolchain}`");
+        let phase = format!(
+            "{} {description}",
+            if opts.fix { "cargo fix" } else { "cargo check" }
+        );
         let mut command = if opts.fix {
             dylint_internal::cargo::fix(&description)
         } else {
@@ -474,14 +1067,69 @@ fn check_or_fix(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
         if let Some(path) = &opts.manifest_path {
             args.extend(["--manifest-path", path]);
         }
-        for spec in &opts.packages {
+        for spec in &packages {
             args.extend(["-p", spec]);
         }
         if opts.workspace {
             args.extend(["--workspace"]);
         }
+        args.extend(cargo_flags.iter().map(String::as_str));
+        if process_diagnostics {
+            args.push(diagnostics::message_format(message_options));
+        }
+        if opts.fix {
+            args.extend(diagnostics::color_flag(&opts.args));
+        }
         args.extend(opts.args.iter().map(String::as_str));
 
+        // smoelius: A check's fingerprint covers the libraries and driver it runs with, in addition
+        // to the arguments passed to `cargo`. Changes to the workspace's own sources do not
+        // invalidate it: resuming means not rechecking what was already checked.
+        let fingerprint = run_state::is_active().then(|| {
+            run_state::fingerprint(&(
+                &phase,
+                &args,
+                paths
+                    .iter()
+                    .map(|path| (path, run_state::file_fingerprint(path)))
+                    .collect::<Vec<_>>(),
+                run_state::file_fingerprint(&driver),
+                format!("{path_filter:?}"),
+                process_diagnostics,
+                opts.lint_deps,
+            ))
+        });
+        if let Some(marker) = fingerprint
+            .as_deref()
+            .and_then(|fingerprint| run_state::completed(&phase, fingerprint))
+        {
+            note(
+                opts,
+                &format!("Skipping `{phase}`, which completed in the interrupted run"),
+            );
+            if let Some(marker_counts) = &marker.counts {
+                counts.add(marker_counts);
+            }
+            continue;
+        }
+
+        let dylint_lib_copies = lib_paths::encode(&library_copies.copy(toolchain, paths)?);
+
+        let lint_names = if process_diagnostics || opts.lint_deps {
+            lint_names(toolchain, &driver, &lib_paths, &dylint_lib_copies)?
+        } else {
+            BTreeSet::new()
+        };
+
+        // smoelius: Cargo runs `RUSTC_WORKSPACE_WRAPPER` only on workspace members, but
+        // `RUSTC_WRAPPER` on every crate. In crates that Cargo caps with `--cap-lints`, the driver
+        // force-warns the lints listed in `DYLINT_LINT_DEPS`, which rustc does not cap.
+        let wrapper = if opts.lint_deps {
+            env::RUSTC_WRAPPER
+        } else {
+            env::RUSTC_WORKSPACE_WRAPPER
+        };
+
         // smoelius: Set CLIPPY_DISABLE_DOCS_LINKS to prevent lints from accidentally linking to the
         // Clippy repository. But set it to the JSON-encoded original value so that the Clippy
         // library can unset the variable.
@@ -490,49 +1138,464 @@ fn check_or_fix(opts: &Dylint, resolved: &ToolchainMap) -> Result<()> {
         // https://github.com/rust-lang/rust-clippy/commit/1a206fc4abae0b57a3f393481367cf3efca23586
         // But I am going to continue to set CLIPPY_DISABLE_DOCS_LINKS because it doesn't seem to
         // hurt and it provides a small amount of backward compatibility.
-        let result = command
+        // smoelius: `sanitize_environment` leaves `RUSTFLAGS` alone, so the user's `RUSTFLAGS`
+        // apply to the check. Only the library and driver builds are protected from them.
+        command
             .sanitize_environment()
             .envs([
                 (
                     env::CLIPPY_DISABLE_DOCS_LINKS,
                     clippy_disable_docs_links.as_str(),
                 ),
-                (env::DYLINT_LIBS, &dylint_libs),
-                (env::RUSTC_WORKSPACE_WRAPPER, &*driver.to_string_lossy()),
+                (env::DYLINT_LIB_PATHS, &lib_paths),
+                (env::DYLINT_LIB_COPIES, &dylint_lib_copies),
+                (env::DYLINT_RUSTFLAGS, &dylint_rustflags),
+                (wrapper, &*driver.to_string_lossy()),
                 (env::RUSTUP_TOOLCHAIN, toolchain),
             ])
-            .args(args)
-            .success();
+            .args(args);
+        if opts.lint_deps {
+            command.envs([(env::DYLINT_LINT_DEPS, serde_json::to_string(&lint_names)?)]);
+        }
+        // smoelius: Setting the driver as the wrapper displaces any wrapper the user set (e.g., a
+        // caching wrapper). So pass that wrapper to the driver, which runs it around itself. Note
+        // that Cargo composes a user's `RUSTC_WRAPPER` with `RUSTC_WORKSPACE_WRAPPER` on its own.
+        if let Some(inner_wrapper) = std::env::var_os(wrapper).filter(|value| !value.is_empty()) {
+            command.envs([(env::DYLINT_RUSTC_WRAPPER, inner_wrapper)]);
+        }
+        if let Some(dylint_toml) = &dylint_toml {
+            command.envs([(env::DYLINT_TOML, dylint_toml)]);
+        }
+        #[cfg(feature = "metadata")]
+        if let Some(lint_names) = manifest_lint_names.get(toolchain.as_str()) {
+            command.envs([(
+                env::DYLINT_MANIFEST_LINTS,
+                manifest_lints.to_json(lint_names)?,
+            )]);
+        }
+        if let Some(pass_dir) = &pass_dir {
+            command.envs([(env::DYLINT_TIMINGS, pass_dir.path())]);
+        }
+        if let Some(per_crate_timeout) = opts.per_crate_timeout {
+            command.envs([(env::DYLINT_PER_CRATE_TIMEOUT, per_crate_timeout.to_string())]);
+        }
+        if opts.broken_lint_is_fatal {
+            command.envs([(env::DYLINT_BROKEN_LINT_IS_FATAL, "1")]);
+        }
+        if let Some(heartbeat) = opts.heartbeat {
+            command.envs([(env::DYLINT_HEARTBEAT, heartbeat.to_string())]);
+        }
+        if let Some(expired_dir) = &expired_dir {
+            command.envs([(env::DYLINT_TIMEOUTS, expired_dir.path())]);
+        }
+        if let Some(deadline) = deadline {
+            command.timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+        let mut toolchain_counts = diagnostics::Counts::default();
+        let mut applied = 0;
+        let result = timings::time(
+            opts,
+            || phase.clone(),
+            || {
+                if let Some(fix_report) = fix_report.as_mut() {
+                    command.success_with_stdout_and_stderr(
+                        |stdout| {
+                            diagnostics::process_messages(
+                                stdout,
+                                &lint_names,
+                                &path_filter,
+                                message_options,
+                                &mut toolchain_counts,
+                                Some(fix_report),
+                            )
+                        },
+                        |stderr| diagnostics::process_fix_status(stderr, &mut applied),
+                    )
+                } else if process_diagnostics {
+                    command.success_with_stdout(|stdout| {
+                        diagnostics::process_messages(
+                            stdout,
+                            &lint_names,
+                            &path_filter,
+                            message_options,
+                            &mut toolchain_counts,
+                            None,
+                        )
+                    })
+                } else {
+                    command.success()
+                }
+            },
+        );
+        counts.add(&toolchain_counts);
+        if let Some(fix_report) = fix_report.as_mut() {
+            fix_report.applied += applied;
+        }
+        let expired = timeout::expired(
+            opts,
+            toolchain,
+            paths,
+            result.as_ref().err(),
+            expired_dir.as_ref().map(tempfile::TempDir::path),
+        )?;
+        if !expired.is_empty() {
+            if !opts.keep_going {
+                if let Some(pass_dir) = &pass_dir {
+                    write_timings(&metadata, pass_dir.path())?;
+                }
+                return Err(timeout::failure(&expired));
+            }
+            timeouts.extend(expired);
+            continue;
+        }
         if result.is_err() {
             if !opts.keep_going {
+                if let Some(pass_dir) = &pass_dir {
+                    write_timings(&metadata, pass_dir.path())?;
+                }
                 return result
                     .with_context(|| format!("Compilation failed with toolchain `{toolchain}`"));
             };
             failures.push(toolchain);
+            continue;
+        }
+        if let Some(fingerprint) = fingerprint {
+            run_state::complete(
+                &phase,
+                run_state::Marker {
+                    fingerprint,
+                    counts: process_diagnostics.then_some(toolchain_counts),
+                },
+            )?;
+        }
+    }
+
+    if opts.summary {
+        counts.write_summary(std::io::stdout().lock())?;
+    }
+
+    if let Some(fix_report) = &fix_report {
+        note(opts, &format!("Fixes: {}", fix_report.summary()));
+    }
+
+    if let Some(pass_dir) = &pass_dir {
+        write_timings(&metadata, pass_dir.path())?;
+    }
+
+    if !timeouts.is_empty() {
+        return Err(timeout::failure(&timeouts));
+    }
+
+    ensure!(
+        failures.is_empty(),
+        "Compilation failed with the following toolchains: {:?}",
+        failures
+    );
+
+    if opts.deny_warnings && counts.warnings > 0 {
+        return Err(WarningsDenied {
+            count: counts.warnings,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// smoelius: The timings are printed even if the check failed, since a failure can be what makes a
+// run slow.
+fn write_timings(metadata: &Metadata, pass_dir: &Path) -> Result<()> {
+    let timings = timings::Timings::collect(pass_dir)?;
+    timings.write_table(std::io::stdout().lock())?;
+    let path = metadata.target_directory.join("dylint/timings.json");
+    timings.write_json(path.as_std_path())
+}
+
+// smoelius: Like `validate`, but for conditions that depend on the workspace metadata.
+fn validate_check_or_fix(
+    opts: &Dylint,
+    path_filter: &diagnostics::PathFilter,
+    process_diagnostics: bool,
+) -> Result<()> {
+    ensure!(
+        path_filter.is_empty() || !opts.fix,
+        "Path filters (`--exclude-path`, `--include-path`) cannot be used with `--fix`"
+    );
+
+    ensure!(
+        !process_diagnostics
+            || !opts
+                .args
+                .iter()
+                .any(|arg| arg.starts_with("--message-format")),
+        "`--deny-warnings`, `--dedup`, `--fix`, `--summary`, `--unremap-paths`, and path \
+         filters (`--exclude-path`, `--include-path`, `--files`) cannot be used with \
+         `--message-format`"
+    );
+
+    Ok(())
+}
+
+fn workspace(metadata: &Metadata) -> diagnostics::Workspace {
+    diagnostics::Workspace::new(
+        metadata.workspace_root.as_std_path(),
+        metadata.target_directory.as_std_path(),
+    )
+}
+
+#[cfg_attr(not(feature = "metadata"), allow(unused_variables))]
+fn path_filter(
+    opts: &Dylint,
+    metadata: &Metadata,
+    workspace: &diagnostics::Workspace,
+) -> Result<diagnostics::PathFilter> {
+    #[allow(unused_mut)]
+    let mut include_paths = opts.include_paths.clone();
+    #[allow(unused_mut)]
+    let mut exclude_paths = opts.exclude_paths.clone();
+
+    #[cfg(feature = "metadata")]
+    if !opts.no_metadata {
+        let (include, exclude) = metadata::path_filter_globs(metadata)?;
+        include_paths.extend(include);
+        exclude_paths.extend(exclude);
+    }
+
+    diagnostics::PathFilter::new(workspace, &include_paths, &exclude_paths)
+}
+
+// smoelius: Maps each file passed to `--files` to the name of the package it belongs to, with the
+// file's path made relative to the workspace root. A file belongs to the workspace target whose
+// source directory (i.e., the directory containing the target's root file) is the longest prefix
+// of the file's path. So a file in a nested module tree (e.g., `src/a/b.rs`) belongs to the target
+// whose root is `src/lib.rs`, and a file in a package nested in another package's directory
+// belongs to the nested package. Paths are canonicalized before they are compared, so that, e.g.,
+// symlinks in the path to the workspace do not matter.
+fn file_packages(opts: &Dylint, metadata: &Metadata) -> Result<BTreeMap<PathBuf, String>> {
+    let workspace_root = metadata
+        .workspace_root
+        .canonicalize()
+        .with_context(|| "Could not canonicalize workspace root")?;
+
+    let mut target_dirs = Vec::new();
+    for package in metadata.workspace_packages() {
+        for target in &package.targets {
+            if let Some(dir) = target
+                .src_path
+                .parent()
+                .and_then(|dir| dir.canonicalize().ok())
+            {
+                target_dirs.push((dir, package.name.clone()));
+            }
+        }
+    }
+
+    let mut file_packages = BTreeMap::new();
+    for file in &opts.files {
+        let package = Path::new(file).canonicalize().ok().and_then(|path| {
+            let relative_path = path.strip_prefix(&workspace_root).ok()?.to_path_buf();
+            target_dirs
+                .iter()
+                .filter(|(dir, _)| path.starts_with(dir))
+                .max_by_key(|(dir, _)| dir.components().count())
+                .map(|(_, name)| (relative_path, name.clone()))
+        });
+        if let Some((relative_path, name)) = package {
+            file_packages.insert(relative_path, name);
+        } else {
+            warn(
+                opts,
+                &format!("`{file}` does not belong to any workspace target; skipping"),
+            );
+        }
+    }
+
+    Ok(file_packages)
+}
+
+// smoelius: The names of the lints in the libraries being checked are needed for two reasons.
+// Names in `[lints.dylint]` tables that are not among them are warned about here, once per run,
+// rather than by `rustc` in every crate. And each toolchain's driver is passed only the names of
+// that toolchain's libraries' lints.
+#[cfg(feature = "metadata")]
+fn manifest_lint_names<'a>(
+    opts: &Dylint,
+    resolved: &'a ToolchainMap,
+    manifest_lints: &ManifestLints,
+) -> Result<BTreeMap<&'a str, BTreeSet<String>>> {
+    let mut manifest_lint_names = BTreeMap::new();
+
+    if manifest_lints.is_empty() {
+        return Ok(manifest_lint_names);
+    }
+
+    for (toolchain, paths) in resolved {
+        let driver = driver_builder::get(opts, toolchain)?;
+        let lint_names = driver_listings(toolchain, &driver, &lib_paths::encode(paths), None)?
+            .into_iter()
+            .flat_map(|listing| listing.lints)
+            .map(|lint| lint.name)
+            .collect::<BTreeSet<_>>();
+        manifest_lint_names.insert(toolchain.as_str(), lint_names);
+    }
+
+    for name in manifest_lints.names() {
+        if !manifest_lint_names
+            .values()
+            .any(|lint_names| lint_names.contains(&manifest_lints::normalize(name)))
+        {
+            warn(
+                opts,
+                &format!(
+                    "`{name}` in a `[lints.dylint]` table is not a lint in any of the libraries \
+                     being checked"
+                ),
+            );
         }
     }
 
-    if failures.is_empty() {
-        Ok(())
+    Ok(manifest_lint_names)
+}
+
+// smoelius: The remappings that apply to the check are those in the flags Cargo passes to `rustc`
+// and those in the flags Dylint passes to the driver. Cargo uses `CARGO_ENCODED_RUSTFLAGS` rather
+// than `RUSTFLAGS` if both are set, and uses `build.rustflags` from its configuration only if
+// neither is set.
+fn path_remapping(dylint_rustflags: &str) -> Result<diagnostics::PathRemapping> {
+    let mut rustflags = if let Ok(encoded) = std::env::var(env::CARGO_ENCODED_RUSTFLAGS) {
+        encoded
+            .split('\x1f')
+            .filter(|rustflag| !rustflag.is_empty())
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>()
+    } else if let Ok(rustflags) = std::env::var(env::RUSTFLAGS) {
+        rustflags
+            .split_whitespace()
+            .map(ToOwned::to_owned)
+            .collect()
+    } else {
+        build_rustflags()?
+    };
+    rustflags.extend(dylint_rustflags.split_whitespace().map(ToOwned::to_owned));
+    Ok(diagnostics::PathRemapping::from_rustflags(&rustflags))
+}
+
+// smoelius: `cargo check` is run in the current directory, so Cargo's configuration is read from
+// there too. Note that `target.<triple>.rustflags` and `target.<cfg>.rustflags` are not consulted.
+#[cfg(feature = "metadata")]
+fn build_rustflags() -> Result<Vec<String>> {
+    let config = util::Config::default()?;
+    Ok(config
+        .build_config()?
+        .rustflags
+        .as_ref()
+        .map(|rustflags| rustflags.as_slice().to_vec())
+        .unwrap_or_default())
+}
+
+// smoelius: Without the `metadata` feature, Dylint does not depend on `cargo`, and so cannot read
+// Cargo's configuration.
+#[cfg(not(feature = "metadata"))]
+#[allow(clippy::unnecessary_wraps)]
+fn build_rustflags() -> Result<Vec<String>> {
+    Ok(Vec::new())
+}
+
+// smoelius: The driver splits `DYLINT_RUSTFLAGS` on whitespace. Flags from the environment come
+// first, then flags from the workspace metadata, then flags from the command line.
+#[cfg_attr(not(feature = "metadata"), allow(unused_variables))]
+fn dylint_rustflags(opts: &Dylint, metadata: &Metadata) -> Result<String> {
+    let mut rustflags = std::env::var(env::DYLINT_RUSTFLAGS)
+        .map(|rustflags| {
+            rustflags
+                .split_whitespace()
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    #[cfg(feature = "metadata")]
+    if !opts.no_metadata {
+        rustflags.extend(metadata::rustflags(metadata)?);
+    }
+
+    rustflags.extend(
+        opts.rustflags
+            .iter()
+            .flat_map(|rustflags| rustflags.split_whitespace().map(ToOwned::to_owned)),
+    );
+
+    Ok(rustflags.join(" "))
+}
+
+// smoelius: Passing the workspace's `dylint.toml` file's contents saves the driver from running
+// `cargo metadata` for each crate to find the file. The contents passed are those of the merged
+// configuration, so the driver need not follow `extends` keys. A `DYLINT_TOML` set by the user
+// takes precedence.
+fn dylint_toml(metadata: &Metadata) -> Result<Option<String>> {
+    if std::env::var_os(env::DYLINT_TOML).is_some() {
+        return Ok(None);
+    }
+    workspace_config(metadata)?
+        .map(|config| config.to_toml_string())
+        .transpose()
+}
+
+fn workspace_config(metadata: &Metadata) -> Result<Option<Config>> {
+    let path = metadata.workspace_root.join("dylint.toml");
+    if !path.exists() {
+        return Ok(None);
+    }
+    Config::load(path.as_std_path()).map(Some)
+}
+
+fn explain_config(opts: &Dylint) -> Result<()> {
+    let config = if let Ok(value) = std::env::var(env::DYLINT_TOML) {
+        Some(Config::parse(env::DYLINT_TOML, &value, &current_dir()?)?)
+    } else {
+        workspace_config(&workspace_metadata(opts)?)?
+    };
+    if let Some(config) = config {
+        print!("{}", config.explain());
     } else {
-        Err(anyhow!(
-            "Compilation failed with the following toolchains: {:?}",
-            failures
-        ))
+        warn(opts, "No `dylint.toml` file was found.");
     }
+    Ok(())
+}
+
+fn target_dir(metadata: &Metadata, toolchain: &str) -> PathBuf {
+    verbatim::long(
+        metadata
+            .target_directory
+            .join("dylint/target")
+            .join(toolchain)
+            .as_std_path(),
+    )
 }
 
-fn target_dir(opts: &Dylint, toolchain: &str) -> Result<PathBuf> {
+fn workspace_metadata(opts: &Dylint) -> Result<Metadata> {
     let mut command = MetadataCommand::new();
     if let Some(path) = &opts.manifest_path {
         command.manifest_path(path);
     }
-    let metadata = command.no_deps().exec()?;
-    Ok(metadata
-        .target_directory
-        .join("dylint/target")
-        .join(toolchain)
-        .into())
+    command
+        .no_deps()
+        .other_options(cargo_flags(opts))
+        .exec()
+        .map_err(Into::into)
+}
+
+/// Returns the `--frozen`, `--locked`, and `--offline` flags to pass to the `cargo` commands run on
+/// the user's behalf, including `cargo metadata`.
+fn cargo_flags(opts: &Dylint) -> Vec<String> {
+    [
+        (opts.frozen, "--frozen"),
+        (opts.locked, "--locked"),
+        (opts.offline, "--offline"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, flag)| enabled.then(|| flag.to_owned()))
+    .collect()
 }
 
 fn clippy_disable_docs_links() -> Result<String> {
@@ -0,0 +1,387 @@
+use crate::{error::warn, library_copies::link_or_copy, run_state::fingerprint, Dylint};
+use anyhow::{anyhow, Context, Result};
+use cargo::util::{Config, FileLock, Filesystem};
+use dylint_internal::{env, library_filename};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{
+        create_dir_all, read_dir, read_to_string, remove_dir, remove_dir_all, remove_file, rename,
+    },
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+const KEY_FILENAME: &str = "key.json";
+
+const LOCK_FILENAME: &str = ".lock";
+
+const PROFILE: &str = "release";
+
+// smoelius: Libraries built from the same commit of the same git repository, with the same
+// toolchain, profile, and features, are the same no matter which workspace names them. So the
+// first workspace to build such a library stores it in a global cache, and other workspaces link
+// or copy it from there rather than build it again. Each cache entry is a directory holding the
+// library and a `key.json` file describing how it was built. A process holds a lock on an entry
+// while building and storing its library, so that two processes building the same library do not
+// corrupt the entry. The second process waits for the first and then uses what the first stored.
+
+/// What determines a library built from a git repository
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Key {
+    pub url: String,
+    pub commit: String,
+    /// The library package's directory, relative to the repository's root
+    pub path: PathBuf,
+    pub lib_name: String,
+    pub toolchain: String,
+    pub profile: String,
+    /// The features enabled in addition to the package's default features
+    pub features: Vec<String>,
+}
+
+impl Key {
+    /// Returns the key of a library built the way workspace metadata entries are built, i.e., with
+    /// the release profile and the package's default features
+    pub fn new(url: &str, commit: &str, path: &Path, lib_name: &str, toolchain: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            commit: commit.to_owned(),
+            path: path.to_path_buf(),
+            lib_name: lib_name.to_owned(),
+            toolchain: toolchain.to_owned(),
+            profile: PROFILE.to_owned(),
+            features: Vec::new(),
+        }
+    }
+
+    fn entry_name(&self) -> String {
+        format!("{}-{}", self.lib_name, fingerprint(self))
+    }
+
+    fn filename(&self) -> String {
+        library_filename::compose(&self.lib_name, &self.toolchain, None)
+    }
+}
+
+/// Makes the library that `key` describes available at `path`. If the library is in the cache, it
+/// is linked or copied from there. Otherwise, `build` is run, which should write the library to
+/// `path`, and the library is stored in the cache.
+pub fn build_or_copy(
+    opts: &Dylint,
+    key: &Key,
+    path: &Path,
+    build: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let entry = libraries_dir()?.join(key.entry_name());
+
+    let _lock = lock(&entry)?;
+
+    let cached = entry.join(key.filename());
+
+    if read_key(&entry).as_ref() == Some(key) && cached.exists() {
+        return place(&cached, path);
+    }
+
+    build()?;
+
+    if let Err(error) = store(&entry, key, path) {
+        warn(
+            opts,
+            &format!(
+                "Could not store `{}` in the library cache: {error:?}",
+                path.to_string_lossy()
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+/// Removes every entry from the cache and prints how much space was freed
+pub fn clean(opts: &Dylint) -> Result<()> {
+    let entries = entries()?;
+
+    let mut size = 0;
+
+    for entry in &entries {
+        remove_entry(opts, entry)?;
+        size += entry.size;
+    }
+
+    println!(
+        "Removed {} cached {} ({})",
+        entries.len(),
+        if entries.len() == 1 {
+            "library"
+        } else {
+            "libraries"
+        },
+        format_size(size)
+    );
+
+    Ok(())
+}
+
+/// Prints the cache's location and, for each entry, the library's name and toolchain, the commit
+/// it was built from, and the entry's size
+pub fn stats() -> Result<()> {
+    let entries = entries()?;
+
+    println!("Location: {}", libraries_dir()?.to_string_lossy());
+
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            let (lib_name, toolchain, source) = entry.key.as_ref().map_or_else(
+                || ("<unknown>".to_owned(), String::new(), String::new()),
+                |key| {
+                    (
+                        key.lib_name.clone(),
+                        key.toolchain.clone(),
+                        format!("{}@{}", key.url, key.commit),
+                    )
+                },
+            );
+            (lib_name, toolchain, format_size(entry.size), source)
+        })
+        .collect::<Vec<_>>();
+
+    let name_width = rows.iter().map(|row| row.0.len()).max().unwrap_or_default();
+    let toolchain_width = rows.iter().map(|row| row.1.len()).max().unwrap_or_default();
+    let size_width = rows.iter().map(|row| row.2.len()).max().unwrap_or_default();
+
+    for (name, toolchain, size, source) in rows {
+        println!(
+            "{name:<name_width$}  {toolchain:<toolchain_width$}  {size:>size_width$}  {source}"
+        );
+    }
+
+    println!(
+        "Total: {} {} ({})",
+        entries.len(),
+        if entries.len() == 1 {
+            "library"
+        } else {
+            "libraries"
+        },
+        format_size(entries.iter().map(|entry| entry.size).sum())
+    );
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Entry {
+    dir: PathBuf,
+    key: Option<Key>,
+    size: u64,
+}
+
+fn entries() -> Result<Vec<Entry>> {
+    let libraries_dir = libraries_dir()?;
+
+    if !libraries_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+
+    for entry in read_dir(&libraries_dir).with_context(|| {
+        format!(
+            "`read_dir` failed for `{}`",
+            libraries_dir.to_string_lossy()
+        )
+    })? {
+        let entry = entry.with_context(|| {
+            format!(
+                "`read_dir` failed for `{}`",
+                libraries_dir.to_string_lossy()
+            )
+        })?;
+        let dir = entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let size = WalkDir::new(&dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(std::fs::Metadata::is_file)
+            .map(|metadata| metadata.len())
+            .sum();
+        entries.push(Entry {
+            key: read_key(&dir),
+            dir,
+            size,
+        });
+    }
+
+    entries.sort_by(|x, y| x.key.cmp(&y.key).then_with(|| x.dir.cmp(&y.dir)));
+
+    Ok(entries)
+}
+
+// smoelius: An entry's lock file is removed only after the lock is released, because on Windows, a
+// file that is open cannot be removed. A process that acquires the lock in the meantime finds the
+// entry empty and rebuilds the library.
+fn remove_entry(opts: &Dylint, entry: &Entry) -> Result<()> {
+    {
+        let _lock = lock(&entry.dir)?;
+        for file in read_dir(&entry.dir)
+            .with_context(|| format!("`read_dir` failed for `{}`", entry.dir.to_string_lossy()))?
+        {
+            let file = file.with_context(|| {
+                format!("`read_dir` failed for `{}`", entry.dir.to_string_lossy())
+            })?;
+            let path = file.path();
+            if path.file_name() == Some(LOCK_FILENAME.as_ref()) {
+                continue;
+            }
+            if path.is_dir() {
+                remove_dir_all(&path)
+            } else {
+                remove_file(&path)
+            }
+            .with_context(|| format!("Could not remove `{}`", path.to_string_lossy()))?;
+        }
+    }
+
+    if let Err(error) =
+        remove_file(entry.dir.join(LOCK_FILENAME)).and_then(|()| remove_dir(&entry.dir))
+    {
+        warn(
+            opts,
+            &format!(
+                "Could not remove `{}`: {error}",
+                entry.dir.to_string_lossy()
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the directory holding the cache's entries: `libraries` in `DYLINT_CACHE_DIR` if it is
+/// set, and in `dylint` in the user's cache directory otherwise
+fn libraries_dir() -> Result<PathBuf> {
+    let cache_dir = if let Ok(cache_dir) = env::var(env::DYLINT_CACHE_DIR) {
+        PathBuf::from(cache_dir)
+    } else {
+        dirs::cache_dir()
+            .ok_or_else(|| anyhow!("Could not find cache directory"))?
+            .join("dylint")
+    };
+    Ok(cache_dir.join("libraries"))
+}
+
+fn lock(entry: &Path) -> Result<FileLock> {
+    let config = Config::default()?;
+    let description = format!(
+        "library cache entry `{}`",
+        entry
+            .file_name()
+            .map_or_else(|| entry.to_string_lossy(), |name| name.to_string_lossy())
+    );
+    Filesystem::new(entry.to_path_buf())
+        .open_rw(LOCK_FILENAME, &config, &description)
+        .with_context(|| format!("Could not lock `{}`", entry.to_string_lossy()))
+}
+
+fn read_key(entry: &Path) -> Option<Key> {
+    let contents = read_to_string(entry.join(KEY_FILENAME)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+// smoelius: The library is written to a temporary file and renamed, so that an interrupted store
+// does not leave a partial library in the entry. The key is written last. An entry is used only if
+// its key matches.
+fn store(entry: &Path, key: &Key, path: &Path) -> Result<()> {
+    let cached = entry.join(key.filename());
+    let temporary = entry.join(format!("{}.tmp", key.filename()));
+    std::fs::copy(path, &temporary).with_context(|| {
+        format!(
+            "Could not copy `{}` to `{}`",
+            path.to_string_lossy(),
+            temporary.to_string_lossy()
+        )
+    })?;
+    rename(&temporary, &cached).with_context(|| {
+        format!(
+            "Could not rename `{}` to `{}`",
+            temporary.to_string_lossy(),
+            cached.to_string_lossy()
+        )
+    })?;
+    let key_path = entry.join(KEY_FILENAME);
+    std::fs::write(&key_path, serde_json::to_string_pretty(key)?)
+        .with_context(|| format!("`write` failed for `{}`", key_path.to_string_lossy()))?;
+    Ok(())
+}
+
+// smoelius: A library already at `path` is removed first, because it could be a hard link to the
+// cached library, which must not be overwritten in place.
+fn place(cached: &Path, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).with_context(|| {
+            format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+        })?;
+    }
+    if path.exists() {
+        remove_file(path)
+            .with_context(|| format!("Could not remove `{}`", path.to_string_lossy()))?;
+    }
+    link_or_copy(cached, path)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for &next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!("0 B", format_size(0));
+        assert_eq!("1023 B", format_size(1023));
+        assert_eq!("1.0 KiB", format_size(1024));
+        assert_eq!("1.5 MiB", format_size(3 * 512 * 1024));
+        assert_eq!("2.0 TiB", format_size(2 * 1024 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn entry_names_differ_by_toolchain() {
+        let key = |toolchain| {
+            Key::new(
+                "https://github.com/trailofbits/dylint",
+                "0123456789abcdef0123456789abcdef01234567",
+                Path::new("examples/general/non_local_effect_before_error_return"),
+                "non_local_effect_before_error_return",
+                toolchain,
+            )
+        };
+        let x = key("nightly-2023-01-01");
+        let y = key("nightly-2023-01-02");
+        assert_eq!(x.entry_name(), key("nightly-2023-01-01").entry_name());
+        assert_ne!(x.entry_name(), y.entry_name());
+        assert!(x
+            .entry_name()
+            .starts_with("non_local_effect_before_error_return-"));
+    }
+}
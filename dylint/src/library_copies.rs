@@ -0,0 +1,165 @@
+use crate::{error::warn, Dylint};
+use anyhow::{anyhow, Context, Result};
+use cargo_metadata::Metadata;
+use dylint_internal::process_exists;
+use std::{
+    ffi::OsStr,
+    fs::{copy, create_dir_all, hard_link, read_dir, remove_dir_all},
+    path::{Path, PathBuf},
+    process,
+};
+
+const PREFIX: &str = "run-";
+
+// smoelius: The driver loads libraries from copies so that a library rebuilt during a run (e.g.,
+// by `cargo build` in another terminal) is never loaded half-written. The copies are kept in
+// `target/dylint/run-<pid>`, which is removed when the run ends. A run killed by ctrl-C does not
+// get the chance to remove its directory. So directories of processes that no longer exist are
+// removed when the next run starts.
+
+/// A run-private directory of library copies, removed when dropped
+#[derive(Debug)]
+pub struct LibraryCopies {
+    dir: PathBuf,
+}
+
+impl LibraryCopies {
+    pub fn new(opts: &Dylint, metadata: &Metadata) -> Result<Self> {
+        let parent = metadata.target_directory.join("dylint");
+        remove_stale(opts, parent.as_std_path());
+        let dir = parent.join(format!("{PREFIX}{}", process::id()));
+        // smoelius: A directory with this process's id can only have been left by an earlier
+        // process that was given the same id.
+        if dir.exists() {
+            remove_dir_all(&dir).with_context(|| format!("`remove_dir_all` failed for `{dir}`"))?;
+        }
+        Ok(Self {
+            dir: dir.into_std_path_buf(),
+        })
+    }
+
+    /// Copies `paths` into the directory and returns the copies' paths, in the same order. Each
+    /// copy keeps its original's filename, from which the driver determines the library's name.
+    pub fn copy<'a>(
+        &self,
+        toolchain: &str,
+        paths: impl IntoIterator<Item = &'a PathBuf>,
+    ) -> Result<Vec<PathBuf>> {
+        paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let filename = path.file_name().ok_or_else(|| {
+                    anyhow!("Could not get filename of `{}`", path.to_string_lossy())
+                })?;
+                let dir = self.dir.join(toolchain).join(i.to_string());
+                create_dir_all(&dir).with_context(|| {
+                    format!("`create_dir_all` failed for `{}`", dir.to_string_lossy())
+                })?;
+                let copy_path = dir.join(filename);
+                link_or_copy(path, &copy_path)?;
+                Ok(copy_path)
+            })
+            .collect()
+    }
+}
+
+impl Drop for LibraryCopies {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.dir);
+    }
+}
+
+// smoelius: A hard link is as good as a copy, because Cargo and `dylint-link` replace a library
+// rather than overwrite it in place. But a hard link cannot cross filesystems, e.g., when a library
+// is not under the target directory. In that case, the library is copied.
+pub fn link_or_copy(from: &Path, to: &Path) -> Result<()> {
+    if hard_link(from, to).is_ok() {
+        return Ok(());
+    }
+    copy(from, to).with_context(|| {
+        format!(
+            "Could not copy `{}` to `{}`",
+            from.to_string_lossy(),
+            to.to_string_lossy()
+        )
+    })?;
+    Ok(())
+}
+
+fn remove_stale(opts: &Dylint, parent: &Path) {
+    let entries = if let Ok(entries) = read_dir(parent) {
+        entries
+    } else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let pid = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .and_then(|filename| filename.strip_prefix(PREFIX))
+            .and_then(|pid| pid.parse::<u32>().ok());
+        if let Some(pid) = pid {
+            if pid == process::id() || process_exists(pid) || !path.is_dir() {
+                continue;
+            }
+            if let Err(error) = remove_dir_all(&path) {
+                warn(
+                    opts,
+                    &format!("Could not remove `{}`: {error}", path.to_string_lossy()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::{read, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn copies_survive_overwrite_and_are_removed() {
+        let tempdir = tempdir().unwrap();
+        let library = tempdir.path().join("libfoo@toolchain.so");
+        write(&library, "original").unwrap();
+
+        let library_copies = LibraryCopies {
+            dir: tempdir.path().join("run-0"),
+        };
+        let copies = library_copies.copy("toolchain", [&library]).unwrap();
+        assert_eq!(1, copies.len());
+        assert_eq!(Some(library.file_name().unwrap()), copies[0].file_name());
+
+        // smoelius: Replace the library the way `dylint-link` does.
+        let temporary = tempdir.path().join("libfoo@toolchain.so.tmp");
+        write(&temporary, "rebuilt").unwrap();
+        std::fs::rename(&temporary, &library).unwrap();
+        assert_eq!(b"original", read(&copies[0]).unwrap().as_slice());
+
+        drop(library_copies);
+        assert!(!tempdir.path().join("run-0").exists());
+        assert!(library.exists());
+    }
+
+    #[test]
+    fn stale_directories_are_removed() {
+        let tempdir = tempdir().unwrap();
+        let own = tempdir.path().join(format!("{PREFIX}{}", process::id()));
+        let stale = tempdir.path().join(format!("{PREFIX}{}", u32::MAX));
+        let unrelated = tempdir.path().join("run-state.json");
+        create_dir_all(&own).unwrap();
+        create_dir_all(&stale).unwrap();
+        write(&unrelated, "{}").unwrap();
+
+        remove_stale(&Dylint::default(), tempdir.path());
+
+        assert!(own.exists());
+        assert_eq!(cfg!(not(unix)), stale.exists());
+        assert!(unrelated.exists());
+    }
+}
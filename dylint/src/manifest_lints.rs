@@ -0,0 +1,293 @@
+use anyhow::{Context, Result};
+use cargo_metadata::Metadata;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::read_to_string,
+    path::Path,
+};
+
+// smoelius: Lint levels can be set in a `[lints.dylint]` table, just as Cargo lets `rustc`'s and
+// Clippy's be set in `[lints.rust]` and `[lints.clippy]` tables. A package with `workspace = true`
+// in its `[lints]` table inherits the `[workspace.lints.dylint]` table. As with Cargo, inheritance
+// is all or nothing: a package that does not inherit the workspace's table is unaffected by it.
+//
+// The driver passes a package's levels to `rustc` before any other flags. So flags from
+// `RUSTFLAGS`, from the `rustflags` metadata key, and from `--rustflags` override them.
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Forbid,
+    Deny,
+    Warn,
+    Allow,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Forbid => "forbid",
+            Self::Deny => "deny",
+            Self::Warn => "warn",
+            Self::Allow => "allow",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LintSpec {
+    Level(Level),
+    Detailed {
+        level: Level,
+        #[serde(default)]
+        priority: i8,
+    },
+}
+
+impl LintSpec {
+    fn level(&self) -> Level {
+        match self {
+            Self::Level(level) | Self::Detailed { level, .. } => *level,
+        }
+    }
+
+    fn priority(&self) -> i8 {
+        match self {
+            Self::Level(_) => 0,
+            Self::Detailed { priority, .. } => *priority,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    lints: Lints,
+    #[serde(default)]
+    workspace: Workspace,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Workspace {
+    #[serde(default)]
+    lints: Lints,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Lints {
+    #[serde(default)]
+    workspace: bool,
+    #[serde(default)]
+    dylint: BTreeMap<String, LintSpec>,
+}
+
+/// Lint levels from `[lints.dylint]` tables, keyed by the manifest directory of the package they
+/// apply to
+#[derive(Debug)]
+pub struct ManifestLints {
+    levels: BTreeMap<String, Vec<(String, Level)>>,
+}
+
+impl ManifestLints {
+    pub fn new(metadata: &Metadata) -> Result<Self> {
+        let workspace_manifest =
+            read_manifest(metadata.workspace_root.join("Cargo.toml").as_std_path())?;
+
+        let mut levels = BTreeMap::new();
+        for package in metadata.workspace_packages() {
+            let manifest = read_manifest(package.manifest_path.as_std_path())?;
+            let package_levels = package_levels(&workspace_manifest, &manifest);
+            if package_levels.is_empty() {
+                continue;
+            }
+            if let Some(manifest_dir) = package.manifest_path.parent() {
+                levels.insert(manifest_dir.to_string(), package_levels);
+            }
+        }
+
+        Ok(Self { levels })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Returns the names of the lints whose levels are set, as written in the tables
+    pub fn names(&self) -> BTreeSet<&str> {
+        self.levels
+            .values()
+            .flatten()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Returns the value of `DYLINT_MANIFEST_LINTS` for libraries declaring `lint_names`, i.e., a
+    /// JSON object mapping each manifest directory to its package's flags. Lints not among
+    /// `lint_names` are omitted, since `rustc` would otherwise warn about them in every crate.
+    pub fn to_json(&self, lint_names: &BTreeSet<String>) -> Result<String> {
+        let flags = self
+            .levels
+            .iter()
+            .map(|(manifest_dir, levels)| {
+                let flags = levels
+                    .iter()
+                    .filter(|(name, _)| lint_names.contains(&normalize(name)))
+                    .map(|(name, level)| format!("--{}={name}", level.as_str()))
+                    .collect::<Vec<_>>();
+                (manifest_dir, flags)
+            })
+            .collect::<BTreeMap<_, _>>();
+        serde_json::to_string(&flags).map_err(Into::into)
+    }
+}
+
+/// Returns a lint name as it appears in diagnostics, e.g., `lint_name` for `lint-name`
+pub fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest> {
+    let contents = read_to_string(path)
+        .with_context(|| format!("`read_to_string` failed for `{}`", path.to_string_lossy()))?;
+    ::toml::from_str(&contents)
+        .with_context(|| format!("Could not parse `[lints]` in `{}`", path.to_string_lossy()))
+}
+
+// smoelius: Like Cargo, sort the lints by priority and then by name. Flags that come later take
+// precedence, so a lint with a higher priority overrides one with a lower priority.
+fn package_levels(workspace_manifest: &Manifest, manifest: &Manifest) -> Vec<(String, Level)> {
+    let lints = if manifest.lints.workspace {
+        &workspace_manifest.workspace.lints.dylint
+    } else {
+        &manifest.lints.dylint
+    };
+
+    let mut lints = lints
+        .iter()
+        .map(|(name, spec)| (spec.priority(), name.clone(), spec.level()))
+        .collect::<Vec<_>>();
+    lints.sort();
+
+    lints
+        .into_iter()
+        .map(|(_, name, level)| (name, level))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    const WORKSPACE_MANIFEST: &str = r#"
+[workspace]
+members = ["a", "b"]
+
+[workspace.lints.dylint]
+const_path_join = "deny"
+"#;
+
+    fn manifest(s: &str) -> Manifest {
+        ::toml::from_str(s).unwrap()
+    }
+
+    fn flags(levels: &[(String, Level)]) -> Vec<String> {
+        let manifest_lints = ManifestLints {
+            levels: BTreeMap::from([("dir".to_owned(), levels.to_vec())]),
+        };
+        let lint_names = manifest_lints.names().into_iter().map(normalize).collect();
+        let json = manifest_lints.to_json(&lint_names).unwrap();
+        serde_json::from_str::<BTreeMap<String, Vec<String>>>(&json)
+            .unwrap()
+            .remove("dir")
+            .unwrap()
+    }
+
+    #[test]
+    fn inherit() {
+        let workspace_manifest = manifest(WORKSPACE_MANIFEST);
+
+        let levels = package_levels(
+            &workspace_manifest,
+            &manifest(
+                r#"
+[package]
+name = "a"
+
+[lints]
+workspace = true
+"#,
+            ),
+        );
+        assert_eq!(vec!["--deny=const_path_join"], flags(&levels));
+    }
+
+    #[test]
+    fn package_replaces_workspace() {
+        let workspace_manifest = manifest(WORKSPACE_MANIFEST);
+
+        let levels = package_levels(
+            &workspace_manifest,
+            &manifest(
+                r#"
+[package]
+name = "b"
+
+[lints.clippy]
+pedantic = "warn"
+
+[lints.dylint]
+env_literal = "warn"
+"#,
+            ),
+        );
+        assert_eq!(vec!["--warn=env_literal"], flags(&levels));
+
+        let levels = package_levels(&workspace_manifest, &manifest("[package]\nname = \"c\"\n"));
+        assert!(levels.is_empty());
+    }
+
+    #[test]
+    fn priority() {
+        let levels = package_levels(
+            &Manifest::default(),
+            &manifest(
+                r#"
+[lints.dylint]
+b = "warn"
+a = { level = "allow", priority = 1 }
+c = { level = "forbid", priority = -1 }
+"#,
+            ),
+        );
+        assert_eq!(vec!["--forbid=c", "--warn=b", "--allow=a"], flags(&levels));
+    }
+
+    #[test]
+    fn unknown_lints_are_omitted() {
+        let manifest_lints = ManifestLints {
+            levels: BTreeMap::from([(
+                "dir".to_owned(),
+                vec![
+                    ("const-path-join".to_owned(), Level::Deny),
+                    ("no_such_lint".to_owned(), Level::Warn),
+                ],
+            )]),
+        };
+        let lint_names = BTreeSet::from(["const_path_join".to_owned()]);
+        assert_eq!(
+            r#"{"dir":["--deny=const-path-join"]}"#,
+            manifest_lints.to_json(&lint_names).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_level() {
+        assert!(
+            ::toml::from_str::<Manifest>("[lints.dylint]\nconst_path_join = \"loud\"\n").is_err()
+        );
+    }
+}
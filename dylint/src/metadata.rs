@@ -1,90 +1,86 @@
 use crate::{
-    error::warn,
+    error::{failure, warn, FailureKind},
+    library_cache::{self, Key},
     toml::{self, DetailedTomlDependency},
 };
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use cargo::{
     core::{
-        source::MaybePackage, Dependency, Features, Package as CargoPackage, PackageId, QueryKind,
-        Source, SourceId,
+        source::MaybePackage, Dependency, Features, Package as CargoPackage, PackageId, PackageSet,
+        QueryKind, Source, SourceId, SourceMap,
     },
     util::Config,
 };
 use cargo_metadata::{Error, Metadata, MetadataCommand};
-use dylint_internal::{env, library_filename, rustup::SanitizeEnvironment};
+use dylint_internal::{env, library_filename, rustup::SanitizeEnvironment, verbatim};
 use glob::glob;
 use if_chain::if_chain;
-use serde::Deserialize;
+use semver::Version;
+use serde::{Deserialize, Serialize};
 use std::{
+    fs::read_to_string,
+    io::Read,
     path::{Path, PathBuf},
     rc::Rc,
+    task::Poll,
 };
 
-#[derive(Clone, Debug)]
+// smoelius: `Package` is serialized as part of the cached resolution (see
+// dylint/src/name_toolchain_map/cache.rs). So it records the workspace's target directory rather
+// than the workspace's `Metadata`.
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Package {
-    metadata: Rc<Metadata>,
     pub root: PathBuf,
     pub id: PackageId,
     pub lib_name: String,
     pub toolchain: String,
-}
-
-impl Eq for Package {}
-
-impl PartialEq for Package {
-    fn eq(&self, other: &Self) -> bool {
-        (&self.root, &self.id, &self.lib_name, &self.toolchain)
-            == (&other.root, &other.id, &other.lib_name, &other.toolchain)
-    }
-}
-
-impl Ord for Package {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (&self.root, &self.id, &self.lib_name, &self.toolchain).cmp(&(
-            &other.root,
-            &other.id,
-            &other.lib_name,
-            &other.toolchain,
-        ))
-    }
-}
-
-impl PartialOrd for Package {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (&self.root, &self.id, &self.lib_name, &self.toolchain).partial_cmp(&(
-            &other.root,
-            &other.id,
-            &other.lib_name,
-            &other.toolchain,
-        ))
-    }
+    workspace_target_directory: PathBuf,
+    /// For a package from a git repository, the key under which the library is stored in the
+    /// global library cache (see dylint/src/library_cache.rs)
+    cache_key: Option<Key>,
 }
 
 impl Package {
     pub fn target_directory(&self) -> PathBuf {
-        self.metadata
-            .target_directory
-            .join("dylint/libraries")
-            .join(&self.toolchain)
-            .into_std_path_buf()
+        verbatim::long(
+            &self
+                .workspace_target_directory
+                .join("dylint/libraries")
+                .join(&self.toolchain),
+        )
     }
 
     pub fn path(&self) -> PathBuf {
         self.target_directory()
             .join("release")
-            .join(library_filename(&self.lib_name, &self.toolchain))
+            .join(library_filename::compose(
+                &self.lib_name,
+                &self.toolchain,
+                None,
+            ))
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct Library {
     pattern: Option<String>,
+    #[serde(default, rename = "registry-source")]
+    registry_source: bool,
     #[serde(flatten)]
     details: DetailedTomlDependency,
 }
 
+// smoelius: A library specification passed with `--libs-from` has the same schema as
+// `[workspace.metadata.dylint]`, but may contain only `libraries`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LibrarySpecification {
+    #[serde(default)]
+    libraries: Vec<Library>,
+}
+
 pub fn workspace_metadata_packages(opts: &crate::Dylint) -> Result<Vec<Package>> {
-    if opts.no_metadata {
+    if opts.no_metadata && opts.libs_from.is_none() {
         return Ok(vec![]);
     }
 
@@ -94,15 +90,31 @@ pub fn workspace_metadata_packages(opts: &crate::Dylint) -> Result<Vec<Package>>
         command.manifest_path(path);
     }
 
+    command.other_options(crate::cargo_flags(opts));
+
     match command.exec() {
         Ok(metadata) => {
-            if let serde_json::Value::Object(object) = &metadata.workspace_metadata {
-                dylint_metadata_packages(opts, &Rc::new(metadata.clone()), object)
-            } else {
-                Ok(vec![])
+            let metadata = Rc::new(metadata);
+            let mut packages = if_chain! {
+                if !opts.no_metadata && !opts.libs_from_only;
+                if let serde_json::Value::Object(object) = &metadata.workspace_metadata;
+                then {
+                    dylint_metadata_packages(opts, &metadata, object)?
+                } else {
+                    vec![]
+                }
+            };
+            if let Some(libs_from) = &opts.libs_from {
+                packages.extend(libs_from_packages(opts, &metadata, libs_from)?);
             }
+            Ok(packages)
         }
         Err(err) => {
+            // smoelius: Libraries from `--libs-from` are built in the workspace's target directory,
+            // like the workspace's own metadata entries. So a workspace is required.
+            if opts.libs_from.is_some() {
+                return Err(err).with_context(|| "`--libs-from` requires a Cargo workspace");
+            }
             if opts.manifest_path.is_none() {
                 if_chain! {
                     if let Error::CargoMetadata { stderr } = err;
@@ -129,13 +141,19 @@ fn dylint_metadata_packages(
         if let serde_json::Value::Object(object) = value {
             let libraries = object
                 .iter()
-                .map(|(key, value)| {
-                    if key == "libraries" {
+                .map(|(key, value)| match key.as_str() {
+                    "libraries" => {
                         let libraries = serde_json::from_value::<Vec<Library>>(value.clone())?;
-                        library_packages(opts, metadata, &libraries)
-                    } else {
-                        bail!("Unknown key `{}`", key)
+                        library_packages(
+                            opts,
+                            metadata,
+                            metadata.workspace_root.as_std_path(),
+                            &libraries,
+                        )
                     }
+                    // smoelius: These keys are handled by `path_filter_globs` and `rustflags`.
+                    "exclude_paths" | "include_paths" | "rustflags" => Ok(vec![]),
+                    _ => bail!("Unknown key `{}`", key),
                 })
                 .collect::<Result<Vec<_>>>()?;
             Ok(libraries.into_iter().flatten().collect())
@@ -147,16 +165,103 @@ fn dylint_metadata_packages(
     }
 }
 
+/// Returns the `include_paths` and `exclude_paths` globs listed under
+/// `[workspace.metadata.dylint]`.
+pub fn path_filter_globs(metadata: &Metadata) -> Result<(Vec<String>, Vec<String>)> {
+    let globs = |key: &str| -> Result<Vec<String>> {
+        metadata
+            .workspace_metadata
+            .get("dylint")
+            .and_then(|dylint| dylint.get(key))
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .with_context(|| format!("`{key}` value must be a list of strings"))
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    };
+    Ok((globs("include_paths")?, globs("exclude_paths")?))
+}
+
+/// Returns the flags listed under `rustflags` in `[workspace.metadata.dylint]`.
+pub fn rustflags(metadata: &Metadata) -> Result<Vec<String>> {
+    let rustflags = metadata
+        .workspace_metadata
+        .get("dylint")
+        .and_then(|dylint| dylint.get("rustflags"))
+        .map(|value| {
+            serde_json::from_value::<Vec<String>>(value.clone())
+                .with_context(|| "`rustflags` value must be a list of strings")
+        })
+        .transpose()?
+        .unwrap_or_default();
+    if let Some(rustflag) = rustflags
+        .iter()
+        .find(|rustflag| rustflag.contains(char::is_whitespace))
+    {
+        bail!(
+            "`rustflags` entries cannot contain whitespace; split `{}` into separate entries",
+            rustflag
+        );
+    }
+    Ok(rustflags)
+}
+
+/// Returns the packages named in the library specification passed with `--libs-from`. `libs_from`
+/// is either a path to a TOML file or `-`, meaning standard input. Relative `path` entries are
+/// resolved relative to the file's directory, or to the current directory for standard input.
+fn libs_from_packages(
+    opts: &crate::Dylint,
+    metadata: &Rc<Metadata>,
+    libs_from: &str,
+) -> Result<Vec<Package>> {
+    let current_dir = std::env::current_dir().with_context(|| "Could not get current directory")?;
+
+    let (contents, root, source) = if libs_from == "-" {
+        let mut contents = String::new();
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .with_context(|| "Could not read library specification from standard input")?;
+        (contents, current_dir, String::from("standard input"))
+    } else {
+        let path = current_dir.join(libs_from);
+        let contents = read_to_string(&path)
+            .with_context(|| format!("`read_to_string` failed for `{}`", path.to_string_lossy()))?;
+        let root = path
+            .parent()
+            .ok_or_else(|| anyhow!("Could not get parent directory"))?
+            .to_path_buf();
+        (contents, root, format!("`{libs_from}`"))
+    };
+
+    let specification = ::toml::from_str::<LibrarySpecification>(&contents)
+        .with_context(|| format!("Could not parse library specification from {source}"))?;
+
+    library_packages(opts, metadata, &root, &specification.libraries)
+}
+
 fn library_packages(
     opts: &crate::Dylint,
     metadata: &Rc<Metadata>,
+    root: &Path,
     libraries: &[Library],
 ) -> Result<Vec<Package>> {
-    let config = Config::default()?;
+    let mut config = Config::default()?;
+    config.configure(
+        0,
+        false,
+        None,
+        opts.frozen,
+        opts.locked,
+        opts.offline,
+        &None,
+        &[],
+        &[],
+    )?;
 
     let packages = libraries
         .iter()
-        .map(|library| library_package(opts, metadata, &config, library))
+        .map(|library| library_package(opts, metadata, root, &config, library))
         .collect::<Result<Vec<_>>>()
         .with_context(|| "Could not build metadata entries")?;
 
@@ -166,14 +271,31 @@ fn library_packages(
 fn library_package(
     opts: &crate::Dylint,
     metadata: &Rc<Metadata>,
+    root: &Path,
     config: &Config,
     library: &Library,
 ) -> Result<Vec<Package>> {
-    let dep = dependency(opts, metadata, config, library)?;
+    if library.registry_source {
+        ensure!(
+            library.details.package().is_some(),
+            "A `registry-source` entry must name its package, e.g., `package = \"acme_lints\"`"
+        );
+    }
+
+    let dep = dependency(opts, root, config, library)?;
+
+    ensure!(
+        library.registry_source == dep.source_id().is_registry(),
+        if library.registry_source {
+            "A `registry-source` entry cannot have a `git` or `path` field"
+        } else {
+            "An entry without a `git` or `path` field must have `registry-source = true`"
+        }
+    );
 
     // smoelius: The dependency root cannot be canonicalized here. It could contain a `glob` pattern
     // (e.g., `*`), because Dylint allows `path` entries to contain `glob` patterns.
-    let dependency_root = dependency_root(config, &dep)?;
+    let dependency_root = dependency_root(config, &dep, library.details.version())?;
 
     let pattern = if let Some(pattern) = &library.pattern {
         dependency_root.join(pattern)
@@ -214,6 +336,15 @@ fn library_package(
         pattern.to_string_lossy()
     );
 
+    // smoelius: A git repository's checkout is named after a prefix of the commit it holds, not the
+    // whole commit. So the commit is read from the checkout.
+    let revision = if dep.source_id().is_git() {
+        let commit = dylint_internal::head_commit(&dependency_root)?;
+        Some((dep.source_id().url().to_string(), commit))
+    } else {
+        None
+    };
+
     // smoelius: Collecting the package ids before building reveals missing/unparsable `Cargo.toml`
     // files sooner.
 
@@ -227,15 +358,33 @@ fn library_package(
         .into_iter()
         .map(|path| {
             if path.is_dir() {
-                let package_id = package_id(dep.source_id(), &path)?;
-                let lib_name = package_library_name(&path)?;
+                let package_id = package_id(opts, dep.source_id(), &path)?;
+                if dep.source_id().is_registry() {
+                    ensure_rust_toolchain(&path, package_id)?;
+                }
+                let lib_name = package_library_name(opts, &path)?;
                 let toolchain = dylint_internal::rustup::active_toolchain(&path)?;
+                let cache_key = revision
+                    .as_ref()
+                    .map(|(url, commit)| -> Result<_> {
+                        let relative_path =
+                            path.strip_prefix(&dependency_root).with_context(|| {
+                                format!(
+                                    "`{}` is not in `{}`",
+                                    path.to_string_lossy(),
+                                    dependency_root.to_string_lossy()
+                                )
+                            })?;
+                        Ok(Key::new(url, commit, relative_path, &lib_name, &toolchain))
+                    })
+                    .transpose()?;
                 Ok(Some(Package {
-                    metadata: metadata.clone(),
                     root: path,
                     id: package_id,
                     lib_name,
                     toolchain,
+                    workspace_target_directory: metadata.target_directory.clone().into(),
+                    cache_key,
                 }))
             } else {
                 Ok(None)
@@ -248,7 +397,7 @@ fn library_package(
 
 fn dependency(
     opts: &crate::Dylint,
-    metadata: &Metadata,
+    root: &Path,
     config: &Config,
     library: &Library,
 ) -> Result<Dependency> {
@@ -267,8 +416,7 @@ fn dependency(
     let name_in_toml = "library";
 
     let mut deps = vec![];
-    let root = PathBuf::from(&metadata.workspace_root);
-    let source_id = SourceId::for_path(&root)?;
+    let source_id = SourceId::for_path(root)?;
     let mut nested_paths = vec![];
     let mut warnings = vec![];
     let features = Features::new(&[], config, &mut warnings, source_id.is_path())?;
@@ -279,7 +427,7 @@ fn dependency(
         config,
         &mut warnings,
         None,
-        &root,
+        root,
         &features,
     );
 
@@ -294,7 +442,7 @@ fn dependency(
     Ok(dependency)
 }
 
-fn dependency_root(config: &Config, dep: &Dependency) -> Result<PathBuf> {
+fn dependency_root(config: &Config, dep: &Dependency, version: Option<&str>) -> Result<PathBuf> {
     let source_id = dep.source_id();
 
     if source_id.is_path() {
@@ -305,8 +453,13 @@ fn dependency_root(config: &Config, dep: &Dependency) -> Result<PathBuf> {
         }
     } else if source_id.is_git() {
         git_dependency_root(config, dep)
+    } else if source_id.is_registry() {
+        registry_dependency_root(config, dep, version)
     } else {
-        bail!("Only git and path entries are supported: {}", source_id)
+        bail!(
+            "Only git, path, and registry entries are supported: {}",
+            source_id
+        )
     }
 }
 
@@ -350,6 +503,106 @@ fn sample_package_id(dep: &Dependency, source: &mut dyn Source) -> Result<Packag
     package_id.ok_or_else(|| anyhow!("Found no packages in `{}`", dep.source_id()))
 }
 
+// smoelius: Cargo downloads the package and unpacks it in its registry cache (e.g.,
+// `~/.cargo/registry/src`), just as it would for a dependency. The package is built from there.
+fn registry_dependency_root(
+    config: &Config,
+    dep: &Dependency,
+    version: Option<&str>,
+) -> Result<PathBuf> {
+    let _lock = config.acquire_package_cache_lock()?;
+
+    #[allow(clippy::default_trait_access)]
+    let mut source = dep.source_id().load(config, &Default::default())?;
+
+    let package_id = if let Some(package_id) = latest_package_id(dep, &mut *source)? {
+        package_id
+    } else {
+        // smoelius: Cargo's registry sources hide yanked versions. So if the entry names a
+        // specific version, check whether that is why nothing was found.
+        if let Some(version) = version.and_then(exact_version) {
+            let package_id = PackageId::new(dep.package_name(), &version, dep.source_id())?;
+            ensure!(
+                !is_yanked(&mut *source, package_id)?,
+                "Version {} of `{}` has been yanked from {}; name a version that has not been \
+                 yanked",
+                version,
+                dep.package_name(),
+                dep.source_id()
+            );
+        }
+        bail!(
+            "Found no version of `{}` matching `{}` in {}",
+            dep.package_name(),
+            dep.version_req(),
+            dep.source_id()
+        );
+    };
+
+    let mut sources = SourceMap::new();
+    sources.insert(source);
+    let package_set = PackageSet::new(&[package_id], sources, config)?;
+    let package = package_set.get_one(package_id)?;
+
+    Ok(package.root().to_path_buf())
+}
+
+#[cfg_attr(
+    dylint_lib = "non_local_effect_before_error_return",
+    allow(non_local_effect_before_error_return)
+)]
+fn latest_package_id(dep: &Dependency, source: &mut dyn Source) -> Result<Option<PackageId>> {
+    let mut package_id: Option<PackageId> = None;
+
+    loop {
+        let poll = source.query(dep, QueryKind::Exact, &mut |summary| {
+            let candidate = summary.package_id();
+            if package_id.map_or(true, |package_id| {
+                package_id.version() < candidate.version()
+            }) {
+                package_id = Some(candidate);
+            }
+        })?;
+        if poll.is_pending() {
+            source.block_until_ready()?;
+        } else {
+            return Ok(package_id);
+        }
+    }
+}
+
+fn is_yanked(source: &mut dyn Source, package_id: PackageId) -> Result<bool> {
+    loop {
+        if let Poll::Ready(yanked) = source.is_yanked(package_id) {
+            return yanked;
+        }
+        source.block_until_ready()?;
+    }
+}
+
+/// Returns the version that `version_req` names, if it is of the form `x.y.z` or `=x.y.z`
+fn exact_version(version_req: &str) -> Option<Version> {
+    Version::parse(version_req.trim().trim_start_matches('=').trim()).ok()
+}
+
+// smoelius: A library's toolchain is determined by its `rust-toolchain` file. Without one, `rustup`
+// would silently use the default toolchain, and the build would likely fail in confusing ways. A
+// git or path library is expected to be fixed by its authors. But a published package cannot be
+// changed, so the error explains what to do instead.
+fn ensure_rust_toolchain(package_root: &Path, package_id: PackageId) -> Result<()> {
+    let has_rust_toolchain = ["rust-toolchain", "rust-toolchain.toml"]
+        .iter()
+        .any(|filename| package_root.join(filename).exists());
+    ensure!(
+        has_rust_toolchain,
+        "Version {} of `{}` was published without a `rust-toolchain` file, so the toolchain to \
+         build it with is unknown; use a `git` entry that names the library's repository instead",
+        package_id.version(),
+        package_id.name()
+    );
+    Ok(())
+}
+
 fn git_dependency_root_from_package<'a>(
     config: &'a Config,
     source: &(dyn Source + 'a),
@@ -374,10 +627,11 @@ fn git_dependency_root_from_package<'a>(
     }
 }
 
-fn package_id(source_id: SourceId, package_root: &Path) -> Result<PackageId> {
+fn package_id(opts: &crate::Dylint, source_id: SourceId, package_root: &Path) -> Result<PackageId> {
     let metadata = MetadataCommand::new()
         .current_dir(package_root)
         .no_deps()
+        .other_options(crate::cargo_flags(opts))
         .exec()?;
 
     let package = dylint_internal::cargo::package_with_root(&metadata, package_root)?;
@@ -385,10 +639,11 @@ fn package_id(source_id: SourceId, package_root: &Path) -> Result<PackageId> {
     PackageId::new(&package.name, &package.version, source_id)
 }
 
-pub fn package_library_name(package_root: &Path) -> Result<String> {
+pub fn package_library_name(opts: &crate::Dylint, package_root: &Path) -> Result<String> {
     let metadata = MetadataCommand::new()
         .current_dir(package_root)
         .no_deps()
+        .other_options(crate::cargo_flags(opts))
         .exec()?;
 
     let package = dylint_internal::cargo::package_with_root(&metadata, package_root)?;
@@ -417,28 +672,69 @@ pub fn build_library(opts: &crate::Dylint, package: &Package) -> Result<PathBuf>
     let path = package.path();
 
     if !opts.no_build {
-        // smoelius: Clear `RUSTFLAGS` so that changes to it do not cause workspace metadata entries
-        // to be rebuilt.
-        dylint_internal::cargo::build(
-            &format!("workspace metadata entry `{}`", package.id.name()),
-            opts.quiet,
-        )
-        .sanitize_environment()
-        .env_remove(env::RUSTFLAGS)
-        .current_dir(&package.root)
-        .args(["--release", "--target-dir", &target_dir.to_string_lossy()])
-        .success()?;
-
-        let exists = path
-            .try_exists()
-            .with_context(|| format!("Could not determine whether {path:?} exists"))?;
-
-        ensure!(exists, "Could not find {path:?} despite successful build");
+        let description = format!("workspace metadata entry `{}`", package.id.name());
+
+        crate::run_state::run(
+            &format!("build `{}`", path.to_string_lossy()),
+            || library_fingerprint(package, &path),
+            || {
+                let build = || {
+                    // smoelius: Clear `RUSTFLAGS` so that changes to it do not cause workspace
+                    // metadata entries to be rebuilt.
+                    crate::timings::time(
+                        opts,
+                        || format!("build {description} with toolchain `{}`", package.toolchain),
+                        || {
+                            dylint_internal::cargo::build(&description, opts.quiet)
+                                .sanitize_environment()
+                                .env_remove(env::RUSTFLAGS)
+                                .current_dir(&package.root)
+                                .args(["--release", "--target-dir", &target_dir.to_string_lossy()])
+                                .args(crate::cargo_flags(opts))
+                                .success()
+                        },
+                    )
+                    .map_err(|error| failure(FailureKind::LibraryBuild, error))?;
+
+                    let exists = path
+                        .try_exists()
+                        .with_context(|| format!("Could not determine whether {path:?} exists"))?;
+
+                    ensure!(exists, "Could not find {path:?} despite successful build");
+
+                    Ok(())
+                };
+
+                if let Some(cache_key) = &package.cache_key {
+                    library_cache::build_or_copy(opts, cache_key, &path, build)
+                } else {
+                    build()
+                }
+            },
+        )?;
     }
 
     Ok(path)
 }
 
+// smoelius: A library's fingerprint covers its package's sources, as `dylint_internal::sources`
+// determines them, and the built library.
+fn library_fingerprint(package: &Package, path: &Path) -> Result<String> {
+    let sources = dylint_internal::sources::Sources::new(&package.root)?
+        .files()?
+        .into_iter()
+        .map(|file| {
+            let fingerprint = crate::run_state::file_fingerprint(&file);
+            (file, fingerprint)
+        })
+        .collect::<Vec<_>>();
+    Ok(crate::run_state::fingerprint(&(
+        &package.toolchain,
+        sources,
+        crate::run_state::file_fingerprint(path),
+    )))
+}
+
 // smoelius: `pkg_dir` and `target_short_hash` are based on functions with the same names in
 // https://github.com/rust-lang/cargo/blob/master/src/cargo/core/compiler/context/compilation_files.rs
 
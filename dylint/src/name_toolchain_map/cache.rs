@@ -0,0 +1,242 @@
+use super::NameToolchainMap;
+use crate::{
+    run_state::{file_fingerprint, fingerprint},
+    Dylint,
+};
+use anyhow::{Context, Result};
+use dylint_internal::env;
+use serde::{Deserialize, Serialize};
+use std::{
+    env::{current_dir, split_paths},
+    ffi::OsString,
+    fs::{create_dir_all, read_to_string, rename, write},
+    path::{Path, PathBuf},
+};
+
+const FILENAME: &str = "resolution.json";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// smoelius: Resolving library names means reading the directories in `DYLINT_LIBRARY_PATH` and,
+// for workspace metadata entries, running `cargo metadata` and possibly fetching git repositories.
+// So the result is written to `target/dylint/resolution.json` and reused as long as the inputs it
+// was computed from are unchanged. Note that the cache does not notice a new commit on a git
+// branch named in the workspace metadata. `--no-cache` forces a full resolution.
+
+/// A resolved name-toolchain map, as written to `target/dylint/resolution.json`
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Resolution {
+    version: String,
+    /// Fingerprint of the environment variables, options, and workspace metadata that determine
+    /// the resolution
+    environment: String,
+    /// Length and modification time of each file and directory the resolution depends upon, or
+    /// `None` if there was no such file or directory
+    inputs: Vec<(PathBuf, Option<(u64, u128)>)>,
+    /// Where each library was found, i.e., in a `DYLINT_LIBRARY_PATH` directory or in the
+    /// workspace metadata
+    name_toolchain_map: NameToolchainMap,
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    dylint_library_path: Option<OsString>,
+    libs_from: Option<PathBuf>,
+    environment: String,
+}
+
+impl Cache {
+    /// Returns `None` if the resolution should not be cached, e.g., if `--no-cache` was passed or
+    /// if there is no workspace
+    pub fn new(opts: &Dylint) -> Option<Self> {
+        // smoelius: A resolution that reads standard input is not cached.
+        if opts.no_cache || opts.libs_from.as_deref() == Some("-") {
+            return None;
+        }
+        let metadata = crate::workspace_metadata(opts).ok()?;
+        let libs_from = match &opts.libs_from {
+            Some(libs_from) => Some(current_dir().ok()?.join(libs_from)),
+            None => None,
+        };
+        Some(Self::from_parts(
+            opts,
+            metadata
+                .target_directory
+                .join("dylint")
+                .join(FILENAME)
+                .as_std_path(),
+            std::env::var_os(env::DYLINT_LIBRARY_PATH),
+            libs_from,
+            metadata.workspace_root.as_std_path(),
+            metadata.workspace_metadata.get("dylint"),
+        ))
+    }
+
+    fn from_parts(
+        opts: &Dylint,
+        path: &Path,
+        dylint_library_path: Option<OsString>,
+        libs_from: Option<PathBuf>,
+        workspace_root: &Path,
+        dylint_metadata: Option<&serde_json::Value>,
+    ) -> Self {
+        let environment = fingerprint(&(
+            &dylint_library_path,
+            &libs_from,
+            workspace_root,
+            dylint_metadata.map(ToString::to_string),
+            opts.no_metadata,
+            opts.libs_from_only,
+        ));
+        Self {
+            path: path.to_path_buf(),
+            dylint_library_path,
+            libs_from,
+            environment,
+        }
+    }
+
+    /// Returns the cached resolution if it was written by this version of Dylint and none of its
+    /// inputs changed
+    pub fn load(&self) -> Option<NameToolchainMap> {
+        let contents = read_to_string(&self.path).ok()?;
+        let resolution = serde_json::from_str::<Resolution>(&contents).ok()?;
+        let fresh = resolution.version == VERSION
+            && resolution.environment == self.environment
+            && resolution
+                .inputs
+                .iter()
+                .all(|(path, fingerprint)| file_fingerprint(path) == *fingerprint);
+        if fresh {
+            Some(resolution.name_toolchain_map)
+        } else {
+            None
+        }
+    }
+
+    // smoelius: As with the run state, the resolution is written to a temporary file that is then
+    // renamed, so that concurrent runs never read a partially written resolution.
+    pub fn store(&self, name_toolchain_map: &NameToolchainMap) -> Result<()> {
+        let resolution = Resolution {
+            version: VERSION.to_owned(),
+            environment: self.environment.clone(),
+            inputs: self
+                .inputs(name_toolchain_map)
+                .into_iter()
+                .map(|path| {
+                    let fingerprint = file_fingerprint(&path);
+                    (path, fingerprint)
+                })
+                .collect(),
+            name_toolchain_map: name_toolchain_map.clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent).with_context(|| {
+                format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+            })?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        write(&tmp, serde_json::to_string_pretty(&resolution)? + "\n")
+            .with_context(|| format!("`write` failed for `{}`", tmp.to_string_lossy()))?;
+        rename(&tmp, &self.path).with_context(|| {
+            format!(
+                "Could not rename `{}` to `{}`",
+                tmp.to_string_lossy(),
+                self.path.to_string_lossy()
+            )
+        })?;
+        Ok(())
+    }
+
+    fn inputs(&self, name_toolchain_map: &NameToolchainMap) -> Vec<PathBuf> {
+        let mut inputs = self
+            .dylint_library_path
+            .as_ref()
+            .map(split_paths)
+            .into_iter()
+            .flatten()
+            .chain(self.libs_from.clone())
+            .chain(
+                name_toolchain_map
+                    .values()
+                    .flat_map(|toolchain_map| toolchain_map.values())
+                    .flatten()
+                    .flat_map(super::MaybeLibrary::inputs),
+            )
+            .collect::<Vec<_>>();
+        inputs.sort();
+        inputs.dedup();
+        inputs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::name_toolchain_map::MaybeLibrary;
+    use std::fs::{remove_dir_all, File};
+    use tempfile::tempdir;
+
+    fn name_toolchain_map(library: PathBuf) -> NameToolchainMap {
+        NameToolchainMap::from([(
+            "foo".to_owned(),
+            [(
+                "toolchain".to_owned(),
+                [MaybeLibrary::from(library)].into_iter().collect(),
+            )]
+            .into_iter()
+            .collect(),
+        )])
+    }
+
+    fn cache(tempdir: &Path, dylint_library_path: &Path) -> Cache {
+        Cache::from_parts(
+            &Dylint::default(),
+            &tempdir.join("target/dylint").join(FILENAME),
+            Some(dylint_library_path.as_os_str().to_owned()),
+            None,
+            tempdir,
+            None,
+        )
+    }
+
+    #[test]
+    fn resolution_is_reused_until_an_input_changes() {
+        let tempdir = tempdir().unwrap();
+        let library_dir = tempdir.path().join("libraries");
+        create_dir_all(&library_dir).unwrap();
+        let library = library_dir.join("libfoo@toolchain.so");
+        File::create(&library).unwrap();
+
+        let cache = cache(tempdir.path(), &library_dir);
+        assert_eq!(None, cache.load());
+
+        let name_toolchain_map = name_toolchain_map(library);
+        cache.store(&name_toolchain_map).unwrap();
+        assert_eq!(Some(&name_toolchain_map), cache.load().as_ref());
+
+        remove_dir_all(&library_dir).unwrap();
+        assert_eq!(None, cache.load());
+    }
+
+    #[test]
+    fn resolution_is_stale_if_dylint_library_path_changes() {
+        let tempdir = tempdir().unwrap();
+        let library_dirs = [tempdir.path().join("a"), tempdir.path().join("b")];
+        for library_dir in &library_dirs {
+            create_dir_all(library_dir).unwrap();
+        }
+        let library = library_dirs[0].join("libfoo@toolchain.so");
+        File::create(&library).unwrap();
+
+        cache(tempdir.path(), &library_dirs[0])
+            .store(&name_toolchain_map(library))
+            .unwrap();
+
+        assert!(cache(tempdir.path(), &library_dirs[0]).load().is_some());
+        assert_eq!(None, cache(tempdir.path(), &library_dirs[1]).load());
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct MaybeLibrary {
     inner: Inner,
 }
@@ -14,6 +15,30 @@ impl MaybeLibrary {
     pub fn build(&self, opts: &crate::Dylint) -> Result<PathBuf> {
         self.inner.build(opts)
     }
+
+    /// Returns the root of the library's package, if the library is built from a path in the
+    /// workspace metadata
+    pub fn source_dir(&self) -> Option<PathBuf> {
+        self.inner.source_dir()
+    }
+
+    /// Returns the name of the library's package, if the library is built from the workspace
+    /// metadata. The package name can differ from the library name, e.g., if the package's
+    /// `Cargo.toml` file sets `lib.name`.
+    pub fn package_name(&self) -> Option<String> {
+        self.inner.package_name()
+    }
+
+    /// Returns true if the library was found in a directory listed in `DYLINT_LIBRARY_PATH`, as
+    /// opposed to being built from the workspace metadata
+    pub const fn is_path(&self) -> bool {
+        matches!(self.inner, Inner::Path(_))
+    }
+
+    /// Returns the files and directories whose changes could change how the library is resolved
+    pub fn inputs(&self) -> Vec<PathBuf> {
+        self.inner.inputs()
+    }
 }
 
 impl From<PathBuf> for MaybeLibrary {
@@ -33,7 +58,7 @@ impl From<crate::metadata::Package> for MaybeLibrary {
     }
 }
 
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum Inner {
     Path(PathBuf),
 
@@ -51,6 +76,50 @@ impl Inner {
         }
     }
 
+    fn source_dir(&self) -> Option<PathBuf> {
+        match self {
+            Self::Path(_) => None,
+
+            #[cfg(feature = "metadata")]
+            Self::Package(package) => package
+                .id
+                .source_id()
+                .is_path()
+                .then(|| package.root.clone()),
+        }
+    }
+
+    fn package_name(&self) -> Option<String> {
+        match self {
+            Self::Path(_) => None,
+
+            #[cfg(feature = "metadata")]
+            Self::Package(package) => Some(package.id.name().to_string()),
+        }
+    }
+
+    // smoelius: A package's library name comes from its `Cargo.toml` file, and its toolchain from
+    // its `rust-toolchain` file. A `pattern` could match a new package in the package's parent
+    // directory.
+    fn inputs(&self) -> Vec<PathBuf> {
+        match self {
+            Self::Path(path) => path.parent().map(Path::to_path_buf).into_iter().collect(),
+
+            #[cfg(feature = "metadata")]
+            Self::Package(package) => package
+                .root
+                .parent()
+                .map(Path::to_path_buf)
+                .into_iter()
+                .chain([package.root.clone()])
+                .chain(
+                    ["Cargo.toml", "rust-toolchain", "rust-toolchain.toml"]
+                        .map(|filename| package.root.join(filename)),
+                )
+                .collect(),
+        }
+    }
+
     #[cfg_attr(not(feature = "metadata"), allow(unused_variables))]
     fn build(&self, opts: &crate::Dylint) -> Result<PathBuf> {
         match self {
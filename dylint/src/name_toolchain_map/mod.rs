@@ -1,5 +1,6 @@
-use anyhow::{ensure, Context, Result};
-use dylint_internal::{env, parse_path_filename};
+use crate::error::warn;
+use anyhow::{bail, ensure, Context, Result};
+use dylint_internal::{env, library_filename, rustup::installed_toolchains};
 use once_cell::sync::OnceCell;
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -8,9 +9,15 @@ use std::{
     path::{Path, PathBuf},
 };
 
+mod cache;
+use cache::Cache;
+
 mod maybe_library;
 pub use maybe_library::MaybeLibrary;
 
+mod near_misses;
+use near_misses::near_misses;
+
 pub type ToolchainMap = BTreeMap<String, BTreeSet<PathBuf>>;
 
 #[allow(clippy::redundant_pub_crate)]
@@ -19,7 +26,6 @@ pub(crate) type NameToolchainMap = BTreeMap<String, LazyToolchainMap>;
 #[allow(clippy::redundant_pub_crate)]
 pub(crate) type LazyToolchainMap = BTreeMap<String, BTreeSet<MaybeLibrary>>;
 
-#[cfg_attr(not(feature = "metadata"), allow(dead_code))]
 struct Inner<'opts> {
     opts: &'opts crate::Dylint,
     name_toolchain_map: OnceCell<NameToolchainMap>,
@@ -44,39 +50,234 @@ impl<'opts> Lazy<'opts> {
         self.inner
             .name_toolchain_map
             .get_or_try_init(|| -> Result<_> {
-                let mut name_toolchain_map = NameToolchainMap::new();
-
-                let dylint_library_paths = dylint_library_paths()?;
-
-                #[cfg(feature = "metadata")]
-                let workspace_metadata_packages =
-                    crate::metadata::workspace_metadata_packages(self.inner.opts)?;
-
-                for path in dylint_library_paths {
-                    for entry in dylint_libraries_in(&path)? {
-                        let (name, toolchain, path) = entry?;
-                        name_toolchain_map
-                            .entry(name)
-                            .or_insert_with(Default::default)
-                            .entry(toolchain)
-                            .or_insert_with(Default::default)
-                            .insert(MaybeLibrary::from(path));
-                    }
+                let cache = Cache::new(self.inner.opts);
+
+                if let Some(name_toolchain_map) = cache.as_ref().and_then(Cache::load) {
+                    return Ok(name_toolchain_map);
                 }
 
-                #[cfg(feature = "metadata")]
-                for package in workspace_metadata_packages {
-                    name_toolchain_map
-                        .entry(package.lib_name.clone())
-                        .or_insert_with(Default::default)
-                        .entry(package.toolchain.clone())
-                        .or_insert_with(Default::default)
-                        .insert(MaybeLibrary::from(package));
+                let name_toolchain_map = resolve(self.inner.opts)?;
+
+                if let Some(cache) = &cache {
+                    if let Err(error) = cache.store(&name_toolchain_map) {
+                        warn(
+                            self.inner.opts,
+                            &format!("Could not write resolution cache: {error:?}"),
+                        );
+                    }
                 }
 
                 Ok(name_toolchain_map)
             })
     }
+
+    /// Returns the libraries that `name` refers to, either by their library names or, for libraries
+    /// built from the workspace metadata, by their package names. `-` and `_` are treated as
+    /// equivalent. If `name` refers to some libraries by their library names and others by their
+    /// package names, a warning is emitted and the former are returned.
+    pub fn toolchain_map_for(&self, name: &str) -> Result<Option<LazyToolchainMap>> {
+        let name_toolchain_map = self.get_or_try_init()?;
+
+        let normalized_name = normalize(name);
+
+        let mut by_lib_name = LazyToolchainMap::new();
+        let mut by_package_name = LazyToolchainMap::new();
+        let mut package_lib_names = BTreeSet::new();
+
+        for (lib_name, toolchain_map) in name_toolchain_map {
+            for (toolchain, maybe_libraries) in toolchain_map {
+                for maybe_library in maybe_libraries {
+                    let toolchain_map = if normalize(lib_name) == normalized_name {
+                        &mut by_lib_name
+                    } else if maybe_library.package_name().map_or(false, |package_name| {
+                        normalize(&package_name) == normalized_name
+                    }) {
+                        package_lib_names.insert(lib_name.as_str());
+                        &mut by_package_name
+                    } else {
+                        continue;
+                    };
+                    toolchain_map
+                        .entry(toolchain.clone())
+                        .or_insert_with(Default::default)
+                        .insert(maybe_library.clone());
+                }
+            }
+        }
+
+        if by_lib_name.is_empty() {
+            return Ok(Some(by_package_name).filter(|map| !map.is_empty()));
+        }
+
+        if !by_package_name.is_empty() {
+            warn(
+                self.inner.opts,
+                &format!(
+                    "`{name}` is the name of a library and of a package whose library is named {}; \
+                     using the library named `{name}`",
+                    package_lib_names
+                        .iter()
+                        .map(|lib_name| format!("`{lib_name}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+        }
+
+        Ok(Some(by_lib_name))
+    }
+
+    /// Removes from `toolchain_map` the libraries found in `DYLINT_LIBRARY_PATH` whose toolchains
+    /// are not installed, unless `--allow-uninstalled-toolchain` was passed. If every library in
+    /// `toolchain_map` is removed, an error listing them is returned.
+    pub fn retain_installed(&self, name: &str, toolchain_map: &mut LazyToolchainMap) -> Result<()> {
+        if self.inner.opts.allow_uninstalled_toolchain
+            || !toolchain_map.values().flatten().any(MaybeLibrary::is_path)
+        {
+            return Ok(());
+        }
+
+        let installed = installed_toolchains().context(
+            "Could not determine the installed toolchains; to skip this check, use \
+             `--allow-uninstalled-toolchain`",
+        )?;
+
+        let mut uninstalled = Vec::new();
+
+        toolchain_map.retain(|toolchain, maybe_libraries| {
+            if is_installed(toolchain, &installed) {
+                return true;
+            }
+            maybe_libraries.retain(|maybe_library| {
+                if maybe_library.is_path() {
+                    uninstalled.push((toolchain.clone(), maybe_library.path()));
+                    false
+                } else {
+                    true
+                }
+            });
+            !maybe_libraries.is_empty()
+        });
+
+        if toolchain_map.is_empty() {
+            bail!(
+                "Found only libraries matching `{}` whose toolchains are not installed:{}\
+                 \n  = help: rebuild the library with an installed toolchain, or install its \
+                 toolchain, e.g., with `rustup toolchain install {}`\
+                 \n  = help: to use the library anyway, use `--allow-uninstalled-toolchain`",
+                name,
+                uninstalled
+                    .iter()
+                    .map(|(toolchain, path)| format!(
+                        "\n    {} (toolchain `{toolchain}`)",
+                        path.to_string_lossy()
+                    ))
+                    .collect::<String>(),
+                uninstalled
+                    .first()
+                    .map_or_else(String::new, |(toolchain, _)| toolchain.clone())
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns notes to append to an error saying that `names` were not found: for each name, the
+    /// library names closest to it (unless `--exact` was passed), and the directories in which
+    /// libraries were looked for
+    pub fn not_found_notes(&self, names: &[&str]) -> Result<String> {
+        let name_toolchain_map = self.get_or_try_init()?;
+
+        let mut notes = String::new();
+
+        if !self.inner.opts.exact {
+            for name in names {
+                let near_misses = near_misses(name, name_toolchain_map.keys().map(String::as_str));
+                if near_misses.is_empty() {
+                    continue;
+                }
+                notes.push_str(&format!(
+                    "\n  = help: the library names closest to `{name}` are: {}",
+                    near_misses
+                        .iter()
+                        .map(|near_miss| format!("`{near_miss}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+
+        let mut dirs = dylint_library_paths()?.into_iter().collect::<BTreeSet<_>>();
+        dirs.extend(
+            name_toolchain_map
+                .values()
+                .flat_map(BTreeMap::values)
+                .flatten()
+                .filter_map(|maybe_library| maybe_library.path().parent().map(Path::to_path_buf)),
+        );
+
+        if dirs.is_empty() {
+            notes.push_str(
+                "\n  = note: no directories were searched; `DYLINT_LIBRARY_PATH` is not set, and \
+                 no libraries are named in the workspace metadata",
+            );
+        } else {
+            notes.push_str("\n  = note: libraries were looked for in:");
+            for dir in dirs {
+                notes.push_str(&format!("\n      {}", dir.to_string_lossy()));
+            }
+        }
+
+        Ok(notes)
+    }
+}
+
+#[cfg_attr(not(feature = "metadata"), allow(unused_variables))]
+fn resolve(opts: &crate::Dylint) -> Result<NameToolchainMap> {
+    let mut name_toolchain_map = NameToolchainMap::new();
+
+    let dylint_library_paths = dylint_library_paths()?;
+
+    #[cfg(feature = "metadata")]
+    let workspace_metadata_packages = crate::metadata::workspace_metadata_packages(opts)?;
+
+    for path in dylint_library_paths {
+        for (name, toolchain, path) in dylint_libraries_in(&path)? {
+            name_toolchain_map
+                .entry(name)
+                .or_insert_with(Default::default)
+                .entry(toolchain)
+                .or_insert_with(Default::default)
+                .insert(MaybeLibrary::from(path));
+        }
+    }
+
+    #[cfg(feature = "metadata")]
+    for package in workspace_metadata_packages {
+        name_toolchain_map
+            .entry(package.lib_name.clone())
+            .or_insert_with(Default::default)
+            .entry(package.toolchain.clone())
+            .or_insert_with(Default::default)
+            .insert(MaybeLibrary::from(package));
+    }
+
+    Ok(name_toolchain_map)
+}
+
+// smoelius: Cargo normalizes a library name's `-`s to `_`s, but not a package name's.
+fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+// smoelius: A library's filename can name its toolchain with or without the host, e.g.,
+// `nightly-2023-06-29-x86_64-unknown-linux-gnu` or `nightly-2023-06-29`. `rustup toolchain list`
+// always includes the host.
+fn is_installed(toolchain: &str, installed: &[String]) -> bool {
+    let with_host = format!("{toolchain}-{}", env!("TARGET"));
+    installed
+        .iter()
+        .any(|installed| installed == toolchain || *installed == with_host)
 }
 
 fn dylint_library_paths() -> Result<Vec<PathBuf>> {
@@ -101,20 +302,105 @@ fn dylint_library_paths() -> Result<Vec<PathBuf>> {
     Ok(paths)
 }
 
-fn dylint_libraries_in(
-    path: &Path,
-) -> Result<impl Iterator<Item = Result<(String, String, PathBuf)>>> {
+// smoelius: A library whose filename names a target other than the host (e.g., one built with
+// `cargo build --target aarch64-unknown-linux-gnu`) could not be loaded by the driver, so it is
+// skipped. A directory can hold both a library whose filename names no target and one whose
+// filename names the host, e.g., if a package was built both with and without `--target`. Rather
+// than report them as ambiguous, the one whose filename names the host is preferred.
+fn dylint_libraries_in(path: &Path) -> Result<Vec<(String, String, PathBuf)>> {
     let iter = read_dir(path)
         .with_context(|| format!("`read_dir` failed for `{}`", path.to_string_lossy()))?;
-    let path_buf = path.to_path_buf();
-    Ok(iter
-        .map(move |entry| -> Result<Option<(String, String, PathBuf)>> {
-            let entry = entry.with_context(|| {
-                format!("`read_dir` failed for `{}`", path_buf.to_string_lossy())
-            })?;
-            let path = entry.path();
-
-            Ok(parse_path_filename(&path).map(|(lib_name, toolchain)| (lib_name, toolchain, path)))
-        })
-        .filter_map(Result::transpose))
+    let mut libraries = BTreeMap::new();
+    for entry in iter {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", path.to_string_lossy()))?;
+        let path = entry.path();
+        if let Some((lib_name, toolchain, target)) = library_filename::parse_path(&path) {
+            if !is_host_target(target.as_deref()) {
+                continue;
+            }
+            let key = (lib_name, toolchain);
+            if target.is_some() || !libraries.contains_key(&key) {
+                libraries.insert(key, path);
+            }
+        }
+    }
+    Ok(libraries
+        .into_iter()
+        .map(|((lib_name, toolchain), path)| (lib_name, toolchain, path))
+        .collect())
+}
+
+/// Returns true if a library whose filename names `target` can be loaded on the host. A library
+/// whose filename names no target is assumed to have been built for the host.
+#[must_use]
+pub fn is_host_target(target: Option<&str>) -> bool {
+    target.map_or(true, |target| target == env!("TARGET"))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    const TOOLCHAIN: &str = "nightly-2023-06-29";
+
+    #[test]
+    fn host_target_is_preferred() {
+        let tempdir = tempdir().unwrap();
+        let plain = tempdir
+            .path()
+            .join(library_filename::compose("foo", TOOLCHAIN, None));
+        let host = tempdir.path().join(library_filename::compose(
+            "foo",
+            TOOLCHAIN,
+            Some(env!("TARGET")),
+        ));
+        let cross = tempdir.path().join(library_filename::compose(
+            "foo",
+            TOOLCHAIN,
+            Some("no_such_arch-unknown-none"),
+        ));
+        let other = tempdir
+            .path()
+            .join(library_filename::compose("bar", TOOLCHAIN, None));
+        for path in [&plain, &host, &cross, &other] {
+            write(path, "").unwrap();
+        }
+
+        assert_eq!(
+            vec![
+                ("bar".to_owned(), TOOLCHAIN.to_owned(), other),
+                ("foo".to_owned(), TOOLCHAIN.to_owned(), host),
+            ],
+            dylint_libraries_in(tempdir.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn installed_with_or_without_host() {
+        let installed = [format!("{TOOLCHAIN}-{}", env!("TARGET"))];
+        assert!(is_installed(TOOLCHAIN, &installed));
+        assert!(is_installed(&installed[0], &installed));
+        assert!(!is_installed("nightly-2000-01-01", &installed));
+    }
+
+    #[test]
+    fn cross_target_is_skipped() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join(library_filename::compose(
+                "foo",
+                TOOLCHAIN,
+                Some("no_such_arch-unknown-none"),
+            )),
+            "",
+        )
+        .unwrap();
+
+        assert!(dylint_libraries_in(tempdir.path()).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,65 @@
+// smoelius: When a library name is not found, the names of the libraries that were found are
+// suggested in order of their edit distances from the name, so that a typo is easy to spot. Names
+// are compared with `-` replaced by `_`, since that is how library filenames are written.
+
+const MAX_NEAR_MISSES: usize = 3;
+
+/// Returns up to three of `candidates`, those closest to `name` by edit distance. Ties are broken
+/// alphabetically.
+pub fn near_misses<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let name = name.replace('-', "_");
+    let mut candidates = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, candidate), candidate))
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+        .into_iter()
+        .take(MAX_NEAR_MISSES)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+// smoelius: Levenshtein distance, computed one row at a time.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != *b_char);
+            curr.push(substitution.min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(0, edit_distance("abc", "abc"));
+        assert_eq!(
+            1,
+            edit_distance("question_mark_in_expresion", "question_mark_in_expression")
+        );
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(3, edit_distance("", "abc"));
+        assert_eq!(3, edit_distance("abc", ""));
+    }
+
+    #[test]
+    fn closest_three() {
+        assert_eq!(
+            vec!["fo_bar", "foo_baz", "foo_qux"],
+            near_misses(
+                "foo-bar",
+                ["completely_different", "foo_qux", "foo_baz", "fo_bar"]
+            )
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::Dylint;
 use anyhow::{anyhow, Context, Result};
-use dylint_internal::{rustup::SanitizeEnvironment, Command};
+use dylint_internal::{rustup::SanitizeEnvironment, verbatim, Command};
 use is_terminal::IsTerminal;
 use std::os::unix::fs::PermissionsExt;
 use std::{
@@ -69,9 +69,8 @@ pub fn bisect(opts: &Dylint, path: &Path, start: &str) -> Result<()> {
 
     let script = script()?;
 
-    let test_dir = path
-        .canonicalize()
-        .with_context(|| format!("Could not canonicalize {path:?}"))?;
+    let test_dir =
+        verbatim::canonicalize(path).with_context(|| format!("Could not canonicalize {path:?}"))?;
 
     remove_temporary_files(path);
 
@@ -0,0 +1,380 @@
+use crate::{
+    error::{failure, note, FailureKind},
+    warn, Dylint,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use cargo_metadata::{MetadataCommand, Package};
+use dylint_internal::{
+    cargo::package_with_root,
+    env,
+    library_filename::{self, Affixes},
+    rustup::SanitizeEnvironment,
+    Command,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{canonicalize, copy, create_dir_all, read, write},
+    path::{Path, PathBuf},
+};
+
+/// The name of the file, in the dist directory, that lists the artifacts in JSON
+const MANIFEST_FILENAME: &str = "manifest.json";
+
+/// The name of the file, in the dist directory, that lists the artifacts' SHA-256 sums in the
+/// format `sha256sum` uses
+const SHA256SUMS_FILENAME: &str = "SHA256SUMS";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DistMetadata {
+    platforms: Vec<Platform>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Platform {
+    toolchain: String,
+    target: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    name: String,
+    version: String,
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Serialize)]
+struct Artifact {
+    toolchain: String,
+    target: String,
+    path: String,
+    sha256: String,
+}
+
+pub fn dist_package(opts: &Dylint, path: &Path) -> Result<()> {
+    let path = canonicalize(path)
+        .with_context(|| format!("Could not canonicalize `{}`", path.to_string_lossy()))?;
+
+    if !opts.allow_dirty {
+        ensure_clean(&path)?;
+    }
+
+    let metadata = MetadataCommand::new()
+        .current_dir(&path)
+        .no_deps()
+        .exec()
+        .with_context(|| format!("Could not get metadata for `{}`", path.to_string_lossy()))?;
+    let package = package_with_root(&metadata, &path)?;
+    let platforms = platforms(&package)?;
+    let lib_name = lib_name(&package)?;
+
+    let target_dir = metadata.target_directory.as_std_path();
+    let out_dir = opts
+        .dist_out_dir
+        .as_ref()
+        .map_or_else(|| target_dir.join("dylint/dist"), PathBuf::from);
+    create_dir_all(&out_dir).with_context(|| {
+        format!(
+            "`create_dir_all` failed for `{}`",
+            out_dir.to_string_lossy()
+        )
+    })?;
+
+    let mut artifacts = Vec::new();
+    let mut failures = Vec::new();
+    for platform in &platforms {
+        match build(opts, &package, &lib_name, platform, target_dir, &out_dir) {
+            Ok(artifact) => artifacts.push(artifact),
+            Err(error) => {
+                let error = error.context(format!(
+                    "Could not build for toolchain `{}`{}",
+                    platform.toolchain,
+                    platform
+                        .target
+                        .as_ref()
+                        .map(|target| format!(" and target `{target}`"))
+                        .unwrap_or_default()
+                ));
+                if !opts.keep_going {
+                    return Err(failure(FailureKind::LibraryBuild, error));
+                }
+                warn(opts, &format!("{error:?}"));
+                failures.push(error.to_string());
+            }
+        }
+    }
+
+    let manifest = Manifest {
+        name: package.name.clone(),
+        version: package.version.to_string(),
+        artifacts,
+    };
+    let manifest_path = write_manifest(&out_dir, &manifest)?;
+
+    if !failures.is_empty() {
+        return Err(failure(
+            FailureKind::LibraryBuild,
+            anyhow!(
+                "Could not build for {} of {} platforms:\n{}",
+                failures.len(),
+                platforms.len(),
+                failures.join("\n")
+            ),
+        ));
+    }
+
+    if opts.dist_tag {
+        tag(opts, &path, &manifest, &manifest_path)?;
+    }
+
+    note(
+        opts,
+        &format!(
+            "Wrote {} artifact(s) to `{}`",
+            manifest.artifacts.len(),
+            out_dir.to_string_lossy()
+        ),
+    );
+
+    Ok(())
+}
+
+// smoelius: Like `cargo package`, refuse to build from sources that differ from what is committed.
+// Only the package's own directory is considered.
+fn ensure_clean(path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(["status", "--porcelain", "--", "."])
+        .output()
+        .with_context(|| {
+            format!(
+                "Could not determine whether `{}` has uncommitted changes. Use `--allow-dirty` to \
+                 override.",
+                path.to_string_lossy()
+            )
+        })?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    ensure!(
+        stdout.trim().is_empty(),
+        "`{}` has uncommitted changes. Commit them, or use `--allow-dirty` to override:\n{}",
+        path.to_string_lossy(),
+        stdout.trim_end()
+    );
+    Ok(())
+}
+
+fn platforms(package: &Package) -> Result<Vec<Platform>> {
+    let value = package
+        .metadata
+        .get("dylint")
+        .and_then(|value| value.get("dist"))
+        .ok_or_else(|| {
+            anyhow!(
+                "Package `{}` has no `[package.metadata.dylint.dist]` table",
+                package.name
+            )
+        })?;
+    let dist = DistMetadata::deserialize(value)
+        .with_context(|| "Could not parse `[package.metadata.dylint.dist]`")?;
+    ensure!(
+        !dist.platforms.is_empty(),
+        "`[package.metadata.dylint.dist]` lists no platforms"
+    );
+    Ok(dist.platforms)
+}
+
+fn lib_name(package: &Package) -> Result<String> {
+    package
+        .targets
+        .iter()
+        .find(|target| target.kind.iter().any(|kind| kind == "cdylib"))
+        .map(|target| target.name.replace('-', "_"))
+        .ok_or_else(|| anyhow!("Package `{}` has no `cdylib` target", package.name))
+}
+
+fn build(
+    opts: &Dylint,
+    package: &Package,
+    lib_name: &str,
+    platform: &Platform,
+    target_dir: &Path,
+    out_dir: &Path,
+) -> Result<Artifact> {
+    let toolchain = &platform.toolchain;
+
+    let target = match &platform.target {
+        Some(target) => target.clone(),
+        None => host(toolchain)?,
+    };
+
+    ensure_target_installed(toolchain, &target)?;
+
+    let package_root = package
+        .manifest_path
+        .parent()
+        .ok_or_else(|| anyhow!("Could not get parent directory"))?;
+
+    // smoelius: Each toolchain gets its own target directory, so that building with one toolchain
+    // does not invalidate the artifacts of another.
+    let target_dir = target_dir.join("dylint/dist-build").join(toolchain);
+    dylint_internal::cargo::build(
+        &format!(
+            "`{}` with toolchain `{toolchain}` for target `{target}`",
+            package.name
+        ),
+        opts.quiet,
+    )
+    .sanitize_environment()
+    .envs([(env::RUSTUP_TOOLCHAIN, toolchain)])
+    .current_dir(package_root)
+    .args([
+        "--release",
+        "--target",
+        &target,
+        "--target-dir",
+        &target_dir.to_string_lossy(),
+    ])
+    .success()?;
+
+    let affixes = Affixes::for_target(&target);
+    let built = target_dir
+        .join(&target)
+        .join("release")
+        .join(format!("{}{lib_name}{}", affixes.prefix, affixes.suffix));
+
+    // smoelius: Each artifact is written to a directory named for its target. So, like a library
+    // built without `--target`, its filename does not name the target.
+    let filename = library_filename::compose_with(affixes, lib_name, toolchain, None);
+    let dest_dir = out_dir.join(&target);
+    create_dir_all(&dest_dir).with_context(|| {
+        format!(
+            "`create_dir_all` failed for `{}`",
+            dest_dir.to_string_lossy()
+        )
+    })?;
+    let dest = dest_dir.join(&filename);
+    copy(&built, &dest).with_context(|| {
+        format!(
+            "Could not copy `{}` to `{}`",
+            built.to_string_lossy(),
+            dest.to_string_lossy()
+        )
+    })?;
+
+    let contents =
+        read(&dest).with_context(|| format!("`read` failed for `{}`", dest.to_string_lossy()))?;
+
+    Ok(Artifact {
+        toolchain: toolchain.clone(),
+        target: target.clone(),
+        path: format!("{target}/{filename}"),
+        sha256: format!("{:x}", Sha256::digest(contents)),
+    })
+}
+
+fn host(toolchain: &str) -> Result<String> {
+    let output = Command::new("rustup")
+        .sanitize_environment()
+        .args(["run", toolchain, "rustc", "-vV"])
+        .output()
+        .with_context(|| format!("Toolchain `{toolchain}` is not installed"))?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("Could not determine host of toolchain `{toolchain}`"))
+}
+
+fn ensure_target_installed(toolchain: &str, target: &str) -> Result<()> {
+    let output = Command::new("rustup")
+        .sanitize_environment()
+        .args(["target", "list", "--installed", "--toolchain", toolchain])
+        .output()
+        .with_context(|| format!("Toolchain `{toolchain}` is not installed"))?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    ensure!(
+        stdout.lines().any(|line| line.trim() == target),
+        "Target `{target}` is not installed for toolchain `{toolchain}`"
+    );
+    Ok(())
+}
+
+fn write_manifest(out_dir: &Path, manifest: &Manifest) -> Result<PathBuf> {
+    let sums = manifest
+        .artifacts
+        .iter()
+        .map(|artifact| format!("{}  {}\n", artifact.sha256, artifact.path))
+        .collect::<String>();
+    let sums_path = out_dir.join(SHA256SUMS_FILENAME);
+    write(&sums_path, sums)
+        .with_context(|| format!("`write` failed for `{}`", sums_path.to_string_lossy()))?;
+
+    let json = serde_json::to_string_pretty(manifest)?;
+    let manifest_path = out_dir.join(MANIFEST_FILENAME);
+    write(&manifest_path, json + "\n")
+        .with_context(|| format!("`write` failed for `{}`", manifest_path.to_string_lossy()))?;
+
+    Ok(manifest_path)
+}
+
+fn tag(opts: &Dylint, path: &Path, manifest: &Manifest, manifest_path: &Path) -> Result<()> {
+    let tag = format!("{}-v{}", manifest.name, manifest.version);
+    if Command::new("git")
+        .current_dir(path)
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/tags/{tag}"),
+        ])
+        .output()
+        .is_ok()
+    {
+        bail!("Tag `{tag}` already exists");
+    }
+    Command::new("git")
+        .current_dir(path)
+        .args([
+            "tag",
+            "--annotate",
+            "--file",
+            &manifest_path.to_string_lossy(),
+            &tag,
+        ])
+        .success()
+        .with_context(|| format!("Could not create tag `{tag}`"))?;
+    note(opts, &format!("Created tag `{tag}`"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn metadata() {
+        let dist = DistMetadata::deserialize(serde_json::json!({
+            "platforms": [
+                { "toolchain": "nightly-2023-06-29" },
+                { "toolchain": "nightly-2023-06-29", "target": "aarch64-apple-darwin" },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(2, dist.platforms.len());
+        assert_eq!(None, dist.platforms[0].target);
+        assert_eq!(
+            Some("aarch64-apple-darwin"),
+            dist.platforms[1].target.as_deref()
+        );
+
+        assert!(DistMetadata::deserialize(serde_json::json!({
+            "platforms": [{ "toolchain": "nightly-2023-06-29", "triple": "x" }]
+        }))
+        .is_err());
+    }
+}
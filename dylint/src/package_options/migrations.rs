@@ -0,0 +1,291 @@
+use anyhow::{Context, Result};
+use cargo_metadata::{diagnostic::DiagnosticLevel, Message};
+use dylint_internal::{find_and_replace, rustup::SanitizeEnvironment, Command};
+use std::{
+    ffi::OsStr,
+    fs::read_to_string,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+// smoelius: Each entry below describes a breaking change to `rustc`'s API that can be fixed with
+// simple syntactic rewrites. The rewrites are meant to handle the common cases only; whatever they
+// miss is left for the compiler to report. `date` is that of the first nightly toolchain known to
+// include the change.
+
+pub struct Migration {
+    pub date: [u32; 3],
+    pub description: &'static str,
+    /// Find-and-replace commands in `sed` syntax
+    pub commands: &'static [&'static str],
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        date: [2023, 1, 3],
+        description: "`Map::get_parent_node` and `Map::find_parent_node` were renamed to \
+                      `parent_id` and `opt_parent_id`",
+        commands: &[
+            r"s/\bget_parent_node\b/parent_id/g",
+            r"s/\bfind_parent_node\b/opt_parent_id/g",
+        ],
+    },
+    Migration {
+        date: [2023, 1, 19],
+        description: "`TyCtxt::fn_sig` returns an `EarlyBinder`, and `bound_fn_sig` was removed",
+        commands: &[
+            r"s/\.fn_sig\(([^()]*)\)/.fn_sig(${1}).skip_binder()/g",
+            r"s/\bbound_fn_sig\b/fn_sig/g",
+        ],
+    },
+    Migration {
+        date: [2023, 2, 16],
+        description: "`TyCtxt::type_of` returns an `EarlyBinder`, and `bound_type_of` was removed",
+        commands: &[
+            r"s/\.type_of\(([^()]*)\)/.type_of(${1}).skip_binder()/g",
+            r"s/\bbound_type_of\b/type_of/g",
+        ],
+    },
+    Migration {
+        date: [2023, 3, 14],
+        description: "the `DefIdTree` trait was removed; its methods are now inherent to `TyCtxt`",
+        commands: &[
+            r"s/(?m)^[ \t]*use rustc_middle::ty::DefIdTree;\r?\n//g",
+            r"s/,\s*DefIdTree\b//g",
+            r"s/\bDefIdTree,\s*//g",
+        ],
+    },
+    Migration {
+        date: [2023, 7, 15],
+        description: "`ty::subst` was renamed to `ty::generic_args`, and `Substs` to `GenericArgs`",
+        commands: &[
+            r"s/\bty::subst::/ty::/g",
+            r"s/\bSubstsRef\b/GenericArgsRef/g",
+            r"s/\bInternalSubsts\b/GenericArgs/g",
+            r"s/\bnode_substs\b/node_args/g",
+        ],
+    },
+    Migration {
+        date: [2023, 7, 15],
+        description: "`EarlyBinder::subst` and `subst_identity` were renamed to `instantiate` and \
+                      `instantiate_identity`",
+        commands: &[
+            r"s/\.subst_identity\(\)/.instantiate_identity()/g",
+            r"s/\.subst\(/.instantiate(/g",
+        ],
+    },
+    Migration {
+        date: [2024, 2, 24],
+        description: "`DiagnosticBuilder` was renamed to `Diag`",
+        commands: &[r"s/\bDiagnosticBuilder\b/Diag/g"],
+    },
+    Migration {
+        date: [2024, 3, 6],
+        description: "`Session::parse_sess` was renamed to `psess`",
+        commands: &[r"s/\.parse_sess\b/.psess/g"],
+    },
+    Migration {
+        date: [2024, 3, 23],
+        description: "`hir::Local` was renamed to `LetStmt`, and `StmtKind::Local` to `Let`",
+        commands: &[
+            r"s/\bhir::Local\b/hir::LetStmt/g",
+            r"s/&'tcx Local<'tcx>/&'tcx LetStmt<'tcx>/g",
+            r"s/\bStmtKind::Local\b/StmtKind::Let/g",
+        ],
+    },
+    Migration {
+        date: [2024, 4, 18],
+        description: "`BindingAnnotation` was renamed to `BindingMode`",
+        commands: &[r"s/\bBindingAnnotation\b/BindingMode/g"],
+    },
+];
+
+/// Returns the migrations that apply when upgrading from `old_nightly` to `new_nightly`
+pub fn applicable(
+    old_nightly: [u32; 3],
+    new_nightly: [u32; 3],
+) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(move |migration| old_nightly < migration.date && migration.date <= new_nightly)
+}
+
+/// Returns the Rust source files in `path`'s `src` directory, i.e., the files to which migrations
+/// are applied
+pub fn source_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut source_files = Vec::new();
+    for entry in WalkDir::new(path.join("src")) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension() == Some(OsStr::new("rs")) {
+            source_files.push(path.to_path_buf());
+        }
+    }
+    Ok(source_files)
+}
+
+/// Applies `migrations` to `source_files`. Returns each migration that changed at least one file,
+/// along with the files it changed (relative to `path`).
+pub fn apply<'a>(
+    path: &Path,
+    source_files: &[PathBuf],
+    migrations: impl IntoIterator<Item = &'a Migration>,
+) -> Result<Vec<(&'a Migration, Vec<PathBuf>)>> {
+    let mut applied = Vec::new();
+    for migration in migrations {
+        let mut changed = Vec::new();
+        for source_file in source_files {
+            let before = read_to_string(source_file).with_context(|| {
+                format!(
+                    "`read_to_string` failed for `{}`",
+                    source_file.to_string_lossy()
+                )
+            })?;
+            find_and_replace(source_file, migration.commands)?;
+            let after = read_to_string(source_file).with_context(|| {
+                format!(
+                    "`read_to_string` failed for `{}`",
+                    source_file.to_string_lossy()
+                )
+            })?;
+            if before != after {
+                changed.push(source_file.strip_prefix(path)?.to_path_buf());
+            }
+        }
+        if !changed.is_empty() {
+            applied.push((migration, changed));
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Checks the package at `path` and returns its compile errors, one per line, in the form
+/// `file:line:column: message`.
+pub fn remaining_errors(path: &Path) -> Result<Vec<String>> {
+    let file_name = path.file_name().unwrap_or(path.as_os_str());
+    let description = format!("`{}`", file_name.to_string_lossy());
+
+    compile_errors(
+        dylint_internal::cargo::check(&description)
+            .sanitize_environment()
+            .current_dir(path),
+    )
+}
+
+/// Runs `command`, a `cargo check` or `cargo build` command, on all targets and returns the
+/// resulting compile errors in the same form as [`remaining_errors`].
+pub fn compile_errors(command: &mut Command) -> Result<Vec<String>> {
+    let mut errors = Vec::new();
+
+    // smoelius: The command is expected to fail if there are errors. So its exit status matters
+    // only if no errors were found.
+    let mut read_result: Result<()> = Ok(());
+    let command_result = command
+        .args(["--all-targets", "--message-format=json"])
+        .success_with_stdout(|stdout| {
+            for message in Message::parse_stream(BufReader::new(stdout)) {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(error) => {
+                        read_result = Err(error).with_context(|| "Could not read message");
+                        break;
+                    }
+                };
+                if let Message::CompilerMessage(compiler_message) = message {
+                    let diagnostic = compiler_message.message;
+                    if diagnostic.level != DiagnosticLevel::Error {
+                        continue;
+                    }
+                    if let Some(span) = diagnostic.spans.iter().find(|span| span.is_primary) {
+                        errors.push(format!(
+                            "{}:{}:{}: {}",
+                            span.file_name, span.line_start, span.column_start, diagnostic.message
+                        ));
+                    }
+                }
+            }
+            Ok(())
+        });
+    read_result?;
+
+    if errors.is_empty() {
+        command_result?;
+    }
+
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::{create_dir, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn migrations_are_sorted() {
+        assert!(MIGRATIONS
+            .windows(2)
+            .all(|window| window[0].date <= window[1].date));
+    }
+
+    #[test]
+    fn applicable_is_exclusive_of_old_and_inclusive_of_new() {
+        let descriptions = |old_nightly, new_nightly| {
+            applicable(old_nightly, new_nightly)
+                .map(|migration| migration.description)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![MIGRATIONS[2].description, MIGRATIONS[3].description],
+            descriptions([2023, 1, 19], [2023, 3, 14])
+        );
+        assert!(descriptions([2023, 3, 14], [2023, 6, 29]).is_empty());
+    }
+
+    #[test]
+    fn apply_rewrites_sources() {
+        let tempdir = tempdir().unwrap();
+        create_dir(tempdir.path().join("src")).unwrap();
+        write(
+            tempdir.path().join("src/lib.rs"),
+            r"use rustc_middle::ty::{self, DefIdTree};
+fn f(cx: &LateContext<'_>, def_id: DefId) {
+    let sig = cx.tcx.fn_sig(def_id);
+    let ty = cx.tcx.bound_type_of(def_id).subst_identity();
+    let parent = cx.tcx.hir().get_parent_node(hir_id);
+}
+",
+        )
+        .unwrap();
+        write(tempdir.path().join("src/unchanged.rs"), "fn g() {}\n").unwrap();
+
+        let source_files = source_files(tempdir.path()).unwrap();
+        assert_eq!(2, source_files.len());
+
+        let applied = apply(
+            tempdir.path(),
+            &source_files,
+            applicable([2022, 12, 1], [2023, 6, 29]),
+        )
+        .unwrap();
+
+        assert_eq!(4, applied.len());
+        assert!(applied
+            .iter()
+            .all(|(_, changed)| changed == &[PathBuf::from("src/lib.rs")]));
+        assert_eq!(
+            r"use rustc_middle::ty::{self};
+fn f(cx: &LateContext<'_>, def_id: DefId) {
+    let sig = cx.tcx.fn_sig(def_id).skip_binder();
+    let ty = cx.tcx.type_of(def_id).subst_identity();
+    let parent = cx.tcx.hir().parent_id(hir_id);
+}
+",
+            read_to_string(tempdir.path().join("src/lib.rs")).unwrap()
+        );
+    }
+}
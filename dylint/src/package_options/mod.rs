@@ -1,4 +1,4 @@
-use crate::Dylint;
+use crate::{error::note, warn, Dylint};
 use anyhow::{anyhow, bail, Context, Result};
 use dylint_internal::{
     clippy_utils::{
@@ -6,7 +6,7 @@ use dylint_internal::{
         set_toolchain_channel, toolchain_channel,
     },
     find_and_replace,
-    packaging::new_template,
+    packaging::{new_template, new_workspace_template},
     rustup::SanitizeEnvironment,
 };
 use heck::{ToKebabCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
@@ -25,9 +25,16 @@ mod bisect;
 mod backup;
 use backup::Backup;
 
+mod dist;
+pub use dist::dist_package;
+
+mod migrations;
+
 mod revs;
 use revs::Revs;
 
+mod search;
+
 pub fn new_package(opts: &Dylint, path: &Path) -> Result<()> {
     let name = path
         .file_name()
@@ -36,6 +43,10 @@ pub fn new_package(opts: &Dylint, path: &Path) -> Result<()> {
 
     let tempdir = tempdir().with_context(|| "`tempdir` failed")?;
 
+    if opts.new_workspace {
+        return new_workspace_package(&name, tempdir.path(), path);
+    }
+
     new_template(tempdir.path())?;
 
     // smoelius: Isolation is now the default.
@@ -57,6 +68,18 @@ pub fn new_package(opts: &Dylint, path: &Path) -> Result<()> {
     Ok(())
 }
 
+// smoelius: The workspace template is its own workspace, so `--isolate` does not apply.
+fn new_workspace_package(name: &str, from: &Path, to: &Path) -> Result<()> {
+    new_workspace_template(from)?;
+
+    find_and_replace(
+        &from.join("example_lint/src/lib.rs"),
+        &[r"s/(?m)^.. (#!\[warn\(unused_extern_crates\)\])$/${1}/"],
+    )?;
+
+    fill_in(name, from, to)
+}
+
 fn fill_in(name: &str, from: &Path, to: &Path) -> Result<()> {
     let lower_snake_case = name.to_snake_case();
     let upper_snake_case = name.to_shouty_snake_case();
@@ -164,12 +187,49 @@ pub fn upgrade_package(opts: &Dylint, path: &Path) -> Result<()> {
     let mut cargo_toml_backup =
         Backup::new(cargo_toml_path).with_context(|| "Could not backup `Cargo.toml`")?;
 
+    let migrations = if_chain! {
+        if should_find_and_replace;
+        if let Some(old_nightly) = parse_as_nightly(&old_channel);
+        if let Some(new_nightly) = parse_as_nightly(&rev.channel);
+        then {
+            migrations::applicable(old_nightly, new_nightly).collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        }
+    };
+
+    let source_files = if migrations.is_empty() {
+        Vec::new()
+    } else {
+        migrations::source_files(path)?
+    };
+
+    let mut source_file_backups = source_files
+        .iter()
+        .map(Backup::new)
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| "Could not backup source files")?;
+
     if should_find_and_replace {
         set_toolchain_channel(path, &rev.channel)?;
         set_clippy_utils_dependency_revision(path, &rev.rev)?;
+
+        for (migration, changed) in migrations::apply(path, &source_files, migrations)? {
+            let changed = changed
+                .iter()
+                .map(|changed_path| format!("`{}`", changed_path.to_string_lossy()))
+                .collect::<Vec<_>>();
+            note(
+                opts,
+                &format!(
+                    "Rewrote {} because {}",
+                    changed.join(", "),
+                    migration.description
+                ),
+            );
+        }
     }
 
-    #[cfg(unix)]
     if opts.bisect {
         let file_name = path
             .file_name()
@@ -181,26 +241,62 @@ pub fn upgrade_package(opts: &Dylint, path: &Path) -> Result<()> {
             .current_dir(path)
             .success()?;
 
-        if dylint_internal::cargo::build(&description, opts.quiet)
-            .sanitize_environment()
-            .current_dir(path)
-            .args(["--all-targets"])
-            .success()
-            .is_err()
+        if opts.bisect_start.is_some()
+            || opts.bisect_end.is_some()
+            || opts.bisect_max_attempts.is_some()
         {
-            let new_nightly = parse_as_nightly(&rev.channel).ok_or_else(|| {
-                anyhow!("Could not not parse channel `{}` as nightly", rev.channel)
-            })?;
+            let start = match &opts.bisect_start {
+                Some(start) => parse_as_nightly_or_date(start)?,
+                None => parse_as_nightly(&old_channel).ok_or_else(|| {
+                    anyhow!("Could not not parse channel `{}` as nightly", old_channel)
+                })?,
+            };
+            let end = match &opts.bisect_end {
+                Some(end) => parse_as_nightly_or_date(end)?,
+                None => {
+                    let channel = toolchain_channel(path)?;
+                    parse_as_nightly(&channel).ok_or_else(|| {
+                        anyhow!("Could not not parse channel `{}` as nightly", channel)
+                    })?
+                }
+            };
 
-            let start = format!(
-                "{:04}-{:02}-{:02}",
-                new_nightly[0], new_nightly[1], new_nightly[2]
-            );
+            search::search_and_report(
+                opts,
+                path,
+                &description,
+                start,
+                end,
+                opts.bisect_max_attempts,
+            )?;
+        } else {
+            #[cfg(unix)]
+            if dylint_internal::cargo::build(&description, opts.quiet)
+                .sanitize_environment()
+                .current_dir(path)
+                .args(["--all-targets"])
+                .success()
+                .is_err()
+            {
+                let new_nightly = parse_as_nightly(&rev.channel).ok_or_else(|| {
+                    anyhow!("Could not not parse channel `{}` as nightly", rev.channel)
+                })?;
+
+                let start = format!(
+                    "{:04}-{:02}-{:02}",
+                    new_nightly[0], new_nightly[1], new_nightly[2]
+                );
 
-            bisect::bisect(opts, path, &start)?;
+                bisect::bisect(opts, path, &start)?;
+            }
         }
     }
 
+    for backup in &mut source_file_backups {
+        backup
+            .disable()
+            .with_context(|| "Could not disable source file backup")?;
+    }
     cargo_toml_backup
         .disable()
         .with_context(|| "Could not disable `rust-toolchain` backup")?;
@@ -208,13 +304,44 @@ pub fn upgrade_package(opts: &Dylint, path: &Path) -> Result<()> {
         .disable()
         .with_context(|| "Could not disable `Cargo.toml` backup")?;
 
+    // smoelius: When bisecting, the package was just built, and any errors were shown.
+    if should_find_and_replace && !opts.bisect && old_channel != rev.channel {
+        report_remaining_errors(opts, path);
+    }
+
     Ok(())
 }
 
+fn report_remaining_errors(opts: &Dylint, path: &Path) {
+    match migrations::remaining_errors(path) {
+        Ok(errors) if errors.is_empty() => {}
+        Ok(errors) => warn(
+            opts,
+            &format!(
+                "{} compile error{} remain{} after upgrading:\n{}",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" },
+                if errors.len() == 1 { "s" } else { "" },
+                errors.join("\n")
+            ),
+        ),
+        Err(error) => warn(
+            opts,
+            &format!("Could not check the upgraded package: {error:?}"),
+        ),
+    }
+}
+
 fn parse_as_nightly(channel: &str) -> Option<[u32; 3]> {
     channel.strip_prefix("nightly-").and_then(parse_date)
 }
 
+fn parse_as_nightly_or_date(s: &str) -> Result<[u32; 3]> {
+    parse_as_nightly(s)
+        .or_else(|| parse_date(s))
+        .ok_or_else(|| anyhow!("Could not parse `{}` as a nightly toolchain or date", s))
+}
+
 fn parse_date(date_str: &str) -> Option<[u32; 3]> {
     date_str
         .split('-')
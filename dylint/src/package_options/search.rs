@@ -0,0 +1,380 @@
+use super::migrations::compile_errors;
+use crate::{error::note, Dylint};
+use anyhow::{bail, ensure, Result};
+use dylint_internal::{
+    clippy_utils::set_toolchain_channel,
+    rustup::{active_toolchain, SanitizeEnvironment},
+    Command,
+};
+use std::{path::Path, process::Stdio};
+
+// smoelius: The search assumes that if a package builds with some nightly toolchain, then it builds
+// with every earlier nightly toolchain in the range. In other words, it assumes there is a single
+// boundary to find.
+
+pub enum Outcome {
+    Success,
+    Failure(Vec<String>),
+    Unavailable,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Boundary {
+    /// The most recent nightly toolchain found with which the package builds
+    pub last_success: [u32; 3],
+    /// The earliest nightly toolchain found with which the package does not build, along with its
+    /// errors
+    pub first_failure: Option<([u32; 3], Vec<String>)>,
+    /// Whether the search ran out of attempts before `last_success` and `first_failure` became
+    /// adjacent
+    pub incomplete: bool,
+}
+
+struct Prober<F> {
+    probe: F,
+    attempts: usize,
+    max_attempts: Option<usize>,
+}
+
+impl<F: FnMut([u32; 3]) -> Result<Outcome>> Prober<F> {
+    // smoelius: Only builds count as attempts. Installing an unavailable toolchain fails quickly.
+    fn probe(&mut self, nightly: [u32; 3]) -> Result<Option<Outcome>> {
+        if self
+            .max_attempts
+            .map_or(false, |max_attempts| self.attempts >= max_attempts)
+        {
+            return Ok(None);
+        }
+        let outcome = (self.probe)(nightly)?;
+        if !matches!(outcome, Outcome::Unavailable) {
+            self.attempts += 1;
+        }
+        Ok(Some(outcome))
+    }
+}
+
+/// Binary searches the nightly toolchains from `start` to `end` (inclusive) for the most recent one
+/// with which a package builds, using `probe` to try each toolchain
+pub fn search(
+    start: [u32; 3],
+    end: [u32; 3],
+    max_attempts: Option<usize>,
+    probe: impl FnMut([u32; 3]) -> Result<Outcome>,
+) -> Result<Boundary> {
+    ensure!(
+        start < end,
+        "`{}` does not precede `{}`",
+        channel(start),
+        channel(end)
+    );
+
+    let mut prober = Prober {
+        probe,
+        attempts: 0,
+        max_attempts,
+    };
+
+    let mut candidates = nightlies(start, end);
+    let mut lo = 0;
+    let mut hi = candidates.len() - 1;
+    let mut lo_verified = false;
+    let mut first_failure = None;
+    let mut incomplete = false;
+
+    // smoelius: Try the end of the range first. If the package builds there, there is nothing to
+    // search for.
+    while first_failure.is_none() && lo < hi {
+        match prober.probe(candidates[hi])? {
+            None => bail!("Ran out of attempts before building with any toolchain"),
+            Some(Outcome::Success) => {
+                return Ok(Boundary {
+                    last_success: candidates[hi],
+                    first_failure: None,
+                    incomplete: false,
+                });
+            }
+            Some(Outcome::Failure(errors)) => first_failure = Some((candidates[hi], errors)),
+            Some(Outcome::Unavailable) => {
+                candidates.remove(hi);
+                hi -= 1;
+            }
+        }
+    }
+
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match prober.probe(candidates[mid])? {
+            None => {
+                incomplete = true;
+                break;
+            }
+            Some(Outcome::Success) => {
+                lo = mid;
+                lo_verified = true;
+            }
+            Some(Outcome::Failure(errors)) => {
+                hi = mid;
+                first_failure = Some((candidates[mid], errors));
+            }
+            Some(Outcome::Unavailable) => {
+                candidates.remove(mid);
+                hi -= 1;
+            }
+        }
+    }
+
+    if !lo_verified {
+        match prober.probe(candidates[lo])? {
+            None => bail!(
+                "Ran out of attempts before building with any toolchain; the earliest failing \
+                 toolchain found is `{}`",
+                first_failure.map_or_else(String::new, |(nightly, _)| channel(nightly))
+            ),
+            Some(Outcome::Success) => {}
+            Some(Outcome::Failure(errors)) => bail!(
+                "The package does not build with `{}`, the start of the range:\n{}",
+                channel(candidates[lo]),
+                errors.join("\n")
+            ),
+            Some(Outcome::Unavailable) => bail!(
+                "`{}`, the start of the range, is not available",
+                channel(candidates[lo])
+            ),
+        }
+    }
+
+    Ok(Boundary {
+        last_success: candidates[lo],
+        first_failure,
+        incomplete,
+    })
+}
+
+/// Searches for the most recent nightly toolchain from `start` to `end` with which the package at
+/// `path` and a matching driver build, sets the package's toolchain to that nightly, and reports
+/// what was found
+pub fn search_and_report(
+    opts: &Dylint,
+    path: &Path,
+    description: &str,
+    start: [u32; 3],
+    end: [u32; 3],
+    max_attempts: Option<usize>,
+) -> Result<()> {
+    let boundary = search(start, end, max_attempts, |nightly| {
+        probe(opts, path, description, nightly)
+    })?;
+
+    set_toolchain_channel(path, &channel(boundary.last_success))?;
+
+    note(
+        opts,
+        &format!(
+            "`{}` is the most recent nightly toolchain found with which {} builds",
+            channel(boundary.last_success),
+            description
+        ),
+    );
+    if boundary.incomplete {
+        note(
+            opts,
+            &format!(
+                "The search stopped after {} build attempts; more recent toolchains may work",
+                max_attempts.unwrap_or_default()
+            ),
+        );
+    }
+    if let Some((nightly, errors)) = boundary.first_failure {
+        note(
+            opts,
+            &format!(
+                "`{}` is the earliest nightly toolchain found with which {} does not build:\n{}",
+                channel(nightly),
+                description,
+                errors.join("\n")
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+// smoelius: A toolchain "works" if both the package and a driver build with it. `driver_builder`
+// reuses drivers that were already built, so retrying a toolchain does not rebuild its driver.
+fn probe(opts: &Dylint, path: &Path, description: &str, nightly: [u32; 3]) -> Result<Outcome> {
+    let channel = channel(nightly);
+
+    if Command::new("rustup")
+        .sanitize_environment()
+        .args([
+            "toolchain",
+            "install",
+            &channel,
+            "--profile",
+            "minimal",
+            "--component",
+            "llvm-tools-preview",
+            "--component",
+            "rustc-dev",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .success()
+        .is_err()
+    {
+        return Ok(Outcome::Unavailable);
+    }
+
+    set_toolchain_channel(path, &channel)?;
+
+    let errors = compile_errors(
+        dylint_internal::cargo::build(&format!("{description} with `{channel}`"), opts.quiet)
+            .sanitize_environment()
+            .current_dir(path),
+    )
+    .unwrap_or_else(|error| vec![format!("{error:?}")]);
+    if !errors.is_empty() {
+        return Ok(Outcome::Failure(errors));
+    }
+
+    let toolchain = active_toolchain(path)?;
+    if let Err(error) = crate::driver_builder::get(opts, &toolchain) {
+        return Ok(Outcome::Failure(vec![format!(
+            "Could not build driver: {error:?}"
+        )]));
+    }
+
+    Ok(Outcome::Success)
+}
+
+pub fn channel(nightly: [u32; 3]) -> String {
+    format!(
+        "nightly-{:04}-{:02}-{:02}",
+        nightly[0], nightly[1], nightly[2]
+    )
+}
+
+fn nightlies(start: [u32; 3], end: [u32; 3]) -> Vec<[u32; 3]> {
+    (days_from_civil(start)..=days_from_civil(end))
+        .map(civil_from_days)
+        .collect()
+}
+
+// smoelius: `days_from_civil` and `civil_from_days` are based on:
+// https://howardhinnant.github.io/date_algorithms.html
+
+fn days_from_civil([y, m, d]: [u32; 3]) -> i64 {
+    let y = i64::from(y) - i64::from(m <= 2);
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (i64::from(m) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(d) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn civil_from_days(z: i64) -> [u32; 3] {
+    let z = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    [y as u32, m as u32, d as u32]
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn nightlies_cross_month_and_leap_day() {
+        assert_eq!(
+            vec![[2024, 2, 28], [2024, 2, 29], [2024, 3, 1]],
+            nightlies([2024, 2, 28], [2024, 3, 1])
+        );
+        assert_eq!(
+            vec![[2022, 12, 31], [2023, 1, 1]],
+            nightlies([2022, 12, 31], [2023, 1, 1])
+        );
+    }
+
+    #[test]
+    fn search_finds_boundary() {
+        let mut probed = Vec::new();
+        let boundary = search([2023, 1, 1], [2023, 1, 31], None, |nightly| {
+            probed.push(nightly);
+            Ok(if nightly <= [2023, 1, 17] {
+                Outcome::Success
+            } else {
+                Outcome::Failure(vec![format!("error with {}", channel(nightly))])
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            Boundary {
+                last_success: [2023, 1, 17],
+                first_failure: Some((
+                    [2023, 1, 18],
+                    vec![String::from("error with nightly-2023-01-18")]
+                )),
+                incomplete: false,
+            },
+            boundary
+        );
+        assert!(probed.len() <= 7);
+    }
+
+    #[test]
+    fn search_skips_unavailable() {
+        let boundary = search([2023, 1, 1], [2023, 1, 10], None, |nightly| {
+            Ok(if nightly == [2023, 1, 10] || nightly == [2023, 1, 5] {
+                Outcome::Unavailable
+            } else if nightly <= [2023, 1, 5] {
+                Outcome::Success
+            } else {
+                Outcome::Failure(Vec::new())
+            })
+        })
+        .unwrap();
+        assert_eq!([2023, 1, 4], boundary.last_success);
+        assert_eq!(
+            Some([2023, 1, 6]),
+            boundary.first_failure.map(|(nightly, _)| nightly)
+        );
+    }
+
+    #[test]
+    fn search_respects_max_attempts() {
+        let mut attempts = 0;
+        let boundary = search([2023, 1, 1], [2023, 12, 31], Some(4), |nightly| {
+            attempts += 1;
+            Ok(if nightly <= [2023, 6, 1] {
+                Outcome::Success
+            } else {
+                Outcome::Failure(Vec::new())
+            })
+        })
+        .unwrap();
+        assert_eq!(4, attempts);
+        assert!(boundary.incomplete);
+        assert!(boundary.last_success <= [2023, 6, 1]);
+    }
+
+    #[test]
+    fn search_fails_if_start_does_not_build() {
+        let error = search([2023, 1, 1], [2023, 1, 31], None, |_| {
+            Ok(Outcome::Failure(vec![String::from("error")]))
+        })
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("The package does not build with `nightly-2023-01-01`"));
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{diagnostics::Counts, error::note, Dylint};
+use anyhow::{Context, Result};
+use cargo_metadata::Metadata;
+use dylint_internal::env;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fs::{create_dir_all, metadata, read_to_string, remove_file, rename, write},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+const FILENAME: &str = "run-state.json";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// smoelius: As with the phases recorded by `timings`, the run state is kept in a static so that
+// code far from `check_or_fix` (e.g., library builds) need not thread it through.
+static RUN_STATE: Mutex<Option<RunState>> = Mutex::new(None);
+
+/// The phases of a run that have completed, as written to `target/dylint/run-state.json`
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+struct State {
+    version: String,
+    /// Fingerprint of the inputs that affect every phase, e.g., `RUSTFLAGS` and `dylint.toml`
+    environment: String,
+    phases: BTreeMap<String, Marker>,
+}
+
+/// A record that a phase completed
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Marker {
+    pub fingerprint: String,
+    /// For a check, the diagnostics it produced, so that a skipped check still counts toward
+    /// `--deny-warnings` and `--summary`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<Counts>,
+}
+
+#[derive(Debug)]
+struct RunState {
+    path: PathBuf,
+    state: State,
+}
+
+/// Reads the run state from the workspace's target directory if `--resume` was passed, or starts
+/// a new one otherwise. Until this function is called, phases run unconditionally and are not
+/// recorded.
+pub fn init(opts: &Dylint) -> Result<()> {
+    let metadata = crate::workspace_metadata(opts)?;
+    let path = metadata.target_directory.join("dylint").join(FILENAME);
+    let environment = environment(opts, &metadata)?;
+    let run_state = RunState::load(opts, path.as_std_path(), &environment)?;
+    if let Ok(mut lock) = RUN_STATE.lock() {
+        *lock = Some(run_state);
+    }
+    Ok(())
+}
+
+pub fn is_active() -> bool {
+    RUN_STATE.lock().map_or(false, |lock| lock.is_some())
+}
+
+/// Returns the marker of phase `name`, if the phase completed with `fingerprint` in the run being
+/// resumed
+pub fn completed(name: &str, fingerprint: &str) -> Option<Marker> {
+    RUN_STATE.lock().ok().and_then(|lock| {
+        lock.as_ref()
+            .and_then(|run_state| run_state.completed(name, fingerprint))
+            .cloned()
+    })
+}
+
+/// Records that phase `name` completed
+pub fn complete(name: &str, marker: Marker) -> Result<()> {
+    if let Ok(mut lock) = RUN_STATE.lock() {
+        if let Some(run_state) = lock.as_mut() {
+            run_state.complete(name, marker)?;
+        }
+    }
+    Ok(())
+}
+
+/// Ends the run, after which phases again run unconditionally and are not recorded. If the run
+/// succeeded, its state is removed, as there is nothing left to resume.
+pub fn finish(succeeded: bool) -> Result<()> {
+    let run_state = RUN_STATE.lock().ok().and_then(|mut lock| lock.take());
+    if let Some(run_state) = run_state {
+        if succeeded {
+            run_state.remove()?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `f` unless phase `name` completed in the run being resumed and `fingerprint()` still
+/// matches. If `f` succeeds, records the phase with `fingerprint()` as computed afterward.
+pub fn run(
+    name: &str,
+    fingerprint: impl Fn() -> Result<String>,
+    f: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    if !is_active() {
+        return f();
+    }
+    if completed(name, &fingerprint()?).is_some() {
+        return Ok(());
+    }
+    f()?;
+    complete(
+        name,
+        Marker {
+            fingerprint: fingerprint()?,
+            counts: None,
+        },
+    )
+}
+
+// smoelius: `DefaultHasher`'s output may differ between builds of Dylint. A difference just causes
+// a phase to be rerun.
+pub fn fingerprint(value: &impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the length and modification time of the file at `path`, or `None` if there is no such
+/// file. Used to detect rebuilt artifacts and changed sources.
+pub fn file_fingerprint(path: &Path) -> Option<(u64, u128)> {
+    let metadata = metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len(), modified.as_nanos()))
+}
+
+fn environment(opts: &Dylint, metadata: &Metadata) -> Result<String> {
+    let vars = [env::RUSTFLAGS, env::DYLINT_TOML].map(std::env::var_os);
+    Ok(fingerprint(&(
+        vars,
+        crate::dylint_rustflags(opts, metadata)?,
+        crate::dylint_toml(metadata)?,
+    )))
+}
+
+impl RunState {
+    /// Reads the state at `path` if `--resume` was passed and the state was written by this
+    /// version of Dylint with the same `environment`. Otherwise, starts over. Either way, the state
+    /// at `path` is rewritten.
+    fn load(opts: &Dylint, path: &Path, environment: &str) -> Result<Self> {
+        let new = State {
+            version: VERSION.to_owned(),
+            environment: environment.to_owned(),
+            phases: BTreeMap::new(),
+        };
+        let state = if opts.resume {
+            match read(path) {
+                Some(state)
+                    if state.version == new.version && state.environment == new.environment =>
+                {
+                    state
+                }
+                Some(_) => {
+                    note(
+                        opts,
+                        "Dylint, `RUSTFLAGS`, or `dylint.toml` changed since the interrupted run; \
+                         starting over",
+                    );
+                    new
+                }
+                None => {
+                    note(opts, "Found no interrupted run to resume; starting over");
+                    new
+                }
+            }
+        } else {
+            new
+        };
+        let run_state = Self {
+            path: path.to_path_buf(),
+            state,
+        };
+        run_state.write()?;
+        Ok(run_state)
+    }
+
+    fn completed(&self, name: &str, fingerprint: &str) -> Option<&Marker> {
+        self.state
+            .phases
+            .get(name)
+            .filter(|marker| marker.fingerprint == fingerprint)
+    }
+
+    fn complete(&mut self, name: &str, marker: Marker) -> Result<()> {
+        self.state.phases.insert(name.to_owned(), marker);
+        self.write()
+    }
+
+    fn remove(self) -> Result<()> {
+        remove_file(&self.path)
+            .with_context(|| format!("`remove_file` failed for `{}`", self.path.to_string_lossy()))
+    }
+
+    // smoelius: The state is written to a temporary file that is then renamed, so that an
+    // interruption mid-write leaves the previous state intact.
+    fn write(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent).with_context(|| {
+                format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+            })?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        write(&tmp, serde_json::to_string_pretty(&self.state)? + "\n")
+            .with_context(|| format!("`write` failed for `{}`", tmp.to_string_lossy()))?;
+        rename(&tmp, &self.path).with_context(|| {
+            format!(
+                "Could not rename `{}` to `{}`",
+                tmp.to_string_lossy(),
+                self.path.to_string_lossy()
+            )
+        })
+    }
+}
+
+// smoelius: A state that cannot be read or parsed is treated as absent.
+fn read(path: &Path) -> Option<State> {
+    let contents = read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::tempdir;
+
+    const ENVIRONMENT: &str = "0123456789abcdef";
+
+    fn opts(resume: bool) -> Dylint {
+        Dylint {
+            quiet: true,
+            resume,
+            ..Dylint::default()
+        }
+    }
+
+    fn marker(fingerprint: &str) -> Marker {
+        Marker {
+            fingerprint: fingerprint.to_owned(),
+            counts: None,
+        }
+    }
+
+    // smoelius: A run is interrupted after its first two phases. The resumed run skips them and
+    // runs only the third.
+    #[test]
+    fn resume_skips_completed_phases() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build driver", marker("a")).unwrap();
+        run_state.complete("build library", marker("b")).unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(true), &path, ENVIRONMENT).unwrap();
+        assert!(run_state.completed("build driver", "a").is_some());
+        assert!(run_state.completed("build library", "b").is_some());
+        assert!(run_state.completed("cargo check", "c").is_none());
+    }
+
+    #[test]
+    fn changed_fingerprint_reruns_phase() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build library", marker("b")).unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(true), &path, ENVIRONMENT).unwrap();
+        assert!(run_state.completed("build library", "b'").is_none());
+    }
+
+    #[test]
+    fn changed_environment_or_version_starts_over() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build driver", marker("a")).unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(true), &path, "fedcba9876543210").unwrap();
+        assert!(run_state.completed("build driver", "a").is_none());
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build driver", marker("a")).unwrap();
+        run_state.state.version = "0.0.0".to_owned();
+        run_state.write().unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(true), &path, ENVIRONMENT).unwrap();
+        assert!(run_state.completed("build driver", "a").is_none());
+    }
+
+    #[test]
+    fn removed_state_is_not_resumed() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build driver", marker("a")).unwrap();
+        run_state.remove().unwrap();
+        assert!(!path.exists());
+
+        let run_state = RunState::load(&opts(true), &path, ENVIRONMENT).unwrap();
+        assert!(run_state.completed("build driver", "a").is_none());
+    }
+
+    #[test]
+    fn without_resume_state_is_rewritten() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state.complete("build driver", marker("a")).unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        assert!(run_state.completed("build driver", "a").is_none());
+        assert!(read(&path).unwrap().phases.is_empty());
+    }
+
+    #[test]
+    fn counts_round_trip() {
+        let tempdir = tempdir().unwrap();
+        let path = tempdir.path().join(FILENAME);
+
+        let counts = Counts {
+            warnings: 2,
+            by_package_and_lint: BTreeMap::from([(("package".to_owned(), "lint".to_owned()), 2)]),
+        };
+
+        let mut run_state = RunState::load(&opts(false), &path, ENVIRONMENT).unwrap();
+        run_state
+            .complete(
+                "cargo check",
+                Marker {
+                    fingerprint: "c".to_owned(),
+                    counts: Some(counts.clone()),
+                },
+            )
+            .unwrap();
+        drop(run_state);
+
+        let run_state = RunState::load(&opts(true), &path, ENVIRONMENT).unwrap();
+        assert_eq!(
+            Some(&counts),
+            run_state
+                .completed("cargo check", "c")
+                .and_then(|marker| marker.counts.as_ref())
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    error::{failure as tag, FailureKind},
+    Dylint,
+};
+use anyhow::{anyhow, Context, Result};
+use dylint_internal::{library_filename, TimedOut};
+use std::{
+    collections::BTreeSet,
+    fs::{read_dir, read_to_string, remove_file},
+    path::{Path, PathBuf},
+};
+
+/// Returns a message for each timeout that expired while checking with `toolchain`: `--timeout`,
+/// if `error` is because of it, and `--per-crate-timeout`, for each crate the driver recorded in
+/// `expired_dir`
+pub fn expired(
+    opts: &Dylint,
+    toolchain: &str,
+    paths: &BTreeSet<PathBuf>,
+    error: Option<&anyhow::Error>,
+    expired_dir: Option<&Path>,
+) -> Result<Vec<String>> {
+    let mut expired = Vec::new();
+
+    if let Some(timeout) = opts.timeout {
+        if error.map_or(false, |error| error.downcast_ref::<TimedOut>().is_some()) {
+            let libraries = paths
+                .iter()
+                .filter_map(|path| {
+                    library_filename::parse_path(path).map(|(name, _, _)| format!("`{name}`"))
+                })
+                .collect::<Vec<_>>();
+            expired.push(format!(
+                "Timed out after {timeout}s checking with toolchain `{toolchain}` and {} {}",
+                if libraries.len() == 1 {
+                    "library"
+                } else {
+                    "libraries"
+                },
+                libraries.join(", ")
+            ));
+        }
+    }
+
+    if let Some(expired_dir) = expired_dir {
+        expired.extend(take_expired_crates(expired_dir)?);
+    }
+
+    Ok(expired)
+}
+
+/// Combines the messages returned by [`expired`] into one error with its own exit code
+pub fn failure(expired: &[String]) -> anyhow::Error {
+    tag(FailureKind::Timeout, anyhow!("{}", expired.join("\n")))
+}
+
+// smoelius: Each driver process that times out writes one file describing the crate it was
+// checking. The files are removed as they are read, so that the crates are not reported again
+// after checking with the next toolchain.
+fn take_expired_crates(expired_dir: &Path) -> Result<Vec<String>> {
+    let mut expired = Vec::new();
+    let entries = read_dir(expired_dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", expired_dir.to_string_lossy()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("`read_dir` failed for `{}`", expired_dir.to_string_lossy()))?
+            .path();
+        let contents = read_to_string(&path)
+            .with_context(|| format!("`read_to_string` failed for `{}`", path.to_string_lossy()))?;
+        let json = serde_json::from_str::<serde_json::Value>(&contents)
+            .with_context(|| format!("Could not parse `{}`", path.to_string_lossy()))?;
+        let description = json["description"].as_str().unwrap_or("a crate");
+        let seconds = json["seconds"].as_u64().unwrap_or_default();
+        expired.push(format!("Timed out after {seconds}s checking {description}"));
+        remove_file(&path)
+            .with_context(|| format!("`remove_file` failed for `{}`", path.to_string_lossy()))?;
+    }
+    expired.sort();
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::{fs::write, time::Duration};
+    use tempfile::tempdir;
+
+    #[test]
+    fn expired_crates_are_reported_once() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("1.json"),
+            r#"{"description": "package `a` with library `b`", "seconds": 60}"#,
+        )
+        .unwrap();
+
+        let messages = expired(
+            &Dylint::default(),
+            "nightly",
+            &BTreeSet::new(),
+            None,
+            Some(tempdir.path()),
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["Timed out after 60s checking package `a` with library `b`"],
+            messages
+        );
+
+        assert!(take_expired_crates(tempdir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn global_timeout() {
+        let opts = Dylint {
+            timeout: Some(600),
+            ..Dylint::default()
+        };
+        let paths = BTreeSet::from([PathBuf::from("/lib/libfoo@nightly-2023-06-29.so")]);
+        let error = anyhow::Error::new(TimedOut {
+            timeout: Duration::from_secs(600),
+        })
+        .context("command timed out");
+
+        let messages = expired(&opts, "nightly-2023-06-29", &paths, Some(&error), None).unwrap();
+        assert_eq!(
+            vec![
+                "Timed out after 600s checking with toolchain `nightly-2023-06-29` and library \
+                 `foo`"
+            ],
+            messages
+        );
+
+        let error = anyhow!("command failed");
+        assert!(
+            expired(&opts, "nightly-2023-06-29", &paths, Some(&error), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
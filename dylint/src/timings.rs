@@ -0,0 +1,174 @@
+use crate::Dylint;
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// smoelius: Phases are recorded in a static so that code far from `check_or_fix` (e.g., library
+// builds) need not thread a collector through.
+static PHASES: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+/// Timings for one run, sorted from slowest to fastest
+#[derive(Debug)]
+pub struct Timings {
+    phases: Vec<(String, Duration)>,
+    passes: Vec<(String, Duration)>,
+}
+
+/// Runs `f`, and if `--timings` was passed, records how long it took as a phase named `name()`
+pub fn time<T>(opts: &Dylint, name: impl FnOnce() -> String, f: impl FnOnce() -> T) -> T {
+    if !opts.timings {
+        return f();
+    }
+    let start = Instant::now();
+    let value = f();
+    let elapsed = start.elapsed();
+    if let Ok(mut phases) = PHASES.lock() {
+        phases.push((name(), elapsed));
+    }
+    value
+}
+
+impl Timings {
+    /// Takes the phases recorded so far, and reads the lint pass timings the driver wrote to
+    /// `pass_dir`
+    pub fn collect(pass_dir: &Path) -> Result<Self> {
+        let phases = PHASES
+            .lock()
+            .map(|mut phases| std::mem::take(&mut *phases))
+            .unwrap_or_default();
+        let passes = read_passes(pass_dir)?;
+        Ok(Self {
+            phases: sorted(phases),
+            passes: sorted(passes),
+        })
+    }
+
+    pub fn write_table(&self, mut w: impl Write) -> std::io::Result<()> {
+        write_section(&mut w, "Phase", &self.phases)?;
+        writeln!(w)?;
+        write_section(&mut w, "Lint pass", &self.passes)
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::json!({
+            "phases": entries(&self.phases),
+            "passes": entries(&self.passes),
+        });
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).with_context(|| {
+                format!("`create_dir_all` failed for `{}`", parent.to_string_lossy())
+            })?;
+        }
+        write(path, serde_json::to_string_pretty(&json)?)
+            .with_context(|| format!("Could not write to `{}`", path.to_string_lossy()))
+    }
+}
+
+// smoelius: Each driver process writes one file mapping `library::Pass` to seconds. A pass runs
+// once per crate, so the times are summed.
+fn read_passes(pass_dir: &Path) -> Result<Vec<(String, Duration)>> {
+    let mut passes = BTreeMap::<String, Duration>::new();
+    let entries = read_dir(pass_dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", pass_dir.to_string_lossy()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("`read_dir` failed for `{}`", pass_dir.to_string_lossy()))?
+            .path();
+        let contents = read_to_string(&path)
+            .with_context(|| format!("`read_to_string` failed for `{}`", path.to_string_lossy()))?;
+        let seconds = serde_json::from_str::<BTreeMap<String, f64>>(&contents)
+            .with_context(|| format!("Could not parse `{}`", path.to_string_lossy()))?;
+        for (name, seconds) in seconds {
+            *passes.entry(name).or_default() += Duration::from_secs_f64(seconds);
+        }
+    }
+    Ok(passes.into_iter().collect())
+}
+
+fn sorted(mut entries: Vec<(String, Duration)>) -> Vec<(String, Duration)> {
+    entries.sort_by(|(name_a, elapsed_a), (name_b, elapsed_b)| {
+        elapsed_b.cmp(elapsed_a).then_with(|| name_a.cmp(name_b))
+    });
+    entries
+}
+
+fn write_section(
+    w: &mut impl Write,
+    heading: &str,
+    entries: &[(String, Duration)],
+) -> std::io::Result<()> {
+    let width = entries
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(std::iter::once(heading.len()))
+        .max()
+        .unwrap_or_default();
+    writeln!(w, "{heading:<width$}  {:>9}", "Time")?;
+    for (name, elapsed) in entries {
+        writeln!(w, "{name:<width$}  {:>8.3}s", elapsed.as_secs_f64())?;
+    }
+    Ok(())
+}
+
+fn entries(entries: &[(String, Duration)]) -> Vec<serde_json::Value> {
+    entries
+        .iter()
+        .map(
+            |(name, elapsed)| serde_json::json!({ "name": name, "seconds": elapsed.as_secs_f64() }),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn passes_are_summed_and_sorted() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("1.json"),
+            r#"{"a::A": 1.0, "b::B": 0.5}"#,
+        )
+        .unwrap();
+        write(tempdir.path().join("2.json"), r#"{"b::B": 1.0}"#).unwrap();
+
+        let passes = sorted(read_passes(tempdir.path()).unwrap());
+        assert_eq!(
+            vec![
+                ("b::B".to_owned(), Duration::from_millis(1500)),
+                ("a::A".to_owned(), Duration::from_secs(1)),
+            ],
+            passes
+        );
+    }
+
+    #[test]
+    fn table() {
+        let timings = Timings {
+            phases: vec![("check".to_owned(), Duration::from_millis(2500))],
+            passes: vec![("a::A".to_owned(), Duration::from_millis(250))],
+        };
+        let mut table = Vec::new();
+        timings.write_table(&mut table).unwrap();
+        assert_eq!(
+            "\
+Phase       Time
+check     2.500s
+
+Lint pass       Time
+a::A          0.250s
+",
+            String::from_utf8(table).unwrap()
+        );
+    }
+}
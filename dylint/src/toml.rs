@@ -38,6 +38,18 @@ impl DetailedTomlDependency {
     }
 }
 
+// smoelius: `DetailedTomlDependency::package` and `DetailedTomlDependency::version` do not appear
+// in the original.
+impl DetailedTomlDependency {
+    pub fn package(&self) -> Option<&str> {
+        self.package.as_deref()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
 // smoelius: `Context::new` does not appear in the original.
 #[allow(clippy::too_many_arguments)]
 impl<'a, 'b> Context<'a, 'b> {
@@ -0,0 +1,247 @@
+use crate::{
+    check_or_fix, error::note, resolve, workspace_metadata, ColorizedError, Dylint, MaybeLibrary,
+    NameToolchainMap, ToolchainMap,
+};
+use anyhow::{Context, Result};
+use dylint_internal::sources::Sources;
+use is_terminal::IsTerminal;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError},
+    },
+    time::Duration,
+};
+
+// smoelius: Editors often save a file with several operations, e.g., writing a temporary file and
+// renaming it. Events less than `DEBOUNCE` apart are treated as one change.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// smoelius: "Erase display", "erase saved lines", and "cursor position".
+const CLEAR: &str = "\x1b[2J\x1b[3J\x1b[H";
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+enum Message {
+    Event(notify::Result<Event>),
+    Interrupt,
+}
+
+// smoelius: The variants are ordered by how much must be redone. A library change requires
+// rebuilding the libraries before checking the workspace.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Change {
+    None,
+    Workspace,
+    Library,
+}
+
+struct Roots {
+    workspace: PathBuf,
+    libraries: BTreeSet<PathBuf>,
+    ignored: Vec<PathBuf>,
+    sources: Vec<Sources>,
+}
+
+/// Checks the workspace, and checks it again whenever its sources or the sources of a path library
+/// change, until interrupted
+pub fn watch(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<()> {
+    let roots = roots(opts, name_toolchain_map)?;
+
+    let (tx, rx) = channel();
+
+    // smoelius: Ctrl-C is also delivered to Cargo, which releases its target directory locks as it
+    // exits. Handling ctrl-C here, rather than exiting immediately, lets Cargo finish doing so.
+    let interrupt_tx = tx.clone();
+    ctrlc::set_handler(move || {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        let _ = interrupt_tx.send(Message::Interrupt);
+    })
+    .with_context(|| "Could not set ctrl-C handler")?;
+
+    let mut watcher = notify::recommended_watcher(move |result| {
+        let _ = tx.send(Message::Event(result));
+    })
+    .with_context(|| "Could not create file watcher")?;
+
+    // smoelius: A library beneath the workspace root is watched as part of the workspace.
+    for path in std::iter::once(&roots.workspace).chain(
+        roots
+            .libraries
+            .iter()
+            .filter(|root| !root.starts_with(&roots.workspace)),
+    ) {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .with_context(|| format!("Could not watch `{}`", path.to_string_lossy()))?;
+    }
+
+    let mut resolved = None;
+    let mut change = Change::Library;
+
+    loop {
+        clear(opts);
+
+        let result = check(opts, name_toolchain_map, &mut resolved, change);
+
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        if let Err(error) = result {
+            eprintln!("{:?}", ColorizedError::new(error));
+        }
+
+        note(opts, "Watching for changes. Press ctrl-C to exit.");
+
+        change = match wait(&rx, &roots)? {
+            Some(change) => change,
+            None => return Ok(()),
+        };
+    }
+}
+
+// smoelius: After a workspace change, the libraries from the previous check are reused. If
+// building them failed, there are none to reuse, and they are built again.
+fn check(
+    opts: &Dylint,
+    name_toolchain_map: &NameToolchainMap,
+    cache: &mut Option<ToolchainMap>,
+    change: Change,
+) -> Result<()> {
+    let resolved = match (change, cache.take()) {
+        (Change::Workspace, Some(resolved)) => resolved,
+        _ => resolve(opts, name_toolchain_map)?,
+    };
+    let result = check_or_fix(opts, &resolved);
+    *cache = Some(resolved);
+    result
+}
+
+/// Waits for a change, and returns it, or returns `None` if interrupted
+fn wait(rx: &Receiver<Message>, roots: &Roots) -> Result<Option<Change>> {
+    let mut change = Change::None;
+
+    loop {
+        let message = if change == Change::None {
+            rx.recv().ok()
+        } else {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(message) => Some(message),
+                Err(RecvTimeoutError::Timeout) => return Ok(Some(change)),
+                Err(RecvTimeoutError::Disconnected) => None,
+            }
+        };
+
+        let event = match message {
+            Some(Message::Event(result)) => result.with_context(|| "File watcher failed")?,
+            Some(Message::Interrupt) | None => return Ok(None),
+        };
+
+        if matches!(event.kind, EventKind::Access(_)) {
+            continue;
+        }
+
+        for path in &event.paths {
+            change = change.max(roots.classify(path));
+        }
+    }
+}
+
+impl Roots {
+    fn classify(&self, path: &Path) -> Change {
+        if path
+            .components()
+            .any(|component| component.as_os_str() == OsStr::new(".git"))
+            || self.ignored.iter().any(|dir| path.starts_with(dir))
+            || !self.is_source(path)
+        {
+            Change::None
+        } else if self.libraries.iter().any(|root| path.starts_with(root)) {
+            Change::Library
+        } else {
+            Change::Workspace
+        }
+    }
+
+    // smoelius: A path is judged by the sources of the most specific root containing it, so that a
+    // library's `package.include` key applies to the library even if it is within the workspace.
+    fn is_source(&self, path: &Path) -> bool {
+        self.sources
+            .iter()
+            .filter(|sources| path.starts_with(sources.root()))
+            .max_by_key(|sources| sources.root().components().count())
+            .map_or(true, |sources| sources.contains(path))
+    }
+}
+
+// smoelius: Target directories are ignored. Otherwise, each check would trigger another. Files
+// that are not sources (e.g., editor backups) are ignored as well.
+fn roots(opts: &Dylint, name_toolchain_map: &NameToolchainMap) -> Result<Roots> {
+    let metadata = workspace_metadata(opts)?;
+
+    let libraries = name_toolchain_map
+        .get_or_try_init()?
+        .iter()
+        .filter(|(name, _)| opts.all || opts.libs.contains(name) || opts.names.contains(name))
+        .flat_map(|(_, toolchain_map)| toolchain_map.values().flatten())
+        .filter_map(MaybeLibrary::source_dir)
+        .collect::<BTreeSet<_>>();
+
+    let ignored = std::iter::once(metadata.target_directory.clone().into_std_path_buf())
+        .chain(libraries.iter().map(|root| root.join("target")))
+        .collect();
+
+    let workspace = metadata.workspace_root.into_std_path_buf();
+
+    let sources = std::iter::once(&workspace)
+        .chain(libraries.iter())
+        .map(|root| Sources::new(root))
+        .collect::<Result<_>>()?;
+
+    Ok(Roots {
+        workspace,
+        libraries,
+        ignored,
+        sources,
+    })
+}
+
+fn clear(opts: &Dylint) {
+    if !opts.no_clear && std::io::stderr().is_terminal() {
+        eprint!("{CLEAR}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classify() {
+        let roots = Roots {
+            workspace: PathBuf::from("/ws"),
+            libraries: [PathBuf::from("/ws/lints/my_lint"), PathBuf::from("/lib")]
+                .into_iter()
+                .collect(),
+            ignored: vec![PathBuf::from("/ws/target"), PathBuf::from("/lib/target")],
+            sources: Vec::new(),
+        };
+
+        for (path, change) in [
+            ("/ws/src/main.rs", Change::Workspace),
+            ("/ws/Cargo.toml", Change::Workspace),
+            ("/ws/lints/my_lint/src/lib.rs", Change::Library),
+            ("/lib/src/lib.rs", Change::Library),
+            ("/ws/target/debug/main", Change::None),
+            ("/lib/target/release/libmy_lint.so", Change::None),
+            ("/ws/.git/index", Change::None),
+        ] {
+            assert_eq!(change, roots.classify(Path::new(path)), "{path}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_then,
+    macros::{find_assert_args, first_node_macro_backtrace},
+    source::{snippet_opt, snippet_with_context},
+    ty::implements_trait,
+};
+use rustc_errors::Applicability;
+use rustc_hir::{BinOpKind, Expr, ExprKind};
+use rustc_lint::{LateContext, LateLintPass, LintContext};
+use rustc_middle::lint::in_external_macro;
+use rustc_span::{sym, Span};
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for `assert!(a == b)` and `assert!(a != b)` (and their `debug_assert!` forms), which
+    /// could use `assert_eq!` or `assert_ne!`.
+    ///
+    /// ### Why is this bad?
+    /// When `assert!(a == b)` fails, the message shows only the expression `a == b`. When
+    /// `assert_eq!(a, b)` fails, the message shows the values of `a` and `b`.
+    ///
+    /// ### Known problems
+    /// - `assert_eq!` and `assert_ne!` require their operands to implement `Debug`. If either
+    ///   operand does not, the lint explains this but does not suggest a fix.
+    /// - No fix is suggested for an `assert!` within a macro definition.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # let (x, y) = (1, 1);
+    /// assert!(x == y, "x = {x}");
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # let (x, y) = (1, 1);
+    /// assert_eq!(x, y, "x = {x}");
+    /// ```
+    pub ASSERT_EQ_OPPORTUNITY,
+    Warn,
+    "`assert!(a == b)` that could be `assert_eq!(a, b)`"
+}
+
+impl<'tcx> LateLintPass<'tcx> for AssertEqOpportunity {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        // smoelius: `first_node_macro_backtrace` yields the innermost macro call first. So if
+        // `expr` is the expansion of an `assert!` that is itself within another macro, the
+        // `assert!` is what is considered.
+        let Some(macro_call) = first_node_macro_backtrace(cx, expr).next() else {
+            return;
+        };
+
+        let macro_name = match cx.tcx.get_diagnostic_name(macro_call.def_id) {
+            Some(sym::assert_macro) => "assert",
+            Some(sym::debug_assert_macro) => "debug_assert",
+            _ => return,
+        };
+
+        if in_external_macro(cx.sess(), macro_call.span) {
+            return;
+        }
+
+        let Some((cond, _)) = find_assert_args(cx, expr, macro_call.expn) else {
+            return;
+        };
+
+        let ExprKind::Binary(op, left, right) = cond.kind else {
+            return;
+        };
+
+        // smoelius: Something like `(a == b) == c` is more likely to be intentional than
+        // `assert_eq!(a == b, c)`.
+        if is_comparison(left) || is_comparison(right) {
+            return;
+        }
+
+        let (suffix, comparison) = match op.node {
+            BinOpKind::Eq => ("eq", "an equality"),
+            BinOpKind::Ne => ("ne", "an inequality"),
+            _ => return,
+        };
+
+        let replacement = format!("{macro_name}_{suffix}");
+
+        let non_debug_tys = cx
+            .tcx
+            .get_diagnostic_item(sym::Debug)
+            .map(|debug_def_id| {
+                [left, right]
+                    .into_iter()
+                    .map(|operand| cx.typeck_results().expr_ty(operand))
+                    .filter(|&ty| !implements_trait(cx, ty, debug_def_id, &[]))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        span_lint_and_then(
+            cx,
+            ASSERT_EQ_OPPORTUNITY,
+            macro_call.span,
+            &format!("`{macro_name}!` of {comparison} comparison"),
+            |diag| {
+                if let Some(ty) = non_debug_tys.first() {
+                    diag.note(format!(
+                        "`{replacement}!` would require `{ty}` to implement `Debug`, which it does \
+                         not"
+                    ));
+                    return;
+                }
+
+                let mut applicability = Applicability::MachineApplicable;
+                if let Some(sugg) = suggestion(
+                    cx,
+                    macro_call.span,
+                    cond,
+                    macro_name,
+                    &replacement,
+                    &mut applicability,
+                ) {
+                    diag.span_suggestion(
+                        macro_call.span,
+                        format!("use `{replacement}!`"),
+                        sugg,
+                        applicability,
+                    );
+                } else {
+                    diag.help(format!("use `{replacement}!`"));
+                }
+            },
+        );
+    }
+}
+
+fn suggestion(
+    cx: &LateContext<'_>,
+    span: Span,
+    cond: &Expr<'_>,
+    macro_name: &str,
+    replacement: &str,
+    applicability: &mut Applicability,
+) -> Option<String> {
+    let ExprKind::Binary(_, left, right) = cond.kind else {
+        return None;
+    };
+    if span.from_expansion() {
+        return None;
+    }
+    let prefix = snippet_opt(cx, span.until(cond.span))?;
+    let prefix = replace_macro_name(&prefix, macro_name, replacement)?;
+    let (left, _) = snippet_with_context(cx, left.span, cond.span.ctxt(), "..", applicability);
+    let (right, _) = snippet_with_context(cx, right.span, cond.span.ctxt(), "..", applicability);
+    // smoelius: Everything after the condition, i.e., any custom message and its format arguments,
+    // is preserved as written.
+    let suffix = snippet_opt(cx, span.with_lo(cond.span.hi()))?;
+    Some(format!("{prefix}{left}, {right}{suffix}"))
+}
+
+fn is_comparison(expr: &Expr<'_>) -> bool {
+    matches!(expr.kind, ExprKind::Binary(op, _, _) if op.node.is_comparison())
+}
+
+// smoelius: `prefix` is the macro path and opening delimiter, e.g., `std::assert!(`. Only the last
+// path segment is replaced.
+fn replace_macro_name(prefix: &str, macro_name: &str, replacement: &str) -> Option<String> {
+    let bang = prefix.rfind('!')?;
+    let path = prefix[..bang].trim_end();
+    let start = path.strip_suffix(macro_name).map(str::len)?;
+    if start > 0 && !path[..start].ends_with("::") {
+        return None;
+    }
+    Some(format!(
+        "{}{replacement}{}",
+        &prefix[..start],
+        &prefix[path.len()..]
+    ))
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui::Test::src_base(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    )
+    .rustc_flags(["--edition=2021"])
+    .run();
+}
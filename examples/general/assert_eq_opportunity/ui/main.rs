@@ -0,0 +1,23 @@
+// run-rustfix
+
+fn main() {
+    let (x, y) = (1, 1);
+
+    assert!(x == y);
+    assert!(x != y + 1);
+    debug_assert!(x == y);
+    debug_assert!(x != y + 1);
+    std::assert!(x == y);
+
+    assert!(x == y, "x and y differ");
+    assert!(x == y, "x = {}, y = {}", x, y);
+    assert!(x == y, "x = {x}, y = {y}",);
+
+    // chained comparisons
+    assert!(x == y && y == x);
+    assert!((x == y) == (y == x));
+    assert!(x < y || x == y);
+
+    assert!(x <= y);
+    assert_eq!(x, y);
+}
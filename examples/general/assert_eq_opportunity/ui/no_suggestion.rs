@@ -0,0 +1,16 @@
+#[derive(PartialEq)]
+struct NotDebug(u32);
+
+macro_rules! local_assert {
+    ($a:expr, $b:expr) => {
+        assert!($a == $b)
+    };
+}
+
+fn main() {
+    let (x, y) = (1, 1);
+
+    assert!(NotDebug(x) == NotDebug(y));
+
+    local_assert!(x, y);
+}
@@ -0,0 +1,130 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{diagnostics::span_lint_and_then, match_def_path};
+use rustc_hir::{def_id::DefId, AsyncGeneratorKind, Body, BodyId, GeneratorKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{Adt, GeneratorInteriorTypeCause};
+use rustc_span::Span;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for `MutexGuard`s, `RwLockReadGuard`s, and `RwLockWriteGuard`s from `std::sync` or
+    /// `parking_lot` that are held across an `await` point inside an async function or block.
+    ///
+    /// ### Why is this bad?
+    /// The locks in `std::sync` and `parking_lot` block the thread that waits for them. If a
+    /// task yields while holding a guard, another task running on the same thread can try to
+    /// acquire the lock and block the thread forever, i.e., the program deadlocks.
+    ///
+    /// ### Known problems
+    /// Guards dropped explicitly (e.g., with `drop(guard)`) before an `await` may still be
+    /// reported ([#6353]).
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # async fn bar() {}
+    /// async fn foo(mutex: &Mutex<u32>) {
+    ///     let mut guard = mutex.lock().unwrap();
+    ///     *guard += 1;
+    ///     bar().await;
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # async fn bar() {}
+    /// async fn foo(mutex: &Mutex<u32>) {
+    ///     {
+    ///         let mut guard = mutex.lock().unwrap();
+    ///         *guard += 1;
+    ///     }
+    ///     bar().await;
+    /// }
+    /// ```
+    /// Or use an async-aware lock, such as `tokio::sync::Mutex`.
+    ///
+    /// [#6353]: https://github.com/rust-lang/rust-clippy/issues/6353
+    pub AWAIT_HOLDING_SYNC_GUARD,
+    Warn,
+    "`std::sync` and `parking_lot` lock guards held across an `await` point"
+}
+
+const SYNC_GUARDS: [&[&str]; 6] = [
+    &["std", "sync", "mutex", "MutexGuard"],
+    &["std", "sync", "rwlock", "RwLockReadGuard"],
+    &["std", "sync", "rwlock", "RwLockWriteGuard"],
+    // smoelius: `parking_lot`'s guards are type aliases for `lock_api`'s.
+    &["lock_api", "mutex", "MutexGuard"],
+    &["lock_api", "rwlock", "RwLockReadGuard"],
+    &["lock_api", "rwlock", "RwLockWriteGuard"],
+];
+
+impl LateLintPass<'_> for AwaitHoldingSyncGuard {
+    fn check_body(&mut self, cx: &LateContext<'_>, body: &'_ Body<'_>) {
+        use AsyncGeneratorKind::{Block, Closure, Fn};
+        if let Some(GeneratorKind::Async(Block | Closure | Fn)) = body.generator_kind {
+            let body_id = BodyId {
+                hir_id: body.value.hir_id,
+            };
+            let typeck_results = cx.tcx.typeck_body(body_id);
+            check_interior_types(
+                cx,
+                typeck_results
+                    .generator_interior_types
+                    .as_ref()
+                    .skip_binder(),
+                body.value.span,
+            );
+        }
+    }
+}
+
+fn check_interior_types(
+    cx: &LateContext<'_>,
+    ty_causes: &[GeneratorInteriorTypeCause<'_>],
+    span: Span,
+) {
+    for ty_cause in ty_causes {
+        if let Adt(adt, _) = ty_cause.ty.kind() {
+            if is_sync_guard(cx, adt.did()) {
+                span_lint_and_then(
+                    cx,
+                    AWAIT_HOLDING_SYNC_GUARD,
+                    ty_cause.span,
+                    &format!(
+                        "this `{}` is held across an `await` point",
+                        cx.tcx.item_name(adt.did())
+                    ),
+                    |diag| {
+                        diag.span_note(
+                            ty_cause.scope_span.unwrap_or(span),
+                            "these are all the `await` points this guard is held through",
+                        );
+                        diag.help(
+                            "consider using an async-aware lock (e.g., `tokio::sync::Mutex` or \
+                             `tokio::sync::RwLock`), or restructuring the code so that the guard \
+                             is dropped before the `await`",
+                        );
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn is_sync_guard(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    SYNC_GUARDS
+        .iter()
+        .any(|path| match_def_path(cx, def_id, path))
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
@@ -0,0 +1,76 @@
+use std::sync::{Mutex, RwLock};
+
+async fn good_scoped(mutex: &Mutex<u32>) {
+    {
+        let mut guard = mutex.lock().unwrap();
+        *guard += 1;
+    }
+    bar().await;
+}
+
+async fn good_temporary(mutex: &Mutex<u32>) {
+    *mutex.lock().unwrap() += 1;
+    bar().await;
+}
+
+async fn bad_mutex(mutex: &Mutex<u32>) {
+    let mut guard = mutex.lock().unwrap();
+    *guard += 1;
+    bar().await;
+}
+
+async fn bad_rwlock_read(rwlock: &RwLock<u32>) {
+    let guard = rwlock.read().unwrap();
+    baz(*guard).await;
+}
+
+async fn bad_rwlock_write(rwlock: &RwLock<u32>) {
+    let mut guard = rwlock.write().unwrap();
+    *guard += 1;
+    bar().await;
+}
+
+async fn bad_parking_lot(mutex: &parking_lot::Mutex<u32>) {
+    let mut guard = mutex.lock();
+    *guard += 1;
+    bar().await;
+}
+
+async fn bad_nested_block(mutex: &Mutex<u32>) {
+    {
+        let mut guard = mutex.lock().unwrap();
+        *guard += 1;
+        bar().await;
+    }
+    bar().await;
+}
+
+#[allow(clippy::manual_async_fn)]
+fn bad_async_block(mutex: &Mutex<u32>) -> impl std::future::Future<Output = ()> + '_ {
+    async move {
+        let mut guard = mutex.lock().unwrap();
+        *guard += 1;
+        bar().await
+    }
+}
+
+async fn bar() {}
+
+async fn baz(value: u32) {
+    let _ = value;
+}
+
+#[allow(unused_must_use)]
+fn main() {
+    let mutex = Mutex::new(0);
+    let rwlock = RwLock::new(0);
+    let parking_lot_mutex = parking_lot::Mutex::new(0);
+    good_scoped(&mutex);
+    good_temporary(&mutex);
+    bad_mutex(&mutex);
+    bad_rwlock_read(&rwlock);
+    bad_rwlock_write(&rwlock);
+    bad_parking_lot(&parking_lot_mutex);
+    bad_nested_block(&mutex);
+    bad_async_block(&mutex);
+}
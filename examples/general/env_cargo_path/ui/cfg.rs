@@ -0,0 +1,10 @@
+// dylint: compile-flags: --cfg test_case_b
+
+#[cfg(test_case_b)]
+fn cargo() -> Option<&'static str> {
+    option_env!("CARGO")
+}
+
+fn main() {
+    let _ = cargo();
+}
@@ -0,0 +1,203 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_help, is_expr_path_def_path, match_def_path,
+    visitors::for_each_expr_with_closures,
+};
+use if_chain::if_chain;
+use rustc_ast::AttrKind;
+use rustc_hir::{Expr, ExprKind, HirId, Node};
+use rustc_lint::{LateContext, LateLintPass};
+use serde::Deserialize;
+use std::ops::ControlFlow;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for calls to `std::env::set_var` and `std::env::remove_var`, in tests and non-test
+    /// code alike. Calls in `main` that precede every thread spawn in `main`'s body are not
+    /// flagged.
+    ///
+    /// ### Why is this bad?
+    /// Modifying the environment while another thread reads or modifies it is a data race on
+    /// most platforms, because the underlying C functions (`setenv`, `getenv`, etc.) are not
+    /// thread safe. Threads can be started by code that does not obviously start threads, e.g.,
+    /// a test harness or an async runtime. For this reason, the functions are being made
+    /// `unsafe` in a future edition.
+    ///
+    /// ### Known problems
+    /// - Only threads spawned with `std::thread::spawn`, `std::thread::scope`, or
+    ///   `std::thread::Builder::spawn` are considered. Moreover, spawns in functions called from
+    ///   `main` are not considered.
+    /// - A `main` function is considered only if it is the crate's entry point.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # fn connect() {}
+    /// std::env::set_var("DATABASE_URL", "postgres://localhost/test");
+    /// connect();
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # fn connect(_: &str) {}
+    /// connect("postgres://localhost/test");
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `allowed_attributes: Vec<String>` (default `[]`): Paths of attributes (e.g.,
+    ///   `"my_tool::allow_env_mutation"`) that allow calls within the item they are applied to.
+    ///   Since rustc accepts only known attributes, an attribute here will typically belong to a
+    ///   tool registered with `#![register_tool(...)]`.
+    pub ENV_VAR_MUTATION,
+    Warn,
+    "calls to `std::env::set_var` and `std::env::remove_var`",
+    EnvVarMutation::new()
+}
+
+const ENV_REMOVE_VAR: [&str; 3] = ["std", "env", "remove_var"];
+const ENV_SET_VAR: [&str; 3] = ["std", "env", "set_var"];
+
+const THREAD_BUILDER_SPAWN: [&str; 4] = ["std", "thread", "Builder", "spawn"];
+const THREAD_SCOPE: [&str; 4] = ["std", "thread", "scoped", "scope"];
+const THREAD_SPAWN: [&str; 3] = ["std", "thread", "spawn"];
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    allowed_attributes: Vec<String>,
+}
+
+struct EnvVarMutation {
+    config: Config,
+}
+
+impl EnvVarMutation {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+
+    fn has_allowed_attribute(&self, cx: &LateContext<'_>, hir_id: HirId) -> bool {
+        std::iter::once(hir_id)
+            .chain(cx.tcx.hir().parent_id_iter(hir_id))
+            .flat_map(|hir_id| cx.tcx.hir().attrs(hir_id))
+            .any(|attr| {
+                if let AttrKind::Normal(normal) = &attr.kind {
+                    let path = normal
+                        .item
+                        .path
+                        .segments
+                        .iter()
+                        .map(|segment| segment.ident.as_str())
+                        .collect::<Vec<_>>()
+                        .join("::");
+                    self.config.allowed_attributes.contains(&path)
+                } else {
+                    false
+                }
+            })
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for EnvVarMutation {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if let ExprKind::Call(callee, _) = expr.kind;
+            if let Some(path) = [&ENV_REMOVE_VAR, &ENV_SET_VAR]
+                .into_iter()
+                .find(|&path| is_expr_path_def_path(cx, callee, path));
+            if !self.has_allowed_attribute(cx, expr.hir_id);
+            if !is_in_main_before_thread_spawn(cx, expr);
+            then {
+                span_lint_and_help(
+                    cx,
+                    ENV_VAR_MUTATION,
+                    expr.span,
+                    &format!(
+                        "calling `{}` can race with other threads that access the environment",
+                        path.join("::")
+                    ),
+                    None,
+                    "consider passing the value to the code that needs it (e.g., as a function \
+                     argument or a field of a configuration struct); to configure a child \
+                     process, use `std::process::Command::env`",
+                );
+            }
+        }
+    }
+}
+
+// smoelius: A call in `main` is safe if no thread spawned from `main` can be running when the call
+// is made. That is the case if no spawn precedes the call, and the call is not in a loop that
+// spawns (where a spawn from a previous iteration could still be running). Spawns in closures are
+// considered, since a closure could be called before the call.
+fn is_in_main_before_thread_spawn(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let Some((main_def_id, _)) = cx.tcx.entry_fn(()) else {
+        return false;
+    };
+    let body_owner = cx.tcx.hir().enclosing_body_owner(expr.hir_id);
+    if body_owner.to_def_id() != main_def_id {
+        return false;
+    }
+    let body = cx.tcx.hir().body(cx.tcx.hir().body_owned_by(body_owner));
+
+    let mut spawn_spans = Vec::new();
+    for_each_expr_with_closures(cx, body.value, |expr| {
+        if is_thread_spawn(cx, expr) {
+            spawn_spans.push(expr.span);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    if spawn_spans
+        .iter()
+        .any(|spawn_span| spawn_span.lo() < expr.span.lo())
+    {
+        return false;
+    }
+
+    !cx.tcx
+        .hir()
+        .parent_iter(expr.hir_id)
+        .any(|(_, node)| match node {
+            Node::Expr(Expr {
+                kind: ExprKind::Loop(..),
+                span,
+                ..
+            }) => spawn_spans
+                .iter()
+                .any(|spawn_span| span.contains(*spawn_span)),
+            _ => false,
+        })
+}
+
+fn is_thread_spawn(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    match expr.kind {
+        ExprKind::Call(callee, _) => [&THREAD_SCOPE[..], &THREAD_SPAWN]
+            .into_iter()
+            .any(|path| is_expr_path_def_path(cx, callee, path)),
+        ExprKind::MethodCall(..) => cx
+            .typeck_results()
+            .type_dependent_def_id(expr.hir_id)
+            .map_or(false, |def_id| {
+                match_def_path(cx, def_id, &THREAD_BUILDER_SPAWN)
+            }),
+        _ => false,
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_allowed_attributes() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_allowed_attributes")
+        .dylint_toml(r#"env_var_mutation.allowed_attributes = ["setup::allow_env_mutation"]"#)
+        .run();
+}
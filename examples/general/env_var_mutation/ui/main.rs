@@ -0,0 +1,24 @@
+use std::{env, thread};
+
+fn main() {
+    // Not flagged: no thread has been spawned yet.
+    env::set_var("KEY", "VALUE");
+
+    for _ in 0..2 {
+        // Flagged: a thread spawned in a previous iteration could still be running.
+        env::set_var("KEY", "VALUE");
+        thread::spawn(|| {});
+    }
+
+    let handle = thread::Builder::new().spawn(|| {}).unwrap();
+
+    env::remove_var("KEY");
+
+    handle.join().unwrap();
+
+    configure();
+}
+
+fn configure() {
+    env::set_var("KEY", "VALUE");
+}
@@ -0,0 +1,19 @@
+#![feature(register_tool)]
+#![register_tool(setup)]
+
+use std::{env, thread};
+
+fn main() {
+    thread::spawn(|| {}).join().unwrap();
+    configure();
+    reset();
+}
+
+#[setup::allow_env_mutation]
+fn configure() {
+    env::set_var("KEY", "VALUE");
+}
+
+fn reset() {
+    env::remove_var("KEY");
+}
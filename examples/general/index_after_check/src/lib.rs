@@ -0,0 +1,327 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_then, eq_expr_value, get_parent_expr, higher, source::snippet_opt,
+    ty::is_type_diagnostic_item, visitors::for_each_expr,
+};
+use rustc_errors::Applicability;
+use rustc_hir::{BinOpKind, BorrowKind, Expr, ExprKind, Mutability, UnOp};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{
+    self,
+    adjustment::{Adjust, AutoBorrow, AutoBorrowMutability},
+};
+use rustc_span::{sym, Span};
+use std::ops::ControlFlow;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for indexing expressions like `map[&k]` in a branch of an `if` whose condition
+    /// checks that the same key is present, e.g., `map.get(&k).is_some()` or
+    /// `map.contains_key(&k)`. Maps (`HashMap` and `BTreeMap`), `Vec`s, `VecDeque`s, slices, and
+    /// arrays are considered.
+    ///
+    /// ### Why is this bad?
+    /// The key is looked up twice. Moreover, the indexing panics if the key is absent, so the code
+    /// depends on the check and the indexing staying in sync. `if let Some(value) = map.get(&k)`
+    /// looks up the key once and cannot panic.
+    ///
+    /// ### Known problems
+    /// - The receiver and key of the check and of the indexing are compared syntactically (after
+    ///   removing `&`s). The lint does not notice if either is modified in between.
+    /// - A fix is suggested only when the condition is exactly one `get(..).is_some()` or
+    ///   `contains_key(..)` call and the branch indexes with the key once. For more complex
+    ///   conditions, the lint warns without suggesting a fix.
+    /// - Indexing expressions that are used mutably are ignored.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # let map = HashMap::<u32, u32>::new();
+    /// # let k = 0;
+    /// if map.get(&k).is_some() {
+    ///     println!("{}", map[&k]);
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # let map = HashMap::<u32, u32>::new();
+    /// # let k = 0;
+    /// if let Some(value) = map.get(&k) {
+    ///     println!("{}", *value);
+    /// }
+    /// ```
+    pub INDEX_AFTER_CHECK,
+    Warn,
+    "indexing with a key whose presence was just checked"
+}
+
+const BINDING: &str = "value";
+
+/// A call that checks whether `key` is present in `receiver`
+struct Check<'tcx> {
+    expr: &'tcx Expr<'tcx>,
+    receiver: &'tcx Expr<'tcx>,
+    key: &'tcx Expr<'tcx>,
+    /// The key is present if and only if `expr` evaluates to `true`.
+    positive: bool,
+}
+
+impl<'tcx> LateLintPass<'tcx> for IndexAfterCheck {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        let Some(higher::If { cond, then, r#else }) = higher::If::hir(expr) else {
+            return;
+        };
+
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        // smoelius: The condition is wrapped in a `DropTemps` whose span is marked as a desugaring.
+        let cond = peel_drop_temps(cond);
+
+        // smoelius: The condition is true in `then` and false in `else`.
+        for (branch, value) in [(Some(then), true), (r#else, false)] {
+            let Some(branch) = branch else {
+                continue;
+            };
+            for check in present_keys(cx, cond, value) {
+                check_branch(cx, cond, value, &check, branch);
+            }
+        }
+    }
+}
+
+/// Returns the checks that, when `expr` evaluates to `value`, imply that a key is present
+fn present_keys<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    value: bool,
+) -> Vec<Check<'tcx>> {
+    match expr.kind {
+        ExprKind::Binary(op, left, right)
+            if (op.node == BinOpKind::And && value) || (op.node == BinOpKind::Or && !value) =>
+        {
+            let mut checks = present_keys(cx, left, value);
+            checks.extend(present_keys(cx, right, value));
+            checks
+        }
+        ExprKind::Unary(UnOp::Not, operand) => present_keys(cx, operand, !value),
+        _ => check(cx, expr)
+            .filter(|check| check.positive == value)
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn check<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<Check<'tcx>> {
+    let ExprKind::MethodCall(path, receiver, args, _) = expr.kind else {
+        return None;
+    };
+    let (receiver, key, positive) = match (path.ident.as_str(), args) {
+        ("contains_key", [key]) => (receiver, key, true),
+        (method @ ("is_some" | "is_none"), []) => {
+            let ExprKind::MethodCall(get, inner, [key], _) = receiver.kind else {
+                return None;
+            };
+            if get.ident.as_str() != "get" {
+                return None;
+            }
+            (inner, key, method == "is_some")
+        }
+        _ => return None,
+    };
+    if !is_indexable(cx, receiver) {
+        return None;
+    }
+    Some(Check {
+        expr,
+        receiver,
+        key,
+        positive,
+    })
+}
+
+fn is_indexable(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let ty = cx.typeck_results().expr_ty(expr).peel_refs();
+    matches!(ty.kind(), ty::Slice(_) | ty::Array(..))
+        || [sym::HashMap, sym::BTreeMap, sym::Vec, sym::VecDeque]
+            .into_iter()
+            .any(|diag_item| is_type_diagnostic_item(cx, ty, diag_item))
+}
+
+fn check_branch<'tcx>(
+    cx: &LateContext<'tcx>,
+    cond: &'tcx Expr<'tcx>,
+    value: bool,
+    check: &Check<'tcx>,
+    branch: &'tcx Expr<'tcx>,
+) {
+    let mut indexes = Vec::new();
+    for_each_expr(branch, |expr| {
+        if let ExprKind::Index(receiver, index) = expr.kind {
+            if !expr.span.from_expansion()
+                && eq_expr_value(cx, peel_refs(receiver), peel_refs(check.receiver))
+                && eq_expr_value(cx, peel_refs(index), peel_refs(check.key))
+                && !is_used_mutably(cx, expr)
+            {
+                indexes.push(expr);
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    // smoelius: The fix rewrites the condition, so it is offered only when there is exactly one
+    // indexing expression and the condition is the check itself.
+    let fixable = value
+        && indexes.len() == 1
+        && check.expr.hir_id == cond.hir_id
+        && !mentions_binding(cx, cond.span.to(branch.span));
+
+    for &index in &indexes {
+        span_lint_and_then(
+            cx,
+            INDEX_AFTER_CHECK,
+            index.span,
+            "indexing with a key whose presence was just checked",
+            |diag| {
+                diag.span_note(check.expr.span, "the key's presence is checked here");
+                let suggestion = if fixable {
+                    suggestion(cx, cond, check, index)
+                } else {
+                    None
+                };
+                if let Some(suggestion) = suggestion {
+                    diag.multipart_suggestion_verbose(
+                        "look up the key once with `get`",
+                        suggestion,
+                        Applicability::MachineApplicable,
+                    );
+                } else {
+                    diag.help("use `if let Some(value) = ...get(...)` to look up the key once");
+                }
+            },
+        );
+    }
+}
+
+fn suggestion(
+    cx: &LateContext<'_>,
+    cond: &Expr<'_>,
+    check: &Check<'_>,
+    index: &Expr<'_>,
+) -> Option<Vec<(Span, String)>> {
+    if cond.span.from_expansion() {
+        return None;
+    }
+    let receiver = snippet_opt(cx, check.receiver.span)?;
+    let key = snippet_opt(cx, check.key.span)?;
+    let (span, replacement) = match get_parent_expr(cx, index).map(|parent| (parent, parent.kind)) {
+        Some((parent, ExprKind::AddrOf(BorrowKind::Ref, Mutability::Not, _)))
+            if !parent.span.from_expansion() =>
+        {
+            (parent.span, BINDING.to_owned())
+        }
+        Some((_, ExprKind::MethodCall(_, receiver, _, _))) if receiver.hir_id == index.hir_id => {
+            (index.span, BINDING.to_owned())
+        }
+        Some((_, ExprKind::Field(base, _))) if base.hir_id == index.hir_id => {
+            (index.span, BINDING.to_owned())
+        }
+        _ => (index.span, format!("*{BINDING}")),
+    };
+    Some(vec![
+        (
+            cond.span,
+            format!("let Some({BINDING}) = {receiver}.get({key})"),
+        ),
+        (span, replacement),
+    ])
+}
+
+// smoelius: The `BINDING` introduced by the fix would shadow any existing variable of that name.
+fn mentions_binding(cx: &LateContext<'_>, span: Span) -> bool {
+    snippet_opt(cx, span).map_or(true, |snippet| {
+        snippet
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == BINDING)
+    })
+}
+
+fn is_used_mutably(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    if cx
+        .typeck_results()
+        .expr_adjustments(expr)
+        .iter()
+        .any(|adjustment| {
+            matches!(
+                adjustment.kind,
+                Adjust::Borrow(AutoBorrow::Ref(_, AutoBorrowMutability::Mut { .. }))
+            )
+        })
+    {
+        return true;
+    }
+    let Some(parent) = get_parent_expr(cx, expr) else {
+        return false;
+    };
+    match parent.kind {
+        ExprKind::Assign(lhs, _, _) | ExprKind::AssignOp(_, lhs, _) => lhs.hir_id == expr.hir_id,
+        ExprKind::AddrOf(_, Mutability::Mut, _) => true,
+        ExprKind::Field(base, _) | ExprKind::Index(base, _) if base.hir_id == expr.hir_id => {
+            is_used_mutably(cx, parent)
+        }
+        _ => false,
+    }
+}
+
+fn peel_refs<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::AddrOf(BorrowKind::Ref, _, inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+fn peel_drop_temps<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::DropTemps(inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
+
+#[test]
+fn each_index_is_flagged() {
+    let diagnostics = dylint_testing::check_str(
+        env!("CARGO_PKG_NAME"),
+        "pub fn f(v: &[u32], i: usize) -> u32 { if v.get(i).is_some() { v[i] + v[i] } else { 0 } }",
+    )
+    .unwrap();
+    assert_eq!(2, diagnostics.len());
+    for diagnostic in diagnostics {
+        let span = diagnostic
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .unwrap();
+        let line = &span.text[0];
+        assert_eq!(
+            "v[i]",
+            &line.text[line.highlight_start - 1..line.highlight_end - 1]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+// dylint: run-rustfix
+
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashMap};
+
+fn hash_map(map: &HashMap<String, u32>, key: &str) -> u32 {
+    if map.get(key).is_some() {
+        return map[key] + 1;
+    }
+    0
+}
+
+fn btree_map(map: &BTreeMap<u32, String>, key: u32) -> usize {
+    if map.contains_key(&key) {
+        map[&key].len()
+    } else {
+        0
+    }
+}
+
+fn vec(v: &Vec<u32>, i: usize) -> u32 {
+    if v.get(i).is_some() {
+        v[i]
+    } else {
+        0
+    }
+}
+
+fn main() {}
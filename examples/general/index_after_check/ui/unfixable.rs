@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+
+fn conjunction(map: &HashMap<u32, u32>, key: u32, flag: bool) -> u32 {
+    if flag && map.contains_key(&key) {
+        map[&key]
+    } else {
+        0
+    }
+}
+
+fn negation(map: &HashMap<u32, u32>, key: u32) -> u32 {
+    if map.get(&key).is_none() {
+        0
+    } else {
+        map[&key]
+    }
+}
+
+fn twice(v: &VecDeque<u32>, i: usize) -> u32 {
+    if v.get(i).is_some() {
+        v[i] + v[i]
+    } else {
+        0
+    }
+}
+
+fn other_key(map: &HashMap<u32, u32>, key: u32, other: u32) -> u32 {
+    if map.contains_key(&key) {
+        map[&other]
+    } else {
+        0
+    }
+}
+
+fn other_map(map: &HashMap<u32, u32>, other: &HashMap<u32, u32>, key: u32) -> u32 {
+    if map.contains_key(&key) {
+        other[&key]
+    } else {
+        0
+    }
+}
+
+fn mutable(v: &mut Vec<u32>, i: usize) {
+    if v.get(i).is_some() {
+        v[i] += 1;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,208 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_sugg, is_integer_literal, source::snippet_with_applicability,
+    ty::implements_trait,
+};
+use rustc_errors::Applicability;
+use rustc_hir::{def_id::DefId, BinOpKind, Expr, ExprKind, HirId};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, AssocKind, Ty};
+use rustc_span::{sym, Symbol};
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for comparisons of `x.len()` to zero, i.e., `x.len() == 0`, `x.len() != 0`,
+    /// `x.len() > 0`, and the same with the operands swapped, where `x`'s type has a visible
+    /// `is_empty` method. The method can be inherent, come from a trait in scope that declares
+    /// both `len` and `is_empty`, or be reached through `Deref`. Unlike Clippy's `len_zero`, this
+    /// lint applies to user-defined types.
+    ///
+    /// ### Why is this bad?
+    /// `is_empty` states the intent more directly, and for some types it is cheaper than
+    /// computing the length.
+    ///
+    /// ### Known problems
+    /// - A trait in scope that declares `is_empty` but not `len` is not considered.
+    /// - Comparisons inside a method named `is_empty` are not flagged, as replacing them would
+    ///   make the method call itself.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # let bag: Vec<u32> = Vec::new();
+    /// if bag.len() == 0 {
+    ///     println!("empty");
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # let bag: Vec<u32> = Vec::new();
+    /// if bag.is_empty() {
+    ///     println!("empty");
+    /// }
+    /// ```
+    pub LEN_ZERO_COMPARISON,
+    Warn,
+    "comparisons of `len()` to zero where `is_empty` is available"
+}
+
+impl<'tcx> LateLintPass<'tcx> for LenZeroComparison {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        let ExprKind::Binary(op, lhs, rhs) = expr.kind else {
+            return;
+        };
+
+        // smoelius: `negated` is true if the comparison holds when the receiver is not empty.
+        let (len_call, negated) = match (
+            op.node,
+            is_integer_literal(lhs, 0),
+            is_integer_literal(rhs, 0),
+        ) {
+            (BinOpKind::Eq, false, true) => (lhs, false),
+            (BinOpKind::Eq, true, false) => (rhs, false),
+            (BinOpKind::Ne | BinOpKind::Gt, false, true) => (lhs, true),
+            (BinOpKind::Ne | BinOpKind::Lt, true, false) => (rhs, true),
+            _ => return,
+        };
+
+        let ExprKind::MethodCall(path, receiver, [], _) = len_call.kind else {
+            return;
+        };
+
+        if path.ident.name != sym::len
+            || receiver.span.from_expansion()
+            || in_is_empty(cx, expr.hir_id)
+            || !has_is_empty(cx, len_call.hir_id, cx.typeck_results().expr_ty(receiver))
+        {
+            return;
+        }
+
+        let mut applicability = Applicability::MachineApplicable;
+        let receiver_snippet =
+            snippet_with_applicability(cx, receiver.span, "..", &mut applicability);
+
+        span_lint_and_sugg(
+            cx,
+            LEN_ZERO_COMPARISON,
+            expr.span,
+            "comparison of `len()` to zero",
+            "use `is_empty`",
+            format!(
+                "{}{receiver_snippet}.is_empty()",
+                if negated { "!" } else { "" }
+            ),
+            applicability,
+        );
+    }
+}
+
+fn in_is_empty(cx: &LateContext<'_>, hir_id: HirId) -> bool {
+    let owner = cx.tcx.hir().get_parent_item(hir_id);
+    cx.tcx.opt_item_name(owner.to_def_id()) == Some(Symbol::intern("is_empty"))
+}
+
+// smoelius: The types are visited in the order that method resolution visits them: the receiver's
+// type, and then the targets of successive `Deref`s. The number of steps is bounded in case of a
+// `Deref` cycle.
+fn has_is_empty<'tcx>(cx: &LateContext<'tcx>, len_call_hir_id: HirId, ty: Ty<'tcx>) -> bool {
+    const MAX_DEREFS: usize = 16;
+
+    let module = cx.tcx.parent_module(len_call_hir_id).to_def_id();
+    let traits = traits_with_is_empty(cx, len_call_hir_id);
+    let deref_trait_id = cx.tcx.lang_items().deref_trait();
+
+    let mut ty = ty.peel_refs();
+    for _ in 0..MAX_DEREFS {
+        if has_inherent_is_empty(cx, module, ty)
+            || traits
+                .iter()
+                .any(|&trait_id| implements_trait(cx, ty, trait_id, &[]))
+        {
+            return true;
+        }
+        let Some(target) = deref_trait_id.and_then(|deref_trait_id| {
+            if implements_trait(cx, ty, deref_trait_id, &[]) {
+                cx.get_associated_type(ty, deref_trait_id, "Target")
+            } else {
+                None
+            }
+        }) else {
+            return false;
+        };
+        ty = target.peel_refs();
+    }
+
+    false
+}
+
+fn has_inherent_is_empty(cx: &LateContext<'_>, module: DefId, ty: Ty<'_>) -> bool {
+    match ty.kind() {
+        ty::Adt(adt_def, _) => cx.tcx.inherent_impls(adt_def.did()).iter().any(|&impl_id| {
+            cx.tcx
+                .associated_items(impl_id)
+                .filter_by_name_unhygienic(Symbol::intern("is_empty"))
+                .any(|item| {
+                    is_is_empty(cx, item)
+                        && cx
+                            .tcx
+                            .visibility(item.def_id)
+                            .is_accessible_from(module, cx.tcx)
+                })
+        }),
+        ty::Array(..) | ty::Slice(..) | ty::Str => true,
+        _ => false,
+    }
+}
+
+// smoelius: The traits that method resolution considers for `len` are recorded for the `len` call.
+// So only traits that declare `len` are found. Of those, the ones that also declare `is_empty` are
+// returned.
+fn traits_with_is_empty(cx: &LateContext<'_>, len_call_hir_id: HirId) -> Vec<DefId> {
+    cx.tcx
+        .in_scope_traits(len_call_hir_id)
+        .unwrap_or_default()
+        .iter()
+        .map(|candidate| candidate.def_id)
+        .filter(|&trait_id| {
+            // smoelius: Only traits whose sole generic parameter is `Self` are considered, so that
+            // `implements_trait` can be called without generic arguments.
+            cx.tcx.generics_of(trait_id).count() == 1
+                && cx
+                    .tcx
+                    .associated_items(trait_id)
+                    .filter_by_name_unhygienic(Symbol::intern("is_empty"))
+                    .any(|item| is_is_empty(cx, item))
+        })
+        .collect()
+}
+
+fn is_is_empty(cx: &LateContext<'_>, item: &ty::AssocItem) -> bool {
+    item.kind == AssocKind::Fn
+        && item.fn_has_self_parameter
+        && cx
+            .tcx
+            .fn_sig(item.def_id)
+            .skip_binder()
+            .inputs()
+            .skip_binder()
+            .len()
+            == 1
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
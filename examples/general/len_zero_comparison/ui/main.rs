@@ -0,0 +1,69 @@
+use std::ops::Deref;
+
+struct Bag(Vec<u32>);
+
+impl Bag {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+}
+
+struct Wrapper(Bag);
+
+impl Deref for Wrapper {
+    type Target = Bag;
+    fn deref(&self) -> &Bag {
+        &self.0
+    }
+}
+
+struct LenOnly(Vec<u32>);
+
+impl LenOnly {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+trait Container {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+}
+
+struct Queue;
+
+impl Container for Queue {
+    fn len(&self) -> usize {
+        0
+    }
+    fn is_empty(&self) -> bool {
+        true
+    }
+}
+
+fn main() {
+    let bag = Bag(Vec::new());
+    let _ = bag.len() == 0;
+    let _ = bag.len() != 0;
+    let _ = bag.len() > 0;
+    let _ = 0 == bag.len();
+    let _ = 0 != bag.len();
+    let _ = 0 < bag.len();
+
+    let wrapper = Wrapper(Bag(Vec::new()));
+    let _ = wrapper.len() == 0;
+    let _ = 0 < wrapper.len();
+
+    let _ = Queue.len() == 0;
+
+    let values = vec![1];
+    let _ = values.len() != 0;
+
+    // negative tests
+    let _ = LenOnly(Vec::new()).len() == 0;
+    let _ = bag.len() == 1;
+}
@@ -0,0 +1,3 @@
+fn main() {
+    dylint_linting::build::emit_rustc_version_cfgs();
+}
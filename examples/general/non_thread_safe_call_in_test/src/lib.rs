@@ -23,9 +23,21 @@ pub fn register_lints(_sess: &rustc_session::Session, lint_store: &mut rustc_lin
         .register_pre_expansion_pass(|| Box::<pre_expansion::NonThreadSafeCallInTest>::default());
 
     lint_store.register_lints(&[late::NON_THREAD_SAFE_CALL_IN_TEST]);
+    register_late_pass(lint_store);
+}
+
+// smoelius: `rustc_late_pass_takes_tcx` is emitted by the build script. It allows the library to be
+// built with toolchains from before and after the late pass constructor began taking an argument.
+#[cfg(rustc_late_pass_takes_tcx)]
+fn register_late_pass(lint_store: &mut rustc_lint::LintStore) {
     lint_store.register_late_pass(|_| Box::<late::NonThreadSafeCallInTest>::default());
 }
 
+#[cfg(not(rustc_late_pass_takes_tcx))]
+fn register_late_pass(lint_store: &mut rustc_lint::LintStore) {
+    lint_store.register_late_pass(|| Box::<late::NonThreadSafeCallInTest>::default());
+}
+
 #[test]
 fn ui_pre_expansion() {
     dylint_testing::ui_test(
@@ -0,0 +1,184 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_then, path_to_local, source::snippet_opt, SpanlessEq,
+};
+use rustc_errors::Applicability;
+use rustc_hir::{
+    Arm, BindingAnnotation, Expr, ExprKind, HirId, HirIdMap, MatchSource, Pat, PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::Ty;
+use rustc_span::Symbol;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for adjacent `match` arms with identical bodies and no guards, which could be merged
+    /// into one arm with an or-pattern.
+    ///
+    /// ### Why is this bad?
+    /// Duplicated arm bodies tend to drift apart over time, e.g., when one is updated and the other
+    /// is forgotten.
+    ///
+    /// ### Known problems
+    /// - Only adjacent arms are considered. Merging non-adjacent arms would move a pattern past the
+    ///   arms in between, which could change which arm a value matches.
+    /// - Arms whose patterns bind different names are not considered, even if merging them would be
+    ///   possible after renaming.
+    /// - If there are comments between the merged arms, they are lost, and the suggestion is not
+    ///   machine applicable.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # let n = 0;
+    /// let size = match n {
+    ///     0 => "small",
+    ///     1 => "small",
+    ///     _ => "large",
+    /// };
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # let n = 0;
+    /// let size = match n {
+    ///     0 | 1 => "small",
+    ///     _ => "large",
+    /// };
+    /// ```
+    pub OR_PATTERN_OPPORTUNITY,
+    Warn,
+    "adjacent `match` arms with identical bodies"
+}
+
+impl<'tcx> LateLintPass<'tcx> for OrPatternOpportunity {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        let ExprKind::Match(_, arms, MatchSource::Normal) = expr.kind else {
+            return;
+        };
+
+        let mut start = 0;
+        while start < arms.len() {
+            let mut end = start + 1;
+            while end < arms.len() && mergeable(cx, &arms[end - 1], &arms[end]) {
+                end += 1;
+            }
+            if end - start >= 2 {
+                lint(cx, &arms[start..end]);
+            }
+            start = end;
+        }
+    }
+}
+
+fn mergeable<'tcx>(cx: &LateContext<'tcx>, lhs: &Arm<'tcx>, rhs: &Arm<'tcx>) -> bool {
+    // smoelius: A wildcard arm is not merged. `A | _ => ...` is just `_ => ...`.
+    if [lhs, rhs].into_iter().any(|arm| {
+        arm.guard.is_some() || arm.span.from_expansion() || matches!(arm.pat.kind, PatKind::Wild)
+    }) {
+        return false;
+    }
+
+    let lhs_bindings = bindings(cx, lhs.pat);
+    let rhs_bindings = bindings(cx, rhs.pat);
+    if lhs_bindings.len() != rhs_bindings.len()
+        || lhs_bindings
+            .iter()
+            .zip(&rhs_bindings)
+            .any(|(lhs, rhs)| (lhs.0, lhs.1, lhs.2) != (rhs.0, rhs.1, rhs.2))
+    {
+        return false;
+    }
+
+    // smoelius: Each arm's bindings have different `HirId`s. So a local in one body is considered
+    // equal to a local in the other if they refer to same-named bindings of the two patterns.
+    let locals = lhs_bindings
+        .iter()
+        .zip(&rhs_bindings)
+        .map(|(lhs, rhs)| (lhs.3, rhs.3))
+        .collect::<HirIdMap<_>>();
+
+    // smoelius: The `SpanlessEq` borrows `locals`, so it must be dropped first.
+    let mut spanless_eq = SpanlessEq::new(cx).expr_fallback(|lhs, rhs| {
+        path_to_local(lhs)
+            .and_then(|lhs| locals.get(&lhs))
+            .map_or(false, |&lhs| path_to_local(rhs) == Some(lhs))
+    });
+    spanless_eq.eq_expr(lhs.body, rhs.body)
+}
+
+/// Returns the bindings of `pat`, sorted by name. For an or-pattern, only the bindings of the
+/// first alternative are returned, as the others resolve to them.
+fn bindings<'tcx>(
+    cx: &LateContext<'tcx>,
+    pat: &Pat<'_>,
+) -> Vec<(Symbol, BindingAnnotation, Ty<'tcx>, HirId)> {
+    let mut bindings = Vec::new();
+    pat.each_binding_or_first(&mut |annotation, hir_id, _, ident| {
+        bindings.push((
+            ident.name,
+            annotation,
+            cx.typeck_results().node_type(hir_id),
+            hir_id,
+        ));
+    });
+    bindings.sort_by(|lhs, rhs| lhs.0.as_str().cmp(rhs.0.as_str()));
+    bindings
+}
+
+fn lint(cx: &LateContext<'_>, arms: &[Arm<'_>]) {
+    let first = &arms[0];
+    let last = &arms[arms.len() - 1];
+    let span = first.pat.span.with_hi(last.body.span.hi());
+
+    span_lint_and_then(
+        cx,
+        OR_PATTERN_OPPORTUNITY,
+        span,
+        "match arms with identical bodies",
+        |diag| {
+            let Some(sugg) = suggestion(cx, arms) else {
+                diag.help("merge them into an or-pattern");
+                return;
+            };
+
+            // smoelius: Everything after the first arm's body is replaced. So comments there would
+            // be lost. Looking for comment openers is conservative: a string literal containing
+            // `//` also makes the suggestion `MaybeIncorrect`.
+            let applicability = snippet_opt(cx, first.body.span.shrink_to_hi().with_hi(span.hi()))
+                .filter(|snippet| !snippet.contains("//") && !snippet.contains("/*"))
+                .map_or(Applicability::MaybeIncorrect, |_| {
+                    Applicability::MachineApplicable
+                });
+
+            diag.span_suggestion(span, "merge them into an or-pattern", sugg, applicability);
+        },
+    );
+}
+
+fn suggestion(cx: &LateContext<'_>, arms: &[Arm<'_>]) -> Option<String> {
+    let pats = arms
+        .iter()
+        .map(|arm| snippet_opt(cx, arm.pat.span))
+        .collect::<Option<Vec<_>>>()?;
+    // smoelius: The bodies are equal modulo spans. The first arm's is kept, comments and all.
+    let body = snippet_opt(cx, arms[0].body.span)?;
+    Some(format!("{} => {body}", pats.join(" | ")))
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
@@ -0,0 +1,78 @@
+// dylint: run-rustfix
+
+#![allow(dead_code)]
+
+enum Shape {
+    Circle(f64),
+    Square(f64),
+    Rectangle(f64, f64),
+    Point,
+    Line,
+}
+
+fn unit_variants(shape: &Shape) -> u32 {
+    match shape {
+        Shape::Point => 0,
+        Shape::Line => 0,
+        _ => 1,
+    }
+}
+
+fn same_bindings(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Circle(x) => x * 2.0,
+        Shape::Square(x) => x * 2.0,
+        Shape::Rectangle(w, h) => w + h,
+        Shape::Point | Shape::Line => 0.0,
+    }
+}
+
+fn three_arms(n: u32) -> &'static str {
+    match n {
+        0 => "small",
+        1 => "small",
+        2 => "small",
+        _ => "large",
+    }
+}
+
+fn block_bodies(n: u32) -> u32 {
+    match n {
+        0 => {
+            let m = n + 1;
+            m * 2
+        }
+        1 => {
+            let m = n + 1;
+            m * 2
+        }
+        _ => n,
+    }
+}
+
+fn differing_bindings(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Circle(_r) => 0.0,
+        Shape::Square(_s) => 0.0,
+        _ => 1.0,
+    }
+}
+
+fn guards(n: u32, flag: bool) -> &'static str {
+    match n {
+        0 if flag => "small",
+        1 => "small",
+        _ => "large",
+    }
+}
+
+fn non_adjacent(n: u32) -> &'static str {
+    match n {
+        0 => "zero",
+        1 => "one",
+        2 => "zero",
+        _ => "many",
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![allow(dead_code)]
+
+fn comment_between_arms(n: u32) -> &'static str {
+    match n {
+        0 => "small",
+        // One is small, too.
+        1 => "small",
+        _ => "large",
+    }
+}
+
+fn main() {}
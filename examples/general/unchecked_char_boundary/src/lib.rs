@@ -0,0 +1,340 @@
+#![feature(rustc_private)]
+#![feature(let_chains)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    consts::{constant, Constant},
+    diagnostics::span_lint_and_help,
+    eq_expr_value, expr_or_init, higher, is_integer_literal, is_res_lang_ctor, path_to_local,
+    ty::is_type_diagnostic_item,
+};
+use rustc_ast::{LitKind, RangeLimits};
+use rustc_hir::{
+    BinOpKind, BindingAnnotation, BorrowKind, Expr, ExprKind, HirId, LangItem, Mutability, Node,
+    Pat, PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::sym;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for slicing a `str` or `String` (e.g., `&s[..n]`), and for calls to `str::split_at`,
+    /// at byte indexes that are not known to be char boundaries. An index is known to be a char
+    /// boundary if it is `0` or the string's `len()`, if it comes from `char_indices`, `find`,
+    /// `rfind`, `floor_char_boundary`, or `ceil_char_boundary`, or if an enclosing `if` checks it
+    /// with `is_char_boundary`. Slicing an ASCII-only string literal is not flagged.
+    ///
+    /// ### Why is this bad?
+    /// Slicing a string at a byte index that is not a char boundary panics. Such an index is easy
+    /// to produce, e.g., by truncating to a fixed number of bytes, and the panic occurs only when
+    /// the string contains non-ASCII characters.
+    ///
+    /// ### Known problems
+    /// - The analysis is local. An index that is a function parameter or the result of arithmetic
+    ///   (e.g., `i + 1` after `find(',')`) is flagged, even if it is a char boundary.
+    /// - Only ranges written inside the brackets are checked. Slicing with a range stored in a
+    ///   variable is not flagged.
+    /// - Indexes from `find` and `rfind` must come from the same string that is sliced. But the
+    ///   strings are compared syntactically, and the lint does not notice if either is modified.
+    /// - `mut` bindings are not followed.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # let s = "hello, world";
+    /// let prefix = &s[..10];
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # let s = "hello, world";
+    /// let prefix = s.get(..10).unwrap_or(s);
+    /// ```
+    pub UNCHECKED_CHAR_BOUNDARY,
+    Warn,
+    "string slicing at byte indexes that may not be char boundaries"
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedCharBoundary {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        // smoelius: Each index is paired with the amount to add to it to get the position that
+        // must be a char boundary. The end of an inclusive range is one byte short of that
+        // position.
+        let (receiver, indexes, msg, help) = match expr.kind {
+            ExprKind::Index(receiver, index)
+                if is_string(cx, cx.typeck_results().expr_ty(receiver)) =>
+            {
+                let Some(higher::Range { start, end, limits }) = higher::Range::hir(index) else {
+                    return;
+                };
+                let offset = u128::from(limits == RangeLimits::Closed);
+                (
+                    receiver,
+                    [start.map(|start| (start, 0)), end.map(|end| (end, offset))],
+                    "slicing a string at a byte index that may not be a char boundary",
+                    "use `get`, which returns `None` instead of panicking, or find the index with \
+                     `char_indices`, `find`, or `floor_char_boundary`",
+                )
+            }
+            ExprKind::MethodCall(path, receiver, [index], _)
+                if path.ident.as_str() == "split_at"
+                    && cx
+                        .typeck_results()
+                        .expr_ty_adjusted(receiver)
+                        .peel_refs()
+                        .is_str() =>
+            {
+                (
+                    receiver,
+                    [Some((index, 0)), None],
+                    "splitting a string at a byte index that may not be a char boundary",
+                    "check the index with `is_char_boundary`, or find it with `char_indices`, \
+                     `find`, or `floor_char_boundary`",
+                )
+            }
+            _ => return,
+        };
+
+        let literal = str_literal(cx, receiver);
+
+        // smoelius: An ASCII-only string has a char boundary at every byte index.
+        if literal.as_ref().map_or(false, |literal| literal.is_ascii()) {
+            return;
+        }
+
+        if indexes.into_iter().flatten().all(|(index, offset)| {
+            if let Some(literal) = &literal
+                && let Some(Constant::Int(n)) = constant(cx, cx.typeck_results(), index)
+            {
+                return usize::try_from(n + offset)
+                    .map_or(false, |position| literal.is_char_boundary(position));
+            }
+            offset == 0
+                && (is_char_boundary(cx, receiver, index) || is_guarded(cx, expr, receiver, index))
+        }) {
+            return;
+        }
+
+        span_lint_and_help(cx, UNCHECKED_CHAR_BOUNDARY, expr.span, msg, None, help);
+    }
+}
+
+fn is_string<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> bool {
+    let ty = ty.peel_refs();
+    ty.is_str() || is_type_diagnostic_item(cx, ty, sym::String)
+}
+
+/// Returns the contents of `receiver` if it is a string literal, or an immutable local initialized
+/// with one
+fn str_literal(cx: &LateContext<'_>, receiver: &Expr<'_>) -> Option<String> {
+    if let ExprKind::Lit(lit) = expr_or_init(cx, peel_refs(receiver)).kind
+        && let LitKind::Str(symbol, _) = lit.node
+    {
+        Some(symbol.as_str().to_owned())
+    } else {
+        None
+    }
+}
+
+/// Returns true if `other` is syntactically the string `receiver`
+fn is_same_string(cx: &LateContext<'_>, receiver: &Expr<'_>, other: &Expr<'_>) -> bool {
+    is_string(cx, cx.typeck_results().expr_ty(other))
+        && eq_expr_value(cx, peel_refs(receiver), peel_refs(other))
+}
+
+/// Returns true if `index` is known to be a char boundary of `receiver`
+fn is_char_boundary<'tcx>(
+    cx: &LateContext<'tcx>,
+    receiver: &Expr<'_>,
+    index: &'tcx Expr<'tcx>,
+) -> bool {
+    if is_integer_literal(index, 0) {
+        return true;
+    }
+    match index.kind {
+        ExprKind::MethodCall(path, inner, args, _) => match (path.ident.as_str(), args) {
+            ("len" | "floor_char_boundary" | "ceil_char_boundary", _) => {
+                is_same_string(cx, receiver, inner)
+            }
+            ("unwrap" | "expect" | "unwrap_or_default", _) => {
+                is_char_boundary_option(cx, receiver, inner)
+            }
+            ("unwrap_or", [default]) => {
+                is_char_boundary_option(cx, receiver, inner)
+                    && is_char_boundary(cx, receiver, default)
+            }
+            _ => false,
+        },
+        _ => path_to_local(index).map_or(false, |hir_id| {
+            is_char_boundary_binding(cx, receiver, hir_id)
+        }),
+    }
+}
+
+/// Returns true if `option` is known to be `None` or `Some` of a char boundary of `receiver`
+fn is_char_boundary_option<'tcx>(
+    cx: &LateContext<'tcx>,
+    receiver: &Expr<'_>,
+    option: &'tcx Expr<'tcx>,
+) -> bool {
+    match option.kind {
+        ExprKind::MethodCall(path, inner, _, _)
+            if matches!(path.ident.as_str(), "find" | "rfind") =>
+        {
+            is_same_string(cx, receiver, inner)
+        }
+        _ => path_to_local(option)
+            .and_then(|hir_id| immutable_binding(cx, hir_id))
+            .and_then(|pat| match cx.tcx.hir().find_parent(pat.hir_id) {
+                Some(Node::Local(local)) => local.init,
+                _ => None,
+            })
+            .map_or(false, |init| is_char_boundary_option(cx, receiver, init)),
+    }
+}
+
+/// Returns true if the binding `hir_id` is known to hold a char boundary of `receiver`
+fn is_char_boundary_binding(cx: &LateContext<'_>, receiver: &Expr<'_>, hir_id: HirId) -> bool {
+    let Some(pat) = immutable_binding(cx, hir_id) else {
+        return false;
+    };
+    match cx.tcx.hir().find_parent(pat.hir_id) {
+        Some(Node::Local(local)) => local
+            .init
+            .map_or(false, |init| is_char_boundary(cx, receiver, init)),
+        Some(Node::Pat(parent)) => match parent.kind {
+            // smoelius: The first element of a `(usize, char)` tuple is assumed to be an index
+            // from `char_indices`.
+            PatKind::Tuple([first, ..], _) if first.hir_id == pat.hir_id => {
+                is_char_index(cx.typeck_results().pat_ty(parent))
+            }
+            PatKind::TupleStruct(ref qpath, [_], _)
+                if is_res_lang_ctor(
+                    cx,
+                    cx.qpath_res(qpath, parent.hir_id),
+                    LangItem::OptionSome,
+                ) =>
+            {
+                pattern_source(cx, parent).map_or(false, |source| {
+                    is_char_boundary_option(cx, receiver, source)
+                })
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn immutable_binding<'tcx>(cx: &LateContext<'tcx>, hir_id: HirId) -> Option<&'tcx Pat<'tcx>> {
+    match cx.tcx.hir().find(hir_id) {
+        Some(Node::Pat(pat))
+            if matches!(
+                pat.kind,
+                PatKind::Binding(BindingAnnotation(_, Mutability::Not), ..)
+            ) =>
+        {
+            Some(pat)
+        }
+        _ => None,
+    }
+}
+
+fn is_char_index(ty: Ty<'_>) -> bool {
+    if let ty::Tuple(tys) = ty.kind() {
+        tys.len() == 2 && matches!(tys[0].kind(), ty::Uint(ty::UintTy::Usize)) && tys[1].is_char()
+    } else {
+        false
+    }
+}
+
+/// Returns the expression that the top-level pattern `pat` is matched against
+fn pattern_source<'tcx>(cx: &LateContext<'tcx>, pat: &Pat<'_>) -> Option<&'tcx Expr<'tcx>> {
+    let hir = cx.tcx.hir();
+    match hir.find_parent(pat.hir_id)? {
+        Node::Local(local) => local.init,
+        Node::Arm(arm) => match hir.find_parent(arm.hir_id)? {
+            Node::Expr(Expr {
+                kind: ExprKind::Match(scrutinee, ..),
+                ..
+            }) => Some(scrutinee),
+            _ => None,
+        },
+        Node::Expr(Expr {
+            kind: ExprKind::Let(let_expr),
+            ..
+        }) => Some(let_expr.init),
+        _ => None,
+    }
+}
+
+/// Returns true if `expr` is in the `then` branch of an `if` whose condition checks that `index`
+/// is a char boundary of `receiver`
+fn is_guarded(
+    cx: &LateContext<'_>,
+    expr: &Expr<'_>,
+    receiver: &Expr<'_>,
+    index: &Expr<'_>,
+) -> bool {
+    let mut child_id = expr.hir_id;
+    for (parent_id, node) in cx.tcx.hir().parent_iter(expr.hir_id) {
+        if let Node::Expr(parent) = node
+            && let Some(higher::If { cond, then, .. }) = higher::If::hir(parent)
+            && then.hir_id == child_id
+            && checks_char_boundary(cx, cond, receiver, index)
+        {
+            return true;
+        }
+        child_id = parent_id;
+    }
+    false
+}
+
+fn checks_char_boundary(
+    cx: &LateContext<'_>,
+    cond: &Expr<'_>,
+    receiver: &Expr<'_>,
+    index: &Expr<'_>,
+) -> bool {
+    match peel_drop_temps(cond).kind {
+        ExprKind::Binary(op, left, right) if op.node == BinOpKind::And => {
+            checks_char_boundary(cx, left, receiver, index)
+                || checks_char_boundary(cx, right, receiver, index)
+        }
+        ExprKind::MethodCall(path, inner, [arg], _) => {
+            path.ident.as_str() == "is_char_boundary"
+                && is_same_string(cx, receiver, inner)
+                && eq_expr_value(cx, arg, index)
+        }
+        _ => false,
+    }
+}
+
+fn peel_refs<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::AddrOf(BorrowKind::Ref, _, inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+fn peel_drop_temps<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::DropTemps(inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
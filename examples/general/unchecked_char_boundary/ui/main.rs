@@ -0,0 +1,50 @@
+#![feature(round_char_boundary)]
+#![allow(unused)]
+
+fn main() {}
+
+fn constant_indexes() {
+    let _ = &"héllo"[..1];
+    let _ = &"héllo"[..2];
+    let _ = &"héllo"[1..3];
+    let _ = &"hello"[..2];
+    let literal = "wörld";
+    let _ = &literal[..2];
+    let _ = "héllo".split_at(2);
+}
+
+fn clean(s: &str, n: usize) {
+    if let Some(i) = s.find(',') {
+        let _ = &s[..i];
+        let _ = s.split_at(i);
+    }
+    let j = s.rfind(' ').unwrap_or(0);
+    let _ = &s[j..];
+    let _ = &s[..s.len()];
+    let _ = &s[0..];
+    for (i, _) in s.char_indices() {
+        let _ = &s[..i];
+    }
+    let k = s.floor_char_boundary(n);
+    let _ = &s[..k];
+    if s.is_char_boundary(n) {
+        let _ = &s[..n];
+    }
+    let owned = s.to_owned();
+    if let Some(i) = owned.find('w') {
+        let _ = &owned[i..];
+    }
+}
+
+fn flagged(s: &str, n: usize) {
+    let _ = &s[..n];
+    let _ = &s[n / 2..];
+    if let Some(i) = s.find(',') {
+        let _ = &s[i + 1..];
+    }
+    let _ = s.split_at(3);
+    let other = "wörld";
+    if let Some(i) = other.find('r') {
+        let _ = &s[..i];
+    }
+}
@@ -0,0 +1,153 @@
+#![feature(rustc_private)]
+#![recursion_limit = "256"]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::diagnostics::span_lint_and_help;
+use if_chain::if_chain;
+use rustc_hir::{def::CtorKind, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::sym;
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for `From` impls that convert a primitive numeric type (e.g., `f64`) into a tuple
+    /// struct whose only field has that same type, i.e., a "unit newtype" like
+    /// `struct Meters(f64)`.
+    ///
+    /// ### Why is this bad?
+    /// A unit newtype exists so that values with different units or scales cannot be confused. A
+    /// `From` impl lets any raw number in, e.g., through `.into()`, without the caller ever naming
+    /// the unit. A named constructor (e.g., `Meters::new`) makes the unit visible at the call site,
+    /// and a `TryFrom` impl can validate the value.
+    ///
+    /// ### Known problems
+    /// Not every single-field numeric wrapper carries a unit (e.g., a wrapper that exists only to
+    /// implement a foreign trait). Such types can be exempted with the `exempt` option (see below).
+    ///
+    /// ### Example
+    /// ```rust
+    /// pub struct Meters(f64);
+    ///
+    /// impl From<f64> for Meters {
+    ///     fn from(value: f64) -> Self {
+    ///         Self(value)
+    ///     }
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// pub struct Meters(f64);
+    ///
+    /// impl Meters {
+    ///     pub fn new(meters: f64) -> Self {
+    ///         Self(meters)
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `exempt: Vec<String>` (default `[]`): A list of type paths (e.g., `units::Ratio`) the
+    ///   lint should not check. Paths are relative to the crate root.
+    pub NUMERIC_NEWTYPE_FROM,
+    Allow,
+    "`From` impls that wrap a raw number in a unit newtype",
+    NumericNewtypeFrom::new()
+}
+
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    exempt: Vec<String>,
+}
+
+struct NumericNewtypeFrom {
+    config: Config,
+}
+
+impl NumericNewtypeFrom {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for NumericNewtypeFrom {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if !item.span.from_expansion();
+            if let ItemKind::Impl(impl_) = item.kind;
+            if let Some(of_trait) = impl_.of_trait;
+            if let Some(trait_ref) = cx.tcx.impl_trait_ref(item.owner_id);
+            let trait_ref = trait_ref.skip_binder();
+            if cx.tcx.is_diagnostic_item(sym::From, trait_ref.def_id);
+            let from_ty = trait_ref.substs.type_at(1);
+            if from_ty.is_integral() || from_ty.is_floating_point();
+            let self_ty = trait_ref.self_ty();
+            if let ty::Adt(adt_def, _) = self_ty.kind();
+            if is_newtype_of(cx, self_ty, from_ty);
+            let path = cx.tcx.def_path_str(adt_def.did());
+            if !self.config.exempt.contains(&path);
+            then {
+                let name = cx.tcx.item_name(adt_def.did());
+                span_lint_and_help(
+                    cx,
+                    NUMERIC_NEWTYPE_FROM,
+                    of_trait.path.span,
+                    &format!("`From<{from_ty}>` impl for unit newtype `{path}`"),
+                    None,
+                    &format!(
+                        "use a named constructor (e.g., `{name}::new`), or implement `TryFrom` and \
+                         validate the value"
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn is_newtype_of<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>, field_ty: Ty<'tcx>) -> bool {
+    if_chain! {
+        if let ty::Adt(adt_def, substs) = ty.kind();
+        if adt_def.is_struct();
+        let variant = adt_def.non_enum_variant();
+        if variant.ctor_kind() == Some(CtorKind::Fn);
+        if let [field] = variant.fields.raw.as_slice();
+        then {
+            field.ty(cx.tcx, substs) == field_ty
+        } else {
+            false
+        }
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_exempt() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_exempt")
+        .dylint_toml(r#"numeric_newtype_from.exempt = ["Ratio"]"#)
+        .run();
+}
+
+#[test]
+fn ui_main_rs_equal() {
+    let ui_main_rs = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/main.rs"),
+    )
+    .unwrap();
+    let ui_exempt_main_rs = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui_exempt/main.rs"),
+    )
+    .unwrap();
+    assert_eq!(ui_main_rs, ui_exempt_main_rs);
+}
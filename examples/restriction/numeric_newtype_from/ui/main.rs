@@ -0,0 +1,55 @@
+#![allow(unknown_lints)]
+#![warn(numeric_newtype_from)]
+#![allow(dead_code)]
+
+pub struct Meters(f64);
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+pub struct Seconds(u64);
+
+impl From<u64> for Seconds {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+pub struct Ratio(f64);
+
+impl From<f64> for Ratio {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+pub struct Point(f64, f64);
+
+impl From<f64> for Point {
+    fn from(value: f64) -> Self {
+        Self(value, value)
+    }
+}
+
+pub struct Kilograms {
+    value: f64,
+}
+
+impl From<f64> for Kilograms {
+    fn from(value: f64) -> Self {
+        Self { value }
+    }
+}
+
+pub struct Millis(u64);
+
+impl From<u32> for Millis {
+    fn from(value: u32) -> Self {
+        Self(u64::from(value))
+    }
+}
+
+fn main() {}
@@ -0,0 +1,232 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{diagnostics::span_lint_and_help, get_trait_def_id};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    FieldDef, FnDecl, ImplItem, ImplItemKind, Item, ItemKind, TraitItem, TraitItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, symbol::Ident, Span};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for `Option<bool>` in the parameters of public functions and in public struct
+    /// fields. Methods in trait impls are not checked, since their signatures are dictated by the
+    /// trait.
+    ///
+    /// ### Why is this bad?
+    /// An `Option<bool>` has three states, but nothing at the use site says what `None` means. A
+    /// dedicated enum with named variants makes each state self-describing.
+    ///
+    /// ### Example
+    /// ```rust
+    /// pub fn walk(follow_symlinks: Option<bool>) {}
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// pub enum FollowSymlinks {
+    ///     Always,
+    ///     Never,
+    ///     Default,
+    /// }
+    ///
+    /// pub fn walk(follow_symlinks: FollowSymlinks) {}
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `exempt_internal: bool` (default `true`): Whether to skip functions and fields that are
+    ///   not reachable from outside the crate.
+    /// - `exempt_deserialize: bool` (default `false`): Whether to skip fields of structs that
+    ///   derive `serde::Deserialize`, e.g., configuration structs in which `None` means "not
+    ///   specified".
+    pub OPTION_BOOL,
+    Allow,
+    "`Option<bool>` in public function parameters and struct fields",
+    OptionBool::new()
+}
+
+const SERDE_DESERIALIZE: [&str; 3] = ["serde", "de", "Deserialize"];
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    exempt_internal: bool,
+    exempt_deserialize: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            exempt_internal: true,
+            exempt_deserialize: false,
+        }
+    }
+}
+
+struct OptionBool {
+    config: Config,
+}
+
+impl OptionBool {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for OptionBool {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        match item.kind {
+            ItemKind::Fn(sig, _, _) => {
+                self.check_fn_params(cx, item.owner_id.def_id, sig.decl);
+            }
+            ItemKind::Struct(variant_data, _) => {
+                if self.config.exempt_deserialize
+                    && derives_deserialize(cx, item.owner_id.to_def_id())
+                {
+                    return;
+                }
+                for field in variant_data.fields() {
+                    self.check_field(cx, field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext<'tcx>, impl_item: &'tcx ImplItem<'tcx>) {
+        if_chain! {
+            if let ImplItemKind::Fn(sig, _) = impl_item.kind;
+            if cx
+                .tcx
+                .impl_of_method(impl_item.owner_id.to_def_id())
+                .and_then(|impl_def_id| cx.tcx.trait_id_of_impl(impl_def_id))
+                .is_none();
+            then {
+                self.check_fn_params(cx, impl_item.owner_id.def_id, sig.decl);
+            }
+        }
+    }
+
+    fn check_trait_item(&mut self, cx: &LateContext<'tcx>, trait_item: &'tcx TraitItem<'tcx>) {
+        if let TraitItemKind::Fn(sig, _) = trait_item.kind {
+            self.check_fn_params(cx, trait_item.owner_id.def_id, sig.decl);
+        }
+    }
+}
+
+impl OptionBool {
+    fn check_fn_params(&self, cx: &LateContext<'_>, def_id: LocalDefId, decl: &FnDecl<'_>) {
+        if self.is_exempt(cx, def_id) {
+            return;
+        }
+        let inputs = cx.tcx.fn_sig(def_id).skip_binder().skip_binder().inputs();
+        let names = cx.tcx.fn_arg_names(def_id);
+        for ((hir_ty, &ty), &name) in decl.inputs.iter().zip(inputs).zip(names) {
+            if is_option_bool(cx, ty) {
+                lint(cx, hir_ty.span, "parameter", name);
+            }
+        }
+    }
+
+    fn check_field(&self, cx: &LateContext<'_>, field: &FieldDef<'_>) {
+        if_chain! {
+            if !self.is_exempt(cx, field.def_id);
+            if is_option_bool(cx, cx.tcx.type_of(field.def_id).skip_binder());
+            then {
+                lint(cx, field.ty.span, "field", field.ident);
+            }
+        }
+    }
+
+    fn is_exempt(&self, cx: &LateContext<'_>, def_id: LocalDefId) -> bool {
+        self.config.exempt_internal && !cx.effective_visibilities.is_exported(def_id)
+    }
+}
+
+fn lint(cx: &LateContext<'_>, span: Span, kind: &str, name: Ident) {
+    if span.from_expansion() {
+        return;
+    }
+    let enum_name = upper_camel_case(name.as_str());
+    // smoelius: Tuple struct fields and parameters bound by patterns have no usable name.
+    let help = if enum_name.starts_with(char::is_alphabetic) {
+        format!(
+            "use an enum with three named variants instead, e.g., `enum {enum_name} {{ Yes, No, \
+             Unspecified }}`"
+        )
+    } else {
+        "use an enum with three named variants instead".to_owned()
+    };
+    span_lint_and_help(
+        cx,
+        OPTION_BOOL,
+        span,
+        &format!("{kind} `{name}` has type `Option<bool>`"),
+        None,
+        &help,
+    );
+}
+
+fn is_option_bool(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    if_chain! {
+        if let ty::Adt(adt_def, substs) = ty.kind();
+        if cx.tcx.is_diagnostic_item(sym::Option, adt_def.did());
+        then {
+            substs.type_at(0).is_bool()
+        } else {
+            false
+        }
+    }
+}
+
+// smoelius: Only derived implementations are considered. A hand-written `Deserialize`
+// implementation need not map `None` to "not specified".
+fn derives_deserialize(cx: &LateContext<'_>, adt_def_id: DefId) -> bool {
+    let Some(deserialize_def_id) = get_trait_def_id(cx, &SERDE_DESERIALIZE) else {
+        return false;
+    };
+    cx.tcx.all_impls(deserialize_def_id).any(|impl_def_id| {
+        cx.tcx.has_attr(impl_def_id, sym::automatically_derived)
+            && cx
+                .tcx
+                .type_of(impl_def_id)
+                .skip_binder()
+                .ty_adt_def()
+                .map_or(false, |adt_def| adt_def.did() == adt_def_id)
+    })
+}
+
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_exempt_deserialize() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_exempt_deserialize")
+        .dylint_toml("option_bool.exempt_deserialize = true")
+        .run();
+}
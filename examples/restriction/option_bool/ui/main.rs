@@ -0,0 +1,37 @@
+#![allow(unknown_lints)]
+#![warn(option_bool)]
+#![allow(dead_code, unused_variables)]
+
+pub fn walk(follow_symlinks: Option<bool>) {}
+
+fn walk_internal(follow_symlinks: Option<bool>) {}
+
+pub struct Options {
+    pub dry_run: Option<bool>,
+    verbose: Option<bool>,
+}
+
+pub struct Flag(pub Option<bool>);
+
+#[derive(serde::Deserialize)]
+pub struct Config {
+    pub color: Option<bool>,
+}
+
+impl Options {
+    pub fn set_dry_run(&mut self, dry_run: Option<bool>) {}
+
+    fn set_verbose(&mut self, verbose: Option<bool>) {}
+}
+
+pub trait Visitor {
+    fn visit(&mut self, recursive: Option<bool>);
+}
+
+impl Visitor for Options {
+    fn visit(&mut self, recursive: Option<bool>) {}
+}
+
+pub fn run(dry_run: bool, retries: Option<u32>) {}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![allow(unknown_lints)]
+#![warn(option_bool)]
+#![allow(dead_code)]
+
+#[derive(serde::Deserialize)]
+pub struct Config {
+    pub color: Option<bool>,
+}
+
+pub struct Options {
+    pub color: Option<bool>,
+}
+
+fn main() {}
@@ -0,0 +1,310 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_help,
+    is_expr_path_def_path,
+    macros::{is_panic, root_macro_call_first_node},
+    ty::is_type_diagnostic_item,
+};
+use if_chain::if_chain;
+use rustc_ast::LitKind;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::{walk_expr, FnKind, Visitor},
+    Block, Body, Expr, ExprKind, FnDecl, StmtKind, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass, LintContext};
+use rustc_middle::{lint::in_external_macro, ty};
+use rustc_span::{sym, Span};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for code that can panic in `Drop::drop`: `panic!`, `assert!` and its relatives,
+    /// `unreachable!`, `todo!`, `unimplemented!`, calls to `unwrap` or `expect` on `Option`s or
+    /// `Result`s, and indexing. Only code directly in `drop` is checked, i.e., functions and
+    /// closures that `drop` calls are not.
+    ///
+    /// Code following `if std::thread::panicking() { return; }`, and code within
+    /// `if !std::thread::panicking() { ... }`, is not flagged.
+    ///
+    /// ### Why is this bad?
+    /// Values are dropped while a thread unwinds. If a `drop` panics during unwinding, the
+    /// process aborts.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # struct Connection { pending: Option<u32> }
+    /// impl Drop for Connection {
+    ///     fn drop(&mut self) {
+    ///         assert!(self.pending.is_none());
+    ///     }
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # struct Connection { pending: Option<u32> }
+    /// impl Drop for Connection {
+    ///     fn drop(&mut self) {
+    ///         if std::thread::panicking() {
+    ///             return;
+    ///         }
+    ///         assert!(self.pending.is_none());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `allow_debug_assert: bool` (default `false`): Whether to allow `debug_assert!`,
+    ///   `debug_assert_eq!`, and `debug_assert_ne!`.
+    pub PANICS_IN_DROP,
+    Warn,
+    "code that can panic in `Drop` implementations",
+    PanicsInDrop::new()
+}
+
+const THREAD_PANICKING: [&str; 3] = ["std", "thread", "panicking"];
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    allow_debug_assert: bool,
+}
+
+struct PanicsInDrop {
+    config: Config,
+}
+
+impl PanicsInDrop {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for PanicsInDrop {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'_>,
+        body: &'tcx Body<'_>,
+        _: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_drop_method(cx, local_def_id.to_def_id()) {
+            Checker {
+                cx,
+                config: &self.config,
+            }
+            .visit_body(body);
+        }
+    }
+}
+
+fn is_drop_method(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    cx.tcx
+        .impl_of_method(def_id)
+        .and_then(|impl_def_id| cx.tcx.trait_id_of_impl(impl_def_id))
+        .map_or(false, |trait_def_id| {
+            cx.tcx.lang_items().drop_trait() == Some(trait_def_id)
+        })
+}
+
+struct Checker<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    config: &'cx Config,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for Checker<'cx, 'tcx> {
+    fn visit_block(&mut self, block: &'tcx Block<'tcx>) {
+        for stmt in block.stmts {
+            self.visit_stmt(stmt);
+            // smoelius: The rest of the block is reached only if the thread is not panicking.
+            if let StmtKind::Expr(expr) | StmtKind::Semi(expr) = stmt.kind {
+                if is_panicking_guard(self.cx, expr) {
+                    return;
+                }
+            }
+        }
+        if let Some(expr) = block.expr {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if let Some(macro_call) = root_macro_call_first_node(self.cx, expr) {
+            if self.is_allowed_debug_assert(macro_call.def_id) {
+                return;
+            }
+            if is_panicking_macro(self.cx, macro_call.def_id) {
+                lint(
+                    self.cx,
+                    macro_call.span,
+                    &format!("`{}!`", self.cx.tcx.item_name(macro_call.def_id)),
+                );
+                return;
+            }
+        }
+
+        match expr.kind {
+            // smoelius: The `then` branch of `if !std::thread::panicking() { ... }` is reached
+            // only if the thread is not panicking.
+            ExprKind::If(condition, _, r#else) => {
+                if let ExprKind::Unary(UnOp::Not, operand) = peel_drop_temps(condition).kind {
+                    if is_panicking_call(self.cx, operand) {
+                        self.visit_expr(condition);
+                        if let Some(r#else) = r#else {
+                            self.visit_expr(r#else);
+                        }
+                        return;
+                    }
+                }
+            }
+            ExprKind::MethodCall(path, receiver, _, _)
+                if !in_external_macro(self.cx.sess(), expr.span) =>
+            {
+                if_chain! {
+                    if matches!(path.ident.as_str(), "expect" | "unwrap");
+                    let receiver_ty = self.cx.typeck_results().expr_ty(receiver).peel_refs();
+                    if is_type_diagnostic_item(self.cx, receiver_ty, sym::Option)
+                        || is_type_diagnostic_item(self.cx, receiver_ty, sym::Result);
+                    then {
+                        lint(self.cx, expr.span, &format!("`{}`", path.ident));
+                    }
+                }
+            }
+            ExprKind::Index(base, index)
+                if !in_external_macro(self.cx.sess(), expr.span)
+                    && index_can_panic(self.cx, base, index) =>
+            {
+                lint(self.cx, expr.span, "indexing");
+            }
+            _ => {}
+        }
+
+        walk_expr(self, expr);
+    }
+}
+
+impl<'cx, 'tcx> Checker<'cx, 'tcx> {
+    fn is_allowed_debug_assert(&self, def_id: DefId) -> bool {
+        self.config.allow_debug_assert
+            && self
+                .cx
+                .tcx
+                .get_diagnostic_name(def_id)
+                .map_or(false, |name| {
+                    matches!(
+                        name.as_str(),
+                        "debug_assert_macro" | "debug_assert_eq_macro" | "debug_assert_ne_macro"
+                    )
+                })
+    }
+}
+
+fn is_panicking_macro(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    is_panic(cx, def_id)
+        || cx.tcx.get_diagnostic_name(def_id).map_or(false, |name| {
+            matches!(
+                name.as_str(),
+                "assert_macro"
+                    | "assert_eq_macro"
+                    | "assert_ne_macro"
+                    | "debug_assert_macro"
+                    | "debug_assert_eq_macro"
+                    | "debug_assert_ne_macro"
+                    | "todo_macro"
+                    | "unimplemented_macro"
+                    | "unreachable_macro"
+            )
+        })
+}
+
+// smoelius: A guard is an `if std::thread::panicking()` with no `else` whose `then` branch
+// returns.
+fn is_panicking_guard(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    if_chain! {
+        if let ExprKind::If(condition, then, None) = expr.kind;
+        if is_panicking_call(cx, peel_drop_temps(condition));
+        if let ExprKind::Block(block, _) = then.kind;
+        then {
+            block
+                .stmts
+                .iter()
+                .any(|stmt| match stmt.kind {
+                    StmtKind::Expr(expr) | StmtKind::Semi(expr) => {
+                        matches!(expr.kind, ExprKind::Ret(_))
+                    }
+                    _ => false,
+                })
+                || block
+                    .expr
+                    .map_or(false, |expr| matches!(expr.kind, ExprKind::Ret(_)))
+        } else {
+            false
+        }
+    }
+}
+
+fn is_panicking_call(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    if let ExprKind::Call(callee, []) = expr.kind {
+        is_expr_path_def_path(cx, callee, &THREAD_PANICKING)
+    } else {
+        false
+    }
+}
+
+fn peel_drop_temps<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::DropTemps(inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+// smoelius: Indexing an array with an in-bounds integer literal cannot panic. (An out-of-bounds
+// literal is a compile-time error.)
+fn index_can_panic(cx: &LateContext<'_>, base: &Expr<'_>, index: &Expr<'_>) -> bool {
+    if_chain! {
+        if let ty::Array(_, len) = cx.typeck_results().expr_ty(base).peel_refs().kind();
+        if let Some(len) = len.try_eval_target_usize(cx.tcx, cx.param_env);
+        if let ExprKind::Lit(lit) = index.kind;
+        if let LitKind::Int(index, _) = lit.node;
+        then {
+            u128::from(len) <= index
+        } else {
+            true
+        }
+    }
+}
+
+fn lint(cx: &LateContext<'_>, span: Span, what: &str) {
+    span_lint_and_help(
+        cx,
+        PANICS_IN_DROP,
+        span,
+        &format!("{what} in a `Drop` implementation can panic"),
+        None,
+        "a panic while the thread is already panicking aborts the process; consider handling the \
+         failure without panicking, or returning early if `std::thread::panicking()`",
+    );
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_allow_debug_assert() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_allow_debug_assert")
+        .dylint_toml("panics_in_drop.allow_debug_assert = true")
+        .run();
+}
@@ -0,0 +1,101 @@
+use std::thread;
+
+struct Unguarded {
+    name: Option<String>,
+    result: Result<u32, String>,
+    values: Vec<u32>,
+    flags: [bool; 2],
+}
+
+impl Drop for Unguarded {
+    fn drop(&mut self) {
+        let _ = self.name.as_ref().unwrap();
+        let _ = self.result.as_ref().expect("result");
+        assert!(self.values.is_empty());
+        assert_eq!(self.values.len(), 0);
+        debug_assert_ne!(self.values.len(), 1);
+        let _ = self.values[0];
+        let _ = self.flags[1];
+        if self.flags[0] {
+            panic!("flag set");
+        }
+        if self.values.len() > 1 {
+            unreachable!();
+        }
+    }
+}
+
+struct Guarded {
+    name: Option<String>,
+    values: Vec<u32>,
+}
+
+impl Drop for Guarded {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        let _ = self.name.as_ref().unwrap();
+        assert!(self.values.is_empty());
+        let _ = self.values[0];
+    }
+}
+
+struct NegatedGuard {
+    name: Option<String>,
+    values: Vec<u32>,
+}
+
+impl Drop for NegatedGuard {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            let _ = self.name.as_ref().unwrap();
+            assert!(self.values.is_empty());
+        } else {
+            let _ = self.values[0];
+        }
+    }
+}
+
+struct GuardAfter {
+    name: Option<String>,
+}
+
+impl Drop for GuardAfter {
+    fn drop(&mut self) {
+        let _ = self.name.as_ref().unwrap();
+        if thread::panicking() {
+            return;
+        }
+    }
+}
+
+struct NotDrop {
+    name: Option<String>,
+}
+
+impl NotDrop {
+    fn name(&self) -> &str {
+        assert!(self.name.is_some());
+        self.name.as_ref().unwrap()
+    }
+}
+
+fn main() {
+    let _ = Unguarded {
+        name: None,
+        result: Ok(0),
+        values: Vec::new(),
+        flags: [false; 2],
+    };
+    let _ = Guarded {
+        name: None,
+        values: Vec::new(),
+    };
+    let _ = NegatedGuard {
+        name: None,
+        values: Vec::new(),
+    };
+    let _ = GuardAfter { name: None };
+    let _ = NotDrop { name: None }.name();
+}
@@ -0,0 +1,16 @@
+struct Checked {
+    values: Vec<u32>,
+}
+
+impl Drop for Checked {
+    fn drop(&mut self) {
+        debug_assert!(self.values.is_empty());
+        debug_assert_eq!(self.values.len(), 0);
+        debug_assert_ne!(self.values.len(), 1);
+        assert!(self.values.is_empty());
+    }
+}
+
+fn main() {
+    let _ = Checked { values: Vec::new() };
+}
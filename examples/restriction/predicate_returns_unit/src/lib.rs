@@ -0,0 +1,164 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::diagnostics::span_lint_and_help;
+use if_chain::if_chain;
+use regex::Regex;
+use rustc_hir::{
+    def_id::LocalDefId, FnDecl, FnRetTy, ImplItem, ImplItemKind, Item, ItemKind, TraitItem,
+    TraitItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, symbol::Ident};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for functions whose names suggest they answer a yes/no question (e.g., `is_ready`),
+    /// but that return `Result<(), _>` or `Option<()>`. Methods in trait impls are not checked,
+    /// since their signatures are dictated by the trait.
+    ///
+    /// ### Why is this bad?
+    /// Callers must infer the answer from whether the function returned `Ok`/`Some`, which makes
+    /// call sites awkward and conflates "no" with "something went wrong". Returning
+    /// `Result<bool, _>`, `bool`, or a dedicated enum makes the answer explicit.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # struct Error;
+    /// fn is_ready() -> Result<(), Error> {
+    ///     Ok(())
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # struct Error;
+    /// fn is_ready() -> Result<bool, Error> {
+    ///     Ok(true)
+    /// }
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `pattern: String` (default `"^(is|has|can|should)_"`): Regular expression that a
+    ///   function's name must match for the function to be considered a predicate.
+    pub PREDICATE_RETURNS_UNIT,
+    Allow,
+    "predicate-named functions that return `Result<(), _>` or `Option<()>`",
+    PredicateReturnsUnit::new()
+}
+
+const DEFAULT_PATTERN: &str = "^(is|has|can|should)_";
+
+#[derive(Deserialize)]
+struct Config {
+    pattern: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            pattern: DEFAULT_PATTERN.to_owned(),
+        }
+    }
+}
+
+struct PredicateReturnsUnit {
+    pattern: Regex,
+}
+
+impl PredicateReturnsUnit {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let pattern = Regex::new(&config.pattern)
+            .unwrap_or_else(|error| panic!("Could not parse `pattern`: {error}"));
+        Self { pattern }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for PredicateReturnsUnit {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let ItemKind::Fn(sig, _, _) = item.kind {
+            self.check_fn_sig(cx, item.owner_id.def_id, item.ident, sig.decl);
+        }
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext<'tcx>, impl_item: &'tcx ImplItem<'tcx>) {
+        if_chain! {
+            if let ImplItemKind::Fn(sig, _) = impl_item.kind;
+            if cx
+                .tcx
+                .impl_of_method(impl_item.owner_id.to_def_id())
+                .and_then(|impl_def_id| cx.tcx.trait_id_of_impl(impl_def_id))
+                .is_none();
+            then {
+                self.check_fn_sig(cx, impl_item.owner_id.def_id, impl_item.ident, sig.decl);
+            }
+        }
+    }
+
+    fn check_trait_item(&mut self, cx: &LateContext<'tcx>, trait_item: &'tcx TraitItem<'tcx>) {
+        if let TraitItemKind::Fn(sig, _) = trait_item.kind {
+            self.check_fn_sig(cx, trait_item.owner_id.def_id, trait_item.ident, sig.decl);
+        }
+    }
+}
+
+impl PredicateReturnsUnit {
+    fn check_fn_sig(
+        &self,
+        cx: &LateContext<'_>,
+        def_id: LocalDefId,
+        ident: Ident,
+        decl: &FnDecl<'_>,
+    ) {
+        if_chain! {
+            if !ident.span.from_expansion();
+            if self.pattern.is_match(ident.as_str());
+            if let FnRetTy::Return(hir_ty) = decl.output;
+            let output = cx.tcx.fn_sig(def_id).skip_binder().skip_binder().output();
+            if let Some((actual, suggestion)) = return_types(cx, output);
+            then {
+                span_lint_and_help(
+                    cx,
+                    PREDICATE_RETURNS_UNIT,
+                    hir_ty.span,
+                    &format!("`{ident}` is named like a predicate, but returns `{actual}`"),
+                    None,
+                    &format!("return `{suggestion}` or a dedicated enum, or rename the function"),
+                );
+            }
+        }
+    }
+}
+
+// smoelius: Returns the actual return type (abbreviated) and the suggested one.
+fn return_types(cx: &LateContext<'_>, ty: Ty<'_>) -> Option<(&'static str, &'static str)> {
+    let ty::Adt(adt_def, substs) = ty.kind() else {
+        return None;
+    };
+    let return_types = if cx.tcx.is_diagnostic_item(sym::Result, adt_def.did()) {
+        ("Result<(), _>", "Result<bool, _>")
+    } else if cx.tcx.is_diagnostic_item(sym::Option, adt_def.did()) {
+        ("Option<()>", "bool")
+    } else {
+        return None;
+    };
+    substs.type_at(0).is_unit().then_some(return_types)
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_pattern() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_pattern")
+        .dylint_toml(r#"predicate_returns_unit.pattern = "^get_""#)
+        .run();
+}
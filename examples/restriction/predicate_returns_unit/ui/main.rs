@@ -0,0 +1,59 @@
+#![allow(unknown_lints)]
+#![warn(predicate_returns_unit)]
+#![allow(dead_code)]
+
+struct Error;
+
+fn is_ready() -> Result<(), Error> {
+    Ok(())
+}
+
+fn has_children() -> Option<()> {
+    None
+}
+
+fn can_write() -> std::io::Result<()> {
+    Ok(())
+}
+
+fn should_retry() -> Result<bool, Error> {
+    Ok(false)
+}
+
+fn is_empty() -> bool {
+    true
+}
+
+fn island() -> Option<()> {
+    None
+}
+
+fn check_ready() -> Result<(), Error> {
+    Ok(())
+}
+
+fn get_value() -> Option<()> {
+    None
+}
+
+struct Widget;
+
+impl Widget {
+    fn is_valid(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+trait Validate {
+    fn is_valid(&self) -> Result<(), Error>;
+}
+
+struct Gadget;
+
+impl Validate for Gadget {
+    fn is_valid(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![allow(unknown_lints)]
+#![warn(predicate_returns_unit)]
+#![allow(dead_code)]
+
+fn is_ready() -> Option<()> {
+    None
+}
+
+fn get_value() -> Option<()> {
+    None
+}
+
+fn main() {}
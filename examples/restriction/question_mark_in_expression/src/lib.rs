@@ -1,14 +1,33 @@
 #![feature(rustc_private)]
 #![warn(unused_extern_crates)]
 
+extern crate rustc_data_structures;
+extern crate rustc_errors;
 extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
 
-use clippy_utils::diagnostics::span_lint_and_help;
+use clippy_utils::{
+    diagnostics::span_lint_and_then,
+    macros::macro_backtrace,
+    source::{indent_of, snippet_opt},
+    ty::needs_ordered_drop,
+    visitors::for_each_expr,
+};
 use if_chain::if_chain;
-use rustc_hir::{Expr, ExprKind, HirId, LangItem, MatchSource, Node, QPath};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_errors::Applicability;
+use rustc_hir::{
+    intravisit::{walk_body, Visitor},
+    BinOpKind, Body, Expr, ExprKind, HirId, LangItem, LoopSource, MatchSource, Node, QPath,
+};
 use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::hir::nested_filter;
+use rustc_span::{symbol::Ident, Pos, Span};
+use serde::Deserialize;
+use std::{cmp::Reverse, ops::ControlFlow};
 
-dylint_linting::declare_late_lint! {
+dylint_linting::impl_late_lint! {
     /// ### What it does
     /// Checks for `?` operators embedded within a larger expression.
     ///
@@ -31,14 +50,131 @@ dylint_linting::declare_late_lint! {
     /// Ok(PathBuf::from(&val))
     /// # })();
     /// ```
+    ///
+    /// When the `?` is evaluated unconditionally by its enclosing statement (e.g., it is a
+    /// function argument or a binary operand), the lint suggests hoisting it into a `let`
+    /// binding before that statement. If a statement contains multiple such `?`s, the suggestion
+    /// hoists all of them, in the order in which they are evaluated.
+    ///
+    /// ### Configuration
+    /// - `exempt_contexts: Vec<String>` (default `[]`): Contexts in which a `?` is not flagged.
+    ///   Each element is one of the following:
+    ///   - `"struct-init"`: the base of a struct expression, e.g., `..base()?`. (A `?` in a field
+    ///     initializer is not flagged regardless.)
+    ///   - `"fn-arg"`: an argument of a function or method call, e.g., `f(x()?)`.
+    ///   - `"index"`: the index of an index expression, e.g., `xs[i()?]`.
+    ///   - `"macro:<name>"`: an argument of the macro `<name>`, e.g., `"macro:vec"` exempts
+    ///     `vec![x()?]`.
     pub QUESTION_MARK_IN_EXPRESSION,
     Warn,
-    "`?` operators embedded within an expression"
+    "`?` operators embedded within an expression",
+    QuestionMarkInExpression::new()
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    exempt_contexts: Vec<Context>,
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+enum Context {
+    StructInit,
+    FnArg,
+    Index,
+    Macro(String),
+}
+
+impl TryFrom<String> for Context {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "struct-init" => Ok(Self::StructInit),
+            "fn-arg" => Ok(Self::FnArg),
+            "index" => Ok(Self::Index),
+            _ => value
+                .strip_prefix("macro:")
+                .filter(|name| !name.is_empty())
+                .map(|name| Self::Macro(name.to_owned()))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown context `{value}`; expected `struct-init`, `fn-arg`, `index`, or \
+                         `macro:<name>`"
+                    )
+                }),
+        }
+    }
+}
+
+struct QuestionMarkInExpression {
+    config: Config,
 }
 
-#[allow(clippy::collapsible_match)]
+impl QuestionMarkInExpression {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+
+    fn is_exempt(&self, cx: &LateContext<'_>, ancestor: &Expr<'_>, child_hir_id: HirId) -> bool {
+        self.config
+            .exempt_contexts
+            .iter()
+            .any(|context| match context {
+                Context::StructInit => matches!(ancestor.kind, ExprKind::Struct(..)),
+                // smoelius: Calls that a macro introduces (e.g., those in the expansion of
+                // `format_args!`) are not function arguments as far as the user is concerned.
+                Context::FnArg => {
+                    !ancestor.span.from_expansion()
+                        && match ancestor.kind {
+                            ExprKind::Call(_, args) | ExprKind::MethodCall(_, _, args, _) => {
+                                args.iter().any(|arg| arg.hir_id == child_hir_id)
+                            }
+                            _ => false,
+                        }
+                }
+                Context::Index => matches!(
+                    ancestor.kind,
+                    ExprKind::Index(_, index) if index.hir_id == child_hir_id
+                ),
+                Context::Macro(name) => macro_backtrace(ancestor.span)
+                    .any(|macro_call| cx.tcx.item_name(macro_call.def_id).as_str() == name),
+            })
+    }
+}
+
+const HELP: &str = "consider breaking this up into multiple expressions";
+
 impl<'tcx> LateLintPass<'tcx> for QuestionMarkInExpression {
-    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &Expr<'_>) {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if !self.is_flagged(cx, expr) {
+            return;
+        }
+
+        let suggestion = self.hoist_suggestion(cx, expr);
+
+        span_lint_and_then(
+            cx,
+            QUESTION_MARK_IN_EXPRESSION,
+            expr.span,
+            "using the `?` operator within an expression",
+            |diag| {
+                if let Some((sugg, applicability)) = suggestion {
+                    diag.multipart_suggestion(HELP, sugg, applicability);
+                } else {
+                    diag.help(HELP);
+                }
+            },
+        );
+    }
+}
+
+impl QuestionMarkInExpression {
+    #[allow(clippy::collapsible_match)]
+    fn is_flagged(&self, cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
         if_chain! {
             if !cx
                 .tcx
@@ -56,16 +192,265 @@ impl<'tcx> LateLintPass<'tcx> for QuestionMarkInExpression {
                 ExprKind::AssignOp(_, _, expr) => expr.hir_id == child_hir_id,
                 _ => false,
             };
+            if !self.is_exempt(cx, ancestor, child_hir_id);
             then {
-                span_lint_and_help(
-                    cx,
-                    QUESTION_MARK_IN_EXPRESSION,
-                    expr.span,
-                    "using the `?` operator within an expression",
-                    None,
-                    "consider breaking this up into multiple expressions",
-                );
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    // smoelius: The suggestion introduces one binding per hoisted `?`, immediately before the
+    // enclosing statement. Only the diagnostic for the statement's first `?` carries the
+    // suggestion. Were each diagnostic to carry one, rustfix would see overlapping suggestions.
+    fn hoist_suggestion<'tcx>(
+        &self,
+        cx: &LateContext<'tcx>,
+        expr: &'tcx Expr<'tcx>,
+    ) -> Option<(Vec<(Span, String)>, Applicability)> {
+        let anchor = hoist_anchor(cx, expr)?;
+        let body = cx.tcx.hir().body(cx.enclosing_body?);
+
+        let mut hoisted = Vec::new();
+        for_each_expr(body.value, |expr| {
+            if self.is_flagged(cx, expr)
+                && hoist_anchor(cx, expr).map(|anchor| anchor.hir_id) == Some(anchor.hir_id)
+            {
+                hoisted.push(expr);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+
+        // smoelius: A `?` nested within another ends before the other does. So sorting by end
+        // position puts the `?`s in evaluation order.
+        hoisted.sort_by_key(|expr| expr.span.hi());
+
+        if hoisted.first()?.hir_id != expr.hir_id {
+            return None;
+        }
+
+        let mut names = used_names(cx, body);
+        let indent = " ".repeat(indent_of(cx, anchor.span)?);
+        let mut bindings = String::new();
+        let mut replacements: Vec<(Span, String)> = Vec::new();
+        for expr in &hoisted {
+            let name = fresh_name(&mut names);
+            let snippet = snippet_with_replacements(cx, expr.span, &replacements)?;
+            bindings.push_str(&format!("let {name} = {snippet};\n{indent}"));
+            replacements.retain(|(span, _)| !expr.span.contains(*span));
+            replacements.push((expr.span, name));
+        }
+
+        let applicability = if is_reordered(body, anchor.span, &hoisted)
+            || hoisted.iter().any(|expr| has_ordered_drop(cx, expr))
+        {
+            Applicability::MaybeIncorrect
+        } else {
+            Applicability::MachineApplicable
+        };
+
+        let mut sugg = vec![(anchor.span.shrink_to_lo(), bindings)];
+        sugg.extend(replacements);
+
+        Some((sugg, applicability))
+    }
+}
+
+/// The statement, or block's trailing expression, before which a `?` is hoisted
+struct Anchor {
+    hir_id: HirId,
+    span: Span,
+}
+
+// smoelius: A `?` can be hoisted only if each expression between it and the enclosing statement
+// evaluates it exactly once and unconditionally. For example, a `?` in a closure or in the right
+// operand of `&&` cannot be hoisted.
+fn hoist_anchor(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<Anchor> {
+    if expr.span.from_expansion() {
+        return None;
+    }
+    let mut child_hir_id = expr.hir_id;
+    for (hir_id, node) in cx.tcx.hir().parent_iter(expr.hir_id) {
+        match node {
+            Node::Expr(ancestor) => {
+                if !evaluates_once(ancestor, child_hir_id) {
+                    return None;
+                }
             }
+            Node::ExprField(_) | Node::Local(_) => {}
+            Node::Stmt(stmt) => {
+                return Some(Anchor {
+                    hir_id: stmt.hir_id,
+                    span: stmt.span,
+                });
+            }
+            Node::Block(block) => {
+                let tail = block.expr.filter(|tail| tail.hir_id == child_hir_id)?;
+                // smoelius: A `while` loop's condition is the trailing expression of a block that
+                // the loop desugars into. Hoisting from the condition would evaluate it only once.
+                if let Node::Expr(Expr {
+                    kind: ExprKind::Loop(_, _, LoopSource::While, _),
+                    ..
+                }) = cx.tcx.hir().get_parent(block.hir_id)
+                {
+                    return None;
+                }
+                return Some(Anchor {
+                    hir_id: tail.hir_id,
+                    span: tail.span,
+                });
+            }
+            _ => return None,
+        }
+        child_hir_id = hir_id;
+    }
+    None
+}
+
+fn evaluates_once(expr: &Expr<'_>, child_hir_id: HirId) -> bool {
+    // smoelius: `DropTemps` expressions wrap `if` conditions, and calls to `Try::branch` are part
+    // of the desugaring of an enclosing `?`. Both have spans from expansions.
+    if expr.span.from_expansion()
+        && !matches!(expr.kind, ExprKind::DropTemps(_))
+        && !is_try_branch_call(expr)
+    {
+        return false;
+    }
+    match expr.kind {
+        ExprKind::Binary(op, lhs, _) => {
+            !matches!(op.node, BinOpKind::And | BinOpKind::Or) || lhs.hir_id == child_hir_id
+        }
+        ExprKind::If(condition, _, _) => condition.hir_id == child_hir_id,
+        ExprKind::Match(scrutinee, _, _) => scrutinee.hir_id == child_hir_id,
+        ExprKind::AddrOf(..)
+        | ExprKind::Array(_)
+        | ExprKind::Assign(..)
+        | ExprKind::AssignOp(..)
+        | ExprKind::Call(..)
+        | ExprKind::Cast(..)
+        | ExprKind::DropTemps(_)
+        | ExprKind::Field(..)
+        | ExprKind::Index(..)
+        | ExprKind::Let(_)
+        | ExprKind::MethodCall(..)
+        | ExprKind::Ret(_)
+        | ExprKind::Struct(..)
+        | ExprKind::Tup(_)
+        | ExprKind::Type(..)
+        | ExprKind::Unary(..) => true,
+        _ => false,
+    }
+}
+
+fn is_try_branch_call(expr: &Expr<'_>) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Call(
+            Expr {
+                kind: ExprKind::Path(QPath::LangItem(LangItem::TryTraitBranch, _, _)),
+                ..
+            },
+            _
+        )
+    )
+}
+
+// smoelius: A hoisted `?` is evaluated before everything else in its statement. If something that
+// could have side effects was evaluated before the `?`, the suggestion changes the program's
+// behavior, e.g., when the `?` returns early.
+fn is_reordered<'tcx>(body: &'tcx Body<'tcx>, anchor: Span, hoisted: &[&Expr<'_>]) -> bool {
+    for_each_expr(body.value, |expr| {
+        let span = expr.span.source_callsite();
+        if matches!(
+            expr.kind,
+            ExprKind::Assign(..)
+                | ExprKind::AssignOp(..)
+                | ExprKind::Call(..)
+                | ExprKind::MethodCall(..)
+        ) && anchor.contains(span)
+            && hoisted.iter().any(|expr| span.hi() <= expr.span.lo())
+            && !hoisted.iter().any(|expr| expr.span.contains(span))
+        {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .is_some()
+}
+
+// smoelius: Temporaries created while evaluating a hoisted `?` are dropped at the end of the new
+// `let` statement, and the `?`'s value is dropped at the end of the enclosing block. Either could
+// change the order in which values are dropped.
+fn has_ordered_drop<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> bool {
+    for_each_expr(expr, |expr| {
+        if needs_ordered_drop(cx, cx.typeck_results().expr_ty(expr)) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .is_some()
+}
+
+fn snippet_with_replacements(
+    cx: &LateContext<'_>,
+    span: Span,
+    replacements: &[(Span, String)],
+) -> Option<String> {
+    let mut snippet = snippet_opt(cx, span)?;
+    let mut inner = replacements
+        .iter()
+        .filter(|(inner, _)| span.contains(*inner))
+        .collect::<Vec<_>>();
+    inner.sort_by_key(|(inner, _)| Reverse(inner.lo()));
+    for (inner, name) in inner {
+        let start = (inner.lo() - span.lo()).to_usize();
+        let end = (inner.hi() - span.lo()).to_usize();
+        snippet.replace_range(start..end, name);
+    }
+    Some(snippet)
+}
+
+// smoelius: A fresh binding must not shadow anything that the body refers to. So every identifier
+// in the body is considered used. Identifiers with dummy spans (e.g., the `val` in the desugaring
+// of `?`) are not visible to the user and are ignored.
+fn used_names<'tcx>(cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) -> FxHashSet<String> {
+    let mut visitor = NameCollector {
+        cx,
+        names: FxHashSet::default(),
+    };
+    walk_body(&mut visitor, body);
+    visitor.names
+}
+
+fn fresh_name(names: &mut FxHashSet<String>) -> String {
+    let mut name = String::from("val");
+    let mut i = 0;
+    while names.contains(&name) {
+        i += 1;
+        name = format!("val_{i}");
+    }
+    names.insert(name.clone());
+    name
+}
+
+struct NameCollector<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    names: FxHashSet<String>,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for NameCollector<'cx, 'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.cx.tcx.hir()
+    }
+
+    fn visit_ident(&mut self, ident: Ident) {
+        if !ident.span.is_dummy() {
+            self.names.insert(ident.name.to_string());
         }
     }
 }
@@ -110,3 +495,49 @@ fn ui_example() {
 fn ui_examples() {
     dylint_testing::ui_test_examples(env!("CARGO_PKG_NAME"));
 }
+
+#[cfg(test)]
+mod test {
+    use std::{fs::copy, path::Path};
+    use tempfile::tempdir;
+
+    #[test]
+    fn default_contexts() {
+        test_contexts("", "default.stderr");
+    }
+
+    #[test]
+    fn exempt_struct_init() {
+        test_contexts(r#"exempt_contexts = ["struct-init"]"#, "struct_init.stderr");
+    }
+
+    #[test]
+    fn exempt_fn_arg() {
+        test_contexts(r#"exempt_contexts = ["fn-arg"]"#, "fn_arg.stderr");
+    }
+
+    #[test]
+    fn exempt_index() {
+        test_contexts(r#"exempt_contexts = ["index"]"#, "index.stderr");
+    }
+
+    #[test]
+    fn exempt_macro() {
+        test_contexts(r#"exempt_contexts = ["macro:vec"]"#, "macro_vec.stderr");
+    }
+
+    // smoelius: Every test uses the same source file, and each configuration has its own expected
+    // stderr. So the source file and the stderr file are copied to a temporary directory as
+    // `main.rs` and `main.stderr`.
+    fn test_contexts(dylint_toml: &str, stderr: &str) {
+        let src_base = Path::new(env!("CARGO_MANIFEST_DIR")).join("ui_contexts");
+        let tempdir = tempdir().unwrap();
+
+        copy(src_base.join("main.rs"), tempdir.path().join("main.rs")).unwrap();
+        copy(src_base.join(stderr), tempdir.path().join("main.stderr")).unwrap();
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml(format!("[question_mark_in_expression]\n{dylint_toml}"))
+            .run();
+    }
+}
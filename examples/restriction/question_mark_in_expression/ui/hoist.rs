@@ -0,0 +1,44 @@
+// run-rustfix
+
+use std::{convert::identity, num::ParseIntError};
+
+fn main() {
+    let _ = fn_arg();
+    let _ = binary_operands();
+    let _ = name_collision();
+    let _ = nested();
+    let _ = tail();
+}
+
+fn fn_arg() -> Result<i32, ParseIntError> {
+    let x = identity(parse()?);
+    Ok(x)
+}
+
+fn binary_operands() -> Result<i32, ParseIntError> {
+    let x = identity(parse()? + parse()?);
+    Ok(x)
+}
+
+fn name_collision() -> Result<i32, ParseIntError> {
+    let val = 1;
+    let x = identity(parse()? + val);
+    Ok(x)
+}
+
+fn nested() -> Result<i32, ParseIntError> {
+    let x = identity(increment(parse()?)?);
+    Ok(x)
+}
+
+fn tail() -> Result<i32, ParseIntError> {
+    Ok(identity(parse()?))
+}
+
+fn parse() -> Result<i32, ParseIntError> {
+    "0".parse()
+}
+
+fn increment(x: i32) -> Result<i32, ParseIntError> {
+    Ok(x + 1)
+}
@@ -0,0 +1,38 @@
+use std::{
+    convert::identity,
+    num::ParseIntError,
+    sync::{Mutex, MutexGuard},
+};
+
+fn main() {
+    let _ = reordered();
+    let _ = ordered_drop(&Mutex::new(0));
+}
+
+// `zero()` is no longer called when `parse()?` returns early.
+fn reordered() -> Result<i32, ParseIntError> {
+    let x = add(zero(), parse()?);
+    Ok(x)
+}
+
+// The guard is held until the end of the block rather than the end of the statement.
+fn ordered_drop(mutex: &Mutex<i32>) -> Result<i32, ()> {
+    let x = identity(*lock(mutex)?);
+    Ok(x)
+}
+
+fn parse() -> Result<i32, ParseIntError> {
+    "0".parse()
+}
+
+fn add(x: i32, y: i32) -> i32 {
+    x + y
+}
+
+fn zero() -> i32 {
+    0
+}
+
+fn lock(mutex: &Mutex<i32>) -> Result<MutexGuard<'_, i32>, ()> {
+    mutex.lock().map_err(|_| ())
+}
@@ -0,0 +1,29 @@
+use std::{convert::identity, num::ParseIntError};
+
+fn main() {
+    let _ = short_circuit();
+    let _ = while_condition();
+    let _ = closure();
+    let _ = macro_argument();
+}
+
+fn short_circuit() -> Result<bool, ParseIntError> {
+    Ok(true && identity(parse()? > 0))
+}
+
+fn while_condition() -> Result<(), ParseIntError> {
+    while identity(parse()?) < 0 {}
+    Ok(())
+}
+
+fn closure() -> Result<Option<i32>, ParseIntError> {
+    Some(0).map(|x| Ok(identity(x + parse()?))).transpose()
+}
+
+fn macro_argument() -> Result<Vec<i32>, ParseIntError> {
+    Ok(vec![parse()?])
+}
+
+fn parse() -> Result<i32, ParseIntError> {
+    "0".parse()
+}
@@ -0,0 +1,33 @@
+#![allow(dead_code)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let _ = contexts();
+}
+
+fn contexts() -> Result<(), std::num::ParseIntError> {
+    let xs = [0, 1];
+    let _ = Point { x: 0, ..point()? };
+    let _ = std::convert::identity(parse()?);
+    let _ = xs[index()?];
+    let _ = vec![parse()?];
+    // A `?` in a field initializer is not flagged, with or without exemptions.
+    let _ = Point { x: parse()?, y: 0 };
+    Ok(())
+}
+
+fn parse() -> Result<i32, std::num::ParseIntError> {
+    "0".parse()
+}
+
+fn index() -> Result<usize, std::num::ParseIntError> {
+    "0".parse()
+}
+
+fn point() -> Result<Point, std::num::ParseIntError> {
+    Ok(Point { x: 0, y: 0 })
+}
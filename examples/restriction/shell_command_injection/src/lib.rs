@@ -0,0 +1,329 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+dylint_linting::dylint_library!();
+
+extern crate rustc_hir;
+extern crate rustc_lint;
+extern crate rustc_session;
+extern crate rustc_span;
+
+use clippy_utils::{
+    consts::{constant, Constant},
+    diagnostics::{span_lint_and_help, span_lint_and_note},
+    get_parent_expr, is_expr_path_def_path, path_to_local_id, peel_hir_expr_refs,
+    ty::match_type,
+};
+use dylint_internal::paths;
+use if_chain::if_chain;
+use rustc_hir::{Expr, ExprKind, HirId, Node, PatKind, StmtKind};
+use rustc_lint::{LateContext, LateLintPass, LintStore};
+use rustc_session::{declare_lint, impl_lint_pass, Session};
+use rustc_span::Symbol;
+use serde::Deserialize;
+
+declare_lint! {
+    /// ### What it does
+    /// Checks for `Command::new` invocations of shells (e.g., `sh` or `cmd`) whose command string
+    /// (the argument following `-c` or `/C`) is built at runtime, e.g., with `format!`,
+    /// concatenation, or a variable.
+    ///
+    /// ### Why is this bad?
+    /// The shell parses the command string. So if any part of it comes from the user, the user can
+    /// inject commands of their own, e.g., a `pattern` of `x; rm -rf ~` in the example below.
+    ///
+    /// ### Known problems
+    /// - Arguments are followed through builder method calls on the value returned by
+    ///   `Command::new`, and through method calls on a local variable it is bound to, but only
+    ///   within the same block.
+    /// - Arguments passed with `args` are considered only when they are given as an array
+    ///   expression.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::process::Command;
+    /// # let pattern = "";
+    /// let _ = Command::new("sh")
+    ///     .arg("-c")
+    ///     .arg(format!("grep {pattern} file"))
+    ///     .output();
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::process::Command;
+    /// # let pattern = "";
+    /// let _ = Command::new("grep").arg(pattern).arg("file").output();
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `shells: Vec<String>` (default `["sh", "bash", "cmd", "powershell"]`): Programs that are
+    ///   considered shells. A program matches if its file name, less any `.exe` extension, matches
+    ///   one of these (ignoring case).
+    pub SHELL_COMMAND_INJECTION,
+    Warn,
+    "command strings built at runtime and passed to a shell"
+}
+
+declare_lint! {
+    /// ### What it does
+    /// Checks for `Command::new` invocations of shells whose command string is a literal. This is
+    /// the lower-severity companion of `shell_command_injection`, and is allow-by-default.
+    ///
+    /// ### Why is this bad?
+    /// A literal command string cannot be injected into. But running the program directly is
+    /// usually simpler and more portable, unless the command needs shell features such as pipes or
+    /// globs.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::process::Command;
+    /// let _ = Command::new("sh").arg("-c").arg("ls -l").output();
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::process::Command;
+    /// let _ = Command::new("ls").arg("-l").output();
+    /// ```
+    pub SHELL_COMMAND_LITERAL,
+    Allow,
+    "literal command strings passed to a shell"
+}
+
+impl_lint_pass!(ShellCommandInjection => [SHELL_COMMAND_INJECTION, SHELL_COMMAND_LITERAL]);
+
+#[allow(clippy::no_mangle_with_rust_abi)]
+#[no_mangle]
+pub fn register_lints(sess: &Session, lint_store: &mut LintStore) {
+    dylint_linting::init_config(sess);
+    lint_store.register_lints(&[SHELL_COMMAND_INJECTION, SHELL_COMMAND_LITERAL]);
+    lint_store.register_late_pass(move |_| Box::new(ShellCommandInjection::new()));
+}
+
+// smoelius: `-c` is what Unix shells and PowerShell Core accept, `/C` is what `cmd` accepts, and
+// `-Command` is what Windows PowerShell accepts.
+const COMMAND_FLAGS: &[&str] = &["-c", "/c", "-command"];
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    shells: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            shells: ["sh", "bash", "cmd", "powershell"]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
+        }
+    }
+}
+
+struct ShellCommandInjection {
+    config: Config,
+}
+
+impl ShellCommandInjection {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for ShellCommandInjection {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if !expr.span.from_expansion();
+            if let ExprKind::Call(callee, [program]) = expr.kind;
+            if is_expr_path_def_path(cx, callee, &paths::COMMAND_NEW);
+            if let Some(program) = string_constant(cx, program);
+            if self.is_shell(&program);
+            let args = command_args(cx, expr);
+            if let Some(position) = args.iter().position(|arg| {
+                arg.and_then(|arg| string_constant(cx, arg))
+                    .map_or(false, |arg| is_command_flag(&arg))
+            });
+            if let Some(Some(command)) = args.get(position + 1);
+            // smoelius: If the command string is built with `format!`, its span is in the macro's
+            // expansion.
+            let span = command.span.source_callsite();
+            then {
+                if string_constant(cx, command).is_some() {
+                    span_lint_and_note(
+                        cx,
+                        SHELL_COMMAND_LITERAL,
+                        span,
+                        &format!("passing a literal command string to `{program}`"),
+                        None,
+                        "unless the command needs shell features, consider running the program \
+                         directly",
+                    );
+                } else {
+                    span_lint_and_help(
+                        cx,
+                        SHELL_COMMAND_INJECTION,
+                        span,
+                        &format!("passing a command string built at runtime to `{program}`"),
+                        None,
+                        "pass the program to `Command::new` and each of its arguments to `.arg()`, \
+                         without a shell",
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ShellCommandInjection {
+    fn is_shell(&self, program: &str) -> bool {
+        let file_name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(program)
+            .to_ascii_lowercase();
+        let stem = file_name.strip_suffix(".exe").unwrap_or(&file_name);
+        self.config
+            .shells
+            .iter()
+            .any(|shell| shell.eq_ignore_ascii_case(stem))
+    }
+}
+
+fn is_command_flag(arg: &str) -> bool {
+    COMMAND_FLAGS
+        .iter()
+        .any(|flag| flag.eq_ignore_ascii_case(arg))
+}
+
+fn string_constant(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<String> {
+    let (expr, _) = peel_hir_expr_refs(expr);
+    if let Some(Constant::Str(s)) = constant(cx, cx.typeck_results(), expr) {
+        Some(s)
+    } else {
+        None
+    }
+}
+
+/// Returns the arguments added to the `Command` that `new_expr` creates, in order. An argument
+/// that cannot be determined (e.g., one of the elements of an `args` argument that is not an array
+/// expression) is `None`.
+fn command_args<'tcx>(
+    cx: &LateContext<'tcx>,
+    new_expr: &'tcx Expr<'tcx>,
+) -> Vec<Option<&'tcx Expr<'tcx>>> {
+    let mut args = Vec::new();
+
+    let mut expr = new_expr;
+    while let Some(parent) = get_parent_expr(cx, expr) {
+        if_chain! {
+            if let ExprKind::MethodCall(method, receiver, method_args, _) = parent.kind;
+            if receiver.hir_id == expr.hir_id;
+            if is_command_builder_call(cx, parent);
+            then {
+                push_args(&mut args, method.ident.name, method_args);
+                expr = parent;
+                continue;
+            }
+        }
+        break;
+    }
+
+    if let Some(local) = bound_local(cx, expr) {
+        follow_local(cx, local, &mut args);
+    }
+
+    args
+}
+
+// smoelius: A builder method is one that returns `&mut Command`. So, e.g., `output` ends the chain.
+fn is_command_builder_call(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let ty = cx.typeck_results().expr_ty(expr).peel_refs();
+    match_type(cx, ty, &paths::COMMAND)
+}
+
+fn push_args<'tcx>(
+    args: &mut Vec<Option<&'tcx Expr<'tcx>>>,
+    method: Symbol,
+    method_args: &'tcx [Expr<'tcx>],
+) {
+    match (method.as_str(), method_args) {
+        ("arg", [arg]) => args.push(Some(arg)),
+        ("args", [arg]) => {
+            if let ExprKind::Array(elems) = peel_hir_expr_refs(arg).0.kind {
+                args.extend(elems.iter().map(Some));
+            } else {
+                args.push(None);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// If `expr` is the initializer of a `let` statement that binds a single variable, returns the
+/// variable's `HirId`.
+fn bound_local(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<HirId> {
+    if_chain! {
+        if let Some(Node::Local(local)) = cx.tcx.hir().find_parent(expr.hir_id);
+        if local.init.map_or(false, |init| init.hir_id == expr.hir_id);
+        if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind;
+        then {
+            Some(hir_id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Appends the arguments added to `local` by the statements following its `let` statement in the
+/// enclosing block.
+fn follow_local<'tcx>(
+    cx: &LateContext<'tcx>,
+    local: HirId,
+    args: &mut Vec<Option<&'tcx Expr<'tcx>>>,
+) {
+    let hir = cx.tcx.hir();
+    let Some(Node::Local(let_stmt)) = hir.find_parent(local) else {
+        return;
+    };
+    let Some(Node::Stmt(stmt)) = hir.find_parent(let_stmt.hir_id) else {
+        return;
+    };
+    let Some(Node::Block(block)) = hir.find_parent(stmt.hir_id) else {
+        return;
+    };
+    let following = block
+        .stmts
+        .iter()
+        .skip_while(|other| other.hir_id != stmt.hir_id)
+        .skip(1)
+        .filter_map(|stmt| match stmt.kind {
+            StmtKind::Local(local) => local.init,
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => Some(expr),
+            StmtKind::Item(_) => None,
+        })
+        .chain(block.expr);
+    for expr in following {
+        let mut calls = Vec::new();
+        let mut receiver = expr;
+        while let ExprKind::MethodCall(method, inner, method_args, _) = receiver.kind {
+            calls.push((receiver, method.ident.name, method_args));
+            receiver = inner;
+        }
+        if !path_to_local_id(receiver, local) {
+            continue;
+        }
+        for (call, method, method_args) in calls.into_iter().rev() {
+            if !is_command_builder_call(cx, call) {
+                break;
+            }
+            push_args(args, method, method_args);
+        }
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
@@ -0,0 +1,63 @@
+#![allow(unknown_lints)]
+#![warn(shell_command_literal)]
+#![allow(unused_variables)]
+
+use std::process::Command;
+
+const SCRIPT: &str = "ls -l | wc -l";
+
+fn main() {
+    let pattern = std::env::args().nth(1).unwrap_or_default();
+
+    // format!-built command strings
+
+    let _ = Command::new("sh")
+        .arg("-c")
+        .arg(format!("grep {pattern} file"))
+        .output();
+
+    let _ = Command::new("/bin/bash")
+        .args(["-c", &format!("grep {pattern} file")])
+        .output();
+
+    let _ = Command::new("cmd.exe")
+        .arg("/C")
+        .arg(&format!("findstr {pattern} file"))
+        .output();
+
+    let _ = Command::new("powershell")
+        .arg("-Command")
+        .arg("Select-String ".to_owned() + &pattern)
+        .status();
+
+    let mut command = Command::new("sh");
+    command.current_dir("/tmp").arg("-c");
+    command.arg(&pattern);
+    let _ = command.output();
+
+    // literal command strings
+
+    let _ = Command::new("sh").arg("-c").arg("ls -l").output();
+
+    let _ = Command::new("bash").args(["-c", SCRIPT]).output();
+
+    // smoelius: The command string is a literal, and the user-controlled argument is passed as a
+    // positional parameter.
+    let _ = Command::new("sh")
+        .arg("-c")
+        .arg(r#"grep "$1" file"#)
+        .arg("sh")
+        .arg(&pattern)
+        .output();
+
+    // properly arg-separated invocations
+
+    let _ = Command::new("grep").arg(&pattern).arg("file").output();
+
+    let _ = Command::new("sh").arg("script.sh").arg(&pattern).output();
+
+    let _ = Command::new("shellcheck").arg("-c").arg(&pattern).output();
+
+    let args = vec!["-c".to_owned(), pattern.clone()];
+    let _ = Command::new("sh").args(&args).output();
+}
@@ -0,0 +1,160 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::diagnostics::span_lint_and_then;
+use rustc_hir::{
+    def::{DefKind, Res},
+    def_id::{DefId, CRATE_DEF_INDEX},
+    Item, ItemKind, UseKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{sym, Span, Symbol};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for `pub use` and `pub extern crate` items that re-export an entire external crate
+    /// or module, rather than specific items.
+    ///
+    /// ### Why is this bad?
+    /// The re-exported crate or module becomes part of the re-exporting crate's public API. So a
+    /// semver-incompatible change to the dependency, e.g., an upgrade to a new major version, is a
+    /// semver-incompatible change to the re-exporting crate too. Often, this coupling is
+    /// unintended.
+    ///
+    /// ### Known problems
+    /// Re-exports of `std`, `core`, and `alloc` are not flagged.
+    ///
+    /// ### Example
+    /// ```rust
+    /// pub use serde;
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// pub use serde::{Deserialize, Serialize};
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `allowed_crates: Vec<String>` (default `[]`): Names of crates that may be re-exported in
+    ///   full, e.g., by a facade crate whose purpose is to re-export them.
+    pub EXTERNAL_CRATE_REEXPORT,
+    Warn,
+    "`pub use` of an entire external crate or module",
+    ExternalCrateReexport::new()
+}
+
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    allowed_crates: Vec<String>,
+}
+
+struct ExternalCrateReexport {
+    config: Config,
+}
+
+impl ExternalCrateReexport {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for ExternalCrateReexport {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        // smoelius: Derive helpers and similar macros often expand to re-exports that their users
+        // never see.
+        if item.span.from_expansion()
+            || !cx.effective_visibilities.is_exported(item.owner_id.def_id)
+        {
+            return;
+        }
+
+        match item.kind {
+            ItemKind::ExternCrate(_) => {
+                if let Some(cnum) = cx.tcx.extern_mod_stmt_cnum(item.owner_id.def_id) {
+                    self.check_reexport(cx, item.span, cnum.as_def_id());
+                }
+            }
+            // smoelius: A `ListStem` is the `a::b` in `use a::b::{c, d}`. The `c` and `d` are
+            // separate `Single` items.
+            ItemKind::Use(path, UseKind::Single | UseKind::Glob) => {
+                if let Some(def_id) = path.res.iter().find_map(|res| match *res {
+                    Res::Def(DefKind::Mod, def_id) => Some(def_id),
+                    _ => None,
+                }) {
+                    self.check_reexport(cx, item.span, def_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ExternalCrateReexport {
+    fn check_reexport(&self, cx: &LateContext<'_>, span: Span, def_id: DefId) {
+        if def_id.is_local() {
+            return;
+        }
+
+        let crate_name = cx.tcx.crate_name(def_id.krate);
+
+        if [sym::std, sym::core, sym::alloc].contains(&crate_name)
+            || self
+                .config
+                .allowed_crates
+                .iter()
+                .any(|allowed| allowed == crate_name.as_str())
+        {
+            return;
+        }
+
+        let msg = if def_id.index == CRATE_DEF_INDEX {
+            format!("re-export of entire crate `{crate_name}`")
+        } else {
+            // smoelius: `def_path_str` would prefer a visible path, which could go through a
+            // `pub extern crate` alias.
+            let path = cx
+                .get_def_path(def_id)
+                .iter()
+                .map(Symbol::as_str)
+                .collect::<Vec<_>>()
+                .join("::");
+            format!("re-export of entire module `{path}` of crate `{crate_name}`")
+        };
+
+        span_lint_and_then(cx, EXTERNAL_CRATE_REEXPORT, span, &msg, |diag| {
+            diag.note(format!(
+                "changes to `{crate_name}`'s public API, including semver-incompatible ones, become \
+                 changes to this crate's public API"
+            ));
+            diag.help("re-export only the items that are needed");
+        });
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_allowed_crates() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_allowed_crates")
+        .dylint_toml(r#"external_crate_reexport.allowed_crates = ["serde"]"#)
+        .run();
+}
+
+// smoelius: Re-exports matter most in library crates, and a library crate's dependencies are
+// easiest to test as a Cargo package.
+#[test]
+fn ui_packages() {
+    dylint_testing::ui_test_packages(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui_packages"),
+    );
+}
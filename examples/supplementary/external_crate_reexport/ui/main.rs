@@ -0,0 +1,44 @@
+#![allow(unused_imports)]
+
+// Whole-crate re-exports
+
+pub use serde;
+
+pub use serde as serialization;
+
+pub extern crate serde as serde_crate;
+
+// Module-level re-exports
+
+pub use serde::de;
+
+pub use serde::ser::*;
+
+// Item-level re-exports
+
+pub use serde::de::{DeserializeOwned, Visitor};
+pub use serde::Deserialize;
+
+// Re-exports of the standard library
+
+pub use std::collections;
+
+// Re-exports that are not part of the public API
+
+use serde as private_serde;
+
+mod private {
+    pub use serde::de;
+}
+
+// Macro-generated re-exports
+
+macro_rules! reexport {
+    () => {
+        pub use serde::ser;
+    };
+}
+
+reexport!();
+
+fn main() {}
@@ -0,0 +1,7 @@
+#![allow(unused_imports)]
+
+pub use serde;
+
+pub use serde::de;
+
+fn main() {}
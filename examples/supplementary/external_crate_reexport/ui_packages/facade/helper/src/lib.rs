@@ -0,0 +1,5 @@
+pub mod inner {
+    pub fn f() {}
+}
+
+pub fn g() {}
@@ -0,0 +1,5 @@
+pub use helper;
+
+pub use helper::inner;
+
+pub use helper::{g, inner::f};
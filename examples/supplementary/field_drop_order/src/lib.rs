@@ -0,0 +1,228 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::diagnostics::span_lint_and_then;
+use if_chain::if_chain;
+use rustc_hir::{FieldDef, HirId, Item, ItemKind, VariantData};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, subst::GenericArgKind};
+use rustc_span::Symbol;
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for structs with a field that owns a resource (e.g., a `TempDir`) and a later field
+    /// that plausibly depends upon that resource (e.g., a `File` inside the `TempDir`).
+    ///
+    /// ### Why is this bad?
+    /// A struct's fields are dropped in declaration order. So if the owning field is declared
+    /// first, it is dropped first, i.e., while the dependent field is still alive. For example, a
+    /// `TempDir` cannot be deleted on Windows while a `File` within it is open. Reordering fields
+    /// during a refactor can thus introduce bugs that are hard to track down.
+    ///
+    /// ### Known problems
+    /// - Whether one field depends upon another is determined heuristically, by the fields' types.
+    /// - The lint does not fire if either field, or the struct itself, has a doc comment
+    ///   mentioning "drop". Such a comment is assumed to document the intended order.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use tempfile::TempDir;
+    /// struct Fixture {
+    ///     tempdir: TempDir,
+    ///     file: File,
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use tempfile::TempDir;
+    /// struct Fixture {
+    ///     file: File,
+    ///     tempdir: TempDir,
+    /// }
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `pairs: Vec<Pair>` (default `[]`): Additional owner/dependent pairs the lint should check.
+    ///   Each entry has the form `{ owner = "...", dependents = ["...", ...] }`, where the values
+    ///   are type paths, e.g., `tempfile::dir::TempDir`. The entries extend the following default
+    ///   pairs:
+    ///   - `std::sync::mutex::Mutex` owns `std::sync::mutex::MutexGuard`
+    ///   - `std::sync::rwlock::RwLock` owns `std::sync::rwlock::RwLockReadGuard` and
+    ///     `std::sync::rwlock::RwLockWriteGuard`
+    ///   - `tempfile::dir::TempDir` owns `std::fs::File`
+    ///   - `tokio::runtime::runtime::Runtime` owns `tokio::runtime::handle::Handle` and
+    ///     `tokio::runtime::task::join::JoinHandle`
+    pub FIELD_DROP_ORDER,
+    Warn,
+    "struct fields whose drop order matters but is undocumented",
+    FieldDropOrder::new()
+}
+
+const DEFAULT_PAIRS: &[(&str, &[&str])] = &[
+    ("std::sync::mutex::Mutex", &["std::sync::mutex::MutexGuard"]),
+    (
+        "std::sync::rwlock::RwLock",
+        &[
+            "std::sync::rwlock::RwLockReadGuard",
+            "std::sync::rwlock::RwLockWriteGuard",
+        ],
+    ),
+    ("tempfile::dir::TempDir", &["std::fs::File"]),
+    (
+        "tokio::runtime::runtime::Runtime",
+        &[
+            "tokio::runtime::handle::Handle",
+            "tokio::runtime::task::join::JoinHandle",
+        ],
+    ),
+];
+
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    pairs: Vec<Pair>,
+}
+
+#[derive(Deserialize)]
+struct Pair {
+    owner: String,
+    dependents: Vec<String>,
+}
+
+struct FieldDropOrder {
+    pairs: Vec<Pair>,
+}
+
+impl FieldDropOrder {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let pairs = DEFAULT_PAIRS
+            .iter()
+            .map(|&(owner, dependents)| Pair {
+                owner: owner.to_owned(),
+                dependents: dependents.iter().copied().map(ToOwned::to_owned).collect(),
+            })
+            .chain(config.pairs)
+            .collect();
+        Self { pairs }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for FieldDropOrder {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if !item.span.from_expansion();
+            if let ItemKind::Struct(VariantData::Struct(fields, _), _) = item.kind;
+            if !mentions_drop(cx, item.hir_id());
+            then {
+                let field_paths = fields
+                    .iter()
+                    .map(|field| type_paths(cx, field))
+                    .collect::<Vec<_>>();
+                for (i, owner) in fields.iter().enumerate() {
+                    for (j, dependent) in fields.iter().enumerate().skip(i + 1) {
+                        if self.depends_on(&field_paths[j], &field_paths[i])
+                            && !mentions_drop(cx, owner.hir_id)
+                            && !mentions_drop(cx, dependent.hir_id)
+                        {
+                            lint(cx, owner, dependent);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FieldDropOrder {
+    fn depends_on(&self, dependent_paths: &[String], owner_paths: &[String]) -> bool {
+        self.pairs.iter().any(|pair| {
+            owner_paths.contains(&pair.owner)
+                && pair
+                    .dependents
+                    .iter()
+                    .any(|dependent| dependent_paths.contains(dependent))
+        })
+    }
+}
+
+fn lint(cx: &LateContext<'_>, owner: &FieldDef<'_>, dependent: &FieldDef<'_>) {
+    span_lint_and_then(
+        cx,
+        FIELD_DROP_ORDER,
+        dependent.span,
+        &format!(
+            "`{}` may depend upon `{}`, which is dropped first",
+            dependent.ident, owner.ident
+        ),
+        |diag| {
+            diag.span_note(
+                owner.span,
+                format!(
+                    "fields are dropped in declaration order, so `{}` is dropped before `{}`",
+                    owner.ident, dependent.ident
+                ),
+            );
+            diag.help(format!(
+                "declare `{}` before `{}`, or add a doc comment explaining why they must be \
+                 dropped in this order",
+                dependent.ident, owner.ident
+            ));
+        },
+    );
+}
+
+// smoelius: A field's type "mentions" each ADT reachable from it, e.g., `Option<File>` mentions
+// both `Option` and `File`.
+fn type_paths(cx: &LateContext<'_>, field: &FieldDef<'_>) -> Vec<String> {
+    let ty = cx.tcx.type_of(field.def_id).skip_binder();
+    ty.walk()
+        .filter_map(|arg| {
+            if_chain! {
+                if let GenericArgKind::Type(ty) = arg.unpack();
+                if let ty::Adt(adt_def, _) = ty.kind();
+                then {
+                    Some(
+                        cx.get_def_path(adt_def.did())
+                            .iter()
+                            .map(Symbol::as_str)
+                            .collect::<Vec<_>>()
+                            .join("::"),
+                    )
+                } else {
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn mentions_drop(cx: &LateContext<'_>, hir_id: HirId) -> bool {
+    cx.tcx.hir().attrs(hir_id).iter().any(|attr| {
+        attr.doc_str()
+            .map_or(false, |doc| doc.as_str().to_lowercase().contains("drop"))
+    })
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_config() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_config")
+        .dylint_toml(
+            r#"field_drop_order.pairs = [
+                { owner = "std::process::Child", dependents = ["std::process::ChildStdin"] },
+            ]"#,
+        )
+        .run();
+}
@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+use tempfile::TempDir;
+use tokio::{
+    runtime::{Handle, Runtime},
+    task::JoinHandle,
+};
+
+struct OwnerFirst {
+    tempdir: TempDir,
+    file: File,
+}
+
+struct DependentFirst {
+    file: File,
+    tempdir: TempDir,
+}
+
+struct OwnerFirstMultipleDependents {
+    tempdir: TempDir,
+    file: File,
+    log: Option<File>,
+}
+
+struct OwnerFirstDocumented {
+    tempdir: TempDir,
+    /// Dropped after `tempdir`, which is okay because `file` is never written to.
+    file: File,
+}
+
+// smoelius: A `PathBuf` merely names a path. Nothing goes wrong if it outlives the `TempDir`.
+struct PathInTempDir {
+    tempdir: TempDir,
+    path: PathBuf,
+}
+
+struct Unrelated {
+    path: PathBuf,
+    file: File,
+}
+
+struct MutexFirst {
+    mutex: Arc<Mutex<Vec<u8>>>,
+    guard: MutexGuard<'static, Vec<u8>>,
+}
+
+struct MutexGuardFirst {
+    guard: MutexGuard<'static, Vec<u8>>,
+    mutex: Arc<Mutex<Vec<u8>>>,
+}
+
+struct RwLockFirstRead {
+    lock: Arc<RwLock<Vec<u8>>>,
+    guard: RwLockReadGuard<'static, Vec<u8>>,
+}
+
+struct RwLockFirstWrite {
+    lock: Arc<RwLock<Vec<u8>>>,
+    guard: RwLockWriteGuard<'static, Vec<u8>>,
+}
+
+struct RwLockGuardFirst {
+    guard: RwLockWriteGuard<'static, Vec<u8>>,
+    lock: Arc<RwLock<Vec<u8>>>,
+}
+
+struct RuntimeFirst {
+    runtime: Runtime,
+    handle: Handle,
+    task: JoinHandle<()>,
+}
+
+struct HandlesFirst {
+    task: JoinHandle<()>,
+    handle: Handle,
+    runtime: Runtime,
+}
+
+fn main() {}
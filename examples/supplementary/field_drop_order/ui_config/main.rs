@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+
+use std::{
+    fs::File,
+    process::{Child, ChildStdin},
+};
+use tempfile::TempDir;
+
+struct ConfiguredOwnerFirst {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+struct ConfiguredDependentFirst {
+    stdin: ChildStdin,
+    child: Child,
+}
+
+struct DefaultOwnerFirst {
+    tempdir: TempDir,
+    file: File,
+}
+
+fn main() {}
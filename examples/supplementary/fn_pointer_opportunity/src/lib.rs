@@ -0,0 +1,329 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_then, get_parent_expr, match_def_path, source::snippet_opt,
+    ty::is_type_diagnostic_item,
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_errors::Applicability;
+use rustc_hir::{
+    def::{CtorKind, CtorOf, DefKind, Res},
+    def_id::{DefId, LocalDefId},
+    Expr, ExprKind, FieldDef, GenericArg, Mutability, QPath, Ty as HirTy, TyKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty, VariantDef};
+use rustc_span::{sym, symbol::Ident, Span};
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for struct fields of type `Box<dyn Fn(..) -> _>` (or `Rc<...>` or `Arc<...>`) that
+    /// are only ever assigned closures that capture nothing, or paths to functions.
+    ///
+    /// ### Why is this bad?
+    /// Such values can be stored as function pointers, e.g., `fn(..) -> _`. Doing so avoids a heap
+    /// allocation per value and dynamic dispatch per call.
+    ///
+    /// ### Known problems
+    /// - Only assignments within the crate that defines the field are considered. Code in other
+    ///   crates could assign the field a closure that captures something.
+    /// - If the field is assigned any expression other than `Box::new(...)` (or `Rc::new(...)` or
+    ///   `Arc::new(...)`) applied to a closure or function path, the lint does not fire.
+    ///
+    /// ### Example
+    /// ```rust
+    /// struct Handler {
+    ///     callback: Box<dyn Fn(u32) -> u32>,
+    /// }
+    ///
+    /// let handler = Handler {
+    ///     callback: Box::new(|x| x + 1),
+    /// };
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// struct Handler {
+    ///     callback: fn(u32) -> u32,
+    /// }
+    ///
+    /// let handler = Handler {
+    ///     callback: |x| x + 1,
+    /// };
+    /// ```
+    pub FN_POINTER_OPPORTUNITY,
+    Warn,
+    "boxed `dyn Fn` fields that could be function pointers",
+    FnPointerOpportunity::default()
+}
+
+const NEW_PATHS: [(&str, &[&str]); 3] = [
+    ("Box", &["alloc", "boxed", "Box", "new"]),
+    ("Rc", &["alloc", "rc", "Rc", "new"]),
+    ("Arc", &["alloc", "sync", "Arc", "new"]),
+];
+
+struct Candidate {
+    def_id: LocalDefId,
+    ident: Ident,
+    ty_span: Span,
+    pointer: &'static str,
+    sugg: String,
+}
+
+#[derive(Default)]
+struct FnPointerOpportunity {
+    candidates: Vec<Candidate>,
+    // smoelius: Maps each field assigned anywhere in the crate to whether every value assigned to
+    // it could be a function pointer.
+    assignments: FxHashMap<DefId, bool>,
+}
+
+impl FnPointerOpportunity {
+    fn record(&mut self, field: DefId, compatible: bool) {
+        *self.assignments.entry(field).or_insert(true) &= compatible;
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for FnPointerOpportunity {
+    fn check_field_def(&mut self, cx: &LateContext<'tcx>, field: &'tcx FieldDef<'tcx>) {
+        if field.span.from_expansion() {
+            return;
+        }
+
+        let ty = cx.tcx.type_of(field.def_id).skip_binder();
+        let Some(pointer) = boxed_fn_pointer(cx, ty) else {
+            return;
+        };
+
+        let Some(sugg) = fn_pointer_snippet(cx, field.ty) else {
+            return;
+        };
+
+        self.candidates.push(Candidate {
+            def_id: field.def_id,
+            ident: field.ident,
+            ty_span: field.ty.span,
+            pointer,
+            sugg,
+        });
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        match expr.kind {
+            ExprKind::Struct(qpath, fields, _) => {
+                if let ty::Adt(adt_def, _) = cx.typeck_results().expr_ty(expr).kind() {
+                    let variant = adt_def.variant_of_res(cx.qpath_res(qpath, expr.hir_id));
+                    for field in fields {
+                        let index = cx.typeck_results().field_index(field.hir_id);
+                        self.record(
+                            variant.fields[index].did,
+                            is_fn_pointer_compatible(cx, field.expr),
+                        );
+                    }
+                }
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                if let Some(field) = field_def_id(cx, lhs) {
+                    self.record(field, is_fn_pointer_compatible(cx, rhs));
+                }
+            }
+            // smoelius: A field borrowed mutably could be assigned anything.
+            ExprKind::AddrOf(_, Mutability::Mut, operand) => {
+                if let Some(field) = field_def_id(cx, operand) {
+                    self.record(field, false);
+                }
+            }
+            ExprKind::Call(callee, args) => {
+                if let Some(variant) = ctor_variant(cx, callee) {
+                    for (field, arg) in variant.fields.iter().zip(args) {
+                        self.record(field.did, is_fn_pointer_compatible(cx, arg));
+                    }
+                }
+            }
+            // smoelius: A tuple struct constructor used as a function value could be passed
+            // anything.
+            ExprKind::Path(_) => {
+                if let Some(variant) = ctor_variant(cx, expr) {
+                    let is_callee = get_parent_expr(cx, expr).map_or(false, |parent| {
+                        matches!(parent.kind, ExprKind::Call(callee, _) if callee.hir_id == expr.hir_id)
+                    });
+                    if !is_callee {
+                        for field in variant.fields.iter() {
+                            self.record(field.did, false);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for candidate in &self.candidates {
+            if self.assignments.get(&candidate.def_id.to_def_id()) != Some(&true) {
+                continue;
+            }
+
+            span_lint_and_then(
+                cx,
+                FN_POINTER_OPPORTUNITY,
+                candidate.ty_span,
+                &format!(
+                    "field `{}` is only assigned closures that capture nothing or functions",
+                    candidate.ident
+                ),
+                |diag| {
+                    diag.span_suggestion(
+                        candidate.ty_span,
+                        "use a function pointer",
+                        &candidate.sugg,
+                        Applicability::MaybeIncorrect,
+                    );
+                    diag.note(format!(
+                        "the `{}::new` around each value assigned to the field must also be \
+                         removed",
+                        candidate.pointer
+                    ));
+                },
+            );
+        }
+    }
+}
+
+/// Returns the name of the pointer type if `ty` is `Box`, `Rc`, or `Arc` of a `dyn Fn`, `dyn
+/// FnMut`, or `dyn FnOnce`
+fn boxed_fn_pointer<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> Option<&'static str> {
+    let ty::Adt(_, substs) = ty.kind() else {
+        return None;
+    };
+
+    let pointer = if ty.is_box() {
+        "Box"
+    } else if is_type_diagnostic_item(cx, ty, sym::Rc) {
+        "Rc"
+    } else if is_type_diagnostic_item(cx, ty, sym::Arc) {
+        "Arc"
+    } else {
+        return None;
+    };
+
+    // smoelius: A trait object has at most one non-auto trait, and function pointers implement all
+    // of the auto traits. So only the principal trait must be checked.
+    if_chain! {
+        if let ty::Dynamic(predicates, _, _) = substs.type_at(0).kind();
+        if let Some(principal) = predicates.principal_def_id();
+        if cx.tcx.fn_trait_kind_from_def_id(principal).is_some();
+        then {
+            Some(pointer)
+        } else {
+            None
+        }
+    }
+}
+
+// smoelius: The suggestion is built from the field's type as written. For example,
+// `Box<dyn for<'a> FnMut(&'a str) -> bool + Send>` becomes `for<'a> fn(&'a str) -> bool`.
+fn fn_pointer_snippet(cx: &LateContext<'_>, hir_ty: &HirTy<'_>) -> Option<String> {
+    let TyKind::Path(QPath::Resolved(None, path)) = hir_ty.kind else {
+        return None;
+    };
+    let args = path.segments.last()?.args?;
+    let Some(GenericArg::Type(arg)) = args.args.first() else {
+        return None;
+    };
+    let TyKind::TraitObject(poly_trait_refs, _, _) = arg.kind else {
+        return None;
+    };
+    let poly_trait_ref = poly_trait_refs.iter().find(|poly_trait_ref| {
+        poly_trait_ref
+            .trait_ref
+            .trait_def_id()
+            .map_or(false, |def_id| {
+                cx.tcx.fn_trait_kind_from_def_id(def_id).is_some()
+            })
+    })?;
+    let trait_path = poly_trait_ref.trait_ref.path;
+    let ident = trait_path.segments.last()?.ident;
+    let binder = snippet_opt(cx, poly_trait_ref.span.until(trait_path.span))?;
+    let signature = snippet_opt(cx, trait_path.span.with_lo(ident.span.hi()))?;
+    Some(format!("{binder}fn{signature}"))
+}
+
+fn is_fn_pointer_compatible(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    if_chain! {
+        if let ExprKind::Call(callee, [arg]) = expr.kind;
+        if let ExprKind::Path(qpath) = &callee.kind;
+        if let Res::Def(_, def_id) = cx.qpath_res(qpath, callee.hir_id);
+        if NEW_PATHS
+            .iter()
+            .any(|(_, path)| match_def_path(cx, def_id, path));
+        then {
+            match arg.kind {
+                ExprKind::Closure(closure) => cx
+                    .tcx
+                    .upvars_mentioned(closure.def_id.to_def_id())
+                    .map_or(true, |upvars| upvars.is_empty()),
+                ExprKind::Path(qpath) => matches!(
+                    cx.qpath_res(&qpath, arg.hir_id),
+                    Res::Def(
+                        DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(_, CtorKind::Fn),
+                        _
+                    )
+                ),
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+fn field_def_id(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<DefId> {
+    let ExprKind::Field(base, _) = expr.kind else {
+        return None;
+    };
+    let ty::Adt(adt_def, _) = cx
+        .typeck_results()
+        .expr_ty_adjusted(base)
+        .peel_refs()
+        .kind()
+    else {
+        return None;
+    };
+    if adt_def.is_enum() {
+        return None;
+    }
+    let index = cx.typeck_results().field_index(expr.hir_id);
+    Some(adt_def.non_enum_variant().fields[index].did)
+}
+
+fn ctor_variant<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'_>) -> Option<&'tcx VariantDef> {
+    let ExprKind::Path(qpath) = &expr.kind else {
+        return None;
+    };
+    let Res::Def(DefKind::Ctor(ctor_of, CtorKind::Fn), ctor_id) = cx.qpath_res(qpath, expr.hir_id)
+    else {
+        return None;
+    };
+    let adt_id = match ctor_of {
+        CtorOf::Struct => cx.tcx.parent(ctor_id),
+        CtorOf::Variant => cx.tcx.parent(cx.tcx.parent(ctor_id)),
+    };
+    Some(cx.tcx.adt_def(adt_id).variant_with_ctor_id(ctor_id))
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+struct Handler {
+    callback: Box<dyn Fn(u32) -> u32>,
+}
+
+struct Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+struct Capturing {
+    callback: Box<dyn Fn(u32) -> u32>,
+}
+
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+fn is_empty(s: &str) -> bool {
+    s.is_empty()
+}
+
+fn main() {
+    let mut handler = Handler {
+        callback: Box::new(|x: u32| x * 2),
+    };
+    handler.callback = Box::new(increment);
+    let _ = (handler.callback)(0);
+
+    let predicate = Predicate(Arc::new(is_empty));
+    let _ = (predicate.0)("");
+
+    let offset = 2;
+    let mut capturing = Capturing {
+        callback: Box::new(|x: u32| x * 2),
+    };
+    capturing.callback = Box::new(move |x: u32| x + offset);
+    let _ = (capturing.callback)(0);
+}
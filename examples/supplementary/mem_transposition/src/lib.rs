@@ -0,0 +1,285 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::{span_lint_and_help, span_lint_and_note},
+    match_def_path, path_to_local,
+};
+use if_chain::if_chain;
+use regex::Regex;
+use rustc_hir::{Block, Expr, ExprKind, Local, PatKind, QPath, Stmt, StmtKind, UnOp};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{sym, Symbol};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for calls to `std::mem::replace`, `std::mem::swap`, and `std::mem::take` whose
+    /// arguments may be transposed. Specifically, the lint checks for:
+    /// - `replace(&mut new, old)`, i.e., a destination named like a new value and a source named
+    ///   like an old one
+    /// - `let new = replace(&mut old, ...)` and `let new = take(&mut old)`, i.e., the old value of
+    ///   a variable named like an old value bound to a variable named like a new one
+    /// - `swap(&mut x, &mut y)` immediately after `let mut y = x.clone();` (or similar), i.e., a
+    ///   swap with a local that was just initialized from the other argument
+    ///
+    /// ### Why is this bad?
+    /// Each of the above suggests that the arguments are in the wrong order. In particular, the
+    /// last one has no effect beyond a clone.
+    ///
+    /// ### Known problems
+    /// The lint relies on names, so it can produce false positives. For this reason, the lint is
+    /// allow-by-default.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # let mut new_value = 1;
+    /// # let old_value = 0;
+    /// std::mem::replace(&mut new_value, old_value);
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # let new_value = 1;
+    /// # let mut old_value = 0;
+    /// std::mem::replace(&mut old_value, new_value);
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `pairs: Vec<{ new: String, old: String }>`: Pairs of regular expressions. A name matching a
+    ///   pair's `new` expression is considered to refer to a new value, and a name matching the same
+    ///   pair's `old` expression is considered to refer to an old one. The default pairs are
+    ///   `(^|_)(new|next|current)($|_)`/`(^|_)(old|prev|previous)($|_)` and
+    ///   `(^|_)after($|_)`/`(^|_)before($|_)`.
+    pub MEM_TRANSPOSITION,
+    Allow,
+    "possibly transposed arguments to `std::mem` functions",
+    MemTransposition::new()
+}
+
+const DEFAULT_PAIRS: &[(&str, &str)] = &[
+    (
+        "(^|_)(new|next|current)($|_)",
+        "(^|_)(old|prev|previous)($|_)",
+    ),
+    ("(^|_)after($|_)", "(^|_)before($|_)"),
+];
+
+// smoelius: Of the three functions, only `replace` is a diagnostic item.
+const MEM_FNS: [(MemFn, [&str; 3]); 3] = [
+    (MemFn::Replace, ["core", "mem", "replace"]),
+    (MemFn::Swap, ["core", "mem", "swap"]),
+    (MemFn::Take, ["core", "mem", "take"]),
+];
+
+#[derive(Clone, Copy)]
+enum MemFn {
+    Replace,
+    Swap,
+    Take,
+}
+
+#[derive(Deserialize)]
+struct Pair {
+    new: String,
+    old: String,
+}
+
+#[derive(Deserialize)]
+struct Config {
+    pairs: Vec<Pair>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            pairs: DEFAULT_PAIRS
+                .iter()
+                .map(|&(new, old)| Pair {
+                    new: new.to_owned(),
+                    old: old.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+struct MemTransposition {
+    pairs: Vec<(Regex, Regex)>,
+}
+
+impl MemTransposition {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let regex = |pattern: &str| {
+            Regex::new(pattern)
+                .unwrap_or_else(|error| panic!("Could not parse `{pattern}`: {error}"))
+        };
+        let pairs = config
+            .pairs
+            .iter()
+            .map(|pair| (regex(&pair.new), regex(&pair.old)))
+            .collect();
+        Self { pairs }
+    }
+
+    fn is_new_and_old(&self, new: Symbol, old: Symbol) -> bool {
+        self.pairs
+            .iter()
+            .any(|(new_re, old_re)| new_re.is_match(new.as_str()) && old_re.is_match(old.as_str()))
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for MemTransposition {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if !expr.span.from_expansion();
+            if let Some((MemFn::Replace, [dest, src])) = mem_call(cx, expr);
+            if let Some(dest_name) = name(dest);
+            if let Some(src_name) = name(src);
+            if self.is_new_and_old(dest_name, src_name);
+            then {
+                span_lint_and_help(
+                    cx,
+                    MEM_TRANSPOSITION,
+                    expr.span,
+                    &format!("possible transposition: `{dest_name}` is replaced with `{src_name}`"),
+                    None,
+                    &format!(
+                        "`{dest_name}` is named like a new value and `{src_name}` like an old one; \
+                         check the order of the arguments"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_local(&mut self, cx: &LateContext<'tcx>, local: &'tcx Local<'tcx>) {
+        if_chain! {
+            if !local.span.from_expansion();
+            if let PatKind::Binding(_, _, ident, None) = local.pat.kind;
+            if let Some(init) = local.init;
+            if let Some((MemFn::Replace | MemFn::Take, [dest, ..])) = mem_call(cx, init);
+            if let Some(dest_name) = name(dest);
+            if self.is_new_and_old(ident.name, dest_name);
+            then {
+                span_lint_and_help(
+                    cx,
+                    MEM_TRANSPOSITION,
+                    local.span,
+                    &format!(
+                        "possible transposition: the old value of `{dest_name}` is bound to \
+                         `{ident}`"
+                    ),
+                    None,
+                    &format!(
+                        "`{ident}` is named like a new value and `{dest_name}` like an old one; \
+                         check that the right variable is being replaced"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_block(&mut self, cx: &LateContext<'tcx>, block: &'tcx Block<'tcx>) {
+        let following = block
+            .stmts
+            .iter()
+            .skip(1)
+            .map(stmt_expr)
+            .chain(std::iter::once(block.expr));
+        for (stmt, expr) in block.stmts.iter().zip(following) {
+            if_chain! {
+                if let Some(expr) = expr;
+                if !expr.span.from_expansion();
+                if let StmtKind::Local(local) = stmt.kind;
+                if let PatKind::Binding(_, binding, ident, None) = local.pat.kind;
+                if let Some(init) = local.init;
+                if let Some((MemFn::Swap, [a, b])) = mem_call(cx, expr);
+                if let Some(other) = [(a, b), (b, a)].into_iter().find_map(|(x, y)| {
+                    let x_local = path_to_local(peel(x))?;
+                    (path_to_local(peel(y)) == Some(binding)
+                        && path_to_local(peel(init)) == Some(x_local))
+                    .then_some(x)
+                });
+                if let Some(other_name) = name(other);
+                then {
+                    span_lint_and_note(
+                        cx,
+                        MEM_TRANSPOSITION,
+                        expr.span,
+                        &format!(
+                            "possible transposition: `{other_name}` is swapped with `{ident}`, \
+                             which was just initialized from it"
+                        ),
+                        Some(local.span),
+                        &format!("`{ident}` is initialized here"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn stmt_expr<'tcx>(stmt: &Stmt<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    match stmt.kind {
+        StmtKind::Expr(expr) | StmtKind::Semi(expr) => Some(expr),
+        StmtKind::Local(_) | StmtKind::Item(_) => None,
+    }
+}
+
+fn mem_call<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(MemFn, &'tcx [Expr<'tcx>])> {
+    if_chain! {
+        if let ExprKind::Call(callee, args) = expr.kind;
+        if let ExprKind::Path(qpath) = &callee.kind;
+        if let Some(def_id) = cx.qpath_res(qpath, callee.hir_id).opt_def_id();
+        if let Some(&(mem_fn, _)) = MEM_FNS
+            .iter()
+            .find(|(_, path)| match_def_path(cx, def_id, path));
+        then {
+            Some((mem_fn, args))
+        } else {
+            None
+        }
+    }
+}
+
+// smoelius: Strips references, dereferences, and calls to `clone`.
+fn peel<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    loop {
+        expr = match expr.kind {
+            ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => inner,
+            ExprKind::MethodCall(path, receiver, [], _) if path.ident.name == sym::clone => {
+                receiver
+            }
+            _ => return expr,
+        };
+    }
+}
+
+fn name(expr: &Expr<'_>) -> Option<Symbol> {
+    match peel(expr).kind {
+        ExprKind::Path(QPath::Resolved(None, path)) => {
+            path.segments.last().map(|segment| segment.ident.name)
+        }
+        ExprKind::Field(_, ident) => Some(ident.name),
+        _ => None,
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_pairs() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_pairs")
+        .dylint_toml(r#"mem_transposition.pairs = [{ new = "^dst$", old = "^src$" }]"#)
+        .run();
+}
@@ -0,0 +1,52 @@
+#![allow(unknown_lints)]
+#![warn(mem_transposition)]
+#![allow(unused_variables)]
+
+use std::mem::{replace, swap, take};
+
+struct State {
+    old: Vec<u32>,
+    new: Vec<u32>,
+}
+
+fn main() {
+    let mut new_value = 1;
+    let old_value = 0;
+    let _ = replace(&mut new_value, old_value);
+
+    let mut state = State {
+        old: Vec::new(),
+        new: vec![1],
+    };
+    let _ = replace(&mut state.new, state.old.clone());
+
+    let mut previous = vec![0];
+    let next = take(&mut previous);
+
+    let mut before = String::new();
+    let after = replace(&mut before, String::from("x"));
+
+    let mut data = vec![1, 2, 3];
+    let mut snapshot = data.clone();
+    swap(&mut data, &mut snapshot);
+
+    // Negative cases
+
+    let mut current = 1;
+    let next_value = 2;
+    let previous_value = replace(&mut current, next_value);
+
+    let mut old = 0;
+    let _ = replace(&mut old, new_value);
+
+    let mut a = 1;
+    let mut b = 2;
+    swap(&mut a, &mut b);
+
+    let mut copy = data.clone();
+    copy.push(4);
+    swap(&mut data, &mut copy);
+
+    let mut buffer = vec![0];
+    let contents = take(&mut buffer);
+}
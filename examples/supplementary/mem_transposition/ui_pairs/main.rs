@@ -0,0 +1,15 @@
+#![allow(unknown_lints)]
+#![warn(mem_transposition)]
+
+use std::mem::replace;
+
+fn main() {
+    let mut dst = 0;
+    let src = 1;
+    let _ = replace(&mut dst, src);
+
+    // The default pairs no longer apply.
+    let mut new_value = 1;
+    let old_value = 0;
+    let _ = replace(&mut new_value, old_value);
+}
@@ -0,0 +1,451 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{diagnostics::span_lint_and_then, path_to_local, ty::is_type_diagnostic_item};
+use if_chain::if_chain;
+use rustc_ast::LitKind;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{BinOpKind, Expr, ExprKind, HirId, Local, PatKind, QPath};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{sym, symbol::Ident, Span};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for arithmetic, comparisons, and function calls that mix integers holding
+    /// timestamps or durations in different units, e.g., seconds and milliseconds.
+    ///
+    /// A value's unit is inferred from how the value was constructed:
+    /// - `Duration::as_secs`, `Duration::as_millis`, etc. return values in the obvious units.
+    /// - Multiplying by 1000 converts a value to the next finer unit (e.g., seconds to
+    ///   milliseconds). Dividing by 1000 converts it to the next coarser unit.
+    /// - A variable, field, constant, or function whose name ends with a unit suffix (e.g.,
+    ///   `timeout_ms` or `elapsed_secs`) is assumed to hold or return a value in that unit.
+    ///
+    /// Units are propagated through simple local assignments, e.g., `let start = now.as_secs();`.
+    ///
+    /// ### Why is this bad?
+    /// Storing timestamps as bare integers makes it easy to compare seconds with milliseconds.
+    /// The resulting bugs typically produce no errors, just wrong answers.
+    ///
+    /// ### Known problems
+    /// - The lint is heuristic. It knows nothing about a value's unit beyond what is described
+    ///   above.
+    /// - Units are not propagated through function calls, struct fields, or control flow.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # let (elapsed, timeout) = (Duration::from_secs(1), Duration::from_secs(2));
+    /// let elapsed = elapsed.as_secs();
+    /// let timeout = timeout.as_millis() as u64;
+    /// if elapsed > timeout {}
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # let (elapsed, timeout) = (Duration::from_secs(1), Duration::from_secs(2));
+    /// let elapsed = elapsed.as_secs() * 1000;
+    /// let timeout = timeout.as_millis() as u64;
+    /// if elapsed > timeout {}
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `suffixes: Vec<Suffix>` (default `[]`): Additional name suffixes that indicate units.
+    ///   Each entry has the form `{ suffix = "...", unit = "..." }`, where `unit` is one of
+    ///   `"seconds"`, `"milliseconds"`, `"microseconds"`, or `"nanoseconds"`. Suffixes are
+    ///   matched case-insensitively, and the longest matching suffix wins. The entries extend
+    ///   the following default suffixes:
+    ///   - seconds: `_secs`, `_seconds`
+    ///   - milliseconds: `_ms`, `_millis`
+    ///   - microseconds: `_us`, `_micros`
+    ///   - nanoseconds: `_ns`, `_nanos`
+    pub MIXED_TIMESTAMP_UNITS,
+    Allow,
+    "arithmetic or comparisons that mix timestamps in different units",
+    MixedTimestampUnits::new()
+}
+
+#[derive(Clone, Copy, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Unit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl Unit {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Seconds => "seconds",
+            Self::Milliseconds => "milliseconds",
+            Self::Microseconds => "microseconds",
+            Self::Nanoseconds => "nanoseconds",
+        }
+    }
+
+    fn finer(self) -> Option<Self> {
+        match self {
+            Self::Seconds => Some(Self::Milliseconds),
+            Self::Milliseconds => Some(Self::Microseconds),
+            Self::Microseconds => Some(Self::Nanoseconds),
+            Self::Nanoseconds => None,
+        }
+    }
+
+    fn coarser(self) -> Option<Self> {
+        match self {
+            Self::Seconds => None,
+            Self::Milliseconds => Some(Self::Seconds),
+            Self::Microseconds => Some(Self::Milliseconds),
+            Self::Nanoseconds => Some(Self::Microseconds),
+        }
+    }
+}
+
+const DEFAULT_SUFFIXES: &[(&str, Unit)] = &[
+    ("_secs", Unit::Seconds),
+    ("_seconds", Unit::Seconds),
+    ("_ms", Unit::Milliseconds),
+    ("_millis", Unit::Milliseconds),
+    ("_us", Unit::Microseconds),
+    ("_micros", Unit::Microseconds),
+    ("_ns", Unit::Nanoseconds),
+    ("_nanos", Unit::Nanoseconds),
+];
+
+const DURATION_METHODS: &[(&str, Unit)] = &[
+    ("as_secs", Unit::Seconds),
+    ("as_secs_f32", Unit::Seconds),
+    ("as_secs_f64", Unit::Seconds),
+    ("as_millis", Unit::Milliseconds),
+    ("as_micros", Unit::Microseconds),
+    ("as_nanos", Unit::Nanoseconds),
+];
+
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    suffixes: Vec<Suffix>,
+}
+
+#[derive(Deserialize)]
+struct Suffix {
+    suffix: String,
+    unit: Unit,
+}
+
+// smoelius: A `Provenance` records a value's unit, along with the steps by which the unit was
+// inferred. The steps are shown to the user as notes.
+#[derive(Clone)]
+struct Provenance {
+    unit: Unit,
+    steps: Vec<(Span, String)>,
+}
+
+impl Provenance {
+    fn new(unit: Unit, span: Span, note: String) -> Self {
+        Self {
+            unit,
+            steps: vec![(span, note)],
+        }
+    }
+
+    fn then(mut self, unit: Unit, span: Span, note: String) -> Self {
+        self.unit = unit;
+        self.steps.push((span, note));
+        self
+    }
+}
+
+struct MixedTimestampUnits {
+    suffixes: Vec<Suffix>,
+    locals: FxHashMap<HirId, Provenance>,
+}
+
+impl MixedTimestampUnits {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let suffixes = DEFAULT_SUFFIXES
+            .iter()
+            .map(|&(suffix, unit)| Suffix {
+                suffix: suffix.to_owned(),
+                unit,
+            })
+            .chain(config.suffixes)
+            .map(|Suffix { suffix, unit }| Suffix {
+                suffix: suffix.to_lowercase(),
+                unit,
+            })
+            .collect();
+        Self {
+            suffixes,
+            locals: FxHashMap::default(),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for MixedTimestampUnits {
+    fn check_local(&mut self, cx: &LateContext<'tcx>, local: &'tcx Local<'tcx>) {
+        if_chain! {
+            if let PatKind::Binding(_, hir_id, ident, None) = local.pat.kind;
+            if let Some(init) = local.init;
+            if let Some(provenance) = self.provenance(cx, init);
+            then {
+                let provenance = assigned(provenance, ident, local.pat.span);
+                self.locals.insert(hir_id, provenance);
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        match expr.kind {
+            ExprKind::Assign(lhs, rhs, _) => {
+                if let Some(hir_id) = path_to_local(lhs) {
+                    match self.provenance(cx, rhs) {
+                        Some(provenance) => {
+                            let ident = Ident::new(cx.tcx.hir().name(hir_id), lhs.span);
+                            self.locals
+                                .insert(hir_id, assigned(provenance, ident, expr.span));
+                        }
+                        None => {
+                            self.locals.remove(&hir_id);
+                        }
+                    }
+                }
+            }
+            ExprKind::Binary(op, left, right) | ExprKind::AssignOp(op, left, right) => {
+                let what = match op.node {
+                    BinOpKind::Add | BinOpKind::Sub => "arithmetic",
+                    BinOpKind::Eq
+                    | BinOpKind::Ne
+                    | BinOpKind::Lt
+                    | BinOpKind::Le
+                    | BinOpKind::Gt
+                    | BinOpKind::Ge => "comparison",
+                    _ => return,
+                };
+                if_chain! {
+                    if let Some(left) = self.provenance(cx, left);
+                    if let Some(right) = self.provenance(cx, right);
+                    if left.unit != right.unit;
+                    then {
+                        lint(
+                            cx,
+                            expr.span,
+                            &format!(
+                                "this {what} mixes {} and {}",
+                                left.unit.name(),
+                                right.unit.name()
+                            ),
+                            [&left, &right],
+                        );
+                    }
+                }
+            }
+            ExprKind::Call(_, args) => {
+                self.check_args(cx, expr, args.iter());
+            }
+            ExprKind::MethodCall(_, receiver, args, _) => {
+                self.check_args(cx, expr, std::iter::once(receiver).chain(args.iter()));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl MixedTimestampUnits {
+    fn check_args<'tcx>(
+        &self,
+        cx: &LateContext<'tcx>,
+        expr: &Expr<'tcx>,
+        args: impl Iterator<Item = &'tcx Expr<'tcx>>,
+    ) {
+        let provenances = args
+            .filter_map(|arg| self.provenance(cx, arg))
+            .collect::<Vec<_>>();
+        if_chain! {
+            if let Some(first) = provenances.first();
+            if let Some(other) = provenances.iter().find(|other| other.unit != first.unit);
+            then {
+                lint(
+                    cx,
+                    expr.span,
+                    &format!(
+                        "this call's arguments mix {} and {}",
+                        first.unit.name(),
+                        other.unit.name()
+                    ),
+                    [first, other],
+                );
+            }
+        }
+    }
+
+    fn provenance<'tcx>(&self, cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> Option<Provenance> {
+        let expr = peel_casts(expr);
+        match expr.kind {
+            ExprKind::MethodCall(segment, receiver, _, _) => {
+                let receiver_ty = cx.typeck_results().expr_ty(receiver).peel_refs();
+                if is_type_diagnostic_item(cx, receiver_ty, sym::Duration) {
+                    DURATION_METHODS
+                        .iter()
+                        .find(|&&(name, _)| segment.ident.as_str() == name)
+                        .map(|&(name, unit)| {
+                            Provenance::new(
+                                unit,
+                                expr.span,
+                                format!("`{name}` returns {}", unit.name()),
+                            )
+                        })
+                } else {
+                    self.suffix_provenance(segment.ident, expr.span)
+                }
+            }
+            ExprKind::Call(callee, _) => match callee.kind {
+                ExprKind::Path(QPath::Resolved(_, path)) => path
+                    .segments
+                    .last()
+                    .and_then(|segment| self.suffix_provenance(segment.ident, expr.span)),
+                ExprKind::Path(QPath::TypeRelative(_, segment)) => {
+                    self.suffix_provenance(segment.ident, expr.span)
+                }
+                _ => None,
+            },
+            ExprKind::Path(QPath::Resolved(None, path)) => path_to_local(expr)
+                .and_then(|hir_id| self.locals.get(&hir_id).cloned())
+                .or_else(|| {
+                    path.segments
+                        .last()
+                        .and_then(|segment| self.suffix_provenance(segment.ident, expr.span))
+                }),
+            ExprKind::Field(_, ident) => self.suffix_provenance(ident, expr.span),
+            ExprKind::Binary(op, left, right) => match op.node {
+                BinOpKind::Mul => {
+                    let operand = if is_thousand(right) {
+                        left
+                    } else if is_thousand(left) {
+                        right
+                    } else {
+                        return None;
+                    };
+                    let provenance = self.provenance(cx, operand)?;
+                    let unit = provenance.unit.finer()?;
+                    let note = format!(
+                        "multiplying by 1000 converts {} to {}",
+                        provenance.unit.name(),
+                        unit.name()
+                    );
+                    Some(provenance.then(unit, expr.span, note))
+                }
+                BinOpKind::Div if is_thousand(right) => {
+                    let provenance = self.provenance(cx, left)?;
+                    let unit = provenance.unit.coarser()?;
+                    let note = format!(
+                        "dividing by 1000 converts {} to {}",
+                        provenance.unit.name(),
+                        unit.name()
+                    );
+                    Some(provenance.then(unit, expr.span, note))
+                }
+                BinOpKind::Add | BinOpKind::Sub => {
+                    match (self.provenance(cx, left), self.provenance(cx, right)) {
+                        (Some(left), Some(right)) if left.unit == right.unit => Some(left),
+                        (Some(provenance), None) | (None, Some(provenance)) => Some(provenance),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn suffix_provenance(&self, ident: Ident, span: Span) -> Option<Provenance> {
+        let name = ident.as_str().to_lowercase();
+        self.suffixes
+            .iter()
+            .filter(|suffix| name.ends_with(&suffix.suffix))
+            .max_by_key(|suffix| suffix.suffix.len())
+            .map(|suffix| {
+                Provenance::new(
+                    suffix.unit,
+                    span,
+                    format!(
+                        "`{ident}` is assumed to be in {} because of its `{}` suffix",
+                        suffix.unit.name(),
+                        suffix.suffix
+                    ),
+                )
+            })
+    }
+}
+
+fn assigned(provenance: Provenance, ident: Ident, span: Span) -> Provenance {
+    let unit = provenance.unit;
+    provenance.then(
+        unit,
+        span,
+        format!("`{ident}` is assigned a value in {}", unit.name()),
+    )
+}
+
+// smoelius: A value's unit is unaffected by casts and borrows.
+fn peel_casts<'a, 'tcx>(mut expr: &'a Expr<'tcx>) -> &'a Expr<'tcx> {
+    while let ExprKind::Cast(inner, _)
+    | ExprKind::DropTemps(inner)
+    | ExprKind::AddrOf(_, _, inner) = expr.kind
+    {
+        expr = inner;
+    }
+    expr
+}
+
+fn is_thousand(expr: &Expr<'_>) -> bool {
+    if_chain! {
+        if let ExprKind::Lit(lit) = peel_casts(expr).kind;
+        if let LitKind::Int(1000, _) = lit.node;
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn lint(cx: &LateContext<'_>, span: Span, msg: &str, provenances: [&Provenance; 2]) {
+    span_lint_and_then(cx, MIXED_TIMESTAMP_UNITS, span, msg, |diag| {
+        for provenance in provenances {
+            for (span, note) in &provenance.steps {
+                diag.span_note(*span, note.clone());
+            }
+        }
+        diag.help("convert one of the values so that both are in the same unit");
+    });
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_config() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_config")
+        .dylint_toml(
+            r#"mixed_timestamp_units.suffixes = [
+                { suffix = "_epoch", unit = "seconds" },
+            ]"#,
+        )
+        .run();
+}
@@ -0,0 +1,34 @@
+#![allow(unknown_lints)]
+#![warn(mixed_timestamp_units)]
+#![allow(dead_code)]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn since_epoch() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+}
+
+fn main() {
+    let created = since_epoch().as_secs();
+    let now = since_epoch().as_millis() as u64;
+    if created < now {}
+
+    let timeout_ms = 5000;
+    let elapsed_secs = 3;
+    if elapsed_secs > timeout_ms {}
+
+    let _ = std::cmp::max(created, now);
+}
+
+fn converted() {
+    let created = since_epoch().as_secs();
+    let now = since_epoch().as_millis() as u64;
+    if created * 1000 < now {}
+    if created < now / 1000 {}
+
+    let timeout_ms = 5000;
+    let elapsed_ms = now - created * 1000;
+    if elapsed_ms > timeout_ms {}
+
+    let _ = std::cmp::max(created, now / 1000);
+}
@@ -0,0 +1,9 @@
+#![allow(unknown_lints)]
+#![warn(mixed_timestamp_units)]
+
+fn main() {
+    let created_epoch: u64 = 0;
+    let now_ms: u64 = 0;
+    if created_epoch < now_ms {}
+    if created_epoch * 1000 < now_ms {}
+}
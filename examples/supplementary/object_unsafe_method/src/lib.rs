@@ -0,0 +1,248 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{diagnostics::span_lint_hir_and_then, is_lint_allowed};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{def_id::LocalDefId, HirId, Item, ItemKind, Ty, TyKind};
+use rustc_lint::{LateContext, LateLintPass, LintContext};
+use rustc_middle::traits::{MethodViolationCode, ObjectSafetyViolation};
+use rustc_span::{Span, Symbol};
+use serde::Deserialize;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for methods that make a public trait not object safe, e.g., methods that return
+    /// `Self` or that have type parameters, and that lack a `where Self: Sized` bound.
+    ///
+    /// The lint applies only to traits whose sole object-safety violations are such methods, as
+    /// determined by the compiler. If the crate uses `dyn Trait` for such a trait, the lint
+    /// warns. Otherwise, by default, it emits only a note (see [Configuration](#configuration)).
+    ///
+    /// ### Why is this bad?
+    /// Adding such a method to a public trait silently makes the trait not object safe. Downstream
+    /// code that uses `dyn Trait` stops compiling.
+    ///
+    /// ### Known problems
+    /// - A use of `dyn Trait` that the compiler checks, e.g., in a function signature, causes a
+    ///   compilation error before the lint runs. So the lint warns only about uses the compiler
+    ///   does not check, such as in a type alias.
+    /// - The lint does not consider uses of `dyn Trait` in other crates.
+    ///
+    /// ### Example
+    /// ```rust
+    /// pub trait Builder {
+    ///     fn name(&self) -> String;
+    ///     fn builder(self) -> Self;
+    /// }
+    ///
+    /// pub type BoxedBuilder = Box<dyn Builder>;
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// pub trait Builder {
+    ///     fn name(&self) -> String;
+    ///     fn builder(self) -> Self
+    ///     where
+    ///         Self: Sized;
+    /// }
+    ///
+    /// pub type BoxedBuilder = Box<dyn Builder>;
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `without_dyn_usage: String` (default `"note"`): What to do when the crate does not use
+    ///   `dyn Trait`. One of `"ignore"`, `"note"` (emit a note), or `"lint"` (warn as though the
+    ///   crate used `dyn Trait`). Notes are not emitted where the lint is allowed.
+    pub OBJECT_UNSAFE_METHOD,
+    Warn,
+    "methods that make public traits not object safe",
+    ObjectUnsafeMethod::new()
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum WithoutDynUsage {
+    Ignore,
+    #[default]
+    Note,
+    Lint,
+}
+
+#[derive(Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    without_dyn_usage: WithoutDynUsage,
+}
+
+struct ObjectUnsafeMethod {
+    config: Config,
+    public_traits: Vec<LocalDefId>,
+    dyn_usages: FxHashMap<LocalDefId, Vec<Span>>,
+}
+
+impl ObjectUnsafeMethod {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+            public_traits: Vec::new(),
+            dyn_usages: FxHashMap::default(),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for ObjectUnsafeMethod {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Trait(..) = item.kind;
+            if cx.effective_visibilities.is_exported(item.owner_id.def_id);
+            then {
+                self.public_traits.push(item.owner_id.def_id);
+            }
+        }
+    }
+
+    fn check_ty(&mut self, _cx: &LateContext<'tcx>, ty: &'tcx Ty<'tcx>) {
+        if let TyKind::TraitObject(poly_trait_refs, _, _) = ty.kind {
+            for poly_trait_ref in poly_trait_refs {
+                if_chain! {
+                    if let Some(def_id) = poly_trait_ref.trait_ref.trait_def_id();
+                    if let Some(local_def_id) = def_id.as_local();
+                    then {
+                        self.dyn_usages
+                            .entry(local_def_id)
+                            .or_default()
+                            .push(ty.span);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for &trait_def_id in &self.public_traits {
+            let Some(methods) = suspicious_methods(cx, trait_def_id) else {
+                continue;
+            };
+            let trait_name = cx.tcx.item_name(trait_def_id.to_def_id());
+            let usages = self.dyn_usages.get(&trait_def_id);
+            for (hir_id, span, reason) in methods {
+                if let Some(usages) = usages {
+                    span_lint_hir_and_then(
+                        cx,
+                        OBJECT_UNSAFE_METHOD,
+                        hir_id,
+                        span,
+                        &format!(
+                            "this method {reason}, which makes `{trait_name}` not object safe, but \
+                             `dyn {trait_name}` is used in this crate"
+                        ),
+                        |diag| {
+                            for &usage in usages {
+                                diag.span_note(usage, format!("`dyn {trait_name}` is used here"));
+                            }
+                            diag.help(HELP);
+                        },
+                    );
+                    continue;
+                }
+                let msg = format!(
+                    "this method {reason}, which makes public trait `{trait_name}` not object safe"
+                );
+                match self.config.without_dyn_usage {
+                    WithoutDynUsage::Ignore => {}
+                    WithoutDynUsage::Note => {
+                        if !is_lint_allowed(cx, OBJECT_UNSAFE_METHOD, hir_id) {
+                            cx.sess().span_note_without_error(span, msg);
+                        }
+                    }
+                    WithoutDynUsage::Lint => {
+                        span_lint_hir_and_then(
+                            cx,
+                            OBJECT_UNSAFE_METHOD,
+                            hir_id,
+                            span,
+                            &msg,
+                            |diag| {
+                                diag.help(HELP);
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+const HELP: &str = "add `where Self: Sized` to the method to exclude it from trait objects";
+
+// smoelius: Returns `None` unless the trait's only object-safety violations, according to the
+// compiler, are methods that return `Self` or that have type parameters. Otherwise, returns each
+// such method's `HirId`, the span of its name, and the reason it is a violation.
+fn suspicious_methods(
+    cx: &LateContext<'_>,
+    trait_def_id: LocalDefId,
+) -> Option<Vec<(HirId, Span, String)>> {
+    let mut reasons = Vec::<(Symbol, Vec<&str>)>::new();
+
+    for violation in cx.tcx.object_safety_violations(trait_def_id.to_def_id()) {
+        let ObjectSafetyViolation::Method(name, code, _) = violation else {
+            return None;
+        };
+        let reason = match code {
+            MethodViolationCode::ReferencesSelfOutput => "returns `Self`",
+            MethodViolationCode::Generic => "has type parameters",
+            _ => return None,
+        };
+        if let Some((_, method_reasons)) = reasons.iter_mut().find(|(other, _)| other == name) {
+            method_reasons.push(reason);
+        } else {
+            reasons.push((*name, vec![reason]));
+        }
+    }
+
+    if reasons.is_empty() {
+        return None;
+    }
+
+    // smoelius: A violation could come from a supertrait. Such a violation is reported for the
+    // supertrait, if at all.
+    reasons
+        .into_iter()
+        .map(|(name, method_reasons)| {
+            let item = cx
+                .tcx
+                .associated_items(trait_def_id.to_def_id())
+                .filter_by_name_unhygienic(name)
+                .next()?;
+            let local_def_id = item.def_id.as_local()?;
+            Some((
+                cx.tcx.hir().local_def_id_to_hir_id(local_def_id),
+                item.ident(cx.tcx).span,
+                method_reasons.join(" and "),
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
+
+#[test]
+fn ui_no_dyn() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui_no_dyn");
+}
+
+#[test]
+fn ui_no_dyn_config() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "ui_no_dyn_config")
+        .dylint_toml(r#"object_unsafe_method.without_dyn_usage = "lint""#)
+        .run();
+}
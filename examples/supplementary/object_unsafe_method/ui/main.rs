@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+pub trait Builder {
+    fn name(&self) -> String;
+    fn builder(self) -> Self;
+}
+
+pub type BoxedBuilder = Box<dyn Builder>;
+
+pub trait Visitor {
+    fn visit<T: std::fmt::Debug>(&self, value: T);
+}
+
+pub type DynVisitor<'a> = &'a dyn Visitor;
+pub type BoxedVisitor = Box<dyn Visitor>;
+
+pub trait Fixed {
+    fn name(&self) -> String;
+    fn builder(self) -> Self
+    where
+        Self: Sized;
+}
+
+pub type BoxedFixed = Box<dyn Fixed>;
+
+// smoelius: `Constructible` is not object safe for other reasons, too.
+pub trait Constructible {
+    fn new() -> Self;
+    fn builder(self) -> Self;
+}
+
+trait Private {
+    fn builder(self) -> Self;
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![allow(dead_code)]
+
+pub trait Builder {
+    fn name(&self) -> String;
+    fn builder(self) -> Self;
+}
+
+pub trait Visitor {
+    fn visit<T: std::fmt::Debug>(&self, value: T);
+}
+
+#[allow(object_unsafe_method)]
+pub trait Quiet {
+    fn builder(self) -> Self;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#![allow(dead_code)]
+
+pub trait Builder {
+    fn name(&self) -> String;
+    fn builder(self) -> Self;
+}
+
+fn main() {}
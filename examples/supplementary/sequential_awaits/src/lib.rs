@@ -0,0 +1,257 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{diagnostics::span_lint_and_help, path_to_local, visitors::for_each_expr};
+use rustc_hir::{
+    hir_id::HirIdSet, Block, BorrowKind, Expr, ExprKind, HirId, MatchSource, Mutability, Node, Pat,
+    Stmt, StmtKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::adjustment::{Adjust, AutoBorrow, AutoBorrowMutability};
+use rustc_span::Span;
+use std::ops::ControlFlow;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for two to four consecutive statements in an async body that each await a call, where
+    /// no call uses a binding introduced by an earlier statement or a variable that an earlier call
+    /// uses mutably.
+    ///
+    /// ### Why is this bad?
+    /// Awaiting the futures one after the other adds their latencies. Awaiting them with a `join!`
+    /// macro (e.g., `futures::join!` or `tokio::join!`) lets them make progress concurrently.
+    ///
+    /// ### Known problems
+    /// - The calls may have side effects that must happen in order, e.g., writing to a log or a
+    ///   socket, which the lint cannot see. This is why the lint is allow-by-default. If the order
+    ///   matters, add `#[allow(sequential_awaits)]` to the function, ideally with a comment saying
+    ///   why.
+    /// - Only uses of local variables are considered. Calls that share state in other ways (e.g.,
+    ///   through a `RefCell` or a `Mutex`) are considered independent.
+    /// - Statements in loops are not checked.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # async fn fetch_a() -> u32 { 0 }
+    /// # async fn fetch_b() -> u32 { 0 }
+    /// # async fn f() -> u32 {
+    /// let a = fetch_a().await;
+    /// let b = fetch_b().await;
+    /// # a + b
+    /// # }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # async fn fetch_a() -> u32 { 0 }
+    /// # async fn fetch_b() -> u32 { 0 }
+    /// # async fn f() -> u32 {
+    /// let (a, b) = futures::join!(fetch_a(), fetch_b());
+    /// # a + b
+    /// # }
+    /// ```
+    pub SEQUENTIAL_AWAITS,
+    Allow,
+    "consecutive `await`s on independent futures that could be joined"
+}
+
+const MAX_WINDOW: usize = 4;
+
+/// A statement (or a block's tail expression) that awaits a call
+struct Await<'tcx> {
+    span: Span,
+    call: &'tcx Expr<'tcx>,
+    /// The pattern of the statement, if it is a `let` statement
+    pat: Option<&'tcx Pat<'tcx>>,
+}
+
+/// The local variables an expression refers to
+#[derive(Default)]
+struct Uses {
+    all: HirIdSet,
+    mutable: HirIdSet,
+}
+
+impl<'tcx> LateLintPass<'tcx> for SequentialAwaits {
+    fn check_block(&mut self, cx: &LateContext<'tcx>, block: &'tcx Block<'tcx>) {
+        if block.span.from_expansion() || in_loop(cx, block.hir_id) {
+            return;
+        }
+
+        let awaits = block
+            .stmts
+            .iter()
+            .map(stmt_await)
+            .chain(block.expr.map(|expr| {
+                awaited_call(expr).map(|call| Await {
+                    span: expr.span,
+                    call,
+                    pat: None,
+                })
+            }))
+            .collect::<Vec<_>>();
+
+        let mut i = 0;
+        while i < awaits.len() {
+            let Some(first) = &awaits[i] else {
+                i += 1;
+                continue;
+            };
+
+            let mut bindings = HirIdSet::default();
+            let mut uses = Uses::default();
+            add(cx, first, &mut bindings, &mut uses);
+
+            let mut last = first;
+            let mut j = i + 1;
+            while j < awaits.len() && j - i < MAX_WINDOW {
+                let Some(next) = &awaits[j] else {
+                    break;
+                };
+                let next_uses = uses_of(cx, next.call);
+                if !next_uses.all.is_disjoint(&bindings)
+                    || !next_uses.all.is_disjoint(&uses.mutable)
+                    || !next_uses.mutable.is_disjoint(&uses.all)
+                {
+                    break;
+                }
+                add(cx, next, &mut bindings, &mut uses);
+                last = next;
+                j += 1;
+            }
+
+            if j - i >= 2 {
+                span_lint_and_help(
+                    cx,
+                    SEQUENTIAL_AWAITS,
+                    first.span.to(last.span),
+                    "these futures are awaited one after the other, but they do not depend on one \
+                     another",
+                    None,
+                    "unless their side effects must happen in order, consider awaiting them \
+                     together with `futures::join!` or `tokio::join!`",
+                );
+            }
+
+            i = j;
+        }
+    }
+}
+
+fn stmt_await<'tcx>(stmt: &'tcx Stmt<'tcx>) -> Option<Await<'tcx>> {
+    let (expr, pat) = match stmt.kind {
+        StmtKind::Local(local) if local.els.is_none() => (local.init?, Some(local.pat)),
+        StmtKind::Expr(expr) | StmtKind::Semi(expr) => (expr, None),
+        _ => return None,
+    };
+    awaited_call(expr).map(|call| Await {
+        span: stmt.span,
+        call,
+        pat,
+    })
+}
+
+// smoelius: `e.await` is desugared to a `match` on `IntoFuture::into_future(e)`, and `e?` to a
+// `match` on `Try::branch(e)`. A trailing `?` is looked through, so that `f().await?` counts as
+// awaiting `f()`.
+fn awaited_call<'tcx>(expr: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    let expr = match expr.kind {
+        ExprKind::Match(scrutinee, _, MatchSource::TryDesugar) => desugared_arg(scrutinee)?,
+        _ => expr,
+    };
+    let ExprKind::Match(scrutinee, _, MatchSource::AwaitDesugar) = expr.kind else {
+        return None;
+    };
+    let call = desugared_arg(scrutinee)?;
+    if matches!(call.kind, ExprKind::Call(..) | ExprKind::MethodCall(..))
+        && !call.span.from_expansion()
+    {
+        Some(call)
+    } else {
+        None
+    }
+}
+
+fn desugared_arg<'tcx>(expr: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    if let ExprKind::Call(_, [arg]) = expr.kind {
+        Some(arg)
+    } else {
+        None
+    }
+}
+
+fn add(cx: &LateContext<'_>, await_: &Await<'_>, bindings: &mut HirIdSet, uses: &mut Uses) {
+    if let Some(pat) = await_.pat {
+        pat.each_binding(|_, hir_id, _, _| {
+            bindings.insert(hir_id);
+        });
+    }
+    let Uses { all, mutable } = uses_of(cx, await_.call);
+    uses.all.extend(all);
+    uses.mutable.extend(mutable);
+}
+
+// smoelius: A variable is considered used mutably if it is borrowed mutably, explicitly or by an
+// adjustment (e.g., as the receiver of a method that takes `&mut self`). Two calls that borrow the
+// same variable mutably could not be joined.
+fn uses_of(cx: &LateContext<'_>, expr: &Expr<'_>) -> Uses {
+    let mut uses = Uses::default();
+    for_each_expr(expr, |expr| {
+        if let ExprKind::AddrOf(BorrowKind::Ref, Mutability::Mut, inner) = expr.kind {
+            if let Some(hir_id) = path_to_local(inner) {
+                uses.mutable.insert(hir_id);
+            }
+        }
+        if let Some(hir_id) = path_to_local(expr) {
+            uses.all.insert(hir_id);
+            if cx
+                .typeck_results()
+                .expr_adjustments(expr)
+                .iter()
+                .any(|adjustment| {
+                    matches!(
+                        adjustment.kind,
+                        Adjust::Borrow(AutoBorrow::Ref(_, AutoBorrowMutability::Mut { .. }))
+                    )
+                })
+            {
+                uses.mutable.insert(hir_id);
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    uses
+}
+
+fn in_loop(cx: &LateContext<'_>, hir_id: HirId) -> bool {
+    for (_, node) in cx.tcx.hir().parent_iter(hir_id) {
+        match node {
+            Node::Expr(Expr {
+                kind: ExprKind::Loop(..),
+                ..
+            }) => return true,
+            Node::Expr(Expr {
+                kind: ExprKind::Closure(..),
+                ..
+            })
+            | Node::Item(_)
+            | Node::ImplItem(_)
+            | Node::TraitItem(_) => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui::Test::src_base(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    )
+    .rustc_flags(["--edition=2021"])
+    .run();
+}
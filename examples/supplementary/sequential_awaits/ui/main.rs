@@ -0,0 +1,116 @@
+#![allow(unknown_lints)]
+#![warn(sequential_awaits)]
+#![allow(dead_code)]
+
+async fn fetch_a() -> u32 {
+    1
+}
+
+async fn fetch_b() -> u32 {
+    2
+}
+
+async fn fetch_c(x: u32) -> u32 {
+    x
+}
+
+async fn try_fetch_a() -> Result<u32, ()> {
+    Ok(1)
+}
+
+async fn try_fetch_b() -> Result<u32, ()> {
+    Ok(2)
+}
+
+async fn log(_message: &str) {}
+
+struct Client;
+
+impl Client {
+    async fn get(&self, _key: &str) -> u32 {
+        0
+    }
+
+    async fn put(&mut self, _key: &str, _value: u32) {}
+}
+
+async fn independent() -> u32 {
+    let a = fetch_a().await;
+    let b = fetch_b().await;
+    a + b
+}
+
+async fn independent_methods(client: &Client) -> u32 {
+    let a = client.get("a").await;
+    let b = client.get("b").await;
+    a + b
+}
+
+async fn independent_with_question_mark() -> Result<u32, ()> {
+    let a = try_fetch_a().await?;
+    let b = try_fetch_b().await?;
+    Ok(a + b)
+}
+
+async fn independent_tail() -> u32 {
+    fetch_a().await;
+    fetch_b().await
+}
+
+// smoelius: At most four statements are flagged together.
+async fn five() -> u32 {
+    let a = fetch_a().await;
+    let b = fetch_b().await;
+    let c = fetch_a().await;
+    let d = fetch_b().await;
+    let e = fetch_a().await;
+    a + b + c + d + e
+}
+
+async fn dependent() -> u32 {
+    let a = fetch_a().await;
+    fetch_c(a).await
+}
+
+// smoelius: Only the first two statements are flagged.
+async fn dependent_after_independent() -> u32 {
+    let a = fetch_a().await;
+    let b = fetch_b().await;
+    let c = fetch_c(b).await;
+    a + c
+}
+
+async fn mutable_receiver(client: &mut Client) {
+    client.put("a", 1).await;
+    client.put("b", 2).await;
+}
+
+async fn separated() -> u32 {
+    let a = fetch_a().await;
+    let x = 1;
+    let b = fetch_b().await;
+    a + b + x
+}
+
+async fn in_loop() {
+    for _ in 0..2 {
+        fetch_a().await;
+        fetch_b().await;
+    }
+}
+
+// smoelius: The lint cannot see that the messages must be logged in order, so this is flagged.
+async fn ordered() {
+    // The first message must be logged before the second.
+    log("starting").await;
+    log("started").await;
+}
+
+// smoelius: The order matters, so the lint is allowed.
+#[allow(sequential_awaits)]
+async fn ordered_and_allowed() {
+    log("stopping").await;
+    log("stopped").await;
+}
+
+fn main() {}
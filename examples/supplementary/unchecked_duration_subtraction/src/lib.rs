@@ -0,0 +1,175 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::{span_lint_and_help, span_lint_and_sugg},
+    get_parent_expr, paths,
+    source::snippet_with_applicability,
+    sugg::Sugg,
+    ty::{is_type_diagnostic_item, match_type},
+};
+use if_chain::if_chain;
+use rustc_errors::Applicability;
+use rustc_hir::{BinOpKind, Expr, ExprKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::Ty;
+use rustc_span::sym;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for subtractions (`-` and `-=`) whose operands are `std::time::Instant`s or
+    /// `std::time::Duration`s, i.e., `Instant - Instant`, `Instant - Duration`, and
+    /// `Duration - Duration`.
+    ///
+    /// When the difference is immediately compared, the lint suggests a saturating subtraction
+    /// (`saturating_duration_since` or `saturating_sub`). A difference that would underflow then
+    /// compares as zero.
+    ///
+    /// ### Why is this bad?
+    /// `Instant - Duration` and `Duration - Duration` panic on underflow. `Instant - Instant`
+    /// currently saturates, but its documentation warns that future versions may panic. Underflow
+    /// is easy to hit in practice, e.g., when a clock is adjusted or an `Instant` comes from
+    /// another thread.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # let start = Instant::now();
+    /// # let timeout = Duration::from_secs(1);
+    /// if Instant::now() - start > timeout {}
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # let start = Instant::now();
+    /// # let timeout = Duration::from_secs(1);
+    /// if Instant::now().saturating_duration_since(start) > timeout {}
+    /// ```
+    pub UNCHECKED_DURATION_SUBTRACTION,
+    Warn,
+    "`Instant` and `Duration` subtractions that can panic"
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Time {
+    Instant,
+    Duration,
+}
+
+impl Time {
+    fn of(cx: &LateContext<'_>, ty: Ty<'_>) -> Option<Self> {
+        // smoelius: `Instant` is not a diagnostic item.
+        if match_type(cx, ty, &paths::INSTANT) {
+            Some(Self::Instant)
+        } else if is_type_diagnostic_item(cx, ty, sym::Duration) {
+            Some(Self::Duration)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Instant => "Instant",
+            Self::Duration => "Duration",
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedDurationSubtraction {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        let (op, lhs, rhs) = match expr.kind {
+            ExprKind::Binary(op, lhs, rhs) if op.node == BinOpKind::Sub => ("-", lhs, rhs),
+            ExprKind::AssignOp(op, lhs, rhs) if op.node == BinOpKind::Sub => ("-=", lhs, rhs),
+            _ => return,
+        };
+
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        let typeck_results = cx.typeck_results();
+        let (Some(lhs_time), Some(rhs_time)) = (
+            Time::of(cx, typeck_results.expr_ty(lhs)),
+            Time::of(cx, typeck_results.expr_ty(rhs)),
+        ) else {
+            return;
+        };
+
+        let msg = format!(
+            "`{} {op} {}` can panic on underflow",
+            lhs_time.name(),
+            rhs_time.name()
+        );
+
+        let saturating_method = match (lhs_time, rhs_time) {
+            (Time::Instant, Time::Instant) => Some("saturating_duration_since"),
+            (Time::Duration, Time::Duration) => Some("saturating_sub"),
+            _ => None,
+        };
+
+        if_chain! {
+            if op == "-";
+            if let Some(saturating_method) = saturating_method;
+            if is_compared(cx, expr);
+            then {
+                let mut applicability = Applicability::MachineApplicable;
+                let receiver = Sugg::hir_with_applicability(cx, lhs, "..", &mut applicability);
+                let arg = snippet_with_applicability(cx, rhs.span, "..", &mut applicability);
+                span_lint_and_sugg(
+                    cx,
+                    UNCHECKED_DURATION_SUBTRACTION,
+                    expr.span,
+                    &msg,
+                    "use a saturating subtraction",
+                    format!("{}.{saturating_method}({arg})", receiver.maybe_par()),
+                    applicability,
+                );
+                return;
+            }
+        }
+
+        let checked_method = if lhs_time == Time::Instant && rhs_time == Time::Instant {
+            "checked_duration_since"
+        } else {
+            "checked_sub"
+        };
+
+        let help = if let Some(saturating_method) = saturating_method {
+            format!(
+                "use `{checked_method}` and handle the `None` case, or use `{saturating_method}`"
+            )
+        } else {
+            format!("use `{checked_method}` and handle the `None` case")
+        };
+
+        span_lint_and_help(
+            cx,
+            UNCHECKED_DURATION_SUBTRACTION,
+            expr.span,
+            &msg,
+            None,
+            &help,
+        );
+    }
+}
+
+fn is_compared(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    get_parent_expr(cx, expr).map_or(
+        false,
+        |parent| matches!(parent.kind, ExprKind::Binary(op, _, _) if op.node.is_comparison()),
+    )
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
@@ -0,0 +1,14 @@
+// dylint: run-rustfix
+
+use std::time::{Duration, Instant};
+
+fn main() {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(1);
+    let budget = Duration::from_secs(2);
+
+    if Instant::now() - start > timeout {}
+    if timeout < Instant::now() - start {}
+    if budget - timeout >= Duration::ZERO {}
+    let _ = budget + timeout - timeout == budget;
+}
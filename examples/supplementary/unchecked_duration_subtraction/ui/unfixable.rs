@@ -0,0 +1,25 @@
+use std::time::{Duration, Instant};
+
+fn main() {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(1);
+
+    let elapsed = Instant::now() - start;
+    let deadline = start - timeout;
+    let remaining = timeout - elapsed;
+
+    let mut instant = Instant::now();
+    instant -= timeout;
+    let mut duration = timeout;
+    duration -= elapsed;
+
+    // smoelius: `Instant` has no saturating subtraction of a `Duration`.
+    if start - timeout < instant {}
+
+    let _ = (deadline, remaining, duration);
+
+    let _ = start + timeout;
+    let _ = timeout.as_secs() - 1;
+    let _ = Instant::now().saturating_duration_since(start);
+    let _ = timeout.checked_sub(elapsed);
+}
@@ -0,0 +1,145 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_help, get_parent_expr, macros::root_macro_call, path_to_local_id,
+    ty::match_type, visitors::for_each_expr,
+};
+use rustc_hir::{Expr, ExprKind, HirId, Local, PatKind};
+use rustc_lint::{LateContext, LateLintPass};
+use std::ops::ControlFlow;
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for local variables bound to newly created `tracing` spans (e.g., by `info_span!`)
+    /// that are never entered or attached to anything.
+    ///
+    /// ### Why is this bad?
+    /// A span records nothing about the code it is meant to cover unless that code runs inside
+    /// it. A span that is created but never entered, e.g., because `.entered()` was forgotten,
+    /// silently loses the intended structure of the traces.
+    ///
+    /// ### Known problems
+    /// - Any use of the span other than a call to one of its inspecting or recording methods (e.g.,
+    ///   `record` or `id`) is considered to enter or attach it. So false negatives could result.
+    /// - Only spans created by `tracing`'s span macros are considered.
+    ///
+    /// ### Example
+    /// ```rust
+    /// # fn work() {}
+    /// let span = tracing::info_span!("work");
+    /// work();
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// # fn work() {}
+    /// let _span = tracing::info_span!("work").entered();
+    /// work();
+    /// ```
+    pub UNENTERED_SPAN,
+    Warn,
+    "`tracing` spans that are created but never entered or attached"
+}
+
+const TRACING_SPAN: [&str; 3] = ["tracing", "span", "Span"];
+
+const SPAN_MACROS: [&str; 6] = [
+    "span",
+    "trace_span",
+    "debug_span",
+    "info_span",
+    "warn_span",
+    "error_span",
+];
+
+// smoelius: Calling one of these methods neither enters the span nor attaches it to anything.
+const NON_ENTERING_METHODS: [&str; 8] = [
+    "field",
+    "has_field",
+    "id",
+    "is_disabled",
+    "is_none",
+    "metadata",
+    "record",
+    "record_all",
+];
+
+impl<'tcx> LateLintPass<'tcx> for UnenteredSpan {
+    fn check_local(&mut self, cx: &LateContext<'tcx>, local: &'tcx Local<'tcx>) {
+        let PatKind::Binding(_, hir_id, ident, None) = local.pat.kind else {
+            return;
+        };
+
+        let Some(init) = local.init else {
+            return;
+        };
+
+        if local.span.from_expansion() || !is_span_macro_call(cx, init) {
+            return;
+        }
+
+        let Some(body_id) = cx.enclosing_body else {
+            return;
+        };
+        let body = cx.tcx.hir().body(body_id);
+
+        if is_entered_or_attached(cx, body.value, hir_id) {
+            return;
+        }
+
+        span_lint_and_help(
+            cx,
+            UNENTERED_SPAN,
+            local.pat.span,
+            &format!("span `{ident}` is created but never entered or attached"),
+            None,
+            "enter it with `.enter()` or `.entered()`, run code in it with `.in_scope(...)`, or \
+             attach it to a future with `.instrument(...)`",
+        );
+    }
+}
+
+// smoelius: The span macros expand to one another. The outermost macro is the one the user wrote.
+fn is_span_macro_call(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let Some(macro_call) = root_macro_call(expr.span) else {
+        return false;
+    };
+
+    cx.tcx.crate_name(macro_call.def_id.krate).as_str() == "tracing"
+        && SPAN_MACROS.contains(&cx.tcx.item_name(macro_call.def_id).as_str())
+        && match_type(cx, cx.typeck_results().expr_ty(expr), &TRACING_SPAN)
+}
+
+// smoelius: Any use other than as the receiver of a non-entering method counts, e.g., passing the
+// span to `instrument`, storing it, or returning it.
+fn is_entered_or_attached<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Expr<'tcx>,
+    hir_id: HirId,
+) -> bool {
+    for_each_expr(body, |expr| {
+        if !path_to_local_id(expr, hir_id) {
+            return ControlFlow::Continue(());
+        }
+        let non_entering = get_parent_expr(cx, expr).map_or(false, |parent| {
+            matches!(
+                parent.kind,
+                ExprKind::MethodCall(path, receiver, _, _)
+                    if receiver.hir_id == expr.hir_id
+                        && NON_ENTERING_METHODS.contains(&path.ident.as_str())
+            )
+        });
+        if non_entering {
+            return ControlFlow::Continue(());
+        }
+        ControlFlow::Break(())
+    })
+    .is_some()
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "ui");
+}
@@ -0,0 +1,46 @@
+use tracing::{field, info_span, span, Instrument, Level};
+
+fn main() {}
+
+#[allow(unused_variables)]
+fn bad_unused() {
+    let span = info_span!("bad_unused");
+    work();
+}
+
+fn bad_recorded_only() {
+    let span = span!(Level::INFO, "bad_recorded_only", value = field::Empty);
+    span.record("value", 32);
+    work();
+}
+
+fn good_enter() {
+    let span = span!(Level::INFO, "good_enter"); //~ OK
+    let _guard = span.enter();
+    work();
+}
+
+fn good_entered() {
+    let span = info_span!("good_entered"); //~ OK
+    let _guard = span.entered();
+    work();
+}
+
+fn good_in_scope() {
+    let span = info_span!("good_in_scope"); //~ OK
+    span.in_scope(work);
+}
+
+async fn good_instrument() {
+    let span = info_span!("good_instrument"); //~ OK
+    async_work().instrument(span).await;
+}
+
+fn good_returned() -> tracing::Span {
+    let span = info_span!("good_returned"); //~ OK
+    span
+}
+
+fn work() {}
+
+async fn async_work() {}
@@ -0,0 +1,458 @@
+#![feature(rustc_private)]
+#![feature(let_chains)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_infer;
+extern crate rustc_middle;
+extern crate rustc_span;
+extern crate rustc_trait_selection;
+
+use clippy_utils::{diagnostics::span_lint_and_then, match_def_path, source::snippet};
+use rustc_errors::Applicability;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::{walk_expr, FnKind, Visitor},
+    Body, Expr, FnDecl, GenericBound, TraitBoundModifier, WherePredicate,
+};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::{
+    hir::nested_filter,
+    ty::{
+        self,
+        subst::{GenericArgKind, SubstsRef},
+        Clause, ClauseKind, Ty, TypeckResults,
+    },
+};
+use rustc_span::{symbol::Ident, Span};
+use rustc_trait_selection::traits::{
+    normalize_param_env_or_error, query::evaluate_obligation::InferCtxtExt, Obligation,
+    ObligationCause,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for bounds on a function's generic parameters, e.g., `T: Clone`, that nothing in the
+    /// function requires. By default, bounds on `Clone`, `Default`, `Eq`, and `Hash` are checked.
+    ///
+    /// A bound is required if the function uses it directly, e.g., by calling `T::clone`, or
+    /// indirectly, e.g., by calling a function or constructing a type that requires it. A callee's
+    /// requirements are determined from its predicates, instantiated with the arguments at the
+    /// call.
+    ///
+    /// ### Why is this bad?
+    /// An unnecessary bound restricts the types the function can be called with. Generic callers
+    /// must carry the same bound, even though nothing needs it.
+    ///
+    /// ### Known problems
+    /// - Trait methods and methods in trait impls are not checked, since their bounds must agree
+    ///   with the trait's.
+    /// - Functions that return `impl Trait` (including `async` functions) or that involve trait
+    ///   objects are not checked.
+    /// - A bound required only by another of the function's bounds (e.g., `Wrapper<T>: Debug`,
+    ///   where `Wrapper<T>` implements `Debug` only if `T: Clone`) is considered unnecessary.
+    ///
+    /// ### Example
+    /// ```rust
+    /// fn process<T: Clone>(items: Vec<T>) -> usize {
+    ///     items.len()
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// fn process<T>(items: Vec<T>) -> usize {
+    ///     items.len()
+    /// }
+    /// ```
+    ///
+    /// ### Configuration
+    /// - `traits: Option<Vec<String>>` (default `None`): Paths of the traits whose bounds are
+    ///   checked, e.g., `["core::fmt::Debug"]`. If set, the paths replace the default list:
+    ///   `core::clone::Clone`, `core::cmp::Eq`, `core::default::Default`, `core::hash::Hash`.
+    pub UNNECESSARY_CAPABILITY_BOUND,
+    Warn,
+    "bounds on generic parameters that nothing in the function requires",
+    UnnecessaryCapabilityBound::new()
+}
+
+const DEFAULT_TRAITS: [&str; 4] = [
+    "core::clone::Clone",
+    "core::cmp::Eq",
+    "core::default::Default",
+    "core::hash::Hash",
+];
+
+#[derive(Default, Deserialize)]
+struct Config {
+    traits: Option<Vec<String>>,
+}
+
+struct UnnecessaryCapabilityBound {
+    traits: Vec<Vec<String>>,
+}
+
+impl UnnecessaryCapabilityBound {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let traits = config
+            .traits
+            .unwrap_or_else(|| DEFAULT_TRAITS.map(ToOwned::to_owned).to_vec());
+        Self {
+            traits: traits
+                .iter()
+                .map(|path| path.split("::").map(ToOwned::to_owned).collect())
+                .collect(),
+        }
+    }
+
+    fn is_checked(&self, cx: &LateContext<'_>, trait_def_id: DefId) -> bool {
+        self.traits.iter().any(|path| {
+            match_def_path(
+                cx,
+                trait_def_id,
+                &path.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        })
+    }
+}
+
+/// A bound on one of the function's own type parameters, written `param: Trait`
+struct Candidate {
+    predicate_pos: usize,
+    bound_pos: usize,
+    param: Ident,
+    param_index: u32,
+    trait_def_id: DefId,
+}
+
+impl Candidate {
+    fn matches(&self, clause: Clause<'_>) -> bool {
+        if let ClauseKind::Trait(trait_predicate) = clause.kind().skip_binder()
+            && trait_predicate.def_id() == self.trait_def_id
+            && let ty::Param(param_ty) = trait_predicate.self_ty().kind()
+        {
+            param_ty.index == self.param_index
+        } else {
+            false
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnnecessaryCapabilityBound {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'_>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if span.from_expansion() || matches!(fn_kind, FnKind::Closure) {
+            return;
+        }
+
+        let def_id = local_def_id.to_def_id();
+
+        if cx.tcx.trait_of_item(def_id).is_some()
+            || cx.tcx.impl_of_method(def_id).map_or(false, |impl_def_id| {
+                cx.tcx.trait_id_of_impl(impl_def_id).is_some()
+            })
+        {
+            return;
+        }
+
+        let Some(generics) = cx.tcx.hir().get_generics(local_def_id) else {
+            return;
+        };
+
+        let candidates = self.candidates(cx, def_id, generics);
+        if candidates.is_empty() {
+            return;
+        }
+
+        let Some(requirements) = requirements(cx, local_def_id, body) else {
+            return;
+        };
+
+        let mut predicates = cx
+            .tcx
+            .predicates_of(def_id)
+            .instantiate_identity(cx.tcx)
+            .predicates;
+
+        // smoelius: A requirement that does not hold even with all of the function's bounds (e.g.,
+        // because of how regions were erased) cannot tell one bound from another.
+        let param_env = param_env_from(cx, def_id, &predicates);
+        let requirements = requirements
+            .into_iter()
+            .filter(|&requirement| holds(cx, param_env, requirement))
+            .collect::<Vec<_>>();
+
+        // smoelius: Bounds are removed cumulatively, so that the bounds flagged can be removed
+        // together. Checked separately, two bounds could each make the other unnecessary, e.g.,
+        // `T: Copy + Clone` when both traits are checked.
+        let mut removable = BTreeMap::<usize, Vec<Candidate>>::new();
+        for candidate in candidates {
+            let remaining = predicates
+                .iter()
+                .copied()
+                .filter(|&clause| !candidate.matches(clause))
+                .collect::<Vec<_>>();
+            let param_env = param_env_from(cx, def_id, &remaining);
+            if requirements
+                .iter()
+                .all(|&requirement| holds(cx, param_env, requirement))
+            {
+                predicates = remaining;
+                removable
+                    .entry(candidate.predicate_pos)
+                    .or_default()
+                    .push(candidate);
+            }
+        }
+
+        for (predicate_pos, candidates) in removable {
+            let WherePredicate::BoundPredicate(bound_predicate) =
+                &generics.predicates[predicate_pos]
+            else {
+                continue;
+            };
+            let bounds = bound_predicate.bounds;
+            let removed = candidates
+                .iter()
+                .map(|candidate| candidate.bound_pos)
+                .collect::<Vec<_>>();
+            let names = removed
+                .iter()
+                .map(|&i| format!("`{}`", snippet(cx, bounds[i].span(), "..")))
+                .collect::<Vec<_>>();
+            let param = candidates[0].param;
+
+            // smoelius: A bound preceded by a bound that is kept is removed along with the `+`
+            // before it. Otherwise, it is removed along with the `+` after it.
+            let removal_spans = if removed.len() == bounds.len() {
+                vec![generics.span_for_predicate_removal(predicate_pos)]
+            } else {
+                removed
+                    .iter()
+                    .map(|&i| {
+                        if (0..i).any(|j| !removed.contains(&j)) {
+                            bounds[i - 1].span().shrink_to_hi().to(bounds[i].span())
+                        } else {
+                            bounds[i].span().until(bounds[i + 1].span())
+                        }
+                    })
+                    .collect()
+            };
+
+            span_lint_and_then(
+                cx,
+                UNNECESSARY_CAPABILITY_BOUND,
+                removed
+                    .iter()
+                    .map(|&i| bounds[i].span())
+                    .collect::<Vec<_>>(),
+                &if names.len() == 1 {
+                    format!("unnecessary {} bound on `{param}`", names[0])
+                } else {
+                    format!("unnecessary {} bounds on `{param}`", names.join(", "))
+                },
+                |diag| {
+                    diag.multipart_suggestion_verbose(
+                        if names.len() == 1 {
+                            "remove the bound"
+                        } else {
+                            "remove the bounds"
+                        },
+                        removal_spans
+                            .into_iter()
+                            .map(|span| (span, String::new()))
+                            .collect(),
+                        Applicability::MachineApplicable,
+                    );
+                },
+            );
+        }
+    }
+}
+
+impl UnnecessaryCapabilityBound {
+    fn candidates(
+        &self,
+        cx: &LateContext<'_>,
+        def_id: DefId,
+        generics: &rustc_hir::Generics<'_>,
+    ) -> Vec<Candidate> {
+        let ty_generics = cx.tcx.generics_of(def_id);
+        let mut candidates = Vec::new();
+        for (predicate_pos, predicate) in generics.predicates.iter().enumerate() {
+            let WherePredicate::BoundPredicate(bound_predicate) = predicate else {
+                continue;
+            };
+            if !bound_predicate.bound_generic_params.is_empty()
+                || bound_predicate.span.from_expansion()
+            {
+                continue;
+            }
+            let Some((param_def_id, param)) = bound_predicate.bounded_ty.as_generic_param() else {
+                continue;
+            };
+            if cx.tcx.parent(param_def_id) != def_id {
+                continue;
+            }
+            let Some(&param_index) = ty_generics.param_def_id_to_index.get(&param_def_id) else {
+                continue;
+            };
+            for (bound_pos, bound) in bound_predicate.bounds.iter().enumerate() {
+                if let GenericBound::Trait(poly_trait_ref, TraitBoundModifier::None) = bound
+                    && poly_trait_ref.bound_generic_params.is_empty()
+                    && let Some(trait_def_id) = poly_trait_ref.trait_ref.trait_def_id()
+                    && self.is_checked(cx, trait_def_id)
+                {
+                    candidates.push(Candidate {
+                        predicate_pos,
+                        bound_pos,
+                        param,
+                        param_index,
+                        trait_def_id,
+                    });
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// Returns the predicates that must hold for the function's signature and body to be well formed,
+/// or `None` if the function uses features the lint does not support
+fn requirements<'tcx>(
+    cx: &LateContext<'tcx>,
+    local_def_id: LocalDefId,
+    body: &'tcx Body<'tcx>,
+) -> Option<Vec<Clause<'tcx>>> {
+    let mut visitor = Requirements {
+        cx,
+        typeck_results: cx.tcx.typeck(local_def_id),
+        clauses: Vec::new(),
+        unsupported: false,
+    };
+
+    let fn_sig = cx.tcx.fn_sig(local_def_id).subst_identity();
+    for ty in fn_sig.skip_binder().inputs_and_output {
+        visitor.add_ty(ty);
+    }
+
+    visitor.visit_body(body);
+
+    if visitor.unsupported {
+        None
+    } else {
+        Some(visitor.clauses)
+    }
+}
+
+struct Requirements<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    clauses: Vec<Clause<'tcx>>,
+    unsupported: bool,
+}
+
+impl<'cx, 'tcx> Requirements<'cx, 'tcx> {
+    fn add_item(&mut self, def_id: DefId, substs: SubstsRef<'tcx>) {
+        let tcx = self.cx.tcx;
+        if tcx.generics_of(def_id).count() != substs.len() {
+            return;
+        }
+        self.clauses.extend(
+            tcx.predicates_of(def_id)
+                .instantiate(tcx, substs)
+                .predicates,
+        );
+    }
+
+    // smoelius: A function returning `impl Trait` may need a bound for its return value to
+    // implement `Trait`. Similarly, coercing a value to a trait object may need a bound for the
+    // value to implement the trait. Neither is a requirement the lint can see.
+    fn add_ty(&mut self, ty: Ty<'tcx>) {
+        for arg in ty.walk() {
+            let GenericArgKind::Type(ty) = arg.unpack() else {
+                continue;
+            };
+            match ty.kind() {
+                ty::Adt(adt_def, substs) => self.add_item(adt_def.did(), substs),
+                ty::FnDef(def_id, substs) => self.add_item(*def_id, substs),
+                ty::Alias(ty::Opaque, _) | ty::Dynamic(..) => self.unsupported = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for Requirements<'cx, 'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.cx.tcx.hir()
+    }
+
+    // smoelius: Method calls and overloaded operators resolve to a callee recorded in the typeck
+    // results. Paths to functions have `FnDef` types, which `add_ty` handles.
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if let Some((_, def_id)) = self.typeck_results.type_dependent_def(expr.hir_id) {
+            self.add_item(def_id, self.typeck_results.node_substs(expr.hir_id));
+        }
+        self.add_ty(self.typeck_results.expr_ty(expr));
+        for adjustment in self.typeck_results.expr_adjustments(expr) {
+            self.add_ty(adjustment.target);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns the `ParamEnv` of a function whose predicates are `predicates`
+fn param_env_from<'tcx>(
+    cx: &LateContext<'tcx>,
+    def_id: DefId,
+    predicates: &[Clause<'tcx>],
+) -> ty::ParamEnv<'tcx> {
+    let param_env = cx.tcx.param_env(def_id);
+    let param_env = ty::ParamEnv::new(
+        cx.tcx.mk_clauses_from_iter(predicates.iter().copied()),
+        param_env.reveal(),
+        param_env.constness(),
+    );
+    // smoelius: `normalize_param_env_or_error` elaborates the predicates, e.g., adds `T: PartialEq`
+    // for `T: Eq`, as the `param_env` query does.
+    normalize_param_env_or_error(cx.tcx, param_env, ObligationCause::dummy())
+}
+
+fn holds<'tcx>(
+    cx: &LateContext<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    requirement: Clause<'tcx>,
+) -> bool {
+    // smoelius: A requirement can mention a projection, e.g., `<I as Iterator>::Item`, which must
+    // be normalized before the requirement can be evaluated.
+    let Ok(requirement) = cx.tcx.try_normalize_erasing_regions(param_env, requirement) else {
+        return false;
+    };
+    let obligation = Obligation::new(cx.tcx, ObligationCause::dummy(), param_env, requirement);
+    cx.tcx
+        .infer_ctxt()
+        .build()
+        .predicate_must_hold_modulo_regions(&obligation)
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
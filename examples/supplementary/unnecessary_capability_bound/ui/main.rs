@@ -0,0 +1,60 @@
+// dylint: run-rustfix
+
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+fn process<T: Clone>(items: Vec<T>) -> usize {
+    items.len()
+}
+
+fn first<T: Clone + Default>(items: &[T]) -> Option<T> {
+    items.first().cloned()
+}
+
+struct Counter;
+
+impl Counter {
+    fn count<T: Hash>(&self, items: &[T]) -> usize {
+        items.len()
+    }
+}
+
+// Bounds used directly
+
+fn duplicate<T: Clone>(x: &T) -> (T, T) {
+    (x.clone(), x.clone())
+}
+
+fn make<T: Default>() -> T {
+    T::default()
+}
+
+fn equal<T: Eq>(x: &T, y: &T) -> bool {
+    x == y
+}
+
+// Bounds used via a callee
+
+fn make_pair<T: Default>() -> (T, T) {
+    (make(), make())
+}
+
+fn distinct<T: Eq + Hash>(items: Vec<T>) -> usize {
+    items.into_iter().collect::<HashSet<_>>().len()
+}
+
+// Bounds of methods in trait impls must agree with the trait's
+
+trait Process {
+    fn process<T: Clone>(items: Vec<T>) -> usize;
+}
+
+impl Process for Counter {
+    fn process<T: Clone>(items: Vec<T>) -> usize {
+        items.len()
+    }
+}
+
+fn main() {}
@@ -1,20 +1,37 @@
 use super::{IGNORED_INHERENTS, WATCHED_INHERENTS};
 use clippy_utils::{def_path_res, get_trait_def_id, match_def_path};
 use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::{def_id::DefId, Unsafety};
+use rustc_infer::infer::{
+    type_variable::{TypeVariableOrigin, TypeVariableOriginKind},
+    InferCtxt, TyCtxtInferExt,
+};
 use rustc_lint::LateContext;
 use rustc_middle::ty::{
     self,
     fold::{BottomUpFolder, TypeFolder},
+    subst::GenericArgKind,
 };
-use rustc_span::symbol::sym;
+use rustc_span::{symbol::sym, DUMMY_SP};
+use rustc_trait_selection::traits::{Obligation, ObligationCause, ObligationCtxt};
+use std::cell::RefCell;
 
-pub fn check_inherents<I: Iterator<Item = DefId>>(cx: &LateContext<'_>, inherent_def_ids: I) {
+// smoelius: `check_inherents` returns a description of each discrepancy it finds, rather than
+// panicking. Whether a discrepancy is fatal is up to the caller.
+pub fn check_inherents<I: Iterator<Item = DefId>>(
+    cx: &LateContext<'_>,
+    inherent_def_ids: I,
+) -> Vec<String> {
     let into_iterator_def_id =
         get_trait_def_id(cx, &["core", "iter", "traits", "collect", "IntoIterator"]).unwrap();
     let iterator_def_id =
         get_trait_def_id(cx, &["core", "iter", "traits", "iterator", "Iterator"]).unwrap();
 
+    let mut discrepancies = Vec::new();
+
+    // smoelius: Only the built-in lists are checked. Inherents added with the `watched_inherents`
+    // configuration option can belong to third-party types, about which nothing is asserted.
     let mut type_paths = WATCHED_INHERENTS
         .iter()
         .filter_map(|path| {
@@ -27,7 +44,7 @@ pub fn check_inherents<I: Iterator<Item = DefId>>(cx: &LateContext<'_>, inherent
 
     type_paths.dedup();
 
-    let of_interest = |def_id| -> bool {
+    let of_interest = |def_id, discrepancies: &mut Vec<String>| -> bool {
         if cx.tcx.visibility(def_id) != ty::Visibility::Public {
             return false;
         }
@@ -45,22 +62,29 @@ pub fn check_inherents<I: Iterator<Item = DefId>>(cx: &LateContext<'_>, inherent
         let input_ty = cx.tcx.erase_late_bound_regions(fn_sig.input(0));
         let output_ty = cx.tcx.erase_late_bound_regions(fn_sig.output());
 
-        if let Some(input_item_ty) = implements_trait_with_item(cx, input_ty, into_iterator_def_id)
+        let item_tys = ItemTys::new(cx, cx.tcx.param_env(def_id));
+        if let Some(input_item_ty) =
+            item_tys.implements_trait_with_item(input_ty, into_iterator_def_id)
         {
-            if let Some(output_item_ty) = implements_trait_with_item(cx, output_ty, iterator_def_id)
+            if let Some(output_item_ty) =
+                item_tys.implements_trait_with_item(output_ty, iterator_def_id)
                 && input_item_ty == output_item_ty
             {
                 return true;
             }
-        } else {
+        } else if input_ty.to_string().starts_with("std::vec::Vec") {
             // smoelius: Sanity.
-            assert!(!input_ty.to_string().starts_with("std::vec::Vec"));
+            discrepancies.push(format!(
+                "{:?} takes a `Vec`, which was found not to implement `IntoIterator`",
+                cx.get_def_path(def_id)
+            ));
         }
 
         [input_ty, output_ty].into_iter().all(|ty| {
             let ty = peel_unwanted(cx, def_id, ty);
             ty.is_slice()
                 || ty.is_str()
+                || (matches!(ty.kind(), ty::Param(_)) && is_cow_impl_item(cx, def_id))
                 || ty.ty_adt_def().map_or(false, |adt_def| {
                     type_paths
                         .iter()
@@ -75,16 +99,16 @@ pub fn check_inherents<I: Iterator<Item = DefId>>(cx: &LateContext<'_>, inherent
             continue;
         }
 
-        let def_id = def_path_res(cx, path)
+        if let Some(def_id) = def_path_res(cx, path)
             .into_iter()
             .find_map(|res| res.opt_def_id())
-            .unwrap();
-
-        assert!(
-            of_interest(def_id),
-            "{:?} is not of interest",
-            cx.get_def_path(def_id)
-        );
+        {
+            if !of_interest(def_id, &mut discrepancies) {
+                discrepancies.push(format!("{:?} is not of interest", cx.get_def_path(def_id)));
+            }
+        } else {
+            discrepancies.push(format!("{path:?} does not resolve to anything"));
+        }
     }
 
     // smoelius: Watched inherents are complete(ish).
@@ -97,65 +121,176 @@ pub fn check_inherents<I: Iterator<Item = DefId>>(cx: &LateContext<'_>, inherent
         .chain(inherent_def_ids.map(|def_id| cx.tcx.parent(def_id)))
     {
         for &assoc_item_def_id in cx.tcx.associated_item_def_ids(impl_def_id) {
-            if of_interest(assoc_item_def_id) {
-                assert!(
-                    WATCHED_INHERENTS
-                        .iter()
-                        .chain(IGNORED_INHERENTS.iter())
-                        .any(|path| match_def_path(cx, assoc_item_def_id, path)),
+            if of_interest(assoc_item_def_id, &mut discrepancies)
+                && !WATCHED_INHERENTS
+                    .iter()
+                    .chain(IGNORED_INHERENTS.iter())
+                    .any(|path| match_def_path(cx, assoc_item_def_id, path))
+            {
+                discrepancies.push(format!(
                     "{:?} is missing",
                     cx.get_def_path(assoc_item_def_id)
-                );
+                ));
             }
         }
     }
+
+    discrepancies
 }
 
 fn is_primitive_impl(path: &[&str]) -> bool {
     path.iter().any(|s| s.starts_with('<'))
 }
 
-fn implements_trait_with_item<'tcx>(
-    cx: &LateContext<'tcx>,
-    ty: ty::Ty<'tcx>,
-    trait_id: DefId,
-) -> Option<ty::Ty<'tcx>> {
-    cx.get_associated_type(replace_params_with_global_ty(cx, ty), trait_id, "Item")
+// smoelius: `ItemTys` determines the `Item` types of types that may contain type parameters. For
+// `Vec<T, A>` to be found to implement `IntoIterator`, something must be substituted for `A`, and
+// that something must implement `Allocator`. So each type parameter that appears as an ADT's
+// argument is replaced with that argument's default, if it has one (e.g., `alloc::alloc::Global`
+// for `Vec`'s `A`, or `std::collections::hash_map::RandomState` for `HashMap`'s `S`). Every other
+// type parameter is replaced with a fresh inference variable. Whether a bound holds is then decided
+// by trait solving. A bound that merely cannot be decided (e.g., `?T: Sized`) is not an error.
+//
+// A parameter is replaced the same way in every type passed to the same `ItemTys`. So, e.g., the
+// `Item` types of a function's input and output can be compared.
+pub struct ItemTys<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    infcx: InferCtxt<'tcx>,
+    params: RefCell<FxHashMap<ty::ParamTy, ty::Ty<'tcx>>>,
 }
 
-// smoelius: This is a hack. For `get_associated_type` to return `Some(..)`, all of its argument
-// type's type parameters must be substituted for. One of the types of interest is `Vec`, and its
-// second type parameter must implement `alloc::alloc::Allocator`. So we instantiate all type
-// parameters with the default `Allocator`, `alloc::alloc::Global`. A more robust solution would
-// at least consider trait bounds and alert when a trait other than `Allocator` was encountered.
-fn replace_params_with_global_ty<'tcx>(cx: &LateContext<'tcx>, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
-    let global_def_id = def_path_res(cx, &["alloc", "alloc", "Global"])
-        .into_iter()
-        .find_map(|res| res.opt_def_id())
-        .unwrap();
-    let global_adt_def = cx.tcx.adt_def(global_def_id);
-    let global_ty = cx.tcx.mk_adt(global_adt_def, ty::List::empty());
-    BottomUpFolder {
-        tcx: cx.tcx,
-        ty_op: |ty| {
-            if matches!(ty.kind(), ty::Param(_)) {
-                global_ty
-            } else {
-                ty
+impl<'cx, 'tcx> ItemTys<'cx, 'tcx> {
+    pub fn new(cx: &'cx LateContext<'tcx>, param_env: ty::ParamEnv<'tcx>) -> Self {
+        Self {
+            cx,
+            param_env,
+            infcx: cx.tcx.infer_ctxt().build(),
+            params: RefCell::default(),
+        }
+    }
+
+    pub fn implements_trait_with_item(
+        &self,
+        ty: ty::Ty<'tcx>,
+        trait_id: DefId,
+    ) -> Option<ty::Ty<'tcx>> {
+        let item_def_id = self
+            .cx
+            .tcx
+            .associated_items(trait_id)
+            .in_definition_order()
+            .find(|assoc_item| assoc_item.name.as_str() == "Item")?
+            .def_id;
+
+        let ty = self.replace_params(ty);
+
+        let cause = ObligationCause::dummy();
+        let ocx = ObligationCtxt::new(&self.infcx);
+        ocx.register_obligation(Obligation::new(
+            self.cx.tcx,
+            cause.clone(),
+            self.param_env,
+            ty::TraitRef::new(self.cx.tcx, trait_id, [ty]),
+        ));
+        let item_ty = ocx.normalize(
+            &cause,
+            self.param_env,
+            self.cx
+                .tcx
+                .mk_projection(item_def_id, self.cx.tcx.mk_substs_trait(ty, [])),
+        );
+        if !ocx.select_where_possible().is_empty() {
+            return None;
+        }
+
+        // smoelius: Inference variables that were unified need not resolve to the same variable,
+        // e.g., a parameter's variable and one created while normalizing. So each is replaced with
+        // its root for the `Item` types to be comparable.
+        let item_ty = BottomUpFolder {
+            tcx: self.cx.tcx,
+            ty_op: |ty| {
+                if let ty::Infer(ty::TyVar(vid)) = ty.kind() {
+                    self.cx.tcx.mk_ty_var(self.infcx.root_var(*vid))
+                } else {
+                    ty
+                }
+            },
+            lt_op: std::convert::identity,
+            ct_op: std::convert::identity,
+        }
+        .fold_ty(self.infcx.resolve_vars_if_possible(item_ty));
+
+        Some(self.cx.tcx.erase_regions(item_ty))
+    }
+
+    fn replace_params(&self, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
+        let mut params = self.params.borrow_mut();
+
+        for arg in ty.walk() {
+            if let GenericArgKind::Type(ty) = arg.unpack()
+                && let ty::Adt(adt_def, substs) = ty.kind()
+            {
+                let generics = self.cx.tcx.generics_of(adt_def.did());
+                for (param_def, subst) in generics.params.iter().zip(substs.iter()) {
+                    if let ty::GenericParamDefKind::Type { has_default: true, .. } = param_def.kind
+                        && let GenericArgKind::Type(subst_ty) = subst.unpack()
+                        && let ty::Param(param_ty) = subst_ty.kind()
+                    {
+                        let default_ty = self.cx.tcx.type_of(param_def.def_id).subst_identity();
+                        // smoelius: A default that refers to other parameters (e.g., `U = Vec<T>`)
+                        // would have to be substituted itself. Such defaults are skipped.
+                        if !contains_param(default_ty) {
+                            params.entry(*param_ty).or_insert(default_ty);
+                        }
+                    }
+                }
             }
-        },
-        lt_op: std::convert::identity,
-        ct_op: std::convert::identity,
+        }
+
+        BottomUpFolder {
+            tcx: self.cx.tcx,
+            ty_op: |ty| {
+                if let ty::Param(param_ty) = ty.kind() {
+                    *params.entry(*param_ty).or_insert_with(|| {
+                        self.infcx.next_ty_var(TypeVariableOrigin {
+                            kind: TypeVariableOriginKind::MiscVariable,
+                            span: DUMMY_SP,
+                        })
+                    })
+                } else {
+                    ty
+                }
+            },
+            lt_op: std::convert::identity,
+            ct_op: std::convert::identity,
+        }
+        .fold_ty(ty)
     }
-    .fold_ty(ty)
 }
 
+fn contains_param(ty: ty::Ty<'_>) -> bool {
+    ty.walk().any(|arg| {
+        matches!(
+            arg.unpack(),
+            GenericArgKind::Type(ty) if matches!(ty.kind(), ty::Param(_))
+        )
+    })
+}
+
+// smoelius: `Cow<'_, T>` and `<T as ToOwned>::Owned` are peeled to `T`, and `OsString` and
+// `PathBuf` are peeled to `OsStr` and `Path`. So, e.g., `Path::to_string_lossy`, which returns a
+// `Cow<'_, str>`, is of interest. Within `Cow`'s own impl, `T` is a type parameter. See
+// `is_cow_impl_item` below.
 fn peel_unwanted<'tcx>(
     cx: &LateContext<'tcx>,
     def_id: DefId,
     mut ty: ty::Ty<'tcx>,
 ) -> ty::Ty<'tcx> {
     const BOX: [&str; 3] = ["alloc", "boxed", "Box"];
+    const OS_STR: [&str; 4] = ["std", "ffi", "os_str", "OsStr"];
+    const OS_STRING: [&str; 4] = ["std", "ffi", "os_str", "OsString"];
+    const PATH: [&str; 3] = ["std", "path", "Path"];
+    const PATH_BUF: [&str; 3] = ["std", "path", "PathBuf"];
 
     loop {
         match ty.kind() {
@@ -167,6 +302,25 @@ fn peel_unwanted<'tcx>(
                 ty = substs[0].expect_ty();
                 continue;
             }
+            ty::Adt(adt_def, substs) if match_def_path(cx, adt_def.did(), &COW) => {
+                ty = substs[1].expect_ty();
+                continue;
+            }
+            ty::Adt(adt_def, _) if match_def_path(cx, adt_def.did(), &OS_STRING) => {
+                ty = non_generic_adt_ty(cx, &OS_STR);
+                continue;
+            }
+            ty::Adt(adt_def, _) if match_def_path(cx, adt_def.did(), &PATH_BUF) => {
+                ty = non_generic_adt_ty(cx, &PATH);
+                continue;
+            }
+            ty::Alias(ty::AliasKind::Projection, alias_ty)
+                if cx.tcx.get_diagnostic_item(sym::ToOwned)
+                    == Some(alias_ty.trait_def_id(cx.tcx)) =>
+            {
+                ty = alias_ty.self_ty();
+                continue;
+            }
             _ => {}
         }
 
@@ -181,6 +335,23 @@ fn peel_unwanted<'tcx>(
     ty
 }
 
+const COW: [&str; 3] = ["alloc", "borrow", "Cow"];
+
+fn is_cow_impl_item(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    let impl_ty = cx.tcx.type_of(cx.tcx.parent(def_id)).skip_binder();
+    impl_ty
+        .ty_adt_def()
+        .map_or(false, |adt_def| match_def_path(cx, adt_def.did(), &COW))
+}
+
+fn non_generic_adt_ty<'tcx>(cx: &LateContext<'tcx>, path: &[&str]) -> ty::Ty<'tcx> {
+    let def_id = def_path_res(cx, path)
+        .into_iter()
+        .find_map(|res| res.opt_def_id())
+        .unwrap();
+    cx.tcx.mk_adt(cx.tcx.adt_def(def_id), ty::List::empty())
+}
+
 fn strip_as_ref<'tcx>(
     cx: &LateContext<'tcx>,
     def_id: DefId,
@@ -15,46 +15,54 @@ extern crate rustc_span;
 extern crate rustc_trait_selection;
 
 use clippy_utils::{
-    diagnostics::{span_lint, span_lint_and_help, span_lint_and_sugg},
-    get_parent_expr, match_def_path,
+    def_path_res,
+    diagnostics::{span_lint, span_lint_and_then},
+    get_parent_expr, match_def_path, path_to_local, path_to_local_id,
     source::snippet_opt,
-    ty::is_copy,
+    ty::{is_copy, is_type_diagnostic_item},
+    visitors::for_each_expr_with_closures,
 };
-use dylint_internal::cargo::current_metadata;
+use dylint_internal::{cargo::current_metadata, env};
 use if_chain::if_chain;
 use rustc_data_structures::fx::FxHashSet;
-use rustc_errors::Applicability;
+use rustc_errors::{Applicability, Diagnostic};
 use rustc_hir::{
     def_id::{DefId, LOCAL_CRATE},
-    BorrowKind, Expr, ExprKind, Mutability,
+    BindingAnnotation, BorrowKind, Expr, ExprKind, HirId, Mutability, Node, PatKind,
 };
 use rustc_index::bit_set::BitSet;
 use rustc_infer::infer::TyCtxtInferExt;
-use rustc_lint::{LateContext, LateLintPass};
+use rustc_lint::{LateContext, LateLintPass, LintContext};
 use rustc_middle::ty::{
     self,
     adjustment::{Adjust, Adjustment, AutoBorrow},
     subst::SubstsRef,
     ClauseKind, EarlyBinder, FnDef, FnSig, Param, ParamTy, ProjectionPredicate, Ty, TypeAndMut,
 };
-use rustc_span::symbol::{sym, Symbol};
+use rustc_span::{
+    symbol::{sym, Symbol},
+    Span,
+};
 use rustc_trait_selection::traits::{
     query::evaluate_obligation::InferCtxtExt, Obligation, ObligationCause,
 };
+use serde::Deserialize;
 use std::{
     collections::{BTreeSet, VecDeque},
     fs::OpenOptions,
     io::Write,
+    ops::ControlFlow,
     path::PathBuf,
 };
 
 mod check_inherents;
-use check_inherents::check_inherents;
+use check_inherents::{check_inherents, ItemTys};
 
 dylint_linting::impl_late_lint! {
     /// ### What it does
     /// Checks for trait-behavior-preserving calls in positions where a trait implementation is
-    /// expected.
+    /// expected. Also checks for such calls whose results are bound to locals that are then used
+    /// only once, in such positions.
     ///
     /// ### Why is this bad?
     /// Such unnecessary calls make the code more verbose and could impact performance.
@@ -71,18 +79,102 @@ dylint_linting::impl_late_lint! {
     /// let _ = Command::new("ls").args(["-a", "-l"]);
     /// let _ = Path::new("/").join(".");
     /// ```
+    ///
+    /// ### Configuration
+    /// - `watched_traits: Vec<String>` (default `[]`): Additional trait methods to check for, as
+    ///   paths of the form `crate_name::Trait::method`, e.g.,
+    ///   `my_crate::AsBytesExt::as_bytes_ext`.
+    /// - `watched_inherents: Vec<String>` (default `[]`): Additional inherent methods to check for,
+    ///   as paths of the form `crate_name::Type::method`.
+    /// - `ignore: Vec<{ callee: String, conversion: String }>` (default `[]`): Pairs of callees and
+    ///   conversions for which no warning is emitted, e.g.,
+    ///   `{ callee = "std::fs::write", conversion = "alloc::string::String::as_str" }`.
+    /// - `verbose: bool` (default `false`): Add a note to each warning showing the `ignore` entry
+    ///   that would silence it.
+    ///
+    /// A path's first component must be a crate's name, not `crate`. If that crate is the crate
+    /// being linted or one of its dependencies, but the path does not resolve, a warning is
+    /// emitted.
     pub UNNECESSARY_CONVERSION_FOR_TRAIT,
     Warn,
     "unnecessary calls that preserve trait behavior",
-    UnnecessaryConversionForTrait::default()
+    UnnecessaryConversionForTrait::new()
+}
+
+// smoelius: Apart from `watched_traits`, `watched_inherents`, `ignore`, and `verbose`, these
+// options are meant for testing and debugging the lint.
+#[derive(Default, Deserialize)]
+struct Config {
+    /// Additional trait methods to watch, as paths of the form `crate_name::Trait::method`.
+    #[serde(default)]
+    watched_traits: Vec<String>,
+
+    /// Additional inherent methods to watch, as paths of the form `crate_name::Type::method`.
+    #[serde(default)]
+    watched_inherents: Vec<String>,
+
+    /// Callee-conversion pairs for which no warning is emitted.
+    #[serde(default)]
+    ignore: Vec<IgnoreEntry>,
+
+    /// Add a note to each warning showing the `ignore` entries that would silence it.
+    #[serde(default)]
+    verbose: bool,
+
+    /// Check that the watched inherents are exactly those that should be watched. Discrepancies
+    /// are reported as a warning, or cause a panic if `DYLINT_SELF_CHECK` is enabled.
+    #[serde(default)]
+    check_inherents: bool,
+
+    /// Write the paths of the watched callees encountered to a file in the target directory.
+    #[serde(default)]
+    coverage: bool,
+
+    /// Emit a warning for each callee that is not watched.
+    #[serde(default)]
+    debug_watchlist: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IgnoreEntry {
+    /// The function or method whose argument is converted, e.g., `std::fs::write`
+    callee: String,
+    /// The watched conversion, e.g., `alloc::string::String::as_str`
+    conversion: String,
 }
 
-#[derive(Default)]
 struct UnnecessaryConversionForTrait {
+    config: Config,
+    watchlist: Vec<Vec<String>>,
     callee_paths: BTreeSet<Vec<String>>,
     inherents_def_ids: FxHashSet<DefId>,
 }
 
+impl UnnecessaryConversionForTrait {
+    pub fn new() -> Self {
+        let config: Config = dylint_linting::config_or_default(env!("CARGO_PKG_NAME"));
+        let watchlist = WATCHED_TRAITS
+            .iter()
+            .chain(WATCHED_INHERENTS.iter())
+            .map(|path| path.iter().map(ToString::to_string).collect())
+            .chain(
+                config
+                    .watched_traits
+                    .iter()
+                    .chain(config.watched_inherents.iter())
+                    .map(|path| split_path(path)),
+            )
+            .collect();
+        Self {
+            config,
+            watchlist,
+            callee_paths: BTreeSet::default(),
+            inherents_def_ids: FxHashSet::default(),
+        }
+    }
+}
+
 const WATCHED_TRAITS: &[&[&str]] = &[
     &["alloc", "borrow", "ToOwned", "to_owned"],
     &["alloc", "string", "ToString", "to_string"],
@@ -95,6 +187,7 @@ const WATCHED_TRAITS: &[&[&str]] = &[
 ];
 
 const WATCHED_INHERENTS: &[&[&str]] = &[
+    &["alloc", "borrow", "Cow", "into_owned"],
     &["alloc", "slice", "<impl [T]>", "into_vec"],
     &["alloc", "slice", "<impl [T]>", "to_vec"],
     &["alloc", "str", "<impl str>", "into_boxed_bytes"],
@@ -131,11 +224,13 @@ const WATCHED_INHERENTS: &[&[&str]] = &[
 ];
 
 const IGNORED_INHERENTS: &[&[&str]] = &[
+    &["alloc", "borrow", "Cow", "to_mut"],
     &["alloc", "str", "<impl str>", "to_ascii_lowercase"],
     &["alloc", "str", "<impl str>", "to_ascii_uppercase"],
     &["alloc", "str", "<impl str>", "to_lowercase"],
     &["alloc", "str", "<impl str>", "to_uppercase"],
     &["alloc", "string", "String", "from_utf16_lossy"],
+    &["alloc", "string", "String", "from_utf8_lossy"],
     &["alloc", "string", "String", "leak"],
     &["alloc", "vec", "Vec", "leak"],
     &["alloc", "vec", "Vec", "spare_capacity_mut"],
@@ -150,6 +245,8 @@ const IGNORED_INHERENTS: &[&[&str]] = &[
     &["core", "str", "<impl str>", "trim_right"],
     &["std", "ffi", "os_str", "OsStr", "to_ascii_lowercase"],
     &["std", "ffi", "os_str", "OsStr", "to_ascii_uppercase"],
+    &["std", "ffi", "os_str", "OsStr", "to_string_lossy"],
+    &["std", "path", "Path", "to_string_lossy"],
 ];
 
 // smoelius: See the comment preceding `check_expr_post` below.
@@ -167,8 +264,48 @@ const MAIN_RS: &str = "fn main() {
 }";
 
 impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
+    // smoelius: A path whose first component does not name this crate or one of its dependencies
+    // is skipped, because in a workspace, the crate that the path names need not be a dependency of
+    // every crate that is linted.
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        for (key, path) in
+            self.config
+                .watched_traits
+                .iter()
+                .map(|path| ("watched_traits", path))
+                .chain(
+                    self.config
+                        .watched_inherents
+                        .iter()
+                        .map(|path| ("watched_inherents", path)),
+                )
+                .chain(
+                    self.config.ignore.iter().flat_map(|entry| {
+                        [("ignore", &entry.callee), ("ignore", &entry.conversion)]
+                    }),
+                )
+        {
+            let path_split = split_path(path);
+            let path_split = path_split.iter().map(String::as_str).collect::<Vec<_>>();
+            // smoelius: `def_path_res` does not resolve paths through primitive impls, e.g.,
+            // `alloc::str::<impl str>::into_string`. So such paths are not checked.
+            if let Some(krate) = path_split.first()
+                && is_crate_name(cx, krate)
+                && !path.contains('<')
+                && def_path_res(cx, &path_split).is_empty()
+            {
+                cx.sess().warn(format!(
+                    "`{key}` entry `{path}` in the `unnecessary_conversion_for_trait` table of \
+                     `dylint.toml` does not resolve to anything"
+                ));
+            }
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        self.check_receiver(cx, expr);
+
         if_chain! {
             if let Some((maybe_call, maybe_arg, ancestor_mutabilities)) =
                 ancestor_addr_of_mutabilities(cx, expr);
@@ -189,8 +326,11 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
             if let Some(input) = outer_fn_sig.inputs().get(i);
             if let Param(param_ty) = input.kind();
             then {
+                let outer_callee_path = cx.get_def_path(outer_callee_def_id);
+
                 let mut strip_unnecessary_conversions = |mut expr, mut mutabilities| {
                     let mut refs_prefix = None;
+                    let mut conversion_paths = Vec::new();
 
                     #[allow(clippy::while_let_loop)]
                     loop {
@@ -222,12 +362,8 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
                             );
                             then {
                                 let inner_callee_path = cx.get_def_path(inner_callee_def_id);
-                                if !WATCHED_TRAITS
-                                    .iter()
-                                    .chain(WATCHED_INHERENTS.iter())
-                                    .any(|path| match_def_path(cx, inner_callee_def_id, path))
-                                {
-                                    if enabled("DEBUG_WATCHLIST") {
+                                if !is_watched(&self.watchlist, &inner_callee_path) {
+                                    if self.config.debug_watchlist {
                                         span_lint(
                                             cx,
                                             UNNECESSARY_CONVERSION_FOR_TRAIT,
@@ -237,12 +373,21 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
                                     }
                                     break;
                                 }
+                                if is_ignored(
+                                    &self.config.ignore,
+                                    &outer_callee_path,
+                                    &inner_callee_path,
+                                ) {
+                                    break;
+                                }
                                 self.callee_paths.insert(
                                     inner_callee_path
-                                        .into_iter()
+                                        .iter()
+                                        .copied()
                                         .map(Symbol::to_ident_string)
                                         .collect(),
                                 );
+                                conversion_paths.push(inner_callee_path);
                                 expr = inner_arg;
                                 mutabilities = new_mutabilities;
                                 refs_prefix = Some(new_refs_prefix);
@@ -253,11 +398,18 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
                         }
                     }
 
-                    Some(expr).zip(refs_prefix)
+                    refs_prefix.map(|refs_prefix| (expr, refs_prefix, conversion_paths))
+                };
+
+                let verbose = self.config.verbose;
+                let note_pairs = |diag: &mut Diagnostic, conversion_paths: &[Vec<Symbol>]| {
+                    if verbose {
+                        note_ignore_entries(diag, &outer_callee_path, conversion_paths);
+                    }
                 };
 
-                if let Some((inner_arg, refs_prefix)) =
-                    strip_unnecessary_conversions(expr, ancestor_mutabilities)
+                if let Some((inner_arg, refs_prefix, conversion_paths)) =
+                    strip_unnecessary_conversions(expr, ancestor_mutabilities.clone())
                 {
                     let (is_bare_method_call, subject) =
                         if matches!(expr.kind, ExprKind::MethodCall(..)) {
@@ -267,43 +419,84 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
                         };
                     let msg = format!("the {subject} implements the required traits");
                     if is_bare_method_call && refs_prefix.is_empty() && !maybe_arg.span.from_expansion() {
-                        span_lint_and_sugg(
+                        let span = maybe_arg.span.with_lo(inner_arg.span.hi());
+                        span_lint_and_then(
                             cx,
                             UNNECESSARY_CONVERSION_FOR_TRAIT,
-                            maybe_arg.span.with_lo(inner_arg.span.hi()),
+                            span,
                             &msg,
-                            "remove this",
-                            String::new(),
-                            Applicability::MachineApplicable,
+                            |diag| {
+                                diag.span_suggestion(
+                                    span,
+                                    "remove this",
+                                    String::new(),
+                                    Applicability::MachineApplicable,
+                                );
+                                note_pairs(diag, &conversion_paths);
+                            },
                         );
                     } else if maybe_arg.span.from_expansion() && let Some(span) = maybe_arg.span.parent_callsite() {
-                        // smoelius: This message could be more informative.
-                        span_lint_and_help(
+                        span_lint_and_then(
                             cx,
                             UNNECESSARY_CONVERSION_FOR_TRAIT,
                             span,
                             &msg,
-                            None,
-                            "use the macro arguments directly",
+                            |diag| {
+                                // smoelius: This message could be more informative.
+                                diag.help("use the macro arguments directly");
+                                note_pairs(diag, &conversion_paths);
+                            },
                         );
                     } else if let Some(snippet) = snippet_opt(cx, inner_arg.span) {
-                        span_lint_and_sugg(
+                        let span = maybe_arg.span;
+                        span_lint_and_then(
                             cx,
                             UNNECESSARY_CONVERSION_FOR_TRAIT,
-                            maybe_arg.span,
+                            span,
                             &msg,
-                            "use",
-                            format!("{refs_prefix}{snippet}"),
-                            Applicability::MachineApplicable,
+                            |diag| {
+                                diag.span_suggestion(
+                                    span,
+                                    "use",
+                                    format!("{refs_prefix}{snippet}"),
+                                    Applicability::MachineApplicable,
+                                );
+                                note_pairs(diag, &conversion_paths);
+                            },
                         );
                     }
+                } else if let Some(binding) = single_use_binding(cx, expr)
+                    && !maybe_arg.span.from_expansion()
+                    && let Some((inner_arg, refs_prefix, conversion_paths)) =
+                        strip_unnecessary_conversions(binding.init, ancestor_mutabilities)
+                    && is_unused_between(cx, inner_arg, binding.let_span, expr.span)
+                    && let Some(snippet) = snippet_opt(cx, inner_arg.span)
+                {
+                    span_lint_and_then(
+                        cx,
+                        UNNECESSARY_CONVERSION_FOR_TRAIT,
+                        binding.init.span,
+                        "the binding is used once, and the value it is converted from implements \
+                         the required traits",
+                        |diag| {
+                            diag.multipart_suggestion(
+                                "pass the original value directly and remove the binding",
+                                vec![
+                                    (binding.removal_span, String::new()),
+                                    (maybe_arg.span, format!("{refs_prefix}{snippet}")),
+                                ],
+                                Applicability::MachineApplicable,
+                            );
+                            note_pairs(diag, &conversion_paths);
+                        },
+                    );
                 }
             }
         }
     }
 
     fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
-        if enabled("COVERAGE") {
+        if self.config.coverage {
             let path = coverage_path(cx.tcx.crate_name(LOCAL_CRATE).as_str());
             // smoelius: Don't overwrite an existing file.
             if path.exists() {
@@ -320,13 +513,35 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
             }
         }
 
-        if enabled("CHECK_INHERENTS") {
-            assert_eq!(INHERENT_SEEDS.len(), self.inherents_def_ids.len());
-            check_inherents(
+        // smoelius: A discrepancy is fatal only if `DYLINT_SELF_CHECK` is enabled, as it is when
+        // the lint's own tests are run. Otherwise, a toolchain bump that adds, e.g., a new
+        // inherent method would cause compilations to abort.
+        if self.config.check_inherents {
+            let mut discrepancies = Vec::new();
+            if INHERENT_SEEDS.len() != self.inherents_def_ids.len() {
+                discrepancies.push(format!(
+                    "found {} of the {} inherent seeds",
+                    self.inherents_def_ids.len(),
+                    INHERENT_SEEDS.len()
+                ));
+            }
+            discrepancies.extend(check_inherents(
                 cx,
                 std::iter::once(cx.tcx.lang_items().slice_len_fn().unwrap())
                     .chain(self.inherents_def_ids.iter().copied()),
-            );
+            ));
+            if !discrepancies.is_empty() {
+                let msg = format!(
+                    "`check_inherents` found discrepancies in the \
+                     `unnecessary_conversion_for_trait` watchlists:{}",
+                    discrepancies
+                        .iter()
+                        .map(|discrepancy| format!("\n    {discrepancy}"))
+                        .collect::<String>()
+                );
+                assert!(!env::enabled(env::DYLINT_SELF_CHECK), "{msg}");
+                cx.sess().warn(msg);
+            }
         }
     }
 
@@ -346,7 +561,7 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
     // Note that a similar hack is not needed to obtain a `DefId` within `core::slice::<impl [T]>`
     // because one can use `LanguageItems::slice_len_fn`.
     fn check_expr_post(&mut self, cx: &LateContext<'tcx>, expr: &Expr<'tcx>) {
-        if enabled("CHECK_INHERENTS")
+        if self.config.check_inherents
             && let Some(def_id) = cx.typeck_results().type_dependent_def_id(expr.hir_id)
             && INHERENT_SEEDS.iter().any(|path| match_def_path(cx, def_id, path))
         {
@@ -355,34 +570,120 @@ impl<'tcx> LateLintPass<'tcx> for UnnecessaryConversionForTrait {
     }
 }
 
+impl UnnecessaryConversionForTrait {
+    // smoelius: `check_expr` does not handle a receiver whose type appears in the return type of
+    // the method called on it (e.g., `IntoIterator::into_iter`), because removing the receiver's
+    // conversion changes the method's return type. `check_receiver` handles such receivers, as
+    // well as receivers produced by collecting into a `Vec`. It follows the method chain that
+    // begins with the call, and checks each method's bounds against the type its receiver would
+    // have without the conversion. If every method in the chain would return a different type,
+    // the new type escapes the chain, and the suggestion could affect type inference elsewhere.
+    //
+    // Removing a `collect` makes the iteration lazy, which could reorder side effects. So that
+    // suggestion is never machine applicable. Moreover, the borrows held by the uncollected
+    // iterator would last until the end of the chain. So a `collect` is flagged only if the new
+    // type does not escape the chain, and the later methods' arguments do not refer to locals
+    // that the iterator refers to.
+    fn check_receiver<'tcx>(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        const COLLECT: [&str; 6] = ["core", "iter", "traits", "iterator", "Iterator", "collect"];
+
+        if_chain! {
+            if let ExprKind::MethodCall(_, recv, _, _) = expr.kind;
+            if !expr.span.from_expansion();
+            if let Some(def_id) = cx.typeck_results().type_dependent_def_id(expr.hir_id);
+            if let ExprKind::MethodCall(_, inner_recv, [], _) = recv.kind;
+            if let Some(inner_callee_def_id) =
+                cx.typeck_results().type_dependent_def_id(recv.hir_id);
+            let inner_callee_path = cx.get_def_path(inner_callee_def_id);
+            let recv_ty = cx.typeck_results().expr_ty(recv);
+            let is_collect = match_def_path(cx, inner_callee_def_id, &COLLECT)
+                && is_type_diagnostic_item(cx, recv_ty, sym::Vec);
+            if is_collect || is_watched(&self.watchlist, &inner_callee_path);
+            // smoelius: If the method's return type does not involve `Self`, then `check_expr`
+            // handles the receiver, unless the receiver is produced by a `collect`.
+            if is_collect || returns_self(cx, def_id);
+            let callee_path = cx.get_def_path(def_id);
+            if !is_ignored(&self.config.ignore, &callee_path, &inner_callee_path);
+            if adjustment_mutabilities(cx, inner_recv).is_empty();
+            let inner_recv_ty = cx.typeck_results().expr_ty(inner_recv);
+            if let Some(into_iterator_def_id) = cx.tcx.get_diagnostic_item(sym::IntoIterator);
+            let item_tys = ItemTys::new(cx, cx.param_env);
+            let inner_recv_item_ty =
+                item_tys.implements_trait_with_item(inner_recv_ty, into_iterator_def_id);
+            let recv_item_ty = item_tys.implements_trait_with_item(recv_ty, into_iterator_def_id);
+            if !matches!(
+                (inner_recv_item_ty, recv_item_ty),
+                (Some(inner_recv_item_ty), Some(recv_item_ty)) if inner_recv_item_ty != recv_item_ty
+            );
+            let mut later_args = Vec::new();
+            if let Some(escapes) = follow_receiver_chain(cx, recv, inner_recv_ty, &mut later_args);
+            if !is_collect || (!escapes && !shares_locals(cx, inner_recv, &later_args));
+            then {
+                if !is_collect {
+                    self.callee_paths.insert(
+                        inner_callee_path
+                            .iter()
+                            .copied()
+                            .map(Symbol::to_ident_string)
+                            .collect(),
+                    );
+                }
+                let applicability = if is_collect || escapes {
+                    Applicability::MaybeIncorrect
+                } else {
+                    Applicability::MachineApplicable
+                };
+                let msg = if recv_item_ty.is_some() {
+                    "the receiver iterates over the same items"
+                } else {
+                    "the receiver implements the required traits"
+                };
+                let span = recv.span.with_lo(inner_recv.span.hi());
+                span_lint_and_then(
+                    cx,
+                    UNNECESSARY_CONVERSION_FOR_TRAIT,
+                    span,
+                    msg,
+                    |diag| {
+                        diag.span_suggestion(span, "remove this", String::new(), applicability);
+                        if self.config.verbose {
+                            note_ignore_entries(diag, &callee_path, &[inner_callee_path]);
+                        }
+                    },
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::{
-        env::{remove_var, set_var, var_os},
-        ffi::{OsStr, OsString},
+        env::{remove_var, set_var},
         fs::{read_to_string, remove_file, write},
-        sync::Mutex,
+        sync::{Mutex, PoisonError},
     };
     use tempfile::tempdir;
 
-    static MUTEX: Mutex<()> = Mutex::new(());
-
-    #[cfg_attr(
-        dylint_lib = "non_thread_safe_call_in_test",
-        allow(non_thread_safe_call_in_test)
-    )]
     #[test]
-    fn general() {
-        let _lock = MUTEX.lock().unwrap();
-        let _var = VarGuard::set("COVERAGE", "1");
+    fn binding() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "binding");
+    }
 
-        assert!(!enabled("CHECK_INHERENTS"));
+    #[test]
+    fn bounds() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "bounds");
+    }
 
+    #[test]
+    fn general() {
         let path = coverage_path("general");
         remove_file(&path).unwrap_or_default();
 
-        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "general");
+        dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "general")
+            .dylint_toml("unnecessary_conversion_for_trait.coverage = true")
+            .run();
 
         let mut combined_watchlist = WATCHED_TRAITS
             .iter()
@@ -404,73 +705,192 @@ mod test {
         assert_eq!(combined_watchlist.len(), coverage_lines.len());
     }
 
-    #[cfg_attr(
-        dylint_lib = "non_thread_safe_call_in_test",
-        allow(non_thread_safe_call_in_test)
-    )]
+    // smoelius: `DYLINT_SELF_CHECK` is set for the entire process. So the tests that depend upon
+    // whether it is set hold a lock.
+    static SELF_CHECK_MUTEX: Mutex<()> = Mutex::new(());
+
     #[test]
     fn check_inherents() {
-        let _lock = MUTEX.lock().unwrap();
-        let _var = VarGuard::set("CHECK_INHERENTS", "1");
-
-        assert!(!enabled("COVERAGE"));
+        let _lock = SELF_CHECK_MUTEX
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
 
         let tempdir = tempdir().unwrap();
 
         // smoelius: Regarding `str::len`, etc., see the comment preceding `check_expr_post` above.
         write(tempdir.path().join("main.rs"), MAIN_RS).unwrap();
 
-        dylint_testing::ui_test(env!("CARGO_PKG_NAME"), tempdir.path());
+        set_var(env::DYLINT_SELF_CHECK, "1");
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml("unnecessary_conversion_for_trait.check_inherents = true")
+            .run();
+
+        remove_var(env::DYLINT_SELF_CHECK);
     }
 
     #[test]
-    fn unnecessary_to_owned() {
-        let _lock = MUTEX.lock().unwrap();
+    fn check_inherents_warns() {
+        let _lock = SELF_CHECK_MUTEX
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
 
-        assert!(!enabled("COVERAGE"));
-        assert!(!enabled("CHECK_INHERENTS"));
+        remove_var(env::DYLINT_SELF_CHECK);
 
-        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "unnecessary_to_owned");
+        let tempdir = tempdir().unwrap();
+
+        // smoelius: None of the inherent seeds is referred to, which is a discrepancy.
+        write(tempdir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(
+            tempdir.path().join("main.stderr"),
+            "warning: `check_inherents` found discrepancies in the \
+             `unnecessary_conversion_for_trait` watchlists:\n             \
+             found 0 of the 3 inherent seeds\n\n\
+             warning: 1 warning emitted\n\n",
+        )
+        .unwrap();
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml("unnecessary_conversion_for_trait.check_inherents = true")
+            .run();
     }
 
     #[test]
-    fn vec() {
-        let _lock = MUTEX.lock().unwrap();
+    fn watched_traits() {
+        let tempdir = tempdir().unwrap();
 
-        assert!(!enabled("COVERAGE"));
-        assert!(!enabled("CHECK_INHERENTS"));
+        write(tempdir.path().join("main.rs"), WATCHED_TRAITS_MAIN_RS).unwrap();
+        write(
+            tempdir.path().join("main.stderr"),
+            WATCHED_TRAITS_MAIN_STDERR,
+        )
+        .unwrap();
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml(
+                "unnecessary_conversion_for_trait.watched_traits = [\
+                     \"main::AsBytesExt::as_bytes_ext\", \
+                     \"main::AsBytesExt::no_such_method\", \
+                     \"no_such_crate::AsBytesExt::as_bytes_ext\"\
+                 ]",
+            )
+            .run();
+    }
 
-        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "vec");
+    const WATCHED_TRAITS_MAIN_RS: &str = r#"trait AsBytesExt {
+    fn as_bytes_ext(&self) -> &[u8];
+}
+
+impl AsBytesExt for str {
+    fn as_bytes_ext(&self) -> &[u8] {
+        self.as_bytes()
     }
+}
+
+fn main() {
+    let _ = std::fs::write("x", "".as_bytes_ext());
+}
+"#;
+
+    const WATCHED_TRAITS_MAIN_STDERR: &str = "\
+warning: `watched_traits` entry `main::AsBytesExt::no_such_method` in the \
+`unnecessary_conversion_for_trait` table of `dylint.toml` does not resolve to anything
+
+error: the receiver implements the required traits
+  --> $DIR/main.rs:12:35
+   |
+LL |     let _ = std::fs::write(\"x\", \"\".as_bytes_ext());
+   |                                   ^^^^^^^^^^^^^^^ help: remove this
+   |
+   = note: `-D unnecessary-conversion-for-trait` implied by `-D warnings`
+
+error: aborting due to previous error; 1 warning emitted
 
-    // smoelius: `VarGuard` is from the following with the use of `option` added:
-    // https://github.com/rust-lang/rust-clippy/blob/9cc8da222b3893bc13bc13c8827e93f8ea246854/tests/compile-test.rs
+";
 
-    /// Restores an env var on drop
-    #[must_use]
-    struct VarGuard {
-        key: &'static str,
-        value: Option<OsString>,
+    #[test]
+    fn ignore() {
+        let tempdir = tempdir().unwrap();
+
+        write(tempdir.path().join("main.rs"), IGNORE_MAIN_RS).unwrap();
+        write(tempdir.path().join("main.stderr"), IGNORE_MAIN_STDERR).unwrap();
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml(
+                "[unnecessary_conversion_for_trait]\n\
+                 verbose = true\n\
+                 ignore = [\
+                     { callee = \"std::fs::write\", \
+                       conversion = \"alloc::borrow::ToOwned::to_owned\" }, \
+                     { callee = \"std::fs::no_such_function\", \
+                       conversion = \"alloc::borrow::ToOwned::to_owned\" }\
+                 ]",
+            )
+            .run();
     }
 
-    impl VarGuard {
-        fn set(key: &'static str, val: impl AsRef<OsStr>) -> Self {
-            let value = var_os(key);
-            set_var(option(key), val);
-            Self { key, value }
-        }
+    const IGNORE_MAIN_RS: &str = r#"fn main() {
+    let _ = std::fs::write("x", "".to_owned());
+    let _ = std::fs::write("x", "".to_string());
+}
+"#;
+
+    const IGNORE_MAIN_STDERR: &str = "\
+warning: `ignore` entry `std::fs::no_such_function` in the `unnecessary_conversion_for_trait` \
+table of `dylint.toml` does not resolve to anything
+
+error: the receiver implements the required traits
+  --> $DIR/main.rs:3:35
+   |
+LL |     let _ = std::fs::write(\"x\", \"\".to_string());
+   |                                   ^^^^^^^^^^^^ help: remove this
+   |
+   = note: to silence this warning for this callee and conversion, add \
+`{ callee = \"std::fs::write\", conversion = \"alloc::string::ToString::to_string\" }` to the \
+`ignore` list
+   = note: `-D unnecessary-conversion-for-trait` implied by `-D warnings`
+
+error: aborting due to previous error; 1 warning emitted
+
+";
+
+    #[test]
+    fn unknown_key() {
+        let tempdir = tempdir().unwrap();
+
+        write(tempdir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(
+            tempdir.path().join("main.stderr"),
+            "warning: unknown key `check_inherent` in the `unnecessary_conversion_for_trait` \
+             table of `dylint.toml`\n\n",
+        )
+        .unwrap();
+
+        dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), tempdir.path())
+            .dylint_toml("unnecessary_conversion_for_trait.check_inherent = true")
+            .run();
     }
 
-    impl Drop for VarGuard {
-        fn drop(&mut self) {
-            match self.value.as_deref() {
-                None => remove_var(option(self.key)),
-                Some(value) => set_var(option(self.key), value),
-            }
-        }
+    #[test]
+    fn generic_params() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "generic_params");
+    }
+
+    #[test]
+    fn receiver() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "receiver");
+    }
+
+    #[test]
+    fn unnecessary_to_owned() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "unnecessary_to_owned");
     }
-}
 
+    #[test]
+    fn vec() {
+        dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "vec");
+    }
+}
 // smoelius: `get_callee_substs_and_args` was copied from:
 // https://github.com/rust-lang/rust-clippy/blob/98bf99e2f8cf8b357d63a67ce67d5fc5ceef8b3c/clippy_lints/src/methods/unnecessary_to_owned.rs#L306-L330
 
@@ -523,6 +943,9 @@ fn inner_arg_implements_traits<'tcx>(
     let destruct_trait_def_id = cx.tcx.lang_items().destruct_trait();
     let sized_trait_def_id = cx.tcx.lang_items().sized_trait();
 
+    // smoelius: The callee's `param_env` includes bounds declared inline, in `where` clauses, and
+    // with `impl Trait` in argument position. Moreover, it is elaborated, i.e., it includes bounds
+    // implied by supertraits and trait aliases. So how a bound is declared does not matter.
     let predicates = cx.tcx.param_env(callee_def_id).caller_bounds();
     let projection_predicates = predicates
         .iter()
@@ -570,14 +993,7 @@ fn inner_arg_implements_traits<'tcx>(
         return false;
     }
 
-    predicates.iter().all(|predicate| {
-        let predicate = EarlyBinder::bind(predicate).subst(cx.tcx, &substs_with_new_ty);
-        let obligation = Obligation::new(cx.tcx, ObligationCause::dummy(), cx.param_env, predicate);
-        cx.tcx
-            .infer_ctxt()
-            .build()
-            .predicate_must_hold_modulo_regions(&obligation)
-    })
+    predicates_hold(cx, callee_def_id, &substs_with_new_ty)
 }
 
 // smoelius: `replace_types` was copied from:
@@ -725,14 +1141,264 @@ fn build_ty_and_refs_prefix<'tcx>(
     (ty, refs_prefix)
 }
 
-#[must_use]
-fn enabled(name: &str) -> bool {
-    let key = option(name);
-    std::env::var(key).map_or(false, |value| value != "0")
+fn is_watched(watchlist: &[Vec<String>], path: &[Symbol]) -> bool {
+    watchlist.iter().any(|watched| {
+        watched
+            .iter()
+            .map(String::as_str)
+            .eq(path.iter().map(Symbol::as_str))
+    })
+}
+
+fn returns_self(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    cx.tcx
+        .fn_sig(def_id)
+        .skip_binder()
+        .skip_binder()
+        .output()
+        .contains(cx.tcx.types.self_param)
+}
+
+// smoelius: Follows the method chain that begins with `recv`, supposing `recv` had type `new_ty`.
+// Returns `None` if the chain contains a method that is not a trait method, or a method whose
+// bounds the new receiver type would not satisfy. Otherwise, returns whether the new type escapes
+// the chain, i.e., whether every method in the chain would return a different type than it does
+// now. The methods' arguments are pushed onto `args`.
+fn follow_receiver_chain<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut recv: &'tcx Expr<'tcx>,
+    mut new_ty: Ty<'tcx>,
+    args: &mut Vec<&'tcx Expr<'tcx>>,
+) -> Option<bool> {
+    while let Some(parent) = get_parent_expr(cx, recv)
+        && let ExprKind::MethodCall(segment, receiver, method_args, _) = parent.kind
+        && receiver.hir_id == recv.hir_id
+    {
+        let def_id = cx.typeck_results().type_dependent_def_id(parent.hir_id)?;
+        let substs = cx.typeck_results().node_substs(parent.hir_id);
+        // smoelius: A method whose receiver is only auto-referenced has `Self` equal to the
+        // receiver's type. Any other adjustment (e.g., a deref) could resolve differently for
+        // `new_ty`. Similarly, an inherent method with the same name would take precedence over
+        // the trait method.
+        if cx.tcx.trait_of_item(def_id).is_none()
+            || substs.type_at(0) != cx.typeck_results().expr_ty(recv)
+            || adjustment_mutabilities(cx, recv).len()
+                != cx.typeck_results().expr_adjustments(recv).len()
+            || !self_only_in_receiver(cx, def_id)
+            || has_inherent_method(cx, new_ty, segment.ident.name)
+        {
+            return None;
+        }
+        let mut substs_with_new_ty = substs.to_vec();
+        substs_with_new_ty[0] = ty::GenericArg::from(new_ty);
+        if !predicates_hold(cx, def_id, &substs_with_new_ty) {
+            return None;
+        }
+        let fn_sig = cx.tcx.fn_sig(def_id).subst(cx.tcx, &substs_with_new_ty);
+        let output = cx
+            .tcx
+            .try_normalize_erasing_regions(
+                cx.param_env,
+                cx.tcx.erase_late_bound_regions(fn_sig).output(),
+            )
+            .ok()?;
+        args.extend(method_args);
+        if output == cx.typeck_results().expr_ty(parent) {
+            return Some(false);
+        }
+        recv = parent;
+        new_ty = output;
+    }
+    Some(true)
+}
+
+// smoelius: Returns true if `Self` appears in the method's signature only as its receiver, i.e.,
+// as `self`, `&self`, or `&mut self`.
+fn self_only_in_receiver(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    let self_ty = cx.tcx.types.self_param;
+    let fn_sig = cx.tcx.fn_sig(def_id).skip_binder().skip_binder();
+    fn_sig
+        .inputs()
+        .split_first()
+        .map_or(false, |(input, rest)| {
+            input.peel_refs() == self_ty && rest.iter().all(|input| !input.contains(self_ty))
+        })
+}
+
+fn has_inherent_method(cx: &LateContext<'_>, ty: Ty<'_>, name: Symbol) -> bool {
+    if let ty::Adt(adt_def, _) = ty.kind() {
+        cx.tcx
+            .inherent_impls(adt_def.did())
+            .iter()
+            .any(|&impl_def_id| {
+                cx.tcx
+                    .associated_items(impl_def_id)
+                    .filter_by_name_unhygienic(name)
+                    .next()
+                    .is_some()
+            })
+    } else {
+        false
+    }
+}
+
+fn predicates_hold<'tcx>(
+    cx: &LateContext<'tcx>,
+    def_id: DefId,
+    substs: &[ty::GenericArg<'tcx>],
+) -> bool {
+    cx.tcx
+        .param_env(def_id)
+        .caller_bounds()
+        .iter()
+        .all(|predicate| {
+            let predicate = EarlyBinder::bind(predicate).subst(cx.tcx, substs);
+            // smoelius: Bounds like `Self::Item: Ord` mention projections, which must be
+            // normalized before the obligation can be evaluated.
+            let Ok(predicate) = cx.tcx.try_normalize_erasing_regions(cx.param_env, predicate)
+            else {
+                return false;
+            };
+            let obligation =
+                Obligation::new(cx.tcx, ObligationCause::dummy(), cx.param_env, predicate);
+            cx.tcx
+                .infer_ctxt()
+                .build()
+                .predicate_must_hold_modulo_regions(&obligation)
+        })
+}
+
+// smoelius: Returns true if any of `exprs` refers to a local that `expr` refers to.
+fn shares_locals<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    exprs: &[&'tcx Expr<'tcx>],
+) -> bool {
+    let mut locals = FxHashSet::default();
+    for_each_expr_with_closures(cx, expr, |expr| {
+        if let Some(hir_id) = path_to_local(expr) {
+            locals.insert(hir_id);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    exprs.iter().any(|&expr| {
+        for_each_expr_with_closures(cx, expr, |expr| {
+            if path_to_local(expr).map_or(false, |hir_id| locals.contains(&hir_id)) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .is_some()
+    })
+}
+
+struct SingleUseBinding<'tcx> {
+    init: &'tcx Expr<'tcx>,
+    let_span: Span,
+    /// The `let` statement's span, extended to the start of the code that follows it
+    removal_span: Span,
+}
+
+// smoelius: A binding qualifies if it is introduced by a statement of the form `let x = init;`,
+// and `expr` is its only use. In particular, a binding that is mutated or borrowed elsewhere does
+// not qualify. A type annotation could cause `init` to be coerced, so annotated bindings do not
+// qualify either.
+fn single_use_binding<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<SingleUseBinding<'tcx>> {
+    if_chain! {
+        if let Some(hir_id) = path_to_local(expr);
+        if let Node::Pat(pat) = cx.tcx.hir().get(hir_id);
+        if let PatKind::Binding(BindingAnnotation::NONE, _, _, None) = pat.kind;
+        if let Node::Local(local) = cx.tcx.hir().get_parent(hir_id);
+        if local.pat.hir_id == hir_id && local.ty.is_none() && local.els.is_none();
+        if let Some(init) = local.init;
+        if !local.span.from_expansion();
+        if let Node::Stmt(stmt) = cx.tcx.hir().get_parent(local.hir_id);
+        if let Node::Block(block) = cx.tcx.hir().get_parent(stmt.hir_id);
+        if let Some(index) = block.stmts.iter().position(|other| other.hir_id == stmt.hir_id);
+        if let Some(next_span) = block
+            .stmts
+            .get(index + 1)
+            .map(|next| next.span)
+            .or_else(|| block.expr.map(|expr| expr.span));
+        if local_use_spans(cx, hir_id).len() == 1;
+        then {
+            Some(SingleUseBinding {
+                init,
+                let_span: local.span,
+                removal_span: stmt.span.with_hi(next_span.lo()),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+// smoelius: The original value must be a local that is not used between the `let` statement and
+// the binding's use. Otherwise, the local could have been moved or modified in the meantime.
+// Moving the evaluation of any other expression could reorder side effects, so other expressions
+// are not considered.
+fn is_unused_between(cx: &LateContext<'_>, expr: &Expr<'_>, lo: Span, hi: Span) -> bool {
+    path_to_local(expr).map_or(false, |hir_id| {
+        local_use_spans(cx, hir_id)
+            .iter()
+            .all(|span| span.hi() <= lo.hi() || hi.lo() <= span.lo())
+    })
+}
+
+fn local_use_spans(cx: &LateContext<'_>, hir_id: HirId) -> Vec<Span> {
+    let mut spans = Vec::new();
+    if let Some(body_id) = cx.enclosing_body {
+        for_each_expr_with_closures(cx, cx.tcx.hir().body(body_id).value, |expr| {
+            if path_to_local_id(expr, hir_id) {
+                spans.push(expr.span);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+    }
+    spans
+}
+
+fn is_ignored(ignore: &[IgnoreEntry], callee_path: &[Symbol], conversion_path: &[Symbol]) -> bool {
+    ignore.iter().any(|entry| {
+        entry.callee == path_to_string(callee_path)
+            && entry.conversion == path_to_string(conversion_path)
+    })
+}
+
+fn note_ignore_entries(
+    diag: &mut Diagnostic,
+    callee_path: &[Symbol],
+    conversion_paths: &[Vec<Symbol>],
+) {
+    for conversion_path in conversion_paths {
+        diag.note(format!(
+            "to silence this warning for this callee and conversion, add \
+             `{{ callee = \"{}\", conversion = \"{}\" }}` to the `ignore` list",
+            path_to_string(callee_path),
+            path_to_string(conversion_path)
+        ));
+    }
+}
+
+fn path_to_string(path: &[Symbol]) -> String {
+    path.iter()
+        .map(Symbol::as_str)
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split("::").map(ToOwned::to_owned).collect()
 }
 
-fn option(name: &str) -> String {
-    env!("CARGO_PKG_NAME").to_uppercase() + "_" + name
+fn is_crate_name(cx: &LateContext<'_>, name: &str) -> bool {
+    std::iter::once(LOCAL_CRATE)
+        .chain(cx.tcx.crates(()).iter().copied())
+        .any(|cnum| cx.tcx.crate_name(cnum).as_str() == name)
 }
 
 fn coverage_path(krate: &str) -> PathBuf {
@@ -0,0 +1,34 @@
+// run-rustfix
+
+use std::path::Path;
+
+fn main() {
+    let s = String::from("x");
+    let path = Path::new("x");
+
+    let x = s.as_str();
+    let _ = std::fs::write("x", x);
+
+    let x = path.to_path_buf();
+    let _ = std::fs::write(x, "");
+
+    // Not flagged: `x` is mutated.
+    let mut x = s.to_string();
+    x.push('x');
+    let _ = std::fs::write("x", x);
+
+    // Not flagged: `x` is borrowed elsewhere.
+    let x = s.to_string();
+    assert!(!x.is_empty());
+    let _ = std::fs::write("x", x);
+
+    // Not flagged: `t` is consumed before `x` is used.
+    let t = String::from("x");
+    let x = t.to_string();
+    drop(t);
+    let _ = std::fs::write("x", x);
+
+    // Not flagged: `x` is converted from something other than a local.
+    let x = String::from("x").into_bytes();
+    let _ = std::fs::write("x", x);
+}
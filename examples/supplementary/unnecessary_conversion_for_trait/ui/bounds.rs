@@ -0,0 +1,34 @@
+#![feature(trait_alias)]
+
+// smoelius: Each of the below functions requires its argument to implement `AsRef<str>`, but each
+// declares the requirement differently. The lint should treat them all the same.
+
+trait StrRef: AsRef<str> {}
+
+impl<T: AsRef<str> + ?Sized> StrRef for T {}
+
+trait StrRefAlias = AsRef<str>;
+
+fn main() {
+    let s = String::from("x");
+
+    inline_bound(s.as_str());
+    where_clause(s.as_str());
+    impl_trait(s.as_str());
+    supertrait(s.as_str());
+    trait_alias(s.as_str());
+}
+
+fn inline_bound<T: AsRef<str>>(_: T) {}
+
+fn where_clause<T>(_: T)
+where
+    T: AsRef<str>,
+{
+}
+
+fn impl_trait(_: impl AsRef<str>) {}
+
+fn supertrait<T: StrRef>(_: T) {}
+
+fn trait_alias<T: StrRefAlias>(_: T) {}
@@ -4,7 +4,7 @@
 #![feature(os_str_bytes)]
 
 use std::{
-    borrow::{Borrow, BorrowMut},
+    borrow::{Borrow, BorrowMut, Cow},
     ffi::{OsStr, OsString},
     io::Read,
     ops::{Deref, DerefMut},
@@ -88,10 +88,23 @@ fn main() {
 
     let _ = std::fs::write(tempdir.path(), "");
     let _ = std::fs::write(tempfile.path(), "");
+
+    let cow_path = Cow::from(path);
+    let cow_str = Cow::from("x");
+
+    let _ = std::fs::write(cow_path.clone().into_owned(), "");
+    into_string(cow_str.clone().into_owned());
+
+    // smoelius: `Cow<'_, str>` implements neither `AsRef<Path>` nor `AsRef<[u8]>`. So removing
+    // `into_owned` would leave an argument that does not implement the required trait.
+    let _ = std::fs::write(cow_str.clone().into_owned(), "");
+    let _ = std::fs::write("x", cow_str.into_owned());
 }
 
 fn read(_: impl Read) {}
 
+fn into_string(_: impl Into<String>) {}
+
 #[must_use]
 fn is_empty<T: From<Box<str>> + PartialEq>(x: T) -> bool {
     x == T::from(String::new().into_boxed_str())
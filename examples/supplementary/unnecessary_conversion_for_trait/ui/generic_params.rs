@@ -0,0 +1,43 @@
+#![feature(allocator_api)]
+
+use std::alloc::{Allocator, Global};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+struct Stack<T, A: Allocator = Global> {
+    items: Vec<T, A>,
+}
+
+impl<T, A: Allocator> Stack<T, A> {
+    fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+}
+
+fn main() {
+    let mut map = HashMap::new();
+    map.insert(1, "one");
+    generic_hash_map(&map);
+
+    let stack = Stack {
+        items: vec![1, 2, 3],
+    };
+    generic_stack(&stack);
+}
+
+fn generic_hash_map<K: Eq + Hash, V, S: BuildHasher>(map: &HashMap<K, V, S>) {
+    let _ = map.iter().collect::<Vec<_>>().into_iter().count();
+
+    let _ = map.values().collect::<Vec<_>>().into_iter().count();
+}
+
+fn generic_stack<T: Clone, A: Allocator>(stack: &Stack<T, A>) {
+    let _ = stack.as_slice().iter().into_iter().count();
+
+    // Not flagged: `stack.as_slice()` iterates over `&T`s, but `stack.as_slice().to_vec()` iterates
+    // over `T`s.
+    let _ = stack.as_slice().to_vec().into_iter().count();
+
+    // Not flagged: `stack.items` iterates over `T`s, but `stack.items.iter()` iterates over `&T`s.
+    let _ = stack.items.iter().into_iter().count();
+}
@@ -0,0 +1,93 @@
+// run-rustfix
+
+use std::collections::HashSet;
+
+trait CountItems: IntoIterator + Sized {
+    fn count_items(self) -> usize {
+        self.into_iter().count()
+    }
+}
+
+impl<T: IntoIterator> CountItems for T {}
+
+fn main() {
+    let xs = [1, 2, 3];
+    let slice = &xs[..];
+
+    let _ = xs
+        .iter()
+        .map(|x| x * 2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .sum::<i32>();
+
+    let _ = xs
+        .iter()
+        .map(|x| x * 2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .filter(|x| *x > 2)
+        .count();
+
+    let _ = xs
+        .iter()
+        .map(|x| x * 2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .len();
+
+    let _ = xs
+        .iter()
+        .map(|x| x * 2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .max();
+
+    let _ = xs.iter().map(|x| x * 2).collect::<Vec<_>>().count_items();
+
+    let _ = slice.iter().into_iter().count();
+
+    let iter = slice.iter().into_iter();
+    let _ = iter.count();
+
+    // Not flagged: collecting into a `HashSet` removes duplicates.
+    let _ = xs.iter().collect::<HashSet<_>>().into_iter().count();
+
+    // Not flagged: `slice` iterates over `&i32`s, but `slice.to_vec()` iterates over `i32`s.
+    let _ = slice.to_vec().into_iter().map(|x: i32| x).count();
+
+    // Not flagged: `vec` iterates over `i32`s, but `vec.iter()` iterates over `&i32`s.
+    let vec = vec![1, 2, 3];
+    let _ = vec.iter().into_iter().count();
+
+    // Not flagged: `Filter` does not implement `ExactSizeIterator`.
+    let _ = xs
+        .iter()
+        .filter(|x| **x > 1)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .len();
+
+    // Not flagged: `TakeWhile` does not implement `DoubleEndedIterator`.
+    let _ = xs
+        .iter()
+        .take_while(|x| **x < 3)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .count();
+
+    // Not flagged: the iterator would borrow `v` while `v` is mutated.
+    let mut v = vec![1, 2, 3];
+    let iter = v.iter().map(|x| x * 2).collect::<Vec<_>>().into_iter();
+    v.push(4);
+    let _ = iter.count();
+
+    // Not flagged: the closure would mutably borrow `v` while the iterator borrows it.
+    v.iter()
+        .copied()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .for_each(|x| v.push(x));
+}
@@ -0,0 +1,256 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    diagnostics::span_lint_and_help, macros::macro_backtrace, ty::is_type_diagnostic_item,
+    visitors::for_each_expr,
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    Expr, ExprKind, Item, ItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, subst::SubstsRef, AssocKind, ClauseKind};
+use rustc_span::{sym, Symbol};
+use std::ops::ControlFlow;
+
+dylint_linting::impl_late_lint! {
+    /// ### What it does
+    /// Checks for structs that derive `Default` but whose `default` is never used within the
+    /// crate, and that have a private field and a constructor that validates its arguments.
+    ///
+    /// A constructor is considered to validate its arguments if it is an associated function named
+    /// `new` or `try_new` that returns a `Result` or an `Option`, or that asserts or panics.
+    ///
+    /// ### Why is this bad?
+    /// The derived `Default` impl constructs a value without going through the constructor, so it
+    /// can produce a value that the constructor would have rejected. If the impl is not needed, it
+    /// is better removed.
+    ///
+    /// ### Known problems
+    /// - Only uses within the crate are considered. If the struct is public, other crates could
+    ///   use its `Default` impl.
+    /// - A struct's `Default` impl is considered used if another struct that derives `Default` has
+    ///   a field of the struct's type, even if the other struct's `Default` impl is never used.
+    ///
+    /// ### Example
+    /// ```rust
+    /// #[derive(Default)]
+    /// struct Port {
+    ///     number: u16,
+    /// }
+    ///
+    /// impl Port {
+    ///     pub fn new(number: u16) -> Result<Self, String> {
+    ///         if number == 0 {
+    ///             return Err("port number must be nonzero".to_owned());
+    ///         }
+    ///         Ok(Self { number })
+    ///     }
+    /// }
+    /// ```
+    /// Use instead:
+    /// ```rust
+    /// struct Port {
+    ///     number: u16,
+    /// }
+    ///
+    /// impl Port {
+    ///     pub fn new(number: u16) -> Result<Self, String> {
+    ///         if number == 0 {
+    ///             return Err("port number must be nonzero".to_owned());
+    ///         }
+    ///         Ok(Self { number })
+    ///     }
+    /// }
+    /// ```
+    pub UNUSED_DERIVED_DEFAULT,
+    Allow,
+    "derived `Default` impls that bypass a validating constructor and are never used",
+    UnusedDerivedDefault::default()
+}
+
+const CONSTRUCTOR_NAMES: &[&str] = &["new", "try_new"];
+
+const VALIDATING_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "panic",
+    "unreachable",
+];
+
+#[derive(Default)]
+struct UnusedDerivedDefault {
+    derived: Vec<LocalDefId>,
+    used: FxHashSet<DefId>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnusedDerivedDefault {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Impl(impl_) = item.kind;
+            if let Some(trait_ref) = &impl_.of_trait;
+            if let Some(trait_def_id) = trait_ref.trait_def_id();
+            if cx.tcx.is_diagnostic_item(sym::Default, trait_def_id);
+            if cx.tcx.has_attr(item.owner_id.to_def_id(), sym::automatically_derived);
+            if let Some(adt_def) = cx
+                .tcx
+                .type_of(item.owner_id.to_def_id())
+                .skip_binder()
+                .ty_adt_def();
+            if adt_def.is_struct();
+            if let Some(local_def_id) = adt_def.did().as_local();
+            then {
+                self.derived.push(local_def_id);
+            }
+        }
+    }
+
+    // smoelius: Calling `Default::default` on a type requires that the type implement `Default`.
+    // So does calling any function with a `T: Default` bound, e.g., `Option::unwrap_or_default` or
+    // `std::mem::take`. So a struct's `Default` impl is considered used if any function whose
+    // instantiated predicates require the struct to implement `Default` is referred to. Note that
+    // this includes the `Default::default` calls in other structs' derived `Default` impls.
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        let Some((def_id, substs)) = fn_def_and_substs(cx, expr) else {
+            return;
+        };
+
+        for clause in cx
+            .tcx
+            .predicates_of(def_id)
+            .instantiate(cx.tcx, substs)
+            .predicates
+        {
+            if_chain! {
+                if let ClauseKind::Trait(trait_predicate) = clause.kind().skip_binder();
+                if cx.tcx.is_diagnostic_item(sym::Default, trait_predicate.def_id());
+                if let Some(adt_def) = trait_predicate.self_ty().ty_adt_def();
+                then {
+                    self.used.insert(adt_def.did());
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for &local_def_id in &self.derived {
+            let def_id = local_def_id.to_def_id();
+
+            if self.used.contains(&def_id) || !has_private_field(cx, def_id) {
+                continue;
+            }
+
+            let Some(constructor) = validating_constructor(cx, def_id) else {
+                continue;
+            };
+
+            let name = cx.tcx.item_name(def_id);
+
+            span_lint_and_help(
+                cx,
+                UNUSED_DERIVED_DEFAULT,
+                cx.tcx
+                    .def_ident_span(def_id)
+                    .unwrap_or_else(|| cx.tcx.def_span(def_id)),
+                &format!(
+                    "`Default` is derived for `{name}` but never used, and it bypasses the \
+                     validation in `{name}::{constructor}`"
+                ),
+                None,
+                "remove `Default` from the `derive` attribute",
+            );
+        }
+    }
+}
+
+fn fn_def_and_substs<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'_>,
+) -> Option<(DefId, SubstsRef<'tcx>)> {
+    match expr.kind {
+        ExprKind::Path(_) => {
+            if let ty::FnDef(def_id, substs) = *cx.typeck_results().expr_ty(expr).kind() {
+                Some((def_id, substs))
+            } else {
+                None
+            }
+        }
+        ExprKind::MethodCall(..) => {
+            let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+            Some((def_id, cx.typeck_results().node_substs(expr.hir_id)))
+        }
+        _ => None,
+    }
+}
+
+fn has_private_field(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    cx.tcx
+        .adt_def(def_id)
+        .all_fields()
+        .any(|field| !field.vis.is_public())
+}
+
+fn validating_constructor(cx: &LateContext<'_>, def_id: DefId) -> Option<Symbol> {
+    cx.tcx.inherent_impls(def_id).iter().find_map(|&impl_id| {
+        cx.tcx
+            .associated_items(impl_id)
+            .in_definition_order()
+            .find(|item| {
+                item.kind == AssocKind::Fn
+                    && !item.fn_has_self_parameter
+                    && CONSTRUCTOR_NAMES.contains(&item.name.as_str())
+                    && is_validating(cx, item.def_id)
+            })
+            .map(|item| item.name)
+    })
+}
+
+fn is_validating(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    let output = cx.tcx.fn_sig(def_id).skip_binder().skip_binder().output();
+    if is_type_diagnostic_item(cx, output, sym::Result)
+        || is_type_diagnostic_item(cx, output, sym::Option)
+    {
+        return true;
+    }
+
+    let Some(body_id) = def_id
+        .as_local()
+        .and_then(|local_def_id| cx.tcx.hir().maybe_body_owned_by(local_def_id))
+    else {
+        return false;
+    };
+
+    for_each_expr(cx.tcx.hir().body(body_id).value, |expr| {
+        if macro_backtrace(expr.span).any(|macro_call| is_validating_macro(cx, macro_call.def_id)) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .is_some()
+}
+
+fn is_validating_macro(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    matches!(cx.tcx.crate_name(def_id.krate).as_str(), "core" | "std")
+        && VALIDATING_MACROS.contains(&cx.tcx.item_name(def_id).as_str())
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
@@ -0,0 +1,132 @@
+#![allow(unknown_lints)]
+#![warn(unused_derived_default)]
+#![allow(dead_code)]
+
+// smoelius: The constructor returns a `Result`, and `default` is never used.
+#[derive(Debug, Default)]
+struct Port {
+    number: u16,
+}
+
+impl Port {
+    pub fn new(number: u16) -> Result<Self, String> {
+        if number == 0 {
+            return Err("port number must be nonzero".to_owned());
+        }
+        Ok(Self { number })
+    }
+}
+
+// smoelius: The constructor asserts, and `default` is never used.
+#[derive(Default)]
+struct Percentage {
+    value: u8,
+}
+
+impl Percentage {
+    pub fn new(value: u8) -> Self {
+        assert!(value <= 100);
+        Self { value }
+    }
+}
+
+// smoelius: `default` is called.
+#[derive(Default)]
+struct Retries {
+    count: u32,
+}
+
+impl Retries {
+    pub fn try_new(count: u32) -> Option<Self> {
+        (count <= 10).then_some(Self { count })
+    }
+}
+
+// smoelius: `default` is used by `unwrap_or_default`.
+#[derive(Default)]
+struct Timeout {
+    millis: u64,
+}
+
+impl Timeout {
+    pub fn new(millis: u64) -> Self {
+        assert_ne!(millis, 0);
+        Self { millis }
+    }
+}
+
+// smoelius: `default` is used by the derived `Default` impl of `Settings`.
+#[derive(Default)]
+struct Threshold {
+    value: f64,
+}
+
+impl Threshold {
+    pub fn new(value: f64) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(format!("threshold {value} is out of range"));
+        }
+        Ok(Self { value })
+    }
+}
+
+#[derive(Default)]
+struct Settings {
+    threshold: Threshold,
+}
+
+// smoelius: All of the fields are public.
+#[derive(Default)]
+struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Result<Self, String> {
+        if x < 0 || y < 0 {
+            return Err("coordinates must be nonnegative".to_owned());
+        }
+        Ok(Self { x, y })
+    }
+}
+
+// smoelius: The constructor does not validate.
+#[derive(Default)]
+struct Counter {
+    count: u64,
+}
+
+impl Counter {
+    pub fn new(count: u64) -> Self {
+        Self { count }
+    }
+}
+
+// smoelius: `Default` is implemented by hand.
+struct Name {
+    value: String,
+}
+
+impl Default for Name {
+    fn default() -> Self {
+        Self {
+            value: "anonymous".to_owned(),
+        }
+    }
+}
+
+impl Name {
+    pub fn new(value: String) -> Result<Self, String> {
+        if value.is_empty() {
+            return Err("name must be nonempty".to_owned());
+        }
+        Ok(Self { value })
+    }
+}
+
+fn main() {
+    let _ = Retries::default();
+    let _ = None::<Timeout>.unwrap_or_default();
+    let _ = Settings::default();
+}
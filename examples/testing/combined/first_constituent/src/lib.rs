@@ -0,0 +1,38 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+
+use clippy_utils::diagnostics::span_lint;
+use rustc_ast::LitKind;
+use rustc_hir::{Expr, ExprKind};
+use rustc_lint::{LateContext, LateLintPass};
+
+dylint_linting::declare_late_lint! {
+    /// ### What it does
+    /// Checks for `bool` literals. This lint is used only for testing purposes.
+    ///
+    /// ### Why is this bad?
+    /// It's not.
+    ///
+    /// ### Known problems
+    /// None.
+    pub BOOL_LITERAL,
+    Warn,
+    "`bool` literals"
+}
+
+impl<'tcx> LateLintPass<'tcx> for BoolLiteral {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        if let ExprKind::Lit(lit) = expr.kind {
+            if let LitKind::Bool(_) = lit.node {
+                span_lint(cx, BOOL_LITERAL, expr.span, "bool literal");
+            }
+        }
+    }
+}
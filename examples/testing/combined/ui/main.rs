@@ -0,0 +1,4 @@
+fn main() {
+    let _ = true;
+    let _ = 'x';
+}
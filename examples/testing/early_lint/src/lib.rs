@@ -0,0 +1,54 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+
+use clippy_utils::diagnostics::span_lint;
+use rustc_ast::{Expr, ExprKind};
+use rustc_lint::{EarlyContext, EarlyLintPass};
+
+dylint_linting::impl_early_lint! {
+    /// ### What it does
+    /// Checks for parenthesized expressions. This lint is used only for testing purposes.
+    ///
+    /// Parentheses are dropped when the AST is lowered to HIR. So only an early (or pre-expansion)
+    /// pass can see them, and this lint fires only if the driver runs the early passes of
+    /// dynamically loaded libraries.
+    ///
+    /// ### Why is this bad?
+    /// It's not.
+    ///
+    /// ### Known problems
+    /// None.
+    pub PARENTHESIZED_EXPRESSION,
+    Warn,
+    "parenthesized expressions",
+    ParenthesizedExpression
+}
+
+struct ParenthesizedExpression;
+
+impl EarlyLintPass for ParenthesizedExpression {
+    fn check_expr(&mut self, cx: &EarlyContext, expr: &Expr) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        if let ExprKind::Paren(_) = expr.kind {
+            span_lint(
+                cx,
+                PARENTHESIZED_EXPRESSION,
+                expr.span,
+                "parenthesized expression",
+            );
+        }
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    );
+}
@@ -0,0 +1,56 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+
+use clippy_utils::diagnostics::span_lint;
+use rustc_ast::{Item, ItemKind};
+use rustc_lint::{EarlyContext, EarlyLintPass, LintContext};
+
+dylint_linting::declare_early_lint! {
+    /// ### What it does
+    /// Checks for function definitions, except those that the `[global]` table of the workspace's
+    /// `dylint.toml` file excludes. This lint is used only for testing purposes.
+    ///
+    /// ### Why is this bad?
+    /// It's not.
+    ///
+    /// ### Known problems
+    /// None.
+    pub FUNCTION_DEFINITION,
+    Warn,
+    "function definitions"
+}
+
+impl EarlyLintPass for FunctionDefinition {
+    fn check_item(&mut self, cx: &EarlyContext, item: &Item) {
+        if item.span.from_expansion() || dylint_linting::is_excluded(cx.sess(), item.span) {
+            return;
+        }
+
+        if let ItemKind::Fn(_) = item.kind {
+            span_lint(
+                cx,
+                FUNCTION_DEFINITION,
+                item.ident.span,
+                "function definition",
+            );
+        }
+    }
+}
+
+#[test]
+fn ui() {
+    dylint_testing::ui::Test::packages(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+    )
+    .dylint_toml(
+        r#"
+[global]
+exclude-paths = ["src/generated/**"]
+exclude-crates = ["*_generated"]
+"#,
+    )
+    .run();
+}
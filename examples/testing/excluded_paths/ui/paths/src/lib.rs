@@ -0,0 +1,3 @@
+pub mod generated;
+
+pub fn handwritten() {}
@@ -0,0 +1,143 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+dylint_linting::dylint_library!();
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_lint;
+extern crate rustc_session;
+
+use clippy_utils::diagnostics::span_lint;
+use rustc_ast::LitKind;
+use rustc_hir::{Expr, ExprKind};
+use rustc_lint::{LateContext, LateLintPass, LintStore};
+use rustc_session::{declare_lint, declare_lint_pass, Session};
+
+declare_lint! {
+    /// ### What it does
+    /// Checks for integer literals. This lint is used only for testing purposes.
+    ///
+    /// ### Why is this bad?
+    /// It's not.
+    ///
+    /// ### Known problems
+    /// None.
+    pub INTEGER_LITERAL,
+    Warn,
+    "integer literals"
+}
+
+declare_lint! {
+    /// ### What it does
+    /// Checks for string literals. This lint is used only for testing purposes.
+    ///
+    /// ### Why is this bad?
+    /// It's not.
+    ///
+    /// ### Known problems
+    /// None.
+    pub STRING_LITERAL,
+    Warn,
+    "string literals"
+}
+
+declare_lint_pass!(TwoLints => [INTEGER_LITERAL, STRING_LITERAL]);
+
+#[allow(clippy::no_mangle_with_rust_abi)]
+#[no_mangle]
+pub fn register_lints(_sess: &Session, lint_store: &mut LintStore) {
+    lint_store.register_lints(&[INTEGER_LITERAL, STRING_LITERAL]);
+    lint_store.register_late_pass(|_| Box::new(TwoLints));
+}
+
+impl<'tcx> LateLintPass<'tcx> for TwoLints {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if expr.span.from_expansion() {
+            return;
+        }
+
+        if let ExprKind::Lit(lit) = expr.kind {
+            match lit.node {
+                LitKind::Int(..) => span_lint(cx, INTEGER_LITERAL, expr.span, "integer literal"),
+                LitKind::Str(..) => span_lint(cx, STRING_LITERAL, expr.span, "string literal"),
+                _ => {}
+            }
+        }
+    }
+}
+
+// smoelius: Each directory contains the same source file. The expected output in each reflects the
+// lints the test is restricted to.
+
+#[test]
+fn ui_integer_literal() {
+    dylint_testing::ui_test_filtered(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/integer_literal"),
+        &["integer_literal"],
+    );
+}
+
+#[test]
+fn ui_string_literal() {
+    dylint_testing::ui_test_filtered(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/string_literal"),
+        &["string_literal"],
+    );
+}
+
+#[test]
+fn ui_both() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/both"),
+    );
+}
+
+// smoelius: Every input file is named `main.rs`. So if the tests shared a build directory, they
+// would overwrite each other's outputs. Each thread is named, as `libtest` names the thread running
+// a test, so that each gets its own build directory.
+#[test]
+fn ui_concurrent() {
+    let tests: [(&str, &[&str]); 4] = [
+        ("integer_literal", &["integer_literal"]),
+        ("string_literal", &["string_literal"]),
+        ("both", &["integer_literal", "string_literal"]),
+        ("both", &[]),
+    ];
+    std::thread::scope(|scope| {
+        let handles = tests
+            .into_iter()
+            .enumerate()
+            .map(|(i, (dir, lints))| {
+                std::thread::Builder::new()
+                    .name(format!("ui_concurrent_{i}"))
+                    .spawn_scoped(scope, move || {
+                        dylint_testing::ui_test_filtered(
+                            env!("CARGO_PKG_NAME"),
+                            &std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                                .join("ui")
+                                .join(dir),
+                            lints,
+                        );
+                    })
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+}
+
+#[test]
+#[should_panic(expected = "The library does not define the following lints: integer_literals")]
+fn ui_unknown_lint() {
+    dylint_testing::ui_test_filtered(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/integer_literal"),
+        &["integer_literals"],
+    );
+}
@@ -12,6 +12,11 @@ pub fn build(description: &str, quiet: bool) -> crate::Command {
     cargo("build", "Building", description, quiet)
 }
 
+#[must_use]
+pub fn clean(description: &str, quiet: bool) -> crate::Command {
+    cargo("clean", "Cleaning", description, quiet)
+}
+
 // smoelius: `cargo check` and `cargo fix` are never silenced.
 #[must_use]
 pub fn check(description: &str) -> crate::Command {
@@ -1,18 +1,36 @@
-use anyhow::{ensure, Context, Result};
+use crate::watchdog::Watchdog;
+use anyhow::{anyhow, ensure, Context, Result};
 use std::{
     ffi::OsStr,
     path::Path,
-    process::{Command as StdCommand, Output, Stdio},
+    process::{Child, ChildStderr, ChildStdout, Command as StdCommand, ExitStatus, Output, Stdio},
+    time::Duration,
 };
 
 pub struct Command {
     command: StdCommand,
+    timeout: Option<Duration>,
 }
 
+/// The error returned when a command runs longer than its timeout and is killed
+#[derive(Debug)]
+pub struct TimedOut {
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timed out after {}s", self.timeout.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
 impl Command {
     pub fn new<S: AsRef<OsStr>>(program: S) -> Self {
         Self {
             command: StdCommand::new(program),
+            timeout: None,
         }
     }
 
@@ -55,6 +73,14 @@ impl Command {
         self
     }
 
+    /// Interrupt the command, and on Unix, every process in its process group, if it runs longer
+    /// than `timeout`. Applies to `success`, `success_with_stdout`, and
+    /// `success_with_stdout_and_stderr`, which then fail with a [`TimedOut`] error.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     #[cfg_attr(
         dylint_lib = "non_local_effect_before_error_return",
         allow(non_local_effect_before_error_return)
@@ -93,15 +119,127 @@ impl Command {
         log::debug!("{:?}", self.command.get_current_dir());
         log::debug!("{:?}", self.command);
 
-        let status = self
-            .command
-            .status()
-            .with_context(|| format!("Could not get status of `{:?}`", self.command))?;
+        let mut child = self.spawn()?;
+
+        let status = self.wait(&mut child, || Ok(()))?;
+
+        ensure!(status.success(), "command failed: {:?}", self.command);
+
+        Ok(())
+    }
+
+    // smoelius: `success_with_stdout` is like `success`, except that stdout is piped to `f` rather
+    // than inherited. `f` is run to completion before the command's exit status is checked.
+    #[cfg_attr(
+        dylint_lib = "non_local_effect_before_error_return",
+        allow(non_local_effect_before_error_return)
+    )]
+    #[cfg_attr(dylint_lib = "overscoped_allow", allow(overscoped_allow))]
+    pub fn success_with_stdout<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(ChildStdout) -> Result<()>,
+    {
+        log::debug!("{:?}", self.command.get_envs().collect::<Vec<_>>());
+        log::debug!("{:?}", self.command.get_current_dir());
+        log::debug!("{:?}", self.command);
+
+        let mut child = self.stdout(Stdio::piped()).spawn()?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Could not get stdout of `{:?}`", self.command))?;
+
+        let status = self.wait(&mut child, || f(stdout))?;
+
+        ensure!(status.success(), "command failed: {:?}", self.command);
+
+        Ok(())
+    }
+
+    // smoelius: `success_with_stdout_and_stderr` is like `success_with_stdout`, except that stderr
+    // is also piped, to `g`. `g` runs on its own thread so that neither pipe can fill up while the
+    // other is being read.
+    #[cfg_attr(
+        dylint_lib = "non_local_effect_before_error_return",
+        allow(non_local_effect_before_error_return)
+    )]
+    #[cfg_attr(dylint_lib = "overscoped_allow", allow(overscoped_allow))]
+    pub fn success_with_stdout_and_stderr<F, G>(&mut self, f: F, g: G) -> Result<()>
+    where
+        F: FnOnce(ChildStdout) -> Result<()>,
+        G: FnOnce(ChildStderr) -> Result<()> + Send,
+    {
+        log::debug!("{:?}", self.command.get_envs().collect::<Vec<_>>());
+        log::debug!("{:?}", self.command.get_current_dir());
+        log::debug!("{:?}", self.command);
+
+        let mut child = self.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Could not get stdout of `{:?}`", self.command))?;
+
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| anyhow!("Could not get stderr of `{:?}`", self.command))?;
+
+        let status = self.wait(&mut child, || {
+            std::thread::scope(|scope| {
+                let handle = scope.spawn(|| g(stderr));
+                let result = f(stdout);
+                let stderr_result = handle
+                    .join()
+                    .map_err(|_| anyhow!("Thread reading stderr panicked"))?;
+                result.and(stderr_result)
+            })
+        })?;
 
         ensure!(status.success(), "command failed: {:?}", self.command);
 
         Ok(())
     }
+
+    fn spawn(&mut self) -> Result<Child> {
+        // smoelius: A command with a timeout gets its own process group, so that the processes it
+        // starts (e.g., `rustc` and build scripts) can be interrupted along with it.
+        #[cfg(unix)]
+        if self.timeout.is_some() {
+            use std::os::unix::process::CommandExt;
+            self.command.process_group(0);
+        }
+
+        self.command
+            .spawn()
+            .with_context(|| format!("Could not spawn `{:?}`", self.command))
+    }
+
+    // smoelius: `f` is run to completion before the child is waited for. A timeout takes precedence
+    // over an error returned by `f`, since the timeout is likely what caused the error.
+    fn wait(&self, child: &mut Child, f: impl FnOnce() -> Result<()>) -> Result<ExitStatus> {
+        let watchdog = self
+            .timeout
+            .map(|timeout| Watchdog::start(child.id(), timeout));
+
+        let result = f();
+
+        let status = child
+            .wait()
+            .with_context(|| format!("Could not get status of `{:?}`", self.command))?;
+
+        if let Some(watchdog) = watchdog {
+            if let Some(timeout) = watchdog.stop() {
+                return Err(TimedOut { timeout })
+                    .with_context(|| format!("command timed out: {:?}", self.command));
+            }
+        }
+
+        result?;
+
+        Ok(status)
+    }
 }
 
 #[allow(unused_variables)]
@@ -127,3 +265,27 @@ pub fn driver(toolchain: &str, driver: &Path) -> Result<Command> {
     }
     Ok(command)
 }
+
+/// Returns true if a process with id `pid` exists. On platforms where this cannot be determined,
+/// returns true.
+#[must_use]
+pub fn process_exists(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // smoelius: Signal 0 performs only the error checks. `EPERM` means the process exists but
+        // belongs to another user.
+        match libc::pid_t::try_from(pid) {
+            Ok(pid) if pid > 0 => {
+                let result = unsafe { libc::kill(pid, 0) };
+                result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+            }
+            _ => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
@@ -0,0 +1,547 @@
+//! Reads `dylint.toml` files, following `extends` keys.
+//!
+//! A `dylint.toml` file may begin with `extends = "PATH"`, where `PATH` names another `dylint.toml`
+//! file and is resolved relative to the directory of the file that contains it. The extended file
+//! may itself contain an `extends` key. The files' tables are merged, with the extending file's
+//! values taking precedence. Tables present in both files are merged recursively. Any other value
+//! (including an array) in the extending file replaces the extended file's value wholesale.
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{
+    de::{self, Deserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use toml::{value::Table, Spanned, Value};
+
+pub const EXTENDS: &str = "extends";
+
+/// The maximum number of files in an `extends` chain
+pub const MAX_DEPTH: usize = 16;
+
+// smoelius: `toml` deserializes a datetime as a map with this one key. See `toml_datetime`'s
+// `__unstable::FIELD`.
+const DATETIME_FIELD: &str = "$__toml_private_datetime";
+
+/// A merged configuration, along with the file and line of each of its values
+#[derive(Debug, Default)]
+pub struct Config {
+    table: Table,
+    origins: BTreeMap<Vec<String>, Origin>,
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origin {
+    pub name: String,
+    pub line: usize,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.line)
+    }
+}
+
+impl Config {
+    /// Reads the `dylint.toml` file at `path` and the files it extends.
+    pub fn load(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Could not canonicalize {path:?}"))?;
+        let contents = read_to_string(&path)
+            .with_context(|| format!("`read_to_string` failed for {path:?}"))?;
+        Self::load_chain(path, contents)
+    }
+
+    /// Parses `contents` as a `dylint.toml` file named `name`. An `extends` key in `contents` is
+    /// resolved relative to `dir`.
+    pub fn parse(name: &str, contents: &str, dir: &Path) -> Result<Self> {
+        Self::load_chain(dir.join(name), contents.to_owned())
+    }
+
+    fn load_chain(path: PathBuf, contents: String) -> Result<Self> {
+        let mut files = Vec::new();
+        let mut path = path;
+        let mut contents = contents;
+        loop {
+            let name = path.to_string_lossy().into_owned();
+            let mut entries = parse_entries(&name, &contents)?;
+            let extends = take_extends(&name, &contents, &mut entries)?;
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            files.push((path, contents, entries));
+            let Some(extends) = extends else {
+                break;
+            };
+            let extended = dir.join(&extends);
+            let extended = extended.canonicalize().with_context(|| {
+                format!("Could not canonicalize {extended:?}, which `{name}` extends")
+            })?;
+            if let Some(index) = files.iter().position(|(path, _, _)| *path == extended) {
+                let cycle = files[index..]
+                    .iter()
+                    .map(|(path, _, _)| path.to_string_lossy())
+                    .chain(std::iter::once(extended.to_string_lossy()))
+                    .collect::<Vec<_>>();
+                bail!("`{EXTENDS}` cycle: {}", cycle.join(" -> "));
+            }
+            ensure!(
+                files.len() < MAX_DEPTH,
+                "`{EXTENDS}` chain starting at `{}` is longer than {MAX_DEPTH} files",
+                files[0].0.to_string_lossy()
+            );
+            contents = read_to_string(&extended)
+                .with_context(|| format!("`read_to_string` failed for {extended:?}"))?;
+            path = extended;
+        }
+
+        let mut config = Self::default();
+        for (path, contents, entries) in files.into_iter().rev() {
+            let name = path.to_string_lossy().into_owned();
+            merge(
+                &mut config.table,
+                &mut config.origins,
+                &[],
+                entries,
+                &name,
+                &contents,
+            );
+            config.paths.insert(0, path);
+        }
+        Ok(config)
+    }
+
+    /// The merged table, without the `extends` keys
+    #[must_use]
+    pub const fn table(&self) -> &Table {
+        &self.table
+    }
+
+    #[must_use]
+    pub fn into_table(self) -> Table {
+        self.table
+    }
+
+    /// The files that were read, starting with the one that was loaded and followed by the ones it
+    /// extends
+    #[must_use]
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The file and line of the value at `key_path`, e.g., `["library", "key"]`
+    #[must_use]
+    pub fn origin(&self, key_path: &[&str]) -> Option<&Origin> {
+        let key_path = key_path
+            .iter()
+            .map(|&key| key.to_owned())
+            .collect::<Vec<_>>();
+        self.origins.get(&key_path)
+    }
+
+    /// Serializes the merged table, e.g., to pass it in `DYLINT_TOML`.
+    pub fn to_toml_string(&self) -> Result<String> {
+        toml::to_string(&self.table).map_err(Into::into)
+    }
+
+    /// Renders the merged table as TOML, with each value followed by a comment giving its origin.
+    #[must_use]
+    pub fn explain(&self) -> String {
+        let mut buf = String::new();
+        explain_table(&mut buf, &self.origins, &mut Vec::new(), &self.table);
+        buf
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Table(Vec<(String, Spanned<Self>)>),
+    Value(Value),
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a TOML value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Value(Value::Boolean(value)))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Node, E> {
+        Ok(Node::Value(Value::Integer(value)))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Node, E> {
+        i64::try_from(value)
+            .map(|value| Node::Value(Value::Integer(value)))
+            .map_err(|_| E::custom("integer is out of range"))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Node, E> {
+        Ok(Node::Value(Value::Float(value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::Value(Value::String(value.to_owned())))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Node, E> {
+        Ok(Node::Value(Value::String(value)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element::<Value>()? {
+            values.push(value);
+        }
+        Ok(Node::Value(Value::Array(values)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == DATETIME_FIELD {
+                let datetime = map.next_value::<String>()?;
+                return datetime
+                    .parse()
+                    .map(|datetime| Node::Value(Value::Datetime(datetime)))
+                    .map_err(de::Error::custom);
+            }
+            let value = map.next_value::<Spanned<Node>>()?;
+            entries.push((key, value));
+        }
+        Ok(Node::Table(entries))
+    }
+}
+
+fn parse_entries(name: &str, contents: &str) -> Result<Vec<(String, Spanned<Node>)>> {
+    match toml::from_str::<Node>(contents).with_context(|| format!("Could not parse `{name}`"))? {
+        Node::Table(entries) => Ok(entries),
+        Node::Value(_) => bail!("`{name}` is not a table"),
+    }
+}
+
+fn take_extends(
+    name: &str,
+    contents: &str,
+    entries: &mut Vec<(String, Spanned<Node>)>,
+) -> Result<Option<String>> {
+    let Some(index) = entries.iter().position(|(key, _)| key == EXTENDS) else {
+        return Ok(None);
+    };
+    let (_, node) = entries.remove(index);
+    let line = line(contents, node.span().start);
+    match node.into_inner() {
+        Node::Value(Value::String(extends)) => Ok(Some(extends)),
+        _ => bail!("`{EXTENDS}` at {name}:{line} is not a string"),
+    }
+}
+
+fn merge(
+    table: &mut Table,
+    origins: &mut BTreeMap<Vec<String>, Origin>,
+    prefix: &[String],
+    entries: Vec<(String, Spanned<Node>)>,
+    name: &str,
+    contents: &str,
+) {
+    for (key, node) in entries {
+        let key_path = prefix
+            .iter()
+            .cloned()
+            .chain(std::iter::once(key.clone()))
+            .collect::<Vec<_>>();
+        let line = line(contents, node.span().start);
+        match node.into_inner() {
+            Node::Table(entries) => {
+                let value = table
+                    .entry(key)
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if !value.is_table() {
+                    remove_origins(origins, &key_path);
+                    *value = Value::Table(Table::new());
+                }
+                if let Value::Table(subtable) = value {
+                    merge(subtable, origins, &key_path, entries, name, contents);
+                }
+            }
+            Node::Value(value) => {
+                remove_origins(origins, &key_path);
+                origins.insert(
+                    key_path,
+                    Origin {
+                        name: name.to_owned(),
+                        line,
+                    },
+                );
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+// smoelius: A value that replaces a table replaces the origins of everything in the table.
+fn remove_origins(origins: &mut BTreeMap<Vec<String>, Origin>, key_path: &[String]) {
+    origins.retain(|other, _| !other.starts_with(key_path));
+}
+
+fn line(contents: &str, offset: usize) -> usize {
+    contents[..offset].matches('\n').count() + 1
+}
+
+fn explain_table(
+    buf: &mut String,
+    origins: &BTreeMap<Vec<String>, Origin>,
+    key_path: &mut Vec<String>,
+    table: &Table,
+) {
+    let (tables, values): (Vec<_>, Vec<_>) = table.iter().partition(|(_, value)| value.is_table());
+
+    if !key_path.is_empty() && (!values.is_empty() || tables.is_empty()) {
+        if !buf.is_empty() {
+            buf.push('\n');
+        }
+        let header = key_path
+            .iter()
+            .map(|key| quote_key(key))
+            .collect::<Vec<_>>()
+            .join(".");
+        writeln!(buf, "[{header}]").unwrap_or_default();
+    }
+
+    for (key, value) in values {
+        key_path.push(key.clone());
+        write!(buf, "{} = {value}", quote_key(key)).unwrap_or_default();
+        if let Some(origin) = origins.get(key_path) {
+            write!(buf, "  # {origin}").unwrap_or_default();
+        }
+        buf.push('\n');
+        key_path.pop();
+    }
+
+    for (key, value) in tables {
+        if let Value::Table(subtable) = value {
+            key_path.push(key.clone());
+            explain_table(buf, origins, key_path, subtable);
+            key_path.pop();
+        }
+    }
+}
+
+fn quote_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_owned()
+    } else {
+        Value::String(key.to_owned()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::{create_dir_all, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn merge_child_over_parent() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("dylint.toml"),
+            r#"
+[library_a]
+work_limit = 1
+paths = ["a", "b"]
+
+[library_a.nested]
+x = 1
+y = 2
+
+[library_b]
+enabled = true
+"#,
+        )
+        .unwrap();
+        let child_dir = tempdir.path().join("tool");
+        create_dir_all(&child_dir).unwrap();
+        write(
+            child_dir.join("dylint.toml"),
+            r#"extends = "../dylint.toml"
+
+[library_a]
+paths = ["c"]
+
+[library_a.nested]
+y = 3
+
+[library_c]
+enabled = false
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load(&child_dir.join("dylint.toml")).unwrap();
+
+        let expected: Table = toml::from_str(
+            r#"
+[library_a]
+work_limit = 1
+paths = ["c"]
+
+[library_a.nested]
+x = 1
+y = 3
+
+[library_b]
+enabled = true
+
+[library_c]
+enabled = false
+"#,
+        )
+        .unwrap();
+        assert_eq!(&expected, config.table());
+        assert_eq!(2, config.paths().len());
+        assert!(!config.table().contains_key(EXTENDS));
+    }
+
+    #[test]
+    fn value_replaces_table() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("parent.toml"),
+            "[library]\nkey = { a = 1, b = 2 }\n",
+        )
+        .unwrap();
+        write(
+            tempdir.path().join("dylint.toml"),
+            "extends = \"parent.toml\"\n\n[library]\nkey = 0\n",
+        )
+        .unwrap();
+
+        let config = Config::load(&tempdir.path().join("dylint.toml")).unwrap();
+
+        assert_eq!(
+            Some(&Value::Integer(0)),
+            config.table()["library"].get("key")
+        );
+        assert!(config.origin(&["library", "key", "a"]).is_none());
+        assert_eq!(4, config.origin(&["library", "key"]).unwrap().line);
+    }
+
+    #[test]
+    fn cycle() {
+        let tempdir = tempdir().unwrap();
+        write(tempdir.path().join("a.toml"), "extends = \"b.toml\"\n").unwrap();
+        write(tempdir.path().join("b.toml"), "extends = \"a.toml\"\n").unwrap();
+
+        let error = Config::load(&tempdir.path().join("a.toml")).unwrap_err();
+
+        let message = error.to_string();
+        assert!(message.starts_with("`extends` cycle: "), "{message}");
+        assert!(message.ends_with("a.toml"), "{message}");
+    }
+
+    #[test]
+    fn self_cycle() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("dylint.toml"),
+            "extends = \"dylint.toml\"\n",
+        )
+        .unwrap();
+
+        let error = Config::load(&tempdir.path().join("dylint.toml")).unwrap_err();
+
+        assert!(error.to_string().starts_with("`extends` cycle: "));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let tempdir = tempdir().unwrap();
+        for i in 0..=MAX_DEPTH {
+            write(
+                tempdir.path().join(format!("{i}.toml")),
+                format!("extends = \"{}.toml\"\n", i + 1),
+            )
+            .unwrap();
+        }
+        write(tempdir.path().join(format!("{}.toml", MAX_DEPTH + 1)), "").unwrap();
+
+        let error = Config::load(&tempdir.path().join("0.toml")).unwrap_err();
+
+        assert!(error.to_string().contains("is longer than"), "{error}");
+
+        Config::load(&tempdir.path().join("2.toml")).unwrap();
+    }
+
+    #[test]
+    fn extends_must_be_a_string() {
+        let error = Config::parse("dylint.toml", "extends = 1\n", Path::new(".")).unwrap_err();
+
+        assert_eq!(
+            "`extends` at ./dylint.toml:1 is not a string",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn provenance() {
+        let tempdir = tempdir().unwrap();
+        write(
+            tempdir.path().join("parent.toml"),
+            "[library]\nname = \"parent\"\nwork_limit = 1\n",
+        )
+        .unwrap();
+        write(
+            tempdir.path().join("dylint.toml"),
+            "extends = \"parent.toml\"\n\n[library]\nname = \"child\"\n\n[\"other library\"]\nx = 1\n",
+        )
+        .unwrap();
+
+        let config = Config::load(&tempdir.path().join("dylint.toml")).unwrap();
+
+        let parent = tempdir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .join("parent.toml")
+            .to_string_lossy()
+            .into_owned();
+        let child = tempdir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .join("dylint.toml")
+            .to_string_lossy()
+            .into_owned();
+        assert_eq!(
+            format!(
+                "[library]\nname = \"child\"  # {child}:4\nwork_limit = 1  # {parent}:3\n\n\
+                [\"other library\"]\nx = 1  # {child}:7\n"
+            ),
+            config.explain()
+        );
+    }
+}
@@ -6,24 +6,48 @@ macro_rules! declare_const {
     };
 }
 
+declare_const!(CARGO_ENCODED_RUSTFLAGS);
 declare_const!(CARGO_HOME);
+declare_const!(CARGO_HTTP_PROXY);
 declare_const!(CARGO_MANIFEST_DIR);
+declare_const!(CARGO_NET_OFFLINE);
 declare_const!(CARGO_PKG_NAME);
 declare_const!(CARGO_TARGET_DIR);
 declare_const!(CARGO_TERM_COLOR);
 declare_const!(CLIPPY_DISABLE_DOCS_LINKS);
 declare_const!(CLIPPY_DRIVER_PATH);
 declare_const!(DOCS_RS);
+declare_const!(DYLINT_BLESS);
+declare_const!(DYLINT_BROKEN_LINT_IS_FATAL);
+declare_const!(DYLINT_CACHE_DIR);
 declare_const!(DYLINT_DRIVER_PATH);
+declare_const!(DYLINT_HEARTBEAT);
 declare_const!(DYLINT_LIBRARY_PATH);
 declare_const!(DYLINT_LIBS);
+declare_const!(DYLINT_LIB_COPIES);
+declare_const!(DYLINT_LIB_PATHS);
+declare_const!(DYLINT_LINK_INSTALL_DIR);
+declare_const!(DYLINT_LINK_OBJCOPY);
+declare_const!(DYLINT_LINK_QUIET);
+declare_const!(DYLINT_LINK_STRIP);
+declare_const!(DYLINT_LINT_DEPS);
 declare_const!(DYLINT_LIST);
+declare_const!(DYLINT_MANIFEST_LINTS);
+declare_const!(DYLINT_PER_CRATE_TIMEOUT);
+declare_const!(DYLINT_RUSTC_WRAPPER);
 declare_const!(DYLINT_RUSTFLAGS);
+declare_const!(DYLINT_SELF_CHECK);
+declare_const!(DYLINT_TESTING_CARGO_HOME);
+declare_const!(DYLINT_TESTING_HERMETIC);
+declare_const!(DYLINT_TIMEOUTS);
+declare_const!(DYLINT_TIMINGS);
 declare_const!(DYLINT_TOML);
+declare_const!(HOME);
 declare_const!(OUT_DIR);
 declare_const!(PATH);
 declare_const!(RUSTC);
 declare_const!(RUSTC_WORKSPACE_WRAPPER);
+declare_const!(RUSTC_WRAPPER);
 declare_const!(RUSTFLAGS);
 declare_const!(RUSTUP_HOME);
 declare_const!(RUSTUP_TOOLCHAIN);
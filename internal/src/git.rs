@@ -79,3 +79,14 @@ pub fn checkout(repository: &Repository, refname: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// Returns the id of the commit checked out in the repository at `path`
+pub fn head_commit(path: &Path) -> Result<String> {
+    let repository = Repository::open(path)
+        .with_context(|| format!("Could not open repository at `{}`", path.to_string_lossy()))?;
+    let commit = repository
+        .head()
+        .and_then(|reference| reference.peel_to_commit())
+        .with_context(|| format!("Could not get `HEAD` of `{}`", path.to_string_lossy()))?;
+    Ok(commit.id().to_string())
+}
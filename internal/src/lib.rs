@@ -9,13 +9,20 @@ mod command;
 #[cfg(feature = "command")]
 pub use command::*;
 
+#[cfg(feature = "command")]
+mod watchdog;
+
+#[cfg(feature = "config")]
+pub mod config;
+
 pub mod env;
 
 #[cfg(feature = "examples")]
 pub mod examples;
 
-mod filename;
-pub use filename::{library_filename, parse_path_filename};
+pub mod lib_paths;
+
+pub mod library_filename;
 
 #[cfg(feature = "git")]
 mod git;
@@ -38,5 +45,10 @@ mod sed;
 #[cfg(feature = "sed")]
 pub use sed::find_and_replace;
 
+#[cfg(feature = "sources")]
+pub mod sources;
+
 #[cfg(feature = "testing")]
 pub mod testing;
+
+pub mod verbatim;
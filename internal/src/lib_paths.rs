@@ -0,0 +1,219 @@
+//! Encoding of the library paths that Dylint passes to the driver in `DYLINT_LIB_PATHS`
+//!
+//! Each path is percent-encoded, and the encoded paths are separated by commas. A byte that is an
+//! ASCII alphanumeric or one of `SAFE` is written as is. Any other byte, including a comma or a
+//! byte that is not valid UTF-8, is written as `%XX`. So the encoding is ASCII and round-trips any
+//! path, including one containing spaces, semicolons, or (on Unix) invalid UTF-8. On Windows, a
+//! path is converted to UTF-8 before being encoded, and an unpaired surrogate is written as
+//! `%uXXXX`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    ffi::OsString,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+const SAFE: &[u8] = b"-./:@\\_~";
+
+const SEPARATOR: char = ',';
+
+/// Encodes `paths` for `DYLINT_LIB_PATHS`.
+///
+/// # Examples
+///
+/// ```
+/// use dylint_internal::lib_paths::{decode, encode};
+/// use std::path::PathBuf;
+///
+/// let paths = [
+///     PathBuf::from("/home/First Last/libfoo@nightly.so"),
+///     PathBuf::from("/tmp/a;b,c/libbar@nightly.so"),
+/// ];
+/// let encoded = encode(&paths);
+/// assert_eq!(
+///     encoded,
+///     "/home/First%20Last/libfoo@nightly.so,/tmp/a%3Bb%2Cc/libbar@nightly.so"
+/// );
+/// assert_eq!(decode(&encoded).unwrap(), paths);
+/// ```
+#[must_use]
+pub fn encode<T: AsRef<Path>>(paths: impl IntoIterator<Item = T>) -> String {
+    paths
+        .into_iter()
+        .map(|path| encode_path(path.as_ref()))
+        .collect::<Vec<_>>()
+        .join(&SEPARATOR.to_string())
+}
+
+/// Decodes a value produced by [`encode`].
+pub fn decode(value: &str) -> Result<Vec<PathBuf>> {
+    if value.is_empty() {
+        return Ok(Vec::new());
+    }
+    value
+        .split(SEPARATOR)
+        .map(|encoded| {
+            decode_path(encoded).with_context(|| format!("Could not decode `{encoded}`"))
+        })
+        .collect()
+}
+
+fn encode_bytes(buf: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || SAFE.contains(&byte) {
+            buf.push(char::from(byte));
+        } else {
+            write!(buf, "%{byte:02X}").unwrap_or_default();
+        }
+    }
+}
+
+#[cfg(unix)]
+fn encode_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut buf = String::new();
+    encode_bytes(&mut buf, path.as_os_str().as_bytes());
+    buf
+}
+
+#[cfg(windows)]
+fn encode_path(path: &Path) -> String {
+    use std::os::windows::ffi::OsStrExt;
+    let mut buf = String::new();
+    for c in char::decode_utf16(path.as_os_str().encode_wide()) {
+        match c {
+            Ok(c) => encode_bytes(&mut buf, c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Err(error) => write!(buf, "%u{:04X}", error.unpaired_surrogate()).unwrap_or_default(),
+        }
+    }
+    buf
+}
+
+enum Unit {
+    Byte(u8),
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Surrogate(u16),
+}
+
+fn decode_units(encoded: &str) -> Result<Vec<Unit>> {
+    let mut units = Vec::new();
+    let mut iter = encoded.as_bytes().iter().copied();
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            units.push(Unit::Byte(byte));
+            continue;
+        }
+        let unit = if iter.clone().next() == Some(b'u') {
+            iter.next();
+            let hex = iter.by_ref().take(4).collect::<Vec<_>>();
+            Unit::Surrogate(parse_hex(&hex, 4)?)
+        } else {
+            let hex = iter.by_ref().take(2).collect::<Vec<_>>();
+            Unit::Byte(u8::try_from(parse_hex(&hex, 2)?)?)
+        };
+        units.push(unit);
+    }
+    Ok(units)
+}
+
+fn parse_hex(hex: &[u8], len: usize) -> Result<u16> {
+    if hex.len() != len {
+        bail!("Truncated escape sequence");
+    }
+    let hex = std::str::from_utf8(hex)?;
+    u16::from_str_radix(hex, 16).map_err(|_| anyhow!("Invalid escape sequence `{hex}`"))
+}
+
+#[cfg(unix)]
+fn decode_path(encoded: &str) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    let bytes = decode_units(encoded)?
+        .into_iter()
+        .map(|unit| match unit {
+            Unit::Byte(byte) => Ok(byte),
+            Unit::Surrogate(_) => Err(anyhow!("`%u` is valid only on Windows")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+#[cfg(windows)]
+fn decode_path(encoded: &str) -> Result<PathBuf> {
+    use std::os::windows::ffi::OsStringExt;
+    let mut wide = Vec::new();
+    let mut bytes = Vec::new();
+    for unit in decode_units(encoded)?
+        .into_iter()
+        .map(Some)
+        .chain(std::iter::once(None))
+    {
+        if let Some(Unit::Byte(byte)) = unit {
+            bytes.push(byte);
+            continue;
+        }
+        wide.extend(std::str::from_utf8(&bytes)?.encode_utf16());
+        bytes.clear();
+        if let Some(Unit::Surrogate(surrogate)) = unit {
+            wide.push(surrogate);
+        }
+    }
+    Ok(PathBuf::from(OsString::from_wide(&wide)))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn round_trip(paths: &[PathBuf]) {
+        let encoded = encode(paths);
+        assert!(encoded.is_ascii(), "{encoded}");
+        assert_eq!(decode(&encoded).unwrap(), paths);
+    }
+
+    #[test]
+    fn spaces_and_semicolons() {
+        round_trip(&[
+            PathBuf::from(r"C:\Users\First Last\lints\foo@nightly-x86_64-pc-windows-msvc.dll"),
+            PathBuf::from("/tmp/a;b/libbar@nightly.so"),
+            PathBuf::from("/tmp/c,d%20e/libbaz@nightly.so"),
+            PathBuf::from("/tmp/ünïcödé/libqux@nightly.so"),
+        ]);
+    }
+
+    #[test]
+    fn empty() {
+        round_trip(&[]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invalid_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+        let path = PathBuf::from(OsString::from_vec(
+            b"/tmp/\xff\xfe/libfoo@nightly.so".to_vec(),
+        ));
+        assert_eq!(encode(&[&path]), "/tmp/%FF%FE/libfoo@nightly.so");
+        round_trip(&[path]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unpaired_surrogate() {
+        use std::os::windows::ffi::OsStringExt;
+        let mut wide = "C:\\".encode_utf16().collect::<Vec<_>>();
+        wide.push(0xD800);
+        wide.extend("\\foo@nightly.dll".encode_utf16());
+        let path = PathBuf::from(OsString::from_wide(&wide));
+        assert_eq!(encode(&[&path]), r"C:\%uD800\foo@nightly.dll");
+        round_trip(&[path]);
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert!(decode("/tmp/%2").is_err());
+        assert!(decode("/tmp/%zz").is_err());
+    }
+}
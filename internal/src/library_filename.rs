@@ -0,0 +1,350 @@
+//! Composing and parsing the filenames of Dylint libraries
+//!
+//! A Dylint library's filename has the form:
+//!
+//! ```text
+//! DLL_PREFIX LIBRARY_NAME '@' TOOLCHAIN [ '@' TARGET ] DLL_SUFFIX
+//! ```
+//!
+//! `DLL_PREFIX` and `DLL_SUFFIX` are those of the platform the library is for, e.g., `lib` and
+//! `.so` on Linux, and the empty string and `.dll` on Windows. `TARGET` is present only if the
+//! library was built with `--target`. None of `LIBRARY_NAME`, `TOOLCHAIN`, and `TARGET` is empty
+//! or contains `@`. So a filename with an empty component or more than two `@`s is not that of a
+//! Dylint library. Note that a toolchain name can contain `-`s, e.g.,
+//! `nightly-2024-01-01-x86_64-unknown-linux-gnu`, so `@` is what separates the components.
+
+use std::{env::consts, path::Path};
+
+/// A platform's prefix and suffix for dynamic library filenames
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Affixes {
+    pub prefix: &'static str,
+    pub suffix: &'static str,
+}
+
+impl Affixes {
+    /// The host's affixes, i.e., [`std::env::consts::DLL_PREFIX`] and
+    /// [`std::env::consts::DLL_SUFFIX`]
+    pub const HOST: Self = Self {
+        prefix: consts::DLL_PREFIX,
+        suffix: consts::DLL_SUFFIX,
+    };
+
+    /// Returns the affixes of `target`'s dynamic libraries
+    #[must_use]
+    pub fn for_target(target: &str) -> Self {
+        let (prefix, suffix) = if target.contains("windows") {
+            ("", ".dll")
+        } else if target.contains("apple") {
+            ("lib", ".dylib")
+        } else {
+            ("lib", ".so")
+        };
+        Self { prefix, suffix }
+    }
+}
+
+/// Returns the filename of a Dylint library for the host. `target` should be `Some` only if the
+/// library was built with `--target`.
+///
+/// # Examples
+///
+/// ```
+/// use dylint_internal::library_filename::compose;
+///
+/// #[cfg(target_os = "linux")]
+/// assert_eq!(
+///     compose("foo", "stable-x86_64-unknown-linux-gnu", None),
+///     "libfoo@stable-x86_64-unknown-linux-gnu.so"
+/// );
+///
+/// #[cfg(target_os = "linux")]
+/// assert_eq!(
+///     compose(
+///         "foo",
+///         "stable-x86_64-unknown-linux-gnu",
+///         Some("aarch64-unknown-linux-gnu")
+///     ),
+///     "libfoo@stable-x86_64-unknown-linux-gnu@aarch64-unknown-linux-gnu.so"
+/// );
+///
+/// #[cfg(target_os = "macos")]
+/// assert_eq!(
+///     compose("foo", "stable-x86_64-apple-darwin", None),
+///     "libfoo@stable-x86_64-apple-darwin.dylib"
+/// );
+///
+/// #[cfg(target_os = "windows")]
+/// assert_eq!(
+///     compose("foo", "stable-x86_64-pc-windows-msvc", None),
+///     "foo@stable-x86_64-pc-windows-msvc.dll"
+/// );
+/// ```
+#[must_use]
+pub fn compose(lib_name: &str, toolchain: &str, target: Option<&str>) -> String {
+    compose_with(Affixes::HOST, lib_name, toolchain, target)
+}
+
+/// Returns the filename of a Dylint library for a platform with `affixes`
+// smoelius: Build a standard rlib, and the filename will use snake case. `compose_with`'s behavior
+// is consistent with that.
+#[must_use]
+pub fn compose_with(
+    affixes: Affixes,
+    lib_name: &str,
+    toolchain: &str,
+    target: Option<&str>,
+) -> String {
+    debug_assert!(
+        [Some(lib_name), Some(toolchain), target]
+            .into_iter()
+            .flatten()
+            .all(|component| !component.contains('@')),
+        "`@` in filename component"
+    );
+    let mut filename = format!(
+        "{}{}@{}",
+        affixes.prefix,
+        lib_name.replace('-', "_"),
+        toolchain
+    );
+    if let Some(target) = target {
+        filename.push('@');
+        filename.push_str(target);
+    }
+    filename.push_str(affixes.suffix);
+    filename
+}
+
+/// Parses the filename of a Dylint library for the host into a tuple of (name, toolchain, target),
+/// where target is `None` if the filename does not name one.
+///
+/// # Examples
+///
+/// ```
+/// use dylint_internal::library_filename::parse;
+///
+/// #[cfg(target_os = "linux")]
+/// assert_eq!(
+///     parse("libfoo@stable-x86_64-unknown-linux-gnu.so"),
+///     Some((
+///         String::from("foo"),
+///         String::from("stable-x86_64-unknown-linux-gnu"),
+///         None
+///     ))
+/// );
+///
+/// #[cfg(target_os = "linux")]
+/// assert_eq!(
+///     parse("libfoo@stable-x86_64-unknown-linux-gnu@aarch64-unknown-linux-gnu.so"),
+///     Some((
+///         String::from("foo"),
+///         String::from("stable-x86_64-unknown-linux-gnu"),
+///         Some(String::from("aarch64-unknown-linux-gnu"))
+///     ))
+/// );
+///
+/// #[cfg(target_os = "macos")]
+/// assert_eq!(
+///     parse("libfoo@stable-x86_64-apple-darwin.dylib"),
+///     Some((
+///         String::from("foo"),
+///         String::from("stable-x86_64-apple-darwin"),
+///         None
+///     ))
+/// );
+///
+/// #[cfg(target_os = "windows")]
+/// assert_eq!(
+///     parse("foo@stable-x86_64-pc-windows-msvc.dll"),
+///     Some((
+///         String::from("foo"),
+///         String::from("stable-x86_64-pc-windows-msvc"),
+///         None
+///     ))
+/// );
+/// ```
+#[must_use]
+pub fn parse(filename: &str) -> Option<(String, String, Option<String>)> {
+    parse_with(Affixes::HOST, filename)
+}
+
+/// Parses the filename of a Dylint library path, as [`parse`] does
+#[must_use]
+pub fn parse_path(path: &Path) -> Option<(String, String, Option<String>)> {
+    let filename = path.file_name()?;
+    parse(&filename.to_string_lossy())
+}
+
+/// Parses the filename of a Dylint library for a platform with `affixes`, as [`parse`] does
+#[must_use]
+pub fn parse_with(affixes: Affixes, filename: &str) -> Option<(String, String, Option<String>)> {
+    let file_stem = filename.strip_suffix(affixes.suffix)?;
+    let target_name = file_stem.strip_prefix(affixes.prefix)?;
+    let mut iter = target_name.split('@');
+    let lib_name = iter.next()?;
+    let toolchain = iter.next()?;
+    let target = iter.next();
+    if iter.next().is_some() || [Some(lib_name), Some(toolchain), target].contains(&Some("")) {
+        return None;
+    }
+    Some((
+        lib_name.to_owned(),
+        toolchain.to_owned(),
+        target.map(ToOwned::to_owned),
+    ))
+}
+
+/// Returns the library name of a host dynamic library whose filename names no toolchain, e.g.,
+/// `foo` for `libfoo.so` as written by the linker
+#[must_use]
+pub fn parse_plain_path(path: &Path) -> Option<String> {
+    let filename = path.file_name()?;
+    parse_plain_with(Affixes::HOST, &filename.to_string_lossy())
+}
+
+fn parse_plain_with(affixes: Affixes, filename: &str) -> Option<String> {
+    let file_stem = filename.strip_suffix(affixes.suffix)?;
+    let lib_name = file_stem.strip_prefix(affixes.prefix)?;
+    if lib_name.is_empty() || lib_name.contains('@') {
+        return None;
+    }
+    Some(lib_name.to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    const LIB_NAMES: &[&str] = &[
+        "a",
+        "foo",
+        "foo_bar",
+        "foo-bar",
+        "lib",
+        "libfoo",
+        "question_mark_in_expression",
+    ];
+
+    const TOOLCHAINS: &[&str] = &[
+        "stable",
+        "1.70.0-aarch64-apple-darwin",
+        "nightly-2024-01-01",
+        "nightly-2024-01-01-x86_64-unknown-linux-gnu",
+        "nightly-2024-01-01-x86_64-pc-windows-msvc",
+        "my-custom-toolchain",
+    ];
+
+    const TARGETS: &[Option<&str>] = &[
+        None,
+        Some("aarch64-unknown-linux-gnu"),
+        Some("x86_64-apple-darwin"),
+        Some("x86_64-pc-windows-msvc"),
+    ];
+
+    fn affixes() -> [Affixes; 4] {
+        [
+            Affixes::HOST,
+            Affixes::for_target("x86_64-unknown-linux-gnu"),
+            Affixes::for_target("aarch64-apple-darwin"),
+            Affixes::for_target("x86_64-pc-windows-msvc"),
+        ]
+    }
+
+    #[test]
+    fn compose_then_parse() {
+        for affixes in affixes() {
+            for &lib_name in LIB_NAMES {
+                for &toolchain in TOOLCHAINS {
+                    for &target in TARGETS {
+                        let filename = compose_with(affixes, lib_name, toolchain, target);
+                        assert_eq!(
+                            Some((
+                                lib_name.replace('-', "_"),
+                                toolchain.to_owned(),
+                                target.map(ToOwned::to_owned)
+                            )),
+                            parse_with(affixes, &filename),
+                            "{filename}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse_then_compose() {
+        for affixes in affixes() {
+            for &lib_name in LIB_NAMES {
+                for &toolchain in TOOLCHAINS {
+                    for &target in TARGETS {
+                        let filename = compose_with(affixes, lib_name, toolchain, target);
+                        let (lib_name, toolchain, target) = parse_with(affixes, &filename).unwrap();
+                        assert_eq!(
+                            filename,
+                            compose_with(affixes, &lib_name, &toolchain, target.as_deref())
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extra_at_signs() {
+        let affixes = Affixes::for_target("x86_64-unknown-linux-gnu");
+        for filename in [
+            "libfoo@stable@x86_64-unknown-linux-gnu@extra.so",
+            "libfoo@@stable.so",
+            "libfoo@stable@.so",
+            "lib@stable.so",
+            "lib@.so",
+            "libfoo@.so",
+            "libfoo.so",
+            "libfoo@stable.dll",
+        ] {
+            assert_eq!(None, parse_with(affixes, filename), "{filename}");
+        }
+        assert_eq!(None, parse_plain_with(affixes, "libfoo@stable.so"));
+    }
+
+    #[test]
+    fn empty_prefix() {
+        let affixes = Affixes::for_target("x86_64-pc-windows-msvc");
+        assert_eq!("", affixes.prefix);
+        assert_eq!(
+            Some(("foo".to_owned(), "stable".to_owned(), None)),
+            parse_with(affixes, "foo@stable.dll")
+        );
+        // smoelius: Without a prefix to strip, a leading `lib` is part of the library name.
+        assert_eq!(
+            Some(("libfoo".to_owned(), "stable".to_owned(), None)),
+            parse_with(affixes, "libfoo@stable.dll")
+        );
+        assert_eq!(Some("foo".to_owned()), parse_plain_with(affixes, "foo.dll"));
+        assert_eq!(None, parse_plain_with(affixes, ".dll"));
+    }
+
+    #[test]
+    fn affixes_for_targets() {
+        assert_eq!(
+            ("lib", ".so"),
+            affix_pair(Affixes::for_target("x86_64-unknown-linux-gnu"))
+        );
+        assert_eq!(
+            ("lib", ".dylib"),
+            affix_pair(Affixes::for_target("aarch64-apple-darwin"))
+        );
+        assert_eq!(
+            ("", ".dll"),
+            affix_pair(Affixes::for_target("x86_64-pc-windows-msvc"))
+        );
+    }
+
+    fn affix_pair(affixes: Affixes) -> (&'static str, &'static str) {
+        (affixes.prefix, affixes.suffix)
+    }
+}
@@ -7,16 +7,36 @@ use std::{
     path::Path,
 };
 
+#[cfg(unix)]
+use std::{
+    ffi::OsStr,
+    fs::{set_permissions, Permissions},
+};
+
 #[derive(RustEmbed)]
 #[folder = "template"]
 #[exclude = "Cargo.lock"]
 #[exclude = "target/*"]
 struct Template;
 
+#[derive(RustEmbed)]
+#[folder = "template_workspace"]
+#[exclude = "Cargo.lock"]
+#[exclude = "target/*"]
+struct WorkspaceTemplate;
+
 pub fn new_template(to: &Path) -> Result<()> {
-    for path in Template::iter() {
-        let embedded_file = Template::get(&path)
-            .ok_or_else(|| anyhow!("Could not get embedded file `{}`", path))?;
+    write_embedded_files::<Template>(to)
+}
+
+pub fn new_workspace_template(to: &Path) -> Result<()> {
+    write_embedded_files::<WorkspaceTemplate>(to)
+}
+
+fn write_embedded_files<T: RustEmbed>(to: &Path) -> Result<()> {
+    for path in T::iter() {
+        let embedded_file =
+            T::get(&path).ok_or_else(|| anyhow!("Could not get embedded file `{}`", path))?;
         let to_path = to.join(path.trim_end_matches('~'));
         let parent = to_path
             .parent()
@@ -31,6 +51,17 @@ pub fn new_template(to: &Path) -> Result<()> {
             .with_context(|| format!("Could not open `{}`", to_path.to_string_lossy()))?;
         file.write_all(&embedded_file.data)
             .with_context(|| format!("Could not write to {to_path:?}"))?;
+        // smoelius: Embedded files do not retain their permissions.
+        #[cfg(unix)]
+        if to_path.extension() == Some(OsStr::new("sh")) {
+            use std::os::unix::fs::PermissionsExt;
+            set_permissions(&to_path, Permissions::from_mode(0o755)).with_context(|| {
+                format!(
+                    "`set_permissions` failed for `{}`",
+                    to_path.to_string_lossy()
+                )
+            })?;
+        }
     }
 
     Ok(())
@@ -141,6 +172,63 @@ mod test {
         assert!(paths.is_empty(), "found {paths:#?}");
     }
 
+    #[test]
+    fn workspace_template_includes_only_whitelisted_paths() {
+        const PATHS: [&str; 12] = [
+            ".cargo/config.toml",
+            ".gitignore",
+            "Cargo.toml~",
+            "README.md",
+            "example_lint/Cargo.toml~",
+            "example_lint/README.md",
+            "example_lint/src/lib.rs",
+            "example_lint/ui/main.rs",
+            "example_lint/ui/main.stderr",
+            "rust-toolchain",
+            "scripts/test.sh",
+            "src/lib.rs",
+        ];
+
+        let mut paths_sorted = PATHS.to_vec();
+        paths_sorted.sort_unstable();
+        assert_eq!(paths_sorted, PATHS);
+
+        let paths = WorkspaceTemplate::iter()
+            .filter(|path| PATHS.binary_search(&&**path).is_err())
+            .collect::<Vec<_>>();
+
+        assert!(paths.is_empty(), "found {paths:#?}");
+    }
+
+    // smoelius: The workspace template should not drift from the single-library template.
+    #[test]
+    fn workspace_template_matches_template() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for (path, workspace_path) in [
+            (".cargo/config.toml", ".cargo/config.toml"),
+            ("Cargo.toml~", "example_lint/Cargo.toml~"),
+            ("rust-toolchain", "rust-toolchain"),
+        ] {
+            let contents = read_to_string(manifest_dir.join("template").join(path)).unwrap();
+            let workspace_contents =
+                read_to_string(manifest_dir.join("template_workspace").join(workspace_path))
+                    .unwrap();
+            if path == "Cargo.toml~" {
+                for line in contents
+                    .lines()
+                    .filter(|line| line.starts_with("clippy_utils") || line.starts_with("dylint_"))
+                {
+                    assert!(
+                        workspace_contents.lines().any(|other| other == line),
+                        "`{workspace_path}` lacks `{line}`"
+                    );
+                }
+            } else {
+                assert_eq!(contents, workspace_contents, "`{workspace_path}` differs");
+            }
+        }
+    }
+
     #[test]
     fn template_has_initial_version() {
         let contents =
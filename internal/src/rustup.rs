@@ -47,6 +47,45 @@ pub fn toolchain_path(path: &Path) -> Result<PathBuf> {
         .ok_or_else(|| anyhow!("Could not get ancestor"))
 }
 
+/// Returns the commit hash that `rustc -vV` reports for `toolchain`, or `None` if it reports none
+/// (e.g., for a locally built compiler)
+pub fn commit_hash(toolchain: &str) -> Result<Option<String>> {
+    let output = Command::new("rustup")
+        .sanitize_environment()
+        .args(["run", toolchain, "rustc", "-vV"])
+        .output()?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    Ok(parse_commit_hash(stdout))
+}
+
+/// Returns the toolchains that `rustup toolchain list` reports as installed
+pub fn installed_toolchains() -> Result<Vec<String>> {
+    let output = Command::new("rustup")
+        .sanitize_environment()
+        .args(["toolchain", "list"])
+        .output()?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    Ok(parse_toolchain_list(stdout))
+}
+
+// smoelius: Each line names a toolchain, possibly followed by annotations like `(default)`.
+fn parse_toolchain_list(toolchain_list: &str) -> Vec<String> {
+    toolchain_list
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn parse_commit_hash(verbose_version: &str) -> Option<String> {
+    verbose_version
+        .lines()
+        .find_map(|line| line.strip_prefix("commit-hash: "))
+        .map(str::trim)
+        .filter(|hash| *hash != "unknown")
+        .map(ToOwned::to_owned)
+}
+
 pub fn is_rustc<T: AsRef<OsStr> + ?Sized>(arg: &T) -> bool {
     Path::new(arg).file_stem() == Some(OsStr::new("rustc"))
 }
@@ -55,3 +94,40 @@ pub fn is_rustc<T: AsRef<OsStr> + ?Sized>(arg: &T) -> bool {
 fn rustc_is_rustc() {
     assert!(is_rustc("rustc"));
 }
+
+#[test]
+fn commit_hash_is_parsed() {
+    let verbose_version = "\
+rustc 1.72.0-nightly (5ea666864 2023-06-27)
+binary: rustc
+commit-hash: 5ea66686467d3ec5f8c81570e7f0f16ad8dd8cc3
+commit-date: 2023-06-27
+host: x86_64-unknown-linux-gnu
+release: 1.72.0-nightly
+LLVM version: 16.0.5
+";
+    assert_eq!(
+        Some("5ea66686467d3ec5f8c81570e7f0f16ad8dd8cc3"),
+        parse_commit_hash(verbose_version).as_deref()
+    );
+    assert_eq!(
+        None,
+        parse_commit_hash("rustc 1.72.0-dev\ncommit-hash: unknown\n")
+    );
+}
+
+#[test]
+fn toolchain_list_is_parsed() {
+    let toolchain_list = "\
+stable-x86_64-unknown-linux-gnu (default)
+nightly-2023-06-29-x86_64-unknown-linux-gnu (active)
+
+";
+    assert_eq!(
+        vec![
+            "stable-x86_64-unknown-linux-gnu",
+            "nightly-2023-06-29-x86_64-unknown-linux-gnu"
+        ],
+        parse_toolchain_list(toolchain_list)
+    );
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::{
+    ffi::OsStr,
+    fs::read_to_string,
+    path::{Component, Path, PathBuf},
+};
+use toml_edit::{Document, Item};
+use walkdir::WalkDir;
+
+const IGNORE_FILENAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// The source files of a package, i.e., the files beneath the package's root that are not build
+/// artifacts, editor backups, etc.
+///
+/// If the package's `Cargo.toml` file has a `package.include` key, a file is a source file if and
+/// only if it matches one of the key's patterns. Otherwise, a file is a source file unless it
+/// matches a pattern in the `package.exclude` key, or in a `.gitignore` or `.ignore` file. As with
+/// Cargo, the patterns use gitignore syntax. In either case, the package's `target` directory and
+/// any `.git` directory are excluded.
+#[derive(Debug)]
+pub struct Sources {
+    root: PathBuf,
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+    ancestors: Vec<PathBuf>,
+}
+
+impl Sources {
+    /// Reads the `package.include` and `package.exclude` keys of `root`'s `Cargo.toml` file, if
+    /// any. `root` need not be the root of a package, e.g., it could be the root of a workspace.
+    pub fn new(root: &Path) -> Result<Self> {
+        let (include, exclude) = manifest_patterns(root)?;
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(matcher(root, &include)?)
+        };
+        let exclude = matcher(root, &exclude)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            include,
+            exclude,
+            ancestors: repository_ancestors(root),
+        })
+    }
+
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns true if `path` is a source file, or a directory that could contain source files.
+    /// The ignore files are reread on each call, so the result reflects any changes to them.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+
+        if is_reserved(relative) {
+            return false;
+        }
+
+        let is_dir = path.is_dir();
+
+        // smoelius: Like Cargo, consider ignore files only when there is no `package.include` key.
+        if let Some(include) = &self.include {
+            return is_dir
+                || include
+                    .matched_path_or_any_parents(path, is_dir)
+                    .is_ignore();
+        }
+
+        if self
+            .exclude
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+        {
+            return false;
+        }
+
+        // smoelius: Ignore files in deeper directories take precedence.
+        let dirs = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .chain(self.ancestors.iter().map(PathBuf::as_path));
+        for dir in dirs {
+            let gitignore = ignore_files(dir);
+            let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+            if matched.is_ignore() {
+                return false;
+            }
+            if matched.is_whitelist() {
+                return true;
+            }
+        }
+
+        true
+    }
+
+    /// Returns the source files, sorted
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in WalkDir::new(&self.root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || self.contains(entry.path()))
+        {
+            let entry = entry.with_context(|| {
+                format!("`read_dir` failed for `{}`", self.root.to_string_lossy())
+            })?;
+            if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn is_reserved(relative: &Path) -> bool {
+    relative.components().next() == Some(Component::Normal(OsStr::new("target")))
+        || relative
+            .components()
+            .any(|component| component.as_os_str() == OsStr::new(".git"))
+}
+
+fn manifest_patterns(root: &Path) -> Result<(Vec<String>, Vec<String>)> {
+    let manifest_path = root.join("Cargo.toml");
+    if !manifest_path.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let contents = read_to_string(&manifest_path).with_context(|| {
+        format!(
+            "`read_to_string` failed for `{}`",
+            manifest_path.to_string_lossy()
+        )
+    })?;
+    let document = contents
+        .parse::<Document>()
+        .with_context(|| format!("Could not parse `{}`", manifest_path.to_string_lossy()))?;
+    let patterns = |key: &str| {
+        document
+            .get("package")
+            .and_then(|package| package.get(key))
+            .and_then(Item::as_array)
+            .map(|array| {
+                array
+                    .iter()
+                    .filter_map(|value| value.as_str().map(ToOwned::to_owned))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    Ok((patterns("include"), patterns("exclude")))
+}
+
+fn matcher(root: &Path, patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid pattern `{pattern}`"))?;
+    }
+    builder
+        .build()
+        .with_context(|| format!("Could not build matcher for `{}`", root.to_string_lossy()))
+}
+
+// smoelius: Like `git`, skip lines that cannot be parsed rather than fail.
+fn ignore_files(dir: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(dir);
+    for filename in IGNORE_FILENAMES {
+        let path = dir.join(filename);
+        if path.is_file() {
+            let _ = builder.add(path);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+// smoelius: The ignore files in the directories above `root`, up to the root of the enclosing git
+// repository, also apply, as they would for `git`. Outside of a repository, only the ignore files
+// at or beneath `root` apply.
+fn repository_ancestors(root: &Path) -> Vec<PathBuf> {
+    if root.join(".git").exists() {
+        return Vec::new();
+    }
+    let mut ancestors = Vec::new();
+    for dir in root.ancestors().skip(1) {
+        ancestors.push(dir.to_path_buf());
+        if dir.join(".git").exists() {
+            return ancestors;
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use std::fs::{create_dir_all, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn nested_gitignores() {
+        let tempdir = tempdir().unwrap();
+        populate(
+            tempdir.path(),
+            &[
+                (".gitignore", "*.bak\n"),
+                ("a.tmp", ""),
+                ("src/lib.rs", ""),
+                ("src/lib.rs.bak", ""),
+                ("sub/.gitignore", "*.tmp\n"),
+                ("sub/a.tmp", ""),
+                ("sub/b.rs", ""),
+                ("target/debug/libfoo.so", ""),
+            ],
+        );
+        assert_eq!(
+            [
+                ".gitignore",
+                "a.tmp",
+                "src/lib.rs",
+                "sub/.gitignore",
+                "sub/b.rs"
+            ],
+            relative_files(tempdir.path()).as_slice()
+        );
+    }
+
+    #[test]
+    fn negation() {
+        let tempdir = tempdir().unwrap();
+        populate(
+            tempdir.path(),
+            &[
+                (".gitignore", "*.log\n!keep.log\n*.txt\n"),
+                ("keep.log", ""),
+                ("other.log", ""),
+                ("notes.txt", ""),
+                ("sub/.ignore", "!notes.txt\n"),
+                ("sub/notes.txt", ""),
+                ("sub/other.txt", ""),
+            ],
+        );
+        assert_eq!(
+            [".gitignore", "keep.log", "sub/.ignore", "sub/notes.txt"],
+            relative_files(tempdir.path()).as_slice()
+        );
+    }
+
+    #[test]
+    fn exclude() {
+        let tempdir = tempdir().unwrap();
+        populate(
+            tempdir.path(),
+            &[
+                ("Cargo.toml", "[package]\nexclude = [\"/tests\"]\n"),
+                ("src/lib.rs", ""),
+                ("tests/test.rs", ""),
+            ],
+        );
+        assert_eq!(
+            ["Cargo.toml", "src/lib.rs"],
+            relative_files(tempdir.path()).as_slice()
+        );
+    }
+
+    #[test]
+    fn include_wins_over_gitignore() {
+        let tempdir = tempdir().unwrap();
+        populate(
+            tempdir.path(),
+            &[
+                (".gitignore", "generated.rs\n"),
+                (
+                    "Cargo.toml",
+                    "[package]\ninclude = [\"/Cargo.toml\", \"/generated.rs\", \"/src\"]\n\
+                     exclude = [\"/src\"]\n",
+                ),
+                ("README.md", ""),
+                ("generated.rs", ""),
+                ("src/lib.rs", ""),
+                ("target/debug/libfoo.so", ""),
+            ],
+        );
+        assert_eq!(
+            ["Cargo.toml", "generated.rs", "src/lib.rs"],
+            relative_files(tempdir.path()).as_slice()
+        );
+    }
+
+    fn populate(root: &Path, files: &[(&str, &str)]) {
+        for (path, contents) in files {
+            let path = root.join(path);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            write(path, contents).unwrap();
+        }
+    }
+
+    fn relative_files(root: &Path) -> Vec<String> {
+        Sources::new(root)
+            .unwrap()
+            .files()
+            .unwrap()
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+}
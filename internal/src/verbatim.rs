@@ -0,0 +1,263 @@
+//! Helpers for Windows verbatim paths, i.e., paths beginning with `\\?\`
+//!
+//! [`std::fs::canonicalize`] returns verbatim paths on Windows. Cargo and the Windows file APIs
+//! accept them, but many other tools (e.g., linkers) do not. Conversely, a path longer than
+//! `MAX_PATH` can be passed to another tool only in verbatim form, unless long paths are enabled
+//! system-wide. On other platforms, the functions in this module leave paths unchanged.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The maximum length of a Windows path without a verbatim prefix, including the terminating nul
+pub const MAX_PATH: usize = 260;
+
+// smoelius: Cargo creates paths well over 100 characters long beneath a target directory, e.g.,
+// `release\build\<package>-<hash>\out\...`. A directory whose path is longer than this limit is
+// given a verbatim prefix by `long` so that those paths do not exceed `MAX_PATH`.
+#[cfg(windows)]
+const HEADROOM: usize = 120;
+
+/// Like [`std::fs::canonicalize`], but the result is [`simplified`]
+pub fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    std::fs::canonicalize(path).map(|path| simplified(&path))
+}
+
+/// Returns `path` without its verbatim prefix, if the prefix can be removed without changing what
+/// `path` refers to; otherwise, returns `path` unchanged
+#[must_use]
+pub fn simplified(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(path_buf) = windows::strip_prefix(path) {
+        return path_buf;
+    }
+
+    path.to_path_buf()
+}
+
+/// Returns `path` with a verbatim prefix, if `path` is absolute and long enough that paths beneath
+/// it could exceed [`MAX_PATH`]; otherwise, returns `path` unchanged
+///
+/// Use this for directories passed to tools that write beneath them (e.g., `cargo --target-dir`),
+/// but not for paths passed to tools that cannot handle verbatim paths (e.g., linkers).
+#[must_use]
+pub fn long(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if path.as_os_str().len() + HEADROOM >= MAX_PATH {
+        if let Some(path_buf) = windows::add_prefix(path) {
+            return path_buf;
+        }
+    }
+
+    path.to_path_buf()
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::MAX_PATH;
+    use std::{
+        ffi::{OsStr, OsString},
+        path::{Component, Path, PathBuf, Prefix},
+    };
+
+    const RESERVED_NAMES: [&str; 22] = [
+        "AUX", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "CON",
+        "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9", "NUL", "PRN",
+    ];
+
+    pub fn strip_prefix(path: &Path) -> Option<PathBuf> {
+        let mut components = path.components();
+
+        let mut plain = match components.next()? {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::VerbatimDisk(disk) => format!("{}:", char::from(disk)),
+                Prefix::VerbatimUNC(server, share) => {
+                    format!(r"\\{}\{}", server.to_str()?, share.to_str()?)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // smoelius: Components of a verbatim path are split on `\` only, and `.` and `..` are not
+        // interpreted. So a component that the Win32 path normalization would alter means the
+        // prefix cannot be removed.
+        let mut is_empty = true;
+        for component in components {
+            match component {
+                Component::RootDir => {}
+                Component::Normal(name) => {
+                    if !is_plain_name(name) {
+                        return None;
+                    }
+                    plain.push('\\');
+                    plain.push_str(name.to_str()?);
+                    is_empty = false;
+                }
+                Component::Prefix(_) | Component::CurDir | Component::ParentDir => return None,
+            }
+        }
+        if is_empty {
+            plain.push('\\');
+        }
+
+        if plain.len() >= MAX_PATH {
+            return None;
+        }
+
+        Some(plain.into())
+    }
+
+    pub fn add_prefix(path: &Path) -> Option<PathBuf> {
+        let mut components = path.components();
+
+        let verbatim = match components.next()? {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(disk) if path.has_root() => format!(r"\\?\{}:", char::from(disk)),
+                Prefix::UNC(server, share) => {
+                    format!(r"\\?\UNC\{}\{}", server.to_str()?, share.to_str()?)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // smoelius: Verbatim paths are not normalized, so `.` and `..` must be resolved here, and
+        // every separator must be `\`. This matches what the Win32 path normalization would do.
+        let mut names = Vec::<&OsStr>::new();
+        for component in components {
+            match component {
+                Component::RootDir | Component::CurDir => {}
+                Component::Normal(name) => names.push(name),
+                Component::ParentDir => {
+                    names.pop();
+                }
+                Component::Prefix(_) => return None,
+            }
+        }
+
+        let mut verbatim = OsString::from(verbatim);
+        if names.is_empty() {
+            verbatim.push(r"\");
+        }
+        for name in names {
+            verbatim.push(r"\");
+            verbatim.push(name);
+        }
+
+        Some(verbatim.into())
+    }
+
+    fn is_plain_name(name: &OsStr) -> bool {
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => return false,
+        };
+        if name.is_empty()
+            || name == "."
+            || name == ".."
+            || name.ends_with(['.', ' '])
+            || name.contains('/')
+        {
+            return false;
+        }
+        let stem = name.split('.').next().unwrap_or(name).trim_end();
+        !RESERVED_NAMES
+            .iter()
+            .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn unchanged() {
+        let path = Path::new("/a").join("b".repeat(MAX_PATH));
+        assert_eq!(path, simplified(&path));
+        assert_eq!(path, long(&path));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn simplified_disk() {
+        assert_eq!(
+            Path::new(r"C:\Users\dylint\target"),
+            simplified(Path::new(r"\\?\C:\Users\dylint\target"))
+        );
+        assert_eq!(Path::new(r"C:\"), simplified(Path::new(r"\\?\C:\")));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn simplified_unc() {
+        assert_eq!(
+            Path::new(r"\\server\share\dylint"),
+            simplified(Path::new(r"\\?\UNC\server\share\dylint"))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn simplified_keeps_prefix() {
+        for path in [
+            r"\\?\C:\Users\..\dylint".to_owned(),
+            r"\\?\C:\Users\dylint.".to_owned(),
+            r"\\?\C:\Users\dylint \target".to_owned(),
+            r"\\?\C:\Users\nul.txt".to_owned(),
+            r"\\?\C:\Users\a/b".to_owned(),
+            r"\\?\GLOBALROOT\Device\HarddiskVolume1".to_owned(),
+            format!(r"\\?\C:\{}", "a".repeat(MAX_PATH)),
+        ] {
+            assert_eq!(Path::new(&path), simplified(Path::new(&path)), "{path}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_short() {
+        for path in [
+            r"C:\Users\dylint\target",
+            r"\\server\share\target",
+            r"target",
+        ] {
+            assert_eq!(Path::new(path), long(Path::new(path)), "{path}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_disk() {
+        let name = "a".repeat(MAX_PATH);
+        let path = Path::new(r"C:\Users\.\dylint\..")
+            .join(&name)
+            .join("dylint/target");
+        assert_eq!(
+            PathBuf::from(format!(r"\\?\C:\Users\{name}\dylint\target")),
+            long(&path)
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_unc() {
+        let name = "a".repeat(MAX_PATH);
+        let path = Path::new(r"\\server\share").join(&name);
+        assert_eq!(
+            PathBuf::from(format!(r"\\?\UNC\server\share\{name}")),
+            long(&path)
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_round_trip() {
+        let path = PathBuf::from(format!(r"C:\Users\{}", "a".repeat(MAX_PATH - 20)));
+        let verbatim = long(&path);
+        assert_ne!(path, verbatim);
+        assert_eq!(path, simplified(&verbatim));
+    }
+}
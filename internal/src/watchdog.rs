@@ -0,0 +1,136 @@
+use std::{
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    thread::{spawn, JoinHandle},
+    time::Duration,
+};
+
+// smoelius: An expired command is first interrupted, as if by ctrl-C. This gives Cargo the chance
+// to release its target directory locks. Processes still running after `GRACE_PERIOD` are killed.
+const GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Interrupts a child process that runs longer than its timeout
+pub(crate) struct Watchdog {
+    tx: Sender<()>,
+    handle: JoinHandle<bool>,
+    timeout: Duration,
+}
+
+impl Watchdog {
+    pub(crate) fn start(pid: u32, timeout: Duration) -> Self {
+        let (tx, rx) = channel();
+
+        #[cfg(unix)]
+        unix::forward_interrupts(pid);
+
+        let handle = spawn(move || {
+            if !matches!(rx.recv_timeout(timeout), Err(RecvTimeoutError::Timeout)) {
+                return false;
+            }
+            interrupt(pid);
+            if matches!(
+                rx.recv_timeout(GRACE_PERIOD),
+                Err(RecvTimeoutError::Timeout)
+            ) {
+                kill(pid);
+            }
+            true
+        });
+
+        Self {
+            tx,
+            handle,
+            timeout,
+        }
+    }
+
+    /// Stops the watchdog, which must be called after the child has exited. Returns the timeout if
+    /// the child was interrupted.
+    pub(crate) fn stop(self) -> Option<Duration> {
+        #[cfg(unix)]
+        unix::stop_forwarding();
+
+        let _ = self.tx.send(());
+        let timed_out = self.handle.join().unwrap_or(true);
+        if timed_out {
+            Some(self.timeout)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn interrupt(pid: u32) {
+    unix::signal_group(pid, libc::SIGINT);
+}
+
+#[cfg(unix)]
+fn kill(pid: u32) {
+    unix::signal_group(pid, libc::SIGKILL);
+}
+
+// smoelius: Windows has no process groups or interrupts that can be sent to a background process.
+// So an expired command and its descendants are killed immediately.
+#[cfg(not(unix))]
+fn interrupt(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .output();
+}
+
+#[cfg(not(unix))]
+fn kill(_pid: u32) {}
+
+#[cfg(unix)]
+mod unix {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    // smoelius: A child in its own process group does not receive the interrupts and terminations
+    // sent to Dylint's process group, e.g., by ctrl-C or by a CI runner. While a child runs, such
+    // signals are forwarded to its group, and then Dylint exits as it otherwise would have.
+    static CHILD_GROUP: AtomicI32 = AtomicI32::new(0);
+
+    const FORWARDED: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+    extern "C" fn forward(signal: libc::c_int) {
+        let pgid = CHILD_GROUP.load(Ordering::SeqCst);
+        unsafe {
+            if pgid != 0 {
+                libc::kill(-pgid, signal);
+            }
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+
+    pub(super) fn forward_interrupts(pid: u32) {
+        CHILD_GROUP.store(to_pgid(pid), Ordering::SeqCst);
+        for signal in FORWARDED {
+            unsafe {
+                libc::signal(signal, forward as libc::sighandler_t);
+            }
+        }
+    }
+
+    pub(super) fn stop_forwarding() {
+        for signal in FORWARDED {
+            unsafe {
+                libc::signal(signal, libc::SIG_DFL);
+            }
+        }
+        CHILD_GROUP.store(0, Ordering::SeqCst);
+    }
+
+    pub(super) fn signal_group(pid: u32, signal: libc::c_int) {
+        let pgid = to_pgid(pid);
+        if pgid != 0 {
+            unsafe {
+                libc::kill(-pgid, signal);
+            }
+        }
+    }
+
+    fn to_pgid(pid: u32) -> i32 {
+        i32::try_from(pid).unwrap_or_default()
+    }
+}
@@ -0,0 +1,13 @@
+#! /bin/bash
+
+# smoelius: Each package is tested separately. Testing them together (e.g., with `--workspace`)
+# would enable each constituent's `constituent` feature, and the constituents' own tests would then
+# fail to find their libraries.
+
+set -euo pipefail
+
+cd "$(dirname "$0")"/..
+
+for MANIFEST_PATH in Cargo.toml */Cargo.toml; do
+    cargo test --manifest-path "$MANIFEST_PATH" "$@"
+done
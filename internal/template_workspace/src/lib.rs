@@ -0,0 +1,15 @@
+#![feature(rustc_private)]
+
+// smoelius: Each constituent library is a dependency with its `constituent` feature enabled. To
+// add a lint to this library, create a package alongside `example_lint`, add it to the workspace
+// and to the dependencies above, and add its name below.
+
+dylint_linting::combine!(example_lint);
+
+#[test]
+fn ui_example_lint() {
+    dylint_testing::ui_test(
+        env!("CARGO_PKG_NAME"),
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("example_lint/ui"),
+    );
+}
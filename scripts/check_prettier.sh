@@ -20,8 +20,8 @@ if ! git diff --exit-code; then
     exit 1
 fi
 
-prettier --write 'examples/**/*.md' 'internal/template/**/*.md' &&
+prettier --write 'examples/**/*.md' 'internal/template/**/*.md' 'internal/template_workspace/example_lint/*.md' &&
     git diff --ignore-blank-lines | (! grep .) &&
-    git checkout examples internal/template
+    git checkout examples internal/template internal/template_workspace/example_lint
 
 scripts/unquote_yaml_strings.sh && git diff --exit-code
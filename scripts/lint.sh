@@ -24,6 +24,21 @@ EXAMPLE_DIRS="$(find examples -mindepth 2 -maxdepth 2 -type d)"
 # toolchain than the other examples.
 EXAMPLE_DIRS="$(echo "$EXAMPLE_DIRS" | sed 's,\<examples/testing/straggler\>[[:space:]]*,,')"
 
+# smoelius: Remove `two_lints`, as it is used only for testing purposes. Its lints fire on every
+# integer and string literal.
+EXAMPLE_DIRS="$(echo "$EXAMPLE_DIRS" | sed 's,\<examples/testing/two_lints\>[[:space:]]*,,')"
+
+# smoelius: Remove `combined` for the same reason. Its lints fire on every `bool` and `char` literal.
+EXAMPLE_DIRS="$(echo "$EXAMPLE_DIRS" | sed 's,\<examples/testing/combined\>[[:space:]]*,,')"
+
+# smoelius: Remove `early_lint` for the same reason. Its lint fires on every parenthesized
+# expression.
+EXAMPLE_DIRS="$(echo "$EXAMPLE_DIRS" | sed 's,\<examples/testing/early_lint\>[[:space:]]*,,')"
+
+# smoelius: Remove `excluded_paths` for the same reason. Its lint fires on every function outside of
+# excluded paths and crates.
+EXAMPLE_DIRS="$(echo "$EXAMPLE_DIRS" | sed 's,\<examples/testing/excluded_paths\>[[:space:]]*,,')"
+
 DIRS=". driver utils/linting $EXAMPLE_DIRS"
 
 EXAMPLES="$(echo "$EXAMPLE_DIRS" | xargs -n 1 basename | tr '\n' ' ')"
@@ -46,7 +46,7 @@ mv "$TMP" README.md
 
 prettier --write README.md
 
-for EXAMPLE in */* ../internal/template; do
+for EXAMPLE in */* ../internal/template ../internal/template_workspace/example_lint; do
     if [[ ! -d "$EXAMPLE" ]]; then
         continue
     fi
@@ -32,8 +32,10 @@ find . -name Cargo.toml -exec sed -i "/^dylint/{
 s/^\(.*\)\<version = \"[^\"]*\"\(.*\)$/\1$REQ\2/
 }" {} \;
 
-# smoelius: `template` must be handled specially because it does not use the `version = "..."`
-# syntax.
-sed -i "s/^\(dylint_[^ ]*\) = \"[^\"]*\"$/\1 = \"$1\"/" internal/template/Cargo.toml~
+# smoelius: The templates must be handled specially because they do not use the
+# `version = "..."` syntax.
+for MANIFEST in internal/template/Cargo.toml~ internal/template_workspace/Cargo.toml~ internal/template_workspace/example_lint/Cargo.toml~; do
+    sed -i "s/^\(dylint_[^ ]*\) = \"[^\"]*\"$/\1 = \"$1\"/" "$MANIFEST"
+done
 
 scripts/update_lockfiles.sh
@@ -66,6 +66,11 @@ for EXAMPLE in examples/*/* internal/template; do
     fi
 done
 
+# smoelius: `template_workspace` uses the same toolchain and `clippy_utils` revision as `template`.
+cp internal/template/rust-toolchain internal/template_workspace/rust-toolchain
+REV="$(sed -n 's/^clippy_utils\>.*\(\<\(rev\|tag\) = "[^"]*"\).*$/\1/;T;p' internal/template/Cargo.toml~)"
+sed -i "s/^\(clippy_utils\>.*\)\<\(rev\|tag\) = \"[^\"]*\"\(.*\)$/\1$REV\3/" internal/template_workspace/example_lint/Cargo.toml~
+
 if git diff --exit-code; then
     exit 0
 fi
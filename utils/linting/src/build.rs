@@ -0,0 +1,162 @@
+//! Build script helpers for libraries that must compile against more than one nightly toolchain
+
+use std::{env::var_os, process::Command};
+
+/// The `rustc` API changes for which [`emit_rustc_version_cfgs`] emits named cfgs
+///
+/// Each entry is a cfg name and the date of the commit that made the change. A toolchain whose
+/// commit date is on or after that date has the change.
+pub const KNOWN_CHANGES: &[(&str, &str)] = &[
+    // smoelius: Relevant PR and merge commit:
+    // - https://github.com/rust-lang/rust/pull/98975
+    // - https://github.com/rust-lang/rust/commit/0ed9c64c3e63acac9bd77abce62501696c390450
+    ("rustc_unstable_opts", "2022-07-14"),
+    // smoelius: Relevant PR and merge commit:
+    // - https://github.com/rust-lang/rust/pull/101501
+    // - https://github.com/rust-lang/rust/commit/87788097b776f8e3662f76627944230684b671bd
+    ("rustc_late_pass_takes_tcx", "2022-09-08"),
+    // smoelius: Relevant PR and merge commit:
+    // - https://github.com/rust-lang/rust/pull/106810
+    // - https://github.com/rust-lang/rust/commit/65d2f2a5f9c323c88d1068e8e90d0b47a20d491c
+    ("rustc_local_crate_source_file_method", "2023-01-19"),
+    ("rustc_early_error_handler", "2023-06-28"),
+];
+
+/// Probes the `rustc` that Cargo uses to build the library and emits cfgs describing it. Call this
+/// function from the library's build script.
+///
+/// The following cfgs are emitted:
+///
+/// - `rustc_ge_1_N` for each `N` up to and including the minor version of `rustc`. A nightly
+///   toolchain counts as the version it reports, e.g., `1.74.0-nightly` counts as 1.74.
+/// - each name in [`KNOWN_CHANGES`] whose change the toolchain includes.
+///
+/// # Panics
+///
+/// Panics if `rustc` cannot be run or its output cannot be parsed.
+pub fn emit_rustc_version_cfgs() {
+    for cfg in probe_rustc_version_cfgs() {
+        println!("cargo:rustc-cfg={cfg}");
+    }
+}
+
+fn probe_rustc_version_cfgs() -> Vec<String> {
+    let rustc = var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(&rustc)
+        .arg("-vV")
+        .output()
+        .unwrap_or_else(|error| panic!("Could not run `{}`: {error}", rustc.to_string_lossy()));
+    assert!(
+        output.status.success(),
+        "`{} -vV` failed: {}",
+        rustc.to_string_lossy(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let verbose_version = String::from_utf8_lossy(&output.stdout);
+    rustc_version_cfgs(&verbose_version).unwrap_or_else(|| {
+        panic!(
+            "Could not parse `{} -vV` output: {verbose_version}",
+            rustc.to_string_lossy()
+        )
+    })
+}
+
+/// Returns the cfgs described in [`emit_rustc_version_cfgs`] for the output of `rustc -vV`, or
+/// `None` if the output cannot be parsed
+#[must_use]
+pub fn rustc_version_cfgs(verbose_version: &str) -> Option<Vec<String>> {
+    let release = field(verbose_version, "release")?;
+    let mut parts = release.split(['.', '-']);
+    let major = parts.next()?.parse::<u64>().ok()?;
+    let minor = parts.next()?.parse::<u64>().ok()?;
+    if major != 1 {
+        return None;
+    }
+
+    let mut cfgs = (0..=minor)
+        .map(|minor| format!("rustc_ge_1_{minor}"))
+        .collect::<Vec<_>>();
+
+    // smoelius: A toolchain built from source may report its commit date as `unknown`. In that
+    // case, no named cfgs are emitted.
+    if let Some(commit_date) = field(verbose_version, "commit-date") {
+        if is_date(commit_date) {
+            cfgs.extend(
+                KNOWN_CHANGES
+                    .iter()
+                    .filter(|&&(_, date)| commit_date >= date)
+                    .map(|&(name, _)| name.to_owned()),
+            );
+        }
+    }
+
+    Some(cfgs)
+}
+
+fn field<'a>(verbose_version: &'a str, key: &str) -> Option<&'a str> {
+    verbose_version.lines().find_map(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(str::trim)
+    })
+}
+
+fn is_date(s: &str) -> bool {
+    s.len() == 10
+        && s.char_indices().all(|(i, c)| {
+            if i == 4 || i == 7 {
+                c == '-'
+            } else {
+                c.is_ascii_digit()
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NIGHTLY: &str = "rustc 1.72.0-nightly (f7ca9df69 2023-06-24)
+binary: rustc
+commit-hash: f7ca9df69549470541fbf542f87a03eb9ed024b6
+commit-date: 2023-06-24
+host: x86_64-unknown-linux-gnu
+release: 1.72.0-nightly
+LLVM version: 16.0.5
+";
+
+    #[test]
+    fn nightly() {
+        let cfgs = rustc_version_cfgs(NIGHTLY).unwrap();
+        assert!(cfgs.contains(&"rustc_ge_1_0".to_owned()));
+        assert!(cfgs.contains(&"rustc_ge_1_72".to_owned()));
+        assert!(!cfgs.contains(&"rustc_ge_1_73".to_owned()));
+        assert!(cfgs.contains(&"rustc_local_crate_source_file_method".to_owned()));
+        assert!(!cfgs.contains(&"rustc_early_error_handler".to_owned()));
+    }
+
+    // smoelius: The library's toolchain is the latest nightly, which includes every known change.
+    #[test]
+    fn current_toolchain() {
+        let cfgs = probe_rustc_version_cfgs();
+        assert!(cfgs.contains(&"rustc_ge_1_72".to_owned()));
+        assert!(KNOWN_CHANGES
+            .iter()
+            .all(|(name, _)| cfgs.contains(&(*name).to_owned())));
+    }
+
+    #[test]
+    fn unknown_commit_date() {
+        let verbose_version = NIGHTLY.replace("2023-06-24\nhost", "unknown\nhost");
+        let cfgs = rustc_version_cfgs(&verbose_version).unwrap();
+        assert!(cfgs.contains(&"rustc_ge_1_72".to_owned()));
+        assert!(KNOWN_CHANGES
+            .iter()
+            .all(|(name, _)| !cfgs.contains(&(*name).to_owned())));
+    }
+
+    #[test]
+    fn unparsable() {
+        assert!(rustc_version_cfgs("rustc 1.72.0").is_none());
+    }
+}
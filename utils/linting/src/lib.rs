@@ -6,7 +6,11 @@
 //! - [`dylint_library!`]
 //! - [`declare_late_lint!`, `declare_early_lint!`, `declare_pre_expansion_lint!`]
 //! - [`impl_late_lint!`, `impl_early_lint!`, `impl_pre_expansion_lint!`]
+//! - [Constituent libraries]
+//! - [Lint metadata]
 //! - [Configurable libraries]
+//! - [Excluding paths and crates]
+//! - [Compiling against multiple toolchains]
 //!
 //! # `dylint_library!`
 //!
@@ -22,8 +26,19 @@
 //!         .unwrap()
 //!         .into_raw()
 //! }
+//!
+//! #[no_mangle]
+//! pub extern "C" fn dylint_metadata() -> *mut std::os::raw::c_char {
+//!     std::ffi::CString::new($crate::__metadata_json(&[]))
+//!         .unwrap()
+//!         .into_raw()
+//! }
 //! ```
 //!
+//! `dylint_metadata` returns the library's [lint metadata] as JSON. `dylint_library!()` exports an
+//! empty list. A library can export its own list with `dylint_library!(metadata = expr)`, where
+//! `expr` is a `Vec<LintMetadata>`.
+//!
 //! If your library uses the `dylint_library!` macro and the [`dylint-link`] tool, then all you
 //! should have to do is implement the [`register_lints`] function. See the [examples] in this
 //! repository.
@@ -77,7 +92,67 @@
 //! ```
 //!
 //! An example use of `impl_pre_expansion_lint!` can be found in [`env_cargo_path`] in this
-//! repository.
+//! repository. An example use of `impl_early_lint!` can be found in [`early_lint`].
+//!
+//! # Constituent libraries
+//!
+//! A library built with `declare_late_lint!`, etc. can also be a constituent of a larger library.
+//! To allow this, give the library `crate-type = ["cdylib", "rlib"]` and a `constituent` feature:
+//!
+//! ```toml
+//! [features]
+//! constituent = []
+//! ```
+//!
+//! When the `constituent` feature is enabled, the above macros omit `dylint_library!` and do not
+//! mark `register_lints` as `#[no_mangle]`. The larger library can then depend upon the
+//! constituent (with `features = ["constituent"]`) and use [`combine!`] to call the constituent's
+//! `register_lints` from its own:
+//!
+//! ```rust,ignore
+//! dylint_linting::combine!(first_constituent, second_constituent);
+//! ```
+//!
+//! The above expands to:
+//!
+//! ```rust,ignore
+//! dylint_linting::dylint_library!();
+//!
+//! #[no_mangle]
+//! pub fn register_lints(sess: &rustc_session::Session, lint_store: &mut rustc_lint::LintStore) {
+//!     first_constituent::register_lints(sess, lint_store);
+//!     second_constituent::register_lints(sess, lint_store);
+//! }
+//! ```
+//!
+//! An example can be found in [`combined`] in this repository.
+//!
+//! `cargo dylint new --workspace` creates a workspace with this structure.
+//!
+//! `combine!` also exports the constituents' [lint metadata]. So each constituent must define a
+//! `lint_metadata` function, as `declare_late_lint!`, etc. do.
+//!
+//! # Lint metadata
+//!
+//! `declare_late_lint!`, etc. accept optional trailing arguments that describe the lint for tools,
+//! e.g., `cargo dylint list --json`:
+//!
+//! ```rust,ignore
+//! dylint_linting::declare_late_lint! {
+//!     /// ...
+//!     pub NAME,
+//!     Warn,
+//!     "description",
+//!     description = "A longer description",
+//!     bad_example = "let x = ...;",
+//!     good_example = "let y = ...;",
+//!     applicability = "MachineApplicable"
+//! }
+//! ```
+//!
+//! Each argument is optional, and they can appear in any order. The macros store the arguments in
+//! a [`LintMetadata`] returned by a generated `lint_metadata` function, which `dylint_library!`
+//! exports as JSON.
 //!
 //! # Configurable libraries
 //!
@@ -143,29 +218,114 @@
 //! }
 //! ```
 //!
+//! A key in a library's table that the library's configuration type does not use (e.g., a
+//! misspelled key) causes a warning naming the library and the key.
+//!
+//! When Dylint checks a workspace, it reads the workspace's `dylint.toml` file and passes its
+//! contents to the driver in the `DYLINT_TOML` environment variable. Otherwise, e.g., when a
+//! library is tested with [`dylint_testing`], the file is located by asking Cargo for the
+//! workspace's root. In either case, the file is read and parsed once per process.
+//!
+//! A `dylint.toml` file that begins with `extends = "PATH"` is merged with the file at `PATH`,
+//! which is resolved relative to the extending file. Libraries see only the merged configuration.
+//!
 //! Additional documentation on `config_or_default`, etc. can be found on [docs.rs].
 //!
+//! # Excluding paths and crates
+//!
+//! Lints often should not fire in generated code. Rather than each library providing its own
+//! configuration for this, a lint can call [`is_excluded`], which consults the `[global]` table of
+//! the workspace's `dylint.toml` file, e.g.:
+//!
+//! ```toml
+//! [global]
+//! exclude-paths = ["src/generated/**"]
+//! exclude-crates = ["*_bindings"]
+//! ```
+//!
+//! `is_excluded` returns true for a span in a file matching one of the `exclude-paths` globs, and
+//! for every span of a crate whose name matches one of the `exclude-crates` globs. A lint typically
+//! calls it before doing any other work, e.g.:
+//!
+//! ```rust,ignore
+//! impl EarlyLintPass for LintName {
+//!     fn check_item(&mut self, cx: &EarlyContext, item: &Item) {
+//!         if dylint_linting::is_excluded(cx.sess(), item.span) {
+//!             return;
+//!         }
+//!         ...
+//!     }
+//! }
+//! ```
+//!
+//! The functions `is_excluded` is built on, [`is_in_allowed_path`] and [`crate_matches`], can be
+//! used with other globs, e.g., ones from a library's own configuration. For a concrete example,
+//! see the [`excluded_paths`] library in this repository.
+//!
+//! # Compiling against multiple toolchains
+//!
+//! The `rustc` APIs that libraries use change frequently. A library that must compile against more
+//! than one nightly toolchain can call [`build::emit_rustc_version_cfgs`] from its build script:
+//!
+//! ```rust,ignore
+//! fn main() {
+//!     dylint_linting::build::emit_rustc_version_cfgs();
+//! }
+//! ```
+//!
+//! To do so, the library must list `dylint_linting` under `[build-dependencies]` as well as under
+//! `[dependencies]`. The function emits `rustc_ge_1_N` cfgs describing the toolchain's version,
+//! e.g., `rustc_ge_1_72` for a 1.72 nightly. It also emits a named cfg for each API change in
+//! [`build::KNOWN_CHANGES`] that the toolchain includes. The library can then choose code based on
+//! those cfgs, e.g.:
+//!
+//! ```rust,ignore
+//! #[cfg(rustc_late_pass_takes_tcx)]
+//! lint_store.register_late_pass(|_| Box::new(LintPassName));
+//!
+//! #[cfg(not(rustc_late_pass_takes_tcx))]
+//! lint_store.register_late_pass(|| Box::new(LintPassName));
+//! ```
+//!
+//! For a concrete example, see the [`non_thread_safe_call_in_test`] library in this repository.
+//!
+//! [`build::emit_rustc_version_cfgs`]: crate::build::emit_rustc_version_cfgs
+//! [`build::known_changes`]: crate::build::KNOWN_CHANGES
+//! [`combine!`]: crate::combine
+//! [`combined`]: ../../examples/testing/combined/src/lib.rs
 //! [`config_or_default`]: crate::config_or_default
 //! [`config_toml`]: crate::config_toml
 //! [`config`]: crate::config
+//! [`crate_matches`]: crate::crate_matches
 //! [`declare_late_lint!`, `declare_early_lint!`, `declare_pre_expansion_lint!`]: #declare_late_lint-etc
 //! [`declare_lint!`]: https://doc.rust-lang.org/nightly/nightly-rustc/rustc_session/macro.declare_lint.html
 //! [`declare_lint_pass!`]: https://doc.rust-lang.org/nightly/nightly-rustc/rustc_session/macro.declare_lint_pass.html
 //! [`dylint-link`]: ../../dylint-link
 //! [`dylint_library!`]: #dylint_library
+//! [`dylint_testing`]: ../testing
+//! [`early_lint`]: ../../examples/testing/early_lint/src/lib.rs
 //! [`env_cargo_path`]: ../../examples/general/env_cargo_path/src/lib.rs
+//! [`excluded_paths`]: ../../examples/testing/excluded_paths/src/lib.rs
 //! [`impl_late_lint!`, `impl_early_lint!`, `impl_pre_expansion_lint!`]: #impl_late_lint-etc
 //! [`impl_lint_pass!`]: https://doc.rust-lang.org/nightly/nightly-rustc/rustc_session/macro.impl_lint_pass.html
 //! [`init_config`]: crate::init_config
+//! [`is_excluded`]: crate::is_excluded
+//! [`is_in_allowed_path`]: crate::is_in_allowed_path
+//! [`lintmetadata`]: crate::LintMetadata
 //! [`lintpass`]: https://doc.rust-lang.org/nightly/nightly-rustc/rustc_lint/trait.LintPass.html
 //! [`non_local_effect_before_error_return`]: ../../examples/general/non_local_effect_before_error_return/src/lib.rs
+//! [`non_thread_safe_call_in_test`]: ../../examples/general/non_thread_safe_call_in_test/src/lib.rs
 //! [`register_lints`]: https://doc.rust-lang.org/nightly/nightly-rustc/rustc_interface/interface/struct.Config.html#structfield.register_lints
 //! [`try_init_config`]: crate::try_init_config
+//! [compiling against multiple toolchains]: #compiling-against-multiple-toolchains
 //! [configurable libraries]: #configurable-libraries
+//! [constituent libraries]: #constituent-libraries
 //! [docs.rs documentation]: https://docs.rs/dylint_linting/latest/dylint_linting/
 //! [docs.rs]: https://docs.rs/dylint_linting/latest/dylint_linting/
 //! [dylint]: ../..
 //! [examples]: ../../examples
+//! [excluding paths and crates]: #excluding-paths-and-crates
+//! [lint metadata]: #lint-metadata
 
 #![feature(rustc_private)]
 #![warn(unused_extern_crates)]
@@ -177,20 +337,82 @@ extern crate rustc_session;
 extern crate rustc_span;
 
 use dylint_internal::env;
-use rustc_span::Symbol;
-use std::{any::type_name, cell::RefCell, fs::read_to_string, path::PathBuf, sync::Mutex};
+use glob::Pattern;
+use rustc_span::{FileName, Span, Symbol};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    any::type_name,
+    cell::RefCell,
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
 use thiserror::Error;
 
 pub const DYLINT_VERSION: &str = "0.1.0";
 
 pub use paste;
 
+pub mod build;
+
+/// Metadata about a lint that [`dylint_library!`] exports for tools, e.g., `cargo dylint list
+/// --json`. See [lint metadata].
+///
+/// [lint metadata]: crate#lint-metadata
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct LintMetadata {
+    #[serde(serialize_with = "serialize_lowercase")]
+    pub name: &'static str,
+    /// A longer description of the lint than the one passed to `declare_lint!`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'static str>,
+    /// Code for which the lint emits a warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bad_example: Option<&'static str>,
+    /// Code that fixes `bad_example`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub good_example: Option<&'static str>,
+    /// The applicability of the lint's suggestions, e.g., `"MachineApplicable"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applicability: Option<&'static str>,
+}
+
+impl LintMetadata {
+    #[must_use]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            description: None,
+            bad_example: None,
+            good_example: None,
+            applicability: None,
+        }
+    }
+}
+
+// smoelius: `declare_lint!` names are upper case, but lints are referred to in lower case.
+fn serialize_lowercase<S: Serializer>(
+    name: &&'static str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&name.to_lowercase())
+}
+
+#[doc(hidden)]
+#[must_use]
+pub fn __metadata_json(metadata: &[LintMetadata]) -> String {
+    serde_json::to_string(metadata).unwrap_or_default()
+}
+
 // smoelius: Including `extern crate rustc_driver` causes the library to link against
 // `librustc_driver.so`, which dylint-driver also links against. So, essentially, the library uses
 // dylint-driver's copy of the Rust compiler crates.
 #[macro_export]
 macro_rules! dylint_library {
     () => {
+        $crate::dylint_library!(metadata = ::std::vec::Vec::<$crate::LintMetadata>::new());
+    };
+    (metadata = $metadata:expr) => {
         #[allow(unused_extern_crates)]
         extern crate rustc_driver;
 
@@ -201,20 +423,65 @@ macro_rules! dylint_library {
                 .unwrap()
                 .into_raw()
         }
+
+        #[doc(hidden)]
+        #[no_mangle]
+        pub extern "C" fn dylint_metadata() -> *mut std::os::raw::c_char {
+            std::ffi::CString::new($crate::__metadata_json(&$metadata))
+                .unwrap()
+                .into_raw()
+        }
+    };
+}
+
+/// Combines [constituent libraries] into one library. The macro expands to `dylint_library!()`,
+/// along with a `register_lints` function that calls each constituent's `register_lints`.
+///
+/// [constituent libraries]: crate#constituent-libraries
+#[macro_export]
+macro_rules! combine {
+    ($($constituent:ident),* $(,)?) => {
+        $crate::dylint_library!(metadata = {
+            let mut metadata = ::std::vec::Vec::<$crate::LintMetadata>::new();
+            $(
+                metadata.extend($constituent::lint_metadata());
+            )*
+            metadata
+        });
+
+        extern crate rustc_lint;
+        extern crate rustc_session;
+
+        #[allow(clippy::no_mangle_with_rust_abi)]
+        #[no_mangle]
+        pub fn register_lints(sess: &rustc_session::Session, lint_store: &mut rustc_lint::LintStore) {
+            $(
+                $constituent::register_lints(sess, lint_store);
+            )*
+        }
     };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __declare_and_register_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $register_pass_method:ident, $pass:expr) => {
-        $crate::dylint_library!();
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $register_pass_method:ident, $pass:expr $(, $($field:ident = $value:expr),+)?) => {
+        #[cfg(not(feature = "constituent"))]
+        $crate::dylint_library!(metadata = lint_metadata());
+
+        #[doc(hidden)]
+        pub fn lint_metadata() -> ::std::vec::Vec<$crate::LintMetadata> {
+            ::std::vec![$crate::LintMetadata {
+                $($($field: ::std::option::Option::Some($value),)+)?
+                ..$crate::LintMetadata::new(stringify!($NAME))
+            }]
+        }
 
         extern crate rustc_lint;
         extern crate rustc_session;
 
         #[allow(clippy::no_mangle_with_rust_abi)]
-        #[no_mangle]
+        #[cfg_attr(not(feature = "constituent"), no_mangle)]
         pub fn register_lints(sess: &rustc_session::Session, lint_store: &mut rustc_lint::LintStore) {
             $crate::init_config(sess);
             lint_store.register_lints(&[$NAME]);
@@ -248,13 +515,14 @@ macro_rules! __make_late_closure {
 
 #[macro_export]
 macro_rules! impl_pre_expansion_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::__declare_and_register_lint!(
             $(#[$attr])* $vis $NAME,
             $Level,
             $desc,
             register_pre_expansion_pass,
             || Box::new($pass)
+            $(, $($field = $value),+)?
         );
         $crate::paste::paste! {
             rustc_session::impl_lint_pass!([< $NAME:camel >] => [$NAME]);
@@ -264,13 +532,14 @@ macro_rules! impl_pre_expansion_lint {
 
 #[macro_export]
 macro_rules! impl_early_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::__declare_and_register_lint!(
             $(#[$attr])* $vis $NAME,
             $Level,
             $desc,
             register_early_pass,
             || Box::new($pass)
+            $(, $($field = $value),+)?
         );
         $crate::paste::paste! {
             rustc_session::impl_lint_pass!([< $NAME:camel >] => [$NAME]);
@@ -280,13 +549,14 @@ macro_rules! impl_early_lint {
 
 #[macro_export]
 macro_rules! impl_late_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::__declare_and_register_lint!(
             $(#[$attr])* $vis $NAME,
             $Level,
             $desc,
             register_late_pass,
             $crate::__make_late_closure!($pass)
+            $(, $($field = $value),+)?
         );
         $crate::paste::paste! {
             rustc_session::impl_lint_pass!([< $NAME:camel >] => [$NAME]);
@@ -296,7 +566,7 @@ macro_rules! impl_late_lint {
 
 #[macro_export]
 macro_rules! declare_pre_expansion_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::paste::paste! {
             $crate::__declare_and_register_lint!(
                 $(#[$attr])* $vis $NAME,
@@ -304,6 +574,7 @@ macro_rules! declare_pre_expansion_lint {
                 $desc,
                 register_pre_expansion_pass,
                 || Box::new([< $NAME:camel >])
+                $(, $($field = $value),+)?
             );
             rustc_session::declare_lint_pass!([< $NAME:camel >] => [$NAME]);
         }
@@ -312,7 +583,7 @@ macro_rules! declare_pre_expansion_lint {
 
 #[macro_export]
 macro_rules! declare_early_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::paste::paste! {
             $crate::__declare_and_register_lint!(
                 $(#[$attr])* $vis $NAME,
@@ -320,6 +591,7 @@ macro_rules! declare_early_lint {
                 $desc,
                 register_early_pass,
                 || Box::new([< $NAME:camel >])
+                $(, $($field = $value),+)?
             );
             rustc_session::declare_lint_pass!([< $NAME:camel >] => [$NAME]);
         }
@@ -328,7 +600,7 @@ macro_rules! declare_early_lint {
 
 #[macro_export]
 macro_rules! declare_late_lint {
-    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr) => {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr $(, $($field:ident = $value:expr),+)? $(,)?) => {
         $crate::paste::paste! {
             $crate::__declare_and_register_lint!(
                 $(#[$attr])* $vis $NAME,
@@ -336,6 +608,7 @@ macro_rules! declare_late_lint {
                 $desc,
                 register_late_pass,
                 $crate::__make_late_closure!([< $NAME:camel >])
+                $(, $($field = $value),+)?
             );
             rustc_session::declare_lint_pass!([< $NAME:camel >] => [$NAME]);
         }
@@ -380,6 +653,10 @@ enum ConfigErrorInner {
 
 static CONFIG_TABLE: Mutex<RefCell<Option<toml::value::Table>>> = Mutex::new(RefCell::new(None));
 
+// smoelius: The names of the entries whose unknown keys have been warned about. A library may read
+// its configuration more than once, but each unknown key should be reported only once.
+static UNKNOWN_KEYS_REPORTED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
 /// Reads and deserializes an entry from the workspace's `dylint.toml` file, and returns the default
 /// value if the entry is not present.
 ///
@@ -415,13 +692,35 @@ pub fn config_or_default<T: Default + serde::de::DeserializeOwned>(name: &str) -
 ///   `name`
 /// - `Err(...)` if an error occurs (e.g., the value cannot be deserialized as `T`)
 ///
+/// If the entry contains keys that `T` does not use, a warning naming each such key is emitted.
+///
 /// Note: `init_config` or `try_init_config` must be called before `config` is called. However, the
 /// `register_lints` function generated by `impl_late_lint`, etc. includes a call to `init_config`.
 pub fn config<T: serde::de::DeserializeOwned>(name: &str) -> ConfigResult<Option<T>> {
     let toml = config_toml(name)?;
-    toml.map(toml::Value::try_into::<T>)
-        .transpose()
-        .map_err(Into::into)
+    toml.map(|toml| -> ConfigResult<T> {
+        let mut unknown_keys = Vec::new();
+        let value = serde_ignored::deserialize(toml, |path| unknown_keys.push(path.to_string()))?;
+        warn_unknown_keys(name, &unknown_keys);
+        Ok(value)
+    })
+    .transpose()
+}
+
+fn warn_unknown_keys(name: &str, unknown_keys: &[String]) {
+    if unknown_keys.is_empty()
+        || !UNKNOWN_KEYS_REPORTED
+            .lock()
+            .unwrap()
+            .insert(name.to_owned())
+    {
+        return;
+    }
+    for key in unknown_keys {
+        early_warn(format!(
+            "unknown key `{key}` in the `{name}` table of `dylint.toml`"
+        ));
+    }
 }
 
 /// Reads an entry from the workspace's `dylint.toml` file as a raw `toml::Value`.
@@ -472,12 +771,12 @@ pub fn try_init_config(sess: &rustc_session::Session) -> ConfigResult<()> {
         return Ok(());
     }
 
-    let value = if let Ok(value) = std::env::var(env::DYLINT_TOML) {
+    let table = if let Ok(value) = std::env::var(env::DYLINT_TOML) {
         sess.parse_sess.env_depinfo.lock().insert((
             Symbol::intern(env::DYLINT_TOML),
             Some(Symbol::intern(&value)),
         ));
-        Some(value)
+        Some(parse_table(&value)?)
     } else if let Some(local_crate_source_file) = local_crate_source_file(sess).and_then(|path| {
         if path == PathBuf::new() {
             None
@@ -515,17 +814,16 @@ pub fn try_init_config(sess: &rustc_session::Session) -> ConfigResult<()> {
                 if dylint_toml.try_exists().map_err(|error| {
                     ConfigErrorInner::Io(format!("`try_exists` failed for {dylint_toml:?}"), error)
                 })? {
-                    let value = read_to_string(&dylint_toml).map_err(|error| {
-                        ConfigErrorInner::Io(
-                            format!("`read_to_string` failed for {dylint_toml:?}"),
-                            error,
-                        )
-                    })?;
-                    sess.parse_sess
-                        .file_depinfo
-                        .lock()
-                        .insert(Symbol::intern(dylint_toml.as_str()));
-                    Some(value)
+                    let config = dylint_internal::config::Config::load(dylint_toml.as_std_path())
+                        .map_err(|error| ConfigErrorInner::Other(format!("{error:?}")))?;
+                    // smoelius: The files that `dylint.toml` extends are dependencies too.
+                    for path in config.paths() {
+                        sess.parse_sess
+                            .file_depinfo
+                            .lock()
+                            .insert(Symbol::intern(&path.to_string_lossy()));
+                    }
+                    Some(config.into_table())
                 } else {
                     None
                 }
@@ -535,21 +833,93 @@ pub fn try_init_config(sess: &rustc_session::Session) -> ConfigResult<()> {
         None
     };
 
-    let toml: Option<toml::Value> = value.as_deref().map(toml::from_str).transpose()?;
-
-    let table = toml
-        .map(|toml| {
-            toml.as_table()
-                .cloned()
-                .ok_or_else(|| ConfigErrorInner::Other("Value is not a table".into()))
-        })
-        .transpose()?;
-
     config_table.replace(Some(table.unwrap_or_default()));
 
     Ok(())
 }
 
+fn parse_table(value: &str) -> ConfigResult<toml::value::Table> {
+    let toml: toml::Value = toml::from_str(value)?;
+    toml.as_table()
+        .cloned()
+        .ok_or_else(|| ConfigErrorInner::Other("Value is not a table".into()).into())
+}
+
+/// The `[global]` table of the workspace's `dylint.toml` file, which applies to every library
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct GlobalConfig {
+    #[serde(default)]
+    exclude_paths: Vec<String>,
+    #[serde(default)]
+    exclude_crates: Vec<String>,
+}
+
+// smoelius: `is_excluded` may be called once per node visited. So the `[global]` table is
+// deserialized only once.
+static GLOBAL_CONFIG: OnceLock<GlobalConfig> = OnceLock::new();
+
+/// Returns true if `span` should not be linted according to the `[global]` table of the
+/// workspace's `dylint.toml` file, i.e., if `span`'s source file matches one of the table's
+/// `exclude-paths`, or the crate being compiled matches one of its `exclude-crates`. See
+/// [`is_in_allowed_path`] and [`crate_matches`] for how the globs are matched.
+///
+/// Note: `init_config` or `try_init_config` must be called before `is_excluded` is called. However,
+/// the `register_lints` function generated by `impl_late_lint`, etc. includes a call to
+/// `init_config`.
+pub fn is_excluded(sess: &rustc_session::Session, span: Span) -> bool {
+    let global_config = GLOBAL_CONFIG.get_or_init(|| config_or_default("global"));
+    is_in_allowed_path(sess, span, &global_config.exclude_paths)
+        || crate_matches(sess, &global_config.exclude_crates)
+}
+
+/// Returns true if `span`'s source file matches one of `patterns`, which are globs (e.g.,
+/// `"src/generated/**"`).
+///
+/// A relative glob is matched against the file's path relative to the current directory. When
+/// Cargo compiles a workspace member, the current directory is the workspace root. An absolute
+/// glob is matched against the file's absolute path. A glob that cannot be parsed matches nothing.
+pub fn is_in_allowed_path(
+    sess: &rustc_session::Session,
+    span: Span,
+    patterns: &[impl AsRef<str>],
+) -> bool {
+    let path = match sess.source_map().span_to_filename(span) {
+        FileName::Real(real_file_name) => {
+            if let Some(path) = real_file_name.local_path() {
+                path.to_path_buf()
+            } else {
+                return false;
+            }
+        }
+        _ => return false,
+    };
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    let absolute_path = current_dir.join(&path);
+    let relative_path = absolute_path.strip_prefix(&current_dir).ok();
+    compile(patterns).any(|pattern| {
+        if Path::new(pattern.as_str()).is_absolute() {
+            pattern.matches_path(&absolute_path)
+        } else {
+            relative_path.map_or(false, |relative_path| pattern.matches_path(relative_path))
+        }
+    })
+}
+
+/// Returns true if the name of the crate being compiled matches one of `patterns`, which are
+/// globs (e.g., `"*_generated"`). A glob that cannot be parsed matches nothing.
+pub fn crate_matches(sess: &rustc_session::Session, patterns: &[impl AsRef<str>]) -> bool {
+    sess.opts.crate_name.as_ref().map_or(false, |crate_name| {
+        compile(patterns).any(|pattern| pattern.matches(crate_name))
+    })
+}
+
+fn compile(patterns: &[impl AsRef<str>]) -> impl Iterator<Item = Pattern> + '_ {
+    patterns
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern.as_ref()).ok())
+}
+
 #[rustversion::before(2023-01-19)]
 fn local_crate_source_file(sess: &rustc_session::Session) -> Option<PathBuf> {
     sess.local_crate_source_file.clone()
@@ -558,9 +928,12 @@ fn local_crate_source_file(sess: &rustc_session::Session) -> Option<PathBuf> {
 // smoelius: Relevant PR and merge commit:
 // - https://github.com/rust-lang/rust/pull/106810
 // - https://github.com/rust-lang/rust/commit/65d2f2a5f9c323c88d1068e8e90d0b47a20d491c
+//
+// `Session::local_crate_source_file` applies `--remap-path-prefix` remappings, and the remapped
+// path need not exist. The input path is used instead.
 #[rustversion::since(2023-01-19)]
 fn local_crate_source_file(sess: &rustc_session::Session) -> Option<PathBuf> {
-    sess.local_crate_source_file()
+    sess.io.input.opt_path().map(Path::to_path_buf)
 }
 
 #[rustversion::before(2023-06-28)]
@@ -571,6 +944,14 @@ fn early_error(msg: String) -> ! {
     )
 }
 
+#[rustversion::before(2023-06-28)]
+fn early_warn(msg: String) {
+    rustc_session::early_warn(
+        rustc_session::config::ErrorOutputType::default(),
+        Box::leak(msg.into_boxed_str()) as &str,
+    );
+}
+
 #[rustversion::since(2023-06-28)]
 extern crate rustc_errors;
 
@@ -580,3 +961,10 @@ fn early_error(msg: impl Into<rustc_errors::DiagnosticMessage>) -> ! {
         rustc_session::EarlyErrorHandler::new(rustc_session::config::ErrorOutputType::default());
     handler.early_error(msg)
 }
+
+#[rustversion::since(2023-06-28)]
+fn early_warn(msg: impl Into<rustc_errors::DiagnosticMessage>) {
+    let handler =
+        rustc_session::EarlyErrorHandler::new(rustc_session::config::ErrorOutputType::default());
+    handler.early_warn(msg);
+}
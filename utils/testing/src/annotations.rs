@@ -0,0 +1,212 @@
+use crate::{directives::compile_flags, lint_filter::lint_names, ui::Config, EnvLock};
+use anyhow::{ensure, Context, Result};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+/// Marks a line on which the library under test must not emit a diagnostic
+pub(crate) const OK_ANNOTATION: &str = "//~ OK";
+
+/// `compiletest` would treat an `OK` annotation as an expected error. So in the copy of the test
+/// files that `compiletest` sees, each annotation is replaced with this ordinary comment.
+pub(crate) const OK_REPLACEMENT: &str = "// OK";
+
+pub(crate) fn is_ok_annotated(line: &str) -> bool {
+    line.trim_end().ends_with(OK_ANNOTATION)
+}
+
+/// Checks that the library emits no diagnostics on the lines of the `.rs` files in `src_base` that
+/// are annotated with `//~ OK`. The files are compiled from `staged`, the translated copy of
+/// `src_base`. The library's diagnostics are identified by their lint names in the driver's JSON
+/// output.
+pub(crate) fn check_ok_lines(
+    driver: &Path,
+    src_base: &Path,
+    staged: &Path,
+    config: &Config,
+) -> Result<()> {
+    let annotated = annotated_files(src_base, src_base)?;
+    if annotated.is_empty() {
+        return Ok(());
+    }
+
+    let _lock = EnvLock::acquire(config);
+
+    let lint_names = lint_names(driver)?;
+
+    let out_dir = tempdir().with_context(|| "`tempdir` failed")?;
+
+    let mut failures = Vec::new();
+    for (path, ok_lines) in annotated {
+        let file = staged.join(&path);
+        for diagnostic in diagnostics(driver, &file, out_dir.path(), config)? {
+            if !is_from_lints(&diagnostic, &lint_names) {
+                continue;
+            }
+            for line in primary_lines(&diagnostic, &file) {
+                if ok_lines.contains(&line) {
+                    failures.push(format!(
+                        "`{}:{line}` is annotated `{OK_ANNOTATION}`, but the following diagnostic \
+                         was emitted:\n{}",
+                        src_base.join(&path).to_string_lossy(),
+                        diagnostic["rendered"].as_str().unwrap_or_default()
+                    ));
+                }
+            }
+        }
+    }
+
+    ensure!(failures.is_empty(), "{}", failures.join("\n"));
+
+    Ok(())
+}
+
+/// Returns the paths, relative to `root`, of the `.rs` files containing `//~ OK` annotations, along
+/// with the 1-based numbers of the annotated lines
+fn annotated_files(root: &Path, dir: &Path) -> Result<Vec<(PathBuf, BTreeSet<usize>)>> {
+    let mut annotated = Vec::new();
+    for entry in read_dir(dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?
+    {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            annotated.extend(annotated_files(root, &path)?);
+        } else if path.extension() == Some("rs".as_ref()) {
+            let contents = read_to_string(&path).with_context(|| {
+                format!("`read_to_string` failed for `{}`", path.to_string_lossy())
+            })?;
+            let ok_lines = ok_lines(&contents);
+            if !ok_lines.is_empty() {
+                annotated.push((path.strip_prefix(root)?.to_path_buf(), ok_lines));
+            }
+        }
+    }
+    annotated.sort();
+    Ok(annotated)
+}
+
+fn ok_lines(contents: &str) -> BTreeSet<usize> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| is_ok_annotated(line))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+// smoelius: The file is compiled with the same flags `compiletest` would use, except that
+// diagnostics are emitted as JSON. The file need not have been staged. `dylint_internal::Command`
+// treats a nonzero exit status as an error. So lints are capped at `warn`, and the only errors are
+// ones that prevent the file from compiling.
+pub(crate) fn diagnostics(
+    driver: &Path,
+    file: &Path,
+    out_dir: &Path,
+    config: &Config,
+) -> Result<Vec<Value>> {
+    let contents = read_to_string(file)
+        .with_context(|| format!("`read_to_string` failed for `{}`", file.to_string_lossy()))?;
+    let compile_flags = compile_flags(&contents);
+
+    let output = dylint_internal::driver(env!("RUSTUP_TOOLCHAIN"), driver)?
+        .args([file])
+        .args([
+            "--error-format=json",
+            "--emit=metadata",
+            "-Dwarnings",
+            "--cap-lints=warn",
+            "-Zui-testing",
+            "--out-dir",
+        ])
+        .args([out_dir])
+        .args(
+            config
+                .rustc_flags
+                .iter()
+                .flat_map(|flag| flag.split_whitespace()),
+        )
+        .args(compile_flags)
+        .output()?;
+
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Could not parse diagnostic: {line}"))
+        })
+        .collect()
+}
+
+fn is_from_lints(diagnostic: &Value, lint_names: &BTreeSet<String>) -> bool {
+    diagnostic["code"]["code"]
+        .as_str()
+        .map_or(false, |code| lint_names.contains(code))
+}
+
+/// Returns the lines in `file` spanned by the diagnostic's primary spans
+fn primary_lines(diagnostic: &Value, file: &Path) -> BTreeSet<usize> {
+    let file = file.to_string_lossy();
+    diagnostic["spans"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|span| {
+            span["is_primary"].as_bool() == Some(true)
+                && span["file_name"].as_str() == Some(file.as_ref())
+        })
+        .filter_map(|span| {
+            let line_start = usize::try_from(span["line_start"].as_u64()?).ok()?;
+            let line_end = usize::try_from(span["line_end"].as_u64()?).ok()?;
+            Some(line_start..=line_end)
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn annotated_lines() {
+        assert_eq!(
+            BTreeSet::from([2, 4]),
+            ok_lines(concat!(
+                "fn main() {\n",
+                "    let x = 0; //~ OK\n",
+                "    let y = 0; //~ ERROR\n",
+                "    f(); //~ OK \n",
+                "}\n",
+            ))
+        );
+    }
+
+    #[test]
+    fn primary_spans() {
+        let diagnostic = serde_json::json!({
+            "code": { "code": "lint_name" },
+            "spans": [
+                { "file_name": "main.rs", "is_primary": true, "line_start": 3, "line_end": 4 },
+                { "file_name": "main.rs", "is_primary": false, "line_start": 7, "line_end": 7 },
+                { "file_name": "other.rs", "is_primary": true, "line_start": 9, "line_end": 9 }
+            ],
+        });
+        assert!(is_from_lints(
+            &diagnostic,
+            &BTreeSet::from(["lint_name".to_owned()])
+        ));
+        assert_eq!(
+            BTreeSet::from([3, 4]),
+            primary_lines(&diagnostic, Path::new("main.rs"))
+        );
+    }
+}
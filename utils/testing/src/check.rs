@@ -0,0 +1,125 @@
+use crate::{annotations::diagnostics, initialize, lint_filter::lint_names, ui::Config, EnvLock};
+use anyhow::{ensure, Context, Result};
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel};
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+use std::{collections::BTreeSet, fs::write};
+use tempfile::tempdir;
+
+static LINT_NAMES: OnceCell<BTreeSet<String>> = OnceCell::new();
+
+/// Compiles `source` as a library crate with the library `name` loaded, and returns the
+/// diagnostics of the library's lints. Fails if `source` does not compile.
+pub(crate) fn check_str(name: &str, source: &str, config: &Config) -> Result<Vec<Diagnostic>> {
+    let driver = initialize(name, config.hermetic)?;
+
+    let lint_names = LINT_NAMES.get_or_try_init(|| lint_names(driver))?;
+
+    let tempdir = tempdir().with_context(|| "`tempdir` failed")?;
+    let file = tempdir.path().join("lib.rs");
+    write(&file, source)
+        .with_context(|| format!("`write` failed for `{}`", file.to_string_lossy()))?;
+
+    // smoelius: `diagnostics` caps lints at `warn`. So the only errors are ones that prevent
+    // `source` from compiling.
+    let mut config = config.clone();
+    config
+        .rustc_flags
+        .extend(["--crate-type=lib", "--edition=2021"].map(ToOwned::to_owned));
+
+    let values = {
+        let _lock = EnvLock::acquire(&config);
+        diagnostics(driver, &file, tempdir.path(), &config)?
+    };
+
+    library_diagnostics(values, lint_names)
+}
+
+fn library_diagnostics(
+    values: Vec<Value>,
+    lint_names: &BTreeSet<String>,
+) -> Result<Vec<Diagnostic>> {
+    let diagnostics = values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value::<Diagnostic>(value)
+                .with_context(|| "Could not parse diagnostic")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Error))
+        .map(|diagnostic| {
+            diagnostic
+                .rendered
+                .clone()
+                .unwrap_or_else(|| diagnostic.message.clone())
+        })
+        .collect::<Vec<_>>();
+    ensure!(
+        errors.is_empty(),
+        "Could not compile source:\n{}",
+        errors.join("\n")
+    );
+
+    Ok(diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic
+                .code
+                .as_ref()
+                .map_or(false, |code| lint_names.contains(&code.code))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn only_library_lints() {
+        let diagnostics = library_diagnostics(
+            vec![
+                diagnostic("warning", Some("lint_name")),
+                diagnostic("warning", Some("dead_code")),
+                diagnostic("warning", None),
+            ],
+            &BTreeSet::from(["lint_name".to_owned()]),
+        )
+        .unwrap();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("lint_name", diagnostics[0].code.as_ref().unwrap().code);
+    }
+
+    #[test]
+    fn errors() {
+        let error = library_diagnostics(
+            vec![
+                diagnostic("warning", Some("lint_name")),
+                diagnostic("error", Some("E0425")),
+            ],
+            &BTreeSet::from(["lint_name".to_owned()]),
+        )
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("Could not compile source"),
+            "unexpected error: {error}"
+        );
+    }
+
+    fn diagnostic(level: &str, code: Option<&str>) -> Value {
+        json!({
+            "message": "message",
+            "code": code.map(|code| json!({ "code": code, "explanation": null })),
+            "level": level,
+            "spans": [],
+            "children": [],
+            "rendered": format!("{level}: message\n"),
+        })
+    }
+}
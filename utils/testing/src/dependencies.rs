@@ -0,0 +1,190 @@
+use crate::ui::Config;
+use anyhow::{anyhow, Context, Result};
+use cargo_metadata::Message;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::{canonicalize, create_dir_all, read_to_string, write},
+    hash::{Hash, Hasher},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+const PACKAGE_NAME: &str = "dylint_testing_dependencies";
+
+/// A dependency of the files a test compiles
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Dependency {
+    Version(String),
+    Path(PathBuf),
+}
+
+/// Returns `config` extended with the flags needed to link against `config.dependencies`. The
+/// dependencies are built in a scratch package, which is reused as long as the dependencies and
+/// the toolchain are unchanged. If `config.dependencies` is empty, `config` is returned unchanged.
+pub(crate) fn link(config: &Config) -> Result<Config> {
+    let mut config = config.clone();
+    if !config.dependencies.is_empty() {
+        let flags = linking_flags(&config.dependencies)?;
+        config.rustc_flags.extend(flags);
+    }
+    Ok(config)
+}
+
+fn linking_flags(dependencies: &[(String, Dependency)]) -> Result<Vec<String>> {
+    let manifest = manifest(dependencies)?;
+
+    let metadata = dylint_internal::cargo::current_metadata()?;
+    // smoelius: `.` cannot appear in a directory name that `build_base` returns, so the scratch
+    // packages cannot collide with a test's build directory.
+    let package_dir = metadata
+        .target_directory
+        .join("dylint_testing/.dependencies")
+        .join(cache_key(&manifest, env!("RUSTUP_TOOLCHAIN")))
+        .into_std_path_buf();
+    write_package(&package_dir, &manifest)?;
+
+    // smoelius: Cargo reports an artifact for each library it builds, or would have built had the
+    // library not been fresh. So the paths are known even when nothing is rebuilt.
+    let target_dir = package_dir.join("target");
+    let mut messages = Vec::new();
+    dylint_internal::cargo::build("ui test dependencies", false)
+        .current_dir(&package_dir)
+        .args(["--message-format=json", "--target-dir"])
+        .args([&target_dir])
+        .success_with_stdout(|stdout| {
+            for message in Message::parse_stream(BufReader::new(stdout)) {
+                messages.push(message.with_context(|| "Could not parse `cargo build` output")?);
+            }
+            Ok(())
+        })
+        .with_context(|| {
+            format!(
+                "Could not build the ui test dependencies in `{}`",
+                package_dir.to_string_lossy()
+            )
+        })?;
+
+    let artifacts = messages
+        .into_iter()
+        .filter_map(|message| match message {
+            Message::CompilerArtifact(artifact) => Some(artifact),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut flags = vec![
+        "-L".to_owned(),
+        format!(
+            "dependency={}",
+            target_dir.join("debug/deps").to_string_lossy()
+        ),
+    ];
+    for (name, _) in dependencies {
+        let crate_name = name.replace('-', "_");
+        let path = artifacts
+            .iter()
+            .filter(|artifact| artifact.target.name.replace('-', "_") == crate_name)
+            .flat_map(|artifact| &artifact.filenames)
+            .min_by_key(|path| match path.extension() {
+                Some("rlib") => 0,
+                Some("rmeta") => 2,
+                _ => 1,
+            })
+            .ok_or_else(|| anyhow!("Could not find a library for dependency `{name}`"))?;
+        flags.extend(["--extern".to_owned(), format!("{crate_name}={path}")]);
+    }
+
+    Ok(flags)
+}
+
+fn manifest(dependencies: &[(String, Dependency)]) -> Result<String> {
+    let mut manifest = format!(
+        "[package]\nname = \"{PACKAGE_NAME}\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+         false\n\n[dependencies]\n"
+    );
+    for (name, dependency) in dependencies {
+        // smoelius: A JSON string is also a valid TOML basic string.
+        let spec = match dependency {
+            Dependency::Version(version) => serde_json::to_string(version)?,
+            Dependency::Path(path) => {
+                let path = canonicalize(path).with_context(|| {
+                    format!("Could not canonicalize `{}`", path.to_string_lossy())
+                })?;
+                format!("{{ path = {} }}", serde_json::to_string(&path)?)
+            }
+        };
+        manifest.push_str(&format!("{} = {spec}\n", serde_json::to_string(name)?));
+    }
+    manifest.push_str("\n[workspace]\n");
+    Ok(manifest)
+}
+
+fn write_package(package_dir: &Path, manifest: &str) -> Result<()> {
+    let src_dir = package_dir.join("src");
+    create_dir_all(&src_dir).with_context(|| {
+        format!(
+            "`create_dir_all` failed for `{}`",
+            src_dir.to_string_lossy()
+        )
+    })?;
+    for (path, contents) in [
+        (package_dir.join("Cargo.toml"), manifest),
+        (src_dir.join("lib.rs"), ""),
+    ] {
+        // smoelius: Rewriting an unchanged file would make Cargo consider the package dirty.
+        if read_to_string(&path).ok().as_deref() != Some(contents) {
+            write(&path, contents)
+                .with_context(|| format!("`write` failed for `{}`", path.to_string_lossy()))?;
+        }
+    }
+    Ok(())
+}
+
+// smoelius: The key includes the manifest rather than the dependency list, so that it changes if
+// the manifest's format does.
+fn cache_key(manifest: &str, toolchain: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    manifest.hash(&mut hasher);
+    toolchain.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn manifest_lists_dependencies() {
+        let manifest = manifest(&[
+            ("tempfile".to_owned(), Dependency::Version("3.6".to_owned())),
+            ("anyhow".to_owned(), Dependency::Version("1.0".to_owned())),
+        ])
+        .unwrap();
+        assert!(
+            manifest.contains("\n[dependencies]\n\"tempfile\" = \"3.6\"\n\"anyhow\" = \"1.0\"\n")
+        );
+        assert!(manifest.ends_with("\n[workspace]\n"));
+    }
+
+    #[test]
+    fn cache_key_depends_on_dependencies_and_toolchain() {
+        let a =
+            manifest(&[("tempfile".to_owned(), Dependency::Version("3.6".to_owned()))]).unwrap();
+        let b =
+            manifest(&[("tempfile".to_owned(), Dependency::Version("3.5".to_owned()))]).unwrap();
+        assert_eq!(
+            cache_key(&a, "nightly-2023-06-29"),
+            cache_key(&a, "nightly-2023-06-29")
+        );
+        assert_ne!(
+            cache_key(&a, "nightly-2023-06-29"),
+            cache_key(&b, "nightly-2023-06-29")
+        );
+        assert_ne!(
+            cache_key(&a, "nightly-2023-06-29"),
+            cache_key(&a, "nightly-2023-07-13")
+        );
+    }
+}
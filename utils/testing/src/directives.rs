@@ -0,0 +1,201 @@
+use crate::annotations::{is_ok_annotated, OK_ANNOTATION, OK_REPLACEMENT};
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::{copy, create_dir_all, read_dir, read_to_string, write},
+    path::Path,
+};
+use tempfile::{tempdir, TempDir};
+
+const PREFIX: &str = "// dylint:";
+
+const DIRECTIVES: &[&str] = &["compile-flags", RUN_RUSTFIX];
+
+/// Handled by `dylint_testing` itself (see [`crate::fix`]) rather than by `compiletest`
+pub(crate) const RUN_RUSTFIX: &str = "run-rustfix";
+
+/// Copies `src_base` to a temporary directory, translating `dylint:` directives into ones
+/// `compiletest` understands, and returns the temporary directory
+pub(crate) fn stage(src_base: &Path) -> Result<TempDir> {
+    let tempdir = tempdir().with_context(|| "`tempdir` failed")?;
+    copy_translated(src_base, tempdir.path())?;
+
+    Ok(tempdir)
+}
+
+fn copy_translated(from: &Path, to: &Path) -> Result<()> {
+    create_dir_all(to)
+        .with_context(|| format!("`create_dir_all` failed for `{}`", to.to_string_lossy()))?;
+    for entry in read_dir(from)
+        .with_context(|| format!("`read_dir` failed for `{}`", from.to_string_lossy()))?
+    {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", from.to_string_lossy()))?;
+        let path = entry.path();
+        let to = to.join(entry.file_name());
+        if path.is_dir() {
+            copy_translated(&path, &to)?;
+        } else if is_checked_by_dylint(&path)? {
+            continue;
+        } else if path.extension() == Some("rs".as_ref()) {
+            let contents = read_to_string(&path).with_context(|| {
+                format!("`read_to_string` failed for `{}`", path.to_string_lossy())
+            })?;
+            let translated = translate(&contents)
+                .with_context(|| format!("Could not translate `{}`", path.to_string_lossy()))?;
+            write(&to, translated)
+                .with_context(|| format!("`write` failed for `{}`", to.to_string_lossy()))?;
+        } else {
+            copy(&path, &to).with_context(|| {
+                format!(
+                    "Could not copy `{}` to `{}`",
+                    path.to_string_lossy(),
+                    to.to_string_lossy()
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if `path` is the `.fixed` file of a `.rs` file containing a `dylint: run-rustfix`
+/// directive. Such files are checked by [`crate::fix`], not by `compiletest`.
+pub(crate) fn is_checked_by_dylint(path: &Path) -> Result<bool> {
+    if path.extension() != Some("fixed".as_ref()) {
+        return Ok(false);
+    }
+    let rs_path = path.with_extension("rs");
+    if !rs_path.exists() {
+        return Ok(false);
+    }
+    let contents = read_to_string(&rs_path).with_context(|| {
+        format!(
+            "`read_to_string` failed for `{}`",
+            rs_path.to_string_lossy()
+        )
+    })?;
+    Ok(has_directive(&contents, RUN_RUSTFIX))
+}
+
+/// Returns true if `contents` contain the `dylint:` directive `name`
+pub(crate) fn has_directive(contents: &str, name: &str) -> bool {
+    contents.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix(PREFIX)
+            .map_or(false, |directive| directive.trim() == name)
+    })
+}
+
+/// Returns the flags in `contents`' `compile-flags` directives, whether or not the directives have
+/// been translated
+pub(crate) fn compile_flags(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            match line.strip_prefix(PREFIX) {
+                Some(directive) => directive.trim_start().strip_prefix("compile-flags:"),
+                None => line.strip_prefix("// compile-flags:"),
+            }
+        })
+        .flat_map(str::split_whitespace)
+}
+
+// smoelius: Each directive and `OK` annotation is translated in place, so that line numbers in the
+// expected output are unaffected.
+fn translate(contents: &str) -> Result<String> {
+    let mut translated = String::with_capacity(contents.len());
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(directive) = trimmed.strip_prefix(PREFIX) {
+            let directive = directive.trim_start();
+            let name = directive
+                .split_once(':')
+                .map_or(directive.trim_end(), |(name, _)| name);
+            if !DIRECTIVES.contains(&name) {
+                bail!("Unknown directive `{}`", name);
+            }
+            translated.push_str(&line[..line.len() - trimmed.len()]);
+            // smoelius: `compiletest` must not see `run-rustfix`, or it would apply the
+            // suggestions itself.
+            if name == RUN_RUSTFIX {
+                translated.push_str("//");
+                translated.push_str(&directive[name.len()..]);
+            } else {
+                translated.push_str("// ");
+                translated.push_str(directive);
+            }
+        } else if let Some((code, rest)) = line
+            .rsplit_once(OK_ANNOTATION)
+            .filter(|_| is_ok_annotated(line))
+        {
+            translated.push_str(code);
+            translated.push_str(OK_REPLACEMENT);
+            translated.push_str(rest);
+        } else {
+            translated.push_str(line);
+        }
+    }
+    Ok(translated)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn compile_flags() {
+        assert_eq!(
+            "// compile-flags: --cfg foo\n\nfn main() {}\n",
+            translate("// dylint: compile-flags: --cfg foo\n\nfn main() {}\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn run_rustfix() {
+        let contents = "// dylint: run-rustfix\n\nfn main() {}\n";
+        assert_eq!("//\n\nfn main() {}\n", translate(contents).unwrap());
+        assert!(has_directive(contents, RUN_RUSTFIX));
+        assert!(!has_directive("// run-rustfix\n", RUN_RUSTFIX));
+    }
+
+    #[test]
+    fn checked_by_dylint() {
+        let tempdir = tempdir().unwrap();
+        write(tempdir.path().join("a.rs"), "// dylint: run-rustfix\n").unwrap();
+        write(tempdir.path().join("b.rs"), "// run-rustfix\n").unwrap();
+        assert!(is_checked_by_dylint(&tempdir.path().join("a.fixed")).unwrap());
+        assert!(!is_checked_by_dylint(&tempdir.path().join("a.stderr")).unwrap());
+        assert!(!is_checked_by_dylint(&tempdir.path().join("b.fixed")).unwrap());
+        assert!(!is_checked_by_dylint(&tempdir.path().join("c.fixed")).unwrap());
+    }
+
+    #[test]
+    fn untranslated_compile_flags() {
+        let contents = "// dylint: compile-flags: --cfg foo\n// compile-flags: --cfg bar\n";
+        assert_eq!(
+            vec!["--cfg", "foo", "--cfg", "bar"],
+            super::compile_flags(contents).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unknown_directive() {
+        assert!(translate("// dylint: run-pass\n").is_err());
+    }
+
+    #[test]
+    fn ok_annotation() {
+        assert_eq!(
+            "    let x = 0; // OK\n",
+            translate("    let x = 0; //~ OK\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn no_directives() {
+        let contents = "// compile-flags: --cfg foo\nfn main() {}";
+        assert_eq!(contents, translate(contents).unwrap());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::{
+    annotations::diagnostics,
+    check_expected,
+    directives::{has_directive, RUN_RUSTFIX},
+    ui::Config,
+    EnvLock,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use rustfix::{apply_suggestions, get_suggestions_from_json, Filter, Suggestion};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fs::{read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+/// Checks the `.rs` files in `src_base` that contain a `dylint: run-rustfix` directive. For each
+/// such file, the machine-applicable suggestions are applied, and the result is compared to the
+/// file's `.fixed` file. The result is then compiled from `staged`, the translated copy of
+/// `src_base`, and must produce no diagnostics. Returns the `.fixed` files that were blessed.
+pub(crate) fn check_fixed(
+    driver: &Path,
+    src_base: &Path,
+    staged: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>> {
+    let files = rustfix_files(src_base, src_base)?;
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let _lock = EnvLock::acquire(config);
+
+    let out_dir = tempdir().with_context(|| "`tempdir` failed")?;
+
+    let mut blessed = Vec::new();
+    for path in files {
+        // smoelius: Translating directives and `OK` annotations changes the lengths of lines, which
+        // would throw off the suggestions' byte offsets. So the suggestions are obtained by
+        // compiling the original file, so that the `.fixed` file contains the original directives.
+        let original = src_base.join(&path);
+        let source = read_to_string(&original).with_context(|| {
+            format!(
+                "`read_to_string` failed for `{}`",
+                original.to_string_lossy()
+            )
+        })?;
+
+        let fixed = fix(
+            &source,
+            &diagnostics(driver, &original, out_dir.path(), config)?,
+            &original,
+        )
+        .with_context(|| {
+            format!(
+                "Could not apply suggestions to `{}`",
+                original.to_string_lossy()
+            )
+        })?;
+
+        let fixed_path = original.with_extension("fixed");
+        let expected = if fixed_path.exists() {
+            read_to_string(&fixed_path).with_context(|| {
+                format!(
+                    "`read_to_string` failed for `{}`",
+                    fixed_path.to_string_lossy()
+                )
+            })?
+        } else {
+            String::new()
+        };
+        if check_expected(&fixed_path, &expected, &fixed, config)? {
+            blessed.push(fixed_path);
+        }
+
+        // smoelius: The fixed source is written next to the staged copy of the original, so that
+        // any files it refers to by relative path are found, and so that `src_base` is unchanged
+        // when blessing is not enabled.
+        let staged_fixed = staged.join(&path).with_extension("fixed");
+        write(&staged_fixed, &fixed)
+            .with_context(|| format!("`write` failed for `{}`", staged_fixed.to_string_lossy()))?;
+        let remaining = diagnostics(driver, &staged_fixed, out_dir.path(), config)?
+            .iter()
+            .filter_map(|diagnostic| diagnostic["rendered"].as_str())
+            .filter(|rendered| !rendered.is_empty())
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        ensure!(
+            remaining.is_empty(),
+            "Compiling the fixed source of `{}` produced diagnostics:\n{}",
+            original.to_string_lossy(),
+            remaining.join("\n")
+        );
+    }
+
+    Ok(blessed)
+}
+
+/// Returns the paths, relative to `root`, of the `.rs` files containing a `dylint: run-rustfix`
+/// directive
+fn rustfix_files(root: &Path, dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in read_dir(dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?
+    {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(rustfix_files(root, &path)?);
+        } else if path.extension() == Some("rs".as_ref()) {
+            let contents = read_to_string(&path).with_context(|| {
+                format!("`read_to_string` failed for `{}`", path.to_string_lossy())
+            })?;
+            if !has_directive(&contents, RUN_RUSTFIX) {
+                continue;
+            }
+            // smoelius: With both directives, the suggestions would be applied twice, once by
+            // `compiletest` and once by `dylint_testing`.
+            if contents
+                .lines()
+                .any(|line| line.trim() == format!("// {RUN_RUSTFIX}"))
+            {
+                bail!(
+                    "`{}` contains both `// {RUN_RUSTFIX}` and `// dylint: {RUN_RUSTFIX}`; use \
+                     only one",
+                    path.to_string_lossy()
+                );
+            }
+            files.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Applies the machine-applicable suggestions in `diagnostics` to `source`, the contents of `file`
+fn fix(source: &str, diagnostics: &[Value], file: &Path) -> Result<String> {
+    let json = diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let suggestions = get_suggestions_from_json(
+        &json,
+        &HashSet::<String>::new(),
+        Filter::MachineApplicableOnly,
+    )
+    .with_context(|| "Could not parse suggestions")?;
+
+    let file_name = file.to_string_lossy();
+    for suggestion in &suggestions {
+        for solution in &suggestion.solutions {
+            for replacement in &solution.replacements {
+                ensure!(
+                    replacement.snippet.file_name == file_name,
+                    "The suggestion `{}` would change `{}`, which is not the file under test",
+                    suggestion.message,
+                    replacement.snippet.file_name
+                );
+            }
+        }
+    }
+
+    // smoelius: `rustfix` refuses to apply suggestions whose replacements overlap. `compiletest`
+    // panics in that case. Here, the suggestions involved are listed instead.
+    apply_suggestions(source, &suggestions).map_err(|error| {
+        anyhow!(
+            "{error}\nThe machine-applicable suggestions may overlap:\n{}",
+            describe(&suggestions)
+        )
+    })
+}
+
+fn describe(suggestions: &[Suggestion]) -> String {
+    suggestions
+        .iter()
+        .flat_map(|suggestion| {
+            suggestion.solutions.iter().map(move |solution| {
+                let positions = solution
+                    .replacements
+                    .iter()
+                    .map(|replacement| {
+                        let range = &replacement.snippet.line_range;
+                        format!("{}:{}", range.start.line, range.start.column)
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "    {}: {} (at {})",
+                    suggestion.message,
+                    solution.message,
+                    positions.join(", ")
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use serde_json::json;
+
+    const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+    #[test]
+    fn machine_applicable() {
+        let diagnostics = [
+            diagnostic("main.rs", 24, 25, "2", "MachineApplicable"),
+            diagnostic("main.rs", 20, 21, "_x", "MaybeIncorrect"),
+        ];
+        assert_eq!(
+            "fn main() {\n    let x = 2;\n}\n",
+            fix(SOURCE, &diagnostics, Path::new("main.rs")).unwrap()
+        );
+    }
+
+    #[test]
+    fn overlapping() {
+        let diagnostics = [
+            diagnostic("main.rs", 20, 25, "y = 2", "MachineApplicable"),
+            diagnostic("main.rs", 24, 25, "3", "MachineApplicable"),
+        ];
+        let error = fix(SOURCE, &diagnostics, Path::new("main.rs")).unwrap_err();
+        assert!(
+            error.to_string().contains("may overlap"),
+            "unexpected error: {error}"
+        );
+    }
+
+    #[test]
+    fn other_file() {
+        let diagnostics = [diagnostic("aux.rs", 24, 25, "2", "MachineApplicable")];
+        let error = fix(SOURCE, &diagnostics, Path::new("main.rs")).unwrap_err();
+        assert!(
+            error.to_string().contains("not the file under test"),
+            "unexpected error: {error}"
+        );
+    }
+
+    fn diagnostic(
+        file_name: &str,
+        byte_start: usize,
+        byte_end: usize,
+        replacement: &str,
+        applicability: &str,
+    ) -> Value {
+        let line = SOURCE.lines().nth(1).unwrap();
+        let span = |suggested_replacement: Option<&str>, applicability: Option<&str>| {
+            json!({
+                "file_name": file_name,
+                "byte_start": byte_start,
+                "byte_end": byte_end,
+                "line_start": 2,
+                "line_end": 2,
+                "column_start": byte_start - 11,
+                "column_end": byte_end - 11,
+                "is_primary": true,
+                "text": [{
+                    "text": line,
+                    "highlight_start": byte_start - 11,
+                    "highlight_end": byte_end - 11,
+                }],
+                "label": null,
+                "suggested_replacement": suggested_replacement,
+                "suggestion_applicability": applicability,
+                "expansion": null,
+            })
+        };
+        json!({
+            "message": "lint message",
+            "code": { "code": "lint_name", "explanation": null },
+            "level": "warning",
+            "spans": [span(None, None)],
+            "children": [{
+                "message": "try",
+                "code": null,
+                "level": "help",
+                "spans": [span(Some(replacement), Some(applicability))],
+                "children": [],
+                "rendered": null,
+            }],
+            "rendered": "warning: lint message\n",
+        })
+    }
+}
@@ -1,14 +1,17 @@
 //! This crate provides convenient access to the [`compiletest_rs`] package for testing [Dylint]
 //! libraries.
 //!
-//! **Note: If your test has dependencies, you must use `ui_test_example` or `ui_test_examples`.**
-//! See the [`question_mark_in_expression`] example in this repository.
+//! **Note: If your test has dependencies, you must use `ui_test_example` or `ui_test_examples`,
+//! or declare the dependencies with [`ui::Test::dependency`] (see [dependencies]).** See the
+//! [`question_mark_in_expression`] example in this repository.
 //!
-//! This crate provides the following three functions:
+//! This crate provides the following five functions:
 //!
 //! - [`ui_test`] - test a library on all source files in a directory
 //! - [`ui_test_example`] - test a library on one example target
 //! - [`ui_test_examples`] - test a library on all example targets
+//! - [`ui_test_package`] - test a library on a Cargo package (see [Cargo packages])
+//! - [`ui_test_packages`] - test a library on all Cargo packages in a directory
 //!
 //! For most situations, you can add the following to your library's `lib.rs` file:
 //!
@@ -27,31 +30,229 @@
 //!
 //! # Test builder
 //!
-//! In addition to the above three functions, [`ui::Test`] is a test "builder." Currently, the main
+//! In addition to the above five functions, [`ui::Test`] is a test "builder." Currently, the main
 //! advantage of using `Test` over the above functions is that `Test` allows flags to be passed to
 //! `rustc`. For an example of its use, see [`non_thread_safe_call_in_test`] in this repository.
 //!
-//! `Test` has three constructors, which correspond to the above three functions as follows:
+//! `Test` has five constructors, which correspond to the above five functions as follows:
 //!
 //! - [`ui::Test::src_base`] <-> [`ui_test`]
 //! - [`ui::Test::example`] <-> [`ui_test_example`]
 //! - [`ui::Test::examples`] <-> [`ui_test_examples`]
+//! - [`ui::Test::package`] <-> [`ui_test_package`]
+//! - [`ui::Test::packages`] <-> [`ui_test_packages`]
 //!
 //! In each case, the constructor's arguments are exactly those of the corresponding function.
 //!
 //! A `Test` instance has the following methods:
 //!
+//! - `bless` - overwrite the expected output files with the actual output (see [blessing])
+//! - `dependency` - make a crate from a registry available to the test's files (see [dependencies])
 //! - `dylint_toml` - set the `dylint.toml` file's contents (for testing [configurable libraries])
+//! - `hermetic` - run the test in [hermetic mode]
+//! - `lints` - compare only the diagnostics of the named lints (see [testing a subset of a
+//!   library's lints])
+//! - `normalize` - replace matches of a regex in the actual output (see [normalization])
+//! - `path_dependency` - make a crate from a local package available to the test's files (see
+//!   [dependencies])
 //! - `rustc_flags` - pass flags to the compiler when running the test
 //! - `run` - run the test
 //!
+//! # Cargo packages
+//!
+//! A test input that depends on other crates can be written as a small Cargo package. The package
+//! must be in its own workspace, i.e., its `Cargo.toml` must contain `[workspace]`. The diagnostics
+//! the package should produce go in an `expected.stderr` file in the package's root. Paths in the
+//! diagnostics are relative to the package's root.
+//!
+//! Each package is checked in its own target directory under `target/dylint_testing`. The
+//! directory is kept between runs, so the package's dependencies are built only once. The package
+//! itself is rebuilt on each run, so that changes to the library take effect.
+//!
+//! `rustc_flags`, `dylint_toml`, and [blessing] apply to packages as they do to other tests.
+//! Per-file directives do not. For an example, see [`external_crate_reexport`] in this repository.
+//!
+//! # Testing a subset of a library's lints
+//!
+//! By default, a test's expected output contains the diagnostics of all of the lints the library
+//! registers. For a library that registers several lints, this means that adding a lint can change
+//! the expected output of every test. To compare only the diagnostics of some lints, name them with
+//! [`ui::Test::lints`], or use [`ui_test_filtered`], e.g.:
+//!
+//! ```rust,ignore
+//! dylint_testing::ui_test_filtered(
+//!     env!("CARGO_PKG_NAME"),
+//!     &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/commented_code"),
+//!     &["commented_code"],
+//! );
+//! ```
+//!
+//! The library's other lints are allowed by passing `-A` flags to the compiler. The lints are
+//! enumerated by asking the driver to list the library's lints. Naming a lint that the library
+//! does not define causes the test to fail. Note that an `allow`, `warn`, or `deny` attribute in a
+//! test's source files takes precedence over the `-A` flags.
+//!
+//! # Per-file compiler flags
+//!
+//! Flags passed with `rustc_flags` apply to every file a test compiles. To pass additional flags
+//! when compiling just one file, put a `dylint: compile-flags` directive in a comment in that file,
+//! e.g.:
+//!
+//! ```rust,ignore
+//! // dylint: compile-flags: --cfg test_case_b
+//! ```
+//!
+//! The flags are passed in addition to those passed with `rustc_flags`. An unrecognized `dylint:`
+//! directive causes the test to fail.
+//!
+//! # Dependencies
+//!
+//! A test's `.rs` files can use third-party crates if the test declares them as dependencies,
+//! e.g.:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn ui() {
+//!     dylint_testing::ui::Test::src_base(
+//!         env!("CARGO_PKG_NAME"),
+//!         &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("ui"),
+//!     )
+//!     .dependency("tempfile", "3.6")
+//!     .path_dependency("helpers", "../helpers")
+//!     .run();
+//! }
+//! ```
+//!
+//! The dependencies are built in a scratch package under `target/dylint_testing`, and each file is
+//! compiled with the `--extern` flags needed to use them. The scratch package is built once and
+//! reused as long as the dependencies and the toolchain stay the same. Changing either causes a new
+//! one to be built. Dependencies cannot be added to tests on [Cargo packages], which declare their
+//! own.
+//!
+//! # Lines without diagnostics
+//!
+//! An empty `.stderr` file shows only that a file produces no diagnostics at all. To check that
+//! specific lines produce no diagnostics from the library under test, annotate them with `//~ OK`,
+//! e.g.:
+//!
+//! ```rust,ignore
+//! let _span = info_span!("work").entered(); //~ OK
+//! ```
+//!
+//! The check uses the compiler's JSON diagnostics rather than the rendered standard error, and
+//! considers only the lints that the library defines. If one of them fires on an annotated line,
+//! the test fails and shows the diagnostic's full rendering. In the rendered standard error, an
+//! annotation appears as `// OK`. Annotations do not apply to [Cargo packages].
+//!
+//! # Checking suggestions
+//!
+//! To check a file's machine-applicable suggestions, put a `dylint: run-rustfix` directive in a
+//! comment in that file:
+//!
+//! ```rust,ignore
+//! // dylint: run-rustfix
+//! ```
+//!
+//! After the file's standard error has been compared, the suggestions whose applicability is
+//! `MachineApplicable` are applied to the file, and the result is compared to the file's `.fixed`
+//! file. The result is then compiled with the library loaded, and the test fails if that produces
+//! any diagnostics. If the suggestions overlap, or if one would change a file other than the one
+//! under test, the test fails and lists the suggestions involved.
+//!
+//! Unlike `compiletest`'s `// run-rustfix` header, which applies every suggestion regardless of
+//! its applicability, the directive applies only machine-applicable ones. A file may contain one or
+//! the other, but not both.
+//!
+//! # Checking source strings
+//!
+//! To make assertions about a library's diagnostics in Rust code rather than in expected output
+//! files, use [`check_str`]. It compiles a source string with the library loaded and returns the
+//! diagnostics of the library's lints as [`Diagnostic`]s, e.g.:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn two_unwraps() {
+//!     let source = "pub fn f(x: Option<u32>, y: Option<u32>) -> u32 { x.unwrap() + y.unwrap() }";
+//!     let diagnostics = dylint_testing::check_str(env!("CARGO_PKG_NAME"), source).unwrap();
+//!     assert_eq!(2, diagnostics.len());
+//! }
+//! ```
+//!
+//! The source is compiled as a library crate, so it need not contain a `main` function. Lints are
+//! capped at `warn`, and the diagnostics of lints other than the library's are discarded. If the
+//! source does not compile, `check_str` returns an error containing the compiler's errors.
+//!
+//! `check_str` shares the library and driver with the tests described above. So only the first
+//! test in a test binary to use either pays the cost of building them.
+//!
+//! # Normalization
+//!
+//! Before the actual output is compared to the expected output, parts of the actual output that
+//! vary from one machine to the next are replaced with placeholders:
+//!
+//! - the directory containing the test's source files is replaced with `$DIR`
+//! - the target directory is replaced with `$TARGET_DIR`
+//! - the directory containing the tested library's `Cargo.toml` file is replaced with
+//!   `$MANIFEST_DIR`
+//! - the home directory is replaced with `$HOME`
+//!
+//! When one of these directories is within another (e.g., the target directory is within the
+//! manifest directory), the more specific placeholder is used.
+//!
+//! Other machine-specific output, such as a toolchain version, can be replaced by calling
+//! [`ui::Test::normalize`] with a regex and a replacement, e.g.:
+//!
+//! ```rust,ignore
+//! dylint_testing::ui::Test::src_base(env!("CARGO_PKG_NAME"), &src_base)
+//!     .normalize(r"nightly-\d{4}-\d{2}-\d{2}", "nightly-YYYY-MM-DD")
+//!     .run();
+//! ```
+//!
+//! Such replacements are applied in the order given, after the placeholders above. Expected
+//! output files should contain the normalized output.
+//!
+//! # Hermetic mode
+//!
+//! By default, `dylint_testing` builds a Dylint driver if one is not already present, which may
+//! require network access and may write to your home directory. In hermetic mode,
+//! `dylint_testing` neither accesses the network nor reads from or writes to your home directory.
+//! Hermetic mode is enabled by setting `DYLINT_TESTING_HERMETIC=1`, or by calling
+//! [`ui::Test::hermetic`].
+//!
+//! Hermetic mode requires the following environment variables to be set:
+//!
+//! - `DYLINT_DRIVER_PATH` - a directory containing a pre-built driver, i.e., a file
+//!   `$DYLINT_DRIVER_PATH/<toolchain>/dylint-driver`
+//! - `RUSTUP_HOME` - the directory in which `rustup` stores its toolchains
+//! - `DYLINT_TESTING_CARGO_HOME` or `CARGO_HOME` - the directory `cargo` uses for its configuration
+//!   and caches (if both are set, `DYLINT_TESTING_CARGO_HOME` takes precedence)
+//!
+//! If any of these are missing, the test fails immediately with an explanation. In addition,
+//! `dylint_testing` runs `cargo` with `CARGO_HOME` set as above and with `CARGO_NET_OFFLINE=true`.
+//! So the directory must already contain whatever the library's dependencies require.
+//!
+//! To provision a driver, run your tests once in non-hermetic mode with `DYLINT_DRIVER_PATH` set
+//! to the directory where the driver should be stored.
+//!
+//! # Running tests in parallel
+//!
+//! The ui tests in a crate run in parallel, as other Rust tests do. Each test builds its inputs in
+//! its own directory under `target/dylint_testing`, named after the test (e.g.,
+//! `target/dylint_testing/ui_main` for a test function `ui_main`). The directories are kept between
+//! runs, so later runs are faster. But they take up disk space: roughly the size of the test's
+//! build outputs, for each test.
+//!
+//! Tests that set `dylint_toml` (see [`ui::Test::dylint_toml`]) are the exception. Because the
+//! configuration is passed through an environment variable, such a test runs while no other ui
+//! test does.
+//!
 //! # Updating `.stderr` files
 //!
 //! If the standard error that results from running your `.rs` file differs from the contents of
-//! your `.stderr` file, `compiletest_rs` will produce a report like the following:
+//! your `.stderr` file, the test fails with a report like the following:
 //!
 //! ```text
-//! diff of stderr:
+//! diff of `.../ui/main.stderr`:
 //!
 //!  error: calling `std::env::set_var` in a test could affect the outcome of other tests
 //!    --> $DIR/main.rs:8:5
@@ -71,9 +272,8 @@
 //! +error: aborting due to 2 previous errors
 //!
 //!
-//!
-//! The actual stderr differed from the expected stderr.
-//! Actual stderr saved to ...
+//! The actual output differed from the expected output. To update the expected output, set
+//! `DYLINT_BLESS=1`.
 //! ```
 //!
 //! The meaning of each line is as follows:
@@ -84,53 +284,104 @@
 //!   error.
 //! - A line beginning with a space (` `) is in both the actual standard error and your `.stderr`
 //!   file, and is provided for context.
-//! - All other lines (e.g., `diff of stderr:`) contain `compiletest_rs` messages.
+//! - All other lines (e.g., `diff of ...`) contain `dylint_testing` messages.
 //!
 //! **Note:** In the actual standard error, a blank line usually follows the `error: aborting due to
 //! N previous errors` line. So a correct `.stderr` file will typically contain one blank line at
 //! the end.
 //!
-//! In general, it is not too hard to update a `.stderr` file by hand. However, the `compiletest_rs`
-//! report should contain a line of the form `Actual stderr saved to PATH`. Copying `PATH` to your
-//! `.stderr` file should update it completely.
+//! In general, it is not too hard to update a `.stderr` file by hand.
+//!
+//! Alternatively, the expected output files can be updated automatically. See [blessing].
 //!
 //! Additional documentation on `compiletest_rs` can be found in [its repository].
 //!
+//! # Blessing
+//!
+//! Setting `DYLINT_BLESS=1`, or calling [`ui::Test::bless`], causes a test to overwrite the
+//! expected `.stderr`, `.stdout`, and `.fixed` files with the actual output, rather than to fail
+//! when they differ. The files that were rewritten are listed on standard error.
+//!
+//! The actual output is normalized (see [normalization]) just as it is when compared. So a test
+//! that was just blessed should pass when run normally. Review the changes (e.g., with `git diff`)
+//! before committing them.
+//!
+//! [`check_str`]: crate::check_str
 //! [`compiletest_rs`]: https://github.com/Manishearth/compiletest-rs
+//! [`diagnostic`]: crate::Diagnostic
+//! [`external_crate_reexport`]: ../../examples/supplementary/external_crate_reexport/src/lib.rs
 //! [`non_thread_safe_call_in_test`]: ../../examples/general/non_thread_safe_call_in_test/src/lib.rs
 //! [`question_mark_in_expression`]: ../../examples/restriction/question_mark_in_expression/Cargo.toml
+//! [`ui::test::bless`]: crate::ui::Test::bless
+//! [`ui::test::dependency`]: crate::ui::Test::dependency
+//! [`ui::test::dylint_toml`]: crate::ui::Test::dylint_toml
 //! [`ui::test::example`]: crate::ui::Test::example
+//! [`ui::test::hermetic`]: crate::ui::Test::hermetic
+//! [`ui::test::lints`]: crate::ui::Test::lints
+//! [`ui::test::normalize`]: crate::ui::Test::normalize
 //! [`ui::test::examples`]: crate::ui::Test::examples
+//! [`ui::test::package`]: crate::ui::Test::package
+//! [`ui::test::packages`]: crate::ui::Test::packages
 //! [`ui::test::src_base`]: crate::ui::Test::src_base
 //! [`ui::test`]: crate::ui::Test
 //! [`ui_test_example`]: crate::ui_test_example
 //! [`ui_test_examples`]: crate::ui_test_examples
+//! [`ui_test_filtered`]: crate::ui_test_filtered
+//! [`ui_test_package`]: crate::ui_test_package
+//! [`ui_test_packages`]: crate::ui_test_packages
 //! [`ui_test`]: crate::ui_test
+//! [blessing]: #blessing
+//! [cargo packages]: #cargo-packages
 //! [configurable libraries]: ../..#configurable-libraries
 //! [docs.rs documentation]: https://docs.rs/dylint_testing/latest/dylint_testing/
+//! [dependencies]: #dependencies
 //! [dylint]: ../..
 //! [examples]: ../../examples
+//! [hermetic mode]: #hermetic-mode
 //! [its repository]: https://github.com/Manishearth/compiletest-rs
+//! [normalization]: #normalization
+//! [testing a subset of a library's lints]: #testing-a-subset-of-a-librarys-lints
 
-use anyhow::{anyhow, ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use cargo_metadata::{Metadata, Package, Target};
 use compiletest_rs as compiletest;
 use dylint_internal::{env, library_filename, rustup::is_rustc};
 use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
 use std::{
+    collections::BTreeMap,
     env::{consts, remove_var, set_var, var_os},
     ffi::{OsStr, OsString},
-    fs::{copy, read_dir, remove_file},
+    fs::{copy, read_dir, read_to_string, remove_file, write},
     io::BufRead,
     path::Path,
     path::PathBuf,
-    sync::Mutex,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread,
 };
 
+mod annotations;
+
+mod check;
+
+mod dependencies;
+
+mod directives;
+
+mod fix;
+
+mod lint_filter;
+
+mod normalize;
+use normalize::Normalizer;
+
+mod package;
+
 pub mod ui;
 use ui::Config;
 
+pub use cargo_metadata::diagnostic::Diagnostic;
+
 static DRIVER: OnceCell<PathBuf> = OnceCell::new();
 static LINKING_FLAGS: OnceCell<Vec<String>> = OnceCell::new();
 
@@ -145,6 +396,19 @@ pub fn ui_test(name: &str, src_base: &Path) {
     ui::Test::src_base(name, src_base).run();
 }
 
+/// Test a subset of a library's lints on all source files in a directory.
+///
+/// - `name` is the name of a Dylint library to be tested.
+/// - `src_base` is a directory containing source files and expected output as described in
+///   [`ui_test`].
+/// - `lints` are the names of the lints whose diagnostics the expected output contains. The
+///   library's other lints are allowed (see [testing a subset of a library's lints]).
+///
+/// [testing a subset of a library's lints]: crate#testing-a-subset-of-a-librarys-lints
+pub fn ui_test_filtered(name: &str, src_base: &Path, lints: &[&str]) {
+    ui::Test::src_base(name, src_base).lints(lints).run();
+}
+
 /// Test a library on one example target.
 ///
 /// - `name` is the name of a Dylint library to be tested.
@@ -160,11 +424,51 @@ pub fn ui_test_examples(name: &str) {
     ui::Test::examples(name).run();
 }
 
-fn initialize(name: &str) -> Result<&Path> {
+/// Test a library on a Cargo package.
+///
+/// - `name` is the name of a Dylint library to be tested.
+/// - `package` is the root of a package on which to test the library. The package must be in its
+///   own workspace, and the diagnostics it should produce must be in an `expected.stderr` file in
+///   its root.
+pub fn ui_test_package(name: &str, package: &Path) {
+    ui::Test::package(name, package).run();
+}
+
+/// Test a library on all Cargo packages in a directory.
+///
+/// - `name` is the name of a Dylint library to be tested.
+/// - `dir` is a directory whose subdirectories are packages as described in [`ui_test_package`].
+pub fn ui_test_packages(name: &str, dir: &Path) {
+    ui::Test::packages(name, dir).run();
+}
+
+/// Compile a source string with a library loaded, and return the library's diagnostics.
+///
+/// - `name` is the name of a Dylint library to be tested.
+/// - `source` is the source of a library crate on which to test the library. The crate is compiled
+///   with edition 2021.
+///
+/// Only diagnostics of lints that the library defines are returned. An error is returned if
+/// `source` does not compile. See [checking source strings].
+///
+/// [checking source strings]: crate#checking-source-strings
+pub fn check_str(name: &str, source: &str) -> Result<Vec<Diagnostic>> {
+    check::check_str(name, source, &Config::default())
+}
+
+fn initialize(name: &str, hermetic: bool) -> Result<&Path> {
     DRIVER
         .get_or_try_init(|| {
             let _ = env_logger::builder().try_init();
 
+            // smoelius: In hermetic mode, check the prerequisites before doing anything else. A
+            // missing prerequisite is more informative than a failure to build offline.
+            let hermetic_driver = if hermetic || env::enabled(env::DYLINT_TESTING_HERMETIC) {
+                Some(initialize_hermetic()?)
+            } else {
+                None
+            };
+
             // smoelius: Try to order failures by how informative they are: failure to build the
             // library, failure to find the library, failure to build/find the driver.
 
@@ -179,8 +483,11 @@ fn initialize(name: &str) -> Result<&Path> {
             set_var(env::DYLINT_LIBRARY_PATH, dylint_library_path);
 
             let dylint_libs = dylint_libs(name)?;
-            let driver =
-                dylint::driver_builder::get(&dylint::Dylint::default(), env!("RUSTUP_TOOLCHAIN"))?;
+            let driver = if let Some(driver) = hermetic_driver {
+                driver
+            } else {
+                dylint::driver_builder::get(&dylint::Dylint::default(), env!("RUSTUP_TOOLCHAIN"))?
+            };
 
             set_var(env::CLIPPY_DISABLE_DOCS_LINKS, "true");
             set_var(env::DYLINT_LIBS, dylint_libs);
@@ -190,11 +497,66 @@ fn initialize(name: &str) -> Result<&Path> {
         .map(PathBuf::as_path)
 }
 
+const PROVISION_INSTRUCTIONS: &str = "To provision a driver, run the tests once without \
+                                      `DYLINT_TESTING_HERMETIC` and with `DYLINT_DRIVER_PATH` set \
+                                      to the directory where the driver should be stored.";
+
+// smoelius: Verifies that nothing below will need the network or the home directory, and
+// configures `cargo` accordingly. Returns the path of the pre-built driver.
+fn initialize_hermetic() -> Result<PathBuf> {
+    let dylint_driver_path = var_os(env::DYLINT_DRIVER_PATH).ok_or_else(|| {
+        anyhow!(
+            "Hermetic mode requires `{}` to be set to a directory containing a pre-built driver. \
+             {}",
+            env::DYLINT_DRIVER_PATH,
+            PROVISION_INSTRUCTIONS
+        )
+    })?;
+    let driver = Path::new(&dylint_driver_path)
+        .join(env!("RUSTUP_TOOLCHAIN"))
+        .join("dylint-driver");
+    ensure!(
+        driver.is_file(),
+        "Hermetic mode requires a pre-built driver, but `{}` does not exist. {}",
+        driver.to_string_lossy(),
+        PROVISION_INSTRUCTIONS
+    );
+
+    ensure!(
+        var_os(env::RUSTUP_HOME).is_some(),
+        "Hermetic mode requires `{}` to be set, so that `rustup` does not look for its toolchains \
+         in the home directory",
+        env::RUSTUP_HOME
+    );
+
+    let cargo_home = var_os(env::DYLINT_TESTING_CARGO_HOME)
+        .or_else(|| var_os(env::CARGO_HOME))
+        .ok_or_else(|| {
+            anyhow!(
+                "Hermetic mode requires `{}` or `{}` to be set to a directory containing `cargo`'s \
+                 configuration and caches, so that `cargo` does not look for them in the home \
+                 directory",
+                env::DYLINT_TESTING_CARGO_HOME,
+                env::CARGO_HOME
+            )
+        })?;
+    ensure!(
+        Path::new(&cargo_home).is_dir(),
+        "Hermetic mode requires `{}` to be a directory",
+        Path::new(&cargo_home).to_string_lossy()
+    );
+
+    set_var(env::CARGO_HOME, cargo_home);
+    set_var(env::CARGO_NET_OFFLINE, "true");
+
+    Ok(driver)
+}
+
 #[doc(hidden)]
 pub fn dylint_libs(name: &str) -> Result<String> {
     let metadata = dylint_internal::cargo::current_metadata().unwrap();
     let rustup_toolchain = env::var(env::RUSTUP_TOOLCHAIN)?;
-    let filename = library_filename(name, &rustup_toolchain);
+    let filename = library_filename::compose(name, &rustup_toolchain, None);
     let path = metadata.target_directory.join("debug").join(filename);
     let paths = vec![path];
     serde_json::to_string(&paths).map_err(Into::into)
@@ -243,13 +605,26 @@ fn run_example_test(
             to.to_string_lossy()
         )
     })?;
-    ["fixed", "stderr", "stdout"]
+    EXPECTED_EXTENSIONS
         .map(|extension| copy_with_extension(&target.src_path, &to, extension).unwrap_or_default());
 
     let mut config = config.clone();
     config.rustc_flags.extend(linking_flags.iter().cloned());
 
-    run_tests(driver, src_base, &config);
+    let blessed = run_tests(driver, src_base, &config)?;
+
+    // smoelius: The test ran on copies in a temporary directory. So blessed files must be copied
+    // back alongside the example's source file.
+    let mut originals = Vec::new();
+    for path in blessed {
+        let extension = path
+            .extension()
+            .ok_or_else(|| anyhow!("Could not get extension"))?;
+        let original = target.src_path.as_std_path().with_extension(extension);
+        copy_or_remove(&path, &original)?;
+        originals.push(original);
+    }
+    report_blessed(&originals);
 
     Ok(())
 }
@@ -404,11 +779,127 @@ fn copy_with_extension<P: AsRef<Path>, Q: AsRef<Path>>(
     copy(from, to).map_err(Into::into)
 }
 
-static MUTEX: Mutex<()> = Mutex::new(());
+const EXPECTED_EXTENSIONS: [&str; 3] = ["fixed", "stderr", "stdout"];
+
+// smoelius: Tests run in parallel, except while a test has set `DYLINT_TOML`. Environment
+// variables are process wide, so that test holds the lock exclusively until it restores the
+// variable.
+static ENV_LOCK: RwLock<()> = RwLock::new(());
+
+/// Holds [`ENV_LOCK`] and, if the test has a `dylint_toml`, keeps `DYLINT_TOML` set. Fields are
+/// dropped in declaration order, so the variable is restored before the lock is released.
+#[must_use]
+struct EnvLock {
+    _var: Option<VarGuard>,
+    _guard: EnvLockGuard,
+}
+
+#[allow(dead_code)]
+enum EnvLockGuard {
+    Shared(RwLockReadGuard<'static, ()>),
+    Exclusive(RwLockWriteGuard<'static, ()>),
+}
+
+impl EnvLock {
+    fn acquire(config: &Config) -> Self {
+        if let Some(dylint_toml) = &config.dylint_toml {
+            let guard = ENV_LOCK.write().unwrap_or_else(PoisonError::into_inner);
+            Self {
+                _var: Some(VarGuard::set(env::DYLINT_TOML, dylint_toml)),
+                _guard: EnvLockGuard::Exclusive(guard),
+            }
+        } else {
+            let guard = ENV_LOCK.read().unwrap_or_else(PoisonError::into_inner);
+            Self {
+                _var: None,
+                _guard: EnvLockGuard::Shared(guard),
+            }
+        }
+    }
+}
+
+// smoelius: Each test gets its own build directory, named after the test, so that the tests in a
+// crate can run in parallel. By default, `compiletest` builds every test in the same directory, so
+// two tests with same-named source files (e.g., `main.rs`) would overwrite each other's outputs.
+// The directory is kept between runs. `libtest` names the thread that runs a test after the test.
+// When tests run on the main thread (e.g., with `--test-threads=1`), they share a directory, but
+// do not run in parallel.
+fn build_base() -> Result<PathBuf> {
+    let metadata = dylint_internal::cargo::current_metadata()?;
+    let thread = thread::current();
+    let test_name = thread.name().unwrap_or("main");
+    Ok(metadata
+        .target_directory
+        .join("dylint_testing")
+        .join(test_dir_name(test_name))
+        .into_std_path_buf())
+}
+
+fn test_dir_name(test_name: &str) -> String {
+    test_name
+        .replace("::", "-")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Runs the tests in `src_base` and returns the expected output files that were blessed, i.e.,
+/// created, rewritten, or removed
+fn run_tests(driver: &Path, src_base: &Path, config: &Config) -> Result<Vec<PathBuf>> {
+    let normalizer = Normalizer::new(config)?;
+
+    // smoelius: `compiletest` does not understand `dylint:` directives, and it cannot apply
+    // `dylint_testing`'s normalizations. So `compiletest` is run on a copy of `src_base` in which
+    // the directives have been translated, and is told to bless the copy's expected output files.
+    // Those files are then normalized and compared to the originals.
+    let staged = directives::stage(src_base)?;
+
+    run_compiletest(driver, staged.path(), config)?;
+
+    let expected = expected_outputs(src_base, src_base)?;
+    let mut actual = expected_outputs(staged.path(), staged.path())?;
+
+    let mut blessed = Vec::new();
+    for (path, expected_contents) in expected {
+        let actual_contents = actual.remove(&path).unwrap_or_default();
+        let original = src_base.join(path);
+        if check_expected(
+            &original,
+            &expected_contents,
+            &normalizer.normalize(&actual_contents),
+            config,
+        )? {
+            blessed.push(original);
+        }
+    }
+    // smoelius: The remaining actual outputs have no corresponding expected output files.
+    for (path, actual_contents) in actual {
+        let original = src_base.join(path);
+        if check_expected(
+            &original,
+            "",
+            &normalizer.normalize(&actual_contents),
+            config,
+        )? {
+            blessed.push(original);
+        }
+    }
+
+    annotations::check_ok_lines(driver, src_base, staged.path(), config)?;
 
-fn run_tests(driver: &Path, src_base: &Path, config: &Config) {
-    let _lock = MUTEX.lock().unwrap();
+    blessed.extend(fix::check_fixed(driver, src_base, staged.path(), config)?);
+    blessed.sort();
 
+    Ok(blessed)
+}
+
+fn run_compiletest(driver: &Path, src_base: &Path, config: &Config) -> Result<()> {
     // smoelius: There doesn't seem to be a way to set environment variables using `compiletest`'s
     // [`Config`](https://docs.rs/compiletest_rs/0.7.1/compiletest_rs/common/struct.Config.html)
     // struct. For comparison, where Clippy uses `compiletest`, it sets environment variables
@@ -416,22 +907,119 @@ fn run_tests(driver: &Path, src_base: &Path, config: &Config) {
     //   Of course, even if `compiletest` had such support, it would need to be incorporated into
     // `dylint_testing`.
 
-    let _var = config
-        .dylint_toml
-        .as_ref()
-        .map(|value| VarGuard::set(env::DYLINT_TOML, value));
+    let _lock = EnvLock::acquire(config);
 
-    let config = compiletest::Config {
+    let compiletest_config = compiletest::Config {
         mode: compiletest::common::Mode::Ui,
+        bless: true,
         rustc_path: driver.to_path_buf(),
         src_base: src_base.to_path_buf(),
+        build_base: build_base()?,
         target_rustcflags: Some(
             config.rustc_flags.clone().join(" ") + " --emit=metadata -Dwarnings -Zui-testing",
         ),
         ..compiletest::Config::default()
     };
 
-    compiletest::run_tests(&config);
+    compiletest::run_tests(&compiletest_config);
+
+    Ok(())
+}
+
+/// Compares `actual` to `expected`, the contents of the file at `path`. If they differ and
+/// blessing is enabled, overwrites the file with `actual` (or removes the file if `actual` is
+/// empty) and returns true. If they differ and blessing is not enabled, fails.
+fn check_expected(path: &Path, expected: &str, actual: &str, config: &Config) -> Result<bool> {
+    let expected = expected.replace("\r\n", "\n");
+    if expected == actual {
+        return Ok(false);
+    }
+
+    if !(config.bless || env::enabled(env::DYLINT_BLESS)) {
+        bail!(
+            "diff of `{}`:\n\n{}\nThe actual output differed from the expected output. To update \
+             the expected output, set `{}=1`.",
+            path.to_string_lossy(),
+            diff_lines(&expected, actual),
+            env::DYLINT_BLESS
+        );
+    }
+
+    if actual.is_empty() {
+        if path.exists() {
+            remove_file(path).with_context(|| {
+                format!("`remove_file` failed for `{}`", path.to_string_lossy())
+            })?;
+        }
+    } else {
+        write(path, actual)
+            .with_context(|| format!("`write` failed for `{}`", path.to_string_lossy()))?;
+    }
+
+    Ok(true)
+}
+
+// smoelius: `compiletest` searches `src_base` recursively. So the expected outputs are collected
+// recursively too. They are keyed by their paths relative to `root`.
+fn expected_outputs(root: &Path, dir: &Path) -> Result<BTreeMap<PathBuf, String>> {
+    let mut outputs = BTreeMap::new();
+    for entry in read_dir(dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?
+    {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            outputs.extend(expected_outputs(root, &path)?);
+        } else if path.extension().map_or(false, |extension| {
+            EXPECTED_EXTENSIONS.iter().any(|&ext| extension == ext)
+        }) && !directives::is_checked_by_dylint(&path)?
+        {
+            let contents = read_to_string(&path).with_context(|| {
+                format!("`read_to_string` failed for `{}`", path.to_string_lossy())
+            })?;
+            outputs.insert(path.strip_prefix(root)?.to_path_buf(), contents);
+        }
+    }
+    Ok(outputs)
+}
+
+// smoelius: `from` does not exist if `compiletest` removed it while blessing.
+fn copy_or_remove(from: &Path, to: &Path) -> Result<()> {
+    if from.exists() {
+        copy(from, to).with_context(|| {
+            format!(
+                "Could not copy `{}` to `{}`",
+                from.to_string_lossy(),
+                to.to_string_lossy()
+            )
+        })?;
+    } else if to.exists() {
+        remove_file(to)
+            .with_context(|| format!("`remove_file` failed for `{}`", to.to_string_lossy()))?;
+    }
+    Ok(())
+}
+
+fn diff_lines(expected: &str, actual: &str) -> String {
+    diff::lines(expected, actual)
+        .into_iter()
+        .map(|result| match result {
+            diff::Result::Left(line) => format!("-{line}\n"),
+            diff::Result::Both(line, _) => format!(" {line}\n"),
+            diff::Result::Right(line) => format!("+{line}\n"),
+        })
+        .collect()
+}
+
+fn report_blessed(blessed: &[PathBuf]) {
+    if blessed.is_empty() {
+        return;
+    }
+    eprintln!("Blessed {} file(s):", blessed.len());
+    for path in blessed {
+        eprintln!("    {}", path.to_string_lossy());
+    }
 }
 
 // smoelius: `VarGuard` was copied from:
@@ -0,0 +1,92 @@
+use crate::ui::Config;
+use anyhow::{ensure, Result};
+use dylint_internal::env;
+use std::{collections::BTreeSet, path::Path};
+
+/// Returns `config` extended with flags that allow each of the library's lints not named in
+/// `config.lints`. If `config.lints` is empty, `config` is returned unchanged.
+pub(crate) fn restrict(driver: &Path, config: &Config) -> Result<Config> {
+    let mut config = config.clone();
+    if !config.lints.is_empty() {
+        let lint_names = lint_names(driver)?;
+        let flags = allow_flags(&lint_names, &config.lints)?;
+        config.rustc_flags.extend(flags);
+    }
+    Ok(config)
+}
+
+// smoelius: The driver lists one lint per line, and each line begins with the lint's name.
+pub(crate) fn lint_names(driver: &Path) -> Result<BTreeSet<String>> {
+    let output = dylint_internal::driver(env!("RUSTUP_TOOLCHAIN"), driver)?
+        .envs([(env::DYLINT_LIST, "1")])
+        .args(["rustc", "-W", "help"])
+        .output()?;
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+// smoelius: Lint names may be given as they appear in diagnostics (`lint_name`) or in flags
+// (`lint-name`). A name the library does not define is most likely a typo, which would otherwise
+// cause every diagnostic to be filtered out.
+fn allow_flags(lint_names: &BTreeSet<String>, lints: &[String]) -> Result<Vec<String>> {
+    let lints = lints
+        .iter()
+        .map(|lint| lint.replace('-', "_"))
+        .collect::<BTreeSet<_>>();
+
+    let unknown = lints.difference(lint_names).cloned().collect::<Vec<_>>();
+    ensure!(
+        unknown.is_empty(),
+        "The library does not define the following lints: {}",
+        unknown.join(", ")
+    );
+
+    Ok(lint_names
+        .difference(&lints)
+        .flat_map(|lint| ["-A".to_owned(), lint.clone()])
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn two_lints() -> BTreeSet<String> {
+        BTreeSet::from([
+            "commented_code".to_owned(),
+            "redundant_reference".to_owned(),
+        ])
+    }
+
+    #[test]
+    fn allows_other_lints() {
+        assert_eq!(
+            vec!["-A", "redundant_reference"],
+            allow_flags(&two_lints(), &["commented_code".to_owned()]).unwrap()
+        );
+        assert_eq!(
+            vec!["-A", "commented_code"],
+            allow_flags(&two_lints(), &["redundant-reference".to_owned()]).unwrap()
+        );
+        assert!(allow_flags(
+            &two_lints(),
+            &[
+                "commented_code".to_owned(),
+                "redundant_reference".to_owned()
+            ]
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn unknown_lint() {
+        assert!(allow_flags(&two_lints(), &["commented_cod".to_owned()]).is_err());
+    }
+}
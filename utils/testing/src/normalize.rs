@@ -0,0 +1,147 @@
+use crate::ui::Config;
+use anyhow::{Context, Result};
+use dylint_internal::env;
+use regex::Regex;
+use std::{
+    cmp::Reverse,
+    env::{current_dir, var_os},
+    path::{Path, PathBuf},
+};
+
+/// Rewrites the machine-specific parts of a test's output, so that expected output files can be
+/// shared between machines
+pub(crate) struct Normalizer {
+    paths: Vec<(String, &'static str)>,
+    filters: Vec<(Regex, String)>,
+}
+
+impl Normalizer {
+    pub(crate) fn new(config: &Config) -> Result<Self> {
+        let metadata = dylint_internal::cargo::current_metadata()?;
+        let manifest_dir = current_dir().with_context(|| "Could not get current directory")?;
+        let home = var_os(env::HOME).map(PathBuf::from);
+        Self::with_paths(
+            metadata.target_directory.as_std_path(),
+            &manifest_dir,
+            home.as_deref(),
+            &config.normalizations,
+        )
+    }
+
+    fn with_paths(
+        target_dir: &Path,
+        manifest_dir: &Path,
+        home: Option<&Path>,
+        normalizations: &[(String, String)],
+    ) -> Result<Self> {
+        let mut paths = [
+            (Some(target_dir), "$TARGET_DIR"),
+            (Some(manifest_dir), "$MANIFEST_DIR"),
+            (home, "$HOME"),
+        ]
+        .into_iter()
+        .filter_map(|(path, replacement)| {
+            let path = path?
+                .to_string_lossy()
+                .trim_end_matches(['/', '\\'])
+                .to_owned();
+            if path.is_empty() {
+                None
+            } else {
+                Some((path, replacement))
+            }
+        })
+        .collect::<Vec<_>>();
+
+        // smoelius: The directories are typically nested, e.g., the target directory is within the
+        // manifest directory, which is within the home directory. Replacing the longest paths first
+        // ensures that each path is replaced with its most specific placeholder.
+        paths.sort_by_key(|(path, _)| Reverse(path.len()));
+
+        let filters = normalizations
+            .iter()
+            .map(|(regex, replacement)| {
+                let regex = Regex::new(regex)
+                    .with_context(|| format!("Could not parse normalization regex `{regex}`"))?;
+                Ok((regex, replacement.clone()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { paths, filters })
+    }
+
+    /// Applies the built-in filters and then the user-supplied ones to `s`
+    pub(crate) fn normalize(&self, s: &str) -> String {
+        let mut s = s.replace("\r\n", "\n");
+        for (path, replacement) in &self.paths {
+            s = s.replace(path, replacement);
+            s = s.replace(&path.replace('\\', "/"), replacement);
+        }
+        for (regex, replacement) in &self.filters {
+            s = regex.replace_all(&s, replacement.as_str()).into_owned();
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    // smoelius: A note like the one below embeds absolute paths, which differ from one machine to
+    // the next. After normalization, both machines' output matches the same expected output.
+    #[test]
+    fn absolute_paths_on_two_machines() {
+        let expected =
+            "note: cached `$TARGET_DIR/debug/cache.json` for `$MANIFEST_DIR/src/lib.rs` \
+             using `$HOME/.config/tool.toml`\n";
+
+        for home in ["/home/alice", "/Users/bob"] {
+            let manifest_dir = format!("{home}/src/project");
+            let target_dir = format!("{manifest_dir}/target");
+            let normalizer = Normalizer::with_paths(
+                Path::new(&target_dir),
+                Path::new(&manifest_dir),
+                Some(Path::new(home)),
+                &[],
+            )
+            .unwrap();
+            let actual = format!(
+                "note: cached `{target_dir}/debug/cache.json` for `{manifest_dir}/src/lib.rs` \
+                 using `{home}/.config/tool.toml`\r\n"
+            );
+            assert_eq!(expected, normalizer.normalize(&actual));
+        }
+    }
+
+    #[test]
+    fn user_supplied_filters() {
+        let normalizer = Normalizer::with_paths(
+            Path::new("/project/target"),
+            Path::new("/project"),
+            None,
+            &[(
+                r"nightly-\d{4}-\d{2}-\d{2}".to_owned(),
+                "nightly-YYYY-MM-DD".to_owned(),
+            )],
+        )
+        .unwrap();
+        assert_eq!(
+            "note: built with `nightly-YYYY-MM-DD` in `$TARGET_DIR`\n",
+            normalizer.normalize("note: built with `nightly-2023-06-29` in `/project/target`\n")
+        );
+    }
+
+    #[test]
+    fn invalid_regex() {
+        assert!(Normalizer::with_paths(
+            Path::new("/project/target"),
+            Path::new("/project"),
+            None,
+            &[("(".to_owned(), String::new())],
+        )
+        .is_err());
+    }
+}
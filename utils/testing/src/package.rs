@@ -0,0 +1,188 @@
+use crate::{check_expected, normalize::Normalizer, ui::Config};
+use anyhow::{anyhow, ensure, Context, Result};
+use cargo_metadata::{Message, MetadataCommand};
+use dylint_internal::{env, rustup::SanitizeEnvironment};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::{read_dir, read_to_string},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// The file, in a package's root, containing the diagnostics the package should produce
+const EXPECTED_STDERR: &str = "expected.stderr";
+
+/// Checks the package at `package_root` with the driver, and compares the diagnostics for the
+/// package's own targets to those in its `expected.stderr` file. Returns the expected output files
+/// that were blessed.
+pub(crate) fn run_package_test(
+    driver: &Path,
+    package_root: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>> {
+    let manifest_path = package_root.join("Cargo.toml");
+    let metadata = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .no_deps()
+        .exec()
+        .with_context(|| {
+            format!(
+                "Could not get metadata for `{}`",
+                manifest_path.to_string_lossy()
+            )
+        })?;
+    let package = metadata.root_package().ok_or_else(|| {
+        anyhow!(
+            "`{}` is not a package in its own workspace (does it contain `[workspace]`?)",
+            manifest_path.to_string_lossy()
+        )
+    })?;
+
+    let target_dir = target_dir(&package.name, package_root)?;
+    let target_dir_str = target_dir.to_string_lossy();
+    let manifest_path_str = manifest_path.to_string_lossy();
+
+    // smoelius: Cargo replays the diagnostics of a package that is up to date. But Cargo does not
+    // know that the library being tested may have changed. So the package itself is rebuilt each
+    // time. Its dependencies are not.
+    if target_dir.exists() {
+        dylint_internal::cargo::clean(&format!("package `{}`", package.name), true)
+            .args([
+                "--manifest-path",
+                &manifest_path_str,
+                "--target-dir",
+                &target_dir_str,
+                "-p",
+                &package.name,
+            ])
+            .success()?;
+    }
+
+    let mut command = dylint_internal::cargo::check(&format!("package `{}`", package.name));
+    command
+        .sanitize_environment()
+        .envs([
+            (env::DYLINT_RUSTFLAGS, config.rustc_flags.join(" ")),
+            (
+                env::RUSTC_WORKSPACE_WRAPPER,
+                driver.to_string_lossy().into_owned(),
+            ),
+            (env::RUSTUP_TOOLCHAIN, env!("RUSTUP_TOOLCHAIN").to_owned()),
+        ])
+        .args([
+            "--manifest-path",
+            &manifest_path_str,
+            "--target-dir",
+            &target_dir_str,
+            "--message-format=json",
+        ]);
+    if let Some(dylint_toml) = &config.dylint_toml {
+        command.envs([(env::DYLINT_TOML, dylint_toml)]);
+    }
+    let output = command.output()?;
+
+    let mut rendered = Vec::new();
+    for message in Message::parse_stream(output.stdout.as_slice()) {
+        let message = message.with_context(|| "Could not parse `cargo check` output")?;
+        if let Message::CompilerMessage(message) = message {
+            if message.package_id == package.id {
+                rendered.extend(message.message.rendered);
+            }
+        }
+    }
+
+    ensure!(
+        output.status.success() || !rendered.is_empty(),
+        "Could not check package `{}`:\n{}",
+        package.name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let normalizer = Normalizer::new(config)?;
+    let actual = normalizer.normalize(&normalize_package_root(&rendered.concat(), package_root));
+
+    let expected_stderr = package_root.join(EXPECTED_STDERR);
+    let expected = if expected_stderr.exists() {
+        read_to_string(&expected_stderr).with_context(|| {
+            format!(
+                "`read_to_string` failed for `{}`",
+                expected_stderr.to_string_lossy()
+            )
+        })?
+    } else {
+        String::new()
+    };
+
+    if check_expected(&expected_stderr, &expected, &actual, config)? {
+        Ok(vec![expected_stderr])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Returns the subdirectories of `dir` that contain a `Cargo.toml` file, in order
+pub(crate) fn package_roots(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut package_roots = Vec::new();
+    for entry in read_dir(dir)
+        .with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?
+    {
+        let entry =
+            entry.with_context(|| format!("`read_dir` failed for `{}`", dir.to_string_lossy()))?;
+        let path = entry.path();
+        if path.join("Cargo.toml").is_file() {
+            package_roots.push(path);
+        }
+    }
+    package_roots.sort();
+    Ok(package_roots)
+}
+
+// smoelius: Each package gets its own target directory so that tests of different packages can
+// run in parallel. Two runs testing the same package share a target directory, which Cargo's
+// build lock protects. The directory is kept between runs so that dependencies are built only
+// once.
+fn target_dir(package_name: &str, package_root: &Path) -> Result<PathBuf> {
+    let metadata = dylint_internal::cargo::current_metadata()?;
+    let mut hasher = DefaultHasher::new();
+    package_root
+        .canonicalize()
+        .with_context(|| {
+            format!(
+                "Could not canonicalize `{}`",
+                package_root.to_string_lossy()
+            )
+        })?
+        .hash(&mut hasher);
+    Ok(metadata
+        .target_directory
+        .join("dylint_testing")
+        .join(env!("RUSTUP_TOOLCHAIN"))
+        .join(format!("{package_name}-{:016x}", hasher.finish()))
+        .into_std_path_buf())
+}
+
+// smoelius: Paths in diagnostics are relative to the package root, except for those outside of it.
+// Absolute paths to the package root are replaced with `$DIR`, as `compiletest` does. This happens
+// before the other normalizations, because the package root is likely within the manifest
+// directory.
+fn normalize_package_root(s: &str, package_root: &Path) -> String {
+    let package_root = package_root.to_string_lossy();
+    let s = s.replace(&*package_root, "$DIR");
+    s.replace(&*package_root.replace('\\', "/"), "$DIR")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn package_root() {
+        assert_eq!(
+            "warning: x\n --> $DIR/src/main.rs:1:1\n",
+            normalize_package_root(
+                "warning: x\n --> /tmp/package/src/main.rs:1:1\n",
+                Path::new("/tmp/package")
+            )
+        );
+    }
+}
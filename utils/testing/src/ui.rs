@@ -1,4 +1,9 @@
-use crate::{example_target, example_targets, initialize, run_example_test, run_tests};
+use crate::{
+    dependencies::{self, Dependency},
+    example_target, example_targets, initialize, lint_filter,
+    package::{package_roots, run_package_test},
+    report_blessed, run_example_test, run_tests,
+};
 use std::{
     env::current_dir,
     path::{Path, PathBuf},
@@ -8,12 +13,19 @@ enum Target {
     SrcBase(PathBuf),
     Example(String),
     Examples,
+    Package(PathBuf),
+    Packages(PathBuf),
 }
 
 #[derive(Clone, Default)]
 pub(super) struct Config {
     pub(super) rustc_flags: Vec<String>,
     pub(super) dylint_toml: Option<String>,
+    pub(super) hermetic: bool,
+    pub(super) bless: bool,
+    pub(super) normalizations: Vec<(String, String)>,
+    pub(super) lints: Vec<String>,
+    pub(super) dependencies: Vec<(String, Dependency)>,
 }
 
 /// Test builder
@@ -48,6 +60,22 @@ impl Test {
         Self::new(name, Target::Examples)
     }
 
+    /// Test a library on a Cargo package (similar to [`ui_test_package`]).
+    ///
+    /// [`ui_test_package`]: crate::ui_test_package
+    #[must_use]
+    pub fn package(name: &str, package: &Path) -> Self {
+        Self::new(name, Target::Package(package.to_owned()))
+    }
+
+    /// Test a library on all Cargo packages in a directory (similar to [`ui_test_packages`]).
+    ///
+    /// [`ui_test_packages`]: crate::ui_test_packages
+    #[must_use]
+    pub fn packages(name: &str, dir: &Path) -> Self {
+        Self::new(name, Target::Packages(dir.to_owned()))
+    }
+
     /// Pass flags to the compiler when running the test.
     pub fn rustc_flags(
         &mut self,
@@ -65,6 +93,71 @@ impl Test {
         self
     }
 
+    /// Run the test without accessing the network or the home directory (see [hermetic mode]).
+    ///
+    /// [hermetic mode]: crate#hermetic-mode
+    pub fn hermetic(&mut self) -> &mut Self {
+        self.config.hermetic = true;
+        self
+    }
+
+    /// Overwrite the expected `.stderr`, `.stdout`, and `.fixed` files with the actual output,
+    /// rather than failing when they differ (see [blessing]).
+    ///
+    /// [blessing]: crate#blessing
+    pub fn bless(&mut self) -> &mut Self {
+        self.config.bless = true;
+        self
+    }
+
+    /// Replace each match of `regex` with `replacement` in the actual output before comparing it
+    /// to the expected output (see [normalization]). `replacement` may refer to capture groups,
+    /// e.g., `$1`.
+    ///
+    /// [normalization]: crate#normalization
+    pub fn normalize(&mut self, regex: impl AsRef<str>, replacement: impl AsRef<str>) -> &mut Self {
+        self.config
+            .normalizations
+            .push((regex.as_ref().to_owned(), replacement.as_ref().to_owned()));
+        self
+    }
+
+    /// Compare only the diagnostics of the named lints, by allowing the library's other lints (see
+    /// [testing a subset of a library's lints]). If this method is never called, the diagnostics
+    /// of all of the library's lints are compared.
+    ///
+    /// [testing a subset of a library's lints]: crate#testing-a-subset-of-a-librarys-lints
+    pub fn lints(&mut self, lints: impl IntoIterator<Item = impl AsRef<str>>) -> &mut Self {
+        self.config
+            .lints
+            .extend(lints.into_iter().map(|s| s.as_ref().to_owned()));
+        self
+    }
+
+    /// Make version `version` of crate `name` available to the files the test compiles (see
+    /// [dependencies]).
+    ///
+    /// [dependencies]: crate#dependencies
+    pub fn dependency(&mut self, name: impl AsRef<str>, version: impl AsRef<str>) -> &mut Self {
+        self.config.dependencies.push((
+            name.as_ref().to_owned(),
+            Dependency::Version(version.as_ref().to_owned()),
+        ));
+        self
+    }
+
+    /// Make the crate `name` in the package at `path` available to the files the test compiles
+    /// (see [dependencies]). A relative `path` is relative to the library's package root.
+    ///
+    /// [dependencies]: crate#dependencies
+    pub fn path_dependency(&mut self, name: impl AsRef<str>, path: impl AsRef<Path>) -> &mut Self {
+        self.config.dependencies.push((
+            name.as_ref().to_owned(),
+            Dependency::Path(path.as_ref().to_owned()),
+        ));
+        self
+    }
+
     /// Run the test.
     pub fn run(&mut self) {
         self.run_immutable();
@@ -79,11 +172,26 @@ impl Test {
     }
 
     fn run_immutable(&self) {
-        let driver = initialize(&self.name).unwrap();
+        let driver = initialize(&self.name, self.config.hermetic).unwrap();
+
+        let config = lint_filter::restrict(driver, &self.config).unwrap();
+
+        // smoelius: A Cargo package declares its own dependencies.
+        let config = if matches!(self.target, Target::Package(_) | Target::Packages(_)) {
+            assert!(
+                config.dependencies.is_empty(),
+                "Dependencies cannot be added to Cargo package tests. Declare them in the \
+                 package's `Cargo.toml`."
+            );
+            config
+        } else {
+            dependencies::link(&config).unwrap()
+        };
 
         match &self.target {
             Target::SrcBase(src_base) => {
-                run_tests(driver, src_base, &self.config);
+                let blessed = run_tests(driver, src_base, &config).unwrap();
+                report_blessed(&blessed);
             }
             Target::Example(example) => {
                 let metadata = dylint_internal::cargo::current_metadata().unwrap();
@@ -92,7 +200,7 @@ impl Test {
                     dylint_internal::cargo::package_with_root(&metadata, &current_dir).unwrap();
                 let target = example_target(&package, example).unwrap();
 
-                run_example_test(driver, &metadata, &package, &target, &self.config).unwrap();
+                run_example_test(driver, &metadata, &package, &target, &config).unwrap();
             }
             Target::Examples => {
                 let metadata = dylint_internal::cargo::current_metadata().unwrap();
@@ -102,7 +210,18 @@ impl Test {
                 let targets = example_targets(&package).unwrap();
 
                 for target in targets {
-                    run_example_test(driver, &metadata, &package, &target, &self.config).unwrap();
+                    run_example_test(driver, &metadata, &package, &target, &config).unwrap();
+                }
+            }
+            Target::Package(package) => {
+                let blessed = run_package_test(driver, package, &config).unwrap();
+                report_blessed(&blessed);
+            }
+            Target::Packages(dir) => {
+                let package_roots = package_roots(dir).unwrap();
+                for package in package_roots {
+                    let blessed = run_package_test(driver, &package, &config).unwrap();
+                    report_blessed(&blessed);
                 }
             }
         }